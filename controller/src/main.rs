@@ -0,0 +1,216 @@
+// main.rs — Desktop controller for the GoldSrc overlay.
+//
+// Shows every setting in esp_config.toml with an editor that applies the
+// change in the game at once (over the overlay's control pipe, see
+// src/control.rs), a button that writes the changes back to the file, the
+// profile and visibility, the connection state and the overlay's recent log.
+// Run it next to the game; it connects whenever the overlay is injected.
+//
+//   cargo run --release -p esp_controller
+
+#![windows_subsystem = "windows"]
+
+mod pipe;
+mod value;
+
+use eframe::egui;
+use pipe::{Command, Shared};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use value::Edit;
+
+const CONNECTED_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 200, 90);
+const OFFLINE_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 70, 60);
+const DIRTY_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 180, 60);
+
+fn main() -> eframe::Result {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([560.0, 760.0]),
+        ..Default::default()
+    };
+    eframe::run_native("GoldSrc overlay controller", options, Box::new(|cc| Ok(Box::new(App::new(cc)))))
+}
+
+/// One setting row.
+struct Row {
+    key:     String,   // "section.key"
+    edit:    Edit,
+    applied: String,   // Value text last read or sent
+    dirty:   bool,     // Changed here, not yet written to the file
+}
+
+impl Row {
+    fn new(key: &str, value: &str) -> Self {
+        Self { key: key.to_string(), edit: Edit::parse(value), applied: value.to_string(), dirty: false }
+    }
+}
+
+struct App {
+    shared:   Arc<Mutex<Shared>>,
+    commands: Sender<Command>,
+    rows:     Vec<Row>,
+    version:  u32,       // Shared::settings_version the rows were built from
+    filter:   String,
+    new_key:  String,
+    new_value: String,
+}
+
+impl App {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let (commands, rx) = mpsc::channel();
+        let ctx = cc.egui_ctx.clone();
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || pipe::run(thread_shared, rx, ctx));
+        Self {
+            shared, commands, rows: Vec::new(), version: 0,
+            filter: String::new(), new_key: String::new(), new_value: String::new(),
+        }
+    }
+
+    fn send(&self, line: String) {
+        let _ = self.commands.send(Command::Send(line));
+    }
+
+    /// Apply an edited row in the game, if its value changed. Profile
+    /// overrides only take effect from the file, so they wait for "Write".
+    fn apply(&mut self, i: usize) {
+        let text = self.rows[i].edit.to_text();
+        if text == self.rows[i].applied { return; }
+        if !self.rows[i].key.starts_with("profile.") {
+            self.send(format!("set {} {}", self.rows[i].key, text));
+        }
+        self.rows[i].applied = text;
+        self.rows[i].dirty = true;
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Copy what the connection thread knows; rebuild the rows when the file was re-read
+        let (connected, error, profile, profiles, visible, config_path, log) = {
+            let s = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+            if s.settings_version != self.version {
+                self.version = s.settings_version;
+                self.rows = s.settings.iter().map(|(k, v)| Row::new(k, v)).collect();
+            }
+            (s.connected, s.error.clone(), s.profile.clone(), s.profiles.clone(), s.visible,
+             s.config_path.clone(), s.log.clone())
+        };
+
+        // --- Connection, profile, visibility ---
+        egui::TopBottomPanel::top("status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if connected {
+                    ui.colored_label(CONNECTED_COLOR, "● connected");
+                } else {
+                    ui.colored_label(OFFLINE_COLOR, "● not connected (is the overlay injected?)");
+                }
+                ui.separator();
+                let label = if profile.is_empty() { "(base settings)".to_string() } else { profile.clone() };
+                let mut chosen = profile.clone();
+                egui::ComboBox::from_label("profile").selected_text(label).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut chosen, String::new(), "(base settings)");
+                    for p in &profiles { ui.selectable_value(&mut chosen, p.clone(), p.as_str()); }
+                });
+                if chosen != profile {
+                    self.send(format!("profile {}", if chosen.is_empty() { "none" } else { chosen.as_str() }));
+                }
+                ui.separator();
+                let text = if visible { "Hide overlay (F6)" } else { "Show overlay (F6)" };
+                if ui.add_enabled(connected, egui::Button::new(text)).clicked() { self.send("toggle".to_string()); }
+            });
+            if !config_path.is_empty() { ui.label(format!("config: {}", config_path)); }
+            if let Some(e) = &error { ui.colored_label(OFFLINE_COLOR, e.as_str()); }
+        });
+
+        // --- Recent log ---
+        egui::TopBottomPanel::bottom("log").resizable(true).default_height(180.0).show(ctx, |ui| {
+            ui.heading("Log");
+            egui::ScrollArea::vertical().auto_shrink([false; 2]).stick_to_bottom(true).show(ui, |ui| {
+                for line in &log { ui.monospace(line.as_str()); }
+            });
+        });
+
+        // --- Settings ---
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("filter").desired_width(160.0));
+                let dirty = self.rows.iter().filter(|r| r.dirty).count();
+                if ui.add_enabled(connected && dirty > 0, egui::Button::new(format!("Write {} change(s) to file", dirty))).clicked() {
+                    for row in self.rows.iter().filter(|r| r.dirty) {
+                        self.send(format!("save {} {}", row.key, row.edit.to_text()));
+                    }
+                    for row in &mut self.rows { row.dirty = false; }
+                }
+                if ui.add_enabled(connected, egui::Button::new("Reload from file")).clicked() {
+                    let _ = self.commands.send(Command::Reload);
+                }
+            });
+            ui.label("Changes apply in the game at once and last until the config file is reloaded.");
+            ui.separator();
+
+            let mut changed: Vec<usize> = Vec::new();
+            egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                let filter = self.filter.to_lowercase();
+                let mut sections: Vec<&str> = Vec::new();
+                for row in &self.rows {
+                    let section = row.key.rsplit_once('.').map_or("general", |(s, _)| s);
+                    if !sections.contains(&section) { sections.push(section); }
+                }
+                let sections: Vec<String> = sections.into_iter().map(str::to_string).collect();
+                for section in sections {
+                    let in_section: Vec<usize> = (0..self.rows.len())
+                        .filter(|&i| self.rows[i].key.rsplit_once('.').map_or("general", |(s, _)| s) == section)
+                        .filter(|&i| filter.is_empty() || self.rows[i].key.to_lowercase().contains(&filter))
+                        .collect();
+                    if in_section.is_empty() { continue; }
+                    egui::CollapsingHeader::new(section.as_str()).default_open(!filter.is_empty()).show(ui, |ui| {
+                        egui::Grid::new(format!("grid {}", section)).num_columns(2).striped(true).show(ui, |ui| {
+                            for i in in_section {
+                                let row = &mut self.rows[i];
+                                let name = row.key.rsplit_once('.').map_or(row.key.as_str(), |(_, k)| k);
+                                if row.dirty { ui.colored_label(DIRTY_COLOR, name); } else { ui.label(name); }
+                                if edit_value(ui, &mut row.edit) { changed.push(i); }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+
+                // --- A setting that isn't in the file yet ---
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_key).hint_text("section.key").desired_width(180.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.new_value).hint_text("value").desired_width(140.0));
+                    if ui.add_enabled(!self.new_key.trim().is_empty() && !self.new_value.trim().is_empty(),
+                                      egui::Button::new("Add")).clicked() {
+                        let mut row = Row::new(self.new_key.trim(), self.new_value.trim());
+                        row.applied.clear();
+                        self.rows.push(row);
+                        changed.push(self.rows.len() - 1);
+                        self.new_key.clear();
+                        self.new_value.clear();
+                    }
+                });
+            });
+            for i in changed { self.apply(i); }
+        });
+
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+/// The editor for one value. Returns whether it was edited (text: when editing ends).
+fn edit_value(ui: &mut egui::Ui, edit: &mut Edit) -> bool {
+    match edit {
+        Edit::Bool(b) => ui.checkbox(b, "").changed(),
+        Edit::Num(n) => {
+            let speed = if n.fract() == 0.0 && n.abs() >= 10.0 { 1.0 } else { 0.05 };
+            ui.add(egui::DragValue::new(n).speed(speed)).changed()
+        }
+        Edit::Color(c, _) => ui.color_edit_button_rgba_unmultiplied(c).changed(),
+        Edit::Text(s) | Edit::Raw(s) => ui.text_edit_singleline(s).lost_focus(),
+    }
+}
@@ -0,0 +1,147 @@
+// pipe.rs — The connection to the overlay's control pipe.
+//
+// A background thread opens the pipe (retrying once a second while the game
+// isn't running), polls status and log, re-reads the settings when asked and
+// sends the requests queued by the window. Everything it learns goes into
+// `Shared`; the window only reads that and queues requests.
+
+use eframe::egui;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const PIPE_NAME: &str = r"\\.\pipe\goldsrc_overlay";
+const POLL: Duration = Duration::from_secs(1);
+const LOG_LINES: usize = 200;
+
+/// What the window shows, as of the last poll.
+#[derive(Default)]
+pub struct Shared {
+    pub connected:   bool,
+    pub error:       Option<String>,         // Last failed request or connection error
+    pub profile:     String,                 // "" = base settings
+    pub profiles:    Vec<String>,
+    pub visible:     bool,
+    pub config_path: String,
+    pub settings:    Vec<(String, String)>,  // (section.key, value text) from the file
+    pub settings_version: u32,               // Bumped whenever `settings` is re-read
+    pub log:         Vec<String>,
+}
+
+/// A request queued by the window.
+pub enum Command {
+    Send(String),   // A request line; a failure lands in `Shared::error`
+    Reload,         // Re-read the settings from the file
+}
+
+struct Pipe {
+    reader: BufReader<File>,
+    writer: File,
+}
+
+impl Pipe {
+    fn open() -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new().read(true).write(true).open(PIPE_NAME)?;
+        Ok(Self { writer: file.try_clone()?, reader: BufReader::new(file) })
+    }
+
+    /// Send one request and read its reply: the data lines, or the error.
+    /// The outer error is the connection failing.
+    fn request(&mut self, line: &str) -> io::Result<Result<Vec<String>, String>> {
+        self.writer.write_all(format!("{}\n", line).as_bytes())?;
+        self.writer.flush()?;
+        let mut data = Vec::new();
+        loop {
+            let mut reply = String::new();
+            if self.reader.read_line(&mut reply)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let reply = reply.trim_end_matches(['\r', '\n']);
+            if let Some(d) = reply.strip_prefix("- ") {
+                data.push(d.to_string());
+            } else if reply == "ok" {
+                return Ok(Ok(data));
+            } else {
+                return Ok(Err(reply.strip_prefix("err ").unwrap_or(reply).to_string()));
+            }
+        }
+    }
+}
+
+/// The connection thread. Returns when the window is gone.
+pub fn run(shared: Arc<Mutex<Shared>>, commands: Receiver<Command>, ctx: egui::Context) {
+    loop {
+        let result = Pipe::open().and_then(|mut pipe| serve(&mut pipe, &shared, &commands, &ctx));
+        if let Ok(mut s) = shared.lock() {
+            s.connected = false;
+            if let Err(e) = &result { s.error = Some(format!("not connected: {}", e)); }
+        }
+        ctx.request_repaint();
+        if result.is_ok() { return; } // The window closed
+
+        // Requests made while disconnected can't go anywhere
+        loop {
+            match commands.recv_timeout(POLL) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+/// Talk to a connected pipe until it fails (Err) or the window closes (Ok).
+fn serve(pipe: &mut Pipe, shared: &Mutex<Shared>, commands: &Receiver<Command>, ctx: &egui::Context) -> io::Result<()> {
+    if let Ok(mut s) = shared.lock() {
+        s.connected = true;
+        s.error = None;
+    }
+    let mut reload = true;
+    loop {
+        if reload {
+            let settings = pipe.request("config")?.unwrap_or_default();
+            if let Ok(mut s) = shared.lock() {
+                s.settings = settings.iter()
+                    .filter_map(|l| l.split_once(" = ").map(|(k, v)| (k.to_string(), v.to_string())))
+                    .collect();
+                s.settings_version = s.settings_version.wrapping_add(1);
+            }
+            reload = false;
+        }
+        poll(pipe, shared)?;
+        ctx.request_repaint();
+
+        match commands.recv_timeout(POLL) {
+            Ok(Command::Send(line)) => {
+                // A profile switch or a save reloads the file in the overlay
+                reload = line.starts_with("profile") || line.starts_with("save");
+                let result = pipe.request(&line)?;
+                if let Ok(mut s) = shared.lock() { s.error = result.err(); }
+            }
+            Ok(Command::Reload) => reload = true,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+    }
+}
+
+/// Refresh the status and the log.
+fn poll(pipe: &mut Pipe, shared: &Mutex<Shared>) -> io::Result<()> {
+    let status = pipe.request("status")?.unwrap_or_default();
+    let log = pipe.request(&format!("log {}", LOG_LINES))?.unwrap_or_default();
+    let Ok(mut s) = shared.lock() else { return Ok(()) };
+    for line in &status {
+        let (name, value) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+        match name {
+            "profile"  => s.profile = value.to_string(),
+            "visible"  => s.visible = value == "on",
+            "config"   => s.config_path = value.to_string(),
+            "profiles" => s.profiles = value.split(',').filter(|p| !p.is_empty()).map(str::to_string).collect(),
+            _ => {}
+        }
+    }
+    s.log = log;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+// value.rs — Setting values as editors: the config file's value text in,
+// an editable form, and value text back out.
+
+/// How a setting is edited.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    Bool(bool),
+    Num(f64),
+    Color([f32; 4], bool),   // Components; whether it had an alpha
+    Text(String),            // Inside of a "string"
+    Raw(String),             // Anything else (pairs, lists), edited as text
+}
+
+impl Edit {
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        match text {
+            "true" => return Edit::Bool(true),
+            "false" => return Edit::Bool(false),
+            _ => {}
+        }
+        if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+            return Edit::Text(text[1..text.len() - 1].to_string());
+        }
+        if let Ok(n) = text.replace('_', "").parse::<f64>() {
+            return Edit::Num(n);
+        }
+        if let Some(inner) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            let parts: Option<Vec<f32>> = inner.split(',').map(|p| p.trim().parse().ok()).collect();
+            if let Some(parts) = parts.filter(|p| (p.len() == 3 || p.len() == 4) && p.iter().all(|c| (0.0..=1.0).contains(c))) {
+                let mut c = [1.0; 4];
+                c[..parts.len()].copy_from_slice(&parts);
+                return Edit::Color(c, parts.len() == 4);
+            }
+        }
+        Edit::Raw(text.to_string())
+    }
+
+    /// The value in config file syntax.
+    pub fn to_text(&self) -> String {
+        match self {
+            Edit::Bool(b) => b.to_string(),
+            Edit::Num(n) => ((n * 1e4).round() / 1e4).to_string(),   // Drag steps leave float noise
+            Edit::Color(c, alpha) => {
+                let n = if *alpha { 4 } else { 3 };
+                let parts: Vec<String> = c[..n].iter().map(|v| format!("{:.2}", v)).collect();
+                format!("[{}]", parts.join(", "))
+            }
+            Edit::Text(s) => format!("\"{}\"", s),
+            Edit::Raw(s) => s.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_values() {
+        assert_eq!(Edit::parse("true"), Edit::Bool(true));
+        assert_eq!(Edit::parse("27_080").to_text(), "27080");
+        assert_eq!(Edit::parse("2.5").to_text(), "2.5");
+        assert_eq!(Edit::Num(0.1 + 0.2).to_text(), "0.3");
+        assert_eq!(Edit::parse("\"0x78\""), Edit::Text("0x78".into()));
+        assert_eq!(Edit::parse("[1.0, 0.2, 0.2]").to_text(), "[1.00, 0.20, 0.20]");
+        assert_eq!(Edit::parse("[0, 0, 0, 0.5]"), Edit::Color([0.0, 0.0, 0.0, 0.5], true));
+        assert_eq!(Edit::parse("[6, 6]"), Edit::Raw("[6, 6]".into()));
+        assert_eq!(Edit::parse("[\"a\", \"b\"]").to_text(), "[\"a\", \"b\"]");
+    }
+}
@@ -0,0 +1,205 @@
+// main.rs — Guardian: injects the overlay into every game it sees start and
+// keeps each game's log.
+//
+//   esp_guardian [path\to\goldsrc_diag_overlay.dll]
+//
+// Once a second it looks for hl.exe. A new game process gets the DLL once
+// the game has loaded OpenGL plus a short settle delay (the point where
+// injecting by hand works), so a launch, a restart or a game started again
+// after a crash is covered without running the injector. When a game exits,
+// the overlay's esp_debug.log (which the next injection truncates) and the
+// exports written during that run are copied into
+// sessions\<start time>_<pid>\ next to the DLL, with a guardian.txt noting
+// the injection result and the exit code.
+//
+// The DLL path defaults to goldsrc_diag_overlay.dll next to this exe.
+
+#![cfg_attr(not(windows), allow(dead_code))]   // Only the tests use it elsewhere
+
+#[cfg(windows)]
+mod process;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const TARGET: &str = "hl.exe";
+const DLL_NAME: &str = "goldsrc_diag_overlay.dll";
+const READY_MODULE: &str = "opengl32.dll";   // Loaded once the game has set up its renderer
+const LOG_NAME: &str = "esp_debug.log";
+const EXPORT_PREFIXES: [&str; 2] = ["esp_session_", "esp_stats_"];
+const SESSIONS_DIR: &str = "sessions";
+const POLL: Duration = Duration::from_secs(1);
+const SETTLE: Duration = Duration::from_secs(3);   // After READY_MODULE shows up, before injecting
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+// ============================================================================
+// Session Folders
+// ============================================================================
+
+/// "YYYYMMDD-HHMMSSZ_<pid>" for a game started at `secs` (UTC), so folders
+/// sort by start time.
+fn session_name(secs: u64, pid: u32) -> String {
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}Z_{}", year, month, day,
+            rem / 3_600, rem / 60 % 60, rem % 60, pid)
+}
+
+/// Whether `name` is an overlay export (session JSON, stats CSV).
+fn is_export(name: &str) -> bool {
+    EXPORT_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+/// Copy the log and the exports written since `started` from `dir` into a
+/// new session folder, with `notes` as guardian.txt. Returns the folder.
+fn collect(dir: &Path, started: SystemTime, pid: u32, notes: &str) -> std::io::Result<PathBuf> {
+    let out = dir.join(SESSIONS_DIR).join(session_name(unix_secs(started), pid));
+    std::fs::create_dir_all(&out)?;
+    let log = dir.join(LOG_NAME);
+    if log.is_file() { std::fs::copy(&log, out.join(LOG_NAME))?; }
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let fresh = entry.metadata().and_then(|m| m.modified()).is_ok_and(|t| t >= started);
+        if is_export(&name) && fresh { std::fs::copy(entry.path(), out.join(&name))?; }
+    }
+    std::fs::write(out.join("guardian.txt"), notes)?;
+    Ok(out)
+}
+
+// ============================================================================
+// Watching
+// ============================================================================
+
+#[cfg(windows)]
+enum State {
+    Starting,                  // Waiting for READY_MODULE
+    Ready(std::time::Instant), // READY_MODULE seen at; settling
+    Injected,
+    Failed,
+}
+
+/// A game process being looked after.
+#[cfg(windows)]
+struct Game {
+    pid:     u32,
+    watch:   process::Watch,
+    started: SystemTime,
+    state:   State,
+    notes:   String,   // Becomes the session's guardian.txt
+}
+
+#[cfg(windows)]
+impl Game {
+    fn note(&mut self, line: String) {
+        println!("[{}] {}", self.pid, line);
+        self.notes.push_str(&line);
+        self.notes.push('\n');
+    }
+
+    /// Move towards injection.
+    fn step(&mut self, dll: &Path) {
+        match self.state {
+            State::Starting => {
+                if process::has_module(self.pid, DLL_NAME) == Some(true) {
+                    self.note("overlay already loaded".to_string());
+                    self.state = State::Injected;
+                } else if process::has_module(self.pid, READY_MODULE) == Some(true) {
+                    self.state = State::Ready(std::time::Instant::now());
+                }
+            }
+            State::Ready(since) if since.elapsed() >= SETTLE => {
+                match process::inject(self.pid, dll) {
+                    Ok(()) => {
+                        self.note(format!("injected {}", dll.display()));
+                        self.state = State::Injected;
+                    }
+                    Err(e) => {
+                        self.note(format!("injection failed: {}", e));
+                        self.state = State::Failed;
+                    }
+                }
+            }
+            State::Ready(_) | State::Injected | State::Failed => {}
+        }
+    }
+}
+
+#[cfg(windows)]
+fn main() {
+    if cfg!(target_pointer_width = "64") {
+        eprintln!("esp_guardian must be built 32-bit like the game: --target i686-pc-windows-msvc");
+        std::process::exit(1);
+    }
+    let dll = match std::env::args_os().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|d| d.join(DLL_NAME)))
+            .unwrap_or_else(|| PathBuf::from(DLL_NAME)),
+    };
+    let dll = std::fs::canonicalize(&dll).unwrap_or(dll);
+    if !dll.is_file() {
+        eprintln!("{} not found (pass the DLL path as the first argument)", dll.display());
+        std::process::exit(1);
+    }
+    let dir = dll.parent().map(Path::to_path_buf).unwrap_or_default();
+    println!("esp_guardian: watching for {}, injecting {}", TARGET, dll.display());
+    println!("game logs go to {}", dir.join(SESSIONS_DIR).display());
+
+    let mut games: Vec<Game> = Vec::new();
+    loop {
+        for pid in process::find(TARGET) {
+            if games.iter().any(|g| g.pid == pid) { continue; }
+            let Some(watch) = process::Watch::open(pid) else { continue };   // Retried next poll
+            let mut game = Game { pid, watch, started: SystemTime::now(), state: State::Starting, notes: String::new() };
+            game.note(format!("{} started (seen at unix {})", TARGET, unix_secs(game.started)));
+            games.push(game);
+        }
+        for game in &mut games { game.step(&dll); }
+
+        games.retain_mut(|game| {
+            let Some(code) = game.watch.exit_code() else { return true };
+            game.note(format!("exited with code {:#x}", code));
+            if matches!(game.state, State::Injected | State::Failed) {
+                match collect(&dir, game.started, game.pid, &game.notes) {
+                    Ok(out) => println!("[{}] log kept in {}", game.pid, out.display()),
+                    Err(e) => eprintln!("[{}] couldn't keep the log: {}", game.pid, e),
+                }
+            }
+            false
+        });
+        std::thread::sleep(POLL);
+    }
+}
+
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("esp_guardian only runs on Windows");
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_session_folders_by_start_time() {
+        assert_eq!(session_name(0, 7), "19700101-000000Z_7");
+        assert_eq!(session_name(951_827_696, 1234), "20000229-123456Z_1234");
+        assert_eq!(session_name(1_791_331_200 + 86_399, 88), "20261007-235959Z_88");
+        assert!(is_export("esp_session_de_dust2_1791331200.json"));
+        assert!(!is_export("esp_config.toml"));
+    }
+}
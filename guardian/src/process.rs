@@ -0,0 +1,147 @@
+// process.rs — Finding game processes and injecting into them (Win32).
+//
+// Injection is the same LoadLibrary + CreateRemoteThread technique as
+// inject.py, using LoadLibraryW so any DLL path works. The guardian is built
+// 32-bit like the game, so its own kernel32 export address is valid in hl.exe
+// and there's no remote export table to walk.
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+use winapi::shared::minwindef::{DWORD, FALSE, FARPROC, LPVOID};
+use winapi::shared::ntdef::HANDLE;
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::libloaderapi::{GetModuleHandleA, GetProcAddress};
+use winapi::um::memoryapi::{VirtualAllocEx, VirtualFreeEx, WriteProcessMemory};
+use winapi::um::minwinbase::LPTHREAD_START_ROUTINE;
+use winapi::um::processthreadsapi::{CreateRemoteThread, GetExitCodeProcess, GetExitCodeThread, OpenProcess};
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::tlhelp32::{
+    CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, Process32FirstW, Process32NextW,
+    MODULEENTRY32W, PROCESSENTRY32W, TH32CS_SNAPMODULE, TH32CS_SNAPMODULE32, TH32CS_SNAPPROCESS,
+};
+use winapi::um::winbase::WAIT_OBJECT_0;
+use winapi::um::winnt::{
+    MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE, PROCESS_CREATE_THREAD, PROCESS_QUERY_INFORMATION,
+    PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE, SYNCHRONIZE,
+};
+
+const LOAD_TIMEOUT_MS: DWORD = 10_000;   // For the remote LoadLibraryW call
+
+/// A handle closed on drop.
+struct Handle(HANDLE);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0); }
+    }
+}
+
+/// A NUL-terminated UTF-16 buffer as a String.
+fn from_wide(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
+/// Process ids of every running `exe` (case-insensitive).
+pub fn find(exe: &str) -> Vec<u32> {
+    let mut out = Vec::new();
+    unsafe {
+        let snap = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snap == INVALID_HANDLE_VALUE { return out; }
+        let snap = Handle(snap);
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as DWORD;
+        let mut ok = Process32FirstW(snap.0, &mut entry);
+        while ok != 0 {
+            if from_wide(&entry.szExeFile).eq_ignore_ascii_case(exe) { out.push(entry.th32ProcessID); }
+            ok = Process32NextW(snap.0, &mut entry);
+        }
+    }
+    out
+}
+
+/// Whether `pid` has module `name` loaded. None = its module list can't be
+/// read (yet; it fails while a process is starting).
+pub fn has_module(pid: u32, name: &str) -> Option<bool> {
+    unsafe {
+        let snap = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32, pid);
+        if snap == INVALID_HANDLE_VALUE { return None; }
+        let snap = Handle(snap);
+        let mut entry: MODULEENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<MODULEENTRY32W>() as DWORD;
+        let mut ok = Module32FirstW(snap.0, &mut entry);
+        while ok != 0 {
+            if from_wide(&entry.szModule).eq_ignore_ascii_case(name) { return Some(true); }
+            ok = Module32NextW(snap.0, &mut entry);
+        }
+        Some(false)
+    }
+}
+
+/// Load `dll` into process `pid`.
+pub fn inject(pid: u32, dll: &Path) -> Result<(), String> {
+    let os_err = |what: &str| format!("{} failed: {}", what, std::io::Error::last_os_error());
+    let wide: Vec<u16> = OsStr::new(dll).encode_wide().chain(Some(0)).collect();
+    let size = wide.len() * 2;
+    unsafe {
+        let access = PROCESS_CREATE_THREAD | PROCESS_QUERY_INFORMATION | PROCESS_VM_OPERATION
+            | PROCESS_VM_WRITE | PROCESS_VM_READ;
+        let process = OpenProcess(access, FALSE, pid);
+        if process.is_null() { return Err(os_err("OpenProcess")); }
+        let process = Handle(process);
+
+        // --- The DLL path, in the game's memory ---
+        let remote = VirtualAllocEx(process.0, ptr::null_mut(), size, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE);
+        if remote.is_null() { return Err(os_err("VirtualAllocEx")); }
+        if WriteProcessMemory(process.0, remote, wide.as_ptr() as _, size, ptr::null_mut()) == 0 {
+            let e = os_err("WriteProcessMemory");
+            VirtualFreeEx(process.0, remote, 0, MEM_RELEASE);
+            return Err(e);
+        }
+
+        // --- LoadLibraryW(path) on a remote thread ---
+        let load = GetProcAddress(GetModuleHandleA(c"kernel32.dll".as_ptr()), c"LoadLibraryW".as_ptr());
+        if load.is_null() { return Err(os_err("GetProcAddress(LoadLibraryW)")); }
+        let start: LPTHREAD_START_ROUTINE = Some(std::mem::transmute::<FARPROC, unsafe extern "system" fn(LPVOID) -> DWORD>(load));
+        let thread = CreateRemoteThread(process.0, ptr::null_mut(), 0, start, remote, 0, ptr::null_mut());
+        if thread.is_null() {
+            let e = os_err("CreateRemoteThread");
+            VirtualFreeEx(process.0, remote, 0, MEM_RELEASE);
+            return Err(e);
+        }
+        let thread = Handle(thread);
+        if WaitForSingleObject(thread.0, LOAD_TIMEOUT_MS) != WAIT_OBJECT_0 {
+            // Still running: the path must stay allocated
+            return Err("LoadLibraryW didn't return in time".to_string());
+        }
+        let mut module: DWORD = 0;
+        GetExitCodeThread(thread.0, &mut module);
+        VirtualFreeEx(process.0, remote, 0, MEM_RELEASE);
+        if module == 0 {
+            return Err("LoadLibraryW returned NULL in the game (see esp_debug.log)".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// A game process being watched for its exit.
+pub struct Watch(Handle);
+
+impl Watch {
+    pub fn open(pid: u32) -> Option<Self> {
+        let h = unsafe { OpenProcess(SYNCHRONIZE | PROCESS_QUERY_INFORMATION, FALSE, pid) };
+        (!h.is_null()).then(|| Watch(Handle(h)))
+    }
+
+    /// The exit code once the process has exited.
+    pub fn exit_code(&self) -> Option<u32> {
+        unsafe {
+            if WaitForSingleObject(self.0 .0, 0) != WAIT_OBJECT_0 { return None; }
+            let mut code: DWORD = 0;
+            GetExitCodeProcess(self.0 .0, &mut code);
+            Some(code)
+        }
+    }
+}
@@ -0,0 +1,195 @@
+// json.rs — Just enough JSON to read the overlay's feed snapshots.
+//
+// The recordings are lines from the live feed (feed.rs), so the parser only
+// has to cope with what that writes: objects, arrays, strings with the usual
+// escapes, numbers, true / false / null.
+
+/// A parsed JSON value. Object keys keep their order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Value>),
+    Obj(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Member `key` of an object (None for other values or a missing key).
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Obj(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self { Value::Num(n) => Some(*n as f32), _ => None }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Value::Bool(b) => Some(*b), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Value::Str(s) => Some(s), _ => None }
+    }
+
+    pub fn as_arr(&self) -> Option<&[Value]> {
+        match self { Value::Arr(a) => Some(a), _ => None }
+    }
+
+    /// A [x, y, z] array.
+    pub fn as_vec3(&self) -> Option<[f32; 3]> {
+        match self.as_arr()? {
+            [x, y, z] => Some([x.as_f32()?, y.as_f32()?, z.as_f32()?]),
+            _ => None,
+        }
+    }
+}
+
+/// Parse one JSON document.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut p = Parser { s: text.as_bytes(), at: 0 };
+    let v = p.value()?;
+    p.ws();
+    if p.at != p.s.len() { return Err(format!("trailing characters at {}", p.at)); }
+    Ok(v)
+}
+
+struct Parser<'a> {
+    s:  &'a [u8],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn ws(&mut self) {
+        while self.s.get(self.at).is_some_and(|c| c.is_ascii_whitespace()) { self.at += 1; }
+    }
+
+    fn err<T>(&self, what: &str) -> Result<T, String> {
+        Err(format!("{} at {}", what, self.at))
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        self.ws();
+        let hit = self.s.get(self.at) == Some(&c);
+        if hit { self.at += 1; }
+        hit
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.ws();
+        match self.s.get(self.at) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Value::Str),
+            Some(b't') => self.word("true", Value::Bool(true)),
+            Some(b'f') => self.word("false", Value::Bool(false)),
+            Some(b'n') => self.word("null", Value::Null),
+            Some(c) if *c == b'-' || c.is_ascii_digit() => self.number(),
+            _ => self.err("expected a value"),
+        }
+    }
+
+    fn word(&mut self, w: &str, v: Value) -> Result<Value, String> {
+        if !self.s[self.at..].starts_with(w.as_bytes()) { return self.err("unknown word"); }
+        self.at += w.len();
+        Ok(v)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.at;
+        while self.s.get(self.at).is_some_and(|c| c.is_ascii_digit() || b"+-.eE".contains(c)) { self.at += 1; }
+        let text = std::str::from_utf8(&self.s[start..self.at]).unwrap_or("");
+        text.parse().map(Value::Num).or_else(|_| self.err("bad number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.at += 1; // Opening quote
+        let mut out = Vec::new();
+        loop {
+            let Some(&c) = self.s.get(self.at) else { return self.err("unterminated string") };
+            self.at += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&e) = self.s.get(self.at) else { return self.err("unterminated escape") };
+                    self.at += 1;
+                    match e {
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'r' => out.push(b'\r'),
+                        b'b' => out.push(8),
+                        b'f' => out.push(12),
+                        b'u' => {
+                            let hex = self.s.get(self.at..self.at + 4).and_then(|h| std::str::from_utf8(h).ok());
+                            let Some(code) = hex.and_then(|h| u32::from_str_radix(h, 16).ok()) else {
+                                return self.err("bad \\u escape");
+                            };
+                            self.at += 4;
+                            let ch = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                        }
+                        other => out.push(other), // \" \\ \/
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.at += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') { return Ok(Value::Arr(items)); }
+        loop {
+            items.push(self.value()?);
+            if self.eat(b']') { return Ok(Value::Arr(items)); }
+            if !self.eat(b',') { return self.err("expected , or ]"); }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.at += 1;
+        let mut members = Vec::new();
+        if self.eat(b'}') { return Ok(Value::Obj(members)); }
+        loop {
+            self.ws();
+            if self.s.get(self.at) != Some(&b'"') { return self.err("expected a key"); }
+            let key = self.string()?;
+            if !self.eat(b':') { return self.err("expected :"); }
+            members.push((key, self.value()?));
+            if self.eat(b'}') { return Ok(Value::Obj(members)); }
+            if !self.eat(b',') { return self.err("expected , or }"); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_feed_snapshot() {
+        let v = parse(r#"{"time": 5, "map": "de_dust2", "viewer": null,
+            "players": [{"name": "a\"bé", "origin": [1.0, -2.5, 3e1], "alive": true}]}"#).unwrap();
+        assert_eq!(v.get("time").and_then(Value::as_f32), Some(5.0));
+        assert_eq!(v.get("map").and_then(Value::as_str), Some("de_dust2"));
+        assert_eq!(v.get("viewer"), Some(&Value::Null));
+        let p = &v.get("players").and_then(Value::as_arr).unwrap()[0];
+        assert_eq!(p.get("name").and_then(Value::as_str), Some("a\"bé"));
+        assert_eq!(p.get("origin").and_then(Value::as_vec3), Some([1.0, -2.5, 30.0]));
+        assert_eq!(p.get("alive").and_then(Value::as_bool), Some(true));
+    }
+
+    #[test]
+    fn rejects_broken_documents() {
+        assert!(parse("{\"a\": 1").is_err());
+        assert!(parse("[1, 2,]").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("{} x").is_err());
+    }
+}
@@ -0,0 +1,318 @@
+// main.rs — Snapshot replays: the overlay's box, fade and label layout logic
+// run off the game against recorded feed snapshots and checked against golden
+// files.
+//
+//   esp_snapshots [--bless] [cases dir]
+//
+// A case is cases/<name>.jsonl: snapshots from the live feed ([feed], the
+// JSON served on http://127.0.0.1:27080/), one per line. Each line is replayed
+// as one overlay frame on a fixed SCREEN_W x SCREEN_H screen, seen through a
+// FOV camera at the viewer's eye: players are projected, smoothed and cached
+// the way esp.rs does it, players missing from a frame fade out of the cache,
+// and the boxes go through the box pass (draw.rs) under the default theme.
+// The frame's draw list is written out as text and must match
+// cases/<name>.golden; with --bless it replaces it. `cargo test -p
+// esp_snapshots` replays every case as well.
+//
+// The projection, cache, layout and draw list code is the DLL's own (math.rs,
+// view.rs, theme.rs, draw.rs), built here for the host; nothing else of the
+// overlay is needed.
+
+#[allow(dead_code)]
+#[path = "../../src/draw.rs"]
+mod draw;
+#[allow(dead_code)]
+#[path = "../../src/math.rs"]
+mod math;
+#[allow(dead_code)]
+#[path = "../../src/theme.rs"]
+mod theme;
+#[allow(dead_code)]
+#[path = "../../src/view.rs"]
+mod view;
+
+mod json;
+
+use draw::{BoxDraw, BoxPass, BracketStyle, Dash, DrawList, LineStyle, SnapTarget, Snaplines};
+use json::Value;
+use math::Vec3;
+use std::path::{Path, PathBuf};
+use theme::{ColorMode, Palette, Shade, Theme};
+use view::{NameFit, PlayerCache, PlayerCacheEntry};
+
+const SCREEN_W: f32 = 1024.0;
+const SCREEN_H: f32 = 768.0;
+const FOV: f32 = 90.0;                 // The game's default horizontal FOV
+const EYE_HEIGHT: f32 = 17.0;          // Standing view offset above the viewer's origin
+const MAXS_Z: f32 = 36.0;              // Hull top (the feed doesn't carry stance)
+const MAX_SLOT: usize = 32;
+const CACHE_TTL_MS: u32 = 1500;        // esp.rs's base cache lifetime
+const OFFLINE_ALPHA: f32 = 0.65;       // Cached boxes with no viewer to project with
+const LOW_HEALTH: i32 = 25;            // [health] low's default
+const UNITS_PER_METER: f32 = 39.37;
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// Cache state carried from frame to frame.
+#[derive(Default)]
+struct Replay {
+    cache: PlayerCache,
+}
+
+fn vec3(v: [f32; 3]) -> Vec3 {
+    Vec3 { x: v[0], y: v[1], z: v[2] }
+}
+
+fn to_px(nx: f32, ny: f32) -> [f32; 2] {
+    view::ndc_to_px(nx, ny, SCREEN_H, 0.0, 0.0, SCREEN_W, SCREEN_H)
+}
+
+impl Replay {
+    /// Replay one snapshot line: the frame's draw list.
+    fn frame(&mut self, snap: &Value) -> Result<DrawList, String> {
+        let now = snap.get("time").and_then(Value::as_f32).ok_or("no time")? as u32;
+        let viewer = match snap.get("viewer") {
+            Some(Value::Null) | None => None,
+            Some(v) => {
+                let origin = v.get("origin").and_then(Value::as_vec3).ok_or("viewer without origin")?;
+                let yaw = v.get("yaw").and_then(Value::as_f32).ok_or("viewer without yaw")?;
+                Some((vec3(origin), yaw))
+            }
+        };
+        let project = |p: Vec3| {
+            let (origin, yaw) = viewer?;
+            let eye = origin + Vec3 { x: 0.0, y: 0.0, z: EYE_HEIGHT };
+            math::world_to_ndc(eye, Vec3 { x: 0.0, y: yaw, z: 0.0 }, FOV, SCREEN_W / SCREEN_H, p)
+        };
+
+        let mut queued = Vec::new();
+        let mut fresh = [false; MAX_SLOT + 1];
+        for p in snap.get("players").and_then(Value::as_arr).unwrap_or(&[]) {
+            let slot = p.get("index").and_then(Value::as_f32).ok_or("player without index")? as usize;
+            if slot == 0 || slot > MAX_SLOT || p.get("alive").and_then(Value::as_bool) == Some(false) { continue; }
+            let origin = vec3(p.get("origin").and_then(Value::as_vec3).ok_or("player without origin")?);
+            let name = p.get("name").and_then(Value::as_str).unwrap_or("").to_string();
+            let team = p.get("team").and_then(Value::as_f32).unwrap_or(0.0) as i32;
+            let health = p.get("health").and_then(Value::as_f32).map(|h| h as i32);
+
+            let entry = self.cache.entry(slot);
+            let (rect, feet) = match view::project_box(origin, MAXS_Z, false, project, to_px) {
+                Some((rect, feet)) => {
+                    if !view::near_screen(feet, SCREEN_W, SCREEN_H) { continue; }
+                    entry.last_projected = now;
+                    (rect, feet)
+                }
+                None if view::hold_projection(now.wrapping_sub(entry.last_projected), entry.last_projected != 0) => {
+                    (entry.rect, entry.feet)
+                }
+                None => continue,
+            };
+            let (rect, feet) = view::smooth_box(entry.rect, entry.feet, rect, feet,
+                now.wrapping_sub(entry.last_seen), entry.last_seen != 0);
+            let dist = viewer.map_or(0.0, |(o, _)| o.distance(origin) / UNITS_PER_METER);
+            let color = pal().player_color(ColorMode::Team, team, 0);
+            queued.push(BoxDraw {
+                rect, feet, dist, color, team,
+                snap_alpha: 1.0,
+                name:       Some(name.clone()),
+                info:       format!("{:.1}m", dist),
+                text_alpha: 1.0,
+                health,
+                focus:      false,
+                head:       None,
+                line:       Dash::Solid,
+            });
+            fresh[slot] = true;
+            *entry = PlayerCacheEntry {
+                rect, feet, dist, color, team, origin, name,
+                maxs_z: MAXS_Z,
+                last_seen: now,
+                last_projected: entry.last_projected,
+                ..PlayerCacheEntry::default()
+            };
+        }
+
+        // Players missing from this frame fade out of the cache, reprojected if we can
+        for (slot, _) in fresh.iter().enumerate().skip(1).filter(|(_, &f)| !f) {
+            let Some(e) = self.cache.get(slot) else { continue };
+            let Some(age) = e.cached_age(now, CACHE_TTL_MS) else { continue };
+            let base = match viewer {
+                None => OFFLINE_ALPHA,
+                Some(_) if e.dist > 0.0 && e.dist < 10.0 => 0.95,
+                Some(_) => 0.60,
+            };
+            let Some(alpha) = view::cached_alpha(base, age) else { continue };
+            let (rect, feet) = if viewer.is_some() {
+                let Some((rect, feet)) = view::project_box(e.origin, e.maxs_z, e.ducking, project, to_px) else { continue };
+                if !view::near_screen(feet, SCREEN_W, SCREEN_H) { continue; }
+                (rect, feet)
+            } else {
+                (e.rect, e.feet)
+            };
+            let c = pal().cached_player_color(ColorMode::Team, e.team, 0);
+            queued.push(BoxDraw {
+                rect, feet,
+                dist:       e.dist,
+                color:      [c[0], c[1], c[2], alpha],
+                team:       e.team,
+                snap_alpha: alpha,
+                name:       (!e.name.is_empty()).then(|| e.name.clone()),
+                info:       format!("{:.1}m", e.dist),
+                text_alpha: alpha,
+                health:     None,
+                focus:      false,
+                head:       None,
+                line:       Dash::Solid,
+            });
+        }
+
+        Ok(draw(&mut queued))
+    }
+}
+
+fn pal() -> &'static Palette {
+    Theme::Default.palette()
+}
+
+/// The box pass under the default settings: solid boxes, health badges,
+/// snap-lines from the bottom of the screen to the feet, labels, LOW always shown.
+fn draw(boxes: &mut [BoxDraw]) -> DrawList {
+    let snap_color = |team: i32| pal().snapline_color(team);
+    let pass = BoxPass {
+        palette:   pal(),
+        line:      LineStyle::DEFAULT,
+        brackets:  BracketStyle::Classic,
+        shade:     &Shade::Solid,
+        badge:     true,
+        snaplines: Some(Snaplines {
+            from: [SCREEN_W * 0.5, SCREEN_H], target: SnapTarget::Feet, style: LineStyle::DEFAULT, color: &snap_color,
+        }),
+        head_lines: None,
+        labels:    true,
+        names:     NameFit::OFF,
+        low:       Some(LOW_HEALTH),
+        focus_width: 0.0,
+    };
+    draw::box_pass(boxes, &pass)
+}
+
+/// Replay a whole recording: the text its golden file holds.
+fn replay(recording: &str) -> Result<String, String> {
+    let mut r = Replay::default();
+    let mut out = String::new();
+    for (n, line) in recording.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let snap = json::parse(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        let list = r.frame(&snap).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        out.push_str(&format!("frame {}\n", n + 1));
+        for c in list.cmds() { out.push_str(&format!("  {}\n", c)); }
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Cases
+// ============================================================================
+
+/// The .jsonl recordings in `dir`, by name.
+fn cases(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "jsonl"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Replay one case against its golden file (or write it, when blessing).
+fn check(case: &Path, bless: bool) -> Result<(), String> {
+    let recording = std::fs::read_to_string(case).map_err(|e| e.to_string())?;
+    let got = replay(&recording)?;
+    let golden = case.with_extension("golden");
+    if bless { return std::fs::write(&golden, got).map_err(|e| e.to_string()); }
+
+    let want = std::fs::read_to_string(&golden)
+        .map_err(|e| format!("{}: {} (run with --bless to create it)", golden.display(), e))?;
+    let mut got_lines = got.lines();
+    for (n, w) in want.lines().enumerate() {
+        match got_lines.next() {
+            Some(g) if g == w => {}
+            Some(g) => return Err(format!("line {}: expected `{}`, got `{}`", n + 1, w.trim(), g.trim())),
+            None => return Err(format!("line {}: expected `{}`, got the end", n + 1, w.trim())),
+        }
+    }
+    match got_lines.next() {
+        Some(g) => Err(format!("extra output `{}`", g.trim())),
+        None => Ok(()),
+    }
+}
+
+fn default_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("cases")
+}
+
+fn main() {
+    let mut bless = false;
+    let mut dir = default_dir();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            _ => dir = PathBuf::from(arg),
+        }
+    }
+
+    let paths = match cases(&dir) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let mut failed = 0;
+    for case in &paths {
+        let name = case.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        match check(case, bless) {
+            Ok(()) if bless => println!("blessed {}", name),
+            Ok(())          => println!("ok      {}", name),
+            Err(e) => {
+                println!("FAIL    {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    println!("{} cases, {} failed", paths.len(), failed);
+    if failed > 0 { std::process::exit(1); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_match_goldens() {
+        let paths = cases(&default_dir()).unwrap();
+        assert!(!paths.is_empty());
+        for case in &paths {
+            if let Err(e) = check(case, false) { panic!("{}: {}", case.display(), e); }
+        }
+    }
+
+    #[test]
+    fn missing_player_fades_then_expires() {
+        let frame = |t: u32, with: bool| {
+            let p = if with { r#"{"index": 3, "name": "x", "team": 1, "origin": [400.0, 0.0, 0.0], "alive": true}"# } else { "" };
+            format!(r#"{{"time": {}, "viewer": {{"origin": [0.0, 0.0, 0.0], "yaw": 0.0}}, "players": [{}]}}"#, t, p)
+        };
+        let text = [frame(1000, true), frame(1100, false), frame(1400, false)].join("\n");
+        let out = replay(&text).unwrap();
+        let frames: Vec<&str> = out.split("frame ").skip(1).collect();
+        let name_line = |f: &str| f.lines().find(|l| l.ends_with("\"x\"")).map(str::to_string);
+        assert!(frames[0].contains("corners"));
+        assert!(frames[1].contains("corners"));
+        assert_ne!(name_line(frames[0]), None);
+        assert_ne!(name_line(frames[1]), name_line(frames[0])); // Faded
+        assert!(!frames[2].contains("corners"));
+    }
+}
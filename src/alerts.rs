@@ -0,0 +1,186 @@
+// alerts.rs — When to play an audio alert about an enemy.
+//
+// Two events, each edge-triggered per player so a player who stays put doesn't
+// beep again:
+//
+//   Near    an enemy comes within `radius` units
+//   Behind  an enemy within `behind_range` moves into the cone behind the
+//           viewer (`behind_cone` degrees wide, centered on the back)
+//
+// Each event kind has its own cooldown: a crossing that happens while it runs
+// is still recorded (so it doesn't fire late) but makes no sound. A player
+// missing from a frame's list resets, so coming back counts as a new crossing.
+// Playing the sound is the caller's job (esp.rs, through the engine).
+//
+// The closest-threat warning is the visual side: while the nearest enemy is
+// within `[threat] range` and outside the view, esp.rs shows which way they
+// are ("REAR-LEFT 4 m") from `closest_threat`.
+
+use crate::math::Vec3;
+use crate::player::MAX_CLIENTS;
+
+const SLOTS: usize = MAX_CLIENTS as usize + 1;
+
+/// An alert to play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alert { Near, Behind }
+
+/// Thresholds, from the config.
+#[derive(Clone, Copy, Debug)]
+pub struct AlertParams {
+    pub radius:       f32,      // Near: distance (units)
+    pub behind_cone:  f32,      // Behind: cone width (degrees)
+    pub behind_range: f32,      // Behind: only enemies this close (units)
+    pub cooldown_ms:  [u32; 2], // Per event: [Near, Behind]
+}
+
+/// Per-player crossing state and per-event cooldowns.
+pub struct Alerts {
+    near:   [bool; SLOTS],
+    behind: [bool; SLOTS],
+    fired:  [u32; 2],        // Last time each event played (0 = never)
+}
+
+impl Default for Alerts {
+    fn default() -> Self { Self::new() }
+}
+
+impl Alerts {
+    pub const fn new() -> Self {
+        Self { near: [false; SLOTS], behind: [false; SLOTS], fired: [0; 2] }
+    }
+
+    /// Feed this frame's enemies (slot, origin) as seen from `eye` looking
+    /// along `yaw` (degrees). Returns the alerts to play now, at most one per kind.
+    pub fn update(&mut self, p: &AlertParams, now: u32, eye: Vec3, yaw: f32, enemies: &[(i32, Vec3)]) -> Vec<Alert> {
+        let mut near = [false; SLOTS];
+        let mut behind = [false; SLOTS];
+        let mut crossed = [false; 2];
+        for &(idx, pos) in enemies {
+            let Some(i) = usize::try_from(idx).ok().filter(|&i| i > 0 && i < SLOTS) else { continue };
+            let dist = eye.distance(pos);
+            near[i] = dist <= p.radius;
+            behind[i] = dist <= p.behind_range && is_behind(eye, yaw, pos, p.behind_cone);
+            crossed[0] |= near[i] && !self.near[i];
+            crossed[1] |= behind[i] && !self.behind[i];
+        }
+        self.near = near;
+        self.behind = behind;
+
+        let mut out = Vec::new();
+        for (k, alert) in [Alert::Near, Alert::Behind].into_iter().enumerate() {
+            if !crossed[k] { continue; }
+            let last = self.fired[k];
+            if last != 0 && now.wrapping_sub(last) < p.cooldown_ms[k] { continue; }
+            self.fired[k] = now;
+            out.push(alert);
+        }
+        out
+    }
+}
+
+/// Whether `pos` lies in the `cone`-degree wedge behind a viewer at `eye`
+/// facing `yaw` (horizontal only).
+pub fn is_behind(eye: Vec3, yaw: f32, pos: Vec3, cone: f32) -> bool {
+    relative_yaw(eye, yaw, pos).is_some_and(|off| 180.0 - off.abs() <= cone * 0.5)
+}
+
+/// Degrees from where a viewer at `eye` facing `yaw` looks to `pos`
+/// (horizontal only): 0 = straight ahead, positive = to the left, +-180 =
+/// straight behind. None when `pos` is right above or below.
+pub fn relative_yaw(eye: Vec3, yaw: f32, pos: Vec3) -> Option<f32> {
+    let (dx, dy) = (pos.x - eye.x, pos.y - eye.y);
+    if dx == 0.0 && dy == 0.0 { return None; }
+    Some((dy.atan2(dx).to_degrees() - yaw + 180.0).rem_euclid(360.0) - 180.0)
+}
+
+/// The nearest enemy, when it's worth a warning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threat {
+    pub dist:    f32, // Units
+    pub bearing: f32, // relative_yaw
+}
+
+impl Threat {
+    /// Which way they are: "LEFT", "REAR-RIGHT", ... (45-degree sectors).
+    pub fn direction(&self) -> &'static str {
+        const NAMES: [&str; 8] = ["FRONT", "FRONT-LEFT", "LEFT", "REAR-LEFT",
+                                  "REAR", "REAR-RIGHT", "RIGHT", "FRONT-RIGHT"];
+        NAMES[((self.bearing.rem_euclid(360.0) + 22.5) / 45.0) as usize % NAMES.len()]
+    }
+}
+
+/// The nearest of `enemies` (slot, origin), if it's within `range` units of
+/// `eye` and more than `half_fov` degrees off `yaw`, i.e. not on screen.
+pub fn closest_threat(eye: Vec3, yaw: f32, half_fov: f32, range: f32, enemies: &[(i32, Vec3)]) -> Option<Threat> {
+    let &(_, pos) = enemies.iter().min_by(|a, b| eye.distance(a.1).total_cmp(&eye.distance(b.1)))?;
+    let dist = eye.distance(pos);
+    let bearing = relative_yaw(eye, yaw, pos)?;
+    (dist <= range && bearing.abs() > half_fov).then_some(Threat { dist, bearing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: AlertParams = AlertParams {
+        radius: 500.0, behind_cone: 90.0, behind_range: 1000.0, cooldown_ms: [2000, 2000],
+    };
+
+    fn at(x: f32, y: f32) -> Vec3 { Vec3 { x, y, z: 0.0 } }
+
+    #[test]
+    fn behind_cone() {
+        let eye = at(0.0, 0.0);
+        assert!(is_behind(eye, 0.0, at(-100.0, 0.0), 90.0));
+        assert!(is_behind(eye, 0.0, at(-100.0, 90.0), 90.0));   // 42 degrees off the back
+        assert!(!is_behind(eye, 0.0, at(-100.0, 110.0), 90.0)); // 48 degrees off
+        assert!(!is_behind(eye, 0.0, at(100.0, 0.0), 90.0));
+        assert!(is_behind(eye, 90.0, at(0.0, -50.0), 90.0));
+        assert!(is_behind(eye, -170.0, at(100.0, 10.0), 90.0)); // Wraps around +-180
+    }
+
+    #[test]
+    fn closest_threat_outside_the_view() {
+        let eye = at(0.0, 0.0);
+        let threat = closest_threat(eye, 0.0, 45.0, 1000.0, &[(1, at(-300.0, 400.0)), (2, at(-900.0, 0.0))]);
+        assert_eq!(threat.map(|t| (t.dist, t.direction())), Some((500.0, "REAR-LEFT")));
+        assert_eq!(closest_threat(eye, 90.0, 45.0, 1000.0, &[(1, at(50.0, -10.0))]).map(|t| t.direction()),
+                   Some("RIGHT"));
+        assert_eq!(closest_threat(eye, 0.0, 45.0, 1000.0, &[(1, at(-100.0, -5.0))]).map(|t| t.direction()),
+                   Some("REAR"));                                                       // Wraps around +-180
+        // The nearest one is in view: no warning, even with another behind
+        assert_eq!(closest_threat(eye, 0.0, 45.0, 1000.0, &[(1, at(200.0, 0.0)), (2, at(-300.0, 0.0))]), None);
+        assert_eq!(closest_threat(eye, 0.0, 45.0, 200.0, &[(1, at(-300.0, 0.0))]), None); // Out of range
+    }
+
+    #[test]
+    fn near_fires_on_entry_only() {
+        let mut a = Alerts::default();
+        let eye = at(0.0, 0.0);
+        assert!(a.update(&P, 100, eye, 0.0, &[(3, at(900.0, 0.0))]).is_empty());
+        assert_eq!(a.update(&P, 200, eye, 0.0, &[(3, at(400.0, 0.0))]), vec![Alert::Near]);
+        assert!(a.update(&P, 5000, eye, 0.0, &[(3, at(300.0, 0.0))]).is_empty()); // Still inside
+    }
+
+    #[test]
+    fn cooldown_per_event() {
+        let mut a = Alerts::default();
+        let eye = at(0.0, 0.0);
+        assert_eq!(a.update(&P, 100, eye, 0.0, &[(1, at(400.0, 0.0))]), vec![Alert::Near]);
+        // A second enemy enters during the cooldown: silent
+        assert!(a.update(&P, 600, eye, 0.0, &[(1, at(400.0, 0.0)), (2, at(0.0, 300.0))]).is_empty());
+        // Behind has its own cooldown
+        let out = a.update(&P, 700, eye, 0.0, &[(1, at(400.0, 0.0)), (2, at(0.0, 300.0)), (5, at(-800.0, 0.0))]);
+        assert_eq!(out, vec![Alert::Behind]);
+    }
+
+    #[test]
+    fn missing_player_resets() {
+        let mut a = Alerts::default();
+        let eye = at(0.0, 0.0);
+        assert_eq!(a.update(&P, 100, eye, 0.0, &[(4, at(-300.0, 0.0))]), vec![Alert::Near, Alert::Behind]);
+        assert!(a.update(&P, 200, eye, 0.0, &[]).is_empty());
+        assert_eq!(a.update(&P, 9000, eye, 0.0, &[(4, at(-300.0, 0.0))]), vec![Alert::Near, Alert::Behind]);
+    }
+}
@@ -0,0 +1,155 @@
+// alive.rs — Whether a player is alive when g_PlayerExtraInfo isn't available.
+//
+// The dead flag normally comes from client.dll's extra info. When neither
+// signature matches (an unknown client.dll, a mod without one configured),
+// dead players would be drawn forever, so entities.rs asks here instead. Each
+// slot goes down a chain of clues, cheapest first:
+//
+//   1. EF_NODRAW in entity_state_t::effects: the server hides a player's
+//      entity once they're dead and observing (their corpse is a copy)
+//   2. A death animation: the sequence's name, read from the model (see
+//      anim.rs), is one of the death sequences the player models use
+//   3. DeathMsg: the player was killed and hasn't been seen alive since, that
+//      is, drawn and not in a death animation RESPAWN_GRACE_MS later. A round
+//      start clears every DeathMsg death.
+//
+// The first clue that says dead wins; none = alive. The diagnostics page
+// shows which source is in use and how many players each clue accounts for.
+
+use crate::player::MAX_CLIENTS;
+
+pub const EF_NODRAW: i32 = 128;          // entity_state_t::effects bit (const.h)
+const RESPAWN_GRACE_MS: u32 = 5000;      // A DeathMsg death holds at least this long
+
+const SLOTS: usize = MAX_CLIENTS as usize + 1;
+
+/// Death sequence names of the CS and HL player models.
+const DEATH_SEQUENCES: &[&str] = &["head", "headshot", "gutshot", "left", "right", "back", "forward"];
+
+/// What a dead player was judged dead by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clue { NoDraw, Sequence, DeathMsg }
+
+impl Clue {
+    pub fn name(self) -> &'static str {
+        match self {
+            Clue::NoDraw   => "EF_NODRAW",
+            Clue::Sequence => "death sequence",
+            Clue::DeathMsg => "DeathMsg",
+        }
+    }
+}
+
+/// Whether a sequence name is a death animation ("death1", "crouch_die",
+/// "die_backwards", "gutshot", ...).
+pub fn is_death_sequence(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    n.starts_with("death") || n.starts_with("die") || n.ends_with("_die") || DEATH_SEQUENCES.contains(&n.as_str())
+}
+
+/// Per-slot fallback state.
+pub struct AliveTracker {
+    died:     [u32; SLOTS],                  // DeathMsg time (ms, 0 = none pending)
+    sequence: [(i32, i32, bool); SLOTS],     // Last (modelindex, sequence) looked up, and if it's a death one
+    verdict:  [Option<Clue>; SLOTS],         // Last judgement (None = alive or not judged)
+}
+
+impl AliveTracker {
+    pub const fn new() -> Self {
+        Self { died: [0; SLOTS], sequence: [(-1, -1, false); SLOTS], verdict: [None; SLOTS] }
+    }
+
+    /// A DeathMsg naming `victim`.
+    pub fn death_msg(&mut self, victim: i32, now: u32) {
+        if (1..=MAX_CLIENTS).contains(&victim) { self.died[victim as usize] = now.max(1); }
+    }
+
+    /// A new round (or map): everyone is alive again as far as DeathMsg goes.
+    pub fn round_start(&mut self) {
+        self.died = [0; SLOTS];
+    }
+
+    /// Judge slot `idx` from its entity: `effects`, `model` and `sequence`.
+    /// `name` looks the sequence name up; it's only called when the model or
+    /// sequence changed. Some(clue) = dead.
+    pub fn judge(&mut self, idx: i32, effects: i32, model: i32, sequence: i32, now: u32,
+                 name: impl FnOnce() -> Option<String>) -> Option<Clue> {
+        if idx <= 0 || idx > MAX_CLIENTS { return None; }
+        let i = idx as usize;
+
+        let nodraw = effects & EF_NODRAW != 0;
+        let (m, s, _) = self.sequence[i];
+        if (m, s) != (model, sequence) {
+            let death = name().is_some_and(|n| is_death_sequence(&n));
+            self.sequence[i] = (model, sequence, death);
+        }
+        let death_seq = self.sequence[i].2;
+
+        let died = self.died[i];
+        if died != 0 && !nodraw && !death_seq && now.wrapping_sub(died) >= RESPAWN_GRACE_MS {
+            self.died[i] = 0; // Seen alive since: respawned
+        }
+
+        let verdict = if nodraw {
+            Some(Clue::NoDraw)
+        } else if death_seq {
+            Some(Clue::Sequence)
+        } else if self.died[i] != 0 {
+            Some(Clue::DeathMsg)
+        } else {
+            None
+        };
+        self.verdict[i] = verdict;
+        verdict
+    }
+
+    /// Forget slot `idx`'s last judgement (it wasn't read this frame).
+    pub fn forget(&mut self, idx: i32) {
+        if (1..=MAX_CLIENTS).contains(&idx) { self.verdict[idx as usize] = None; }
+    }
+
+    /// How many players each clue currently accounts for.
+    pub fn counts(&self) -> Vec<(Clue, usize)> {
+        [Clue::NoDraw, Clue::Sequence, Clue::DeathMsg].into_iter()
+            .map(|c| (c, self.verdict.iter().filter(|v| **v == Some(c)).count()))
+            .filter(|(_, n)| *n > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_death_sequences() {
+        for n in ["death1", "DEATH3", "crouch_die", "die_backwards", "gutshot", "head", "back"] {
+            assert!(is_death_sequence(n), "{}", n);
+        }
+        for n in ["ref_aim_ak47", "run", "crouch_idle", "ref_reload_m4a1", "backflip"] {
+            assert!(!is_death_sequence(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn clues_in_order_and_deathmsg_until_seen_alive() {
+        let mut t = AliveTracker::new();
+        let seq = |n: &'static str| move || Some(n.to_string());
+        assert_eq!(t.judge(3, 0, 1, 4, 100, seq("run")), None);
+        assert_eq!(t.judge(3, EF_NODRAW, 1, 4, 150, seq("run")), Some(Clue::NoDraw));
+        assert_eq!(t.judge(3, 0, 1, 9, 200, seq("death2")), Some(Clue::Sequence));
+
+        // Killed: held through the grace time, then dropped once seen alive
+        t.death_msg(3, 1000);
+        assert_eq!(t.judge(3, 0, 1, 4, 1100, seq("run")), Some(Clue::DeathMsg));
+        assert_eq!(t.counts(), [(Clue::DeathMsg, 1)]);
+        assert_eq!(t.judge(3, 0, 1, 4, 1000 + RESPAWN_GRACE_MS, || None), None);
+
+        t.death_msg(3, 9000);
+        t.round_start();
+        assert_eq!(t.judge(3, 0, 1, 4, 9100, || None), None);
+        t.forget(3);
+        assert!(t.counts().is_empty());
+        assert_eq!(t.judge(0, EF_NODRAW, 1, 4, 1, || None), None);
+    }
+}
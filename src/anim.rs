@@ -0,0 +1,158 @@
+// anim.rs — What a player's weapon is doing: reloading or switching.
+//
+// Neither is sent to the client as such, so both are inferred per slot:
+//
+//   - Reloading: CS player models play the upper-body sequence
+//     "ref_reload_<weapon>" while reloading. entity_state_t::sequence is just
+//     an index, so its name is read from the model's studio header (the
+//     sequence table in studiohdr_t, reached through model_t::cache). The name
+//     is only looked up when the sequence or model changes.
+//   - Switching: entity_state_t::weaponmodel changes when a new weapon is
+//     drawn; the tag shows for a while after that.
+//
+// Studio headers are checked (IDST magic, version 10, sequence in range)
+// before anything is read from them; a model the engine hasn't loaded yet has
+// no header, and just gives no reload tag.
+
+use crate::player::{EngineReader, MAX_CLIENTS};
+
+const MODEL_CACHE_DATA: usize = 0x184;  // model_t::cache.data (studiohdr_t once loaded)
+const STUDIO_ID:        i32 = i32::from_le_bytes(*b"IDST");
+const STUDIO_VERSION:   i32 = 10;
+const STUDIO_NUMSEQ:    usize = 0xA4;   // studiohdr_t::numseq
+const STUDIO_SEQINDEX:  usize = 0xA8;   // studiohdr_t::seqindex (offset from the header)
+const SEQDESC_SIZE:     usize = 0xB0;   // sizeof(mstudioseqdesc_t); label[32] comes first
+const SEQ_LABEL_LEN:    usize = 32;
+const MAX_SEQUENCES:    i32 = 2048;     // More than this isn't a real header
+
+const SLOTS: usize = MAX_CLIENTS as usize + 1;
+
+/// A transient weapon state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action { Reloading, Switching }
+
+impl Action {
+    /// Tag shown under the box.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Action::Reloading => "RELOADING",
+            Action::Switching => "SWITCHING",
+        }
+    }
+}
+
+/// What a player slot shows this frame, from entity_state_t.
+#[derive(Clone, Copy, Debug)]
+pub struct Seen {
+    pub weapon:   i32, // weaponmodel
+    pub model:    i32, // modelindex
+    pub sequence: i32,
+}
+
+/// Name of sequence `seq` of the model whose model_t is at `model` (None if
+/// the model isn't loaded or the header doesn't check out).
+pub fn sequence_name(r: &impl EngineReader, model: usize, seq: i32) -> Option<String> {
+    if model == 0 || seq < 0 { return None; }
+    let hdr = r.read_i32(model + MODEL_CACHE_DATA) as u32 as usize;
+    if hdr == 0 || r.read_i32(hdr) != STUDIO_ID || r.read_i32(hdr + 4) != STUDIO_VERSION { return None; }
+    let count = r.read_i32(hdr + STUDIO_NUMSEQ);
+    if count <= 0 || count > MAX_SEQUENCES || seq >= count { return None; }
+    let label = hdr + r.read_i32(hdr + STUDIO_SEQINDEX) as u32 as usize + seq as usize * SEQDESC_SIZE;
+    let bytes: Vec<u8> = (0..SEQ_LABEL_LEN).map(|i| r.read_u8(label + i)).take_while(|&b| b != 0).collect();
+    if bytes.is_empty() || !bytes.iter().all(|b| b.is_ascii_graphic()) { return None; }
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether a sequence name is a reload animation.
+pub fn is_reload(name: &str) -> bool {
+    name.to_ascii_lowercase().contains("reload")
+}
+
+/// Per-slot weapon state tracking.
+pub struct ActionTracker {
+    weapon:   [i32; SLOTS],          // Last weaponmodel (0 = not seen)
+    switched: [u32; SLOTS],          // When it last changed (ms, 0 = never)
+    sequence: [(i32, i32); SLOTS],   // Last (modelindex, sequence) looked up
+    reload:   [bool; SLOTS],         // Whether that sequence is a reload
+}
+
+impl ActionTracker {
+    pub const fn new() -> Self {
+        Self {
+            weapon:   [0; SLOTS],
+            switched: [0; SLOTS],
+            sequence: [(-1, -1); SLOTS],
+            reload:   [false; SLOTS],
+        }
+    }
+
+    /// Record what slot `idx` shows as of `now` and say what it's doing.
+    /// `name` looks the sequence name up; it's only called when the model or
+    /// sequence changed. A switch shows for `switch_ms`.
+    pub fn update(&mut self, idx: i32, seen: Seen, now: u32, switch_ms: u32,
+                  name: impl FnOnce() -> Option<String>) -> Option<Action> {
+        if idx <= 0 || idx > MAX_CLIENTS { return None; }
+        let i = idx as usize;
+        let Seen { weapon, model, sequence } = seen;
+
+        if weapon != self.weapon[i] {
+            // The first weapon seen isn't a switch
+            if self.weapon[i] != 0 && weapon != 0 { self.switched[i] = now.max(1); }
+            self.weapon[i] = weapon;
+        }
+        if self.sequence[i] != (model, sequence) {
+            self.sequence[i] = (model, sequence);
+            self.reload[i] = name().is_some_and(|n| is_reload(&n));
+        }
+
+        if self.reload[i] { return Some(Action::Reloading); }
+        let since = self.switched[i];
+        (since != 0 && now.wrapping_sub(since) < switch_ms).then_some(Action::Switching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockEngine;
+
+    const MODEL: usize = 0x3000_0000;
+    const HDR: usize = 0x3100_0000;
+
+    fn studio(e: &MockEngine, names: &[&str]) {
+        e.write_i32(MODEL + MODEL_CACHE_DATA, HDR as i32);
+        e.write(HDR, b"IDST");
+        e.write_i32(HDR + 4, STUDIO_VERSION);
+        e.write_i32(HDR + STUDIO_NUMSEQ, names.len() as i32);
+        e.write_i32(HDR + STUDIO_SEQINDEX, 0x400);
+        for (i, n) in names.iter().enumerate() {
+            e.write(HDR + 0x400 + i * SEQDESC_SIZE, n.as_bytes());
+        }
+    }
+
+    #[test]
+    fn reads_sequence_names_from_the_studio_header() {
+        let e = MockEngine::default();
+        studio(&e, &["idle1", "ref_reload_ak47"]);
+        assert_eq!(sequence_name(&e, MODEL, 1).as_deref(), Some("ref_reload_ak47"));
+        assert_eq!(sequence_name(&e, MODEL, 2), None);
+        assert_eq!(sequence_name(&e, MODEL, -1), None);
+        e.write_i32(HDR + 4, 44); // Not a GoldSrc studio model
+        assert_eq!(sequence_name(&e, MODEL, 0), None);
+        assert_eq!(sequence_name(&e, 0, 0), None);
+    }
+
+    #[test]
+    fn tracks_reloads_and_switches() {
+        let mut t = ActionTracker::new();
+        let idle = || Some("ref_aim_ak47".to_string());
+        let seen = |weapon, sequence| Seen { weapon, model: 5, sequence };
+        assert_eq!(t.update(3, seen(10, 1), 1000, 750, idle), None); // First weapon seen
+        assert_eq!(t.update(3, seen(11, 1), 1100, 750, || panic!("looked up again")), Some(Action::Switching));
+        assert_eq!(t.update(3, seen(11, 1), 1849, 750, idle), Some(Action::Switching));
+        assert_eq!(t.update(3, seen(11, 1), 1850, 750, idle), None);
+        assert_eq!(t.update(3, seen(11, 2), 1900, 750, || Some("ref_reload_ak47".into())), Some(Action::Reloading));
+        assert_eq!(t.update(3, seen(11, 1), 2900, 750, idle), None);
+        assert_eq!(t.update(0, seen(11, 1), 2900, 750, idle), None);
+    }
+}
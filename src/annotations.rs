@@ -0,0 +1,170 @@
+// annotations.rs — Named points marked on a map, kept in a file per map.
+//
+// `[annotations] key` (or `esp_mark <name>` in the console) drops a marker
+// where the crosshair points, or `distance` units along the view;
+// `esp_unmark` takes away the one nearest that point. Each map's markers are saved to
+// `esp_annotations/<map>.txt` next to the DLL and loaded whenever the map
+// does (a map loaded event, esp.rs), one per line:
+//
+//   x y z name
+//
+// e.g. `-512.0 1024.5 96.0 boost to b window`. Lines starting with `#` and
+// lines that don't parse are skipped, so the files can be edited by hand.
+
+use crate::bsp::Wall;
+use crate::math::Vec3;
+
+pub const UNMARK_RANGE: f32 = 128.0; // esp_unmark only takes a marker this close
+pub const AIM_REACH: f32 = 2048.0;   // Furthest a marker goes along the crosshair
+const WALL_GAP: f32 = 4.0;           // A marker on a wall is kept this far in front of it
+
+/// One marker.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mark {
+    pub pos:  Vec3,
+    pub name: String,
+}
+
+/// The markers of one map.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Marks {
+    pub map:   String,   // "maps/de_dust2.bsp"
+    pub marks: Vec<Mark>,
+}
+
+impl Marks {
+    pub fn new(map: &str) -> Self {
+        Self { map: map.to_string(), marks: Vec::new() }
+    }
+
+    /// Markers from a file's text.
+    pub fn parse(map: &str, text: &str) -> Self {
+        let mut out = Self::new(map);
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let mut rest = line;
+            let mut num = || {
+                let (n, r) = rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+                rest = r.trim_start();
+                n.parse::<f32>().ok().filter(|n| n.is_finite())
+            };
+            let (Some(x), Some(y), Some(z)) = (num(), num(), num()) else { continue };
+            out.marks.push(Mark { pos: Vec3::new(x, y, z), name: rest.to_string() });
+        }
+        out
+    }
+
+    /// The file's text.
+    pub fn to_text(&self) -> String {
+        let mut out = format!("# {}: x y z name\n", self.map);
+        for m in &self.marks {
+            out.push_str(&format!("{:.1} {:.1} {:.1} {}\n", m.pos.x, m.pos.y, m.pos.z, m.name));
+        }
+        out
+    }
+
+    /// Add a marker at `pos`; without a name it's "mark N".
+    pub fn add(&mut self, pos: Vec3, name: Option<&str>) -> &Mark {
+        let name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(n) => n.replace(['\r', '\n'], " "),
+            None => {
+                let n = (1..).find(|n| !self.marks.iter().any(|m| m.name == format!("mark {}", n))).unwrap_or(1);
+                format!("mark {}", n)
+            }
+        };
+        self.marks.push(Mark { pos, name });
+        self.marks.last().unwrap()
+    }
+
+    /// Take away the marker closest to `pos`, if it's within `range`.
+    pub fn remove_nearest(&mut self, pos: Vec3, range: f32) -> Option<Mark> {
+        let (i, d) = self.marks.iter().enumerate()
+            .map(|(i, m)| (i, m.pos.distance(pos)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        (d <= range).then(|| self.marks.remove(i))
+    }
+}
+
+/// Where the view ray from `eye` along `dir` (a unit vector) first meets one
+/// of the map's walls or the floor at height `floor_z`, else AIM_REACH along
+/// it. The walls are the map's flattened vertical faces (bsp.rs) and the
+/// floor is taken as flat, so on slopes and stairs it's an estimate.
+pub fn aim_point(eye: Vec3, dir: Vec3, floor_z: f32, walls: &[Wall]) -> Vec3 {
+    let mut t = AIM_REACH;
+    if dir.z < 0.0 { t = t.min(((floor_z - eye.z) / dir.z).max(0.0)); }
+    if let Some(hit) = walls.iter().filter_map(|w| wall_hit(eye, dir, w)).min_by(f32::total_cmp) {
+        if hit < t { t = (hit - WALL_GAP).max(0.0); }
+    }
+    eye + dir * t
+}
+
+/// How far along the ray from `eye` along `dir` it meets `w`, if it does
+/// within the wall's height.
+fn wall_hit(eye: Vec3, dir: Vec3, w: &Wall) -> Option<f32> {
+    let cross = |a: [f32; 2], b: [f32; 2]| a[0] * b[1] - a[1] * b[0];
+    let (d, e) = ([dir.x, dir.y], [w.b[0] - w.a[0], w.b[1] - w.a[1]]);
+    let denom = cross(d, e);
+    if denom.abs() < 1e-6 { return None; } // Parallel (or looking straight up or down)
+    let to_a = [w.a[0] - eye.x, w.a[1] - eye.y];
+    let (t, u) = (cross(to_a, e) / denom, cross(to_a, d) / denom);
+    let z = eye.z + dir.z * t;
+    (t >= 0.0 && (0.0..=1.0).contains(&u) && z >= w.z[0] && z <= w.z[1]).then_some(t)
+}
+
+/// The file a map's markers are kept in: "de_dust2.txt" for
+/// "maps/de_dust2.bsp", anything unsafe in a file name replaced.
+pub fn file_name(map: &str) -> String {
+    let stem = map.rsplit(['/', '\\']).next().unwrap_or(map).trim_end_matches(".bsp");
+    let stem: String = stem.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+        .collect();
+    format!("{}.txt", stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_writes_marks() {
+        let text = "# comment\n-512  1024.5\t96 boost to b window\n1 2 nope\n\n0 0 0\n";
+        let m = Marks::parse("maps/de_dust2.bsp", text);
+        assert_eq!(m.marks.len(), 2);
+        assert_eq!(m.marks[0], Mark { pos: Vec3::new(-512.0, 1024.5, 96.0), name: "boost to b window".into() });
+        assert_eq!(m.marks[1].name, "");
+        let again = Marks::parse("maps/de_dust2.bsp", &m.to_text());
+        assert_eq!(again, m);
+        assert!(m.to_text().starts_with("# maps/de_dust2.bsp: x y z name\n-512.0 1024.5 96.0 boost"));
+    }
+
+    #[test]
+    fn adds_names_and_removes_nearest() {
+        let mut m = Marks::new("maps/cs_office.bsp");
+        assert_eq!(m.add(Vec3::new(0.0, 0.0, 0.0), None).name, "mark 1");
+        assert_eq!(m.add(Vec3::new(500.0, 0.0, 0.0), Some(" clip\nbug ")).name, "clip bug");
+        assert_eq!(m.add(Vec3::new(90.0, 0.0, 0.0), Some("")).name, "mark 2");
+        assert_eq!(m.remove_nearest(Vec3::new(80.0, 0.0, 0.0), UNMARK_RANGE).unwrap().name, "mark 2");
+        assert_eq!(m.remove_nearest(Vec3::new(300.0, 0.0, 0.0), UNMARK_RANGE), None);
+        assert_eq!(m.add(Vec3::new(1.0, 0.0, 0.0), None).name, "mark 2");
+
+        assert_eq!(file_name("maps/de_dust2.bsp"), "de_dust2.txt");
+        assert_eq!(file_name("maps\\my map:1.bsp"), "my_map_1.txt");
+    }
+
+    #[test]
+    fn aims_at_the_first_wall_or_the_floor() {
+        let eye = Vec3::new(0.0, 0.0, 64.0);
+        let wall = Wall { a: [100.0, -50.0], b: [100.0, 50.0], z: [0.0, 128.0] };
+        let near = |a: Vec3, b: Vec3| a.distance(b) < 1e-3;
+        let down = Vec3::new(1.0, 0.0, -1.0) * std::f32::consts::FRAC_1_SQRT_2;
+
+        assert!(near(aim_point(eye, down, 0.0, &[]), Vec3::new(64.0, 0.0, 0.0)));               // Floor
+        assert!(near(aim_point(eye, Vec3::new(1.0, 0.0, 0.0), 0.0, &[wall]), Vec3::new(96.0, 0.0, 64.0)));
+        let far = Wall { a: [300.0, -50.0], b: [300.0, 50.0], z: [0.0, 128.0] };
+        assert!(near(aim_point(eye, Vec3::new(1.0, 0.0, 0.0), 0.0, &[far, wall]), Vec3::new(96.0, 0.0, 64.0)));
+        assert!(near(aim_point(eye, down, 0.0, &[far]), Vec3::new(64.0, 0.0, 0.0)));            // Floor first
+        let low = Wall { z: [0.0, 32.0], ..wall };
+        assert!(near(aim_point(eye, Vec3::new(1.0, 0.0, 0.0), 0.0, &[low]), Vec3::new(AIM_REACH, 0.0, 64.0))); // Over it
+        assert!(near(aim_point(eye, Vec3::new(-1.0, 0.0, 0.0), 0.0, &[wall]), Vec3::new(-AIM_REACH, 0.0, 64.0))); // Behind
+        assert!(near(aim_point(eye, Vec3::new(0.0, 0.0, 1.0), 0.0, &[wall]), Vec3::new(0.0, 0.0, 64.0 + AIM_REACH)));
+    }
+}
@@ -0,0 +1,112 @@
+// bench.rs — Synthetic players for the benchmark mode ([performance] benchmark).
+//
+// With the mode on, esp.rs draws these instead of reading the engine, so box,
+// label and layout changes can be profiled and screenshotted in any OpenGL
+// app, not only during a CS session. The scene is a pure function of time:
+// PLAYERS players on rings around a camera that turns slowly, walking around
+// their ring, some of them ducking, so boxes move, overlap and cross the
+// screen edges every frame the way a busy server's do. Nothing here touches
+// engine memory.
+
+use crate::math::{self, Vec3};
+
+/// Number of synthetic players.
+pub const PLAYERS: usize = 64;
+
+const FOV: f32 = 90.0;                   // Horizontal field of view of the camera
+const EYE_Z: f32 = 64.0;                 // Camera height above the players' floor
+const CAMERA_DEG_PER_S: f32 = 12.0;      // How fast the camera turns
+const RINGS: usize = 4;                  // Players are spread over this many rings
+const RING_STEP: f32 = 300.0;            // Radius added per ring (world units)
+const WALK_UNITS_PER_S: f32 = 120.0;     // Walking speed along a ring
+const STAND_MAXS_Z: f32 = 36.0;          // Hull top of a standing player
+const DUCK_MAXS_Z: f32 = 18.0;           // ... and of a ducking one
+const WEAPONS: [&str; 6] = ["ak47", "m4a1", "awp", "deagle", "usp", "knife"];
+
+/// One synthetic player.
+#[derive(Clone, Debug)]
+pub struct FakePlayer {
+    pub name:    String,
+    pub team:    i32,          // 1 = T, 2 = CT, alternating
+    pub origin:  Vec3,         // Hull center
+    pub maxs_z:  f32,
+    pub ducking: bool,
+    pub health:  i32,
+    pub weapon:  &'static str,
+}
+
+/// The camera and players at one moment.
+pub struct Scene {
+    pub eye:     Vec3,
+    pub angles:  Vec3,         // Pitch, yaw, roll (degrees)
+    pub players: Vec<FakePlayer>,
+}
+
+/// The scene `time_ms` into the benchmark.
+pub fn scene(time_ms: u32) -> Scene {
+    let t = time_ms as f32 / 1000.0;
+    let players = (0..PLAYERS)
+        .map(|i| {
+            let ring = i % RINGS;
+            let radius = RING_STEP * (ring + 1) as f32;
+            // Spread evenly around the ring, walking (alternate rings the other way)
+            let dir = if ring & 1 == 0 { 1.0 } else { -1.0 };
+            let start = (i / RINGS) as f32 / (PLAYERS / RINGS) as f32 * std::f32::consts::TAU;
+            let angle = start + dir * WALK_UNITS_PER_S * t / radius;
+            let ducking = i % 5 == 0;
+            let maxs_z = if ducking { DUCK_MAXS_Z } else { STAND_MAXS_Z };
+            FakePlayer {
+                name:    format!("bench{:02}", i + 1),
+                team:    1 + (i % 2) as i32,
+                origin:  Vec3 { x: radius * angle.cos(), y: radius * angle.sin(), z: maxs_z },
+                maxs_z,
+                ducking,
+                health:  100 - ((i * 7 + time_ms as usize / 1000) % 100) as i32,
+                weapon:  WEAPONS[i % WEAPONS.len()],
+            }
+        })
+        .collect();
+    Scene {
+        eye:    Vec3 { x: 0.0, y: 0.0, z: EYE_Z },
+        angles: Vec3 { x: 0.0, y: (t * CAMERA_DEG_PER_S) % 360.0, z: 0.0 },
+        players,
+    }
+}
+
+impl Scene {
+    /// Project a world point for a viewport `aspect` (width / height) wide, in
+    /// the engine's NDC convention.
+    pub fn project(&self, p: Vec3, aspect: f32) -> Option<(f32, f32)> {
+        math::world_to_ndc(self.eye, self.angles, FOV, aspect, p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_is_deterministic_and_full() {
+        let (a, b) = (scene(12_345), scene(12_345));
+        assert_eq!(a.players.len(), PLAYERS);
+        assert_eq!(a.angles, b.angles);
+        for (p, q) in a.players.iter().zip(&b.players) {
+            assert_eq!((&p.name, p.origin, p.team), (&q.name, q.origin, q.team));
+        }
+        assert!(a.players.iter().all(|p| (1..=2).contains(&p.team) && (1..=100).contains(&p.health)));
+        assert_eq!(a.players.iter().filter(|p| p.team == 1).count(), PLAYERS / 2);
+    }
+
+    #[test]
+    fn scene_moves_and_some_players_are_in_view() {
+        let (a, b) = (scene(0), scene(1000));
+        assert_ne!(a.angles.y, b.angles.y);
+        assert!(a.players.iter().zip(&b.players).all(|(p, q)| p.origin != q.origin));
+
+        let visible = a.players.iter()
+            .filter_map(|p| a.project(p.origin, 16.0 / 9.0))
+            .filter(|&(x, y)| x.abs() <= 1.0 && y.abs() <= 1.0)
+            .count();
+        assert!(visible > 0 && visible < PLAYERS, "{} in view", visible);
+    }
+}
@@ -0,0 +1,383 @@
+// bsp.rs — Reading GoldSrc (version 30) .bsp map files.
+//
+// A .bsp starts with the version and a directory of 15 lumps (offset, length).
+// Only what the overlay needs is read:
+//
+//   - the entity lump — the map's entity list as text, `{ "key" "value" ... }`
+//     blocks — which names the class of every brush entity ("model" "*12" ->
+//     "classname" "func_door");
+//   - the world's bounding box (model 0's mins / maxs), which limits where a
+//     player can be and how far the minimap needs to reach;
+//   - the world's walls for the minimap: every face of model 0 (the world brush)
+//     whose plane is close to vertical, flattened to a 2D segment plus the
+//     height range it covers. Faces are found through models -> faces ->
+//     surfedges -> edges -> vertexes; floors, ceilings and slopes are dropped.
+//
+// The file comes from disk, possibly a custom or broken map, so every offset
+// is bounds-checked and a bad file yields None rather than a panic.
+
+/// The only BSP version GoldSrc loads.
+pub const BSP_VERSION: i32 = 30;
+
+const HEADER_LUMPS: usize = 15;
+const LUMP_ENTITIES:  usize = 0;
+const LUMP_PLANES:    usize = 1;
+const LUMP_VERTEXES:  usize = 3;
+const LUMP_FACES:     usize = 7;
+const LUMP_EDGES:     usize = 12;
+const LUMP_SURFEDGES: usize = 13;
+const LUMP_MODELS:    usize = 14;
+
+const PLANE_SIZE:  usize = 20;  // dplane_t: normal[3], dist, type
+const VERTEX_SIZE: usize = 12;  // dvertex_t: point[3]
+const FACE_SIZE:   usize = 20;  // dface_t: planenum, side, firstedge, numedges, texinfo, styles[4], lightofs
+const EDGE_SIZE:   usize = 4;   // dedge_t: v[2] (u16)
+const MODEL_SIZE:  usize = 64;  // dmodel_t: mins, maxs, origin, headnode[4], visleafs, firstface, numfaces
+
+/// Faces whose plane normal has a larger |z| than this are floors, ceilings or
+/// slopes, not walls.
+const WALL_MAX_NORMAL_Z: f32 = 0.3;
+
+/// Wall endpoints are snapped to this grid (units) to merge duplicates: the two
+/// sides of a thin wall, or one wall split into several faces of the same size.
+const WALL_SNAP: f32 = 4.0;
+
+/// One `{ ... }` block of the entity lump.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BspEntity {
+    pub fields: Vec<(String, String)>,
+}
+
+impl BspEntity {
+    /// Value of `key` (the first one, if repeated).
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+/// A wall flattened to the map's XY plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wall {
+    pub a: [f32; 2],
+    pub b: [f32; 2],
+    pub z: [f32; 2], // Lowest and highest point
+}
+
+/// The world's bounding box, from model 0 (the world brush).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub mins: [f32; 3],
+    pub maxs: [f32; 3],
+}
+
+impl Bounds {
+    /// Whether `p` lies inside, allowing `margin` units on every side.
+    pub fn contains(&self, p: [f32; 3], margin: f32) -> bool {
+        (0..3).all(|i| p[i] >= self.mins[i] - margin && p[i] <= self.maxs[i] + margin)
+    }
+
+    /// The larger of the horizontal extents (width, depth).
+    #[cfg(feature = "radar")]
+    pub fn extent(&self) -> f32 {
+        (self.maxs[0] - self.mins[0]).max(self.maxs[1] - self.mins[1])
+    }
+}
+
+fn read_i32(data: &[u8], at: usize) -> Option<i32> {
+    let b = data.get(at..at + 4)?;
+    Some(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    let b = data.get(at..at + 2)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_f32(data: &[u8], at: usize) -> Option<f32> {
+    read_i32(data, at).map(|v| f32::from_bits(v as u32)).filter(|v| v.is_finite())
+}
+
+fn read_vec3(data: &[u8], at: usize) -> Option<[f32; 3]> {
+    Some([read_f32(data, at)?, read_f32(data, at + 4)?, read_f32(data, at + 8)?])
+}
+
+/// Bytes of lump `idx`, if the header is valid and the lump lies in the file.
+pub fn lump(data: &[u8], idx: usize) -> Option<&[u8]> {
+    if idx >= HEADER_LUMPS || read_i32(data, 0)? != BSP_VERSION { return None; }
+    let off = read_i32(data, 4 + idx * 8)?;
+    let len = read_i32(data, 8 + idx * 8)?;
+    if off < 0 || len < 0 { return None; }
+    data.get(off as usize..(off as usize).checked_add(len as usize)?)
+}
+
+/// Parse the entity lump of a .bsp file.
+pub fn entities(data: &[u8]) -> Option<Vec<BspEntity>> {
+    let text = lump(data, LUMP_ENTITIES)?;
+    let text = text.split(|&b| b == 0).next().unwrap_or(text); // NUL-terminated
+    Some(parse_entity_text(&String::from_utf8_lossy(text)))
+}
+
+/// Parse entity lump text. Unterminated blocks and stray tokens are dropped.
+pub fn parse_entity_text(text: &str) -> Vec<BspEntity> {
+    let mut out = Vec::new();
+    let mut current: Option<BspEntity> = None;
+    let mut key: Option<String> = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => { current = Some(BspEntity::default()); key = None; }
+            '}' => {
+                if let Some(e) = current.take() { out.push(e); }
+                key = None;
+            }
+            '"' => {
+                let mut s = String::new();
+                for c in chars.by_ref() {
+                    if c == '"' { break; }
+                    s.push(c);
+                }
+                let Some(e) = current.as_mut() else { continue };
+                match key.take() {
+                    None    => key = Some(s),
+                    Some(k) => e.fields.push((k, s)),
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Brush entity classes by inline model name: ("*12", "func_door"), ...
+pub fn brush_classes(ents: &[BspEntity]) -> Vec<(String, String)> {
+    ents.iter()
+        .filter_map(|e| {
+            let model = e.get("model")?;
+            if !model.starts_with('*') { return None; }
+            Some((model.to_string(), e.get("classname")?.to_string()))
+        })
+        .collect()
+}
+
+// ============================================================
+// Geometry
+// ============================================================
+
+/// The world's bounding box. None if the models lump is missing or the box
+/// is empty or inside out.
+pub fn world_bounds(data: &[u8]) -> Option<Bounds> {
+    let world = lump(data, LUMP_MODELS)?.get(..MODEL_SIZE)?;
+    let (mins, maxs) = (read_vec3(world, 0)?, read_vec3(world, 12)?);
+    (0..3).all(|i| mins[i] < maxs[i]).then_some(Bounds { mins, maxs })
+}
+
+/// The world's walls, deduplicated. None if a geometry lump is missing or
+/// indexes outside another.
+pub fn walls(data: &[u8]) -> Option<Vec<Wall>> {
+    let planes = lump(data, LUMP_PLANES)?;
+    let verts = lump(data, LUMP_VERTEXES)?;
+    let faces = lump(data, LUMP_FACES)?;
+    let edges = lump(data, LUMP_EDGES)?;
+    let surfedges = lump(data, LUMP_SURFEDGES)?;
+    let models = lump(data, LUMP_MODELS)?;
+
+    // Model 0 is the world
+    let world = models.get(..MODEL_SIZE)?;
+    let first_face = read_i32(world, 56)?.max(0) as usize;
+    let num_faces = read_i32(world, 60)?.max(0) as usize;
+
+    let mut out: Vec<Wall> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for f in first_face..first_face + num_faces {
+        let face = faces.get(f * FACE_SIZE..(f + 1) * FACE_SIZE)?;
+        let plane = read_u16(face, 0)? as usize;
+        let normal = read_vec3(planes, plane * PLANE_SIZE)?;
+        if normal[2].abs() > WALL_MAX_NORMAL_Z { continue; }
+
+        let first_edge = read_i32(face, 4)?.max(0) as usize;
+        let num_edges = read_u16(face, 8)? as usize;
+        let mut points = Vec::with_capacity(num_edges);
+        for e in first_edge..first_edge + num_edges {
+            let se = read_i32(surfedges, e * 4)?;
+            let edge = se.unsigned_abs() as usize * EDGE_SIZE;
+            let v = if se >= 0 { read_u16(edges, edge)? } else { read_u16(edges, edge + 2)? };
+            points.push(read_vec3(verts, v as usize * VERTEX_SIZE)?);
+        }
+        let Some(wall) = flatten(&points, normal) else { continue };
+
+        let key = snap_key(&wall);
+        if seen.insert(key) { out.push(wall); }
+    }
+    Some(out)
+}
+
+/// A vertical polygon as a 2D segment: its extent along the wall's horizontal
+/// direction (perpendicular to the normal), plus its height range.
+fn flatten(points: &[[f32; 3]], normal: [f32; 3]) -> Option<Wall> {
+    let len = (normal[0] * normal[0] + normal[1] * normal[1]).sqrt();
+    if points.len() < 3 || len < 1e-4 { return None; }
+    let dir = [-normal[1] / len, normal[0] / len];
+    let along = |p: &[f32; 3]| p[0] * dir[0] + p[1] * dir[1];
+
+    let (mut lo, mut hi) = (points[0], points[0]);
+    let (mut zmin, mut zmax) = (points[0][2], points[0][2]);
+    for p in points {
+        if along(p) < along(&lo) { lo = *p; }
+        if along(p) > along(&hi) { hi = *p; }
+        zmin = zmin.min(p[2]);
+        zmax = zmax.max(p[2]);
+    }
+    if along(&hi) - along(&lo) < 1.0 { return None; } // Degenerate sliver
+    Some(Wall { a: [lo[0], lo[1]], b: [hi[0], hi[1]], z: [zmin, zmax] })
+}
+
+/// Grid-snapped, direction-independent identity of a wall.
+fn snap_key(w: &Wall) -> [i32; 6] {
+    let s = |v: f32| (v / WALL_SNAP).round() as i32;
+    let (a, b) = ([s(w.a[0]), s(w.a[1])], [s(w.b[0]), s(w.b[1])]);
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    [a[0], a[1], b[0], b[1], s(w.z[0]), s(w.z[1])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal .bsp with the given lumps (missing ones are empty).
+    fn build_bsp(lumps: &[(usize, Vec<u8>)]) -> Vec<u8> {
+        let mut data = BSP_VERSION.to_le_bytes().to_vec();
+        data.resize(4 + HEADER_LUMPS * 8, 0);
+        for (idx, bytes) in lumps {
+            let off = data.len() as i32;
+            data[4 + idx * 8..8 + idx * 8].copy_from_slice(&off.to_le_bytes());
+            data[8 + idx * 8..12 + idx * 8].copy_from_slice(&(bytes.len() as i32).to_le_bytes());
+            data.extend_from_slice(bytes);
+        }
+        data
+    }
+
+    const ENTS: &str = r#"{
+"classname" "worldspawn"
+"wad" "\half-life\cstrike.wad"
+}
+{
+"model" "*1"
+"classname" "func_door"
+"speed" "100"
+}
+{
+"classname" "info_player_start"
+"origin" "0 0 36"
+}
+{
+"model" "*2"
+"classname" "func_breakable"
+}
+"#;
+
+    #[test]
+    fn parses_entity_text() {
+        let ents = parse_entity_text(ENTS);
+        assert_eq!(ents.len(), 4);
+        assert_eq!(ents[0].get("classname"), Some("worldspawn"));
+        assert_eq!(ents[1].get("speed"), Some("100"));
+        assert_eq!(ents[2].get("model"), None);
+    }
+
+    #[test]
+    fn brush_classes_by_model() {
+        let ents = parse_entity_text(ENTS);
+        assert_eq!(brush_classes(&ents), vec![
+            ("*1".to_string(), "func_door".to_string()),
+            ("*2".to_string(), "func_breakable".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn reads_entity_lump() {
+        let mut text = ENTS.as_bytes().to_vec();
+        text.push(0);
+        let data = build_bsp(&[(LUMP_ENTITIES, text)]);
+        assert_eq!(entities(&data).unwrap().len(), 4);
+    }
+
+    /// A .bsp whose world is the given quads (4 corners each).
+    fn build_world(quads: &[([f32; 3], [[f32; 3]; 4])]) -> Vec<u8> {
+        let (mut planes, mut verts, mut faces, mut edges, mut surfedges) =
+            (Vec::new(), Vec::new(), Vec::new(), vec![0u8; EDGE_SIZE], Vec::new());
+        for (i, (normal, corners)) in quads.iter().enumerate() {
+            for c in normal { planes.extend_from_slice(&c.to_le_bytes()); }
+            planes.extend_from_slice(&[0u8; 8]);
+            for (k, corner) in corners.iter().enumerate() {
+                for c in corner { verts.extend_from_slice(&c.to_le_bytes()); }
+                let (v0, v1) = ((i * 4 + k) as u16, (i * 4 + (k + 1) % 4) as u16);
+                let edge = (edges.len() / EDGE_SIZE) as i32;
+                edges.extend_from_slice(&v0.to_le_bytes());
+                edges.extend_from_slice(&v1.to_le_bytes());
+                surfedges.extend_from_slice(&edge.to_le_bytes());
+            }
+            faces.extend_from_slice(&(i as u16).to_le_bytes());
+            faces.extend_from_slice(&0i16.to_le_bytes());
+            faces.extend_from_slice(&((i * 4) as i32).to_le_bytes());
+            faces.extend_from_slice(&4i16.to_le_bytes());
+            faces.extend_from_slice(&[0u8; 10]);
+        }
+        let mut model = vec![0u8; 56];  // mins, maxs, origin, headnode, visleafs
+        model.extend_from_slice(&0i32.to_le_bytes());
+        model.extend_from_slice(&(quads.len() as i32).to_le_bytes());
+        build_bsp(&[
+            (LUMP_PLANES, planes), (LUMP_VERTEXES, verts), (LUMP_FACES, faces),
+            (LUMP_EDGES, edges), (LUMP_SURFEDGES, surfedges), (LUMP_MODELS, model),
+        ])
+    }
+
+    #[test]
+    fn walls_from_vertical_faces() {
+        let wall = ([0.0, -1.0, 0.0], [[0.0, 0.0, 0.0], [128.0, 0.0, 0.0], [128.0, 0.0, 96.0], [0.0, 0.0, 96.0]]);
+        let back = ([0.0, 1.0, 0.0], [[128.0, 1.0, 0.0], [0.0, 1.0, 0.0], [0.0, 1.0, 96.0], [128.0, 1.0, 96.0]]);
+        let floor = ([0.0, 0.0, 1.0], [[0.0, 0.0, 0.0], [64.0, 0.0, 0.0], [64.0, 64.0, 0.0], [0.0, 64.0, 0.0]]);
+        let walls = walls(&build_world(&[wall, back, floor])).unwrap();
+        assert_eq!(walls.len(), 1); // Floor dropped, back side merged
+        let w = walls[0];
+        let xs = [w.a[0].min(w.b[0]), w.a[0].max(w.b[0])];
+        assert_eq!((xs, w.z), ([0.0, 128.0], [0.0, 96.0]));
+    }
+
+    #[test]
+    fn reads_world_bounds() {
+        let mut model = Vec::new();
+        for c in [-2048.0f32, -1024.0, -256.0, 2048.0, 3072.0, 512.0] { model.extend_from_slice(&c.to_le_bytes()); }
+        model.resize(MODEL_SIZE, 0);
+        let b = world_bounds(&build_bsp(&[(LUMP_MODELS, model.clone())])).unwrap();
+        assert_eq!((b.mins, b.maxs), ([-2048.0, -1024.0, -256.0], [2048.0, 3072.0, 512.0]));
+        #[cfg(feature = "radar")]
+        assert_eq!(b.extent(), 4096.0);
+        assert!(b.contains([0.0, 3100.0, 0.0], 64.0));
+        assert!(!b.contains([0.0, 3200.0, 0.0], 64.0));
+        model[12..16].copy_from_slice(&(-4096.0f32).to_le_bytes()); // maxs.x below mins.x
+        assert!(world_bounds(&build_bsp(&[(LUMP_MODELS, model)])).is_none());
+        assert!(world_bounds(&build_bsp(&[])).is_none());
+    }
+
+    #[test]
+    fn walls_need_geometry() {
+        assert!(walls(&build_bsp(&[(LUMP_ENTITIES, b"{}".to_vec())])).is_none()); // No world model
+        let mut data = build_world(&[([1.0, 0.0, 0.0], [[0.0; 3]; 4])]);
+        let faces = 4 + LUMP_FACES * 8;
+        let off = i32::from_le_bytes(data[faces..faces + 4].try_into().unwrap()) as usize;
+        data[off..off + 2].copy_from_slice(&500u16.to_le_bytes()); // Plane index out of range
+        assert!(walls(&data).is_none());
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert!(entities(&[]).is_none());
+        let mut data = build_bsp(&[(LUMP_ENTITIES, b"{}".to_vec())]);
+        data[0] = 29; // Quake / wrong version
+        assert!(entities(&data).is_none());
+        let mut data = build_bsp(&[(LUMP_ENTITIES, b"{}".to_vec())]);
+        data[8..12].copy_from_slice(&1000i32.to_le_bytes()); // Lump runs past the end
+        assert!(entities(&data).is_none());
+        assert!(parse_entity_text("{ \"a\" \"b\"").is_empty()); // Unterminated
+    }
+}
@@ -0,0 +1,128 @@
+// budget.rs — Frame-time budget for the overlay, and what to drop when it's
+// exceeded.
+//
+// esp.rs times each overlay frame (CPU time spent in the frame handler; GL
+// calls are queued, not waited on). With `[performance] budget_ms` set, a
+// running average above the budget sheds the most expensive optional elements
+// one step at a time, in this order:
+//
+//   1 Trails        many short lines per player
+//   2 Labels        text is the costliest thing drawn, plus the layout pass
+//   3 Snaplines
+//   4 CachedBoxes   boxes of players not seen this frame
+//
+// A step is added after the average stays over budget for ESCALATE_MS, and
+// one is given back after it stays well under (RECOVER_FRACTION) for
+// RECOVER_MS, so the overlay doesn't flicker between levels. Both are time
+// (the frame delta from time.rs), not frame counts, so they mean the same at
+// 60 and at 250 fps.
+
+const ESCALATE_MS: f32 = 500.0;    // Time over budget before shedding another element
+const RECOVER_MS: f32 = 10_000.0;  // Time well under budget before restoring one
+const RECOVER_FRACTION: f32 = 0.6; // "Well under" = below this part of the budget
+const AVG_WEIGHT: f32 = 0.1;       // Weight of the newest frame in the running average
+
+/// Elements dropped under load, in shedding order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shed { Trails = 1, Labels = 2, Snaplines = 3, CachedBoxes = 4 }
+
+const ALL: [Shed; 4] = [Shed::Trails, Shed::Labels, Shed::Snaplines, Shed::CachedBoxes];
+
+impl Shed {
+    pub fn name(self) -> &'static str {
+        match self {
+            Shed::Trails      => "trails",
+            Shed::Labels      => "labels",
+            Shed::Snaplines   => "snaplines",
+            Shed::CachedBoxes => "cached boxes",
+        }
+    }
+}
+
+/// Whether `what` is dropped at degradation `level` (0 = nothing dropped).
+pub fn sheds(level: u8, what: Shed) -> bool {
+    level >= what as u8
+}
+
+/// Names of the elements dropped at `level`, for the indicator.
+pub fn shed_names(level: u8) -> Vec<&'static str> {
+    ALL.iter().filter(|&&s| sheds(level, s)).map(|s| s.name()).collect()
+}
+
+/// Tracks the overlay's frame time against the budget.
+#[derive(Default)]
+pub struct FrameBudget {
+    avg_us: f32, // Running average of the overlay time (microseconds)
+    level:  u8,  // Elements shed (0..=4)
+    over:   f32, // Time (ms) continuously over budget
+    calm:   f32, // Time (ms) continuously well under budget
+}
+
+impl FrameBudget {
+    pub const fn new() -> Self { Self { avg_us: 0.0, level: 0, over: 0.0, calm: 0.0 } }
+
+    /// Record one frame's overlay time, `dt_ms` after the previous frame.
+    /// `budget_us` <= 0 turns the guard off. Returns the degradation level
+    /// for the next frame.
+    pub fn record(&mut self, frame_us: f32, budget_us: f32, dt_ms: f32) -> u8 {
+        self.avg_us += (frame_us - self.avg_us) * AVG_WEIGHT;
+        if budget_us <= 0.0 {
+            (self.level, self.over, self.calm) = (0, 0.0, 0.0);
+            return 0;
+        }
+
+        if self.avg_us > budget_us {
+            self.calm = 0.0;
+            self.over += dt_ms;
+            if self.over >= ESCALATE_MS && (self.level as usize) < ALL.len() {
+                self.level += 1;
+                self.over = 0.0;
+            }
+        } else if self.avg_us < budget_us * RECOVER_FRACTION {
+            self.over = 0.0;
+            self.calm += dt_ms;
+            if self.calm >= RECOVER_MS && self.level > 0 {
+                self.level -= 1;
+                self.calm = 0.0;
+            }
+        } else {
+            (self.over, self.calm) = (0.0, 0.0);
+        }
+        self.level
+    }
+
+    pub fn level(&self) -> u8 { self.level }
+
+    pub fn avg_us(&self) -> f32 { self.avg_us }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheds_in_order_and_recovers() {
+        let mut b = FrameBudget::new();
+        for _ in 0..200 { b.record(100.0, 500.0, 10.0); }
+        assert_eq!(b.level(), 0);
+
+        // Sustained overload: one more element every ESCALATE_MS
+        let mut levels = Vec::new();
+        for _ in 0..400 { levels.push(b.record(2000.0, 500.0, 10.0)); }
+        assert!(levels[..(ESCALATE_MS / 10.0) as usize].contains(&0));
+        assert_eq!(b.level(), 4);
+        assert_eq!(shed_names(2), ["trails", "labels"]);
+        assert!(sheds(3, Shed::Snaplines) && !sheds(3, Shed::CachedBoxes));
+
+        // Back under: one element at a time, slowly
+        for _ in 0..(RECOVER_MS / 10.0) as usize + 50 { b.record(50.0, 500.0, 10.0); }
+        assert_eq!(b.level(), 3);
+
+        // Same time at a higher frame rate: the same one step
+        for _ in 0..(RECOVER_MS / 4.0) as usize + 50 { b.record(50.0, 500.0, 4.0); }
+        assert_eq!(b.level(), 2);
+
+        // Guard off: nothing shed
+        assert_eq!(b.record(5000.0, 0.0, 10.0), 0);
+    }
+}
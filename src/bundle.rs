@@ -0,0 +1,134 @@
+// bundle.rs — Support bundles: a bug report's files in one zip.
+//
+// Most reports need the same handful of files, and they tend to arrive one at
+// a time over several replies. `[bundle] key`, the `esp_bundle` console
+// command (`[bundle] command`) or `bundle` on the control pipe writes them
+// all into `esp_bundle_<unix time>.zip` next to the DLL (zip.rs):
+//
+//   esp_debug.log      the log so far
+//   environment.txt    the startup environment report (its `env:` lines)
+//   entities.txt       every client entity as the overlay reads it, taken
+//                      at the frame the bundle was asked for
+//   esp_config.toml    the config file as it is on disk, and the profile in use
+//   esp_offsets.toml   the structure offsets in use (overrides and tuner
+//                      nudges included), in the override file's format
+//
+// A request only sets a flag, like the self-test: the entity dump calls
+// engine functions, so it's taken on the render thread at the next overlay
+// frame. The files are read and the archive written on a background thread.
+
+use crate::config;
+use crate::entities::{self, EngineApi};
+use crate::input;
+use crate::offsets;
+use crate::world::RawEntity;
+use crate::zip::Zip;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Key state last frame (GetAsyncKeyState fallback).
+static KEY_PREV: AtomicBool = AtomicBool::new(false);
+
+/// A bundle has been asked for.
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Bundles finished (written or failed); the control pipe waits for this to move.
+static RUNS: AtomicU32 = AtomicU32::new(0);
+
+/// Where the last bundle went, or why it couldn't be written.
+static LAST: Mutex<Option<Result<String, String>>> = Mutex::new(None);
+
+/// Ask for a bundle at the next overlay frame.
+pub fn request() {
+    PENDING.store(true, Ordering::Release);
+}
+
+/// Number of bundles finished so far.
+#[cfg(feature = "ipc")]
+pub fn runs() -> u32 {
+    RUNS.load(Ordering::Acquire)
+}
+
+/// The last bundle's path, or why it failed.
+#[cfg(feature = "ipc")]
+pub fn last() -> Option<Result<String, String>> {
+    LAST.lock().ok()?.clone()
+}
+
+/// Check the key, and collect a bundle if one was asked for. Called once per
+/// overlay frame.
+pub unsafe fn frame() {
+    let cfg = config::get();
+    if input::key_pressed(cfg.bundle_key, &KEY_PREV) { request(); }
+    if !PENDING.swap(false, Ordering::AcqRel) { return; }
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let log = entities::recent_log(usize::MAX);
+    let dump = match EngineApi::resolve() {
+        Some(api) => entity_dump(&api.iter_entities().collect::<Vec<_>>(), api.level_name().as_deref()),
+        None => "engine API not resolved (no map loaded?)\n".to_string(),
+    };
+    let offsets = offsets::format(&offsets::current(), "in use when the bundle was taken");
+    let profile = cfg.profile.clone();
+
+    let path = entities::dll_dir().join(format!("esp_bundle_{}.zip", stamp));
+    std::thread::spawn(move || {
+        let config = match std::fs::read_to_string(config::file_path()) {
+            Ok(text) => format!("# profile in use: {}\n{}", if profile.is_empty() { "none" } else { &profile }, text),
+            Err(e) => format!("# {}: {}\n", config::file_path().display(), e),
+        };
+        let env: Vec<&str> = log.iter().map(String::as_str).filter(|l| l.starts_with("env: ")).collect();
+        let env = if env.is_empty() { "no environment report in the log yet\n".to_string() } else { env.join("\n") + "\n" };
+
+        let mut zip = Zip::new(stamp);
+        zip.add("esp_debug.log", (log.join("\n") + "\n").as_bytes());
+        zip.add("environment.txt", env.as_bytes());
+        zip.add("entities.txt", dump.as_bytes());
+        zip.add("esp_config.toml", config.as_bytes());
+        zip.add("esp_offsets.toml", offsets.as_bytes());
+
+        let result = match std::fs::write(&path, zip.finish()) {
+            Ok(()) => {
+                entities::logf(format!("bundle: wrote {}", path.display()));
+                Ok(path.display().to_string())
+            }
+            Err(e) => {
+                entities::logf(format!("bundle: {}: {}", path.display(), e));
+                Err(format!("{}: {}", path.display(), e))
+            }
+        };
+        if let Ok(mut g) = LAST.lock() { *g = Some(result); }
+        RUNS.fetch_add(1, Ordering::AcqRel);
+    });
+}
+
+/// One line per entity, players marked.
+fn entity_dump(ents: &[RawEntity], map: Option<&str>) -> String {
+    let mut out = format!("# map {}, {} entities\n", map.unwrap_or("unknown"), ents.len());
+    out.push_str("# index player model origin mins maxs solid movetype effects current message\n");
+    let v = |v: crate::math::Vec3| format!("{:.1},{:.1},{:.1}", v.x, v.y, v.z);
+    for e in ents {
+        out.push_str(&format!("{} {} {} {} {} {} {} {} {:#x} {} {}\n",
+            e.index, e.is_player as u8, e.model_index, v(e.origin), v(e.mins), v(e.maxs),
+            e.solid, e.movetype, e.effects, e.current as u8, e.message));
+    }
+    out
+}
+
+/// Register `esp_bundle` once the engine table is known, if enabled. Opt-in
+/// like `esp_profile`: the engine can't unregister it, so the DLL then
+/// refuses to eject.
+pub unsafe fn install_command() {
+    static ATTEMPTED: AtomicBool = AtomicBool::new(false);
+    if !config::get().bundle_command || ATTEMPTED.swap(true, Ordering::Relaxed) { return; }
+    if entities::add_command(b"esp_bundle\0", cmd_bundle) {
+        entities::log("console command esp_bundle registered");
+    } else {
+        entities::log("console command esp_bundle: AddCommand not available");
+    }
+}
+
+unsafe extern "C" fn cmd_bundle() {
+    request();
+}
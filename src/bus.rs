@@ -0,0 +1,131 @@
+// bus.rs — Frame events: what happened since the last overlay frame.
+//
+// Things that happen somewhere else — a map loading (entities.rs), a round
+// starting or ending, a player dying and a chat or server message arriving
+// (usermsg.rs), the config file being
+// reloaded (config.rs), one of our hooks being overwritten (selftest.rs) —
+// are published here from whatever thread sees them. The overlay frame drains
+// the queue once and hands each event to the handlers subscribed to its
+// topic (esp.rs), in the order they were published, so a subsystem that
+// cares about a map change doesn't keep its own copy of the map name to
+// compare against every frame.
+
+use crate::chat;
+use crate::stats::DeathMsg;
+use std::sync::Mutex;
+
+const MAX_QUEUE: usize = 64; // Events kept until the next frame drains them
+
+/// Something that happened since the last overlay frame.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The level changed: the map that ended and the one now loaded (None
+    /// = no map, e.g. a disconnect).
+    MapLoaded { ended: Option<String>, map: Option<String> },
+    RoundStart,             // RoundTime
+    RoundEnd,               // TeamScore
+    PlayerDied(DeathMsg),
+    Message(chat::Line),    // SayText, TextMsg, or a console print
+    ConfigReloaded,
+    /// A hook of ours was overwritten by something else (its name).
+    HookLost(&'static str),
+}
+
+/// What a handler subscribes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topic { Map, Round, Death, Chat, Config, Hooks }
+
+impl Event {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Event::MapLoaded { .. }              => Topic::Map,
+            Event::RoundStart | Event::RoundEnd  => Topic::Round,
+            Event::PlayerDied(_)                 => Topic::Death,
+            Event::Message(_)                    => Topic::Chat,
+            Event::ConfigReloaded                => Topic::Config,
+            Event::HookLost(_)                   => Topic::Hooks,
+        }
+    }
+}
+
+/// Events waiting for the next frame, oldest first. Past MAX_QUEUE new ones
+/// are dropped (and counted) rather than the queue growing while no frame
+/// drains it.
+pub struct Queue {
+    events:  Vec<Event>,
+    dropped: u32,
+}
+
+impl Queue {
+    pub const fn new() -> Self {
+        Self { events: Vec::new(), dropped: 0 }
+    }
+
+    pub fn push(&mut self, e: Event) {
+        if self.events.len() < MAX_QUEUE {
+            self.events.push(e);
+        } else {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+    }
+
+    pub fn take(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Events dropped since load because the queue was full.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue::new());
+
+/// Queue `e` for the next overlay frame (any thread).
+pub fn publish(e: Event) {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner()).push(e);
+}
+
+/// The events published since the last call, oldest first.
+pub fn drain() -> Vec<Event> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Events dropped since load (diagnostics).
+pub fn dropped() -> u32 {
+    QUEUE.lock().map(|q| q.dropped()).unwrap_or(0)
+}
+
+/// The handlers in `table` subscribed to `e`'s topic, in table order.
+pub fn subscribers<'a, H: Copy>(table: &'a [(Topic, H)], e: &Event) -> impl Iterator<Item = H> + 'a {
+    let topic = e.topic();
+    table.iter().filter(move |(t, _)| *t == topic).map(|&(_, h)| h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_keeps_order_and_drops_past_its_size() {
+        let mut q = Queue::new();
+        q.push(Event::RoundStart);
+        q.push(Event::HookLost("wglSwapBuffers"));
+        assert_eq!(q.take(), [Event::RoundStart, Event::HookLost("wglSwapBuffers")]);
+        assert!(q.take().is_empty());
+
+        for _ in 0..MAX_QUEUE + 3 { q.push(Event::RoundEnd); }
+        assert_eq!(q.take().len(), MAX_QUEUE);
+        assert_eq!(q.dropped(), 3);
+    }
+
+    #[test]
+    fn handlers_by_topic_in_table_order() {
+        let table = [(Topic::Round, 'a'), (Topic::Map, 'b'), (Topic::Round, 'c')];
+        let got: Vec<char> = subscribers(&table, &Event::RoundEnd).collect();
+        assert_eq!(got, ['a', 'c']);
+        let map = Event::MapLoaded { ended: None, map: Some("maps/de_dust2.bsp".into()) };
+        assert_eq!(subscribers(&table, &map).collect::<Vec<_>>(), ['b']);
+        assert_eq!(subscribers(&table, &Event::ConfigReloaded).count(), 0);
+    }
+}
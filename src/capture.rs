@@ -0,0 +1,76 @@
+// capture.rs — Overlay screenshots for bug reports (F11 by default).
+//
+// The engine's own `snapshot` is exactly what the overlay hides from (see
+// "Screenshot Detection" in esp.rs), so it can't show rendering artifacts. This
+// key grabs the framebuffer right after the overlay has drawn, and — with
+// `[screenshot] before` on — also right before, so the two files can be
+// compared to see what the overlay changed.
+//
+// The reads go to the real glReadPixels (hook::read_pixels_original), so they
+// don't count as a capture and hide the overlay. Files are written as
+// `esp_shot_<unix time>[_before|_after].tga` next to the DLL, on a background
+// thread so the frame doesn't stall on the disk.
+
+use crate::config;
+use crate::entities;
+use crate::input;
+use crate::render;
+use crate::tga;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Key state last frame (GetAsyncKeyState fallback).
+static KEY_PREV: AtomicBool = AtomicBool::new(false);
+
+/// The pre-overlay frame of a capture in progress.
+static BEFORE: Mutex<Option<(u16, u16, Vec<u8>)>> = Mutex::new(None);
+
+/// A capture was requested this frame.
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Check the key and, for a capture with `before` on, grab the frame as the
+/// game drew it. Called before the overlay draws.
+pub unsafe fn before_overlay() {
+    let cfg = config::get();
+    if !input::key_pressed(cfg.screenshot_key, &KEY_PREV) { return; }
+    PENDING.store(true, Ordering::Relaxed);
+    if cfg.screenshot_before {
+        if let Ok(mut b) = BEFORE.lock() { *b = render::read_viewport(); }
+    }
+}
+
+/// Finish a requested capture: grab the frame with the overlay and write the
+/// files. Called after the overlay draws.
+pub unsafe fn after_overlay() {
+    if !PENDING.swap(false, Ordering::Relaxed) { return; }
+    let before = BEFORE.lock().ok().and_then(|mut b| b.take());
+    let after = render::read_viewport();
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut files = Vec::new();
+    match before {
+        Some(frame) => {
+            files.push((format!("esp_shot_{}_before.tga", stamp), frame));
+            if let Some(frame) = after { files.push((format!("esp_shot_{}_after.tga", stamp), frame)); }
+        }
+        None => {
+            if let Some(frame) = after { files.push((format!("esp_shot_{}.tga", stamp), frame)); }
+        }
+    }
+    if files.is_empty() {
+        entities::logf("screenshot: no viewport to read".to_string());
+        return;
+    }
+
+    let dir = entities::dll_dir();
+    std::thread::spawn(move || {
+        for (name, (w, h, bgr)) in files {
+            let path = dir.join(&name);
+            match std::fs::write(&path, tga::encode_bgr(w, h, &bgr)) {
+                Ok(()) => entities::logf(format!("screenshot: wrote {}", path.display())),
+                Err(e) => entities::logf(format!("screenshot: {}: {}", path.display(), e)),
+            }
+        }
+    });
+}
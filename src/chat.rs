@@ -0,0 +1,202 @@
+// chat.rs — Chat and server messages, kept longer than the engine shows them.
+//
+// usermsg.rs decodes two user messages and, with `chat.console` on, what
+// client.dll prints to the console through pfnConsolePrint:
+//
+//   SayText   BYTE sender, STRING text                       (Half-Life: "\x02name: text\n")
+//             BYTE sender, STRING format, STRING name, STRING text
+//                                                           (Counter-Strike: "#Cstrike_Chat_All", "", ...)
+//   TextMsg   BYTE destination, STRING message, up to 4 STRING arguments for its %s1..%s4
+//
+// Counter-Strike leaves the name empty and has the client put the sender's
+// name in, so such a line keeps the sender's slot and the name is looked up
+// when it's drawn. Localization keys ("#Game_will_restart_in") can't be
+// translated without the game's resource files; they're shown as the key
+// with its arguments after it.
+//
+// The lines become frame events (bus.rs); esp.rs writes them to the log and
+// the session file and keeps a History for the `[chat]` panel.
+
+use crate::names;
+use std::collections::VecDeque;
+
+const MAX_HISTORY: usize = 64; // Lines kept for the panel; older ones are dropped first
+const MAX_ARGS: usize = 4;     // TextMsg %s1..%s4
+
+/// Counter-Strike's SayText formats and what goes before the name.
+const CHAT_FORMATS: [(&str, &str); 8] = [
+    ("#Cstrike_Chat_All",     ""),
+    ("#Cstrike_Chat_AllDead", "*DEAD* "),
+    ("#Cstrike_Chat_AllSpec", "*SPEC* "),
+    ("#Cstrike_Chat_CT",      "(Counter-Terrorist) "),
+    ("#Cstrike_Chat_CT_Dead", "*DEAD*(Counter-Terrorist) "),
+    ("#Cstrike_Chat_T",       "(Terrorist) "),
+    ("#Cstrike_Chat_T_Dead",  "*DEAD*(Terrorist) "),
+    ("#Cstrike_Chat_Spec",    "(Spectator) "),
+];
+
+/// Where a line came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Chat,    // SayText
+    Server,  // TextMsg (notify, console, center, radio, ...)
+    Console, // pfnConsolePrint
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Chat    => "chat",
+            Kind::Server  => "server",
+            Kind::Console => "console",
+        }
+    }
+}
+
+/// One captured line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub kind:   Kind,
+    pub sender: i32,          // Slot whose name goes before the text (0 = the text has it, or none)
+    pub tag:    &'static str, // "*DEAD* ", "(Terrorist) ", ...
+    pub text:   String,
+}
+
+impl Line {
+    fn new(kind: Kind, text: String) -> Self {
+        Self { kind, sender: 0, tag: "", text }
+    }
+
+    /// The line as shown, `name_of` giving a player slot's name.
+    pub fn render(&self, name_of: impl Fn(i32) -> Option<String>) -> String {
+        match self.sender {
+            0 => format!("{}{}", self.tag, self.text),
+            s => {
+                let name = name_of(s).unwrap_or_else(|| format!("P{}", s));
+                format!("{}{}: {}", self.tag, name, self.text)
+            }
+        }
+    }
+}
+
+/// Decode a SayText payload.
+pub fn parse_say_text(buf: &[u8]) -> Option<Line> {
+    let [sender, rest @ ..] = buf else { return None };
+    let args = c_strings(rest);
+    let first = args.first()?;
+    if args.len() < 3 || !first.starts_with(b"#") {
+        return Some(Line::new(Kind::Chat, decode(first)?));
+    }
+    let format = String::from_utf8_lossy(first);
+    let tag = CHAT_FORMATS.iter().find(|(f, _)| *f == format).map_or("", |&(_, t)| t);
+    let text = decode(args[2])?;
+    Some(match decode(args[1]) {
+        Some(name) => Line { kind: Kind::Chat, sender: 0, tag, text: format!("{}: {}", name, text) },
+        None => Line { kind: Kind::Chat, sender: *sender as i32, tag, text },
+    })
+}
+
+/// Decode a TextMsg payload: %s1..%s4 in the message are replaced by the
+/// arguments; a localization key is followed by them instead.
+pub fn parse_text_msg(buf: &[u8]) -> Option<Line> {
+    let [_dest, rest @ ..] = buf else { return None };
+    let args = c_strings(rest);
+    let (msg, args) = args.split_first()?;
+    let args: Vec<String> = args.iter().take(MAX_ARGS)
+        .map(|a| decode(a).unwrap_or_default().trim_start_matches('#').to_string())
+        .collect();
+    let msg = decode(msg)?;
+    let text = match msg.strip_prefix('#') {
+        Some(key) => std::iter::once(key.to_string()).chain(args).collect::<Vec<_>>().join(" "),
+        None => args.iter().enumerate()
+            .fold(msg, |m, (i, a)| m.replace(&format!("%s{}", i + 1), a)),
+    };
+    Some(Line::new(Kind::Server, text))
+}
+
+/// A console print, or None if there's nothing printable in it.
+pub fn console_line(raw: &[u8]) -> Option<Line> {
+    let raw = raw.split(|&b| b == 0).next().unwrap_or(raw);
+    Some(Line::new(Kind::Console, decode(raw)?))
+}
+
+/// The NUL-terminated strings in `buf`, in order.
+fn c_strings(buf: &[u8]) -> Vec<&[u8]> {
+    let mut out: Vec<&[u8]> = buf.split(|&b| b == 0).collect();
+    if buf.last() == Some(&0) || buf.is_empty() { out.pop(); }
+    out
+}
+
+/// Text without color codes, newlines and surrounding spaces (None if empty).
+fn decode(raw: &[u8]) -> Option<String> {
+    names::decode(raw).map(|(text, _)| text.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// The latest lines and when they arrived (time::now_ms).
+pub struct History {
+    lines: VecDeque<(u32, Line)>,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self { lines: VecDeque::new() }
+    }
+
+    pub fn push(&mut self, now: u32, line: Line) {
+        if self.lines.len() >= MAX_HISTORY { self.lines.pop_front(); }
+        self.lines.push_back((now, line));
+    }
+
+    /// Up to `max` lines at most `hold_ms` old, oldest first, with their age.
+    pub fn recent(&self, now: u32, hold_ms: u32, max: usize) -> Vec<(&Line, u32)> {
+        let fresh: Vec<(&Line, u32)> = self.lines.iter()
+            .map(|(t, l)| (l, now.wrapping_sub(*t)))
+            .filter(|&(_, age)| age <= hold_ms)
+            .collect();
+        fresh[fresh.len().saturating_sub(max)..].to_vec()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_say_text_and_text_msg() {
+        let hl = parse_say_text(b"\x03\x02bob: hi there\n\0").unwrap();
+        assert_eq!((hl.kind, hl.sender, hl.text.as_str()), (Kind::Chat, 0, "bob: hi there"));
+
+        let cs = parse_say_text(b"\x05#Cstrike_Chat_T_Dead\0\0\x01gg\n\0").unwrap();
+        assert_eq!((cs.sender, cs.tag), (5, "*DEAD*(Terrorist) "));
+        assert_eq!(cs.render(|s| (s == 5).then(|| "ann".to_string())), "*DEAD*(Terrorist) ann: gg");
+        assert_eq!(cs.render(|_| None), "*DEAD*(Terrorist) P5: gg");
+        let named = parse_say_text(b"\x05#Custom_Say\0eve\0hello\0").unwrap();
+        assert_eq!(named.render(|_| None), "eve: hello");
+
+        let msg = parse_text_msg(b"\x03%s1 bought %s2\0bob\0#AWP\0").unwrap();
+        assert_eq!((msg.kind, msg.text.as_str()), (Kind::Server, "bob bought AWP"));
+        let key = parse_text_msg(b"\x04#Game_will_restart_in\x003\0#SECONDS\0").unwrap();
+        assert_eq!(key.text, "Game_will_restart_in 3 SECONDS");
+        assert_eq!(parse_text_msg(b"\x02\n\0"), None);
+        assert_eq!(console_line(b"] status\n\0junk").unwrap().text, "] status");
+    }
+
+    #[test]
+    fn history_keeps_the_latest_fresh_lines() {
+        let mut h = History::new();
+        for (t, s) in [(100, "a"), (5_000, "b"), (6_000, "c"), (7_000, "d")] {
+            h.push(t, Line::new(Kind::Console, s.to_string()));
+        }
+        let got: Vec<(&str, u32)> = h.recent(8_000, 5_000, 2).iter().map(|(l, a)| (l.text.as_str(), *a)).collect();
+        assert_eq!(got, [("c", 2_000), ("d", 1_000)]);
+        assert_eq!(h.recent(8_000, 5_000, 10).len(), 3);   // "a" is too old
+        for _ in 0..MAX_HISTORY { h.push(9_000, Line::new(Kind::Chat, "x".into())); }
+        assert_eq!(h.recent(9_000, 0, 1_000).len(), MAX_HISTORY);
+        h.clear();
+        assert!(h.recent(9_000, 5_000, 10).is_empty());
+    }
+}
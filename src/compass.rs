@@ -0,0 +1,114 @@
+// compass.rs — Enemy bearings on a horizontal compass strip.
+//
+// The `[compass]` strip, centered at the top of the screen, covers `span`
+// degrees around where the viewer looks: a tick every 15 degrees, the
+// headings named every 45 (N is the map's +y, E its +x), and a mark per
+// enemy at their bearing (alerts::relative_yaw), taller the closer they are.
+// An enemy outside the span is pinned to the nearer edge, dimmed, so the
+// strip still says which way to turn. Directions only, no positions: a
+// lighter alternative to the minimap.
+
+use crate::draw::{Cmd, DrawList, LineStyle};
+
+const TICK_EVERY: i32 = 15;      // Degrees between ticks
+const LABEL_EVERY: i32 = 45;     // ... between heading names
+const MARK_W: f32 = 3.0;         // Enemy mark width (px)
+const MARK_MIN: f32 = 0.3;       // Shortest mark (farthest enemy), fraction of the strip height
+const EDGE_ALPHA: f32 = 0.5;     // Alpha of a mark pinned to an edge
+
+const NAMES: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// An enemy for the strip: relative_yaw (positive = left) and distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bearing {
+    pub yaw:  f32,
+    pub dist: f32,
+}
+
+/// Colors of the strip.
+pub struct CompassColors {
+    pub scale: [f32; 4], // Line, ticks, heading names
+    pub enemy: [f32; 4],
+}
+
+/// Compass heading (0 = N, 90 = E) of a view yaw (0 = +x, 90 = +y).
+pub fn heading(yaw: f32) -> f32 {
+    (90.0 - yaw).rem_euclid(360.0)
+}
+
+/// The strip in `area` for a viewer facing `yaw`, showing `span` degrees;
+/// marks shorten with distance out to `range` units.
+pub fn strip(yaw: f32, span: f32, range: f32, enemies: &[Bearing], area: [f32; 4], colors: &CompassColors) -> DrawList {
+    let mut list = DrawList::new();
+    let [x0, y0, x1, y1] = area;
+    let half = span.clamp(10.0, 360.0) * 0.5;
+    let cx = (x0 + x1) * 0.5;
+    let x_of = |rel: f32| cx - rel / half * (cx - x0); // Left is positive
+    let style = LineStyle { width: 1.0, smooth: false };
+    list.line([x0, y1], [x1, y1], colors.scale, style);
+    list.line([cx, y0], [cx, y1], colors.scale, style);
+
+    // Ticks at whole headings, from the left edge rightwards
+    let facing = heading(yaw);
+    let first = ((facing - half) / TICK_EVERY as f32).ceil() as i32 * TICK_EVERY;
+    let mut h = first;
+    while h as f32 <= facing + half {
+        let x = x_of(facing - h as f32);
+        let named = h.rem_euclid(LABEL_EVERY) == 0;
+        let top = if named { y0 + (y1 - y0) * 0.5 } else { y1 - (y1 - y0) * 0.25 };
+        list.line([x, top], [x, y1], colors.scale, style);
+        if named {
+            list.text([x - 3.0, y0], NAMES[(h.rem_euclid(360) / LABEL_EVERY) as usize], colors.scale, 1.0);
+        }
+        h += TICK_EVERY;
+    }
+
+    for e in enemies {
+        let near = if range > 0.0 { 1.0 - (e.dist / range).clamp(0.0, 1.0) } else { 1.0 };
+        let height = (y1 - y0) * (MARK_MIN + (1.0 - MARK_MIN) * near);
+        let mut color = colors.enemy;
+        if e.yaw.abs() > half { color[3] *= EDGE_ALPHA; }
+        let x = x_of(e.yaw.clamp(-half, half)).clamp(x0 + MARK_W * 0.5, x1 - MARK_W * 0.5);
+        list.push(Cmd::Fill { rect: [x - MARK_W * 0.5, y1 - height, x + MARK_W * 0.5, y1], color });
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: CompassColors = CompassColors { scale: [1.0; 4], enemy: [1.0, 0.0, 0.0, 1.0] };
+
+    #[test]
+    fn headings_from_view_yaw() {
+        assert_eq!(heading(90.0), 0.0);   // Facing +y: north
+        assert_eq!(heading(0.0), 90.0);
+        assert_eq!(heading(-90.0), 180.0);
+        assert_eq!(heading(180.0), 270.0);
+    }
+
+    #[test]
+    fn ticks_and_names_around_the_view() {
+        // Facing north, 90 degrees across 0..180 px
+        let list = strip(90.0, 90.0, 0.0, &[], [0.0, 0.0, 180.0, 20.0], &COLORS);
+        let names: Vec<(f32, &str)> = list.cmds().iter().filter_map(|c| match c {
+            Cmd::Text { at, text, .. } => Some((at[0] + 3.0, text.as_str())),
+            _ => None,
+        }).collect();
+        assert_eq!(names, [(0.0, "NW"), (90.0, "N"), (180.0, "NE")]);
+        let lines = list.cmds().iter().filter(|c| matches!(c, Cmd::Line { .. })).count();
+        assert_eq!(lines, 2 + 7); // Base and center, ticks -45..=45
+    }
+
+    #[test]
+    fn enemies_at_their_bearing_and_pinned_outside() {
+        let enemies = [Bearing { yaw: 22.5, dist: 0.0 }, Bearing { yaw: -120.0, dist: 500.0 }];
+        let list = strip(0.0, 90.0, 1000.0, &enemies, [0.0, 0.0, 180.0, 20.0], &COLORS);
+        let marks: Vec<&Cmd> = list.cmds().iter().filter(|c| matches!(c, Cmd::Fill { .. })).collect();
+        // 22.5 degrees left of center, full height
+        assert_eq!(*marks[0], Cmd::Fill { rect: [43.5, 0.0, 46.5, 20.0], color: COLORS.enemy });
+        // Far behind on the right: at the right edge, half alpha, shorter
+        assert_eq!(*marks[1], Cmd::Fill { rect: [177.0, 7.0, 180.0, 20.0], color: [1.0, 0.0, 0.0, 0.5] });
+    }
+}
@@ -0,0 +1,1218 @@
+// config.rs — User settings loaded from `esp_config.toml` next to the DLL.
+//
+// The file is a small TOML subset (toml.rs): `[section]` headers, `key = value`
+// lines with booleans, numbers, quoted strings and single-line arrays (e.g.
+// `[1.0, 0.2, 0.2, 1.0]` for RGBA colors), plus `#` comments. Missing keys keep
+// their defaults; unknown keys and bad values are logged and skipped, so a typo
+// never disables the overlay.
+//
+// Profiles: `[profile.<name>]` and `[profile.<name>.<section>]` tables hold
+// overrides applied on top of the settings above them, so any setting can
+// differ per profile. `[profiles] default` picks the one used at startup; the
+// hotkey and the `esp_profile` console command switch between them.
+//
+// The worker thread polls the file's modification time and reloads it live.
+// Everything else reads settings through `config::get()`, which hands out a cheap
+// shared snapshot that stays consistent for the whole frame.
+
+use crate::bus::{self, Event};
+use crate::entities;
+use crate::layout::{Anchor, Placement};
+use crate::player;
+use crate::draw::{BracketStyle, Dash, LineStyle, SnapTarget};
+use crate::time;
+use crate::toml::{self, Value};
+use crate::theme::{self, ColorMode, Shade, Theme};
+use crate::watch::{self, Watch};
+use crate::view::NameFit;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Name of the config file (resolved next to the DLL).
+const CONFIG_FILE: &str = "esp_config.toml";
+
+// ============================================================
+// Settings
+// ============================================================
+
+/// Where snap-lines start on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapOrigin { Bottom, Crosshair, Top }
+
+/// How box outlines are colored from top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoxStyle {
+    Solid,    // One color
+    Gradient, // Box color at the top fading into the bottom color
+    TwoTone,  // Box color on the top brackets, bottom color on the lower ones
+}
+
+/// Where a player's health bracket color (theme.rs) is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthColor {
+    Off,
+    Box,   // Replaces the box color
+    Badge, // Small square beside the box's top corner
+}
+
+/// How the stroke font draws zero (and the foot of one).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigitStyle {
+    Slashed, // Slash through 0 (CS 1.6 style)
+    Dotted,  // Dot inside 0
+    Plain,   // Bare 0, 1 without a foot
+}
+
+/// Where the overlay is drawn each frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawSite {
+    SwapBuffers, // On top of everything, just before the frame is presented
+    HudRedraw,   // Inside client.dll's HUD pass, under the engine's own UI
+}
+
+/// All user-tunable settings.
+#[derive(Clone, Debug)]
+pub struct Config {
+    // (top level)
+    pub hide_on_screenshot:   bool,     // Skip drawing while a screenshot is being taken
+    pub draw_site:            DrawSite,
+    pub theme:                Theme,    // Color palette (theme.rs)
+    pub color_mode:           ColorMode, // Team colors or enemy/friend
+
+    // [snapline]
+    pub snapline_enabled:     bool,
+    pub snapline_origin:      SnapOrigin,
+    pub snapline_target:      SnapTarget,
+    pub snapline_color_t:     Option<[f32; 4]>, // Terrorists (None = theme color)
+    pub snapline_color_ct:    Option<[f32; 4]>, // Counter-Terrorists
+    pub snapline_color_other: Option<[f32; 4]>, // Unknown team
+    pub snapline_head_line:   bool,     // Also a line from the crosshair to each player's eye point
+    pub snapline_head_color:  [f32; 4],
+
+    // [crosshair]
+    pub crosshair_enabled:    bool,
+    pub crosshair_size:       f32,      // Arm length (pixels)
+    pub crosshair_gap:        f32,      // Empty space around the center (pixels)
+    pub crosshair_color:      [f32; 4],
+
+    // [fov_circle]
+    pub fov_circle_enabled:   bool,
+    pub fov_circle_radius:    f32,      // Radius in degrees from the crosshair
+    pub fov_circle_color:     [f32; 4],
+
+    // [focus]
+    pub focus_enabled:        bool,     // Highlight the player closest to the crosshair
+    pub focus_angle:          f32,      // Only within this many degrees of it
+    pub focus_width:          f32,      // Extra corner width (px) of the highlighted box
+
+    // [projection]
+    pub fov_indicator:        bool,     // Show detected FOV + W2S check result
+    pub fov_correction:       bool,     // Rescale projections if W2S ignores zoom
+    pub w2s_cross_check:      bool,     // Log where TriAPI and the refdef projection disagree
+
+    // [spectate]
+    pub spectate_box_target:  bool,     // Still box the player watched in first person
+
+    // [death_markers]
+    pub death_markers_enabled: bool,
+    pub death_markers_seconds: f32,     // How long a marker stays (fades out over it)
+
+    // [annotations]
+    pub annotations_enabled:  bool,     // Show and edit the map's saved markers
+    pub annotations_key:      u32,      // Virtual key that drops a marker (0 = only esp_mark)
+    pub annotations_distance: f32,      // Markers go this far along the view (0 = where the crosshair points)
+    pub annotations_max_distance: f32,  // Markers further away aren't drawn (0 = all)
+    pub annotations_color:    [f32; 4],
+
+    // [grenades]
+    pub grenades_enabled:     bool,     // Label thrown grenades with their kind and fuse
+    pub grenades_he:          bool,
+    pub grenades_flash:       bool,
+    pub grenades_smoke:       bool,
+
+    // [stats]
+    pub stats_enabled:        bool,     // Keep kill / death / damage / time-alive tallies
+    pub stats_summary_seconds: f32,     // How long the end-of-round summary shows (0 = never)
+    pub stats_csv:            bool,     // Write the session table to esp_stats_<map>_<time>.csv at map end
+    pub stats_user_msgs_rva:  u32,      // hw.dll offset of the user message list head (0 = don't hook)
+
+    // [session]
+    pub session_export:       bool,     // Write esp_session_<map>_<time>.json at map end / disconnect
+
+    // [feed]
+    pub feed_enabled:         bool,     // Serve player snapshots over HTTP / WebSocket
+    pub feed_bind:            String,   // Address to listen on (read once, at the first snapshot)
+    pub feed_port:            u16,
+    pub feed_rate_hz:         f32,      // Snapshots per second
+
+    // [hulls]
+    pub hull_stand:           f32,      // Standing hull height override (0 = client.dll's hull table)
+    pub hull_duck:            f32,      // Ducking hull height override (0 = client.dll's hull table)
+
+    // [extra_info]
+    pub extra_info_signature: String,   // client.dll pattern referencing g_PlayerExtraInfo, tried first (empty = built-in only)
+    pub extra_info_pointer_offset: u32, // Offset of the array pointer from the match start
+
+    // [markers]
+    pub markers_enabled:      bool,     // Mark frame rate drops, loss spikes and lost hooks in the session / feed
+    pub markers_fps_below:    f32,      // Frame rate under this raises a marker (0 = never)
+    pub markers_loss_above:   f32,      // Loss (percent) over this raises one (0 = never; needs the [netgraph] signature)
+
+    // [netgraph]
+    pub netgraph_enabled:     bool,     // Net channel mini panel (latency, choke, loss, incoming rate)
+    pub netgraph_signature:   String,   // hw.dll pattern referencing the net channel (empty = off)
+    pub netgraph_pointer_offset: u32,   // Offset of the netchan_t address from the match start
+    pub netgraph_latency_offset: u32,   // netchan_t field offsets (0 = not read): latency (float, s)
+    pub netgraph_choke_offset: u32,     // ... choke (float, percent)
+    pub netgraph_loss_offset: u32,      // ... loss (float, percent)
+    pub netgraph_rate_in_offset: u32,   // ... incoming rate (float, kB/s)
+
+    // [slots]
+    pub slots_enabled:        bool,     // Slot occupancy strip (who's in each player slot)
+
+    // [spectators]
+    pub spectators_enabled:   bool,     // List who is spectating whom, highlighting those watching you
+    pub spectators_hide_empty: bool,    // No panel while nobody spectates
+    pub spectators_color:     [f32; 4], // Lines of those watching you
+
+    // [chat]
+    pub chat_enabled:         bool,     // Capture SayText / TextMsg (needs stats.user_msgs_rva)
+    pub chat_console:         bool,     // ... and client.dll's console prints (pfnConsolePrint, at load)
+    pub chat_log:             bool,     // Write captured lines to the log
+    pub chat_panel:           bool,     // Show the latest lines in a panel
+    pub chat_hold_seconds:    f32,      // How long a line stays in the panel
+    pub chat_lines:           usize,    // Lines shown at most
+    pub chat_server_color:    [f32; 4], // TextMsg lines
+    pub chat_console_color:   [f32; 4], // Console prints
+
+    // [interp]
+    pub interp_enabled:       bool,     // Mark a player's interpolated, state and history origins
+    pub interp_slot:          i32,      // Which player (0 = the one closest to the crosshair)
+
+    // [history_graph]
+    pub history_graph_enabled: bool,    // Graph a player's speed and height over their position history
+    pub history_graph_slot:   i32,      // Which player (0 = the one closest to the crosshair)
+
+    // [script]
+    pub script_enabled:       bool,     // Run the Rhai overlay script (needs the `scripting` build)
+    pub script_file:          String,   // Next to the DLL
+
+    // [control]
+    pub control_pipe:         bool,     // Serve the control pipe (controller app, scripts); read at attach
+    pub control_window_message: bool,   // Handle the registered control window message (AutoHotkey, tray apps)
+
+    // [plugins]
+    pub plugins_enabled:      bool,     // Load native plugins at attach and call them each frame
+    pub plugins_dir:          String,   // Folder next to the DLL holding plugin .dll files
+
+    // [trails]
+    pub trails_enabled:       bool,
+    pub trails_seconds:       f32,      // Length of the trail in time
+
+    // [alerts]
+    pub alerts_enabled:       bool,
+    pub alerts_radius:        f32,      // Beep when an enemy comes this close (units)
+    pub alerts_behind_cone:   f32,      // Width of the "behind you" wedge (degrees)
+    pub alerts_behind_range:  f32,      // Only enemies this close count as behind (units)
+    pub alerts_near_cooldown: f32,      // Seconds before the near alert can sound again
+    pub alerts_behind_cooldown: f32,    // Seconds before the behind alert can sound again
+    pub alerts_near_sound:    String,   // Under sound/ ("buttons/blip1.wav")
+    pub alerts_behind_sound:  String,
+    pub alerts_volume:        f32,      // 0..1
+
+    // [threat]
+    pub threat_enabled:       bool,     // Warn which way the nearest enemy is while they're off screen
+    pub threat_range:         f32,      // ... if they're this close (units)
+    pub threat_color:         [f32; 4],
+
+    // [compass]
+    pub compass_enabled:      bool,     // Enemy bearings on a compass strip at the top of the screen
+    pub compass_span:         f32,      // Degrees the strip covers
+    pub compass_range:        f32,      // Enemy marks shorten with distance out to this (units, 0 = all full height)
+    pub compass_width:        f32,      // Strip width (px)
+    pub compass_y:            f32,      // Strip top (px from the top of the screen)
+    pub compass_color:        [f32; 4], // Enemy marks
+
+    // [pvs]
+    pub pvs_tint:             bool,     // Tint boxes of players missing from the latest update
+    pub pvs_color:            [f32; 4], // Color mixed in (alpha ignored)
+    pub pvs_strength:         f32,      // 0 = box color, 1 = pvs_color
+    pub pvs_lines:            Dash,     // Pattern of their boxes and snap-lines (with or without the tint)
+
+    // [layout]
+    pub layout_gap:           f32,      // Space between panels stacked in one corner (pixels)
+    pub status_place:         Placement, // Status lines (toggle state, FOV, warnings)
+    pub diag_place:           Placement, // F7 diagnostics panel
+    pub watch_place:          Placement, // Watch list panel
+    pub stats_place:          Placement, // End-of-round summary panel
+    pub netgraph_place:       Placement, // Net channel mini panel
+    pub threat_place:         Placement, // Closest-threat warning
+    pub slots_place:          Placement, // Slot occupancy strip
+    pub interp_place:         Placement, // Interpolation diagnostics
+    pub history_graph_place:  Placement, // Position history graph
+    pub spectators_place:     Placement, // Spectator list
+    pub chat_place:           Placement, // Chat history
+
+    // [status]
+    pub status_enabled:       bool,     // Show the banner line ("[ESP ON] ...")
+    pub status_template:      String,   // Banner text: {state} {fps} {opacity} {players} {map} {path}
+    pub toggle_fade_ms:       f32,      // The overlay fades in / out over this long on a toggle (0 = instant)
+
+    // [panics]
+    pub panic_limit:          u32,      // A part of the overlay panicking this often in a minute is switched off (0 = never)
+
+    // [crash]
+    pub crash_dump:           bool,     // Write a minidump and the log tail to crashes/ when our code faults
+    pub crash_full_memory:    bool,     // ... with all of the process's memory (hundreds of MB) instead of the stacks
+
+    // [opacity]
+    pub opacity:              f32,      // Master opacity of the overlay (0.05..1)
+    pub opacity_key:          u32,      // Virtual key that steps it 100/75/50/25% (0 = no hotkey)
+    pub status_color:         Option<[f32; 4]>, // Status text color (None = theme color)
+
+    // [minimap]
+    pub minimap_enabled:      bool,
+    pub minimap_place:        Placement, // Corner from [layout], offset from minimap.x / minimap.y
+    pub minimap_size:         f32,      // Side length (pixels)
+    pub minimap_range:        f32,      // World units from the center to the edge
+    pub minimap_rotate:       bool,     // Turn with the view (forward = up) instead of north-up
+    pub minimap_height:       f32,      // Walls further than this above/below you are dimmed (units)
+    pub minimap_wall_color:   [f32; 4],
+    pub minimap_background:   [f32; 4],
+    pub minimap_image:        String,   // .tga next to the DLL drawn as the background ("" = plain fill)
+
+    // [sound_rings]
+    pub sound_rings_enabled:  bool,
+    pub sound_rings_seconds:  f32,      // How long a ring grows and fades
+    pub sound_rings_radius:   f32,      // Final ring radius (units)
+    pub sound_rings_footsteps: bool,
+    pub sound_rings_gunfire:  bool,
+    pub sound_rings_footstep_color: [f32; 4],
+    pub sound_rings_gunfire_color:  [f32; 4],
+    pub sound_start_rva:      u32,      // hw.dll offset of S_StartDynamicSound (0 = don't hook)
+
+    // [flash]
+    pub flash_indicator:      bool,     // "FLASHED" and a countdown bar while blinded
+    pub flash_full_alpha:     bool,     // Keep the ESP at full alpha; false = dim it with the fade
+    pub flash_color:          [f32; 4],
+
+    // [tracers]
+    pub tracers_enabled:      bool,
+    pub tracers_seconds:      f32,      // How long a shot's line stays
+    pub tracers_length:       f32,      // Line length from the shooter's eyes (units)
+    pub tracers_team_color:   bool,     // Shooter's box color instead of tracers_color
+    pub tracers_color:        [f32; 4],
+
+    // [watch]
+    pub watch_list:           Vec<(String, Watch)>, // Label -> memory to show in the watch panel
+
+    // [team_colors] / [player_colors]
+    pub team_colors:          Vec<(i32, [f32; 4])>, // Team number -> box color
+    pub team_name_colors:     Vec<(String, [f32; 4])>, // Team name (TeamInfo) -> box color
+    pub player_colors:        Vec<(u64, [f32; 4])>, // SteamID64 -> box color
+
+    // [box]
+    pub box_style:            BoxStyle,
+    pub box_corners:          BracketStyle,
+    pub box_cached_lines:     Dash,     // Pattern of cached (fading) boxes and their snap-lines
+    pub box_bottom_color:     [f32; 4], // What the lower end mixes toward (gradient / two_tone)
+    pub box_bottom_strength:  f32,      // 0 = box color, 1 = box_bottom_color
+    pub box_stance_ms:        f32,      // Box height blend on a duck / stand change (0 = off)
+
+    // [health]
+    pub health_color:         HealthColor,
+    pub health_low:           i32,      // Flash "LOW" under players below this health (0 = never)
+    pub health_pulse_ms:      f32,      // Time constant of the brightening after a hit (0 = off)
+
+    // [weapon_state]
+    pub weapon_state:         bool,     // RELOADING / SWITCHING tags under boxes
+    pub weapon_switch_ms:     u32,      // How long SWITCHING shows after a weapon change
+
+    // [tags]
+    pub tag_kit:              bool,     // [KIT] after the names of CTs carrying a defuse kit
+    pub tag_helmet:           bool,     // [H] after the names of players wearing a helmet
+
+    // [labels]
+    pub label_above:          String,   // Template above boxes: {name} {tags} {dist} {weapon} {hp} {team} {index} {dead} {action} {pvs}
+    pub label_below:          String,   // Template below boxes (same placeholders)
+
+    // [names]
+    pub name_fit:             NameFit,  // Name labels shrink with distance and are cut off past a width
+
+    // [lines]
+    pub box_line:             LineStyle, // Box corners and their shadow outline
+    pub snapline_line:        LineStyle,
+    pub crosshair_line:       LineStyle, // Crosshair and FOV circle
+    pub world_line:           LineStyle, // Trails, death markers, sound rings, tracers, brush outlines
+
+    // [font]
+    pub font_digits:          DigitStyle,
+
+    // [debug_esp]
+    pub debug_brushes:        bool,     // Outline brush entities (F8 on the diagnostics status page)
+    pub debug_classes:        Vec<String>, // Entity classnames to outline ("func_door", ...)
+    pub debug_color:          [f32; 4],
+
+    // [performance]
+    pub read_every:           u32,      // Read player data every N frames (1 = every frame)
+    pub read_hz:              f32,      // Or this many times a second (0 = use read_every)
+    pub read_extrapolate:     bool,     // Move players along their velocity between reads
+    pub budget_ms:            f32,      // Overlay time per frame before elements are shed (0 = no limit)
+    pub benchmark:            bool,     // Draw bench.rs's synthetic players instead of reading the engine
+
+    // [worker]
+    pub worker_retry_ms:      u32,      // Retry interval of a pending hook install / scan
+    pub worker_config_ms:     u32,      // Config file change checks (0 = never)
+    pub worker_heartbeat_s:   u32,      // A `heartbeat:` log line this often (0 = off)
+
+    // [log]
+    pub log_raw_names:        bool,     // Log the raw bytes of non-ASCII player names
+    pub gl_audit:             bool,     // Log GL state the overlay fails to restore
+    pub log_debug_string:     bool,     // Mirror log lines to OutputDebugString (DebugView)
+    pub log_etw:              bool,     // ... and to an ETW provider (WPA, tracelog)
+
+    // [screenshot]
+    pub screenshot_key:       u32,      // Virtual key that saves a screenshot with the overlay (0 = none)
+    pub screenshot_before:    bool,     // Also save the frame from just before the overlay drew
+
+    // [bundle]
+    pub bundle_key:           u32,      // Virtual key that writes a support bundle (0 = none)
+    pub bundle_command:       bool,     // Register the `esp_bundle` console command
+
+    // [scrub]
+    pub scrub_key:            u32,      // Virtual key that freezes the ESP / goes back to live (0 = off, nothing recorded)
+    pub scrub_back_key:       u32,      // While frozen: step back ...
+    pub scrub_forward_key:    u32,      // ... and forward
+    pub scrub_step_ms:        u32,      // How far one step goes
+    pub scrub_seconds:        f32,      // How much is kept to scrub through
+
+    // [profiles]
+    pub profile_default:      String,   // Profile used at startup ("" = none)
+    pub profile_key:          u32,      // Virtual key that cycles profiles (0 = no hotkey)
+    pub profile_command:      bool,     // Register the `esp_profile` console command
+    pub profile:              String,   // Active profile (set by load, "" = none)
+    pub profile_names:        Vec<String>, // Profiles in the file, in order (set by load)
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            hide_on_screenshot:   true,
+            draw_site:            DrawSite::SwapBuffers,
+            theme:                Theme::Default,
+            color_mode:           ColorMode::Team,
+            snapline_enabled:     true,
+            snapline_origin:      SnapOrigin::Bottom,
+            snapline_target:      SnapTarget::Feet,
+            snapline_color_t:     None,
+            snapline_color_ct:    None,
+            snapline_color_other: None,
+            snapline_head_line:   false,
+            snapline_head_color:  [0.2, 1.0, 1.0, 0.7],
+            crosshair_enabled:    false,
+            crosshair_size:       6.0,
+            crosshair_gap:        3.0,
+            crosshair_color:      [0.10, 0.95, 0.10, 0.90],
+            fov_circle_enabled:   false,
+            fov_circle_radius:    5.0,
+            fov_circle_color:     [1.0, 1.0, 1.0, 0.35],
+            focus_enabled:        true,
+            focus_angle:          10.0,
+            focus_width:          1.5,
+            fov_indicator:        true,
+            fov_correction:       true,
+            w2s_cross_check:      false,
+            spectate_box_target:  false,
+            death_markers_enabled: true,
+            death_markers_seconds: 5.0,
+            annotations_enabled:  false,
+            annotations_key:      0x2E,     // Delete
+            annotations_distance: 0.0,
+            annotations_max_distance: 0.0,
+            annotations_color:    [0.3, 1.0, 0.6, 1.0],
+            grenades_enabled:     true,
+            grenades_he:          true,
+            grenades_flash:       true,
+            grenades_smoke:       true,
+            stats_enabled:        true,
+            stats_summary_seconds: 8.0,
+            stats_csv:            true,
+            stats_user_msgs_rva:  0,
+            session_export:       false,
+            feed_enabled:         false,
+            feed_bind:            "127.0.0.1".to_string(),
+            feed_port:            27080,
+            feed_rate_hz:         10.0,
+            markers_enabled:      false,
+            markers_fps_below:    30.0,
+            markers_loss_above:   5.0,
+            netgraph_enabled:     false,
+            netgraph_signature:   String::new(),
+            hull_stand:           0.0,
+            hull_duck:            0.0,
+            extra_info_signature: String::new(),
+            extra_info_pointer_offset: 0,
+            netgraph_pointer_offset: 0,
+            netgraph_latency_offset: 0,
+            netgraph_choke_offset: 0,
+            netgraph_loss_offset: 0,
+            netgraph_rate_in_offset: 0,
+            slots_enabled:        false,
+            spectators_enabled:   false,
+            spectators_hide_empty: true,
+            spectators_color:     [1.0, 0.75, 0.2, 1.0],
+            chat_enabled:         false,
+            chat_console:         false,
+            chat_log:             true,
+            chat_panel:           false,
+            chat_hold_seconds:    30.0,
+            chat_lines:           8,
+            chat_server_color:    [0.6, 0.85, 1.0, 1.0],
+            chat_console_color:   [0.7, 0.7, 0.7, 0.9],
+            interp_enabled:       false,
+            interp_slot:          0,
+            history_graph_enabled: false,
+            history_graph_slot:   0,
+            script_enabled:       false,
+            script_file:          "esp_script.rhai".to_string(),
+            control_pipe:         true,
+            control_window_message: true,
+            plugins_enabled:      true,
+            plugins_dir:          "plugins".to_string(),
+            trails_enabled:       false,
+            trails_seconds:       1.5,
+            alerts_enabled:       false,
+            alerts_radius:        600.0,
+            alerts_behind_cone:   90.0,
+            alerts_behind_range:  1000.0,
+            alerts_near_cooldown: 3.0,
+            alerts_behind_cooldown: 3.0,
+            alerts_near_sound:    "buttons/blip1.wav".to_string(),
+            alerts_behind_sound:  "buttons/blip2.wav".to_string(),
+            alerts_volume:        0.8,
+            threat_enabled:       false,
+            threat_range:         800.0,
+            threat_color:         [1.0, 0.3, 0.2, 1.0],
+            compass_enabled:      false,
+            compass_span:         120.0,
+            compass_range:        2000.0,
+            compass_width:        360.0,
+            compass_y:            4.0,
+            compass_color:        [1.0, 0.3, 0.2, 1.0],
+            pvs_tint:             false,
+            pvs_color:            [0.55, 0.55, 0.55, 1.0],
+            pvs_strength:         0.6,
+            pvs_lines:            Dash::Dashed,
+            layout_gap:           6.0,
+            status_place:         Placement::new(Anchor::TopLeft, 6.0, 14.0),
+            diag_place:           Placement::new(Anchor::TopRight, 6.0, 6.0),
+            watch_place:          Placement::new(Anchor::BottomRight, 6.0, 6.0),
+            stats_place:          Placement::new(Anchor::TopRight, 6.0, 6.0),
+            netgraph_place:       Placement::new(Anchor::BottomRight, 6.0, 6.0),
+            threat_place:         Placement::new(Anchor::TopLeft, 6.0, 14.0),
+            slots_place:          Placement::new(Anchor::BottomLeft, 6.0, 120.0),
+            interp_place:         Placement::new(Anchor::TopRight, 6.0, 6.0),
+            history_graph_place:  Placement::new(Anchor::BottomRight, 6.0, 6.0),
+            spectators_place:     Placement::new(Anchor::TopRight, 6.0, 6.0),
+            chat_place:           Placement::new(Anchor::BottomLeft, 6.0, 120.0),
+            status_enabled:       true,
+            status_template:      "[ESP {state}]  F6=toggle  {path}".to_string(),
+            toggle_fade_ms:       150.0,
+            panic_limit:          5,
+            crash_dump:           true,
+            crash_full_memory:    false,
+            opacity:              1.0,
+            opacity_key:          0,
+            status_color:         None,
+            minimap_enabled:      false,
+            minimap_place:        Placement::new(Anchor::TopLeft, 6.0, 100.0),
+            minimap_size:         200.0,
+            minimap_range:        1500.0,
+            minimap_rotate:       true,
+            minimap_height:       128.0,
+            minimap_wall_color:   [0.85, 0.85, 0.85, 0.90],
+            minimap_background:   [0.0, 0.0, 0.0, 0.50],
+            minimap_image:        String::new(),
+            sound_rings_enabled:  false,
+            sound_rings_seconds:  1.5,
+            sound_rings_radius:   64.0,
+            sound_rings_footsteps: true,
+            sound_rings_gunfire:  true,
+            sound_rings_footstep_color: [0.30, 0.80, 1.0, 0.90],
+            sound_rings_gunfire_color:  [1.0, 0.55, 0.10, 0.90],
+            sound_start_rva:      0,
+            flash_indicator:      true,
+            flash_full_alpha:     true,
+            flash_color:          [1.0, 0.95, 0.30, 1.0],
+            tracers_enabled:      false,
+            tracers_seconds:      0.5,
+            tracers_length:       1500.0,
+            tracers_team_color:   true,
+            tracers_color:        [1.0, 0.90, 0.40, 0.90],
+            watch_list:           Vec::new(),
+            team_colors:          Vec::new(),
+            team_name_colors:     Vec::new(),
+            player_colors:        Vec::new(),
+            box_style:            BoxStyle::Solid,
+            box_corners:          BracketStyle::Classic,
+            box_cached_lines:     Dash::Dotted,
+            box_bottom_color:     [0.10, 0.10, 0.10, 1.0],
+            box_bottom_strength:  0.6,
+            box_stance_ms:        100.0,
+            health_color:         HealthColor::Off,
+            health_low:           25,
+            health_pulse_ms:      150.0,
+            weapon_state:         true,
+            weapon_switch_ms:     750,
+            tag_kit:              true,
+            tag_helmet:           true,
+            label_above:          "{name} {tags}".to_string(),
+            label_below:          "{dist}m  [{weapon}]  {dead}  {action}  {pvs}".to_string(),
+            name_fit:             NameFit { shrink_from: 15.0, shrink_to: 60.0, min_scale: 0.6, max_width: 110.0 },
+            box_line:             LineStyle::DEFAULT,
+            snapline_line:        LineStyle::DEFAULT,
+            crosshair_line:       LineStyle::DEFAULT,
+            world_line:           LineStyle::DEFAULT,
+            font_digits:          DigitStyle::Slashed,
+            debug_brushes:        false,
+            debug_classes:        ["func_door", "func_door_rotating", "func_breakable", "func_ladder"]
+                .iter().map(|s| s.to_string()).collect(),
+            debug_color:          [1.0, 0.55, 0.0, 0.80],
+            read_every:           1,
+            read_hz:              0.0,
+            read_extrapolate:     true,
+            budget_ms:            0.0,
+            benchmark:            false,
+            worker_retry_ms:      500,
+            worker_config_ms:     500,
+            worker_heartbeat_s:   300,
+            log_raw_names:        false,
+            gl_audit:             false,
+            log_debug_string:     false,
+            log_etw:              false,
+            screenshot_key:       0x7A,     // F11
+            screenshot_before:    true,
+            bundle_key:           0,
+            bundle_command:       false,
+            scrub_key:            0x13,     // Pause
+            scrub_back_key:       0x6D,     // Numpad - ([ / ] belong to the F7 offset tuner)
+            scrub_forward_key:    0x6B,     // Numpad +
+            scrub_step_ms:        100,
+            scrub_seconds:        10.0,
+            profile_default:      String::new(),
+            profile_key:          0x78,     // F9
+            profile_command:      false,
+            profile:              String::new(),
+            profile_names:        Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Snap-line color for a team number (1=T, 2=CT): the configured color,
+    /// or the theme's.
+    pub fn snapline_color(&self, team: i32) -> [f32; 4] {
+        let set = match team {
+            1 => self.snapline_color_t,
+            2 => self.snapline_color_ct,
+            _ => self.snapline_color_other,
+        };
+        set.unwrap_or_else(|| self.theme.palette().snapline_color(team))
+    }
+
+    /// Outline shading for a box of `color` under the configured box style.
+    /// The bottom color keeps the box's alpha, so fades apply to both ends.
+    pub fn box_shade(&self, color: [f32; 4]) -> Shade {
+        let bottom = theme::blend(color, self.box_bottom_color, self.box_bottom_strength);
+        match self.box_style {
+            BoxStyle::Solid    => Shade::Solid(color),
+            BoxStyle::Gradient => Shade::Gradient(color, bottom),
+            BoxStyle::TwoTone  => Shade::TwoTone(color, bottom),
+        }
+    }
+
+    /// Configured box color for a player: their SteamID's entry first, then
+    /// their team name's (any case), then their team number's. None = use the theme.
+    pub fn color_override(&self, steam_id: u64, team: i32, team_name: Option<&str>) -> Option<[f32; 4]> {
+        let by_player = self.player_colors.iter().find(|(id, _)| steam_id != 0 && *id == steam_id);
+        let by_name = || team_name.and_then(|n| self.team_name_colors.iter().find(|(t, _)| t.eq_ignore_ascii_case(n)))
+            .map(|(_, c)| *c);
+        let by_team = || self.team_colors.iter().find(|(t, _)| *t == team).map(|(_, c)| *c);
+        by_player.map(|(_, c)| *c).or_else(by_name).or_else(by_team)
+    }
+
+    /// Apply one `section.key = value` pair. Returns false if the key is unknown
+    /// or the value has the wrong type.
+    fn apply(&mut self, key: &str, v: &Value) -> bool {
+        match key {
+            "hide_on_screenshot" => set(&mut self.hide_on_screenshot, v.as_bool()),
+            "draw_site" => set(&mut self.draw_site, v.as_str().and_then(|s| match s {
+                "swapbuffers" => Some(DrawSite::SwapBuffers),
+                "hud_redraw"  => Some(DrawSite::HudRedraw),
+                _ => None,
+            })),
+            "theme" => set(&mut self.theme, v.as_str().and_then(Theme::from_name)),
+            "color_mode" => set(&mut self.color_mode, v.as_str().and_then(|s| match s {
+                "team"     => Some(ColorMode::Team),
+                "relation" => Some(ColorMode::Relation),
+                _ => None,
+            })),
+            "snapline.enabled" => set(&mut self.snapline_enabled, v.as_bool()),
+            "snapline.origin" => set(&mut self.snapline_origin, v.as_str().and_then(|s| match s {
+                "bottom"    => Some(SnapOrigin::Bottom),
+                "crosshair" => Some(SnapOrigin::Crosshair),
+                "top"       => Some(SnapOrigin::Top),
+                _ => None,
+            })),
+            "snapline.target" => set(&mut self.snapline_target, v.as_str().and_then(|s| match s {
+                "feet"   => Some(SnapTarget::Feet),
+                "center" => Some(SnapTarget::Center),
+                "head"   => Some(SnapTarget::Head),
+                _ => None,
+            })),
+            "snapline.color_t"     => set(&mut self.snapline_color_t, v.as_color().map(Some)),
+            "snapline.color_ct"    => set(&mut self.snapline_color_ct, v.as_color().map(Some)),
+            "snapline.color_other" => set(&mut self.snapline_color_other, v.as_color().map(Some)),
+            "snapline.head_line"   => set(&mut self.snapline_head_line, v.as_bool()),
+            "snapline.head_color"  => set(&mut self.snapline_head_color, v.as_color()),
+            "crosshair.enabled"    => set(&mut self.crosshair_enabled, v.as_bool()),
+            "crosshair.size"       => set(&mut self.crosshair_size, v.as_f32()),
+            "crosshair.gap"        => set(&mut self.crosshair_gap, v.as_f32()),
+            "crosshair.color"      => set(&mut self.crosshair_color, v.as_color()),
+            "fov_circle.enabled"   => set(&mut self.fov_circle_enabled, v.as_bool()),
+            "fov_circle.radius"    => set(&mut self.fov_circle_radius, v.as_f32()),
+            "fov_circle.color"     => set(&mut self.fov_circle_color, v.as_color()),
+            "focus.enabled"        => set(&mut self.focus_enabled, v.as_bool()),
+            "focus.angle"          => set(&mut self.focus_angle, v.as_f32().map(|n| n.clamp(0.0, 180.0))),
+            "focus.width"          => set(&mut self.focus_width, v.as_f32().map(|n| n.max(0.0))),
+            "projection.fov_indicator"  => set(&mut self.fov_indicator, v.as_bool()),
+            "projection.fov_correction" => set(&mut self.fov_correction, v.as_bool()),
+            "projection.cross_check"    => set(&mut self.w2s_cross_check, v.as_bool()),
+            "spectate.box_target"       => set(&mut self.spectate_box_target, v.as_bool()),
+            "death_markers.enabled"     => set(&mut self.death_markers_enabled, v.as_bool()),
+            "death_markers.seconds"     => set(&mut self.death_markers_seconds, v.as_f32()),
+            "annotations.enabled"       => set(&mut self.annotations_enabled, v.as_bool()),
+            "annotations.key"           => set(&mut self.annotations_key, v.as_hex()),
+            "annotations.distance"      => set(&mut self.annotations_distance, v.as_f32().map(|d| d.max(0.0))),
+            "annotations.max_distance"  => set(&mut self.annotations_max_distance, v.as_f32().map(|d| d.max(0.0))),
+            "annotations.color"         => set(&mut self.annotations_color, v.as_color()),
+            "grenades.enabled"          => set(&mut self.grenades_enabled, v.as_bool()),
+            "grenades.he"               => set(&mut self.grenades_he, v.as_bool()),
+            "grenades.flash"            => set(&mut self.grenades_flash, v.as_bool()),
+            "grenades.smoke"            => set(&mut self.grenades_smoke, v.as_bool()),
+            "stats.enabled"             => set(&mut self.stats_enabled, v.as_bool()),
+            "stats.summary_seconds"     => set(&mut self.stats_summary_seconds, v.as_f32()),
+            "stats.csv"                 => set(&mut self.stats_csv, v.as_bool()),
+            "stats.user_msgs_rva"       => set(&mut self.stats_user_msgs_rva, v.as_hex()),
+            "session.export"            => set(&mut self.session_export, v.as_bool()),
+            "feed.enabled"              => set(&mut self.feed_enabled, v.as_bool()),
+            "feed.bind"                 => set(&mut self.feed_bind, v.as_str().map(str::to_string)),
+            "feed.port"                 => set(&mut self.feed_port, v.as_f32().map(|n| n.clamp(1.0, 65535.0) as u16)),
+            "feed.rate_hz"              => set(&mut self.feed_rate_hz, v.as_f32().map(|n| n.clamp(0.5, 60.0))),
+            "hulls.stand"               => set(&mut self.hull_stand, v.as_f32()),
+            "hulls.duck"                => set(&mut self.hull_duck, v.as_f32()),
+            "extra_info.signature"      => set(&mut self.extra_info_signature, v.as_str().map(str::to_string)),
+            "extra_info.pointer_offset" => set(&mut self.extra_info_pointer_offset, v.as_hex()),
+            "markers.enabled"           => set(&mut self.markers_enabled, v.as_bool()),
+            "markers.fps_below"         => set(&mut self.markers_fps_below, v.as_f32().map(|f| f.max(0.0))),
+            "markers.loss_above"        => set(&mut self.markers_loss_above, v.as_f32().map(|f| f.max(0.0))),
+            "netgraph.enabled"          => set(&mut self.netgraph_enabled, v.as_bool()),
+            "netgraph.signature"        => set(&mut self.netgraph_signature, v.as_str().map(str::to_string)),
+            "netgraph.pointer_offset"   => set(&mut self.netgraph_pointer_offset, v.as_hex()),
+            "netgraph.latency_offset"   => set(&mut self.netgraph_latency_offset, v.as_hex()),
+            "netgraph.choke_offset"     => set(&mut self.netgraph_choke_offset, v.as_hex()),
+            "netgraph.loss_offset"      => set(&mut self.netgraph_loss_offset, v.as_hex()),
+            "netgraph.rate_in_offset"   => set(&mut self.netgraph_rate_in_offset, v.as_hex()),
+            "slots.enabled"             => set(&mut self.slots_enabled, v.as_bool()),
+            "spectators.enabled"        => set(&mut self.spectators_enabled, v.as_bool()),
+            "spectators.hide_empty"     => set(&mut self.spectators_hide_empty, v.as_bool()),
+            "spectators.color"          => set(&mut self.spectators_color, v.as_color()),
+            "chat.enabled"              => set(&mut self.chat_enabled, v.as_bool()),
+            "chat.console"              => set(&mut self.chat_console, v.as_bool()),
+            "chat.log"                  => set(&mut self.chat_log, v.as_bool()),
+            "chat.panel"                => set(&mut self.chat_panel, v.as_bool()),
+            "chat.hold_seconds"         => set(&mut self.chat_hold_seconds, v.as_f32().map(|s| s.max(0.0))),
+            "chat.lines"                => set(&mut self.chat_lines, v.as_f32().map(|n| n.clamp(1.0, 32.0) as usize)),
+            "chat.server_color"         => set(&mut self.chat_server_color, v.as_color()),
+            "chat.console_color"        => set(&mut self.chat_console_color, v.as_color()),
+            "interp.enabled"            => set(&mut self.interp_enabled, v.as_bool()),
+            "interp.slot"               => set(&mut self.interp_slot, v.as_f32().map(|n| n.clamp(0.0, 32.0) as i32)),
+            "history_graph.enabled"     => set(&mut self.history_graph_enabled, v.as_bool()),
+            "history_graph.slot"        => set(&mut self.history_graph_slot, v.as_f32().map(|n| n.clamp(0.0, 32.0) as i32)),
+            "script.enabled"            => set(&mut self.script_enabled, v.as_bool()),
+            "script.file"               => set(&mut self.script_file, v.as_str().map(str::to_string)),
+            "control.pipe"              => set(&mut self.control_pipe, v.as_bool()),
+            "control.window_message"    => set(&mut self.control_window_message, v.as_bool()),
+            "plugins.enabled"           => set(&mut self.plugins_enabled, v.as_bool()),
+            "plugins.dir"               => set(&mut self.plugins_dir, v.as_str().map(str::to_string)),
+            "trails.enabled"            => set(&mut self.trails_enabled, v.as_bool()),
+            "trails.seconds"            => set(&mut self.trails_seconds, v.as_f32()),
+            "alerts.enabled"            => set(&mut self.alerts_enabled, v.as_bool()),
+            "alerts.radius"             => set(&mut self.alerts_radius, v.as_f32()),
+            "alerts.behind_cone"        => set(&mut self.alerts_behind_cone, v.as_f32()),
+            "alerts.behind_range"       => set(&mut self.alerts_behind_range, v.as_f32()),
+            "alerts.near_cooldown"      => set(&mut self.alerts_near_cooldown, v.as_f32()),
+            "alerts.behind_cooldown"    => set(&mut self.alerts_behind_cooldown, v.as_f32()),
+            "alerts.near_sound"         => set(&mut self.alerts_near_sound, v.as_str().map(str::to_string)),
+            "alerts.behind_sound"       => set(&mut self.alerts_behind_sound, v.as_str().map(str::to_string)),
+            "alerts.volume"             => set(&mut self.alerts_volume, v.as_f32()),
+            "threat.enabled"            => set(&mut self.threat_enabled, v.as_bool()),
+            "threat.range"              => set(&mut self.threat_range, v.as_f32()),
+            "threat.color"              => set(&mut self.threat_color, v.as_color()),
+            "compass.enabled"           => set(&mut self.compass_enabled, v.as_bool()),
+            "compass.span"              => set(&mut self.compass_span, v.as_f32().map(|n| n.clamp(10.0, 360.0))),
+            "compass.range"             => set(&mut self.compass_range, v.as_f32().map(|n| n.max(0.0))),
+            "compass.width"             => set(&mut self.compass_width, v.as_f32().map(|n| n.max(60.0))),
+            "compass.y"                 => set(&mut self.compass_y, v.as_f32().map(|n| n.max(0.0))),
+            "compass.color"             => set(&mut self.compass_color, v.as_color()),
+            "pvs.tint"                  => set(&mut self.pvs_tint, v.as_bool()),
+            "pvs.lines"                 => set(&mut self.pvs_lines, v.as_str().and_then(Dash::from_name)),
+            "pvs.color"                 => set(&mut self.pvs_color, v.as_color()),
+            "pvs.strength"              => set(&mut self.pvs_strength, v.as_f32()),
+            "layout.gap"                => set(&mut self.layout_gap, v.as_f32()),
+            "layout.status"             => set(&mut self.status_place.anchor, v.as_anchor()),
+            "layout.status_offset"      => set(&mut self.status_place.offset, v.as_pair()),
+            "layout.minimap"            => set(&mut self.minimap_place.anchor, v.as_anchor()),
+            "layout.diag"               => set(&mut self.diag_place.anchor, v.as_anchor()),
+            "layout.diag_offset"        => set(&mut self.diag_place.offset, v.as_pair()),
+            "layout.watch"              => set(&mut self.watch_place.anchor, v.as_anchor()),
+            "layout.watch_offset"       => set(&mut self.watch_place.offset, v.as_pair()),
+            "layout.stats"              => set(&mut self.stats_place.anchor, v.as_anchor()),
+            "layout.stats_offset"       => set(&mut self.stats_place.offset, v.as_pair()),
+            "layout.netgraph"           => set(&mut self.netgraph_place.anchor, v.as_anchor()),
+            "layout.netgraph_offset"    => set(&mut self.netgraph_place.offset, v.as_pair()),
+            "layout.threat"             => set(&mut self.threat_place.anchor, v.as_anchor()),
+            "layout.threat_offset"      => set(&mut self.threat_place.offset, v.as_pair()),
+            "layout.slots"              => set(&mut self.slots_place.anchor, v.as_anchor()),
+            "layout.slots_offset"       => set(&mut self.slots_place.offset, v.as_pair()),
+            "layout.interp"             => set(&mut self.interp_place.anchor, v.as_anchor()),
+            "layout.interp_offset"      => set(&mut self.interp_place.offset, v.as_pair()),
+            "layout.history_graph"      => set(&mut self.history_graph_place.anchor, v.as_anchor()),
+            "layout.history_graph_offset" => set(&mut self.history_graph_place.offset, v.as_pair()),
+            "layout.spectators"         => set(&mut self.spectators_place.anchor, v.as_anchor()),
+            "layout.spectators_offset"  => set(&mut self.spectators_place.offset, v.as_pair()),
+            "layout.chat"               => set(&mut self.chat_place.anchor, v.as_anchor()),
+            "layout.chat_offset"        => set(&mut self.chat_place.offset, v.as_pair()),
+            "status.enabled"            => set(&mut self.status_enabled, v.as_bool()),
+            "status.template"           => set(&mut self.status_template, v.as_str().map(str::to_string)),
+            "status.toggle_fade_ms"     => set(&mut self.toggle_fade_ms, v.as_f32().map(|n| n.max(0.0))),
+            "panics.disable_after"      => set(&mut self.panic_limit, v.as_f32().map(|n| n.max(0.0) as u32)),
+            "crash.minidump"            => set(&mut self.crash_dump, v.as_bool()),
+            "crash.full_memory"         => set(&mut self.crash_full_memory, v.as_bool()),
+            "opacity.level"             => set(&mut self.opacity, v.as_f32().map(|n| (n / 100.0).clamp(0.05, 1.0))),
+            "opacity.key"               => set(&mut self.opacity_key, v.as_hex()),
+            "status.color"              => set(&mut self.status_color, v.as_color().map(Some)),
+            "performance.read_every"    => set(&mut self.read_every, v.as_f32().map(|n| n.max(1.0) as u32)),
+            "performance.read_hz"       => set(&mut self.read_hz, v.as_f32().map(|n| n.max(0.0))),
+            "performance.extrapolate"   => set(&mut self.read_extrapolate, v.as_bool()),
+            "performance.budget_ms"     => set(&mut self.budget_ms, v.as_f32().map(|n| n.max(0.0))),
+            "performance.benchmark"     => set(&mut self.benchmark, v.as_bool()),
+            "worker.retry_ms"           => set(&mut self.worker_retry_ms, v.as_f32().map(|n| n.max(50.0) as u32)),
+            "worker.config_poll_ms"     => set(&mut self.worker_config_ms, v.as_f32().map(|n| n.max(0.0) as u32)),
+            "worker.heartbeat_s"        => set(&mut self.worker_heartbeat_s, v.as_f32().map(|n| n.max(0.0) as u32)),
+            "screenshot.key"            => set(&mut self.screenshot_key, v.as_hex()),
+            "screenshot.before"         => set(&mut self.screenshot_before, v.as_bool()),
+            "bundle.key"                => set(&mut self.bundle_key, v.as_hex()),
+            "bundle.command"            => set(&mut self.bundle_command, v.as_bool()),
+            "scrub.key"                 => set(&mut self.scrub_key, v.as_hex()),
+            "scrub.back_key"            => set(&mut self.scrub_back_key, v.as_hex()),
+            "scrub.forward_key"         => set(&mut self.scrub_forward_key, v.as_hex()),
+            "scrub.step_ms"             => set(&mut self.scrub_step_ms, v.as_f32().map(|n| n.max(1.0) as u32)),
+            "scrub.seconds"             => set(&mut self.scrub_seconds, v.as_f32()),
+            "profiles.default"          => set(&mut self.profile_default, v.as_str().map(str::to_string)),
+            "profiles.key"              => set(&mut self.profile_key, v.as_hex()),
+            "profiles.command"          => set(&mut self.profile_command, v.as_bool()),
+            "minimap.enabled"           => set(&mut self.minimap_enabled, v.as_bool()),
+            "minimap.x"                 => set(&mut self.minimap_place.offset[0], v.as_f32()),
+            "minimap.y"                 => set(&mut self.minimap_place.offset[1], v.as_f32()),
+            "minimap.size"              => set(&mut self.minimap_size, v.as_f32()),
+            "minimap.range"             => set(&mut self.minimap_range, v.as_f32()),
+            "minimap.rotate"            => set(&mut self.minimap_rotate, v.as_bool()),
+            "minimap.height_window"     => set(&mut self.minimap_height, v.as_f32()),
+            "minimap.wall_color"        => set(&mut self.minimap_wall_color, v.as_color()),
+            "minimap.background"        => set(&mut self.minimap_background, v.as_color()),
+            "minimap.image"             => set(&mut self.minimap_image, v.as_str().map(str::to_string)),
+            "sound_rings.enabled"       => set(&mut self.sound_rings_enabled, v.as_bool()),
+            "sound_rings.seconds"       => set(&mut self.sound_rings_seconds, v.as_f32()),
+            "sound_rings.radius"        => set(&mut self.sound_rings_radius, v.as_f32()),
+            "sound_rings.footsteps"     => set(&mut self.sound_rings_footsteps, v.as_bool()),
+            "sound_rings.gunfire"       => set(&mut self.sound_rings_gunfire, v.as_bool()),
+            "sound_rings.footstep_color" => set(&mut self.sound_rings_footstep_color, v.as_color()),
+            "sound_rings.gunfire_color" => set(&mut self.sound_rings_gunfire_color, v.as_color()),
+            "sound_rings.start_sound_rva" => set(&mut self.sound_start_rva, v.as_hex()),
+            "flash.indicator"           => set(&mut self.flash_indicator, v.as_bool()),
+            "flash.full_alpha"          => set(&mut self.flash_full_alpha, v.as_bool()),
+            "flash.color"               => set(&mut self.flash_color, v.as_color()),
+            "tracers.enabled"           => set(&mut self.tracers_enabled, v.as_bool()),
+            "tracers.seconds"           => set(&mut self.tracers_seconds, v.as_f32()),
+            "tracers.length"            => set(&mut self.tracers_length, v.as_f32()),
+            "tracers.team_color"        => set(&mut self.tracers_team_color, v.as_bool()),
+            "tracers.color"             => set(&mut self.tracers_color, v.as_color()),
+            "box.style" => set(&mut self.box_style, v.as_str().and_then(|s| match s {
+                "solid"    => Some(BoxStyle::Solid),
+                "gradient" => Some(BoxStyle::Gradient),
+                "two_tone" => Some(BoxStyle::TwoTone),
+                _ => None,
+            })),
+            "box.corners" => set(&mut self.box_corners, v.as_str().and_then(|s| match s {
+                "classic" => Some(BracketStyle::Classic),
+                "short"   => Some(BracketStyle::Short),
+                "frame"   => Some(BracketStyle::Frame),
+                "dashed"  => Some(BracketStyle::Dashed),
+                _ => None,
+            })),
+            "box.cached_lines"          => set(&mut self.box_cached_lines, v.as_str().and_then(Dash::from_name)),
+            "box.bottom_color"          => set(&mut self.box_bottom_color, v.as_color()),
+            "box.bottom_strength"       => set(&mut self.box_bottom_strength, v.as_f32()),
+            "box.stance_ms"             => set(&mut self.box_stance_ms, v.as_f32()),
+            "health.color" => set(&mut self.health_color, v.as_str().and_then(|s| match s {
+                "off"   => Some(HealthColor::Off),
+                "box"   => Some(HealthColor::Box),
+                "badge" => Some(HealthColor::Badge),
+                _ => None,
+            })),
+            "health.low"                => set(&mut self.health_low, v.as_f32().map(|n| n.max(0.0) as i32)),
+            "tags.kit"                  => set(&mut self.tag_kit, v.as_bool()),
+            "tags.helmet"               => set(&mut self.tag_helmet, v.as_bool()),
+            "labels.above"              => set(&mut self.label_above, v.as_str().map(str::to_string)),
+            "labels.below"              => set(&mut self.label_below, v.as_str().map(str::to_string)),
+            "names.shrink_from"         => set(&mut self.name_fit.shrink_from, v.as_f32()),
+            "names.shrink_to"           => set(&mut self.name_fit.shrink_to, v.as_f32()),
+            "names.min_scale"           => set(&mut self.name_fit.min_scale, v.as_f32().map(|n| n.clamp(0.1, 1.0))),
+            "names.max_width"           => set(&mut self.name_fit.max_width, v.as_f32().map(|n| n.max(0.0))),
+            "weapon_state.enabled"      => set(&mut self.weapon_state, v.as_bool()),
+            "weapon_state.switch_ms"    => set(&mut self.weapon_switch_ms, v.as_f32().map(|n| n.max(0.0) as u32)),
+            "health.pulse_ms"           => set(&mut self.health_pulse_ms, v.as_f32().map(|n| n.max(0.0))),
+            "lines.box_width"           => set(&mut self.box_line.width, v.as_f32()),
+            "lines.box_smooth"          => set(&mut self.box_line.smooth, v.as_bool()),
+            "lines.snapline_width"      => set(&mut self.snapline_line.width, v.as_f32()),
+            "lines.snapline_smooth"     => set(&mut self.snapline_line.smooth, v.as_bool()),
+            "lines.crosshair_width"     => set(&mut self.crosshair_line.width, v.as_f32()),
+            "lines.crosshair_smooth"    => set(&mut self.crosshair_line.smooth, v.as_bool()),
+            "lines.world_width"         => set(&mut self.world_line.width, v.as_f32()),
+            "lines.world_smooth"        => set(&mut self.world_line.smooth, v.as_bool()),
+            "font.digits" => set(&mut self.font_digits, v.as_str().and_then(|s| match s {
+                "slashed" => Some(DigitStyle::Slashed),
+                "dotted"  => Some(DigitStyle::Dotted),
+                "plain"   => Some(DigitStyle::Plain),
+                _ => None,
+            })),
+            "debug_esp.brushes"         => set(&mut self.debug_brushes, v.as_bool()),
+            "debug_esp.classes"         => set(&mut self.debug_classes, v.as_str_list()),
+            "debug_esp.color"           => set(&mut self.debug_color, v.as_color()),
+            k if k.starts_with("watch.") => {
+                let label = k["watch.".len()..].trim_matches('"').to_string();
+                match v.as_str().map(watch::parse) {
+                    Some(Ok(w)) => { upsert(&mut self.watch_list, label, w); true }
+                    Some(Err(e)) => { entities::logf(format!("config: watch.{}: {}", label, e)); false }
+                    None => false,
+                }
+            }
+            k if k.starts_with("team_colors.") => {
+                let key = k["team_colors.".len()..].trim_matches('"');
+                match (key.parse::<i32>(), v.as_color()) {
+                    (Ok(t), Some(c)) => { upsert(&mut self.team_colors, t, c); true }
+                    (Err(_), Some(c)) if !key.is_empty() => { upsert(&mut self.team_name_colors, key.to_string(), c); true }
+                    _ => false,
+                }
+            }
+            k if k.starts_with("player_colors.") => {
+                match (player::parse_steam_id(&k["player_colors.".len()..]), v.as_color()) {
+                    (Some(id), Some(c)) => { upsert(&mut self.player_colors, id, c); true }
+                    _ => false,
+                }
+            }
+            "log.raw_names"             => set(&mut self.log_raw_names, v.as_bool()),
+            "log.gl_audit"              => set(&mut self.gl_audit, v.as_bool()),
+            "log.debug_string"          => set(&mut self.log_debug_string, v.as_bool()),
+            "log.etw"                   => set(&mut self.log_etw, v.as_bool()),
+            _ => false,
+        }
+    }
+}
+
+/// Insert or replace the entry for `key`.
+fn upsert<K: PartialEq, V>(list: &mut Vec<(K, V)>, key: K, value: V) {
+    match list.iter_mut().find(|(k, _)| *k == key) {
+        Some(e) => e.1 = value,
+        None    => list.push((key, value)),
+    }
+}
+
+/// Store `v` into `slot` if present. Returns whether it was.
+fn set<T>(slot: &mut T, v: Option<T>) -> bool {
+    match v {
+        Some(v) => { *slot = v; true }
+        None => false,
+    }
+}
+
+// ============================================================
+// Global State
+// ============================================================
+
+/// The active settings (None until first access or load).
+static CONFIG: Mutex<Option<Arc<Config>>> = Mutex::new(None);
+
+/// Modification time of the config file when it was last loaded.
+static LOADED_MTIME: Mutex<Option<SystemTime>> = Mutex::new(None);
+
+/// Profile picked with the hotkey or command (None = `[profiles] default`).
+static ACTIVE_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// time::now_ms() of the last profile switch (0 = none yet).
+static PROFILE_SWITCHED: AtomicU32 = AtomicU32::new(0);
+
+/// Get a snapshot of the current settings.
+pub fn get() -> Arc<Config> {
+    let mut guard = match CONFIG.lock() {
+        Ok(g) => g,
+        Err(_) => return Arc::new(Config::default()),
+    };
+    guard.get_or_insert_with(|| Arc::new(Config::default())).clone()
+}
+
+/// Replace the current settings.
+fn store(cfg: Config) {
+    if let Ok(mut guard) = CONFIG.lock() { *guard = Some(Arc::new(cfg)); }
+}
+
+/// Apply an in-memory change on top of the current settings (used by the menu).
+/// Not written to disk — the next file reload replaces it.
+pub fn update(f: impl FnOnce(&mut Config)) {
+    let mut cfg = (*get()).clone();
+    f(&mut cfg);
+    store(cfg);
+}
+
+/// Path of the config file (next to the DLL).
+fn config_path() -> std::path::PathBuf {
+    entities::dll_dir().join(CONFIG_FILE)
+}
+
+/// Load (or reload) the config file. A missing file means all defaults.
+pub fn load() {
+    let path = config_path();
+    let mtime = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if let Ok(mut guard) = LOADED_MTIME.lock() { *guard = mtime; }
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(_) => {
+            entities::logf(format!("config: {} not found, using defaults", path.display()));
+            store(Config::default());
+            return;
+        }
+    };
+
+    let entries = toml::parse(&text);
+    let mut cfg = Config::default();
+    let mut applied = 0usize;
+    let mut apply = |cfg: &mut Config, line_no: usize, key: &str, value: &Option<Value>| {
+        let ok = match value {
+            Some(v) => cfg.apply(key, v),
+            None => false,
+        };
+        if ok {
+            applied += 1;
+        } else {
+            entities::logf(format!("config: line {}: ignoring '{}'", line_no, key));
+        }
+    };
+
+    // Base settings first, then the active profile's overrides on top
+    for (line_no, key, value) in &entries {
+        if profile_key(key).is_none() { apply(&mut cfg, *line_no, key, value); }
+    }
+    for (_, key, _) in &entries {
+        if let Some((name, _)) = profile_key(key) {
+            if !cfg.profile_names.iter().any(|n| n == name) { cfg.profile_names.push(name.to_string()); }
+        }
+    }
+    let chosen = ACTIVE_PROFILE.lock().ok().and_then(|g| g.clone());
+    let active = chosen.unwrap_or_else(|| cfg.profile_default.clone());
+    if cfg.profile_names.contains(&active) {
+        for (line_no, key, value) in &entries {
+            match profile_key(key) {
+                Some((name, rest)) if name == active => apply(&mut cfg, *line_no, rest, value),
+                _ => {}
+            }
+        }
+        cfg.profile = active;
+    } else if !active.is_empty() {
+        entities::logf(format!("config: no profile '{}', using the base settings", active));
+    }
+
+    let profile = if cfg.profile.is_empty() { String::new() } else { format!(" (profile '{}')", cfg.profile) };
+    entities::logf(format!("config: loaded {} setting(s) from {}{}", applied, path.display(), profile));
+    store(cfg);
+}
+
+/// Reload the config file if it changed on disk since the last load, and
+/// tell the next frame (bus.rs). Called periodically from the worker thread.
+pub fn poll_reload() {
+    let mtime = std::fs::metadata(config_path()).and_then(|m| m.modified()).ok();
+    let changed = match LOADED_MTIME.lock() {
+        Ok(guard) => *guard != mtime,
+        Err(_) => false,
+    };
+    if changed {
+        load();
+        bus::publish(Event::ConfigReloaded);
+    }
+}
+
+// ============================================================
+// Remote Changes (control pipe, see ipc.rs)
+// ============================================================
+
+/// Check a `key` / value text pair against the settings. Profile keys are
+/// checked as the setting they override.
+#[cfg(feature = "ipc")]
+fn check_setting(key: &str, value: &str) -> Result<Value, String> {
+    let v = toml::parse_value(value).ok_or_else(|| format!("can't parse value '{}'", value))?;
+    let base = profile_key(key).map_or(key, |(_, k)| k);
+    if Config::default().apply(base, &v) { Ok(v) } else { Err(format!("unknown setting or wrong type: {}", key)) }
+}
+
+/// Apply one setting now, on top of the current settings (like the menu;
+/// the next file reload replaces it).
+#[cfg(feature = "ipc")]
+pub fn set_live(key: &str, value: &str) -> Result<(), String> {
+    let v = check_setting(key, value)?;
+    if profile_key(key).is_some() { return Err("profile settings can only be saved".to_string()); }
+    update(|cfg| { cfg.apply(key, &v); });
+    Ok(())
+}
+
+/// Write one setting into the config file: its line is rewritten (keeping
+/// the comment), or it's added to the end of its section. poll_reload picks
+/// the change up like a hand edit.
+#[cfg(feature = "ipc")]
+pub fn save_setting(key: &str, value: &str) -> Result<(), String> {
+    check_setting(key, value)?;
+    let path = config_path();
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    std::fs::write(&path, with_setting(&text, key, value))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Every setting in the config file as (key, value text), in file order.
+#[cfg(feature = "ipc")]
+pub fn file_settings() -> Vec<(String, String)> {
+    let text = std::fs::read_to_string(config_path()).unwrap_or_default();
+    toml::parse_raw(&text).into_iter().filter_map(|(_, k, v)| Some((k, v?))).collect()
+}
+
+/// Path of the config file (control pipe status, support bundles).
+pub fn file_path() -> std::path::PathBuf {
+    config_path()
+}
+
+/// `text` with `key` set to `value`.
+#[cfg(feature = "ipc")]
+fn with_setting(text: &str, key: &str, value: &str) -> String {
+    let (section, name) = key.rsplit_once('.').unwrap_or(("", key));
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut current = String::new();
+    let mut section_end = if section.is_empty() { Some(0) } else { None }; // Where to add it
+    for (i, raw) in text.lines().enumerate() {
+        let line = toml::strip_comment(raw).trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            current = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+        if current != section || line.is_empty() { continue; }
+        section_end = Some(i + 1);
+        if line.split_once('=').map(|(k, _)| k.trim()) == Some(name) {
+            let comment = &raw[toml::strip_comment(raw).len()..];
+            let indent = &raw[..raw.len() - raw.trim_start().len()];
+            let pad = if comment.is_empty() { "" } else { "  " };
+            lines[i] = format!("{}{} = {}{}{}", indent, name, value, pad, comment.trim_start());
+            return lines.join("\n") + "\n";
+        }
+    }
+    match section_end {
+        Some(at) => lines.insert(at, format!("{} = {}", name, value)),
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) { lines.push(String::new()); }
+            lines.push(format!("[{}]", section));
+            lines.push(format!("{} = {}", name, value));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+// ============================================================
+// Profiles
+// ============================================================
+
+/// Split a `profile.<name>.<key>` entry into (name, key). None = a base setting.
+fn profile_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("profile.")?.split_once('.')
+}
+
+/// Switch to profile `name` ("" = the base settings) and reload the file.
+/// Settings changed from the menu are replaced, as on any reload.
+pub fn select_profile(name: &str) {
+    if let Ok(mut g) = ACTIVE_PROFILE.lock() { *g = Some(name.to_string()); }
+    load();
+    PROFILE_SWITCHED.store(time::now_ms(), Ordering::Relaxed);
+}
+
+/// Switch to the next profile in file order; after the last, back to the base
+/// settings.
+pub fn next_profile() {
+    let cfg = get();
+    let next = match cfg.profile_names.iter().position(|n| *n == cfg.profile) {
+        Some(i) => cfg.profile_names.get(i + 1).cloned().unwrap_or_default(),
+        None    => cfg.profile_names.first().cloned().unwrap_or_default(),
+    };
+    select_profile(&next);
+}
+
+/// When the profile was last switched (time::now_ms(), 0 = never), for the
+/// on-screen notice.
+pub fn profile_switched_ms() -> u32 {
+    PROFILE_SWITCHED.load(Ordering::Relaxed)
+}
+
+/// Whether a console command points into the DLL, so it can't be unloaded.
+#[cfg(feature = "ipc")]
+pub fn command_registered() -> bool {
+    entities::commands_added()
+}
+
+/// Register `esp_profile [name]` once the engine table is known, if enabled.
+/// No name cycles like the hotkey; `esp_profile none` returns to the base
+/// settings. The engine can't unregister a command, so it's opt-in: typing it
+/// after the DLL is unloaded would call freed code.
+pub unsafe fn install_command() {
+    static ATTEMPTED: AtomicBool = AtomicBool::new(false);
+    if !get().profile_command || ATTEMPTED.swap(true, Ordering::Relaxed) { return; }
+    if entities::add_command(b"esp_profile\0", cmd_profile) {
+        entities::log("console command esp_profile registered");
+    } else {
+        entities::log("console command esp_profile: AddCommand not available");
+    }
+}
+
+unsafe extern "C" fn cmd_profile() {
+    match entities::cmd_args().first().map(String::as_str) {
+        None         => next_profile(),
+        Some("none") => select_profile(""),
+        Some(name)   => select_profile(name),
+    }
+}
+
+// ============================================================
+// Setting Values
+// ============================================================
+
+impl Value {
+    /// A screen corner: "top_left", "top_right", "bottom_left" or "bottom_right".
+    fn as_anchor(&self) -> Option<Anchor> {
+        self.as_str().and_then(Anchor::from_name)
+    }
+}
@@ -0,0 +1,171 @@
+// control.rs — The control pipe's line protocol (`\\.\pipe\goldsrc_overlay`).
+//
+// ipc.rs serves the pipe; external tools (the controller app in
+// `controller/`, scripts) write one request per line and read the reply:
+// zero or more data lines starting with "- ", then `ok` or `err <reason>`.
+//
+//   status                 - profile <name>, - visible <on|off>, - config <path>, - profiles <a,b>
+//   config                 - <section.key> = <value> for every setting in the file
+//   log [n]                - the last n log lines (default 50)
+//   set <key> <value>      apply a setting now, like the menu (value in TOML syntax)
+//   save <key> <value>     write a setting into esp_config.toml (reloaded from there)
+//   profile [name]         switch profile (no name = next, "none" = base settings)
+//   toggle                 flip overlay visibility, like F6
+//   visible <on|off>       show or hide the overlay
+//   eject                  unhook and unload the DLL (the pipe closes after the reply)
+//   selftest               - <item> pass|FAIL <detail> per hook health check, as
+//                          run at the next overlay frame (also shown in game)
+//   bundle                 - <path> of a support bundle (log, environment report,
+//                          entity dump, config, offsets) taken at the next frame
+//
+// Tools that can only post window messages (AutoHotkey, tray apps) use the
+// registered message `WINDOW_MESSAGE` instead (see input.rs), sent or
+// broadcast to the game window with wParam picking the command:
+//
+//   1 toggle   2 show   3 hide   4 next profile   5 base settings
+//   6 profile number lParam (1 = first [profile.*] in the file)
+//   7 sent by a second injected copy of the DLL as it unloads (lib.rs):
+//     logged, and the overlay is shown
+
+#[cfg(feature = "ipc")] pub const PIPE_NAME: &str = r"\\.\pipe\goldsrc_overlay";
+#[cfg(feature = "ipc")] pub const DEFAULT_LOG_LINES: usize = 50;
+#[cfg(feature = "ipc")] pub const MAX_LINE: usize = 4096; // Longer requests are rejected
+pub const WINDOW_MESSAGE: &str = "GoldSrcOverlayControl"; // RegisterWindowMessage name
+pub const MSG_DUPLICATE: usize = 7;    // wParam: another copy was injected and backed out
+
+/// A parsed request line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg(feature = "ipc")]
+pub enum Request {
+    Status,
+    Config,
+    Log(usize),
+    Set { key: String, value: String },
+    Save { key: String, value: String },
+    Profile(Option<String>),
+    Toggle,
+    Visible(bool),
+    Eject,
+    SelfTest,
+    Bundle,
+}
+
+#[cfg(feature = "ipc")]
+impl Request {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let setting = |rest: &str| -> Result<(String, String), String> {
+            match rest.split_once(char::is_whitespace) {
+                Some((k, v)) if !v.trim().is_empty() => Ok((k.to_string(), v.trim().to_string())),
+                _ => Err(format!("usage: {} <section.key> <value>", verb)),
+            }
+        };
+        match verb {
+            "status" => Ok(Request::Status),
+            "config" => Ok(Request::Config),
+            "log" if rest.is_empty() => Ok(Request::Log(DEFAULT_LOG_LINES)),
+            "log" => rest.parse().map(Request::Log).map_err(|_| "usage: log [lines]".to_string()),
+            "set" => setting(rest).map(|(key, value)| Request::Set { key, value }),
+            "save" => setting(rest).map(|(key, value)| Request::Save { key, value }),
+            "profile" => Ok(Request::Profile((!rest.is_empty()).then(|| rest.to_string()))),
+            "toggle" => Ok(Request::Toggle),
+            "visible" => match rest {
+                "on" => Ok(Request::Visible(true)),
+                "off" => Ok(Request::Visible(false)),
+                _ => Err("usage: visible <on|off>".to_string()),
+            },
+            "eject" => Ok(Request::Eject),
+            "selftest" => Ok(Request::SelfTest),
+            "bundle" => Ok(Request::Bundle),
+            "" => Err("empty request".to_string()),
+            _ => Err(format!("unknown request '{}'", verb)),
+        }
+    }
+}
+
+/// The request a posted `WINDOW_MESSAGE` stands for. `profiles` are the
+/// profile names in file order. None = unknown command or profile number.
+#[cfg(feature = "ipc")]
+pub fn message_request(wparam: usize, lparam: isize, profiles: &[String]) -> Option<Request> {
+    match wparam {
+        1 => Some(Request::Toggle),
+        2 => Some(Request::Visible(true)),
+        3 => Some(Request::Visible(false)),
+        4 => Some(Request::Profile(None)),
+        5 => Some(Request::Profile(Some("none".to_string()))),
+        6 => usize::try_from(lparam).ok()
+            .and_then(|n| profiles.get(n.checked_sub(1)?))
+            .map(|name| Request::Profile(Some(name.clone()))),
+        MSG_DUPLICATE => Some(Request::Visible(true)),
+        _ => None,
+    }
+}
+
+/// A reply: data lines, then the result line. Line breaks inside data are
+/// flattened so a line can't end the reply early.
+#[cfg(feature = "ipc")]
+pub fn reply(data: &[String], result: Result<(), String>) -> String {
+    let mut out = String::new();
+    for line in data {
+        out.push_str("- ");
+        out.push_str(&line.replace(['\r', '\n'], " "));
+        out.push('\n');
+    }
+    match result {
+        Ok(()) => out.push_str("ok\n"),
+        Err(e) => {
+            out.push_str("err ");
+            out.push_str(&e.replace(['\r', '\n'], " "));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(all(test, feature = "ipc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!(Request::parse("status\r\n"), Ok(Request::Status));
+        assert_eq!(Request::parse("log"), Ok(Request::Log(DEFAULT_LOG_LINES)));
+        assert_eq!(Request::parse("log 10"), Ok(Request::Log(10)));
+        assert!(Request::parse("log many").is_err());
+        assert_eq!(Request::parse("set box.thickness  2.5"),
+            Ok(Request::Set { key: "box.thickness".into(), value: "2.5".into() }));
+        assert_eq!(Request::parse("save theme \"high contrast\""),
+            Ok(Request::Save { key: "theme".into(), value: "\"high contrast\"".into() }));
+        assert!(Request::parse("set box.thickness").is_err());
+        assert_eq!(Request::parse("profile"), Ok(Request::Profile(None)));
+        assert_eq!(Request::parse("profile scrim"), Ok(Request::Profile(Some("scrim".into()))));
+        assert_eq!(Request::parse("visible off"), Ok(Request::Visible(false)));
+        assert!(Request::parse("visible maybe").is_err());
+        assert_eq!(Request::parse("eject"), Ok(Request::Eject));
+        assert_eq!(Request::parse("selftest"), Ok(Request::SelfTest));
+        assert_eq!(Request::parse("bundle"), Ok(Request::Bundle));
+        assert!(Request::parse("").is_err());
+        assert!(Request::parse("explode").is_err());
+    }
+
+    #[test]
+    fn maps_window_messages() {
+        let profiles = ["minimal".to_string(), "full".to_string()];
+        assert_eq!(message_request(1, 0, &profiles), Some(Request::Toggle));
+        assert_eq!(message_request(3, 0, &profiles), Some(Request::Visible(false)));
+        assert_eq!(message_request(5, 0, &profiles), Some(Request::Profile(Some("none".into()))));
+        assert_eq!(message_request(6, 2, &profiles), Some(Request::Profile(Some("full".into()))));
+        assert_eq!(message_request(6, 0, &profiles), None);
+        assert_eq!(message_request(6, 3, &profiles), None);
+        assert_eq!(message_request(MSG_DUPLICATE, 0, &profiles), Some(Request::Visible(true)));
+        assert_eq!(message_request(9, 0, &profiles), None);
+    }
+
+    #[test]
+    fn replies_end_with_a_result_line() {
+        assert_eq!(reply(&["a".into(), "b\nok".into()], Ok(())), "- a\n- b ok\nok\n");
+        assert_eq!(reply(&[], Err("no such key".into())), "err no such key\n");
+    }
+}
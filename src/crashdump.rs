@@ -0,0 +1,183 @@
+// crashdump.rs — Minidump and log tail when the overlay's own code faults.
+//
+// A vectored exception handler sees every exception in the process first.
+// It only acts on faults (fault.rs) whose address is inside this DLL: the
+// game's own exceptions, and the ones its code handles itself, go by
+// untouched. For one of ours it writes, to `crashes/` next to the DLL:
+//
+//   crash_<unix time>.dmp   MiniDumpWriteDump of the process (`[crash] full_memory`
+//                           for all of its memory instead of the stacks)
+//   crash_<unix time>.log   what faulted, where (DLL offset) and the log tail
+//
+// There's no resuming an instruction that faulted, so the exception is then
+// passed on (EXCEPTION_CONTINUE_SEARCH): the game's crash handler or Windows
+// Error Reporting gets it as before. The overlay is switched off first, so if
+// something up the chain does recover, our code isn't run again.
+//
+// The handler runs on the faulting thread with the process in whatever state
+// the fault left it in: it only reads atomics set up in advance, takes the
+// log lock only if it's free, and writes one report per session.
+
+use crate::config::Config;
+use crate::entities;
+use crate::esp;
+use crate::fault;
+use std::os::windows::io::AsRawHandle;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use winapi::shared::minwindef::{BOOL, DWORD};
+use winapi::um::errhandlingapi::{AddVectoredExceptionHandler, RemoveVectoredExceptionHandler};
+use winapi::um::libloaderapi::{GetProcAddress, LoadLibraryA};
+use winapi::um::processthreadsapi::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId};
+use winapi::um::psapi::{GetModuleInformation, MODULEINFO};
+use winapi::um::winnt::{EXCEPTION_POINTERS, HANDLE, LONG};
+
+const CRASH_DIR: &str = "crashes";
+const LOG_TAIL: usize = 200;                  // Log lines written with the dump
+const EXCEPTION_CONTINUE_SEARCH: LONG = 0;
+
+// MINIDUMP_TYPE flags (dbghelp.h)
+const MINIDUMP_NORMAL: u32 = 0x0000;
+const MINIDUMP_WITH_FULL_MEMORY: u32 = 0x0002;
+const MINIDUMP_WITH_INDIRECTLY_REFERENCED_MEMORY: u32 = 0x0040;
+const MINIDUMP_WITH_THREAD_INFO: u32 = 0x1000;
+
+/// MINIDUMP_EXCEPTION_INFORMATION (dbghelp.h).
+#[repr(C)]
+struct MinidumpExceptionInfo {
+    thread_id:       DWORD,
+    pointers:        *mut EXCEPTION_POINTERS,
+    client_pointers: BOOL,
+}
+
+/// dbghelp!MiniDumpWriteDump (winapi doesn't declare it).
+type FnMiniDumpWriteDump = unsafe extern "system" fn(
+    process: HANDLE, pid: DWORD, file: HANDLE, dump_type: u32,
+    exception: *const MinidumpExceptionInfo, user_streams: *const u8, callback: *const u8,
+) -> BOOL;
+
+/// Handle from AddVectoredExceptionHandler (0 = not installed).
+static HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+/// This DLL's image, start and end (the region the handler acts on).
+static MODULE_START: AtomicUsize = AtomicUsize::new(0);
+static MODULE_END: AtomicUsize = AtomicUsize::new(0);
+
+/// MiniDumpWriteDump, resolved at install (0 = dbghelp unavailable: log only).
+static WRITE_DUMP: AtomicUsize = AtomicUsize::new(0);
+
+/// `[crash]` settings, copied here so the handler never takes the config lock.
+static DUMP_ENABLED: AtomicBool = AtomicBool::new(true);
+static FULL_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// Set once our code has faulted; the overlay stays off from then on.
+static FAULTED: AtomicBool = AtomicBool::new(false);
+
+/// What faulted and the report's path, for the diagnostics page.
+static LAST: Mutex<Option<String>> = Mutex::new(None);
+
+/// Register the handler for the DLL loaded at `hinst`.
+pub unsafe fn install(hinst: usize) {
+    if HANDLER.load(Ordering::Relaxed) != 0 { return; }
+    let mut info: MODULEINFO = std::mem::zeroed();
+    let ok = GetModuleInformation(GetCurrentProcess(), hinst as _, &mut info,
+                                  std::mem::size_of::<MODULEINFO>() as u32);
+    if ok == 0 {
+        entities::log("crash handler: module range unknown; not installed");
+        return;
+    }
+    MODULE_START.store(info.lpBaseOfDll as usize, Ordering::Relaxed);
+    MODULE_END.store(info.lpBaseOfDll as usize + info.SizeOfImage as usize, Ordering::Relaxed);
+
+    // Loaded now rather than in the handler, where the loader lock may be held
+    let dbghelp = LoadLibraryA(c"dbghelp.dll".as_ptr());
+    if !dbghelp.is_null() {
+        WRITE_DUMP.store(GetProcAddress(dbghelp, c"MiniDumpWriteDump".as_ptr()) as usize, Ordering::Relaxed);
+    }
+    if WRITE_DUMP.load(Ordering::Relaxed) == 0 {
+        entities::log("crash handler: MiniDumpWriteDump unavailable; faults get a log only");
+    }
+
+    let h = AddVectoredExceptionHandler(1, Some(handler));
+    HANDLER.store(h as usize, Ordering::Release);
+    if h.is_null() { entities::log("crash handler: AddVectoredExceptionHandler failed"); }
+}
+
+/// Remove the handler (eject, before the DLL is unloaded).
+pub unsafe fn uninstall() {
+    let h = HANDLER.swap(0, Ordering::AcqRel);
+    if h != 0 { RemoveVectoredExceptionHandler(h as _); }
+}
+
+/// Pick up `[crash]` settings (at startup and after each config change).
+pub fn configure(cfg: &Config) {
+    DUMP_ENABLED.store(cfg.crash_dump, Ordering::Relaxed);
+    FULL_MEMORY.store(cfg.crash_full_memory, Ordering::Relaxed);
+}
+
+/// Whether the overlay's code has faulted (it isn't drawn again).
+pub fn faulted() -> bool {
+    FAULTED.load(Ordering::Relaxed)
+}
+
+/// The fault and where its report went, if there was one (diagnostics page).
+pub fn last() -> Option<String> {
+    LAST.try_lock().ok().and_then(|l| l.clone())
+}
+
+/// The vectored exception handler.
+unsafe extern "system" fn handler(pointers: *mut EXCEPTION_POINTERS) -> LONG {
+    if pointers.is_null() || (*pointers).ExceptionRecord.is_null() { return EXCEPTION_CONTINUE_SEARCH; }
+    let rec = &*(*pointers).ExceptionRecord;
+    let addr = rec.ExceptionAddress as usize;
+    let range = (MODULE_START.load(Ordering::Relaxed), MODULE_END.load(Ordering::Relaxed));
+    if !fault::is_fault(rec.ExceptionCode) || !fault::in_module(range, addr) {
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+    // One report per session: a fault while writing it, or the same fault
+    // coming around again, goes straight on
+    if FAULTED.swap(true, Ordering::AcqRel) { return EXCEPTION_CONTINUE_SEARCH; }
+    esp::set_visible(false);
+
+    let params = &rec.ExceptionInformation[..(rec.NumberParameters as usize).min(rec.ExceptionInformation.len())];
+    let what = fault::describe(rec.ExceptionCode, params);
+    let written = if DUMP_ENABLED.load(Ordering::Relaxed) { write_report(pointers, &what, addr, range.0) } else { None };
+    let summary = match &written {
+        Some(path) => format!("{} at overlay+{:#x}; report in {}", what, addr - range.0, path),
+        None       => format!("{} at overlay+{:#x}", what, addr - range.0),
+    };
+    if let Ok(mut l) = LAST.try_lock() { *l = Some(summary.clone()); }
+    entities::try_logf(format!("FAULT: {}; overlay switched off", summary));
+    EXCEPTION_CONTINUE_SEARCH
+}
+
+/// Write `crashes/crash_<time>.dmp` and `.log`. The stem's path, or None if
+/// the DLL's directory isn't known or nothing could be written.
+unsafe fn write_report(pointers: *mut EXCEPTION_POINTERS, what: &str, addr: usize, base: usize) -> Option<String> {
+    let dir = entities::try_dll_dir()?.join(CRASH_DIR);
+    std::fs::create_dir_all(&dir).ok()?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let stem = dir.join(fault::file_stem(stamp));
+    let thread = GetCurrentThreadId();
+
+    let tail = entities::try_recent_log(LOG_TAIL).unwrap_or_default();
+    let log_ok = std::fs::write(stem.with_extension("log"), fault::report(what, addr, base, thread, &tail)).is_ok();
+
+    let mut dump_ok = false;
+    let f = WRITE_DUMP.load(Ordering::Relaxed);
+    if f != 0 {
+        if let Ok(file) = std::fs::File::create(stem.with_extension("dmp")) {
+            let write: FnMiniDumpWriteDump = std::mem::transmute(f);
+            let info = MinidumpExceptionInfo { thread_id: thread, pointers, client_pointers: 0 };
+            let kind = if FULL_MEMORY.load(Ordering::Relaxed) {
+                MINIDUMP_WITH_FULL_MEMORY | MINIDUMP_WITH_THREAD_INFO
+            } else {
+                MINIDUMP_NORMAL | MINIDUMP_WITH_INDIRECTLY_REFERENCED_MEMORY | MINIDUMP_WITH_THREAD_INFO
+            };
+            dump_ok = write(GetCurrentProcess(), GetCurrentProcessId(), file.as_raw_handle() as HANDLE,
+                            kind, &info, std::ptr::null(), std::ptr::null()) != 0;
+        }
+    }
+    (log_ok || dump_ok).then(|| stem.display().to_string())
+}
@@ -0,0 +1,481 @@
+// diag.rs — On-screen diagnostics pages (F7 cycles: off -> status -> log -> tuner -> inspector -> off).
+//
+// Everything here is also in esp_debug.log, but reading the log means alt-tabbing
+// out of a fullscreen game. The status page shows where startup got to: hook
+// status, every hook with its target and state, engine table and
+// g_PlayerExtraInfo addresses, per-slot verification,
+// the structure offsets in use, the engine build and a frame counter, plus the
+// most recent failures. The log page shows the tail of the log itself.
+//
+// While the status page is up, F8 toggles the brush entity debug ESP (esp.rs)
+// and Home runs the hook self-test (selftest.rs), whose results get a panel
+// of their own for a while.
+//
+// The offset tuner page edits the build-dependent structure offsets
+// (offsets.rs) live, for bringing up a new engine build: PgUp/PgDn pick one,
+// [ and ] nudge it by 4 bytes (0x40 with Shift) while the boxes show whether
+// players line up, Home puts it back to the build 4554 value, and End saves
+// the working set to esp_offsets.toml, which is applied at every startup.
+//
+// The memory inspector page shows one player's cl_entity_t live (inspect.rs),
+// with the known fields named at the offsets in use: [ and ] pick the player,
+// PgUp/PgDn scroll (a row at a time with Shift), Home jumps to the next known
+// field and End cycles hex / int32 / float.
+//
+// The `[watch]` list (watch.rs) is shown in a panel of its own, whatever the
+// page, as long as it has entries.
+
+use crate::bus;
+use crate::config;
+use crate::crashdump;
+use crate::entities::{self, EngineApi, SlotCheck};
+use crate::esp;
+#[cfg(feature = "radar")]
+use crate::feed;
+use crate::hook::{self, HookState};
+use crate::inspect::{self, View, ROW_BYTES};
+use crate::input;
+#[cfg(feature = "ipc")]
+use crate::ipc;
+use crate::layout::{Layout, Placement};
+use crate::mapdata;
+use crate::offsets;
+use crate::player::{self, EngineReader};
+use crate::plugin;
+use crate::registry::State;
+use crate::selftest;
+use crate::slots::{self, ReadOutcome};
+use crate::subsystems;
+use crate::theme;
+use crate::ui::{self, Input, Style, UiState, Window};
+use crate::watch;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use winapi::shared::windef::HDC;
+
+const VK_F7: u32 = 0x76;              // Virtual key code for F7
+const VK_F8: u32 = 0x77;              // Toggles the brush entity ESP while the status page is up
+const PAGE_COUNT: u8 = 5;             // Off, status, log, offset tuner, memory inspector
+const PAGE_TUNER: u8 = 3;
+const PAGE_INSPECT: u8 = 4;
+
+// Offset tuner / memory inspector keys
+const VK_PRIOR: u32 = 0x21;           // PgUp: previous offset / scroll up
+const VK_NEXT: u32 = 0x22;            // PgDn: next offset / scroll down
+const VK_OEM_4: u32 = 0xDB;           // [ : decrease / previous player
+const VK_OEM_6: u32 = 0xDD;           // ] : increase / next player
+const VK_HOME: u32 = 0x24;            // Back to the build 4554 value / next known field / self-test
+const VK_END: u32 = 0x23;             // Save to esp_offsets.toml / next view
+const VK_SHIFT: u32 = 0x10;
+const NUDGE: usize = 4;               // Bytes per [ / ]
+const NUDGE_SHIFT: usize = 0x40;      // ... with Shift held
+const PANEL_W: f32 = 380.0;           // Panel width (pixels)
+const INSPECT_W: f32 = 560.0;         // Memory inspector width (room for two float columns and names)
+const INSPECT_ROWS: usize = 16;       // Rows of memory shown
+const INSPECT_MAX: usize = 0x1000;    // Furthest into cl_entity_t the window scrolls
+const LOG_LINES: usize = 24;          // Log lines shown on the log page
+const ERROR_LINES: usize = 4;         // Recent failures shown on the status page
+
+const COLOR_TITLE: [f32; 4] = [1.0, 0.15, 0.15, 1.0];
+const COLOR_TEXT:  [f32; 4] = [0.90, 0.90, 0.90, 1.0];
+const COLOR_OK:    [f32; 4] = [0.10, 0.95, 0.10, 1.0];
+const COLOR_BAD:   [f32; 4] = [1.0, 0.35, 0.15, 1.0];
+
+const STYLE: Style = Style {
+    row_h:    12.0,
+    pad:      5.0,
+    slider_w: 0.0,
+    bg:       [0.0, 0.0, 0.0, 0.70],
+    border:   None,
+    hover:    [0.0; 4],
+    text:     COLOR_TEXT,
+    accent:   COLOR_TITLE,
+    track:    [0.0; 4],
+};
+
+/// Current page (0 = hidden).
+static PAGE: AtomicU8 = AtomicU8::new(0);
+
+/// Previous F7 / F8 state when polling without the WndProc subclass.
+static F7_PREV: AtomicBool = AtomicBool::new(false);
+static F8_PREV: AtomicBool = AtomicBool::new(false);
+
+/// Tuner / inspector key states when polling without the WndProc subclass,
+/// in the order PgUp, PgDn, [, ], Home, End.
+static PAGE_KEYS_PREV: [AtomicBool; 6] = [
+    AtomicBool::new(false), AtomicBool::new(false), AtomicBool::new(false),
+    AtomicBool::new(false), AtomicBool::new(false), AtomicBool::new(false),
+];
+
+/// Selected row on the tuner page.
+static TUNER_UI: Mutex<UiState> = Mutex::new(UiState::new());
+
+/// Memory inspector: player slot, window start (offset into cl_entity_t) and
+/// view (index into View::ALL).
+static INSPECT_SLOT: AtomicI32 = AtomicI32::new(1);
+static INSPECT_AT: AtomicUsize = AtomicUsize::new(0);
+static INSPECT_VIEW: AtomicU8 = AtomicU8::new(0);
+
+/// Overlay frames drawn since injection.
+static FRAMES: AtomicU32 = AtomicU32::new(0);
+
+/// Count one overlay frame.
+pub fn count_frame() {
+    FRAMES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Overlay frames drawn since injection.
+pub fn frames() -> u32 {
+    FRAMES.load(Ordering::Relaxed)
+}
+
+/// Handle F7 / F8 and draw the current page. Called once per overlay frame,
+/// inside the 2D drawing block.
+pub unsafe fn frame(hdc: HDC, layout: &mut Layout) {
+    if input::key_pressed(VK_F7, &F7_PREV) {
+        let next = (PAGE.load(Ordering::Relaxed) + 1) % PAGE_COUNT;
+        PAGE.store(next, Ordering::Relaxed);
+    }
+    // Read every frame so a press on another page isn't acted on later
+    if input::key_pressed(VK_F8, &F8_PREV) && PAGE.load(Ordering::Relaxed) == 1 {
+        config::update(|c| c.debug_brushes = !c.debug_brushes);
+    }
+
+    let keys = [VK_PRIOR, VK_NEXT, VK_OEM_4, VK_OEM_6, VK_HOME, VK_END];
+    let page_keys: Vec<bool> = keys.iter().zip(&PAGE_KEYS_PREV).map(|(&vk, prev)| input::key_pressed(vk, prev)).collect();
+    if page_keys[4] && PAGE.load(Ordering::Relaxed) == 1 { selftest::request(); }
+    selftest::frame();
+
+    watch_panel(hdc, layout);
+    selftest_panel(hdc, layout);
+
+    let lines = match PAGE.load(Ordering::Relaxed) {
+        1 => status_page(),
+        2 => log_page(),
+        PAGE_TUNER => return tuner_page(hdc, layout, &page_keys),
+        PAGE_INSPECT => inspect_page(&page_keys),
+        _ => return,
+    };
+    let width = if PAGE.load(Ordering::Relaxed) == PAGE_INSPECT { INSPECT_W } else { PANEL_W };
+    draw_panel(hdc, layout, config::get().diag_place, width, &lines);
+}
+
+/// The `[watch]` list, read this frame, in its own panel (hidden while the
+/// list is empty).
+unsafe fn watch_panel(hdc: HDC, layout: &mut Layout) {
+    let cfg = config::get();
+    if cfg.watch_list.is_empty() { return; }
+    let mut lines = vec![("WATCH".to_string(), COLOR_TITLE)];
+    for (label, w) in &cfg.watch_list {
+        lines.push(match entities::read_watch(w) {
+            Ok(bytes) => (format!("{:<14} {}", label, watch::format_value(w.kind, &bytes)), COLOR_TEXT),
+            Err(e)    => (format!("{:<14} {}", label, e), COLOR_BAD),
+        });
+    }
+    draw_panel(hdc, layout, cfg.watch_place, PANEL_W, &lines);
+}
+
+/// The last self-test's results, for SHOW_MS after it ran.
+unsafe fn selftest_panel(hdc: HDC, layout: &mut Layout) {
+    let Some(checks) = selftest::shown() else { return };
+    let mut lines = vec![("SELF-TEST".to_string(), COLOR_TITLE)];
+    for c in &checks {
+        lines.push(match &c.result {
+            Ok(detail) => (format!("{:<13} pass  {}", c.name, detail), COLOR_OK),
+            Err(e)     => (format!("{:<13} FAIL  {}", c.name, e), COLOR_BAD),
+        });
+    }
+    draw_panel(hdc, layout, config::get().diag_place, PANEL_W, &lines);
+}
+
+/// Lines of the status page, each with its color.
+pub unsafe fn status_page() -> Vec<(String, [f32; 4])> {
+    let mut out = vec![("DIAGNOSTICS  (F7: log page, Home: self-test)".to_string(), COLOR_TITLE)];
+    let ok = |good: bool| if good { COLOR_OK } else { COLOR_BAD };
+
+    let (status_text, color) = match entities::hook_status() {
+        Some(Ok(()))  => ("ok".to_string(), COLOR_OK),
+        Some(Err(e))  => (e.to_string(), COLOR_BAD),
+        None          => ("pending (client.dll not loaded yet)".to_string(), COLOR_TEXT),
+    };
+    out.push((format!("Initialize hook: {}", status_text), color));
+
+    let table = entities::engine_table();
+    out.push((format!("engine table:    {:#010x}", table), ok(table != 0)));
+    let game_mod = entities::game_mod();
+    out.push((format!("mod:             {}", game_mod.name()), COLOR_TEXT));
+    let extra = entities::extra_info_base();
+    let extra_text = match entities::extra_info_error() {
+        Some(e) if extra == 0 => e.to_string(),
+        _ => format!("{:#010x}", extra),
+    };
+    out.push((format!("extra info:      {}", extra_text), ok(extra != 0 || !game_mod.profile().extra_info)));
+    let hulls = entities::hulls();
+    out.push((format!("hulls:           standing {:.0}, ducking {:.0}", hulls.stand, hulls.duck), COLOR_TEXT));
+    out.push((format!("alive from:      {}", entities::alive_source()), COLOR_TEXT));
+    out.push((format!("map changes:     {} (pointers re-validated)", entities::map_changes()), COLOR_TEXT));
+    let dropped = bus::dropped();
+    if dropped > 0 {
+        out.push((format!("frame events:    {} dropped (queue full)", dropped), COLOR_BAD));
+    }
+    let build = entities::engine_build().unwrap_or_else(|| "?".to_string());
+    out.push((format!("engine build:    {} (offsets for 4554)", build), COLOR_TEXT));
+    if let Some(path) = hook::present_path_label() {
+        out.push((format!("present path:    {}", path), COLOR_TEXT));
+    }
+    out.push((format!("frames:          {}", FRAMES.load(Ordering::Relaxed)), COLOR_TEXT));
+    let (cost_us, level) = esp::frame_cost();
+    out.push((format!("overlay time:    {:.0} us, {} element(s) shed", cost_us, level), ok(level == 0)));
+    let slowest: Vec<String> = esp::slowest_subsystems(3).iter().map(|(name, us)| format!("{} {:.0} us", name, us)).collect();
+    if !slowest.is_empty() {
+        out.push((format!("slowest parts:   {}", slowest.join(", ")), COLOR_TEXT));
+    }
+    match crashdump::last() {
+        Some(f) => out.push((format!("fault:           {}", f), COLOR_BAD)),
+        None    => out.push(("fault:           none".to_string(), COLOR_OK)),
+    }
+    let (panics, last_minute, off) = esp::panic_report();
+    let off = if off.is_empty() { String::new() } else { format!(", switched off: {}", off.join(", ")) };
+    out.push((format!("panics:          {} ({} in the last minute){}", panics, last_minute, off), ok(panics == 0)));
+    match esp::frame_pacing() {
+        Some(p) => {
+            let cap = p.cap.map_or("not capped".to_string(), |c| format!("capped at ~{}", c));
+            out.push((format!("game fps:        {:.0} ({}), jitter {:.2} ms", p.fps, cap, p.jitter_ms), COLOR_TEXT));
+            let blame = if p.overlay_suspect() { "overlay" } else if p.spikes > 0 { "game / server" } else { "-" };
+            out.push((format!("frame spikes:    {} ({} with a slow overlay): {}", p.spikes, p.overlay_spikes, blame),
+                ok(!p.overlay_suspect())));
+        }
+        None => out.push(("game fps:        measuring...".to_string(), COLOR_TEXT)),
+    }
+    let camera = match entities::refdef(1000) {
+        Some(r) => format!("({:.0}, {:.0}, {:.0}) yaw {:.0} fov {:.0}", r.origin.x, r.origin.y, r.origin.z, r.angles.y, r.fov),
+        None    => "not captured".to_string(),
+    };
+    out.push((format!("camera:          {}", camera), COLOR_TEXT));
+    if let Some(api) = EngineApi::resolve() {
+        let ents: Vec<_> = api.iter_entities().collect();
+        let current = ents.iter().filter(|e| e.current).count();
+        let players = ents.iter().filter(|e| e.is_player).count();
+        out.push((format!("entities:        {} in use ({} current, {} players)", ents.len(), current, players),
+            COLOR_TEXT));
+        let map = match mapdata::current(&api) {
+            Some(m) => format!("{} ({} brush models)", m.level, m.brush_classes.len()),
+            None    => "not loaded".to_string(),
+        };
+        out.push((format!("map data:        {}", map), COLOR_TEXT));
+    }
+    let cfg = config::get();
+    let profile = if cfg.profile.is_empty() { "(base settings)" } else { cfg.profile.as_str() };
+    out.push((format!("profile:         {} of {}", profile, cfg.profile_names.len()), COLOR_TEXT));
+    out.push((format!("brush ESP (F8):  {} [{}]", if cfg.debug_brushes { "on" } else { "off" },
+        cfg.debug_classes.join(", ")), ok(cfg.debug_brushes)));
+    #[cfg(feature = "radar")]
+    if cfg.feed_enabled {
+        let (text, good) = match feed::error() {
+            Some(e) => (e, false),
+            None    => (format!("{}:{}, {} client(s)", cfg.feed_bind, cfg.feed_port, feed::clients()), true),
+        };
+        out.push((format!("live feed:       {}", text), ok(good)));
+    }
+    #[cfg(feature = "ipc")]
+    if cfg.control_pipe {
+        let state = if ipc::connected() { "client connected" } else { "listening" };
+        out.push((format!("control pipe:    {}", state), COLOR_OK));
+    }
+    let plugins = plugin::names();
+    if !plugins.is_empty() {
+        out.push((format!("plugins:         {}", plugins.join(", ")), COLOR_OK));
+    }
+    let modules = subsystems::list();
+    if !modules.is_empty() {
+        let failed = modules.iter().any(|(_, _, s)| matches!(s, State::Failed(_)));
+        let text: Vec<String> = modules.into_iter().map(|(name, _, state)| match state {
+            State::Running    => format!("{} on", name),
+            State::Stopped    => format!("{} off", name),
+            State::Starting   => format!("{} starting", name),
+            State::Failed(e)  => format!("{} failed ({})", name, e),
+        }).collect();
+        out.push((format!("modules:         {}", text.join(", ")), ok(!failed)));
+    }
+
+    out.push(("hooks:".to_string(), COLOR_TITLE));
+    for h in hook::hooks() {
+        let state = match &h.state {
+            HookState::Enabled   => "enabled".to_string(),
+            HookState::Disabled  => "disabled".to_string(),
+            HookState::Failed(e) => format!("failed: {}", e),
+        };
+        out.push((format!("  {:<32} {:<7} {:#010x} {}", h.name, format!("{:?}", h.kind), h.target, state),
+                  ok(h.state == HookState::Enabled)));
+    }
+
+    let suspect = entities::sanity_status();
+    out.push((format!("sanity:          {} rejected since injection, {} slot(s) failing now",
+        entities::sanity_rejects(), suspect.len()), ok(suspect.is_empty())));
+    for (idx, s) in suspect {
+        let verdict = if s.rejected() { "rejected" } else { "kept" };
+        out.push((format!("  P{:<2} {:<8} {}", idx, verdict, s.reasons().join(", ")), ok(!s.rejected())));
+    }
+
+    let reads = entities::read_outcomes();
+    let missing = slots::missing(&reads);
+    out.push((format!("player reads:    {} read{}{}", reads.iter().filter(|&&r| r == ReadOutcome::Read).count(),
+        if missing.is_empty() { "" } else { ", " }, missing), COLOR_TEXT));
+
+    out.push(("engine slots:".to_string(), COLOR_TITLE));
+    for (&(slot, name, _), st) in entities::VERIFIED_SLOTS.iter().zip(entities::slot_status()) {
+        out.push((format!("  {:>3} {:<18} {:?}", slot, name, st), ok(st == SlotCheck::Ok)));
+    }
+    let failures = entities::engine_fn_failures();
+    if !failures.is_empty() {
+        let list: Vec<String> = failures.iter().map(|(f, n)| format!("{:?} {}", f, n)).collect();
+        out.push((format!("  failed calls: {}", list.join(", ")), COLOR_BAD));
+    }
+
+    out.push(("offsets:".to_string(), COLOR_TITLE));
+    for (name, off, default) in entities::offsets() {
+        if off == default {
+            out.push((format!("  {:<20} {:#x}", name, off), COLOR_TEXT));
+        } else {
+            out.push((format!("  {:<20} {:#x} (4554: {:#x})", name, off, default), COLOR_BAD));
+        }
+    }
+
+    let errors: Vec<String> = entities::recent_log(200).into_iter()
+        .filter(|l| { let l = l.to_ascii_lowercase(); l.contains("fail") || l.contains("err") })
+        .collect();
+    if !errors.is_empty() {
+        out.push(("last errors:".to_string(), COLOR_TITLE));
+        for e in &errors[errors.len().saturating_sub(ERROR_LINES)..] {
+            out.push((format!("  {}", e), COLOR_BAD));
+        }
+    }
+    out
+}
+
+/// Lines of the log page.
+fn log_page() -> Vec<(String, [f32; 4])> {
+    let mut out = vec![("LOG  (F7: offset tuner)".to_string(), COLOR_TITLE)];
+    out.extend(entities::recent_log(LOG_LINES).into_iter().map(|l| (l, COLOR_TEXT)));
+    out
+}
+
+/// The offset tuner: one stepper per overridable offset, driven by the tuner
+/// keys (`keys` = this frame's presses, in PAGE_KEYS_PREV order), and what the
+/// current offsets make of the player slots.
+unsafe fn tuner_page(hdc: HDC, layout: &mut Layout, keys: &[bool]) {
+    let Ok(mut state) = TUNER_UI.lock() else { return };
+    let (home, end) = (keys[4], keys[5]);
+    let shift = input::key_down(VK_SHIFT);
+    let step = if shift { NUDGE_SHIFT } else { NUDGE };
+    let input = Input { prev: keys[0], next: keys[1], left: keys[2], right: keys[3], ..Input::NONE };
+
+    let summary = players_summary();
+    let rows = offsets::ALL.len() + 3;
+    let rect = layout.place(config::get().diag_place, PANEL_W, ui::window_height(&STYLE, rows));
+    let mut w = Window::begin(hdc, &STYLE, &mut state, input, rect, "OFFSET TUNER  (F7: memory inspector)");
+    for o in offsets::ALL {
+        let mut v = o.get();
+        let text = if v == o.default_value() {
+            format!("{:#x}", v)
+        } else {
+            format!("{:#x} (4554: {:#x})", v, o.default_value())
+        };
+        if w.stepper(o.name(), &mut v, step, &text) { o.set(v); }
+    }
+    w.label(&summary, COLOR_TEXT);
+    w.label("PgUp/PgDn pick  [ ] nudge (Shift: 0x40)", COLOR_TEXT);
+    w.label("Home: 4554 value  End: save esp_offsets.toml", COLOR_TEXT);
+    w.end();
+
+    if home {
+        if let Some(&o) = offsets::ALL.get(state.selected()) { o.set(o.default_value()); }
+    }
+    if end {
+        if let Err(e) = entities::save_offsets() {
+            entities::logf(format!("saving offsets failed: {}", e));
+        }
+    }
+}
+
+/// Lines of the memory inspector, after applying its keys (`keys` as for
+/// tuner_page).
+unsafe fn inspect_page(keys: &[bool]) -> Vec<(String, [f32; 4])> {
+    let shift = input::key_down(VK_SHIFT);
+    let page = if shift { ROW_BYTES } else { ROW_BYTES * INSPECT_ROWS };
+    let last = INSPECT_MAX - ROW_BYTES * INSPECT_ROWS;
+    let fields = inspect::fields();
+
+    let mut slot = INSPECT_SLOT.load(Ordering::Relaxed);
+    if keys[2] { slot = if slot <= 1 { player::MAX_CLIENTS } else { slot - 1 }; }
+    if keys[3] { slot = if slot >= player::MAX_CLIENTS { 1 } else { slot + 1 }; }
+    INSPECT_SLOT.store(slot, Ordering::Relaxed);
+
+    let mut at = INSPECT_AT.load(Ordering::Relaxed);
+    if keys[0] { at = at.saturating_sub(page); }
+    if keys[1] { at += page; }
+    if keys[4] {
+        // Jumped-to fields sit on the second row; move on to the first one past it
+        if let Some(off) = inspect::next_field(at + 2 * ROW_BYTES - 1, &fields) {
+            at = (off / ROW_BYTES).saturating_sub(1) * ROW_BYTES;
+        }
+    }
+    at = (at / ROW_BYTES * ROW_BYTES).min(last);
+    INSPECT_AT.store(at, Ordering::Relaxed);
+
+    let mut view = INSPECT_VIEW.load(Ordering::Relaxed) as usize % View::ALL.len();
+    if keys[5] { view = (view + 1) % View::ALL.len(); }
+    INSPECT_VIEW.store(view as u8, Ordering::Relaxed);
+    let view = View::ALL[view];
+
+    let mut out = vec![("MEMORY INSPECTOR  (F7: close)".to_string(), COLOR_TITLE)];
+    let base = EngineApi::resolve().map(|api| api.entity(slot)).unwrap_or(0);
+    out.push((format!("player {} cl_entity_t {:#010x}  {}", slot, base, view.name()), COLOR_TEXT));
+    for row in 0..INSPECT_ROWS {
+        let off = at + row * ROW_BYTES;
+        let bytes = if base == 0 { None } else { entities::read_bytes(base + off, ROW_BYTES) };
+        let named = fields.iter().any(|f| (off..off + ROW_BYTES).contains(&f.0));
+        let color = if bytes.is_none() { COLOR_BAD } else if named { COLOR_OK } else { COLOR_TEXT };
+        out.push((inspect::format_row(off, bytes.as_deref(), view, &fields), color));
+    }
+    out.push(("[ ] player  PgUp/PgDn scroll (Shift: row)".to_string(), COLOR_TEXT));
+    out.push(("Home: next known field  End: hex/int32/float".to_string(), COLOR_TEXT));
+    out
+}
+
+/// What the current offsets read from the player slots: how many interpret
+/// as players, and their teams (wrong extra_info offsets show up as team 0).
+unsafe fn players_summary() -> String {
+    let Some(api) = EngineApi::resolve() else { return "players: engine not resolved".to_string() };
+    let mut teams = [0usize; 3];
+    let mut dead = 0;
+    for idx in 1..=player::MAX_CLIENTS {
+        let Some(p) = player::interpret_player(&api, idx) else { continue };
+        teams[theme::team_index(p.team)] += 1;
+        if p.is_dead { dead += 1; }
+    }
+    format!("players: {} read (T {}, CT {}, other {}), {} dead",
+        teams.iter().sum::<usize>(), teams[1], teams[2], teams[0], dead)
+}
+
+/// Draw `lines` (the first is the title) in a panel `width` pixels wide at
+/// `place`.
+pub unsafe fn draw_panel(hdc: HDC, layout: &mut Layout, place: Placement, width: f32, lines: &[(String, [f32; 4])]) {
+    draw_panel_with_area(hdc, layout, place, width, lines, 0);
+}
+
+/// draw_panel with `area_rows` more rows below the text, left empty for the
+/// caller to draw into: their rect (None without lines).
+pub unsafe fn draw_panel_with_area(hdc: HDC, layout: &mut Layout, place: Placement, width: f32,
+                                   lines: &[(String, [f32; 4])], area_rows: usize) -> Option<[f32; 4]> {
+    let ((title, _), rows) = lines.split_first()?;
+    let rect = layout.place(place, width, ui::window_height(&STYLE, rows.len() + area_rows));
+    let mut state = UiState::new();
+    let mut w = Window::begin(hdc, &STYLE, &mut state, Input::NONE, rect, title);
+    for (text, color) in rows {
+        w.label(text, *color);
+    }
+    let area = w.area(area_rows);
+    w.end();
+    Some(area)
+}
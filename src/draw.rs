@@ -0,0 +1,481 @@
+// draw.rs — Draw lists: what the overlay draws, as data.
+//
+// The ESP logic builds a DrawList of rect, line, circle and text commands with
+// their styles; render.rs turns a list into GL calls (render::draw_list). The
+// box pass (boxes, snap-lines, health badges and the laid-out labels) is built
+// here from plain inputs rather than in esp.rs, so it runs off the game too:
+// the snapshot replays in snapshots/ compare its output with golden files, and
+// another backend (an external window, GDI) would only need its own draw_list.
+
+use crate::theme::{Palette, Shade};
+use crate::view::{self, Label, NameFit};
+use std::fmt;
+
+const HEALTH_BADGE_PX: f32 = 5.0;      // Side of the health badge square
+const SHADOW: [f32; 4] = [0.0, 0.0, 0.0, 0.6]; // Dark outline behind every box
+const FRAME_CORNER_EXTRA: f32 = 1.0;    // Extra width of the corners over a framed box's outline
+
+// ============================================================
+// Styles
+// ============================================================
+
+/// How lines are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineStyle {
+    pub width:  f32,  // Pixels
+    pub smooth: bool, // GL_LINE_SMOOTH (anti-aliased)
+}
+
+impl LineStyle {
+    /// The overlay's original look; also used for text and the menu.
+    pub const DEFAULT: LineStyle = LineStyle { width: 1.5, smooth: false };
+}
+
+/// How a box's corners are drawn (`[box] corners`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BracketStyle {
+    #[default]
+    Classic, // An L at each corner, about a fifth of the side
+    Short,   // Small ticks at the corners
+    Frame,   // The whole outline, corners drawn thicker
+    Dashed,  // The whole outline, dashed
+}
+
+/// Line pattern, for lines that should read as less certain than the rest:
+/// cached boxes, players outside the PVS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Dash {
+    /// Parse a config name ("solid", "dashed", "dotted").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "solid"  => Some(Dash::Solid),
+            "dashed" => Some(Dash::Dashed),
+            "dotted" => Some(Dash::Dotted),
+            _ => None,
+        }
+    }
+
+    /// On and off lengths (px); None = solid.
+    pub fn pattern(self) -> Option<[f32; 2]> {
+        match self {
+            Dash::Solid  => None,
+            Dash::Dashed => Some([4.0, 3.0]),
+            Dash::Dotted => Some([1.5, 2.5]),
+        }
+    }
+
+    /// `from`..`to` in this pattern.
+    pub fn segments(self, from: [f32; 2], to: [f32; 2]) -> Vec<Seg> {
+        match self.pattern() {
+            Some(p) => dash(from, to, p),
+            None => vec![(from, to)],
+        }
+    }
+}
+
+/// A line segment, from and to.
+pub type Seg = ([f32; 2], [f32; 2]);
+
+/// The segments of a box's brackets in `rect`, in runs of one width: each
+/// with its extra width over the box line. A `line` pattern cuts every
+/// segment (and replaces the dashed style's own dashes).
+pub fn brackets(rect: [f32; 4], kind: BracketStyle, line: Dash) -> Vec<(f32, Vec<Seg>)> {
+    let [x0, y0, x1, y1] = rect;
+    let (bw, bh) = (x1 - x0, y1 - y0);
+    let ls = |frac: f32, min: f32, max: f32| ((bw * frac).clamp(min, max), (bh * frac).clamp(min, max));
+    let corners = |(lw, lh): (f32, f32)| vec![
+        ([x0, y0], [x0 + lw, y0]), ([x0, y0], [x0, y0 + lh]),
+        ([x1, y0], [x1 - lw, y0]), ([x1, y0], [x1, y0 + lh]),
+        ([x0, y1], [x0 + lw, y1]), ([x0, y1], [x0, y1 - lh]),
+        ([x1, y1], [x1 - lw, y1]), ([x1, y1], [x1, y1 - lh]),
+    ];
+    let outline = [([x0, y0], [x1, y0]), ([x1, y0], [x1, y1]), ([x1, y1], [x0, y1]), ([x0, y1], [x0, y0])];
+    let runs = match kind {
+        BracketStyle::Classic => vec![(0.0, corners(ls(0.22, 4.0, 18.0)))],
+        BracketStyle::Short   => vec![(0.0, corners(ls(0.10, 2.0, 6.0)))],
+        BracketStyle::Frame   => vec![(0.0, outline.to_vec()), (FRAME_CORNER_EXTRA, corners(ls(0.22, 4.0, 18.0)))],
+        BracketStyle::Dashed  => {
+            let line = if line == Dash::Solid { Dash::Dashed } else { line };
+            return vec![(0.0, outline.iter().flat_map(|&(a, b)| line.segments(a, b)).collect())];
+        }
+    };
+    if line == Dash::Solid { return runs; }
+    runs.into_iter().map(|(extra, segs)| (extra, segs.into_iter().flat_map(|(a, b)| line.segments(a, b)).collect())).collect()
+}
+
+/// `from`..`to` cut into dashes: `pattern` is the on and off length (px),
+/// starting with a dash at `from`. The last dash is cut short at `to`.
+pub fn dash(from: [f32; 2], to: [f32; 2], pattern: [f32; 2]) -> Vec<Seg> {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let len = (dx * dx + dy * dy).sqrt();
+    let [on, off] = pattern;
+    if on <= 0.0 || off <= 0.0 || len <= on { return vec![(from, to)]; }
+    let at = |d: f32| [from[0] + dx * d / len, from[1] + dy * d / len];
+    let mut segs = Vec::new();
+    let mut d = 0.0;
+    while d < len {
+        segs.push((at(d), at((d + on).min(len))));
+        d += on + off;
+    }
+    segs
+}
+
+/// Which point of the player box snap-lines end at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapTarget { Feet, Center, Head }
+
+// ============================================================
+// Commands
+// ============================================================
+
+/// One drawing command. Coordinates are pixels, top-left origin.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cmd {
+    Rect    { rect: [f32; 4], shade: Shade, style: LineStyle },  // Outline
+    Corners { rect: [f32; 4], shade: Shade, style: LineStyle, kind: BracketStyle, line: Dash },  // Corner brackets
+    Fill    { rect: [f32; 4], color: [f32; 4] },
+    Line    { from: [f32; 2], to: [f32; 2], color: [f32; 4], style: LineStyle },
+    Circle  { center: [f32; 2], radius: f32, segments: u32, color: [f32; 4], style: LineStyle },
+    Text    { at: [f32; 2], text: String, color: [f32; 4], scale: f32 },
+}
+
+/// Commands in drawing order (later ones on top).
+#[derive(Clone, Debug, Default)]
+pub struct DrawList {
+    cmds: Vec<Cmd>,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        DrawList::default()
+    }
+
+    pub fn push(&mut self, cmd: Cmd) {
+        self.cmds.push(cmd);
+    }
+
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], color: [f32; 4], style: LineStyle) {
+        self.push(Cmd::Line { from, to, color, style });
+    }
+
+    pub fn text(&mut self, at: [f32; 2], text: impl Into<String>, color: [f32; 4], scale: f32) {
+        self.push(Cmd::Text { at, text: text.into(), color, scale });
+    }
+
+    pub fn cmds(&self) -> &[Cmd] {
+        &self.cmds
+    }
+
+    /// Append the laid-out `labels`, nearest (first queued) on top.
+    pub fn labels(&mut self, labels: &mut [Label]) {
+        view::layout_labels(labels);
+        for l in labels.iter().rev() {
+            self.text([l.x, l.y], l.text.as_str(), l.color, l.scale);
+        }
+    }
+}
+
+fn rgba(c: [f32; 4]) -> String {
+    format!("{:.2},{:.2},{:.2},{:.2}", c[0], c[1], c[2], c[3])
+}
+
+fn shade(s: &Shade) -> String {
+    match s {
+        Shade::Solid(c)       => rgba(*c),
+        Shade::Gradient(a, b) => format!("{}>{}", rgba(*a), rgba(*b)),
+        Shade::TwoTone(a, b)  => format!("{}|{}", rgba(*a), rgba(*b)),
+    }
+}
+
+/// One line per command, coordinates to 0.1 px (the golden file format).
+impl fmt::Display for Cmd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = |r: &[f32; 4]| format!("{:.1} {:.1} {:.1} {:.1}", r[0], r[1], r[2], r[3]);
+        let w = |s: &LineStyle| format!("w={:.1}{}", s.width, if s.smooth { " aa" } else { "" });
+        match self {
+            Cmd::Rect { rect, shade: s, style } => write!(f, "rect {} {} {}", r(rect), shade(s), w(style)),
+            Cmd::Corners { rect, shade: s, style, kind, line } => {
+                write!(f, "corners {} {} {}", r(rect), shade(s), w(style))?;
+                if *kind != BracketStyle::Classic { write!(f, " {:?}", kind)?; }
+                if *line != Dash::Solid { write!(f, " {:?}", line)?; }
+                Ok(())
+            }
+            Cmd::Fill { rect, color } => write!(f, "fill {} {}", r(rect), rgba(*color)),
+            Cmd::Line { from, to, color, style } => write!(f, "line {:.1} {:.1} {:.1} {:.1} {} {}",
+                from[0], from[1], to[0], to[1], rgba(*color), w(style)),
+            Cmd::Circle { center, radius, segments, color, style } => write!(f, "circle {:.1} {:.1} r={:.1} n={} {} {}",
+                center[0], center[1], radius, segments, rgba(*color), w(style)),
+            Cmd::Text { at, text, color, scale } if *scale != 1.0 => write!(f, "text {:.1} {:.1} {} {:?} x{:.2}",
+                at[0], at[1], rgba(*color), text, scale),
+            Cmd::Text { at, text, color, .. } => write!(f, "text {:.1} {:.1} {} {:?}", at[0], at[1], rgba(*color), text),
+        }
+    }
+}
+
+// ============================================================
+// Box Pass
+// ============================================================
+// Fresh and cached boxes are collected for the whole frame first, then sorted
+// far-to-near and drawn, so a distant box can never cover a closer one.
+
+/// Everything needed to draw one player's box and labels.
+#[derive(Clone, Debug)]
+pub struct BoxDraw {
+    pub rect:       [f32; 4],       // [x0, y0, x1, y1] in pixels
+    pub feet:       [f32; 2],       // Snap-line target in pixels
+    pub dist:       f32,            // Distance in meters (sort key)
+    pub color:      [f32; 4],       // Corner bracket color (alpha already applied)
+    pub team:       i32,            // Team number (selects the snap-line color)
+    pub snap_alpha: f32,            // Multiplier for the snap-line color's alpha
+    pub name:       Option<String>, // Label above the box
+    pub info:       String,         // Label below the box (distance / weapon)
+    pub text_alpha: f32,            // Alpha for both labels
+    pub health:     Option<i32>,    // Known health (fresh boxes only)
+    pub focus:      bool,           // Closest to the crosshair: thicker corners, larger name
+    pub head:       Option<[f32; 2]>, // Projected eye point, the head-line target (None = not projected)
+    pub line:       Dash,           // Corner and snap-line pattern (stale data isn't solid)
+}
+
+/// Where snap-lines go this frame.
+pub struct Snaplines<'a> {
+    pub from:   [f32; 2],
+    pub target: SnapTarget,
+    pub style:  LineStyle,
+    pub color:  &'a dyn Fn(i32) -> [f32; 4], // By team
+}
+
+/// Lines from the crosshair to each player's projected eye point, apart
+/// from the snap-lines: where the head math puts the head, not the box top.
+pub struct HeadLines {
+    pub from:  [f32; 2],
+    pub color: [f32; 4],
+    pub style: LineStyle,
+}
+
+/// How the box pass draws: the settings that apply to it, resolved for this frame.
+pub struct BoxPass<'a> {
+    pub palette:   &'a Palette,
+    pub line:      LineStyle,
+    pub brackets:  BracketStyle,                    // How box corners are drawn
+    pub shade:     &'a dyn Fn([f32; 4]) -> Shade,   // Outline shading for a box color
+    pub badge:     bool,                            // Health badge beside the box
+    pub snaplines: Option<Snaplines<'a>>,           // None = off (or shed)
+    pub head_lines: Option<HeadLines>,              // None = off (or shed)
+    pub labels:    bool,                            // False = shed
+    pub names:     NameFit,                         // Name label shrinking and truncation
+    pub low:       Option<i32>,                     // "LOW" under boxes below this health (None = not this frame)
+    pub focus_width: f32,                           // Extra corner width (px) of the focused box
+}
+
+/// Name label scale of the focused box, over its distance scale.
+pub const FOCUS_NAME_SCALE: f32 = 1.25;
+
+/// Sort `boxes` by distance and build their commands, farthest first.
+/// Labels are laid out nearest-first so close players keep their preferred
+/// spot, then drawn in reverse so the nearest labels end up on top.
+pub fn box_pass(boxes: &mut [BoxDraw], pass: &BoxPass) -> DrawList {
+    let pal = pass.palette;
+    let mut list = DrawList::new();
+    boxes.sort_by(|a, b| b.dist.total_cmp(&a.dist));
+
+    for b in boxes.iter() {
+        let [x0, y0, x1, y1] = b.rect;
+        list.push(Cmd::Rect {
+            rect: [x0 - 1.0, y0 - 1.0, x1 + 1.0, y1 + 1.0], shade: Shade::Solid(SHADOW), style: pass.line,
+        });
+        let style = if b.focus { LineStyle { width: pass.line.width + pass.focus_width, ..pass.line } } else { pass.line };
+        list.push(Cmd::Corners { rect: b.rect, shade: (pass.shade)(b.color), style, kind: pass.brackets, line: b.line });
+        if let (true, Some(h)) = (pass.badge, b.health) {
+            let c = pal.health_color(h);
+            list.push(Cmd::Fill {
+                rect:  [x1 + 2.0, y0, x1 + 2.0 + HEALTH_BADGE_PX, y0 + HEALTH_BADGE_PX],
+                color: [c[0], c[1], c[2], b.color[3]],
+            });
+        }
+
+        if let Some(s) = &pass.snaplines {
+            let to = match s.target {
+                SnapTarget::Feet   => b.feet,
+                SnapTarget::Center => [(x0 + x1) * 0.5, (y0 + y1) * 0.5],
+                SnapTarget::Head   => [(x0 + x1) * 0.5, y0],
+            };
+            let mut c = (s.color)(b.team);
+            c[3] *= b.snap_alpha;
+            for (from, to) in b.line.segments(s.from, to) { list.line(from, to, c, s.style); }
+        }
+        if let (Some(h), Some(to)) = (&pass.head_lines, b.head) {
+            let c = [h.color[0], h.color[1], h.color[2], h.color[3] * b.snap_alpha];
+            for (from, to) in b.line.segments(h.from, to) { list.line(from, to, c, h.style); }
+        }
+    }
+
+    if !pass.labels { return list; }
+    let mut labels: Vec<Label> = Vec::with_capacity(boxes.len() * 2);
+    for b in boxes.iter().rev() {
+        let [x0, y0, x1, y1] = b.rect;
+        let text_color = [pal.text[0], pal.text[1], pal.text[2], pal.text[3] * b.text_alpha];
+        if let Some(name) = &b.name {
+            let cx = (x0 + x1) * 0.5;
+            let (text, mut scale) = pass.names.fit(name, b.dist);
+            if b.focus { scale *= FOCUS_NAME_SCALE; }
+            let w = view::text_width(&text) * scale;
+            labels.push(Label {
+                x: cx - w * 0.5, y: y0 - 2.0, w, h: view::TEXT_HEIGHT * scale,
+                text, color: text_color, dir: -1.0, scale,
+            });
+        }
+        labels.push(Label {
+            x: x0, y: y1 + 12.0, w: view::text_width(&b.info), h: view::TEXT_HEIGHT,
+            text: b.info.clone(), color: text_color, dir: 1.0, scale: 1.0,
+        });
+        if pass.low.is_some_and(|low| b.health.is_some_and(|h| h < low)) {
+            let c = pal.health[0];
+            labels.push(Label {
+                x: x0, y: y1 + 14.0 + view::TEXT_HEIGHT, w: view::text_width("LOW"), h: view::TEXT_HEIGHT,
+                text: "LOW".to_string(), color: [c[0], c[1], c[2], b.text_alpha], dir: 1.0, scale: 1.0,
+            });
+        }
+    }
+    list.labels(&mut labels);
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    fn boxed(dist: f32, x: f32, health: Option<i32>) -> BoxDraw {
+        BoxDraw {
+            rect: [x, 100.0, x + 20.0, 140.0], feet: [x + 10.0, 140.0], dist, color: [0.0, 1.0, 0.0, 1.0],
+            team: 1, snap_alpha: 1.0, name: Some(format!("p{}", dist)), info: format!("{:.1}m", dist),
+            text_alpha: 1.0, health, focus: false, head: None, line: Dash::Solid,
+        }
+    }
+
+    #[test]
+    fn box_pass_draws_far_to_near_with_labels_on_top() {
+        let pal = Theme::Default.palette();
+        let snap_color = |_team: i32| [1.0, 1.0, 0.0, 0.5];
+        let pass = BoxPass {
+            palette: pal, line: LineStyle::DEFAULT, brackets: BracketStyle::Classic, shade: &Shade::Solid, badge: true,
+            snaplines: Some(Snaplines { from: [320.0, 480.0], target: SnapTarget::Head, style: LineStyle::DEFAULT,
+                                        color: &snap_color }),
+            head_lines: None, labels: true, names: NameFit::OFF, low: Some(25), focus_width: 1.0,
+        };
+        let mut boxes = vec![boxed(5.0, 10.0, Some(80)), boxed(30.0, 200.0, Some(10))];
+        let list = box_pass(&mut boxes, &pass);
+        let cmds = list.cmds();
+
+        // Far box first: shadow, corners, badge, snap-line to its head
+        assert!(matches!(cmds[0], Cmd::Rect { rect, .. } if rect == [199.0, 99.0, 221.0, 141.0]));
+        assert!(matches!(cmds[1], Cmd::Corners { rect, .. } if rect == [200.0, 100.0, 220.0, 140.0]));
+        assert!(matches!(cmds[2], Cmd::Fill { .. }));
+        assert!(matches!(cmds[3], Cmd::Line { to, .. } if to == [210.0, 100.0]));
+        // Then the near one, then the labels; LOW only under the hurt player
+        assert!(matches!(cmds[5], Cmd::Corners { rect, .. } if rect == [10.0, 100.0, 30.0, 140.0]));
+        let texts: Vec<&str> = cmds.iter().filter_map(|c| match c { Cmd::Text { text, .. } => Some(text.as_str()), _ => None }).collect();
+        assert_eq!(texts.len(), 5);
+        assert_eq!(texts.iter().filter(|t| **t == "LOW").count(), 1);
+        assert_eq!(*texts.last().unwrap(), "p5"); // Nearest name drawn last (on top)
+    }
+
+    #[test]
+    fn shed_labels_and_snaplines_leave_boxes() {
+        let pal = Theme::Default.palette();
+        let pass = BoxPass {
+            palette: pal, line: LineStyle::DEFAULT, brackets: BracketStyle::Classic, shade: &Shade::Solid, badge: false,
+            snaplines: None, head_lines: None, labels: false, names: NameFit::OFF, low: None, focus_width: 1.0,
+        };
+        let list = box_pass(&mut [boxed(5.0, 10.0, Some(80))], &pass);
+        assert_eq!(list.cmds().len(), 2);
+        assert_eq!(list.cmds()[1].to_string(), "corners 10.0 100.0 30.0 140.0 0.00,1.00,0.00,1.00 w=1.5");
+    }
+
+    #[test]
+    fn focused_box_gets_thicker_corners_and_a_larger_name() {
+        let pal = Theme::Default.palette();
+        let pass = BoxPass {
+            palette: pal, line: LineStyle::DEFAULT, brackets: BracketStyle::Classic, shade: &Shade::Solid, badge: false,
+            snaplines: None, head_lines: None, labels: true, names: NameFit::OFF, low: None, focus_width: 1.0,
+        };
+        let mut focused = boxed(5.0, 10.0, None);
+        focused.focus = true;
+        let list = box_pass(&mut [focused, boxed(30.0, 200.0, None)], &pass);
+        let widths: Vec<f32> = list.cmds().iter()
+            .filter_map(|c| match c { Cmd::Corners { style, .. } => Some(style.width), _ => None }).collect();
+        assert_eq!(widths, [1.5, 2.5]); // Far plain box, then the focused one
+        let scales: Vec<(&str, f32)> = list.cmds().iter()
+            .filter_map(|c| match c { Cmd::Text { text, scale, .. } => Some((text.as_str(), *scale)), _ => None }).collect();
+        assert!(scales.contains(&("p5", FOCUS_NAME_SCALE)) && scales.contains(&("p30", 1.0)));
+    }
+
+    #[test]
+    fn head_lines_go_to_the_eye_point_when_known() {
+        let pal = Theme::Default.palette();
+        let pass = BoxPass {
+            palette: pal, line: LineStyle::DEFAULT, brackets: BracketStyle::Classic, shade: &Shade::Solid, badge: false, snaplines: None,
+            head_lines: Some(HeadLines { from: [320.0, 240.0], color: [0.0, 1.0, 1.0, 0.8], style: LineStyle::DEFAULT }),
+            labels: false, names: NameFit::OFF, low: None, focus_width: 0.0,
+        };
+        let mut headed = boxed(5.0, 10.0, None);
+        headed.head = Some([20.0, 104.0]);
+        headed.snap_alpha = 0.5;
+        let list = box_pass(&mut [headed, boxed(30.0, 200.0, None)], &pass);
+        let lines: Vec<String> = list.cmds().iter().filter(|c| matches!(c, Cmd::Line { .. })).map(|c| c.to_string()).collect();
+        assert_eq!(lines, ["line 320.0 240.0 20.0 104.0 0.00,1.00,1.00,0.40 w=1.5"]); // None for the unprojected one
+    }
+
+    #[test]
+    fn bracket_presets() {
+        let rect = [0.0, 0.0, 100.0, 200.0];
+        let classic = brackets(rect, BracketStyle::Classic, Dash::Solid);
+        assert_eq!(classic.len(), 1);
+        assert_eq!(classic[0].1[0], ([0.0, 0.0], [18.0, 0.0]));       // 22 % of 100, capped at 18
+        assert_eq!(brackets(rect, BracketStyle::Short, Dash::Solid)[0].1[1], ([0.0, 0.0], [0.0, 6.0]));
+        let frame = brackets(rect, BracketStyle::Frame, Dash::Solid);
+        assert_eq!((frame[0].0, frame[0].1.len(), frame[1].0, frame[1].1.len()), (0.0, 4, FRAME_CORNER_EXTRA, 8));
+        let dashed = &brackets([0.0, 0.0, 10.0, 10.0], BracketStyle::Dashed, Dash::Solid)[0].1;
+        assert_eq!(dashed.len(), 8);                                   // Two dashes per side
+        assert_eq!(dashed[1], ([7.0, 0.0], [10.0, 0.0]));              // Second one cut short at the corner
+    }
+
+    #[test]
+    fn dashes_along_a_line() {
+        assert_eq!(dash([0.0, 0.0], [0.0, 20.0], [4.0, 3.0]),
+                   [([0.0, 0.0], [0.0, 4.0]), ([0.0, 7.0], [0.0, 11.0]), ([0.0, 14.0], [0.0, 18.0])]);
+        assert_eq!(dash([0.0, 0.0], [3.0, 0.0], [4.0, 3.0]), [([0.0, 0.0], [3.0, 0.0])]); // Shorter than a dash
+        assert_eq!(dash([0.0, 0.0], [9.0, 0.0], [0.0, 3.0]).len(), 1);                    // No pattern: solid
+    }
+
+    #[test]
+    fn stale_boxes_get_patterned_lines() {
+        let dotted = brackets([0.0, 0.0, 100.0, 200.0], BracketStyle::Classic, Dash::Dotted);
+        assert_eq!(dotted[0].1.len(), 8 * 5);                          // 18 px legs: 5 dots each
+        assert_eq!(dotted[0].1[1], ([4.0, 0.0], [5.5, 0.0]));
+        assert_eq!(brackets([0.0, 0.0, 10.0, 10.0], BracketStyle::Dashed, Dash::Dotted)[0].1.len(), 4 * 3);
+
+        let pal = Theme::Default.palette();
+        let snap_color = |_team: i32| [1.0; 4];
+        let pass = BoxPass {
+            palette: pal, line: LineStyle::DEFAULT, brackets: BracketStyle::Classic, shade: &Shade::Solid, badge: false,
+            snaplines: Some(Snaplines { from: [10.0, 100.0], target: SnapTarget::Feet, style: LineStyle::DEFAULT,
+                                        color: &snap_color }),
+            head_lines: None, labels: false, names: NameFit::OFF, low: None, focus_width: 0.0,
+        };
+        let mut stale = boxed(5.0, 0.0, None);
+        stale.feet = [10.0, 120.0];
+        stale.line = Dash::Dashed;
+        let list = box_pass(&mut [stale], &pass);
+        assert!(list.cmds()[1].to_string().ends_with(" Dashed"));
+        let lines = list.cmds().iter().filter(|c| matches!(c, Cmd::Line { .. })).count();
+        assert_eq!(lines, 3);                                          // 20 px snap-line in 4 + 3 dashes
+    }
+}
@@ -12,16 +12,35 @@
 #![allow(dead_code)]
 #![allow(static_mut_refs)]
 
-use crate::math::Vec3;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use crate::alive::AliveTracker;
+use crate::error::OverlayError;
+use crate::flash::ScreenFade;
+use crate::math::{self, Vec3};
+use crate::mods::{GameMod, Hulls};
+use crate::names;
+use crate::netgraph::NetSample;
+use crate::world::{self, RawEntity};
+use crate::pattern::Pattern;
+use crate::bsp::Bounds;
+use crate::player::{self, EngineReader, SlotInfo, PlayerFields, Rejected, Sanity};
+use crate::offsets::{self, Offset};
+use crate::player::{ES_WEAPONMODEL, ES_USEHULL, ES_IUSER1, ES_IUSER2, MAX_CLIENTS};
+use crate::scancache;
+use crate::slots::ReadOutcome;
+use crate::time::{self, Stopwatch};
+use crate::watch::{Base, Watch};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use winapi::shared::evntprov::{EventRegister, EventUnregister, EventWriteString};
+use winapi::shared::guiddef::GUID;
+use winapi::um::debugapi::OutputDebugStringA;
 use winapi::um::libloaderapi::{GetModuleHandleA, GetModuleFileNameA, GetProcAddress};
 use winapi::um::psapi::{GetModuleInformation, MODULEINFO};
 use winapi::um::processthreadsapi::GetCurrentProcess;
-use winapi::um::memoryapi::{VirtualQuery, VirtualProtect};
+use winapi::um::memoryapi::VirtualQuery;
 use winapi::um::winnt::{
     MEMORY_BASIC_INFORMATION, PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
-    PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, MEM_COMMIT,
-    PAGE_EXECUTE_READWRITE as PAGE_RWX,
+    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, MEM_COMMIT,
 };
 
 // ============================================================
@@ -33,40 +52,22 @@ use winapi::um::winnt::{
 const SLOT_GET_LOCAL_PLAYER:    usize = 51;  // cl_enginefunc_t::GetLocalPlayer
 const SLOT_GET_ENTITY_BY_INDEX: usize = 53;  // cl_enginefunc_t::GetEntityByIndex
 const SLOT_GET_PLAYER_INFO:     usize = 21;  // cl_enginefunc_t::pfnGetPlayerInfo
+const SLOT_PLAY_SOUND_BY_NAME:  usize = 22;  // cl_enginefunc_t::pfnPlaySoundByName
+const SLOT_GET_CVAR_FLOAT:      usize = 15;  // cl_enginefunc_t::pfnGetCvarFloat
+const SLOT_GET_CVAR_STRING:     usize = 16;  // cl_enginefunc_t::pfnGetCvarString
+const SLOT_ADD_COMMAND:         usize = 17;  // cl_enginefunc_t::pfnAddCommand
+const SLOT_CMD_ARGC:            usize = 38;  // cl_enginefunc_t::Cmd_Argc
+const SLOT_CMD_ARGV:            usize = 39;  // cl_enginefunc_t::Cmd_Argv
+const SLOT_GET_VIEW_ANGLES:     usize = 34;  // cl_enginefunc_t::GetViewAngles
+const SLOT_GET_GAME_DIRECTORY:  usize = 71;  // cl_enginefunc_t::pfnGetGameDirectory ("cstrike")
+const SLOT_GET_LEVEL_NAME:      usize = 74;  // cl_enginefunc_t::pfnGetLevelName ("maps/de_dust2.bsp")
+const SLOT_GET_SCREEN_FADE:     usize = 75;  // cl_enginefunc_t::pfnGetScreenFade
+const SLOT_GET_CLIENT_TIME:     usize = 54;  // cl_enginefunc_t::GetClientTime (cl.time, seconds)
 const SLOT_GET_MODEL_BY_INDEX:  usize = 107; // cl_enginefunc_t::pfnGetModelByIndex
 const SLOT_PTRIAPI:             usize = 82;  // cl_enginefunc_t::pTriAPI (triangles API, has W2S)
+const SLOT_PDEMOAPI:            usize = 85;  // cl_enginefunc_t::pDemoAPI (demo record/playback state)
 
-
-const MAX_CLIENTS: i32 = 32; // Maximum player slots in GoldSrc
-
-// ============================================================
-// Entity Structure Offsets
-// ============================================================
-// These are byte offsets into the engine's cl_entity_t structure.
-// They vary by engine build — these are for Build 4554.
-
-const CURSTATE_OFFSET: usize = 0x2B0;  // Offset to entity_state_t (current state)
-const ENT_ORIGIN:      usize = 0xB48;  // cl_entity_t::origin (interpolated position)
-const ENT_CURPOS:      usize = 0x404;  // Current position history index
-const ENT_PH_BASE:     usize = 0x408;  // Start of position history array
-const PH_ENTRY_SIZE:   usize = 28;     // Size of one position history entry
-const PH_HISTORY_MASK: usize = 63;     // Bitmask for position history ring buffer index
-
-// Entity state sub-offsets (relative to CURSTATE_OFFSET)
-const ES_ORIGIN:       usize = 0x10;   // entity_state_t::origin
-const ES_WEAPONMODEL:  usize = 0xB4;   // entity_state_t::weaponmodel (model index)
-const ES_MAXS:         usize = 0x88;   // entity_state_t::maxs (bounding box top)
-const ES_USEHULL:      usize = 0xC8;   // entity_state_t::usehull (0=standing, 1=ducking)
-
-// ============================================================
-// Player Extra Info Offsets
-// ============================================================
-// g_PlayerExtraInfo is client.dll's per-player metadata array.
-// Used to get team numbers and alive/dead status.
-
-const EXTRA_OFF_TEAMNUMBER: usize = 0x2A;  // Team number (1=T, 2=CT)
-const EXTRA_OFF_DEAD:       usize = 0x3C;  // Dead flag (0=alive, nonzero=dead)
-const EXTRA_STRIDE:         usize = 0x68;  // Size of one extra_player_info_t entry
+const DEMOAPI_IS_PLAYINGBACK:   usize = 1;   // demo_api_t::IsPlayingback
 
 // ============================================================
 // Global State
@@ -81,22 +82,63 @@ static MAP_LOADED: AtomicBool = AtomicBool::new(false);
 /// Whether our Initialize hook has been installed.
 static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
 
-/// Hook status code (0 = success, 0xDEAD = not attempted, 0xE0xx = error).
-static HOOK_STATUS: AtomicUsize = AtomicUsize::new(0xDEAD);
+/// Outcome of installing the Initialize hook (None = pending: not attempted,
+/// or client.dll not loaded yet).
+static HOOK_STATUS: Mutex<Option<Result<(), OverlayError>>> = Mutex::new(None);
 
 /// Cached address of g_PlayerExtraInfo array.
 static EXTRA_INFO_BASE: AtomicUsize = AtomicUsize::new(0);
 
-/// Frame counter for staleness tracking.
-static FRAME_COUNTER: AtomicU32 = AtomicU32::new(0);
+/// Scans a frame asked for, run by the worker thread (`background_scans`);
+/// setting one wakes the worker.
+static EXTRA_INFO_WANTED: AtomicBool = AtomicBool::new(false);
+static RESCAN_TABLE: AtomicUsize = AtomicUsize::new(0); // Table that failed verification (0 = none)
+static NETCHAN_WANTED: AtomicBool = AtomicBool::new(false);
+
+/// When a g_PlayerExtraInfo scan last came up empty (0 = hasn't); it's not
+/// retried within EXTRA_INFO_RETRY_MS of that, unless the map changes.
+static EXTRA_INFO_FAILED_MS: AtomicU32 = AtomicU32::new(0);
+const EXTRA_INFO_RETRY_MS: u32 = 5000;
+/// The running mod, from the game directory (None = not read yet).
+static GAME_MOD: Mutex<Option<GameMod>> = Mutex::new(None);
+
+/// client.dll's hull table (None = not asked yet; Some(None) = not usable).
+static HULL_TABLE: Mutex<Option<Option<Hulls>>> = Mutex::new(None);
+
+/// Why the last g_PlayerExtraInfo lookup failed (None = found or not tried).
+static EXTRA_INFO_ERROR: Mutex<Option<OverlayError>> = Mutex::new(None);
 
-/// How many frames before a player's cached origin is considered stale.
-const ORIGIN_STALE_FRAMES: u32 = 30;
+/// How long (ms) before a player's cached origin is considered stale.
+const ORIGIN_STALE_MS: u32 = 500;
+
+/// How long (ms) a stale origin keeps being reported before the player is dropped.
+const ORIGIN_DROP_MS: u32 = 4000;
 
 /// Per-player cached data for origin staleness detection.
-static mut LAST_KNOWN_ORIGIN: [Vec3; 33] = [Vec3 { x: 0.0, y: 0.0, z: 0.0 }; 33];
+static mut LAST_KNOWN_ORIGIN: [Vec3; 33] = [Vec3::ZERO; 33];
 static mut LAST_CURPOS: [usize; 33] = [0usize; 33];
-static mut LAST_CURPOS_FRAME: [u32; 33] = [0u32; 33];
+static mut LAST_CURPOS_MS: [u32; 33] = [0u32; 33];
+
+/// Per-player life state from the previous read (0 = unknown, 1 = alive, 2 = dead),
+/// used to catch the alive→dead transition.
+static mut LIFE_STATE: [u8; 33] = [0u8; 33];
+
+/// Dead / alive from the entity and DeathMsg while g_PlayerExtraInfo isn't known.
+static ALIVE: Mutex<AliveTracker> = Mutex::new(AliveTracker::new());
+
+/// Breadcrumb trail: samples per player, and the minimum spacing between samples.
+pub const TRAIL_LEN: usize = 32;
+const TRAIL_SAMPLE_MS: u32 = 50;
+
+/// Per-player ring buffer of (origin, time_ms) samples; `TRAIL_HEAD` is the next write slot.
+static mut TRAIL: [[(Vec3, u32); TRAIL_LEN]; 33] = [[(Vec3::ZERO, 0u32); TRAIL_LEN]; 33];
+static mut TRAIL_HEAD: [usize; 33] = [0usize; 33];
+
+/// Death spots are forgotten after this long regardless of the display time.
+const DEATH_SPOT_MAX_MS: u32 = 60_000;
+
+/// Recorded death positions, oldest first.
+static DEATH_SPOTS: Mutex<Vec<DeathSpot>> = Mutex::new(Vec::new());
 
 
 
@@ -104,11 +146,8 @@ static mut LAST_CURPOS_FRAME: [u32; 33] = [0u32; 33];
 // Initialize Hook (captures the engine function table)
 // ============================================================
 
-/// Trampoline buffer for the JMP hook (stores original bytes + jump-back).
-static mut TRAMPOLINE: [u8; 16] = [0u8; 16];
-
-/// Address of the original Initialize function (for restoration).
-static INIT_TARGET: AtomicUsize = AtomicUsize::new(0);
+/// Trampoline to the original Initialize (owned by hook::HookManager).
+static INIT_ORIGINAL: AtomicUsize = AtomicUsize::new(0);
 
 /// Function signature of client.dll's Initialize export.
 type FnInitialize = unsafe extern "C" fn(eng: *mut u8, version: i32) -> i32;
@@ -116,10 +155,12 @@ type FnInitialize = unsafe extern "C" fn(eng: *mut u8, version: i32) -> i32;
 /// Our replacement for Initialize — captures the engine table pointer, then
 /// calls the original Initialize so the game continues normally.
 unsafe extern "C" fn hk_initialize(eng: *mut u8, version: i32) -> i32 {
+    let _guard = crate::hook::DetourGuard::enter();
     logf(format!("hk_initialize: eng={:08X} ver={}", eng as usize, version));
     // Call the original Initialize via our trampoline
-    let tramp: FnInitialize = std::mem::transmute(TRAMPOLINE.as_ptr());
-    let ret = tramp(eng, version);
+    let tramp = INIT_ORIGINAL.load(Ordering::Acquire);
+    if tramp == 0 { return 0; }
+    let ret = std::mem::transmute::<usize, FnInitialize>(tramp)(eng, version);
 
     // Save the engine table pointer for later use
     if !eng.is_null() {
@@ -130,22 +171,8 @@ unsafe extern "C" fn hk_initialize(eng: *mut u8, version: i32) -> i32 {
     ret
 }
 
-/// Write a 5-byte JMP instruction at `from` that jumps to `to`.
-/// Used to redirect function calls (inline hooking).
-unsafe fn write_jmp(from: usize, to: usize) -> bool {
-    // Make the target memory writable
-    let mut old: u32 = 0;
-    if VirtualProtect(from as *mut _, 5, PAGE_RWX, &mut old) == 0 { return false; }
-
-    let p = from as *mut u8;
-    *p = 0xE9; // JMP rel32 opcode
-    let rel = (to as i64 - from as i64 - 5) as i32; // Calculate relative offset
-    std::ptr::write_unaligned(p.add(1) as *mut i32, rel);
-
-    // Restore original memory protection
-    VirtualProtect(from as *mut _, 5, old, &mut old);
-    true
-}
+/// Name the Initialize JMP patch is registered under (hook.rs).
+pub const HOOK_INITIALIZE: &str = "client.dll!Initialize";
 
 /// Install the Initialize hook to capture the engine function table.
 /// Tries two approaches:
@@ -157,17 +184,14 @@ pub unsafe fn install_initialize_hook() {
     // Get client.dll's base address
     let client = GetModuleHandleA(b"client.dll\0".as_ptr() as _);
     if client.is_null() {
-        log("client.dll not loaded yet");
-        flush_log();
+        set_hook_status(None);                 // Not an error: the game loads it with the first map
         return;
     }
 
     // Get the address of the Initialize export
     let init_addr = GetProcAddress(client, b"Initialize\0".as_ptr() as _) as usize;
     if init_addr == 0 {
-        HOOK_STATUS.store(0xE001, Ordering::Relaxed);
-        log("Initialize not exported");
-        flush_log();
+        set_hook_status(Some(Err(OverlayError::ExportMissing { module: "client.dll", export: "Initialize" })));
         return;
     }
 
@@ -176,79 +200,383 @@ pub unsafe fn install_initialize_hook() {
     if let Some(table) = find_gengfuncs_in_client() {
         ENGINE_TABLE.store(table, Ordering::Release);
         MAP_LOADED.store(true, Ordering::Release);
-        HOOK_STATUS.store(0, Ordering::Relaxed);
         HOOK_INSTALLED.store(true, Ordering::Relaxed);
         log("engine table found via memory scan");
-        flush_log();
+        set_hook_status(Some(Ok(())));
         return;
     }
 
     // Memory scan failed — install a JMP hook on Initialize
     // so we catch the engine table when the next map loads
-    if !is_readable(init_addr, 5) {
-        HOOK_STATUS.store(0xE002, Ordering::Relaxed);
-        log("Initialize not readable");
-        flush_log();
+    let detour = hk_initialize as *const () as usize;
+    if let Err(e) = crate::hook::jmp_hook(HOOK_INITIALIZE, init_addr, detour, &INIT_ORIGINAL) {
+        set_hook_status(Some(Err(e)));
         return;
     }
 
-    // Build the trampoline: save original 5 bytes + JMP back
-    let src = init_addr as *const u8;
-    for i in 0..5usize { TRAMPOLINE[i] = *src.add(i); }
-    TRAMPOLINE[5] = 0xE9; // JMP opcode
-    let tramp_ptr = TRAMPOLINE.as_ptr() as usize;
-    let rel_back = (init_addr as i64 + 5 - tramp_ptr as i64 - 10) as i32;
-    std::ptr::write_unaligned(TRAMPOLINE.as_mut_ptr().add(6) as *mut i32, rel_back);
+    HOOK_INSTALLED.store(true, Ordering::Relaxed);
+    log("Initialize JMP hook installed");
+    set_hook_status(Some(Ok(())));
+}
+
+/// Record the outcome of install_initialize_hook (None while client.dll isn't
+/// loaded yet), logging failures once (the worker retries the hook every
+/// `[worker] retry_ms` until it succeeds).
+fn set_hook_status(status: Option<Result<(), OverlayError>>) {
+    let Ok(mut g) = HOOK_STATUS.lock() else { return };
+    if *g == status { return; }
+    if let Some(Err(e)) = &status {
+        logf(format!("Initialize hook: {}", e));
+    }
+    *g = status;
+    flush_log();
+}
 
-    // Make the trampoline executable
-    let mut old: u32 = 0;
-    VirtualProtect(TRAMPOLINE.as_mut_ptr() as *mut _, 16, PAGE_RWX, &mut old);
 
-    INIT_TARGET.store(init_addr, Ordering::Relaxed);
 
-    // Overwrite Initialize's first 5 bytes with a JMP to our hook
-    if !write_jmp(init_addr, hk_initialize as *const () as usize) {
-        HOOK_STATUS.store(0xE003, Ordering::Relaxed);
-        log("write_jmp failed");
-        flush_log();
-        return;
+// ============================================================
+// HUD_UpdateClientData Hook (captures the current FOV)
+// ============================================================
+// The engine calls client.dll's HUD_UpdateClientData every frame and renders
+// with whatever `fov` the client writes back into client_data_t — including
+// scope zoom. Reading it after the original returns gives the exact render FOV,
+// which the default_fov cvar alone doesn't reflect while zoomed.
+
+/// client_data_t (only the leading fields we read).
+#[repr(C)]
+struct ClientData {
+    origin:      Vec3, // Local player origin
+    viewangles:  Vec3, // Local view angles
+    weapon_bits: i32,  // Owned weapons bitmask
+    fov:         f32,  // Current field of view (degrees)
+}
+
+/// Function signature of client.dll's HUD_UpdateClientData export.
+type FnUpdateClientData = unsafe extern "C" fn(cdata: *mut ClientData, time: f32) -> i32;
+
+/// Trampoline to the original HUD_UpdateClientData.
+static UPDATE_CLIENT_DATA_ORIG: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the HUD_UpdateClientData hook has been installed.
+static CLIENT_DATA_HOOKED: AtomicBool = AtomicBool::new(false);
+
+/// Last FOV written by the client (f32 bits, 0 = not captured yet).
+static CURRENT_FOV: AtomicU32 = AtomicU32::new(0);
+
+/// Our replacement for HUD_UpdateClientData — runs the original, then records the FOV.
+unsafe extern "C" fn hk_update_client_data(cdata: *mut ClientData, time: f32) -> i32 {
+    let _guard = crate::hook::DetourGuard::enter();
+    let orig: FnUpdateClientData = std::mem::transmute(UPDATE_CLIENT_DATA_ORIG.load(Ordering::Acquire));
+    let ret = orig(cdata, time);
+    if !cdata.is_null() {
+        let fov = (*cdata).fov;
+        if fov.is_finite() && fov > 0.0 && fov < 180.0 {
+            CURRENT_FOV.store(fov.to_bits(), Ordering::Relaxed);
+        }
     }
+    ret
+}
 
-    HOOK_STATUS.store(0, Ordering::Relaxed);
-    HOOK_INSTALLED.store(true, Ordering::Relaxed);
-    log("Initialize JMP hook installed");
-    flush_log();
+/// Hook client.dll!HUD_UpdateClientData (no-op once installed or if client.dll isn't loaded).
+unsafe fn install_client_data_hook() {
+    if CLIENT_DATA_HOOKED.load(Ordering::Relaxed) { return; }
+    match crate::hook::hook_export(
+        b"client.dll\0", b"HUD_UpdateClientData\0",
+        hk_update_client_data as *mut std::ffi::c_void,
+    ) {
+        Ok(orig) => {
+            UPDATE_CLIENT_DATA_ORIG.store(orig as usize, Ordering::Release);
+            log("HUD_UpdateClientData hook installed");
+        }
+        Err(e) => logf(format!("HUD_UpdateClientData hook failed: {}", e)),
+    }
+    // Only attempt once — a failure here just means falling back to default_fov
+    CLIENT_DATA_HOOKED.store(true, Ordering::Relaxed);
+}
+
+// ============================================================
+// V_CalcRefdef Hook (captures the camera)
+// ============================================================
+// The engine asks client.dll's V_CalcRefdef where to put the camera each frame.
+// While alive that's the local player's eyes, but dead or spectating it's a
+// death cam, a chase camera or a free-flying spectator, none of which
+// GetLocalPlayer's origin follows. After the original returns, ref_params_t
+// holds the final camera: origin, angles and basis vectors, and the viewport.
+// The FOV isn't in it; it comes from HUD_UpdateClientData (CURRENT_FOV).
+
+/// ref_params_t (layout from the HL SDK).
+#[repr(C)]
+struct RefParamsRaw {
+    vieworg:        Vec3,
+    viewangles:     Vec3,
+    forward:        Vec3,
+    right:          Vec3,
+    up:             Vec3,
+    frametime:      f32,
+    time:           f32,
+    intermission:   i32,
+    paused:         i32,
+    spectator:      i32,
+    onground:       i32,
+    waterlevel:     i32,
+    simvel:         Vec3,
+    simorg:         Vec3,
+    viewheight:     Vec3,
+    idealpitch:     f32,
+    cl_viewangles:  Vec3,
+    health:         i32,
+    crosshairangle: Vec3,
+    viewsize:       f32,
+    punchangle:     Vec3,
+    maxclients:     i32,
+    viewentity:     i32,
+    playernum:      i32,
+    max_entities:   i32,
+    demoplayback:   i32,
+    hardware:       i32,
+    smoothing:      i32,
+    cmd:            *mut u8,
+    movevars:       *mut u8,
+    viewport:       [i32; 4],
+    next_view:      i32,
+    only_client_draw: i32,
+}
+
+/// The camera of the last rendered view.
+#[derive(Clone, Copy, Default)]
+pub struct Refdef {
+    pub origin:    Vec3,     // Camera position
+    pub angles:    Vec3,     // View angles (pitch, yaw, roll in degrees), punch included
+    pub forward:   Vec3,     // Basis vectors for `angles`
+    pub right:     Vec3,
+    pub up:        Vec3,
+    pub fov:       f32,      // Horizontal FOV (degrees) the view is rendered with
+    pub viewport:  [i32; 4], // x, y, width, height (pixels, GL convention)
+    pub spectator: bool,     // The local client is a spectator (HLTV / spectator slot)
+    pub health:    i32,      // Local player's health as the client sees it (<= 0 = dead)
+    pub time_ms:   u32,      // time::now_ms() when captured
 }
 
+/// Function signature of client.dll's V_CalcRefdef export.
+type FnCalcRefdef = unsafe extern "C" fn(pparams: *mut RefParamsRaw);
+
+static CALC_REFDEF_ORIG: AtomicUsize = AtomicUsize::new(0);
+static CALC_REFDEF_HOOKED: AtomicBool = AtomicBool::new(false);
+static LAST_REFDEF: Mutex<Option<Refdef>> = Mutex::new(None);
+
+/// Our replacement for V_CalcRefdef — runs the original, then records the camera.
+/// Only the first view of a frame is kept: extra views (`nextView`, e.g. an
+/// overview map) are asked for after it.
+unsafe extern "C" fn hk_calc_refdef(pparams: *mut RefParamsRaw) {
+    let _guard = crate::hook::DetourGuard::enter();
+    static IN_FRAME: AtomicBool = AtomicBool::new(false);
+    let orig: FnCalcRefdef = std::mem::transmute(CALC_REFDEF_ORIG.load(Ordering::Acquire));
+    orig(pparams);
+    if pparams.is_null() { return; }
+    let p = &*pparams;
+    let extra_view = IN_FRAME.swap(p.next_view != 0, Ordering::Relaxed);
+    if extra_view { return; }
+
+    if !p.vieworg.is_finite() || !p.viewangles.is_finite() { return; }
+    let fov = match f32::from_bits(CURRENT_FOV.load(Ordering::Relaxed)) {
+        f if f > 0.0 && f < 180.0 => f,
+        _ => 90.0,
+    };
+    let rd = Refdef {
+        origin:    p.vieworg,
+        angles:    p.viewangles,
+        forward:   p.forward,
+        right:     p.right,
+        up:        p.up,
+        fov,
+        viewport:  p.viewport,
+        spectator: p.spectator != 0,
+        health:    p.health,
+        time_ms:   time::now_ms(),
+    };
+    if let Ok(mut last) = LAST_REFDEF.lock() { *last = Some(rd); }
+}
 
+/// Hook client.dll!V_CalcRefdef (attempted once).
+unsafe fn install_calc_refdef_hook() {
+    if CALC_REFDEF_HOOKED.load(Ordering::Relaxed) { return; }
+    match crate::hook::hook_export(
+        b"client.dll\0", b"V_CalcRefdef\0",
+        hk_calc_refdef as *mut std::ffi::c_void,
+    ) {
+        Ok(orig) => {
+            CALC_REFDEF_ORIG.store(orig as usize, Ordering::Release);
+            log("V_CalcRefdef hook installed");
+        }
+        Err(e) => logf(format!("V_CalcRefdef hook failed: {}", e)),
+    }
+    CALC_REFDEF_HOOKED.store(true, Ordering::Relaxed);
+}
+
+/// The last rendered camera, if one was captured within `max_age_ms`.
+pub fn refdef(max_age_ms: u32) -> Option<Refdef> {
+    let rd = (*LAST_REFDEF.lock().ok()?)?;
+    if time::now_ms().wrapping_sub(rd.time_ms) > max_age_ms { return None; }
+    Some(rd)
+}
+
+/// Refdef age (ms) still good enough to project with.
+const REFDEF_W2S_MAX_MS: u32 = 250;
+
+/// NDC distance between the two W2S paths that counts as divergence.
+const W2S_DIVERGENCE: f32 = 0.02;
+
+/// Project with the captured camera (the fallback for a missing TriAPI).
+fn refdef_world_to_screen(world: Vec3) -> Option<(f32, f32)> {
+    let rd = refdef(REFDEF_W2S_MAX_MS)?;
+    let [_, _, w, h] = rd.viewport;
+    if w <= 0 || h <= 0 { return None; }
+    math::world_to_ndc(rd.origin, rd.angles, rd.fov, w as f32 / h as f32, world)
+}
+
+/// Compare TriAPI's projection of `world` with the refdef one and log when
+/// they disagree (at most once a second).
+fn cross_check_w2s(world: Vec3, tri: (f32, f32)) {
+    static LAST_LOG: AtomicU32 = AtomicU32::new(0);
+    let Some(own) = refdef_world_to_screen(world) else { return };
+    let (dx, dy) = (own.0 - tri.0, own.1 - tri.1);
+    if dx.abs() <= W2S_DIVERGENCE && dy.abs() <= W2S_DIVERGENCE { return; }
+    let now = time::now_ms();
+    let last = LAST_LOG.load(Ordering::Relaxed);
+    if last != 0 && now.wrapping_sub(last) < 1000 { return; }
+    LAST_LOG.store(now, Ordering::Relaxed);
+    logf(format!("w2s: TriAPI ({:.3}, {:.3}) vs refdef ({:.3}, {:.3}) for ({:.0}, {:.0}, {:.0})",
+        tri.0, tri.1, own.0, own.1, world.x, world.y, world.z));
+}
 
 // ============================================================
-// Frame Counter (for staleness tracking)
+// CL_CreateMove Hook (captures the local usercmd)
 // ============================================================
+// The engine calls client.dll's CL_CreateMove once per input tick with the
+// usercmd_t the client is about to send. After the original fills it in, the
+// final view angles, buttons and movement are copied out for the overlay.
+
+/// usercmd_t (fields up to `buttons`; layout from the HL SDK).
+#[repr(C)]
+struct UserCmdRaw {
+    lerp_msec:   i16,
+    msec:        u8,
+    _pad0:       u8,
+    viewangles:  Vec3,
+    forwardmove: f32,
+    sidemove:    f32,
+    upmove:      f32,
+    lightlevel:  u8,
+    _pad1:       u8,
+    buttons:     u16,
+}
+
+/// Snapshot of the last usercmd sent by the local player.
+#[derive(Clone, Copy, Default)]
+pub struct InputCmd {
+    pub view_angles: Vec3,   // Final view angles for the tick (pitch, yaw, roll)
+    pub forward:     f32,    // forwardmove
+    pub side:        f32,    // sidemove
+    pub up:          f32,    // upmove
+    pub buttons:     u16,    // IN_* button bits
+    pub msec:        u8,     // Duration of the command
+    pub frametime:   f32,    // Frame time passed to CL_CreateMove (seconds)
+    pub active:      bool,   // Whether the client was accepting input
+    pub time_ms:     u32,    // time::now_ms() when captured
+}
 
-/// Update the frame counter (called by esp.rs each frame).
-pub fn set_frame_counter(f: u32) {
-    FRAME_COUNTER.store(f, Ordering::Relaxed);
+/// Function signature of client.dll's CL_CreateMove export.
+type FnCreateMove = unsafe extern "C" fn(frametime: f32, cmd: *mut UserCmdRaw, active: i32);
+
+/// Trampoline to the original CL_CreateMove.
+static CREATE_MOVE_ORIG: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the CL_CreateMove hook has been attempted.
+static CREATE_MOVE_HOOKED: AtomicBool = AtomicBool::new(false);
+
+/// Last captured usercmd.
+static LAST_CMD: Mutex<Option<InputCmd>> = Mutex::new(None);
+
+/// Our replacement for CL_CreateMove — runs the original, then records the command.
+unsafe extern "C" fn hk_create_move(frametime: f32, cmd: *mut UserCmdRaw, active: i32) {
+    let _guard = crate::hook::DetourGuard::enter();
+    let orig: FnCreateMove = std::mem::transmute(CREATE_MOVE_ORIG.load(Ordering::Acquire));
+    orig(frametime, cmd, active);
+    if cmd.is_null() { return; }
+    let c = &*cmd;
+    let snap = InputCmd {
+        view_angles: c.viewangles,
+        forward:     c.forwardmove,
+        side:        c.sidemove,
+        up:          c.upmove,
+        buttons:     c.buttons,
+        msec:        c.msec,
+        frametime,
+        active:      active != 0,
+        time_ms:     time::now_ms(),
+    };
+    if let Ok(mut last) = LAST_CMD.lock() { *last = Some(snap); }
+}
+
+/// Hook client.dll!CL_CreateMove (attempted once).
+unsafe fn install_create_move_hook() {
+    if CREATE_MOVE_HOOKED.load(Ordering::Relaxed) { return; }
+    match crate::hook::hook_export(
+        b"client.dll\0", b"CL_CreateMove\0",
+        hk_create_move as *mut std::ffi::c_void,
+    ) {
+        Ok(orig) => {
+            CREATE_MOVE_ORIG.store(orig as usize, Ordering::Release);
+            log("CL_CreateMove hook installed");
+        }
+        Err(e) => logf(format!("CL_CreateMove hook failed: {}", e)),
+    }
+    CREATE_MOVE_HOOKED.store(true, Ordering::Relaxed);
+}
+
+/// The last usercmd, if one was captured within `max_age_ms`.
+pub fn last_cmd(max_age_ms: u32) -> Option<InputCmd> {
+    let cmd = (*LAST_CMD.lock().ok()?)?;
+    if time::now_ms().wrapping_sub(cmd.time_ms) > max_age_ms { return None; }
+    Some(cmd)
 }
 
 // ============================================================
 // Debug Logging
 // ============================================================
 
-use std::sync::Mutex;
-
-/// Accumulated log lines (written to file on flush).
+/// Accumulated log lines (written to file on flush). Lines logged before the
+/// DLL's directory is known stay here and go out with the first flush after.
 static LOG_LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
 
 /// Module handle of our DLL (used to determine log file path).
 static DLL_HINST: AtomicUsize = AtomicUsize::new(0);
 
-/// Store the DLL's module handle (called from DllMain).
-pub fn set_dll_hinst(h: usize) { DLL_HINST.store(h, Ordering::Relaxed); }
+/// Store the DLL's module handle (called from DllMain) and write out the
+/// lines logged so far.
+pub fn set_dll_hinst(h: usize) {
+    DLL_HINST.store(h, Ordering::Relaxed);
+    flush_log();
+}
+
+/// Mirror log lines to OutputDebugString (`[log] debug_string`).
+static MIRROR_DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// ETW provider registration for `[log] etw` (0 = not registered).
+static ETW_HANDLE: AtomicU64 = AtomicU64::new(0);
+
+/// ETW provider the log lines are written to, as level 4 (informational)
+/// strings: {5C0F2E8A-3B71-4D6E-9A54-7E21C8D4B9F3}.
+const ETW_PROVIDER: GUID = GUID {
+    Data1: 0x5c0f2e8a, Data2: 0x3b71, Data3: 0x4d6e,
+    Data4: [0x9a, 0x54, 0x7e, 0x21, 0xc8, 0xd4, 0xb9, 0xf3],
+};
+const ETW_LEVEL_INFO: u8 = 4;
 
 /// Append a log message and flush to file.
 pub fn log(s: &str) {
     if let Ok(mut v) = LOG_LINES.lock() {
+        mirror(s);
         v.push(s.to_string());
         let _ = flush_log_inner(&v);
     }
@@ -257,36 +585,120 @@ pub fn log(s: &str) {
 /// Append a formatted log message and flush to file.
 pub fn logf(s: String) {
     if let Ok(mut v) = LOG_LINES.lock() {
+        mirror(&s);
         v.push(s);
         let _ = flush_log_inner(&v);
     }
 }
 
-/// Get the log file path (next to the DLL file, named "esp_debug.log").
-fn log_path() -> std::path::PathBuf {
-    let hinst = DLL_HINST.load(Ordering::Relaxed);
-    if hinst != 0 {
-        let mut buf = [0u8; 512];
-        let len = unsafe {
-            GetModuleFileNameA(hinst as _, buf.as_mut_ptr() as _, buf.len() as u32)
-        } as usize;
-        if len > 0 {
-            if let Ok(s) = std::str::from_utf8(&buf[..len]) {
-                if let Some(dir) = std::path::Path::new(s).parent() {
-                    return dir.join("esp_debug.log");
-                }
-            }
+/// Switch the live mirrors of the log on or off (`[log] debug_string` /
+/// `etw`; applied at startup and after each config change). A mirror that
+/// comes on gets the lines logged so far first, so startup is visible in
+/// DebugView / WPA too.
+pub fn set_log_mirror(debug_string: bool, etw: bool) {
+    let Ok(v) = LOG_LINES.lock() else { return };
+    let debug_on = debug_string && !MIRROR_DEBUG.swap(debug_string, Ordering::Relaxed);
+    if !debug_string { MIRROR_DEBUG.store(false, Ordering::Relaxed); }
+
+    let handle = ETW_HANDLE.load(Ordering::Relaxed);
+    let etw_on = etw && handle == 0;
+    if etw_on {
+        let mut h = 0u64;
+        let status = unsafe { EventRegister(&ETW_PROVIDER, None, std::ptr::null_mut(), &mut h) };
+        if status != 0 {
+            drop(v);
+            logf(format!("log: ETW provider registration failed ({})", status));
+            return;
         }
+        ETW_HANDLE.store(h, Ordering::Relaxed);
+    } else if !etw && handle != 0 {
+        ETW_HANDLE.store(0, Ordering::Relaxed);
+        unsafe { EventUnregister(handle); }
+    }
+
+    for l in v.iter() {
+        if debug_on { debug_string_line(l); }
+        if etw_on { etw_line(ETW_HANDLE.load(Ordering::Relaxed), l); }
+    }
+}
+
+/// Send one log line to the enabled mirrors.
+fn mirror(s: &str) {
+    if MIRROR_DEBUG.load(Ordering::Relaxed) { debug_string_line(s); }
+    let h = ETW_HANDLE.load(Ordering::Relaxed);
+    if h != 0 { etw_line(h, s); }
+}
+
+fn debug_string_line(s: &str) {
+    if let Ok(c) = std::ffi::CString::new(format!("[esp] {}\n", s)) {
+        unsafe { OutputDebugStringA(c.as_ptr()); }
+    }
+}
+
+fn etw_line(handle: u64, s: &str) {
+    let wide: Vec<u16> = s.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe { EventWriteString(handle, ETW_LEVEL_INFO, 0, wide.as_ptr()); }
+}
+
+/// The last `n` log lines, oldest first (for the on-screen diagnostics).
+pub fn recent_log(n: usize) -> Vec<String> {
+    match LOG_LINES.lock() {
+        Ok(v) => v[v.len().saturating_sub(n)..].to_vec(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Like `recent_log`, but None instead of waiting if the log is locked (the
+/// crash handler, which may be running on the thread that holds it).
+pub fn try_recent_log(n: usize) -> Option<Vec<String>> {
+    let v = LOG_LINES.try_lock().ok()?;
+    Some(v[v.len().saturating_sub(n)..].to_vec())
+}
+
+/// Like `logf`, but dropped instead of waiting if the log is locked.
+pub fn try_logf(s: String) {
+    if let Ok(mut v) = LOG_LINES.try_lock() {
+        mirror(&s);
+        v.push(s);
+        let _ = flush_log_inner(&v);
     }
-    std::path::PathBuf::from("esp_debug.log")
+}
+
+/// Directory containing our DLL. None before `set_dll_hinst` or when
+/// GetModuleFileNameA fails (or truncates).
+pub fn try_dll_dir() -> Option<std::path::PathBuf> {
+    let hinst = DLL_HINST.load(Ordering::Relaxed);
+    if hinst == 0 { return None; }
+    let mut buf = [0u8; 512];
+    let len = unsafe {
+        GetModuleFileNameA(hinst as _, buf.as_mut_ptr() as _, buf.len() as u32)
+    } as usize;
+    if len == 0 || len >= buf.len() { return None; }
+    let s = std::str::from_utf8(&buf[..len]).ok()?;
+    std::path::Path::new(s).parent().map(|d| d.to_path_buf())
+}
+
+/// Directory containing our DLL (empty path = current directory if unknown).
+/// Config and other side files live here.
+pub fn dll_dir() -> std::path::PathBuf {
+    try_dll_dir().unwrap_or_default()
+}
+
+/// Get the log file path (next to the DLL file, named "esp_debug.log").
+/// None while the DLL's directory isn't known: the game's working directory
+/// is often not writable (Steam installs), so the log never falls back to it.
+fn log_path() -> Option<std::path::PathBuf> {
+    try_dll_dir().map(|d| d.join("esp_debug.log"))
 }
 
 /// Write all accumulated log lines to the log file (overwrites each time).
+/// Does nothing until the path is known; the lines stay in LOG_LINES.
 fn flush_log_inner(lines: &[String]) -> std::io::Result<()> {
     use std::io::Write;
+    let Some(path) = log_path() else { return Ok(()) };
     let mut f = std::fs::OpenOptions::new()
         .create(true).write(true).truncate(true)
-        .open(log_path())?;
+        .open(path)?;
     for l in lines { writeln!(f, "{}", l)?; }
     Ok(())
 }
@@ -296,6 +708,102 @@ pub fn flush_log() {
     if let Ok(v) = LOG_LINES.lock() { let _ = flush_log_inner(&v); }
 }
 
+// ============================================================
+// Engine Table Calls
+// ============================================================
+// Every engine function call goes through `engine_fn`: a slot's pointer is
+// checked (executable memory) the first time it's called for a table and
+// cached, so later calls are a load instead of a read and a VirtualQuery. A
+// slot that doesn't hold code (checked again at the next call, it may not be
+// filled in yet), or a call that gives back nothing usable, counts a failure
+// against the slot (F7 status page). A map change or a slot verification
+// starts the cache over, as the table may have changed under it.
+
+/// The engine functions the overlay calls, by the slot they're in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineFn {
+    GetCvarFloat, GetCvarString, AddCommand, GetPlayerInfo, PlaySoundByName, GetViewAngles,
+    CmdArgc, CmdArgv, GetLocalPlayer, GetEntityByIndex, GetClientTime, GetGameDirectory,
+    GetLevelName, GetScreenFade, GetModelByIndex,
+}
+
+const ENGINE_FNS: usize = 15;
+
+impl EngineFn {
+    pub const ALL: [EngineFn; ENGINE_FNS] = [
+        EngineFn::GetCvarFloat, EngineFn::GetCvarString, EngineFn::AddCommand, EngineFn::GetPlayerInfo,
+        EngineFn::PlaySoundByName, EngineFn::GetViewAngles, EngineFn::CmdArgc, EngineFn::CmdArgv,
+        EngineFn::GetLocalPlayer, EngineFn::GetEntityByIndex, EngineFn::GetClientTime,
+        EngineFn::GetGameDirectory, EngineFn::GetLevelName, EngineFn::GetScreenFade, EngineFn::GetModelByIndex,
+    ];
+
+    fn slot(self) -> usize {
+        match self {
+            EngineFn::GetCvarFloat     => SLOT_GET_CVAR_FLOAT,
+            EngineFn::GetCvarString    => SLOT_GET_CVAR_STRING,
+            EngineFn::AddCommand       => SLOT_ADD_COMMAND,
+            EngineFn::GetPlayerInfo    => SLOT_GET_PLAYER_INFO,
+            EngineFn::PlaySoundByName  => SLOT_PLAY_SOUND_BY_NAME,
+            EngineFn::GetViewAngles    => SLOT_GET_VIEW_ANGLES,
+            EngineFn::CmdArgc          => SLOT_CMD_ARGC,
+            EngineFn::CmdArgv          => SLOT_CMD_ARGV,
+            EngineFn::GetLocalPlayer   => SLOT_GET_LOCAL_PLAYER,
+            EngineFn::GetEntityByIndex => SLOT_GET_ENTITY_BY_INDEX,
+            EngineFn::GetClientTime    => SLOT_GET_CLIENT_TIME,
+            EngineFn::GetGameDirectory => SLOT_GET_GAME_DIRECTORY,
+            EngineFn::GetLevelName     => SLOT_GET_LEVEL_NAME,
+            EngineFn::GetScreenFade    => SLOT_GET_SCREEN_FADE,
+            EngineFn::GetModelByIndex  => SLOT_GET_MODEL_BY_INDEX,
+        }
+    }
+}
+
+static FN_TABLE: AtomicUsize = AtomicUsize::new(0); // Table the cache is for
+static FN_PTRS: [AtomicUsize; ENGINE_FNS] = [const { AtomicUsize::new(0) }; ENGINE_FNS]; // 0 = not checked
+static FN_FAILURES: [AtomicU32; ENGINE_FNS] = [const { AtomicU32::new(0) }; ENGINE_FNS];
+
+/// The function in `f`'s slot of `table`, as its fn pointer type `F`. None
+/// (and a failure counted) if the slot doesn't hold code.
+unsafe fn engine_fn<F: Copy>(table: usize, f: EngineFn) -> Option<F> {
+    debug_assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<usize>());
+    if table == 0 { return None; }
+    if FN_TABLE.swap(table, Ordering::AcqRel) != table { forget_engine_fns(); }
+    let cached = &FN_PTRS[f as usize];
+    let mut ptr = cached.load(Ordering::Acquire);
+    if ptr == 0 {
+        ptr = read_u32(table + f.slot() * 4) as usize;
+        if !is_executable(ptr) {
+            engine_fn_failed(f);
+            return None;
+        }
+        cached.store(ptr, Ordering::Release);
+    }
+    Some(std::mem::transmute_copy::<usize, F>(&ptr))
+}
+
+/// Count a call to `f` that gave back nothing usable.
+fn engine_fn_failed(f: EngineFn) {
+    FN_FAILURES[f as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Drop the cached pointers (new table, or a map change).
+fn forget_engine_fns() {
+    for p in &FN_PTRS { p.store(0, Ordering::Release); }
+}
+
+/// Failed calls per engine function since injection, those with any.
+pub fn engine_fn_failures() -> Vec<(EngineFn, u32)> {
+    EngineFn::ALL.iter()
+        .map(|&f| (f, FN_FAILURES[f as usize].load(Ordering::Relaxed)))
+        .filter(|&(_, n)| n > 0)
+        .collect()
+}
+
+/// The function in `f`'s slot of the current engine table (engine_fn).
+unsafe fn current_fn<F: Copy>(f: EngineFn) -> Option<F> {
+    engine_fn(ENGINE_TABLE.load(Ordering::Acquire), f)
+}
+
 // ============================================================
 // Engine API Wrapper
 // ============================================================
@@ -304,6 +812,29 @@ pub fn flush_log() {
 type FnGetLocalPlayer   = unsafe extern "C" fn() -> *mut u8;
 type FnGetEntityByIndex = unsafe extern "C" fn(idx: i32) -> *mut u8;
 type FnGetPlayerInfo    = unsafe extern "C" fn(idx: i32, info: *mut HudPlayerInfo);
+type FnGetString        = unsafe extern "C" fn() -> *const i8;
+type FnGetCvarFloat     = unsafe extern "C" fn(name: *const i8) -> f32;
+type FnGetCvarString    = unsafe extern "C" fn(name: *const i8) -> *const i8;
+type FnAddCommand       = unsafe extern "C" fn(name: *const i8, f: unsafe extern "C" fn()) -> i32;
+type FnArgc             = unsafe extern "C" fn() -> i32;
+type FnArgv             = unsafe extern "C" fn(i: i32) -> *const i8;
+type FnGetViewAngles    = unsafe extern "C" fn(out: *mut f32);
+type FnGetClientTime    = unsafe extern "C" fn() -> f32;
+type FnGetScreenFade    = unsafe extern "C" fn(fade: *mut ScreenFadeRaw);
+type FnPlaySoundByName  = unsafe extern "C" fn(name: *const i8, volume: f32);
+type FnGetModelByIndex  = unsafe extern "C" fn(idx: i32) -> *mut u8;
+
+/// screenfade_t (layout from the HL SDK), filled by pfnGetScreenFade.
+#[repr(C)]
+#[derive(Default)]
+struct ScreenFadeRaw {
+    speed:      f32,
+    end:        f32,
+    total_end:  f32,
+    reset:      f32,
+    color:      [u8; 4],
+    flags:      i32,
+}
 
 /// HUD player info structure (returned by engine's GetPlayerInfo).
 #[repr(C)]
@@ -326,239 +857,771 @@ pub struct PlayerData {
     pub maxs_z:     f32,      // Bounding box height (from maxs.z)
     pub team:       i32,      // Team number (1=T, 2=CT)
     pub name:       String,   // Display name
+    pub steam_id:   u64,      // SteamID64 (0 = bot / unknown)
     pub weapon:     String,   // Current weapon name
+    pub view_angles: Vec3,    // Aim (pitch, yaw, roll in degrees; see player::view_from_model_angles)
     pub is_local:   bool,     // Is this the local player?
     pub is_ducking: bool,     // Is the player crouching?
+    pub is_dead:    bool,     // Dead per extra info or alive.rs (only reported during demo playback)
+    pub health:     Option<i32>, // Health per extra info (None = not known for this player)
+    pub has_kit:    Option<bool>, // Defuse kit (None = not known)
+    pub has_helmet: Option<bool>, // Helmet (None = not known)
+    pub weapon_model: i32,    // entity_state_t::weaponmodel (changes on a weapon switch)
+    pub model_index: i32,     // Player model (for sequence names, see anim.rs)
+    pub sequence:   i32,      // Upper-body animation sequence
+    pub message:    i32,      // entity_state_t::messagenum (see player::in_pvs)
+}
+
+// ============================================================
+// Engine Table Slot Verification
+// ============================================================
+// Slot indices come from the standard cl_enginefunc_t layout; a modified or
+// non-standard client can put something else there, and transmuting a data
+// pointer into a function would crash the game. Before `resolve()` succeeds,
+// each slot the overlay calls is checked once per table: the pointer must land
+// in executable memory, and where it's safe to call, the result must look
+// right (GetEntityByIndex(0) is the world, GetLocalPlayer is in that array).
+
+/// Initialize hook status: None while it's pending (client.dll not loaded yet).
+pub fn hook_status() -> Option<Result<(), OverlayError>> {
+    HOOK_STATUS.lock().ok().and_then(|g| g.clone())
+}
+
+/// Captured engine table address (0 = not captured).
+pub fn engine_table() -> usize { ENGINE_TABLE.load(Ordering::Acquire) }
+
+/// The running mod (Counter-Strike until the game directory has been read).
+pub fn game_mod() -> GameMod {
+    GAME_MOD.lock().ok().and_then(|g| *g).unwrap_or_default()
+}
+
+/// Read the game directory once and pick the mod profile (mods.rs).
+unsafe fn detect_mod(table: usize) {
+    let Ok(mut g) = GAME_MOD.lock() else { return };
+    if g.is_some() { return; }
+    let Some(dir) = read_engine_string(table, EngineFn::GetGameDirectory).filter(|s| !s.is_empty()) else { return };
+    let m = GameMod::from_game_dir(&dir);
+    let p = m.profile();
+    logf(format!("mod: {} ({}); extra info {}, {} team colors, {} weapon names", dir, m.name(),
+        if p.extra_info { "used" } else { "not used" }, p.team_colors.len(), p.weapons.len()));
+    if let Some(l) = p.extra_layout {
+        for (o, v) in [(Offset::ExtraStride, l.stride), (Offset::ExtraTeam, l.team), (Offset::ExtraDead, l.dead),
+                       (Offset::ExtraHealth, l.health), (Offset::ExtraKit, 0), (Offset::ExtraHelmet, 0)] {
+            o.set(v);
+        }
+        logf(format!("extra info layout for {}: stride {:#x}, team {:#x}, dead {:#x}", m.name(), l.stride, l.team, l.dead));
+        load_offsets(); // esp_offsets.toml still wins
+    }
+    *g = Some(m);
+}
+
+/// Feed a DeathMsg or round start (frame event, bus.rs) to the alive fallback.
+pub fn note_event(e: &crate::bus::Event, now: u32) {
+    use crate::bus::Event;
+    let mut alive = ALIVE.lock().unwrap_or_else(|e| e.into_inner());
+    match e {
+        Event::PlayerDied(d) => alive.death_msg(d.victim, now),
+        Event::RoundStart    => alive.round_start(),
+        _ => {}
+    }
+}
+
+/// Where the dead flag comes from, for diagnostics: the extra info, or the
+/// fallback clues and how many players each accounts for.
+pub fn alive_source() -> String {
+    if extra_info_base() != 0 { return "extra info".to_string(); }
+    let counts = ALIVE.lock().unwrap_or_else(|e| e.into_inner()).counts();
+    let deathmsg = if crate::usermsg::hooked() { "DeathMsg" } else { "no DeathMsg" };
+    let dead = if counts.is_empty() {
+        "none dead".to_string()
+    } else {
+        counts.iter().map(|(c, n)| format!("{} {}", n, c.name())).collect::<Vec<_>>().join(", ")
+    };
+    format!("fallback (EF_NODRAW, sequence, {}): {}", deathmsg, dead)
+}
+
+/// Player hull heights in use: `[hulls]` overrides, else client.dll's hull
+/// table, else the mod's standard hulls.
+pub fn hulls() -> Hulls {
+    let cfg = crate::config::get();
+    let base = hull_table().unwrap_or(game_mod().profile().hulls);
+    Hulls {
+        stand: if cfg.hull_stand > 0.0 { cfg.hull_stand } else { base.stand },
+        duck:  if cfg.hull_duck > 0.0 { cfg.hull_duck } else { base.duck },
+    }
+}
+
+/// Ask client.dll's HUD_GetHullBounds for hulls 0 (standing) and 1 (ducking),
+/// once client.dll is loaded. The HL SDK's version returns 1 without filling
+/// in the vectors, so they start out NaN and an unfilled table isn't used.
+fn hull_table() -> Option<Hulls> {
+    type FnGetHullBounds = unsafe extern "C" fn(hull: i32, mins: *mut f32, maxs: *mut f32) -> i32;
+    let mut g = HULL_TABLE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(known) = *g { return known; }
+    unsafe {
+        let client = GetModuleHandleA(c"client.dll".as_ptr());
+        if client.is_null() { return None; }
+        let addr = GetProcAddress(client, c"HUD_GetHullBounds".as_ptr()) as usize;
+        let bounds = |f: FnGetHullBounds, hull: i32| {
+            let (mut mins, mut maxs) = ([f32::NAN; 3], [f32::NAN; 3]);
+            (f(hull, mins.as_mut_ptr(), maxs.as_mut_ptr()) != 0).then_some((mins[2], maxs[2]))
+        };
+        let table = if addr == 0 {
+            log("hull table: client.dll has no HUD_GetHullBounds; using the standard hulls");
+            None
+        } else {
+            let f: FnGetHullBounds = std::mem::transmute(addr);
+            let found = bounds(f, 0).zip(bounds(f, 1)).and_then(|(s, d)| Hulls::from_bounds(s, d));
+            match found {
+                Some(h) => logf(format!("hull table: standing {:.0}, ducking {:.0}", h.stand, h.duck)),
+                None    => log("hull table: HUD_GetHullBounds gave no usable hulls; using the standard hulls"),
+            }
+            found
+        };
+        *g = Some(table);
+        table
+    }
+}
+
+/// Cached g_PlayerExtraInfo address (0 = not found yet). Never scans.
+pub fn extra_info_base() -> usize { EXTRA_INFO_BASE.load(Ordering::Relaxed) }
+
+/// Why g_PlayerExtraInfo hasn't been found (None = found or not looked for yet).
+pub fn extra_info_error() -> Option<OverlayError> {
+    EXTRA_INFO_ERROR.lock().ok().and_then(|g| g.clone())
+}
+
+/// Name of the offset override file (next to the DLL).
+const OFFSETS_FILE: &str = "esp_offsets.toml";
+
+/// Structure offsets in use, for diagnostics: the overridable ones (current
+/// value, build 4554 value), then the fixed entity_state_t fields.
+pub fn offsets() -> Vec<(&'static str, usize, usize)> {
+    let mut out: Vec<_> = offsets::ALL.iter().map(|o| (o.name(), o.get(), o.default_value())).collect();
+    for (name, off) in [("state.weaponmodel", ES_WEAPONMODEL), ("state.usehull", ES_USEHULL)] {
+        out.push((name, off, off));
+    }
+    out
+}
+
+/// Apply esp_offsets.toml, if there is one. Called once at startup.
+pub fn load_offsets() {
+    let path = dll_dir().join(OFFSETS_FILE);
+    let Ok(text) = std::fs::read_to_string(&path) else { return };
+    let (values, bad) = offsets::parse(&text);
+    for &(o, v) in &values {
+        o.set(v);
+        logf(format!("offset override: {} = {:#x}", o.name(), v));
+    }
+    for line in bad {
+        logf(format!("{}:{}: not an offset (name = value), skipped", OFFSETS_FILE, line));
+    }
+}
+
+/// Write the offsets in use to esp_offsets.toml (the offset tuner's export).
+pub unsafe fn save_offsets() -> std::io::Result<std::path::PathBuf> {
+    let path = dll_dir().join(OFFSETS_FILE);
+    let build = engine_build().unwrap_or_else(|| "?".to_string());
+    let note = format!("engine build {}, saved by the offset tuner", build);
+    std::fs::write(&path, offsets::format(&offsets::current(), &note))?;
+    logf(format!("offsets saved to {}", path.display()));
+    Ok(path)
+}
+
+/// Engine build reported by the `sv_version` cvar ("1.1.2.7/Stdio,48,8684" -> "8684"),
+/// read once the table is captured.
+pub unsafe fn engine_build() -> Option<String> {
+    static BUILD: Mutex<Option<String>> = Mutex::new(None);
+    if let Some(b) = BUILD.lock().ok()?.clone() { return Some(b); }
+
+    let f: FnGetCvarString = current_fn(EngineFn::GetCvarString)?;
+    let version = read_cstr(f(b"sv_version\0".as_ptr() as _), 64)?;
+    let build = version.rsplit(',').next()?.trim().to_string();
+    if build.is_empty() { return None; }
+    *BUILD.lock().ok()? = Some(build.clone());
+    Some(build)
+}
+
+/// Whether a console command was registered (an eject must then be refused).
+static COMMANDS_ADDED: AtomicBool = AtomicBool::new(false);
+
+/// Register a console command (pfnAddCommand). `name` is NUL-terminated and
+/// kept by the engine, as is `f`, until the game exits.
+pub unsafe fn add_command(name: &'static [u8], f: unsafe extern "C" fn()) -> bool {
+    let Some(add) = current_fn::<FnAddCommand>(EngineFn::AddCommand) else { return false };
+    add(name.as_ptr() as _, f);
+    COMMANDS_ADDED.store(true, Ordering::Relaxed);
+    true
+}
+
+/// Whether any console command of ours is registered: the engine would call
+/// into the DLL after it's unloaded.
+#[cfg(feature = "ipc")]
+pub fn commands_added() -> bool {
+    COMMANDS_ADDED.load(Ordering::Relaxed)
+}
+
+/// Arguments of the console command being run (Cmd_Argv 1..Cmd_Argc). Only
+/// meaningful inside a command handler.
+pub unsafe fn cmd_args() -> Vec<String> {
+    let (Some(argc), Some(argv)) = (current_fn::<FnArgc>(EngineFn::CmdArgc), current_fn::<FnArgv>(EngineFn::CmdArgv))
+        else { return Vec::new() };
+    (1..argc()).filter_map(|i| read_cstr(argv(i), 64)).collect()
+}
+
+/// Result of verifying one engine table slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotCheck {
+    Unchecked,
+    Ok,
+    NotExecutable, // Pointer is null or not in executable memory
+    BadResult,     // Callable, but returned something implausible
+}
+
+/// Slots verified before use: (slot index, name, required for resolve()).
+pub const VERIFIED_SLOTS: [(usize, &str, bool); 5] = [
+    (SLOT_GET_PLAYER_INFO,     "GetPlayerInfo",    false),
+    (SLOT_GET_LOCAL_PLAYER,    "GetLocalPlayer",   true),
+    (SLOT_GET_ENTITY_BY_INDEX, "GetEntityByIndex", true),
+    (SLOT_PTRIAPI,             "pTriAPI",          true),
+    (SLOT_GET_MODEL_BY_INDEX,  "GetModelByIndex",  false),
+];
+
+/// The table last checked with its per-slot results (same order as
+/// VERIFIED_SLOTS), and the table that passed.
+static SLOT_STATUS: Mutex<(usize, [SlotCheck; 5])> = Mutex::new((0, [SlotCheck::Unchecked; 5]));
+static VERIFIED_TABLE: AtomicUsize = AtomicUsize::new(0);
+
+/// Retry interval for a table that failed verification, and the last attempt.
+const VERIFY_RETRY_MS: u32 = 1000;
+static LAST_VERIFY_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Current verification results, for diagnostics.
+pub fn slot_status() -> [SlotCheck; 5] {
+    SLOT_STATUS.lock().map(|g| g.1).unwrap_or([SlotCheck::Unchecked; 5])
+}
+
+/// Verify the current engine table's slots again now, ignoring the cached
+/// result (self-test). Err names the slots that fail.
+pub unsafe fn recheck_slots() -> Result<(), String> {
+    let table = ENGINE_TABLE.load(Ordering::Acquire);
+    if table == 0 { return Err("no engine table".to_string()); }
+    if !is_readable(table, (SLOT_GET_MODEL_BY_INDEX + 1) * 4) { return Err(format!("table {:#010x} not readable", table)); }
+    let failed: Vec<String> = VERIFIED_SLOTS.iter()
+        .map(|&(slot, name, _)| (name, check_slot(table, slot)))
+        .filter(|&(_, st)| st != SlotCheck::Ok)
+        .map(|(name, st)| format!("{} {:?}", name, st))
+        .collect();
+    if failed.is_empty() { Ok(()) } else { Err(failed.join(", ")) }
+}
+
+/// Whether `slot` passed verification (slots not in VERIFIED_SLOTS always pass).
+fn slot_ok(slot: usize) -> bool {
+    match VERIFIED_SLOTS.iter().position(|&(s, _, _)| s == slot) {
+        Some(i) => slot_status()[i] == SlotCheck::Ok,
+        None => true,
+    }
+}
+
+/// Verify the slots of `table`. Returns false if a required slot failed.
+/// A passing table is verified once; a failing one is retried every
+/// VERIFY_RETRY_MS (e.g. GetLocalPlayer isn't meaningful mid-connect). A
+/// table other than the one last checked is checked at once: the results
+/// are only ever used for the table they were taken on.
+unsafe fn verify_slots(table: usize) -> bool {
+    let now = time::now_ms();
+    let settled = VERIFIED_TABLE.load(Ordering::Acquire) == table;
+    let (checked, prev) = SLOT_STATUS.lock().map(|g| *g).unwrap_or((0, [SlotCheck::Unchecked; 5]));
+    let last = LAST_VERIFY_MS.load(Ordering::Relaxed);
+    if !settled && (checked != table || last == 0 || now.wrapping_sub(last) >= VERIFY_RETRY_MS) {
+        LAST_VERIFY_MS.store(now, Ordering::Relaxed);
+        forget_engine_fns(); // Re-check the cached pointers along with the slots
+        let mut status = [SlotCheck::Unchecked; 5];
+        for (i, &(slot, name, _)) in VERIFIED_SLOTS.iter().enumerate() {
+            status[i] = check_slot(table, slot);
+            if status[i] != SlotCheck::Ok && status[i] != prev[i] {
+                logf(format!("engine slot {} ({}) failed verification: {:?}", slot, name, status[i]));
+            }
+        }
+        if let Ok(mut g) = SLOT_STATUS.lock() { *g = (table, status); }
+        if required_ok(&status) { VERIFIED_TABLE.store(table, Ordering::Release); }
+    }
+    SLOT_STATUS.lock().is_ok_and(|g| g.0 == table && required_ok(&g.1))
+}
+
+/// Whether every required slot in `status` passed.
+fn required_ok(status: &[SlotCheck; 5]) -> bool {
+    VERIFIED_SLOTS.iter().zip(status.iter()).all(|(&(_, _, required), &st)| !required || st == SlotCheck::Ok)
+}
+
+/// Check one slot's pointer and, where safe, its behavior.
+unsafe fn check_slot(table: usize, slot: usize) -> SlotCheck {
+    let ptr = read_u32(table + slot * 4) as usize;
+    match slot {
+        SLOT_PTRIAPI => {
+            // Data pointer to triangleapi_t; its WorldToScreen must be code
+            if !is_readable(ptr, 13 * 4) { return SlotCheck::NotExecutable; }
+            if !is_executable(read_u32(ptr + 12 * 4) as usize) { return SlotCheck::NotExecutable; }
+            SlotCheck::Ok
+        }
+        _ if !is_executable(ptr) => SlotCheck::NotExecutable,
+        SLOT_GET_ENTITY_BY_INDEX => {
+            let f: FnGetEntityByIndex = std::mem::transmute(ptr);
+            let world = f(0) as usize;
+            if is_readable(world, Offset::Curstate.get()) && read_i32(world) == 0 {
+                SlotCheck::Ok
+            } else {
+                SlotCheck::BadResult
+            }
+        }
+        SLOT_GET_LOCAL_PLAYER => {
+            // The local entity must be the one GetEntityByIndex returns for its index
+            let get_ent = read_u32(table + SLOT_GET_ENTITY_BY_INDEX * 4) as usize;
+            if !is_executable(get_ent) { return SlotCheck::BadResult; }
+            let f: FnGetLocalPlayer = std::mem::transmute(ptr);
+            let local = f() as usize;
+            if !is_readable(local, Offset::Curstate.get()) { return SlotCheck::BadResult; }
+            let idx = read_i32(local);
+            if !(1..=MAX_CLIENTS).contains(&idx) { return SlotCheck::BadResult; }
+            let by_index: FnGetEntityByIndex = std::mem::transmute(get_ent);
+            if by_index(idx) as usize == local { SlotCheck::Ok } else { SlotCheck::BadResult }
+        }
+        _ => SlotCheck::Ok,
+    }
+}
+
+/// Whether `addr` is in committed executable memory (execute-only pages too).
+pub unsafe fn is_executable(addr: usize) -> bool {
+    if addr < 0x10000 { return false; }
+    let exec = PAGE_EXECUTE | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
+    let mut mbi: MEMORY_BASIC_INFORMATION = std::mem::zeroed();
+    let ret = VirtualQuery(addr as *const _, &mut mbi,
+        std::mem::size_of::<MEMORY_BASIC_INFORMATION>());
+    ret != 0 && mbi.State == MEM_COMMIT && mbi.Protect & exec != 0
+}
+
+/// Where (and when) a player died, recorded on the alive→dead transition.
+#[derive(Clone)]
+pub struct DeathSpot {
+    pub origin:  Vec3,    // Last origin read for the player (hull center)
+    pub team:    i32,     // Team number at the time of death
+    pub name:    String,  // Display name
+    pub time_ms: u32,     // time::now_ms() when the death was seen
+}
+
+/// Death spots recorded within the last `max_age_ms`; older entries are pruned.
+pub fn death_spots(max_age_ms: u32) -> Vec<DeathSpot> {
+    let now = time::now_ms();
+    let mut spots = match DEATH_SPOTS.lock() { Ok(g) => g, Err(p) => p.into_inner() };
+    spots.retain(|d| now.wrapping_sub(d.time_ms) <= DEATH_SPOT_MAX_MS);
+    spots.iter()
+        .filter(|d| now.wrapping_sub(d.time_ms) <= max_age_ms)
+        .cloned()
+        .collect()
+}
+
+/// Record a death, replacing any earlier spot with the same name.
+fn record_death(origin: Vec3, team: i32, name: String) {
+    let mut spots = match DEATH_SPOTS.lock() { Ok(g) => g, Err(p) => p.into_inner() };
+    spots.retain(|d| d.name != name);
+    spots.push(DeathSpot { origin, team, name, time_ms: time::now_ms() });
+}
+
+/// Append a trail sample for slot `i`, rate-limited to one per TRAIL_SAMPLE_MS.
+unsafe fn push_trail(i: usize, origin: Vec3, now: u32) {
+    let head = TRAIL_HEAD[i];
+    let (_, last_ms) = TRAIL[i][(head + TRAIL_LEN - 1) % TRAIL_LEN];
+    if last_ms != 0 && now.wrapping_sub(last_ms) < TRAIL_SAMPLE_MS { return; }
+    TRAIL[i][head] = (origin, now);
+    TRAIL_HEAD[i] = (head + 1) % TRAIL_LEN;
+}
+
+/// Forget slot `i`'s trail (death, respawn, or the player going stale).
+unsafe fn clear_trail(i: usize) {
+    TRAIL[i] = [(Vec3::default(), 0u32); TRAIL_LEN];
+    TRAIL_HEAD[i] = 0;
+}
+
+/// Recent positions of player `idx` within `max_age_ms`, oldest first, with their timestamps.
+pub fn trail(idx: i32, max_age_ms: u32) -> Vec<(Vec3, u32)> {
+    if idx < 1 || idx > MAX_CLIENTS { return Vec::new(); }
+    let i = idx as usize;
+    let now = time::now_ms();
+    unsafe {
+        let head = TRAIL_HEAD[i];
+        (0..TRAIL_LEN)
+            .map(|k| TRAIL[i][(head + k) % TRAIL_LEN])
+            .filter(|&(_, t)| t != 0 && now.wrapping_sub(t) <= max_age_ms)
+            .collect()
+    }
 }
 
 /// High-level wrapper around the engine function table.
-pub struct EngineApi { table: usize }
+pub struct EngineApi {
+    table:       usize,
+    demo:        bool, // A .dem is being played back (sampled once per resolve)
+    cross_check: bool, // projection.cross_check (sampled once per resolve)
+    bounds:      Option<Bounds>, // Current map's world bounds (sampled once per resolve)
+}
 
 impl EngineApi {
     /// Try to resolve the engine API. Returns None if:
     ///   - The hook isn't installed yet
     ///   - No map is loaded
     ///   - The engine table is invalid
+    /// Never scans: the scans it needs are left to the worker thread.
     pub unsafe fn resolve() -> Option<Self> {
-        // Install the Initialize hook if not done yet
-        if !HOOK_INSTALLED.load(Ordering::Relaxed) {
-            install_initialize_hook();
-        }
+        // The Initialize hook is installed (and retried) by the worker thread
         if !MAP_LOADED.load(Ordering::Acquire) { return None; }
 
         let table = ENGINE_TABLE.load(Ordering::Acquire);
         if table == 0 { return None; }
 
         // Validate that key slots contain valid function pointers
-        let s51 = read_u32(table + SLOT_GET_LOCAL_PLAYER * 4);
-        let s53 = read_u32(table + SLOT_GET_ENTITY_BY_INDEX * 4);
-        if s51 == 0 || s53 == 0 { return None; }
+        if engine_fn::<FnGetLocalPlayer>(table, EngineFn::GetLocalPlayer).is_none()
+            || engine_fn::<FnGetEntityByIndex>(table, EngineFn::GetEntityByIndex).is_none() {
+            return None;
+        }
+        check_map_change(table);
+        detect_mod(table);
+        if !verify_slots(table) {
+            if REVALIDATE.swap(false, Ordering::Relaxed) {
+                RESCAN_TABLE.store(table, Ordering::Relaxed);
+                crate::wake_worker();
+            }
+            return None;
+        }
+        REVALIDATE.store(false, Ordering::Relaxed);
 
-        // Try to find g_PlayerExtraInfo if not cached yet
-        if EXTRA_INFO_BASE.load(Ordering::Relaxed) == 0 {
-            get_extra_info_base();
+        // Have the worker look for g_PlayerExtraInfo if it isn't cached yet
+        if EXTRA_INFO_BASE.load(Ordering::Relaxed) == 0 && !EXTRA_INFO_WANTED.swap(true, Ordering::Relaxed) {
+            crate::wake_worker();
         }
 
-        Some(Self { table })
+        // Capture the render FOV from the client
+        install_client_data_hook();
+        install_create_move_hook();
+        install_calc_refdef_hook();
+        crate::events::install(table);
+        crate::usermsg::install();
+        crate::usermsg::install_console(table);
+        crate::config::install_command();
+        crate::bundle::install_command();
+
+        let demo = is_demo_playback(table);
+        let cross_check = crate::config::get().w2s_cross_check;
+        let mut api = Self { table, demo, cross_check, bounds: None };
+        api.bounds = crate::mapdata::current(&api).and_then(|m| m.bounds);
+        Some(api)
     }
 
+    /// Whether a demo is being played back. During playback the overlay acts as a
+    /// review tool: dead players are reported (flagged) instead of dropped, and
+    /// only the recording player counts as "local".
+    pub fn demo_playback(&self) -> bool { self.demo }
+
     /// Whether a map is currently loaded.
     pub fn map_loaded() -> bool { MAP_LOADED.load(Ordering::Acquire) }
 
+    /// The function in `f`'s slot of this table (see engine_fn).
+    unsafe fn func<F: Copy>(&self, f: EngineFn) -> Option<F> {
+        engine_fn(self.table, f)
+    }
+
+    /// The local player's cl_entity_t (GetLocalPlayer).
+    unsafe fn local_entity(&self) -> Option<usize> {
+        let f: FnGetLocalPlayer = self.func(EngineFn::GetLocalPlayer)?;
+        let ent = f() as usize;
+        if ent == 0 { engine_fn_failed(EngineFn::GetLocalPlayer); return None; }
+        Some(ent)
+    }
+
     /// Get the local player's world position.
     pub unsafe fn local_origin(&self) -> Option<Vec3> {
-        let fn_ptr = read_u32(self.table + SLOT_GET_LOCAL_PLAYER * 4) as usize;
-        if fn_ptr == 0 { return None; }
-        let f: FnGetLocalPlayer = std::mem::transmute(fn_ptr);
-        let ent = f();
-        if ent.is_null() { return None; }
-
-        let o = read_vec3(ent as usize + ENT_ORIGIN);
+        let ent = self.local_entity()?;
+        let o = read_vec3(ent + Offset::Origin.get());
         if o.is_zero() { return None; }
         Some(o)
     }
 
+    /// The local player's slot (cl_entity_t::index of GetLocalPlayer).
+    pub unsafe fn local_index(&self) -> Option<i32> {
+        Some(read_i32(self.local_entity()?))
+    }
+
+    /// The local player's team number from g_PlayerExtraInfo (indexed by the
+    /// local entity's index). None if either isn't available.
+    pub unsafe fn local_team(&self) -> Option<i32> {
+        player::extra_team(self, self.local_index()?)
+    }
+
+    /// Message number of the newest server update: the local player's
+    /// entity_state_t::messagenum (the local player is in every update).
+    pub unsafe fn latest_message(&self) -> Option<i32> {
+        let ent = self.local_entity()?;
+        Some(read_i32(ent + Offset::Curstate.get() + world::ES_MESSAGENUM))
+    }
+
+    /// Observer state of the local player: (mode, target slot).
+    /// Mode 0 means not spectating; see player::OBS_IN_EYE for first-person spectate.
+    pub unsafe fn observer(&self) -> Option<(i32, i32)> {
+        let cs = self.local_entity()? + Offset::Curstate.get();
+        Some((read_i32(cs + ES_IUSER1), read_i32(cs + ES_IUSER2)))
+    }
+
+    /// Get the local view angles (pitch, yaw, roll in degrees).
+    pub unsafe fn view_angles(&self) -> Option<Vec3> {
+        let f: FnGetViewAngles = self.func(EngineFn::GetViewAngles)?;
+        let mut a = [0f32; 3];
+        f(a.as_mut_ptr());
+        if !a.iter().all(|v| v.is_finite()) { engine_fn_failed(EngineFn::GetViewAngles); return None; }
+        Some(Vec3 { x: a[0], y: a[1], z: a[2] })
+    }
+
+    /// Client time in seconds (the clock screen fades are timed against).
+    pub unsafe fn client_time(&self) -> Option<f32> {
+        let f: FnGetClientTime = self.func(EngineFn::GetClientTime)?;
+        let t = f();
+        if !t.is_finite() { engine_fn_failed(EngineFn::GetClientTime); return None; }
+        Some(t)
+    }
+
+    /// The engine's current screen fade (flashbangs, death fades).
+    pub unsafe fn screen_fade(&self) -> Option<ScreenFade> {
+        let f: FnGetScreenFade = self.func(EngineFn::GetScreenFade)?;
+        let mut raw = ScreenFadeRaw::default();
+        f(&mut raw);
+        if ![raw.speed, raw.end, raw.reset].iter().all(|v| v.is_finite()) {
+            engine_fn_failed(EngineFn::GetScreenFade);
+            return None;
+        }
+        Some(ScreenFade { speed: raw.speed, end: raw.end, reset: raw.reset, color: raw.color, flags: raw.flags })
+    }
+
     /// Read all relevant data for a specific player by slot index.
     /// Returns None for invalid, dead, spectating, or unresolvable players.
     pub unsafe fn read_player(&self, idx: i32) -> Option<PlayerData> {
-        if idx <= 0 || idx > MAX_CLIENTS { return None; }
-
-        // --- Get player info (name, spectator status) ---
-        let mut pinfo: HudPlayerInfo = std::mem::zeroed();
-        let mut name: Option<String> = None;
-        if let Some(f_info) = self.get_player_info_fn() {
-            f_info(idx, &mut pinfo as *mut HudPlayerInfo);
-            name = read_cstr(pinfo.name, 32);
-            if name.is_none() { return None; }        // No name = slot is empty
-            if pinfo.spectator != 0 { return None; }  // Skip spectators
-        }
-
-        // --- Get the entity pointer ---
-        let fn_ptr = read_u32(self.table + SLOT_GET_ENTITY_BY_INDEX * 4) as usize;
-        if fn_ptr == 0 { return None; }
-        let f: FnGetEntityByIndex = std::mem::transmute(fn_ptr);
-        let ent = f(idx);
-        if ent.is_null() { return None; }
-        let base = ent as usize;
-
-        // Validate entity index and player flag
-        let ent_index = read_i32(base + 0x00);
-        let is_player = read_i32(base + 0x04);
-        if is_player == 0 { return None; }
-        if ent_index > 0 && ent_index <= MAX_CLIENTS && ent_index != idx { return None; }
-
-        let cs = base + CURSTATE_OFFSET; // entity_state_t pointer
-
-        // --- Resolve player origin (with multiple fallbacks) ---
-        // Try: interpolated origin -> position history -> entity state origin
-        let mut origin = read_vec3(base + ENT_ORIGIN);
-        if !origin.x.is_finite() || !origin.y.is_finite() || !origin.z.is_finite() || origin.is_zero() {
-            // Fallback 1: position history ring buffer
-            let cur_pos = read_i32(base + ENT_CURPOS) as usize & PH_HISTORY_MASK;
-            let ph_addr = base + ENT_PH_BASE + cur_pos * PH_ENTRY_SIZE;
-            let ph_origin = read_vec3(ph_addr + 4);
-            if ph_origin.x.is_finite() && ph_origin.y.is_finite() && ph_origin.z.is_finite() && !ph_origin.is_zero() {
-                origin = ph_origin;
-            } else {
-                // Fallback 2: entity state origin
-                let cs_origin = read_vec3(cs + ES_ORIGIN);
-                if cs_origin.x.is_finite() && cs_origin.y.is_finite() && cs_origin.z.is_finite() && !cs_origin.is_zero() {
-                    origin = cs_origin;
-                } else {
-                    return None; // All origin sources failed
-                }
+        let PlayerFields {
+            name, is_local, steam_id, is_bot, mut origin, cur_pos, message, team, has_extra, mut is_dead,
+            health, has_kit, has_helmet, weapon_model, model_index, sequence, view_angles, is_ducking, maxs_z,
+            sanity,
+        } = match player::interpret_player_checked(self, idx) {
+            Ok(fields) => fields,
+            Err(why) => {
+                let (failed, outcome) = match why {
+                    Rejected::Insane(s) => (s, ReadOutcome::Insane),
+                    Rejected::Empty     => (Sanity::default(), ReadOutcome::Empty),
+                    Rejected::Spectator => (Sanity::default(), ReadOutcome::Spectator),
+                    Rejected::NoEntity  => (Sanity::default(), ReadOutcome::NoEntity),
+                    Rejected::NoOrigin  => (Sanity::default(), ReadOutcome::NoOrigin),
+                };
+                record_sanity(idx, failed);
+                record_read(idx, outcome);
+                ALIVE.lock().unwrap_or_else(|e| e.into_inner()).forget(idx);
+                return None;
             }
-        }
+        };
+        record_sanity(idx, sanity);
 
         // --- Staleness detection ---
-        // If a player's position history index hasn't changed for too many frames,
+        // If a player's position history index hasn't changed for too long,
         // their data might be stale (e.g. they disconnected but weren't cleaned up).
-        let frame = FRAME_COUNTER.load(Ordering::Relaxed);
+        let now = time::now_ms();
         let i = idx as usize;
-        let cur_pos_val = read_i32(base + ENT_CURPOS) as usize & PH_HISTORY_MASK;
+
+        // A bot in the latest update is live even if its history didn't move:
+        // a bot standing still (camping, bot_stop) can go without a new entry.
+        let newest = if is_bot { self.latest_message().unwrap_or(0) } else { 0 };
+        let bot_fresh = newest != 0 && message >= newest;
 
         let last_cp = LAST_CURPOS[i];
-        if last_cp != cur_pos_val {
+        if last_cp != cur_pos || bot_fresh {
             // Position history updated — player is active
-            LAST_CURPOS[i] = cur_pos_val;
-            LAST_CURPOS_FRAME[i] = frame;
+            LAST_CURPOS[i] = cur_pos;
+            LAST_CURPOS_MS[i] = now;
             LAST_KNOWN_ORIGIN[i] = origin;
         } else {
             // Position history hasn't changed — check staleness
-            let last_frame = LAST_CURPOS_FRAME[i];
-            if last_frame == 0 { return None; }
-            let age = frame.wrapping_sub(last_frame);
-            if age > ORIGIN_STALE_FRAMES {
+            let last_ms = LAST_CURPOS_MS[i];
+            if last_ms == 0 { record_read(idx, ReadOutcome::Stale); return None; }
+            let age = now.wrapping_sub(last_ms);
+            if age > ORIGIN_STALE_MS {
                 // Use cached origin for a while, then give up
-                if age <= ORIGIN_STALE_FRAMES.saturating_mul(8) {
+                if age <= ORIGIN_DROP_MS {
                     let cached = LAST_KNOWN_ORIGIN[i];
-                    if cached.is_zero() { return None; }
+                    if cached.is_zero() { record_read(idx, ReadOutcome::Stale); return None; }
                     origin = cached;
                 } else {
+                    clear_trail(i);
+                    record_read(idx, ReadOutcome::Stale);
                     return None; // Too stale
                 }
             }
         }
 
-        // --- Team and alive/dead status from g_PlayerExtraInfo ---
-        let base_ei = get_extra_info_base();
-        let slot_addr = if base_ei != 0 { base_ei + (idx as usize) * EXTRA_STRIDE } else { 0 };
-
-        let team = if slot_addr != 0 {
-            read_i16(slot_addr + EXTRA_OFF_TEAMNUMBER) as i32
-        } else { 0 };
-
-        // Skip dead players
-        if slot_addr != 0 {
-            let is_dead = read_u8(slot_addr + EXTRA_OFF_DEAD);
-            if is_dead != 0 { return None; }
+        // Without extra info, dead comes from the fallback clues (alive.rs)
+        if !has_extra {
+            let cs = self.entity(idx) + Offset::Curstate.get();
+            let effects = read_i32(cs + world::ES_EFFECTS);
+            is_dead = ALIVE.lock().unwrap_or_else(|e| e.into_inner())
+                .judge(idx, effects, model_index, sequence, now,
+                       || crate::anim::sequence_name(self, self.model(model_index), sequence))
+                .is_some();
         }
 
-        // --- Weapon name (from the weapon model path) ---
-        let weapon_name = {
-            let wmodel_idx = read_i32(cs + ES_WEAPONMODEL);
-            if wmodel_idx > 0 {
-                self.get_weapon_name(wmodel_idx)
-            } else {
-                String::new()
-            }
-        };
+        // Track the alive→dead transition so the death spot can be marked
+        if is_dead && LIFE_STATE[i] == 1 {
+            let who = name.clone().unwrap_or_else(|| format!("P{}", idx));
+            record_death(origin, team, who);
+        }
+        LIFE_STATE[i] = if is_dead { 2 } else { 1 };
 
-        // --- Ducking detection ---
-        let usehull = read_i32(cs + ES_USEHULL);
-        let is_ducking = usehull == 1; // Hull 1 = duck hull
+        // Breadcrumb trail (alive players only; a respawn starts a fresh trail)
+        if is_dead { clear_trail(i); } else { push_trail(i, origin, now); }
 
-        // --- Bounding box height ---
-        let margin = 4.0;
-        let maxs_z = if is_ducking {
-            let maxs_duck = read_f32(cs + ES_MAXS + 8); // maxs.z
-            if maxs_duck > 0.0 && maxs_duck < 60.0 { maxs_duck + margin } else { 44.0 + margin }
-        } else {
-            let maxs_stand = read_f32(cs + ES_MAXS + 8);
-            if maxs_stand > 60.0 && maxs_stand < 90.0 { maxs_stand + margin } else { 72.0 + margin }
-        };
+        // Skip dead players (kept and flagged during demo playback)
+        if is_dead && !self.demo { record_read(idx, ReadOutcome::Dead); return None; }
+        record_read(idx, ReadOutcome::Read);
 
-        let name = name.unwrap_or_else(|| format!("P{}", idx));
-        let is_local = pinfo.thisplayer != 0;
+        // --- Weapon name (from the weapon model path) ---
+        let weapon = if weapon_model > 0 { self.get_weapon_name(weapon_model) } else { String::new() };
 
         Some(PlayerData {
             origin,
             maxs_z,
             team,
-            name,
-            weapon: weapon_name,
+            name: name.unwrap_or_else(|| if is_bot { format!("BOT {}", idx) } else { format!("P{}", idx) }),
+            steam_id,
+            weapon,
+            view_angles,
             is_local,
             is_ducking,
+            is_dead,
+            health,
+            has_kit,
+            has_helmet,
+            weapon_model,
+            model_index,
+            sequence,
+            message,
         })
     }
 
+    /// Every non-empty entity (players included) up to the engine's edict count.
+    /// See world.rs; costs one GetEntityByIndex per slot plus a short probe.
+    pub fn iter_entities(&self) -> impl Iterator<Item = RawEntity> {
+        world::scan(self).into_iter()
+    }
+
+    /// Play a sound file (relative to sound/, e.g. "buttons/blip1.wav") through
+    /// the engine, at `volume` 0..1. Doesn't block; honors the game's volume.
+    pub unsafe fn play_sound(&self, name: &str, volume: f32) {
+        let Some(f) = self.func::<FnPlaySoundByName>(EngineFn::PlaySoundByName) else { return };
+        let Ok(cname) = std::ffi::CString::new(name) else { return };
+        f(cname.as_ptr(), volume.clamp(0.0, 1.0));
+    }
+
+    /// Read a float cvar through the engine (None if the slot is invalid).
+    /// Unknown cvars read as 0.0, as the engine reports them.
+    pub unsafe fn cvar_float(&self, name: &str) -> Option<f32> {
+        let f: FnGetCvarFloat = self.func(EngineFn::GetCvarFloat)?;
+        let cname = std::ffi::CString::new(name).ok()?;
+        Some(f(cname.as_ptr()))
+    }
+
+    /// Current horizontal field of view in degrees, including scope zoom.
+    /// Falls back to the default_fov cvar, then 90, if the client hook hasn't fired.
+    pub unsafe fn fov(&self) -> f32 {
+        let bits = CURRENT_FOV.load(Ordering::Relaxed);
+        if bits != 0 { return f32::from_bits(bits); }
+        match self.cvar_float("default_fov") {
+            Some(f) if f > 0.0 && f < 180.0 => f,
+            _ => 90.0,
+        }
+    }
+
     /// Maximum number of player slots.
     pub fn max_clients(&self) -> i32 { MAX_CLIENTS }
 
     /// Get a weapon's display name from its model index.
     /// The engine stores weapon models like "models/p_ak47.mdl".
-    /// We extract "AK47" from the model path.
+    /// We extract "ak47" from the model path and look it up in the mod's
+    /// weapon names ("AK47" when it isn't listed).
     pub unsafe fn get_weapon_name(&self, model_index: i32) -> String {
-        type FnGetModelByIndex = unsafe extern "C" fn(idx: i32) -> *mut u8;
-        let fn_ptr = read_u32(self.table + SLOT_GET_MODEL_BY_INDEX * 4) as usize;
-        if fn_ptr < 0x10000 { return String::new(); }
-
-        let f: FnGetModelByIndex = std::mem::transmute(fn_ptr);
-        let model = f(model_index);
-        if model.is_null() { return String::new(); }
-        let model_addr = model as usize;
-        if !is_readable(model_addr, 64) { return String::new(); }
-
-        if let Some(name) = read_cstr(model_addr as *const i8, 64) {
+        if let Some(name) = self.model_name(model_index) {
             // Look for "p_" prefix (player weapon model) or "w_" (world weapon model)
             for prefix in &["p_", "w_"] {
                 if let Some(start) = name.find(prefix) {
                     let after = &name[start + 2..];
                     let end = after.find('.').unwrap_or(after.len());
-                    return after[..end].to_uppercase();
+                    return game_mod().profile().weapon_name(&after[..end]);
                 }
             }
         }
         String::new()
     }
 
-    /// Get the GetPlayerInfo function pointer from the engine table.
+    /// The model_t of a precached model (0 = none).
+    pub unsafe fn model(&self, model_index: i32) -> usize {
+        if !slot_ok(SLOT_GET_MODEL_BY_INDEX) { return 0; }
+        let Some(f) = self.func::<FnGetModelByIndex>(EngineFn::GetModelByIndex) else { return 0 };
+        f(model_index) as usize
+    }
+
+    /// Name of a precached model: a path like "models/p_ak47.mdl", or "*12"
+    /// for a brush entity's inline model.
+    pub unsafe fn model_name(&self, model_index: i32) -> Option<String> {
+        let model_addr = self.model(model_index);
+        if model_addr == 0 || !is_readable(model_addr, 64) { return None; }
+        read_cstr(model_addr as *const i8, 64)
+    }
+
+    /// Path of the loaded map relative to the game directory ("maps/de_dust2.bsp").
+    /// None between maps.
+    pub unsafe fn level_name(&self) -> Option<String> {
+        self.engine_string(EngineFn::GetLevelName).filter(|s| !s.is_empty())
+    }
+
+    /// The mod's game directory ("cstrike", "czero", ...).
+    pub unsafe fn game_dir(&self) -> Option<String> {
+        self.engine_string(EngineFn::GetGameDirectory).filter(|s| !s.is_empty())
+    }
+
+    /// Call a string-returning engine function with no arguments.
+    unsafe fn engine_string(&self, f: EngineFn) -> Option<String> {
+        read_engine_string(self.table, f)
+    }
+
+    /// GetPlayerInfo, once it passed verification.
     unsafe fn get_player_info_fn(&self) -> Option<FnGetPlayerInfo> {
-        let fn_ptr = read_u32(self.table + SLOT_GET_PLAYER_INFO * 4) as usize;
-        if fn_ptr <= 0x10000 { return None; }
-        Some(std::mem::transmute(fn_ptr))
+        if !slot_ok(SLOT_GET_PLAYER_INFO) { return None; }
+        self.func(EngineFn::GetPlayerInfo)
     }
 
-    /// Project a 3D world position to 2D screen coordinates using the engine's TriAPI.
-    /// Returns NDC coordinates (normalized device coordinates) or None if behind camera.
+    /// Project a world point to normalized screen coordinates (-1..1, y up).
+    /// Uses the engine's TriAPI WorldToScreen; when that's missing (some custom
+    /// clients zero pTriAPI) or returns garbage, falls back to projecting with
+    /// the captured refdef (math::world_to_ndc). With `projection.cross_check`
+    /// on, both are computed and divergence is logged (once a second at most).
+    /// Returns None if the point is behind the camera.
     pub unsafe fn world_to_screen(&self, world: Vec3) -> Option<(f32, f32)> {
-        // Get the TriAPI interface pointer
-        let tri_api = read_u32(self.table + SLOT_PTRIAPI * 4) as usize;
-        if tri_api < 0x10000 { return None; }
+        match self.tri_world_to_screen(world) {
+            Some(Some(ndc)) => {
+                if self.cross_check { cross_check_w2s(world, ndc); }
+                Some(ndc)
+            }
+            Some(None) => None,
+            None => refdef_world_to_screen(world),
+        }
+    }
 
-        // TriAPI slot 12 = WorldToScreen function
-        let w2s_fn_ptr = read_u32(tri_api + 12 * 4) as usize;
-        if w2s_fn_ptr == 0 { return None; }
+    /// TriAPI WorldToScreen: None if it can't be used, Some(None) if the point
+    /// is behind the camera.
+    unsafe fn tri_world_to_screen(&self, world: Vec3) -> Option<Option<(f32, f32)>> {
+        // TriAPI interface -> slot 12 = WorldToScreen function
+        let slot = follow_chain(self.table + SLOT_PTRIAPI * 4, &[12 * 4]).ok()?;
+        let w2s_fn_ptr = read_u32(slot) as usize;
+        if w2s_fn_ptr < 0x10000 { return None; }
 
         type FnWorldToScreen = unsafe extern "C" fn(world: *const f32, screen: *mut f32) -> i32;
         let w2s_fn: FnWorldToScreen = std::mem::transmute(w2s_fn_ptr);
@@ -568,9 +1631,207 @@ impl EngineApi {
         let z_clipped = w2s_fn(world_arr.as_ptr(), screen.as_mut_ptr());
 
         // z_clipped != 0 means the point is behind the camera
-        if z_clipped != 0 { return None; }
-        Some((screen[0], screen[1]))
+        if z_clipped != 0 { return Some(None); }
+        if !screen[0].is_finite() || !screen[1].is_finite() { return None; }
+        Some(Some((screen[0], screen[1])))
+    }
+}
+
+// ============================================================
+// Entity Pointer Cache
+// ============================================================
+// A player's cl_entity_t is an element of the engine's entity array, which
+// only moves when a map loads, yet every read of a slot asked the engine for
+// it again (several times per slot per frame). The pointers of the player
+// slots are kept instead: the first use in a frame only checks that the
+// cached one is still readable and still holds that index, later uses in the
+// frame take it as is, and a map change drops them all.
+
+const ENT_CACHE_SLOTS: usize = MAX_CLIENTS as usize + 1; // World (0) and the player slots
+
+static ENT_PTRS: [AtomicUsize; ENT_CACHE_SLOTS] = [const { AtomicUsize::new(0) }; ENT_CACHE_SLOTS];
+static ENT_FRAME: [AtomicU64; ENT_CACHE_SLOTS] = [const { AtomicU64::new(0) }; ENT_CACHE_SLOTS]; // time::frame_start() of the last check
+
+/// Slot `idx`'s entity from the cache, or from `fetch` (GetEntityByIndex)
+/// when it isn't cached or no longer checks out. Past the player slots it's
+/// always `fetch`.
+unsafe fn cached_entity(idx: i32, fetch: impl FnOnce() -> usize) -> usize {
+    let Some(i) = usize::try_from(idx).ok().filter(|&i| i < ENT_CACHE_SLOTS) else { return fetch() };
+    let frame = time::frame_start();
+    let ptr = ENT_PTRS[i].load(Ordering::Acquire);
+    if ptr != 0 {
+        if ENT_FRAME[i].load(Ordering::Relaxed) == frame { return ptr; }
+        if is_readable(ptr, Offset::Curstate.get()) && read_i32(ptr) == idx {
+            ENT_FRAME[i].store(frame, Ordering::Relaxed);
+            return ptr;
+        }
+    }
+    let ptr = fetch();
+    ENT_PTRS[i].store(ptr, Ordering::Release);
+    ENT_FRAME[i].store(frame, Ordering::Relaxed);
+    ptr
+}
+
+/// Drop the cached entity pointers (map change).
+fn forget_entities() {
+    for p in &ENT_PTRS { p.store(0, Ordering::Release); }
+}
+
+/// Live engine access for player::interpret_player. Every read is checked
+/// with is_readable, so bad addresses read as 0 instead of faulting.
+impl EngineReader for EngineApi {
+    fn read_i32(&self, addr: usize) -> i32 { unsafe { read_i32(addr) } }
+    fn read_i16(&self, addr: usize) -> i16 { unsafe { read_i16(addr) } }
+    fn read_u8(&self, addr: usize) -> u8 { unsafe { read_u8(addr) } }
+    fn read_f32(&self, addr: usize) -> f32 { unsafe { read_f32(addr) } }
+
+    fn player_info(&self, idx: i32) -> Option<SlotInfo> {
+        unsafe {
+            let f = self.get_player_info_fn()?;
+            let mut pinfo: HudPlayerInfo = std::mem::zeroed();
+            f(idx, &mut pinfo as *mut HudPlayerInfo);
+            Some(SlotInfo {
+                name:      read_name(pinfo.name, idx),
+                spectator: pinfo.spectator != 0,
+                local:     pinfo.thisplayer != 0,
+                steam_id:  pinfo.steam_id,
+                bot:       pinfo.steam_id == 0 && pinfo.ping == 0 && pinfo.thisplayer == 0,
+            })
+        }
+    }
+
+    fn entity(&self, idx: i32) -> usize {
+        unsafe {
+            cached_entity(idx, || match self.func::<FnGetEntityByIndex>(EngineFn::GetEntityByIndex) {
+                Some(f) => f(idx) as usize,
+                None => 0,
+            })
+        }
+    }
+
+    fn extra_info_base(&self) -> usize { EXTRA_INFO_BASE.load(Ordering::Relaxed) }
+
+    fn world_bounds(&self) -> Option<Bounds> { self.bounds }
+
+    fn game_mod(&self) -> GameMod { game_mod() }
+
+    fn hulls(&self) -> Hulls { hulls() }
+}
+
+/// Call a string-returning engine function (`f` of `table`) and read the result.
+unsafe fn read_engine_string(table: usize, f: EngineFn) -> Option<String> {
+    let get: FnGetString = engine_fn(table, f)?;
+    let s = get();
+    if s.is_null() || !is_readable(s as usize, 1) { engine_fn_failed(f); return None; }
+    read_cstr(s, 260)
+}
+
+// ============================================================
+// Sanity
+// ============================================================
+
+/// Sanity checks each slot failed at its last read (player::Sanity).
+static SANITY: Mutex<[Sanity; MAX_CLIENTS as usize + 1]> = Mutex::new([Sanity(0); MAX_CLIENTS as usize + 1]);
+
+/// Slots rejected by the sanity checks since injection.
+static SANITY_REJECTS: AtomicU32 = AtomicU32::new(0);
+
+fn record_sanity(idx: i32, s: Sanity) {
+    if s.rejected() { SANITY_REJECTS.fetch_add(1, Ordering::Relaxed); }
+    if let Ok(mut g) = SANITY.lock() {
+        if let Some(slot) = g.get_mut(idx as usize) { *slot = s; }
+    }
+}
+
+/// Slots whose last read failed a sanity check, with what failed (diagnostics).
+pub fn sanity_status() -> Vec<(i32, Sanity)> {
+    let Ok(g) = SANITY.lock() else { return Vec::new() };
+    g.iter().enumerate().filter(|(_, s)| s.0 != 0).map(|(i, s)| (i as i32, *s)).collect()
+}
+
+/// Slot reads rejected by the sanity checks since injection.
+pub fn sanity_rejects() -> u32 { SANITY_REJECTS.load(Ordering::Relaxed) }
+
+/// How the last read_player of each slot went (slots.rs).
+static READ_OUTCOMES: Mutex<[ReadOutcome; MAX_CLIENTS as usize + 1]> =
+    Mutex::new([ReadOutcome::Empty; MAX_CLIENTS as usize + 1]);
+
+fn record_read(idx: i32, outcome: ReadOutcome) {
+    if let Ok(mut g) = READ_OUTCOMES.lock() {
+        if let Some(slot) = g.get_mut(idx as usize) { *slot = outcome; }
+    }
+}
+
+/// How the last read of every slot went, slot 1 first (status grid, "no players" hint).
+pub fn read_outcomes() -> Vec<ReadOutcome> {
+    READ_OUTCOMES.lock().map(|g| g[1..].to_vec()).unwrap_or_default()
+}
+
+// ============================================================
+// Map Changes
+// ============================================================
+// The engine table and g_PlayerExtraInfo are resolved once and cached, but
+// a map change or a reconnect can leave them stale (boxes from garbage
+// memory). A change of level name, a disconnect (no level) included, drops
+// the slot verification and the extra-info address so both are redone before
+// anything is read again; if the table then fails verification, client.dll is
+// scanned for one that moved. The change is also published (bus.rs) for the
+// statistics and the session export.
+
+/// Level name seen at the last resolve (None = no map loaded).
+static LEVEL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set by a map change until the table has been verified again.
+static REVALIDATE: AtomicBool = AtomicBool::new(false);
+
+/// Map changes seen since injection (diagnostics).
+static MAP_CHANGES: AtomicU32 = AtomicU32::new(0);
+
+/// Map changes seen since injection.
+pub fn map_changes() -> u32 { MAP_CHANGES.load(Ordering::Relaxed) }
+
+/// Drop what was resolved for the previous map if the level changed.
+unsafe fn check_map_change(table: usize) {
+    let level = read_engine_string(table, EngineFn::GetLevelName).filter(|s| !s.is_empty());
+    {
+        let Ok(mut last) = LEVEL.lock() else { return };
+        if *last == level { return; }
+        logf(format!("map change: {} -> {}; re-validating the engine table and extra info",
+            last.as_deref().unwrap_or("(none)"), level.as_deref().unwrap_or("(none)")));
+        let ended = std::mem::replace(&mut *last, level.clone());
+        crate::bus::publish(crate::bus::Event::MapLoaded { ended, map: level });
     }
+    MAP_CHANGES.fetch_add(1, Ordering::Relaxed);
+    VERIFIED_TABLE.store(0, Ordering::Release);
+    LAST_VERIFY_MS.store(0, Ordering::Relaxed);
+    forget_engine_fns();
+    forget_entities();
+    EXTRA_INFO_BASE.store(0, Ordering::Relaxed);
+    EXTRA_INFO_FAILED_MS.store(0, Ordering::Relaxed);
+    ALIVE.lock().unwrap_or_else(|e| e.into_inner()).round_start();
+    REVALIDATE.store(true, Ordering::Relaxed);
+}
+
+/// Scan for the engine table again after `old` failed verification
+/// following a map change, and switch to it if it moved.
+unsafe fn rescan_table(old: usize) {
+    match find_gengfuncs_in_client() {
+        Some(table) if table != old => {
+            logf(format!("engine table moved: {:#010x} -> {:#010x}", old, table));
+            ENGINE_TABLE.store(table, Ordering::Release);
+        }
+        _ => log("engine table failed verification after the map change; keeping it"),
+    }
+}
+
+/// Ask the engine's demo API whether a demo is playing back.
+unsafe fn is_demo_playback(table: usize) -> bool {
+    type FnIsPlayingback = unsafe extern "C" fn() -> i32;
+    let Ok(slot) = follow_chain(table + SLOT_PDEMOAPI * 4, &[DEMOAPI_IS_PLAYINGBACK * 4]) else { return false };
+    let fn_ptr = read_u32(slot) as usize;
+    if fn_ptr < 0x10000 { return false; }
+    let f: FnIsPlayingback = std::mem::transmute(fn_ptr);
+    f() != 0
 }
 
 // ============================================================
@@ -584,6 +1845,18 @@ unsafe fn find_gengfuncs_in_client() -> Option<usize> {
     let (cl_base, cl_end) = module_range(b"client.dll\0")?;
     let (hw_base, hw_end) = module_range(b"hw.dll\0")?;
 
+    // Same binaries as last time? Reuse the address if it still validates.
+    let in_hw = |p: usize| p >= hw_base && p < hw_end;
+    if let Some(table) = scancache::lookup("engine_table") {
+        if table >= cl_base && table < cl_end && is_readable(table, (SLOT_GET_ENTITY_BY_INDEX + 1) * 4)
+            && in_hw(read_u32(table + SLOT_GET_LOCAL_PLAYER * 4) as usize)
+            && in_hw(read_u32(table + SLOT_GET_ENTITY_BY_INDEX * 4) as usize)
+        {
+            log("engine table from scan cache");
+            return Some(table);
+        }
+    }
+
     let readable_flags = PAGE_READONLY | PAGE_READWRITE | PAGE_WRITECOPY
         | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
 
@@ -615,6 +1888,7 @@ unsafe fn find_gengfuncs_in_client() -> Option<usize> {
                 let s51 = std::ptr::read_unaligned((scan + 51 * 4) as *const u32) as usize;
                 let s53 = std::ptr::read_unaligned((scan + 53 * 4) as *const u32) as usize;
                 if s51 >= hw_base && s51 < hw_end && s53 >= hw_base && s53 < hw_end {
+                    scancache::store("engine_table", scan);
                     return Some(scan);
                 }
             }
@@ -626,66 +1900,46 @@ unsafe fn find_gengfuncs_in_client() -> Option<usize> {
 }
 
 /// Scan client.dll for g_PlayerExtraInfo — a global array of per-player metadata.
-/// Uses two byte patterns (primary + alternate) to locate the array pointer.
-unsafe fn find_player_extra_info() -> Option<usize> {
-    let (cl_base, cl_end) = module_range(b"client.dll\0")?;
-
-    // Primary pattern (references g_PlayerExtraInfo via a pointer operand)
-    let pat: &[u8] = &[
-        0x0F, 0xBF, 0x87, 0xCC, 0xCC, 0xCC, 0xCC,
-        0x8B, 0x16, 0x50, 0x68, 0xCC, 0xCC, 0xCC, 0xCC,
-        0x8B, 0xCE, 0xFF, 0x52, 0xCC,
-        0x8D, 0x4C, 0xAD, 0x00,
-        0x66, 0x8B, 0x04, 0x8D,
-    ];
-    let mask: &[u8] = &[
-        1,1,1,0,0,0,0,
-        1,1,1,1,0,0,0,0,
-        1,1,1,1,0,
-        1,1,1,1,
-        1,1,1,1,
-    ];
-
-    // Try primary pattern
-    if let Some(addr) = scan_with_pattern(cl_base, cl_end, pat, mask, 27, 4) {
-        return Some(addr);
+/// `[extra_info] signature` is tried first, then the running mod's built-in ones.
+unsafe fn find_player_extra_info() -> Result<usize, OverlayError> {
+    let (cl_base, cl_end) = module_range(b"client.dll\0")
+        .ok_or(OverlayError::ModuleMissing("client.dll".into()))?;
+
+    if let Some(addr) = scancache::lookup("player_extra_info") {
+        if is_readable(addr, Offset::ExtraStride.get() * 33) {
+            log("g_PlayerExtraInfo from scan cache");
+            return Ok(addr);
+        }
     }
 
-    // Alternate pattern (different code generation, same data)
-    let pat2: &[u8] = &[
-        0x0F, 0xBF, 0x87, 0xCC, 0xCC, 0xCC, 0xCC,
-        0x8B, 0x16, 0x50, 0x68, 0xCC, 0xCC, 0xCC, 0xCC,
-        0x8B, 0xCE, 0xFF, 0x52, 0xCC,
-        0x8B, 0xCD, 0xC1, 0xE1, 0x05,
-        0x66, 0x8B, 0x81, 0xCC, 0xCC, 0xCC, 0xCC,
-        0x66, 0x3D, 0x01, 0x00, 0x7D, 0x46,
-    ];
-    let mask2: &[u8] = &[
-        1,1,1,0,0,0,0,
-        1,1,1,1,0,0,0,0,
-        1,1,1,1,0,
-        1,1,1,1,1,
-        1,1,1,0,0,0,0,
-        1,1,1,1,1,1,
-    ];
-
-    // Try alternate pattern
-    if let Some(addr) = scan_with_pattern(cl_base, cl_end, pat2, mask2, 3, 4) {
-        return Some(addr);
+    let cfg = crate::config::get();
+    let custom = (!cfg.extra_info_signature.trim().is_empty())
+        .then(|| (cfg.extra_info_signature.as_str(), cfg.extra_info_pointer_offset as usize));
+    let sigs: Vec<(&str, usize)> = custom.into_iter().chain(game_mod().profile().extra_sigs.iter().copied()).collect();
+    if sigs.is_empty() {
+        return Err(OverlayError::NoSignature { what: "g_PlayerExtraInfo", name: game_mod().name(), key: "[extra_info] signature" });
     }
 
-    None
+    let started = Stopwatch::start();
+    for (sig, ptr_offset) in sigs {
+        let pat = match Pattern::parse(sig) {
+            Ok(p) => p,
+            Err(e) => { logf(format!("extra info signature skipped: {}", e)); continue; }
+        };
+        if let Some(addr) = scan_with_pattern(cl_base, cl_end, &pat, ptr_offset, Offset::ExtraStride.get() * 33) {
+            logf(format!("g_PlayerExtraInfo at {:#x} (scan {} us)", addr, started.elapsed_us()));
+            scancache::store("player_extra_info", addr);
+            return Ok(addr);
+        }
+    }
+    Err(OverlayError::PatternNotFound("g_PlayerExtraInfo"))
 }
 
 /// Generic masked byte pattern scanner.
-/// Scans memory from `start` to `end` for `pattern` (0xCC bytes in mask=0 are wildcards).
-/// On match, reads a 4-byte pointer at `match_offset` bytes from the match start.
-/// Validates the pointer points to readable memory of size `validate_size * 33`.
-unsafe fn scan_with_pattern(
-    start: usize, end: usize,
-    pattern: &[u8], mask: &[u8],
-    ptr_offset: usize, _validate_size: usize,
-) -> Option<usize> {
+/// Scans memory from `start` to `end` for `pattern`.
+/// On match, reads a 4-byte pointer at `ptr_offset` bytes from the match start
+/// and returns it if it points to a readable block of `size` bytes.
+unsafe fn scan_with_pattern(start: usize, end: usize, pattern: &Pattern, ptr_offset: usize, size: usize) -> Option<usize> {
     let readable_flags = PAGE_READONLY | PAGE_READWRITE | PAGE_WRITECOPY
         | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
 
@@ -698,25 +1952,17 @@ unsafe fn scan_with_pattern(
         let region_end = (mbi.BaseAddress as usize + mbi.RegionSize).min(end);
 
         if mbi.State == MEM_COMMIT && mbi.Protect & readable_flags != 0 {
-            let mut scan = addr;
-            while scan + pattern.len() <= region_end {
-                let mut matched = true;
-                for i in 0..pattern.len() {
-                    if mask[i] == 1 {
-                        let b = std::ptr::read_unaligned((scan + i) as *const u8);
-                        if b != pattern[i] { matched = false; break; }
-                    }
-                }
-                if matched {
-                    let pa = scan + ptr_offset;
-                    if is_readable(pa, 4) {
-                        let arr_ptr = std::ptr::read_unaligned(pa as *const u32) as usize;
-                        if arr_ptr > 0x10000 && is_readable(arr_ptr, EXTRA_STRIDE * 33) {
-                            return Some(arr_ptr);
-                        }
+            let region = std::slice::from_raw_parts(addr as *const u8, region_end - addr);
+            let mut from = 0usize;
+            while let Some(at) = pattern.find(region, from) {
+                let pa = addr + at + ptr_offset;
+                if is_readable(pa, 4) {
+                    let arr_ptr = std::ptr::read_unaligned(pa as *const u32) as usize;
+                    if arr_ptr > 0x10000 && is_readable(arr_ptr, size) {
+                        return Some(arr_ptr);
                     }
                 }
-                scan += 1;
+                from = at + 1;
             }
         }
         addr = region_end;
@@ -724,15 +1970,121 @@ unsafe fn scan_with_pattern(
     None
 }
 
-/// Get the cached g_PlayerExtraInfo base address, scanning for it if needed.
+/// Get the cached g_PlayerExtraInfo base address, scanning for it if needed
+/// (worker thread).
 unsafe fn get_extra_info_base() -> usize {
     let cached = EXTRA_INFO_BASE.load(Ordering::Relaxed);
     if cached != 0 { return cached; }
-    if let Some(ptr) = find_player_extra_info() {
-        EXTRA_INFO_BASE.store(ptr, Ordering::Relaxed);
-        return ptr;
+    let m = game_mod();
+    if !m.profile().extra_info {
+        if let Ok(mut g) = EXTRA_INFO_ERROR.lock() { *g = Some(OverlayError::NotInMod { what: "g_PlayerExtraInfo", name: m.name() }); }
+        return 0;
+    }
+    let found = find_player_extra_info();
+    let base = *found.as_ref().unwrap_or(&0);
+    if base != 0 { EXTRA_INFO_BASE.store(base, Ordering::Relaxed); }
+    EXTRA_INFO_FAILED_MS.store(if base == 0 { crate::time::now_ms() } else { 0 }, Ordering::Relaxed);
+    if let Ok(mut g) = EXTRA_INFO_ERROR.lock() { *g = found.err(); }
+    base
+}
+
+// ============================================================
+// Net Channel (netgraph panel)
+// ============================================================
+
+/// The net channel found with the signature in the tuple (rescanned when
+/// `[netgraph] signature` changes).
+static NETCHAN: Mutex<Option<(String, Result<usize, OverlayError>)>> = Mutex::new(None);
+
+/// Find the engine's netchan_t in hw.dll with `[netgraph] signature`.
+unsafe fn find_netchan(cfg: &crate::config::Config, size: usize) -> Result<usize, OverlayError> {
+    let (hw_base, hw_end) = module_range(b"hw.dll\0")
+        .ok_or(OverlayError::ModuleMissing("hw.dll".into()))?;
+    let pat = match Pattern::parse(&cfg.netgraph_signature) {
+        Ok(p) => p,
+        Err(e) => {
+            logf(format!("netgraph signature skipped: {}", e));
+            return Err(OverlayError::PatternNotFound("the net channel"));
+        }
+    };
+    let started = Stopwatch::start();
+    let addr = scan_with_pattern(hw_base, hw_end, &pat, cfg.netgraph_pointer_offset as usize, size)
+        .ok_or(OverlayError::PatternNotFound("the net channel"))?;
+    logf(format!("net channel at {:#x} (scan {} us)", addr, started.elapsed_us()));
+    Ok(addr)
+}
+
+/// Bytes of the net channel `[netgraph]` reads.
+fn netchan_size(cfg: &crate::config::Config) -> usize {
+    let offsets = [cfg.netgraph_latency_offset, cfg.netgraph_choke_offset,
+                   cfg.netgraph_loss_offset, cfg.netgraph_rate_in_offset];
+    offsets.iter().max().map_or(4, |&o| o as usize + 4)
+}
+
+/// Read the net channel fields `[netgraph]` names. Err when the channel can't
+/// be found (no signature, no match) or the worker hasn't looked for it with
+/// this signature yet; fields that can't be read are None.
+pub unsafe fn net_sample(cfg: &crate::config::Config) -> Result<NetSample, String> {
+    if cfg.netgraph_signature.trim().is_empty() {
+        return Err("set [netgraph] signature for this hw.dll build".to_string());
+    }
+    let found = match NETCHAN.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some((sig, found)) if *sig == cfg.netgraph_signature => found.clone(),
+        _ => {
+            if !NETCHAN_WANTED.swap(true, Ordering::Relaxed) { crate::wake_worker(); }
+            return Err("looking for the net channel...".to_string());
+        }
+    };
+    let base = found.map_err(|e| e.to_string())?;
+    let field = |off: u32| {
+        let addr = base + off as usize;
+        (off != 0 && is_readable(addr, 4)).then(|| read_f32(addr)).filter(|v| v.is_finite())
+    };
+    Ok(NetSample {
+        latency_ms: field(cfg.netgraph_latency_offset).map(|s| s * 1000.0),
+        choke:      field(cfg.netgraph_choke_offset),
+        loss:       field(cfg.netgraph_loss_offset),
+        rate_in:    field(cfg.netgraph_rate_in_offset),
+    })
+}
+
+// ============================================================
+// Background Scans (worker thread)
+// ============================================================
+
+/// Whether something is waiting on a retry (the Initialize hook, a
+/// g_PlayerExtraInfo scan that came up empty): the worker then wakes every
+/// `[worker] retry_ms` for `background_scans`.
+pub fn scans_pending() -> bool {
+    !HOOK_INSTALLED.load(Ordering::Relaxed) || EXTRA_INFO_WANTED.load(Ordering::Relaxed)
+}
+
+/// Run the scans frames have asked for since the last call, and keep
+/// retrying the Initialize hook until it's in. Called by the worker thread
+/// whenever it wakes, so a frame never waits on a pattern scan.
+pub unsafe fn background_scans() {
+    if !HOOK_INSTALLED.load(Ordering::Relaxed) {
+        install_initialize_hook();
+    }
+    let old = RESCAN_TABLE.swap(0, Ordering::Relaxed);
+    if old != 0 { rescan_table(old); }
+
+    // Within the retry interval the request stays pending (frames don't
+    // wake the worker again for it); the worker's retry task comes back to it
+    let failed = EXTRA_INFO_FAILED_MS.load(Ordering::Relaxed);
+    if EXTRA_INFO_WANTED.load(Ordering::Relaxed)
+        && (failed == 0 || crate::time::now_ms().wrapping_sub(failed) >= EXTRA_INFO_RETRY_MS)
+    {
+        EXTRA_INFO_WANTED.store(false, Ordering::Relaxed);
+        get_extra_info_base();
+    }
+
+    if NETCHAN_WANTED.swap(false, Ordering::Relaxed) {
+        // Not under the lock: net_sample would wait for the scan
+        let cfg = crate::config::get();
+        let found = find_netchan(&cfg, netchan_size(&cfg));
+        *NETCHAN.lock().unwrap_or_else(|e| e.into_inner()) = Some((cfg.netgraph_signature.clone(), found));
     }
-    0
 }
 
 // ============================================================
@@ -740,7 +2092,7 @@ unsafe fn get_extra_info_base() -> usize {
 // ============================================================
 
 /// Get the base address and end address of a loaded module.
-unsafe fn module_range(name: &[u8]) -> Option<(usize, usize)> {
+pub unsafe fn module_range(name: &[u8]) -> Option<(usize, usize)> {
     let h = GetModuleHandleA(name.as_ptr() as _);
     if h.is_null() { return None; }
     let mut info: MODULEINFO = std::mem::zeroed();
@@ -753,7 +2105,7 @@ unsafe fn module_range(name: &[u8]) -> Option<(usize, usize)> {
 }
 
 /// Check if a memory region is readable (committed + has read permission).
-unsafe fn is_readable(addr: usize, len: usize) -> bool {
+pub unsafe fn is_readable(addr: usize, len: usize) -> bool {
     if addr == 0 || len == 0 { return false; }
     let readable = PAGE_READONLY | PAGE_READWRITE | PAGE_WRITECOPY
         | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
@@ -768,11 +2120,17 @@ unsafe fn is_readable(addr: usize, len: usize) -> bool {
 
 /// Read a u32 from a remote memory address (returns 0 if unreadable).
 #[inline]
-unsafe fn read_u32(addr: usize) -> u32 {
+pub unsafe fn read_u32(addr: usize) -> u32 {
     if !is_readable(addr, 4) { return 0; }
     std::ptr::read_unaligned(addr as *const u32)
 }
 
+/// Read `len` bytes from a remote memory address (None if unreadable).
+pub unsafe fn read_bytes(addr: usize, len: usize) -> Option<Vec<u8>> {
+    if !is_readable(addr, len) { return None; }
+    Some(std::slice::from_raw_parts(addr as *const u8, len).to_vec())
+}
+
 /// Read an i32 from a remote memory address (returns 0 if unreadable).
 #[inline]
 unsafe fn read_i32(addr: usize) -> i32 {
@@ -802,22 +2160,122 @@ unsafe fn read_f32(addr: usize) -> f32 {
 }
 
 /// Read a Vec3 (three consecutive f32s) from a memory address.
-unsafe fn read_vec3(addr: usize) -> Vec3 {
+pub unsafe fn read_vec3(addr: usize) -> Vec3 {
     Vec3 { x: read_f32(addr), y: read_f32(addr + 4), z: read_f32(addr + 8) }
 }
 
-/// Read a null-terminated C string from a memory address.
-/// Only includes printable ASCII characters (32-126).
-unsafe fn read_cstr(ptr: *const i8, max_len: usize) -> Option<String> {
-    if ptr.is_null() { return None; }
+/// Read the bytes of a null-terminated C string (without the terminator),
+/// stopping at `max_len` bytes or unreadable memory.
+pub unsafe fn read_cbytes(ptr: *const i8, max_len: usize) -> Vec<u8> {
     let mut out: Vec<u8> = Vec::new();
+    if ptr.is_null() { return out; }
     let base = ptr as usize;
     for i in 0..max_len {
         let a = base + i;
         if !is_readable(a, 1) { break; }
         let b = std::ptr::read(a as *const u8);
-        if b == 0 { break; }                      // Null terminator
-        if (32..=126).contains(&b) { out.push(b); } // Printable ASCII only
+        if b == 0 { break; } // Null terminator
+        out.push(b);
     }
+    out
+}
+
+/// Read a null-terminated C string from a memory address.
+/// Only includes printable ASCII characters (32-126).
+pub unsafe fn read_cstr(ptr: *const i8, max_len: usize) -> Option<String> {
+    let out: Vec<u8> = read_cbytes(ptr, max_len).into_iter()
+        .filter(|b| (32..=126).contains(b)) // Printable ASCII only
+        .collect();
     if out.is_empty() { None } else { Some(String::from_utf8_lossy(&out).into_owned()) }
-}
\ No newline at end of file
+}
+
+/// Read and decode a player name (see names.rs). With `log.raw_names` set,
+/// non-ASCII names are logged with their raw bytes whenever a slot's name changes.
+unsafe fn read_name(ptr: *const i8, idx: i32) -> Option<String> {
+    static LOGGED: Mutex<Vec<(i32, Vec<u8>)>> = Mutex::new(Vec::new());
+
+    let raw = read_cbytes(ptr, 32);
+    let decoded = names::decode(&raw);
+    if !raw.is_ascii() && crate::config::get().log_raw_names {
+        let mut logged = match LOGGED.lock() { Ok(g) => g, Err(p) => p.into_inner() };
+        if !logged.iter().any(|(i, r)| *i == idx && *r == raw) {
+            let shown = match &decoded {
+                Some((text, enc)) => format!("{:?} ({:?})", text, enc),
+                None              => "nothing printable".to_string(),
+            };
+            logf(format!("name slot {}: [{}] -> {}", idx, names::hex(&raw), shown));
+            logged.retain(|(i, _)| *i != idx);
+            logged.push((idx, raw));
+        }
+    }
+    decoded.map(|(text, _)| text)
+}
+
+// ============================================================
+// Pointer Chains
+// ============================================================
+
+/// How long a resolved module chain is reused before it's walked again.
+const CHAIN_CACHE_MS: u32 = 200;
+
+/// Most chains kept in the cache (the oldest is dropped).
+const CHAIN_CACHE_LEN: usize = 64;
+
+/// A resolved resolve_chain call.
+struct CachedChain {
+    module:  String,
+    offsets: Vec<usize>,
+    addr:    usize,
+    at_ms:   u32,
+}
+
+static CHAINS: Mutex<Vec<CachedChain>> = Mutex::new(Vec::new());
+
+/// Follow a pointer chain from `addr`: for each offset, read the pointer at
+/// the current address and add the offset to it. Every pointer is checked
+/// before it's read; the address reached isn't (the caller reads it).
+pub unsafe fn follow_chain(mut addr: usize, offsets: &[usize]) -> Result<usize, OverlayError> {
+    for (i, off) in offsets.iter().enumerate() {
+        if !is_readable(addr, 4) { return Err(OverlayError::ChainBroken { step: i + 1, addr }); }
+        addr = read_u32(addr) as usize + off;
+    }
+    Ok(addr)
+}
+
+/// Resolve `module`+offsets[0] -> offsets[1] -> ...: the first offset is into
+/// the module, each further one is added to the pointer read at the address so
+/// far (see follow_chain). Results are cached for CHAIN_CACHE_MS, so callers
+/// reading the same chain every frame don't walk it every frame.
+pub unsafe fn resolve_chain(module: &str, offsets: &[usize]) -> Result<usize, OverlayError> {
+    let now = time::now_ms();
+    if let Ok(cache) = CHAINS.lock() {
+        let hit = cache.iter().find(|c| c.module == module && c.offsets == offsets);
+        if let Some(c) = hit.filter(|c| now.wrapping_sub(c.at_ms) < CHAIN_CACHE_MS) {
+            return Ok(c.addr);
+        }
+    }
+
+    let (base, _) = module_range(format!("{}\0", module).as_bytes())
+        .ok_or_else(|| OverlayError::ModuleMissing(module.to_string().into()))?;
+    let (first, rest) = offsets.split_first().map_or((0, &[][..]), |(f, r)| (*f, r));
+    let addr = follow_chain(base + first, rest)?;
+
+    if let Ok(mut cache) = CHAINS.lock() {
+        cache.retain(|c| !(c.module == module && c.offsets == offsets));
+        if cache.len() >= CHAIN_CACHE_LEN { cache.remove(0); }
+        cache.push(CachedChain { module: module.to_string(), offsets: offsets.to_vec(), addr, at_ms: now });
+    }
+    Ok(addr)
+}
+
+/// Follow a watch expression and read the bytes of its value.
+pub unsafe fn read_watch(w: &Watch) -> Result<Vec<u8>, OverlayError> {
+    let addr = match &w.base {
+        Base::Absolute(a) => follow_chain(*a, &w.chain)?,
+        Base::Module(name, off) => {
+            let offsets: Vec<usize> = std::iter::once(*off).chain(w.chain.iter().copied()).collect();
+            resolve_chain(name, &offsets)?
+        }
+    };
+    read_bytes(addr, w.kind.size()).ok_or(OverlayError::NotReadable(addr))
+}
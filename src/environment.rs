@@ -0,0 +1,108 @@
+// environment.rs — Pieces of the startup environment report (startup.rs).
+//
+// Most support requests start by asking for the same things: which Windows,
+// which game build, which renderer, which client.dll, and what else is
+// hooked into the game. The report answers them once, in the log, at
+// startup; this is the part of it that doesn't need Windows: naming the OS
+// build, recognising other overlays by their module, formatting versions,
+// and the checksum of client.dll (CRC-32, what 7-Zip and most hash tools
+// show, so a user can compare files without the overlay).
+
+/// Modules of other overlays and hook DLLs that are known to patch the same
+/// GL / present functions we do, by file name (lowercase).
+const KNOWN_HOOKS: [(&str, &str); 12] = [
+    ("gameoverlayrenderer.dll", "Steam overlay"),
+    ("discordhook.dll",         "Discord overlay"),
+    ("graphics-hook32.dll",     "OBS game capture"),
+    ("rtsshooks.dll",           "RivaTuner Statistics Server"),
+    ("nvspcap.dll",             "NVIDIA ShadowPlay / overlay"),
+    ("fraps.dll",               "Fraps"),
+    ("bdcamvk32.dll",           "Bandicam"),
+    ("bdcap32.dll",             "Bandicam"),
+    ("dxtoryhk.dll",            "Dxtory"),
+    ("action_x86.dll",          "Mirillis Action!"),
+    ("reshade32.dll",           "ReShade"),
+    ("overwolf.dll",            "Overwolf"),
+];
+
+/// What a loaded module is, if it's a known overlay or hook DLL. `path` may
+/// be a full path; only the file name is compared, ignoring case.
+pub fn known_hook(path: &str) -> Option<&'static str> {
+    let file = path.rsplit(['\\', '/']).next().unwrap_or(path).to_ascii_lowercase();
+    KNOWN_HOOKS.iter().find(|(name, _)| *name == file).map(|&(_, what)| what)
+}
+
+/// Whether a GL-facing module (opengl32.dll, ...) was loaded from outside
+/// the system directory: a wrapper (ReShade, an injector's proxy) standing
+/// in for the real one.
+pub fn is_wrapper(path: &str, system_dir: &str) -> bool {
+    let dir = path.rsplit_once(['\\', '/']).map_or("", |(d, _)| d);
+    !dir.eq_ignore_ascii_case(system_dir.trim_end_matches(['\\', '/']))
+}
+
+/// Marketing name of a Windows version, from RtlGetVersion (which, unlike
+/// GetVersionEx, isn't capped at 6.2 by the game's manifest).
+pub fn os_name(major: u32, minor: u32, build: u32) -> String {
+    let name = match (major, minor) {
+        (10, 0) if build >= 22000 => "Windows 11",
+        (10, 0) => "Windows 10",
+        (6, 3)  => "Windows 8.1",
+        (6, 2)  => "Windows 8",
+        (6, 1)  => "Windows 7",
+        (6, 0)  => "Windows Vista",
+        _       => "Windows",
+    };
+    format!("{} ({}.{}.{})", name, major, minor, build)
+}
+
+/// "1.1.2.2" from VS_FIXEDFILEINFO's dwFileVersionMS / dwFileVersionLS.
+pub fn file_version(ms: u32, ls: u32) -> String {
+    format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF)
+}
+
+/// CRC-32 (IEEE 802.3, reflected) of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_known_hooks_by_file_name() {
+        assert_eq!(known_hook(r"C:\Program Files (x86)\Steam\GameOverlayRenderer.dll"), Some("Steam overlay"));
+        assert_eq!(known_hook("graphics-hook32.dll"), Some("OBS game capture"));
+        assert_eq!(known_hook(r"C:\Games\Half-Life\client.dll"), None);
+        assert_eq!(known_hook(r"C:\overlay\gameoverlayrenderer.dll.bak"), None);
+    }
+
+    #[test]
+    fn spots_wrappers_outside_the_system_directory() {
+        assert!(!is_wrapper(r"C:\WINDOWS\SYSTEM32\opengl32.dll", r"C:\Windows\System32"));
+        assert!(!is_wrapper(r"C:\Windows\SysWOW64\opengl32.dll", r"C:\Windows\SysWOW64\"));
+        assert!(is_wrapper(r"C:\Games\Half-Life\opengl32.dll", r"C:\Windows\SysWOW64"));
+    }
+
+    #[test]
+    fn names_and_versions() {
+        assert_eq!(os_name(10, 0, 19045), "Windows 10 (10.0.19045)");
+        assert_eq!(os_name(10, 0, 22631), "Windows 11 (10.0.22631)");
+        assert_eq!(os_name(6, 1, 7601), "Windows 7 (6.1.7601)");
+        assert_eq!(os_name(5, 1, 2600), "Windows (5.1.2600)");
+        assert_eq!(file_version(0x0001_0001, 0x0002_0002), "1.1.2.2");
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+}
@@ -0,0 +1,86 @@
+// error.rs — Error type shared by the hook and engine-access code.
+//
+// Every failure the overlay can hit while setting itself up — MinHook calls,
+// missing modules or exports, signatures that don't match, memory that can't
+// be made writable — is an OverlayError. Its Display text is what ends up in
+// esp_debug.log and on the F7 diagnostics page, so a report from the field
+// says what failed rather than carrying a bare status number.
+
+use minhook_sys::MH_STATUS;
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverlayError {
+    /// A MinHook call returned a status other than MH_OK.
+    MinHook { call: &'static str, status: MH_STATUS },
+    /// A module isn't loaded in the process.
+    ModuleMissing(Cow<'static, str>),
+    /// A module is loaded but doesn't export the function.
+    ExportMissing { module: &'static str, export: &'static str },
+    /// No signature for the named item matched.
+    PatternNotFound(&'static str),
+    /// VirtualProtect refused to change the protection at this address.
+    ProtectFailed(usize),
+    /// Memory that had to be read isn't committed/readable.
+    NotReadable(usize),
+    /// VirtualAlloc refused the trampoline page for a patch at this address.
+    AllocFailed(usize),
+    /// No hook is registered under this name.
+    HookUnknown(String),
+    /// A pointer chain step's pointer (at this address) can't be read.
+    ChainBroken { step: usize, addr: usize },
+    /// The running mod doesn't have this (g_PlayerExtraInfo outside CS).
+    NotInMod { what: &'static str, name: &'static str },
+    /// There's no signature for this in the running mod; `key` can supply one.
+    NoSignature { what: &'static str, name: &'static str, key: &'static str },
+}
+
+impl fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MinHook { call, status } => write!(f, "{} failed: {} ({})", call, mh_status_name(*status), status),
+            Self::ModuleMissing(m)         => write!(f, "{} not loaded", m),
+            Self::ExportMissing { module, export } => write!(f, "{}!{} not exported", module, export),
+            Self::PatternNotFound(what)    => write!(f, "no signature matched for {}", what),
+            Self::ProtectFailed(addr)      => write!(f, "VirtualProtect failed at {:#010x}", addr),
+            Self::NotReadable(addr)        => write!(f, "memory at {:#010x} not readable", addr),
+            Self::AllocFailed(addr)        => write!(f, "no trampoline memory for the patch at {:#010x}", addr),
+            Self::HookUnknown(name)        => write!(f, "no hook named {}", name),
+            Self::ChainBroken { step, addr } => write!(f, "pointer chain step {}: {:#010x} not readable", step, addr),
+            Self::NotInMod { what, name }  => write!(f, "{} not used in {}", what, name),
+            Self::NoSignature { what, name, key } => write!(f, "no {} signature for {}; set {}", what, name, key),
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {}
+
+/// Turn a MinHook status into a Result.
+pub fn mh(call: &'static str, status: MH_STATUS) -> Result<(), OverlayError> {
+    if status == minhook_sys::MH_OK { Ok(()) } else { Err(OverlayError::MinHook { call, status }) }
+}
+
+/// Name of a MinHook status code (MH_ERROR_* without the prefix).
+fn mh_status_name(status: MH_STATUS) -> &'static str {
+    match status {
+        1  => "ALREADY_INITIALIZED",
+        2  => "NOT_INITIALIZED",
+        3  => "ALREADY_CREATED",
+        4  => "NOT_CREATED",
+        5  => "ENABLED",
+        6  => "DISABLED",
+        7  => "NOT_EXECUTABLE",
+        8  => "UNSUPPORTED_FUNCTION",
+        9  => "MEMORY_ALLOC",
+        10 => "MEMORY_PROTECT",
+        11 => "MODULE_NOT_FOUND",
+        12 => "FUNCTION_NOT_FOUND",
+        _  => "UNKNOWN",
+    }
+}
+
+/// Module name without the trailing NUL of the byte strings passed to Win32.
+pub fn cstr_name(s: &'static [u8]) -> &'static str {
+    std::str::from_utf8(s.strip_suffix(b"\0").unwrap_or(s)).unwrap_or("?")
+}
@@ -6,15 +6,92 @@
 //
 // Features:
 //   - F6 hotkey to toggle overlay on/off
-//   - Team-colored bounding boxes with corner brackets
-//   - Snap-line from screen bottom to each player's feet
+//   - Team-colored bounding boxes with corner brackets (colors from the theme palette)
+//   - Snap-lines to each player (origin, target, per-team color configurable)
 //   - Name labels above boxes, distance + weapon below
 //   - Cached boxes that fade out when a player disappears temporarily
-
-use crate::entities::EngineApi;
-use crate::math::Vec3;
+//   - Label layout pass that stacks overlapping labels instead of drawing them on top of each other
+//   - Distance-sorted draw order so nearer players are always drawn on top
+//   - Screen-space smoothing so boxes glide between network updates
+//   - Optional custom crosshair and FOV circle (radius in degrees, zoom-aware)
+//   - Projection check against the current FOV, with a correction path and indicator
+//   - Demo playback mode: dead players stay visible (dimmed) for reviewing .dem files
+//   - First-person spectate: the watched player is suppressed and distances use their eyes
+//   - Dead / free-look / chase cam: distances and the minimap follow the rendered camera
+//   - Death-spot markers: a fading X + name where a player died
+//   - Grenade labels: kind and fuse countdown at each thrown HE / flashbang / smoke
+//   - Round statistics: kills, deaths, damage, time alive; end-of-round summary, CSV at map end
+//   - Live feed: player snapshots over HTTP polling and WebSocket push (browser radar)
+//   - Session export: players seen (SteamID, teams, frags) and hook diagnostics as JSON at map end
+//   - Breadcrumb trails: a fading line along each player's recent path
+//   - Sound rings: expanding circles where footsteps and gunshots were heard
+//   - Shot tracers: a short-lived line from each shooter's eyes along their aim
+//   - Flash indicator: "FLASHED" and a countdown bar while the screen fade blinds you
+//   - Audio alerts (engine sound) when an enemy comes near or gets behind you
+//   - PVS tint: boxes of players left out of the latest server update are grayed
+//   - Top-down minimap from the map's .bsp walls, with player blips
+//   - Brush entity debug ESP: outlines of doors, breakables, ladders (F8 on the diagnostics page)
+//   - Screenshot-clean mode: nothing is drawn around a screenshot (snapshot key or readback)
+//   - F7 diagnostics pages (hook status, addresses, slots, offsets, log tail)
+//   - Frame budget: over `[performance] budget_ms`, trails, labels, snap-lines and cached boxes are shed in turn
+//   - Screen panels (status lines, minimap, diagnostics) anchored to corners, stacked per corner
+
+use crate::alerts::{self, Alert, AlertParams, Alerts};
+use crate::bench;
+use crate::anim::{self, ActionTracker, Seen};
+use crate::annotations::{self, Marks};
+use crate::budget::{self, FrameBudget, Shed};
+use crate::bus::{self, Event, Topic};
+use crate::chat;
+use crate::bundle;
+use crate::capture;
+use crate::compass::{self, Bearing, CompassColors};
+use crate::config::{self, Config, DrawSite, HealthColor, SnapOrigin};
+use crate::crashdump;
+use crate::diag;
+use crate::draw::{self, BoxDraw, BoxPass, Cmd, Dash, DrawList, HeadLines, Snaplines};
+use crate::entities::{self, EngineApi, PlayerData};
+#[cfg(feature = "radar")]
+use crate::feed::FeedPlayer;
+use crate::flash::{Blind, FlashTracker};
+use crate::grenades::{self, FuseTracker, Grenade};
+use crate::events;
+use crate::hook;
+use crate::input;
+use crate::layout::{self, Layout};
+use crate::mapdata;
+use crate::math::{self, Angles, Frustum, Vec3};
+#[cfg(feature = "menu")]
+use crate::menu;
+#[cfg(feature = "radar")]
+use crate::minimap::Blip;
+use crate::names;
+use crate::player::{self, EngineReader, ViewRef};
+use crate::plugin::{self, EspPlayer};
+use crate::posgraph;
+use crate::markers::{self, Marker, Watch};
+use crate::netgraph::{BarColors, NetGraph};
+use crate::pacing::{self, Pacing};
+use crate::panics::{self, PanicLog, Subsystem};
+use crate::readrate::{ReadRate, Throttle, Track};
 use crate::render;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+#[cfg(feature = "scripting")]
+use crate::script::{self, ScriptFrame, ScriptPlayer};
+use crate::session::Session;
+use crate::sounds::{self, SoundKind};
+use crate::stats::Stats;
+use crate::subsystems::{self, FrameCtx};
+use crate::scrub::Scrubber;
+use crate::slots::{self, Slot, SlotColors};
+use crate::spectators;
+use crate::theme::{self, ColorMode, Shade};
+use crate::time::{self, Stopwatch, Timings};
+use crate::usermsg;
+use crate::view::{self, NameFit, OverlayFade, PlayerCache, PlayerCacheEntry, Text3d, TextSide};
+use crate::warmup;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
 use winapi::shared::windef::{HDC, RECT};
 use winapi::um::winuser::{GetAsyncKeyState, GetClientRect, WindowFromDC};
 
@@ -22,44 +99,106 @@ use winapi::um::winuser::{GetAsyncKeyState, GetClientRect, WindowFromDC};
 // Configuration Constants
 // ============================================================
 
-const VK_F6: i32 = 0x75;               // Virtual key code for F6
-const BOX_ASPECT: f32 = 0.50;          // Width/height ratio for ESP boxes
+const VK_F6: u32 = 0x75;               // Virtual key code for F6
+const VK_F5: i32 = 0x74;               // Default `snapshot` bind
+const VK_F12: i32 = 0x7B;              // Steam overlay screenshot key
+const SCREENSHOT_HIDE_MS: u32 = 300;   // Overlay stays hidden this long around a capture
 const UNITS_PER_METER: f32 = 39.37;    // GoldSrc units to meters conversion
-const PIXEL_MARGIN: f32 = 1_000_000.0; // Off-screen culling threshold
-const CACHE_TTL_FRAMES: u32 = 90;      // How many frames to keep showing a cached box
+const CACHE_TTL_MS: u32 = 1500;        // How long (ms) to keep showing a cached box
+const PROBE_DIST: f32 = 32768.0;       // Projection probe distance (far, so eye-height error is negligible)
+const PROBE_TOLERANCE: f32 = 0.03;     // Relative projection error tolerated before correcting
+const EYE_HEIGHT: f32 = 17.0;          // Standing view offset above the player origin
+const CAMERA_MAX_AGE_MS: u32 = 250;    // A captured camera older than this isn't trusted
+const DEAD_ALPHA: f32 = 0.40;          // Alpha for dead players shown during demo playback
+const DEATH_MARK_PX: f32 = 5.0;        // Half-size of the death-spot X
+const FLOOR_DROP: f32 = 36.0;          // Origin (hull center) to floor, for markers and trails
+const TRAIL_ALPHA: f32 = 0.70;         // Alpha of the newest trail segment
+const RING_SEGMENTS: usize = 24;       // Segments per sound ring
+const RING_SELF_DIST: f32 = 48.0;      // Sounds this close to the viewer are our own
+const TRACER_PIECES: usize = 8;        // A tracer is projected in pieces so one end can be off-screen
+const FLASH_PX: f32 = 3.0;             // Half size of the muzzle flash dot
+const FLASH_FRACTION: f32 = 0.25;      // The flash shows for this part of a tracer's life
+const BLIND_BAR_W: f32 = 120.0;        // Width of the flash countdown bar
+const BLIND_BAR_H: f32 = 4.0;
+const BLIND_Y: f32 = 60.0;             // Top of the flash indicator
+const STATUS_LINE_H: f32 = 14.0;       // Status line spacing
+const STATUS_LINES: usize = 5;         // Lines reserved for the status block, so panels below don't move
+const STATUS_W: f32 = 360.0;           // Status block width (right anchors align to its edge)
+const ERR_BADGE_COLOR: [f32; 4] = [0.85, 0.1, 0.1, 0.9]; // "ERR n" badge after the first status line
+const PROFILE_NOTICE_MS: u32 = 1500;   // How long the profile name shows after a switch
+const PROFILE_Y: f32 = 40.0;           // Top of the profile notice
+const THREAT_PAD: f32 = 3.0;           // Padding around the closest-threat warning
+const THREAT_BACK: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const COMPASS_H: f32 = 24.0;           // Height of the compass strip
+const LOW_FLASH_MS: u32 = 250;         // "LOW" is shown and hidden this long in turn
+const DAMAGE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0]; // What a damage pulse brightens the box toward
+const GRENADE_PX: f32 = 4.0;           // Half-size of the grenade diamond
+const ANNOTATION_PX: f32 = 5.0;        // Half-size of an annotation's marker
+const HE_COLOR: [f32; 4] = [1.0, 0.35, 0.2, 1.0];
+const FLASHBANG_COLOR: [f32; 4] = [1.0, 1.0, 0.6, 1.0];
+const SMOKE_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+const STATS_W: f32 = 400.0;            // Width of the round summary panel
+const STATS_NAME_LEN: usize = 16;      // Names are cut to this many characters in the summary
+const SESSION_SAMPLE_INTERVAL_MS: u32 = 1000; // Player slots are sampled for the session this often
 
 // ============================================================
-// State: Toggle & Frame Counter
+// State: Toggle
 // ============================================================
 
 /// Whether the ESP overlay is currently visible.
 static VISIBLE: AtomicBool = AtomicBool::new(true);
 
+/// Overlay alpha across toggles (`[status] toggle_fade_ms`).
+static TOGGLE_FADE: Mutex<OverlayFade> = Mutex::new(OverlayFade::new());
+
 /// Previous F6 key state (for edge detection: press, not hold).
 static F6_PREV: AtomicBool = AtomicBool::new(false);
 
-/// Global frame counter (incremented each frame).
-static FRAME_ID: AtomicU32 = AtomicU32::new(0);
+/// Previous profile hotkey state (GetAsyncKeyState fallback).
+static PROFILE_KEY_PREV: AtomicBool = AtomicBool::new(false);
+
+/// Previous opacity hotkey state (GetAsyncKeyState fallback).
+static OPACITY_KEY_PREV: AtomicBool = AtomicBool::new(false);
+
+/// Previous annotation hotkey state (GetAsyncKeyState fallback).
+static MARK_KEY_PREV: AtomicBool = AtomicBool::new(false);
+
+/// Previous scrub key states: freeze, back, forward (GetAsyncKeyState fallback).
+static SCRUB_KEYS_PREV: [AtomicBool; 3] = [AtomicBool::new(false), AtomicBool::new(false), AtomicBool::new(false)];
 
 // ============================================================
-// Per-Player Cache (for fade-out effect when players disappear)
+// State: Frame Rate (status line {fps})
 // ============================================================
-// These arrays are indexed by player slot (1-32). Slot 0 is unused.
 
-/// Cached bounding box coordinates [x0, y0, x1, y1] per player.
-static mut LAST_BOX: [[f32; 4]; 33] = [[0.0; 4]; 33];
+static FPS_START: AtomicU32 = AtomicU32::new(0);  // Start of the current one-second window
+static FPS_FRAMES: AtomicU32 = AtomicU32::new(0); // Frames so far in that window
+static FPS: AtomicU32 = AtomicU32::new(0);        // Frames counted in the last full window
+
+/// Count one overlay frame; the rate is updated once a second.
+fn count_fps(now: u32) {
+    let frames = FPS_FRAMES.fetch_add(1, Ordering::Relaxed) + 1;
+    let start = FPS_START.load(Ordering::Relaxed);
+    let elapsed = now.wrapping_sub(start);
+    if start == 0 || elapsed >= 1000 {
+        if start != 0 { FPS.store(frames * 1000 / elapsed, Ordering::Relaxed); }
+        FPS_START.store(now, Ordering::Relaxed);
+        FPS_FRAMES.store(0, Ordering::Relaxed);
+    }
+}
 
-/// Cached feet screen position [x, y] per player (for snap-lines).
-static mut LAST_FEET: [[f32; 2]; 33] = [[0.0; 2]; 33];
+// ============================================================
+// Per-Player Cache (for fade-out effect when players disappear)
+// ============================================================
 
-/// Cached distance (meters) per player.
-static mut LAST_DIST: [f32; 33] = [0.0; 33];
+/// Last drawn box, labels and timestamps per player slot (see view.rs).
+static PLAYER_CACHE: Mutex<PlayerCache> = Mutex::new(PlayerCache::new());
 
-/// Cached team color per player.
-static mut LAST_COLOR: [[f32; 4]; 33] = [[0.0; 4]; 33];
+/// Team of the player whose view is shown (local player, or the first-person
+/// spectate target), for enemy/friend colors. 0 = unknown.
+static VIEW_TEAM: AtomicI32 = AtomicI32::new(0);
 
-/// Frame number when each player was last seen.
-static mut LAST_SEEN: [u32; 33] = [0; 33];
+/// Per-player reload / weapon switch inference (see anim.rs).
+static ACTIONS: Mutex<ActionTracker> = Mutex::new(ActionTracker::new());
 
 /// Cached local player position (fallback when engine returns None briefly).
 static mut LAST_LOCAL: [f32; 3] = [0.0; 3];
@@ -73,34 +212,1281 @@ static LAST_LOCAL_VALID: AtomicBool = AtomicBool::new(false);
 
 /// Poll the F6 key and toggle visibility on rising edge (press, not hold).
 fn poll_toggle() {
-    let down = unsafe { (GetAsyncKeyState(VK_F6) as u16) & 0x8000 != 0 };
-    let was = F6_PREV.swap(down, Ordering::Relaxed);
-    if down && !was {
-        // XOR with true = flip the boolean
-        VISIBLE.fetch_xor(true, Ordering::Relaxed);
+    // Prefer real key messages from the WndProc subclass (focused, not typing)
+    if input::key_pressed(VK_F6, &F6_PREV) { toggle_visible(); }
+}
+
+/// Flip overlay visibility (F6, or from outside through the control pipe).
+pub fn toggle_visible() {
+    // XOR with true = flip the boolean
+    VISIBLE.fetch_xor(true, Ordering::Relaxed);
+}
+
+/// Show or hide the overlay (control pipe, window message).
+pub fn set_visible(on: bool) {
+    VISIBLE.store(on, Ordering::Relaxed);
+}
+
+/// Whether the overlay is visible.
+#[cfg(feature = "ipc")]
+pub fn visible() -> bool {
+    VISIBLE.load(Ordering::Relaxed)
+}
+
+/// Cycle config profiles on `[profiles] key`.
+fn poll_profile_key(cfg: &Config) {
+    if input::key_pressed(cfg.profile_key, &PROFILE_KEY_PREV) { config::next_profile(); }
+}
+
+/// Step the master opacity down (100/75/50/25%, then full again) on
+/// `[opacity] key`. Like the menu, it lasts until the config is reloaded.
+fn poll_opacity_key(cfg: &Config) {
+    if input::key_pressed(cfg.opacity_key, &OPACITY_KEY_PREV) {
+        config::update(|c| c.opacity = view::next_opacity(c.opacity));
     }
 }
 
+// ============================================================
+// Screenshot Detection
+// ============================================================
+// The screenshot keys are caught as they go down, before the engine runs the
+// bound command, so even captures of the presented (front) buffer come out
+// clean. Anything reading the framebuffer through glReadPixels is caught by
+// the hook in hook.rs.
+
+/// time::now_ms() when a screenshot key was last pressed (0 = never).
+static SHOT_KEY_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Whether a screenshot is being taken around `now`.
+fn screenshot_pending(now: u32) -> bool {
+    let down = unsafe {
+        (GetAsyncKeyState(VK_F5) as u16) & 0x8000 != 0
+            || (GetAsyncKeyState(VK_F12) as u16) & 0x8000 != 0
+    };
+    if down { SHOT_KEY_MS.store(now, Ordering::Relaxed); }
+    let key_ms = SHOT_KEY_MS.load(Ordering::Relaxed);
+    let key_recent = key_ms != 0 && now.wrapping_sub(key_ms) <= SCREENSHOT_HIDE_MS;
+    key_recent || hook::capture_recent(now, SCREENSHOT_HIDE_MS)
+}
+
+// ============================================================
+// Colors
+// ============================================================
+
+/// `c` with its alpha replaced.
+fn with_alpha(c: [f32; 4], alpha: f32) -> [f32; 4] {
+    [c[0], c[1], c[2], alpha]
+}
+
+// ============================================================
+// Box Draw List
+// ============================================================
+
+/// Build the box pass (draw.rs) for the queued boxes under this frame's
+/// settings and draw it.
+unsafe fn draw_box_list(hdc: HDC, cfg: &Config, boxes: &mut [BoxDraw], snap_from: [f32; 2], center: [f32; 2], level: u8) {
+    let shade = |c: [f32; 4]| cfg.box_shade(c);
+    let snap_color = |team: i32| cfg.snapline_color(team);
+    let pass = BoxPass {
+        palette:   cfg.theme.palette(),
+        line:      cfg.box_line,
+        brackets:  cfg.box_corners,
+        shade:     &shade,
+        badge:     cfg.health_color == HealthColor::Badge,
+        snaplines: (cfg.snapline_enabled && !budget::sheds(level, Shed::Snaplines)).then_some(Snaplines {
+            from: snap_from, target: cfg.snapline_target, style: cfg.snapline_line, color: &snap_color,
+        }),
+        head_lines: (cfg.snapline_head_line && !budget::sheds(level, Shed::Snaplines)).then_some(HeadLines {
+            from: center, color: cfg.snapline_head_color, style: cfg.snapline_line,
+        }),
+        labels:    !budget::sheds(level, Shed::Labels),
+        names:     cfg.name_fit,
+        low:       ((time::now_ms() / LOW_FLASH_MS) & 1 == 0).then_some(cfg.health_low),
+        focus_width: cfg.focus_width,
+    };
+    render::draw_list(hdc, &draw::box_pass(boxes, &pass));
+}
+
+/// Configured box color for a player, else the running mod's own color for
+/// their team (team color mode only). None = use the theme.
+fn box_color(cfg: &Config, steam_id: u64, team: i32, team_name: Option<&str>) -> Option<[f32; 4]> {
+    cfg.color_override(steam_id, team, team_name).or_else(|| match cfg.color_mode {
+        ColorMode::Team     => entities::game_mod().profile().team_color(team),
+        ColorMode::Relation => None,
+    })
+}
+
+/// Color of a cached box: its configured or mod color, or the theme's cached color.
+fn cached_color(cfg: &Config, e: &PlayerCacheEntry) -> [f32; 4] {
+    let view_team = VIEW_TEAM.load(Ordering::Relaxed);
+    box_color(cfg, e.steam_id, e.team, e.team_name.as_deref())
+        .unwrap_or_else(|| cfg.theme.palette().cached_player_color(cfg.color_mode, e.team, view_team))
+}
+
+/// A cached box `age_ms` old, faded from `base_alpha`, with the name and
+/// weapon it was last drawn with. None once it has faded out.
+fn cached_box(cfg: &Config, e: &PlayerCacheEntry, age_ms: u32, base_alpha: f32) -> Option<BoxDraw> {
+    let alpha = view::cached_alpha(base_alpha, age_ms)?;
+    let mut info = format!("{:.1}m", e.dist);
+    if !e.weapon.is_empty() { info.push_str(&format!("  [{}]", e.weapon)); }
+    Some(BoxDraw {
+        rect: e.rect,
+        feet: e.feet,
+        dist: e.dist,
+        color: with_alpha(cached_color(cfg, e), alpha),
+        team: e.team,
+        snap_alpha: alpha,
+        name: (!e.name.is_empty()).then(|| e.name.clone()),
+        info,
+        text_alpha: alpha,
+        health: None,
+        focus: false,
+        head: None,
+        line: cfg.box_cached_lines,
+    })
+}
+
+// ============================================================
+// Crosshair & FOV Circle
+// ============================================================
+
+/// Add the custom crosshair at `center` (if enabled).
+fn crosshair(cfg: &Config, center: [f32; 2], list: &mut DrawList) {
+    if !cfg.crosshair_enabled { return; }
+    let [x, y] = center;
+    let (gap, len, c, style) = (cfg.crosshair_gap, cfg.crosshair_size, cfg.crosshair_color, cfg.crosshair_line);
+    list.line([x - gap - len, y], [x - gap, y], c, style);
+    list.line([x + gap, y], [x + gap + len, y], c, style);
+    list.line([x, y - gap - len], [x, y - gap], c, style);
+    list.line([x, y + gap], [x, y + gap + len], c, style);
+}
+
+/// Draw the FOV circle (if enabled). The configured radius is an angle from the
+/// crosshair, converted to pixels with the current horizontal FOV so the circle
+/// tightens correctly while scoped.
+fn fov_circle(cfg: &Config, center: [f32; 2], vw: f32, fov_deg: f32, list: &mut DrawList) {
+    if !cfg.fov_circle_enabled { return; }
+    let half_fov = (fov_deg * 0.5).to_radians();
+    let radius = cfg.fov_circle_radius.to_radians();
+    if half_fov <= 0.0 || radius <= 0.0 || radius >= std::f32::consts::FRAC_PI_2 { return; }
+    let r_px = vw * 0.5 * radius.tan() / half_fov.tan();
+    list.push(Cmd::Circle { center, radius: r_px, segments: 64, color: cfg.fov_circle_color, style: cfg.crosshair_line });
+}
+
+// ============================================================
+// Audio Alerts
+// ============================================================
+
+/// Crossing state of the audio alerts (render thread only).
+static ALERTS: Mutex<Alerts> = Mutex::new(Alerts::new());
+
+/// Play the configured sound for every alert this frame's enemies trigger.
+unsafe fn play_alerts(cfg: &Config, api: &EngineApi, eye: Vec3, yaw: f32, now: u32, enemies: &[(i32, Vec3)]) {
+    if !cfg.alerts_enabled { return; }
+    let params = AlertParams {
+        radius:       cfg.alerts_radius,
+        behind_cone:  cfg.alerts_behind_cone,
+        behind_range: cfg.alerts_behind_range,
+        cooldown_ms:  [
+            (cfg.alerts_near_cooldown.max(0.0) * 1000.0) as u32,
+            (cfg.alerts_behind_cooldown.max(0.0) * 1000.0) as u32,
+        ],
+    };
+    let Ok(mut state) = ALERTS.lock() else { return };
+    for alert in state.update(&params, now, eye, yaw, enemies) {
+        let sound = match alert {
+            Alert::Near   => &cfg.alerts_near_sound,
+            Alert::Behind => &cfg.alerts_behind_sound,
+        };
+        if !sound.is_empty() { api.play_sound(sound, cfg.alerts_volume); }
+    }
+}
+
+/// "REAR-LEFT 4 m" while the nearest enemy is within `[threat] range` and off
+/// screen (more than half the FOV off the view direction).
+unsafe fn draw_threat(hdc: HDC, cfg: &Config, eye: Vec3, yaw: f32, fov: f32, enemies: &[(i32, Vec3)], layout: &mut Layout) {
+    if !cfg.threat_enabled { return; }
+    let Some(threat) = alerts::closest_threat(eye, yaw, fov * 0.5, cfg.threat_range, enemies) else { return };
+    let text = format!("{} {:.0} m", threat.direction(), threat.dist / UNITS_PER_METER);
+    let w = render::text_width(&text);
+    let [x0, y0, x1, y1] = layout.place(cfg.threat_place, w + 2.0 * THREAT_PAD, render::TEXT_HEIGHT + 2.0 * THREAT_PAD);
+    render::fill_rect(x0, y0, x1, y1, THREAT_BACK);
+    render::draw_text(hdc, x0 + THREAT_PAD, y0 + THREAT_PAD, &text, cfg.threat_color);
+}
+
+/// `[compass]`: enemy bearings on a strip centered at the top of the screen.
+unsafe fn draw_compass(hdc: HDC, cfg: &Config, eye: Vec3, yaw: f32, enemies: &[(i32, Vec3)], screen_w: f32) {
+    if !cfg.compass_enabled { return; }
+    let bearings: Vec<Bearing> = enemies.iter()
+        .filter_map(|&(_, pos)| Some(Bearing { yaw: alerts::relative_yaw(eye, yaw, pos)?, dist: eye.distance(pos) }))
+        .collect();
+    let x0 = ((screen_w - cfg.compass_width) * 0.5).max(0.0);
+    let area = [x0, cfg.compass_y, x0 + cfg.compass_width, cfg.compass_y + COMPASS_H];
+    render::fill_rect(area[0], area[1], area[2], area[3], THREAT_BACK);
+    let colors = CompassColors { scale: cfg.theme.palette().text, enemy: cfg.compass_color };
+    render::draw_list(hdc, &compass::strip(yaw, cfg.compass_span, cfg.compass_range, &bearings, area, &colors));
+}
+
+// ============================================================
+// Flash Indicator
+// ============================================================
+
+/// Length of the flash being shown (render thread only).
+static FLASH: Mutex<FlashTracker> = Mutex::new(FlashTracker::new());
+
+/// Whether the local player is blinded by the screen fade right now.
+unsafe fn blind_state(api: &EngineApi) -> Option<Blind> {
+    let fade = api.screen_fade()?;
+    let time = api.client_time()?;
+    FLASH.lock().ok()?.update(&fade, time)
+}
+
+/// "FLASHED 2.3s" centered near the top, with a bar counting down to clear.
+unsafe fn draw_flash_indicator(hdc: HDC, cfg: &Config, blind: &Blind, screen_w: f32) {
+    if !cfg.flash_indicator { return; }
+    let text = if blind.remaining.is_finite() {
+        format!("FLASHED {:.1}s", blind.remaining)
+    } else {
+        "FLASHED".to_string()
+    };
+    let cx = screen_w * 0.5;
+    render::draw_text(hdc, cx - render::text_width(&text) * 0.5, BLIND_Y, &text, cfg.flash_color);
+    let (x0, y0) = (cx - BLIND_BAR_W * 0.5, BLIND_Y + 6.0);
+    render::fill_rect(x0, y0, x0 + BLIND_BAR_W, y0 + BLIND_BAR_H, [0.0, 0.0, 0.0, 0.6]);
+    render::fill_rect(x0, y0, x0 + BLIND_BAR_W * blind.fraction, y0 + BLIND_BAR_H, cfg.flash_color);
+}
+
+// ============================================================
+// Brush Entity Debug ESP
+// ============================================================
+
+/// Edges of a box as pairs of corner indices (bit 0 = x, bit 1 = y, bit 2 = z max).
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1), (2, 3), (4, 5), (6, 7), // Along x
+    (0, 2), (1, 3), (4, 6), (5, 7), // Along y
+    (0, 4), (1, 5), (2, 6), (3, 7), // Along z
+];
+
+/// Outline every current brush entity whose class (looked up in the map's
+/// entity lump by its inline model name) is in `debug_esp.classes`, with the
+/// class name at its top. Nothing is drawn until the map file is loaded.
+unsafe fn draw_brush_entities(
+    hdc: HDC, cfg: &Config, api: &EngineApi, proj_scale: f32,
+    to_px: impl Fn(f32, f32) -> [f32; 2],
+) {
+    if !cfg.debug_brushes || cfg.debug_classes.is_empty() { return; }
+    let Some(map) = mapdata::current(api) else { return };
+    let frustum = view_frustum();
+
+    for ent in api.iter_entities().filter(|e| e.current && !e.is_player) {
+        let Some(model) = api.model_name(ent.model_index) else { continue };
+        let Some(class) = map.brush_classes.get(&model) else { continue };
+        if !cfg.debug_classes.iter().any(|c| c == class) { continue; }
+
+        let o = ent.origin;
+        let (lo, hi) = (o + ent.mins, o + ent.maxs);
+        if frustum.is_some_and(|f| !f.intersects_box(lo, hi)) { continue; }
+        let corners: Vec<Option<[f32; 2]>> = (0..8)
+            .map(|i| Vec3 {
+                x: if i & 1 != 0 { hi.x } else { lo.x },
+                y: if i & 2 != 0 { hi.y } else { lo.y },
+                z: if i & 4 != 0 { hi.z } else { lo.z },
+            })
+            .map(|p| project(api, p, proj_scale)
+                .filter(|(x, y)| x.is_finite() && y.is_finite())
+                .map(|(x, y)| to_px(x, y)))
+            .collect();
+        for (a, b) in BOX_EDGES {
+            if let (Some(a), Some(b)) = (corners[a], corners[b]) {
+                render::draw_line(a[0], a[1], b[0], b[1], cfg.debug_color, cfg.world_line);
+            }
+        }
+
+        let top = Vec3 { z: hi.z, ..lo.lerp(hi, 0.5) };
+        let Some((nx, ny)) = project(api, top, proj_scale) else { continue };
+        if !nx.is_finite() || !ny.is_finite() { continue; }
+        let [x, y] = to_px(nx, ny);
+        let label = format!("{} {}", class, model);
+        render::draw_text(hdc, x - render::text_width(&label) * 0.5, y - 12.0, &label, cfg.debug_color);
+    }
+}
+
+// ============================================================
+// Death-Spot Markers
+// ============================================================
+
+/// Draw a fading X with the player's name at each recent death spot.
+unsafe fn draw_death_markers(cfg: &Config, world: &WorldView<impl Fn(f32, f32) -> [f32; 2]>, now: u32) {
+    if !cfg.death_markers_enabled || cfg.death_markers_seconds <= 0.0 { return; }
+    let ttl_ms = (cfg.death_markers_seconds * 1000.0) as u32;
+
+    for spot in entities::death_spots(ttl_ms) {
+        let floor = Vec3 { x: spot.origin.x, y: spot.origin.y, z: spot.origin.z - FLOOR_DROP };
+        let Some([x, y]) = world.project(floor) else { continue };
+
+        // Linear fade over the configured lifetime
+        let age = now.wrapping_sub(spot.time_ms) as f32;
+        let alpha = (1.0 - age / ttl_ms as f32).clamp(0.0, 1.0);
+        if alpha <= 0.02 { continue; }
+
+        let pal = cfg.theme.palette();
+        let view_team = VIEW_TEAM.load(Ordering::Relaxed);
+        let base = box_color(cfg, 0, spot.team, None)
+            .unwrap_or_else(|| pal.player_color(cfg.color_mode, spot.team, view_team));
+        let color = with_alpha(base, alpha);
+
+        let r = DEATH_MARK_PX;
+        render::draw_line(x - r, y - r, x + r, y + r, color, cfg.world_line);
+        render::draw_line(x - r, y + r, x + r, y - r, color, cfg.world_line);
+        let style = Text3d {
+            color: with_alpha(pal.text, pal.text[3] * alpha), side: TextSide::Below, gap: r + 2.0, fit: cfg.name_fit,
+        };
+        world.draw_text_3d(floor, &names::display(&spot.name), &style);
+    }
+}
+
+// ============================================================
+// Map Annotations
+// ============================================================
+
+/// An `esp_mark` / `esp_unmark` waiting for the next frame.
+enum MarkCommand {
+    Add(Option<String>),
+    Remove,
+}
+
+static ANNOTATIONS: Mutex<Option<Marks>> = Mutex::new(None);
+static MARK_COMMANDS: Mutex<Vec<MarkCommand>> = Mutex::new(Vec::new());
+static MARK_COMMANDS_ATTEMPTED: AtomicBool = AtomicBool::new(false);
+
+fn annotations_path(map: &str) -> std::path::PathBuf {
+    entities::dll_dir().join("esp_annotations").join(annotations::file_name(map))
+}
+
+/// Load the new map's markers (a map change event).
+unsafe fn annotations_event(e: &Event, _: &mut EventCtx) {
+    let Event::MapLoaded { map, .. } = e else { return };
+    let marks = map.as_deref().map(|map| {
+        let text = std::fs::read_to_string(annotations_path(map)).unwrap_or_default();
+        let marks = Marks::parse(map, &text);
+        if !marks.marks.is_empty() { entities::logf(format!("annotations: {} for {}", marks.marks.len(), map)); }
+        marks
+    });
+    *ANNOTATIONS.lock().unwrap_or_else(|e| e.into_inner()) = marks;
+}
+
+/// Write the map's markers out, on a background thread.
+fn save_annotations(marks: &Marks) {
+    let path = annotations_path(&marks.map);
+    let text = marks.to_text();
+    std::thread::spawn(move || {
+        let written = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::write(&path, text));
+        if let Err(e) = written { entities::logf(format!("annotations: {}: {}", path.display(), e)); }
+    });
+}
+
+unsafe extern "C" fn cmd_mark() {
+    let name = entities::cmd_args().join(" ");
+    let name = (!name.is_empty()).then_some(name);
+    MARK_COMMANDS.lock().unwrap_or_else(|e| e.into_inner()).push(MarkCommand::Add(name));
+}
+
+unsafe extern "C" fn cmd_unmark() {
+    MARK_COMMANDS.lock().unwrap_or_else(|e| e.into_inner()).push(MarkCommand::Remove);
+}
+
+/// Register `esp_mark` and `esp_unmark`, once. Each is logged on its own:
+/// the engine can take one and not the other.
+unsafe fn install_mark_commands() {
+    if MARK_COMMANDS_ATTEMPTED.swap(true, Ordering::Relaxed) { return; }
+    let commands: [(&'static [u8], &str, unsafe extern "C" fn()); 2] =
+        [(b"esp_mark\0", "esp_mark", cmd_mark), (b"esp_unmark\0", "esp_unmark", cmd_unmark)];
+    for (name, shown, f) in commands {
+        if entities::add_command(name, f) {
+            entities::logf(format!("console command {} registered", shown));
+        } else {
+            entities::logf(format!("console command {}: AddCommand not available", shown));
+        }
+    }
+}
+
+/// Apply the hotkey and console commands, then draw the map's markers with
+/// their names and distances.
+unsafe fn draw_annotations(cfg: &Config, world: &WorldView<impl Fn(f32, f32) -> [f32; 2]>, pos: Vec3, have_pos: bool) {
+    if !cfg.annotations_enabled { return; }
+    install_mark_commands();
+    if input::key_pressed(cfg.annotations_key, &MARK_KEY_PREV) {
+        MARK_COMMANDS.lock().unwrap_or_else(|e| e.into_inner()).push(MarkCommand::Add(None));
+    }
+
+    let mut guard = ANNOTATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(marks) = guard.as_mut() else {
+        // No map to put them on; keeping them would apply them to the next one
+        let dropped = std::mem::take(&mut *MARK_COMMANDS.lock().unwrap_or_else(|e| e.into_inner())).len();
+        if dropped > 0 { entities::logf(format!("annotations: no map loaded, {} mark command(s) dropped", dropped)); }
+        return;
+    };
+    // Without a position (dead, spectating, before the first read) they wait for one
+    let commands = if have_pos {
+        std::mem::take(&mut *MARK_COMMANDS.lock().unwrap_or_else(|e| e.into_inner()))
+    } else {
+        Vec::new()
+    };
+    if !commands.is_empty() {
+        let eye = pos + Vec3::new(0.0, 0.0, EYE_HEIGHT);
+        let at = match world.api.view_angles().map(|a| Angles::from(a).forward()) {
+            Some(dir) if cfg.annotations_distance > 0.0 => eye + dir * cfg.annotations_distance,
+            Some(dir) => {
+                let map = mapdata::current(world.api);
+                let walls = map.as_deref().map_or(&[][..], |m| m.walls.as_slice());
+                annotations::aim_point(eye, dir, pos.z - FLOOR_DROP, walls)
+            }
+            None => pos,
+        };
+        for c in commands {
+            match c {
+                MarkCommand::Add(name) => {
+                    let m = marks.add(at, name.as_deref());
+                    entities::logf(format!("annotations: added \"{}\" at {:.0} {:.0} {:.0}", m.name, at.x, at.y, at.z));
+                }
+                MarkCommand::Remove => match marks.remove_nearest(at, annotations::UNMARK_RANGE) {
+                    Some(m) => entities::logf(format!("annotations: removed \"{}\"", m.name)),
+                    None => entities::log("annotations: no marker close enough to remove"),
+                },
+            }
+        }
+        save_annotations(marks);
+    }
+
+    for m in &marks.marks {
+        let dist = m.pos.distance(pos);
+        if cfg.annotations_max_distance > 0.0 && dist > cfg.annotations_max_distance { continue; }
+        let Some([x, y]) = world.project(m.pos) else { continue };
+        let (color, r) = (cfg.annotations_color, ANNOTATION_PX);
+        render::draw_line(x - r, y, x + r, y, color, cfg.world_line);
+        render::draw_line(x, y - r, x, y + r, color, cfg.world_line);
+        let label = if m.name.is_empty() { format!("{:.0}u", dist) } else { format!("{} {:.0}u", m.name, dist) };
+        let fit = NameFit { max_width: 0.0, ..cfg.name_fit }; // The name is the point; never cut it
+        world.draw_text_3d(m.pos, &label, &Text3d { color, side: TextSide::Above, gap: r, fit });
+    }
+}
+
+// ============================================================
+// Grenade Labels
+// ============================================================
+
+static GRENADES: Mutex<Option<FuseTracker>> = Mutex::new(None);
+
+/// Mark each thrown grenade of a shown kind with a diamond, its kind and the
+/// fuse time left (grenades.rs).
+unsafe fn draw_grenades(cfg: &Config, world: &WorldView<impl Fn(f32, f32) -> [f32; 2]>, now: u32) {
+    if !cfg.grenades_enabled { return; }
+    let api = world.api;
+    let Ok(mut guard) = GRENADES.lock() else { return };
+    let tracker = guard.get_or_insert_with(FuseTracker::default);
+
+    for ent in api.iter_entities().filter(|e| e.current && e.movetype == grenades::MOVETYPE_BOUNCE) {
+        let Some((kind, left_ms)) = tracker.update(ent.index, ent.model_index, now,
+            || api.model_name(ent.model_index)) else { continue };
+        let (shown, color) = match kind {
+            Grenade::He    => (cfg.grenades_he, HE_COLOR),
+            Grenade::Flash => (cfg.grenades_flash, FLASHBANG_COLOR),
+            Grenade::Smoke => (cfg.grenades_smoke, SMOKE_COLOR),
+        };
+        if !shown { continue; }
+
+        let Some([x, y]) = world.project(ent.origin) else { continue };
+        let r = GRENADE_PX;
+        render::draw_line(x, y - r, x + r, y, color, cfg.world_line);
+        render::draw_line(x + r, y, x, y + r, color, cfg.world_line);
+        render::draw_line(x, y + r, x - r, y, color, cfg.world_line);
+        render::draw_line(x - r, y, x, y - r, color, cfg.world_line);
+
+        let label = if left_ms > 0 {
+            format!("{} {:.1}s", kind.name(), left_ms as f32 / 1000.0)
+        } else {
+            kind.name().to_string()
+        };
+        world.draw_text_3d(ent.origin, &label, &Text3d { color, side: TextSide::Above, gap: r, fit: cfg.name_fit });
+    }
+    tracker.end_frame(now);
+}
+
+// ============================================================
+// Frame Events (bus.rs)
+// ============================================================
+
+/// What an event handler gets: this frame's settings, engine and time.
+struct EventCtx<'a> {
+    cfg: &'a Config,
+    api: &'a EngineApi,
+    now: u32,
+}
+
+type Handler = unsafe fn(&Event, &mut EventCtx);
+
+/// Who handles which events, in order, and the guard each runs under.
+const HANDLERS: &[(Topic, (Subsystem, Handler))] = &[
+    (Topic::Death,  (Subsystem::Frame, alive_event)),
+    (Topic::Round,  (Subsystem::Frame, alive_event)),
+    (Topic::Map,    (Subsystem::Frame, player_cache_event)),
+    (Topic::Map,    (Subsystem::Stats, stats_event)),
+    (Topic::Death,  (Subsystem::Stats, stats_event)),
+    (Topic::Round,  (Subsystem::Stats, stats_event)),
+    (Topic::Map,    (Subsystem::Session, session_event)),
+    (Topic::Map,    (Subsystem::Annotations, annotations_event)),
+    (Topic::Chat,   (Subsystem::Chat, chat_event)),
+    (Topic::Chat,   (Subsystem::Session, session_chat_event)),
+    (Topic::Config, (Subsystem::Frame, panics_event)),
+    (Topic::Hooks,  (Subsystem::Frame, hook_lost_event)),
+    (Topic::Hooks,  (Subsystem::Markers, markers_event)),
+];
+
+const HOOK_LOST_SHOW_MS: u32 = 15_000; // How long the status line names a lost hook
+
+static HOOK_LOST: Mutex<Option<(&'static str, u32)>> = Mutex::new(None); // (hook, time::now_ms())
+
+/// Hand what was published since the last frame to its handlers.
+unsafe fn dispatch_events(cfg: &Config, api: &EngineApi, now: u32) {
+    let mut ctx = EventCtx { cfg, api, now };
+    for e in bus::drain() {
+        for (sub, handler) in bus::subscribers(HANDLERS, &e) {
+            guarded(sub, || handler(&e, &mut ctx));
+        }
+    }
+}
+
+unsafe fn alive_event(e: &Event, ctx: &mut EventCtx) {
+    entities::note_event(e, ctx.now);
+}
+
+/// The cached boxes and health reads are of the old map: start over.
+unsafe fn player_cache_event(_: &Event, _: &mut EventCtx) {
+    *PLAYER_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = PlayerCache::new();
+}
+
+/// After a reload the subsystems switched off for panicking are on again
+/// (their flags come from the file): take them off the diagnostics list.
+unsafe fn panics_event(_: &Event, _: &mut EventCtx) {
+    let back = PANICS.lock().unwrap_or_else(|e| e.into_inner()).reenable();
+    if back.is_empty() { return; }
+    let names: Vec<&str> = back.iter().map(|s| s.name()).collect();
+    entities::logf(format!("config reloaded: {} back on", names.join(", ")));
+}
+
+unsafe fn hook_lost_event(e: &Event, ctx: &mut EventCtx) {
+    let Event::HookLost(name) = e else { return };
+    entities::logf(format!("hook lost: {} was overwritten by something else", name));
+    if let Ok(mut g) = HOOK_LOST.lock() { *g = Some((*name, ctx.now)); }
+}
+
+/// "HOOK LOST: <name>" for a while after a hook was found overwritten.
+fn hook_lost_line(now: u32) -> Option<String> {
+    let (name, at) = (*HOOK_LOST.lock().ok()?)?;
+    (now.wrapping_sub(at) < HOOK_LOST_SHOW_MS).then(|| format!("HOOK LOST: {} (see the self-test)", name))
+}
+
+// ============================================================
+// Round Statistics
+// ============================================================
+
+static STATS: Mutex<Stats> = Mutex::new(Stats::new());
+static LAST_DEATH_MS: AtomicU32 = AtomicU32::new(0);        // Newest death spot counted
+
+/// Feed this frame's deaths, damage and time alive into the session
+/// statistics (kills and rounds come as frame events, see stats_event).
+unsafe fn update_stats(cfg: &Config, now: u32, alive: &[String], damaged: &[String]) {
+    if !cfg.stats_enabled { return; }
+    let Ok(mut stats) = STATS.lock() else { return };
+
+    // Without DeathMsg, deaths are the alive→dead transitions (no killer)
+    let last = LAST_DEATH_MS.load(Ordering::Relaxed);
+    let mut newest = last;
+    for spot in entities::death_spots(u32::MAX) {
+        if spot.time_ms.wrapping_sub(last) as i32 <= 0 { continue; }
+        if !usermsg::hooked() { stats.death(&names::display(&spot.name)); }
+        if spot.time_ms.wrapping_sub(newest) as i32 > 0 { newest = spot.time_ms; }
+    }
+    LAST_DEATH_MS.store(newest, Ordering::Relaxed);
+
+    for n in damaged { stats.damage(n); }
+    stats.frame(now, alive);
+}
+
+/// A kill or round from the user messages, or a map change: at the end of a
+/// map the statistics are written out for it and start over.
+unsafe fn stats_event(e: &Event, ctx: &mut EventCtx) {
+    if !ctx.cfg.stats_enabled { return; }
+    let Ok(mut stats) = STATS.lock() else { return };
+    let name = |idx: i32| ctx.api.player_info(idx).and_then(|p| p.name).map(|n| names::display(&n));
+    match e {
+        Event::MapLoaded { ended, .. } => {
+            if let Some(old) = ended.as_ref().filter(|_| ctx.cfg.stats_csv && !stats.is_empty()) {
+                export_file(&format!("esp_stats_{}", map_stem(old)), "csv", stats.to_csv());
+            }
+            *stats = Stats::new();
+        }
+        Event::PlayerDied(d) => {
+            let Some(victim) = name(d.victim) else { return };
+            let killer = if d.killer > 0 { name(d.killer) } else { None };
+            stats.kill(killer.as_deref(), &victim, d.headshot);
+        }
+        Event::RoundStart => stats.round_start(),
+        Event::RoundEnd => stats.round_end(ctx.now),
+        _ => {}
+    }
+}
+
+
+// ============================================================
+// Net Graph
+// ============================================================
+
+/// Net channel samples for the `[netgraph]` panel (netgraph.rs).
+static NETGRAPH: Mutex<NetGraph> = Mutex::new(NetGraph::new());
+const NETGRAPH_W: f32 = 220.0;
+const NETGRAPH_ROWS: usize = 3;  // Height of the bar graph, in text rows
+
+/// The net channel mini panel: latest values and a latency bar per sample.
+unsafe fn draw_netgraph(hdc: HDC, cfg: &Config, panels: &mut Panels) {
+    if !cfg.netgraph_enabled { return; }
+    let mut graph = NETGRAPH.lock().unwrap_or_else(|e| e.into_inner());
+    let now = time::now_ms();
+    let pal = cfg.theme.palette();
+    let mut lines = vec![("NET".to_string(), pal.text)];
+    match entities::net_sample(cfg) {
+        Ok(sample) => {
+            if graph.due(now) { graph.push(now, sample); }
+            lines.extend(graph.lines().into_iter().map(|l| (l, pal.text)));
+        }
+        Err(e) => {
+            graph.clear();
+            lines.push((e, pal.text));
+        }
+    }
+    let rows = if graph.latest().is_some() { NETGRAPH_ROWS } else { 0 };
+    let Some(area) = diag::draw_panel_with_area(hdc, &mut panels.layout, cfg.netgraph_place, NETGRAPH_W, &lines, rows)
+        else { return };
+    if rows > 0 {
+        let colors = BarColors { ok: pal.health[2], warn: pal.health[1], bad: pal.health[0] };
+        render::draw_list(hdc, &graph.bars(area, &colors));
+    }
+}
+
+// ============================================================
+// Health Markers
+// ============================================================
+
+static MARKERS: Mutex<Watch> = Mutex::new(Watch::new());
+static MARKERS_CHECKED: AtomicU32 = AtomicU32::new(0); // When the frame rate and loss were last looked at
+const MARKERS_CHECK_MS: u32 = 1000;
+
+/// Log a marker and put it in the session and the next feed snapshot.
+fn record_marker(cfg: &Config, m: Marker) {
+    entities::logf(format!("marker: {}: {}", m.kind.name(), m.detail));
+    if let Some(s) = SESSION.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        s.mark(unix_secs(), m.kind.name(), &m.detail);
+    }
+    #[cfg(feature = "radar")]
+    if cfg.feed_enabled { markers::queue_for_feed(m); }
+}
+
+/// Compare the frame rate and loss against `[markers]` (once a second).
+unsafe fn update_markers(cfg: &Config, now: u32) {
+    if !cfg.markers_enabled { return; }
+    if now.wrapping_sub(MARKERS_CHECKED.load(Ordering::Relaxed)) < MARKERS_CHECK_MS { return; }
+    MARKERS_CHECKED.store(now, Ordering::Relaxed);
+
+    let mut watch = MARKERS.lock().unwrap_or_else(|e| e.into_inner());
+    let fps = frame_pacing().and_then(|r| watch.fps(now, r.fps, cfg.markers_fps_below));
+    let loss = entities::net_sample(cfg).ok().and_then(|s| s.loss)
+        .and_then(|l| watch.loss(now, l, cfg.markers_loss_above));
+    drop(watch);
+    for m in fps.into_iter().chain(loss) { record_marker(cfg, m); }
+}
+
+/// A hook found overwritten is a marker too.
+unsafe fn markers_event(e: &Event, ctx: &mut EventCtx) {
+    let Event::HookLost(name) = e else { return };
+    if ctx.cfg.markers_enabled { record_marker(ctx.cfg, Marker::hook_lost(ctx.now, name)); }
+}
+
+// ============================================================
+// Slot Strip
+// ============================================================
+
+const SLOTS_W: f32 = 270.0;
+const SLOT_UNREAD: [f32; 4] = [0.55, 0.55, 0.55, 1.0]; // Connected, but no player data
+
+/// The `[slots]` panel: population counts, a cell per player slot and how its
+/// last read went, and which slots failed why (slots.rs).
+unsafe fn draw_slots(hdc: HDC, cfg: &Config, api: &EngineApi, layout: &mut Layout) {
+    if !cfg.slots_enabled { return; }
+    let mut teams = [None; 33]; // Team of every slot the last read got a player from
+    if let Ok(reads) = READS.lock() {
+        for (idx, p) in &reads.players {
+            if let Some(t) = teams.get_mut(*idx as usize) { *t = Some(p.team); }
+        }
+    }
+    let occupancy: Vec<Slot> = (1..=api.max_clients()).map(|idx| {
+        let team = teams.get(idx as usize).copied().flatten();
+        match api.player_info(idx) {
+            _ if team.is_some() => Slot::Player(team),
+            Some(info) if info.spectator => Slot::Spectator,
+            Some(info) if info.name.is_some() => Slot::Player(None),
+            _ => Slot::Empty,
+        }
+    }).collect();
+
+    let reads = entities::read_outcomes();
+
+    let pal = cfg.theme.palette();
+    let mut lines = vec![("SLOTS".to_string(), pal.text)];
+    lines.extend(slots::summary(&occupancy).into_iter().map(|l| (l, pal.text)));
+    lines.extend(slots::legend(&reads));
+    let Some(area) = diag::draw_panel_with_area(hdc, layout, cfg.slots_place, SLOTS_W, &lines, 3) else { return };
+    let colors = SlotColors {
+        team: pal.team, unread: SLOT_UNREAD, hollow: pal.text, empty: with_alpha(pal.text, 0.3), text: pal.text,
+    };
+    render::draw_list(hdc, &slots::strip(&occupancy, Some(&reads), area, (area[3] - area[1]) / 3.0, &colors));
+}
+
+// ============================================================
+// Spectators
+// ============================================================
+
+const SPECTATORS_W: f32 = 320.0;
+
+/// The `[spectators]` panel: who is spectating and on whom, the ones
+/// watching the local player highlighted (spectators.rs).
+unsafe fn draw_spectators(hdc: HDC, cfg: &Config, api: &EngineApi, layout: &mut Layout) {
+    if !cfg.spectators_enabled { return; }
+    let local = api.local_index().unwrap_or(0);
+    let specs = spectators::read(api, local, api.latest_message().unwrap_or(0));
+    if specs.is_empty() && cfg.spectators_hide_empty { return; }
+    let text = cfg.theme.palette().text;
+    let name_of = |idx: i32| api.player_info(idx).and_then(|p| p.name).map(|n| names::display(&n));
+    let lines: Vec<(String, [f32; 4])> = spectators::lines(&specs, name_of).into_iter()
+        .map(|(line, you)| (line, if you { cfg.spectators_color } else { text }))
+        .collect();
+    diag::draw_panel(hdc, layout, cfg.spectators_place, SPECTATORS_W, &lines);
+}
+
+// ============================================================
+// Chat History
+// ============================================================
+
+const CHAT_W: f32 = 460.0;
+const CHAT_FADE_MS: u32 = 1000; // Lines fade out over the end of chat.hold_seconds
+
+static CHAT: Mutex<chat::History> = Mutex::new(chat::History::new());
+
+/// A captured line with its sender's name filled in.
+unsafe fn chat_text(line: &chat::Line, api: &EngineApi) -> String {
+    line.render(|idx| api.player_info(idx).and_then(|p| p.name))
+}
+
+/// Log a captured line and keep it for the panel.
+unsafe fn chat_event(e: &Event, ctx: &mut EventCtx) {
+    let Event::Message(line) = e else { return };
+    if !ctx.cfg.chat_enabled { return; }
+    if ctx.cfg.chat_log { entities::logf(format!("{}: {}", line.kind.name(), chat_text(line, ctx.api))); }
+    CHAT.lock().unwrap_or_else(|e| e.into_inner()).push(ctx.now, line.clone());
+}
+
+/// The `[chat]` panel: the latest captured lines, each shown for
+/// `chat.hold_seconds` however long the game's own chat keeps it.
+unsafe fn draw_chat(hdc: HDC, cfg: &Config, api: &EngineApi, now: u32, layout: &mut Layout) {
+    if !cfg.chat_enabled || !cfg.chat_panel { return; }
+    let hold_ms = (cfg.chat_hold_seconds * 1000.0) as u32;
+    let history = CHAT.lock().unwrap_or_else(|e| e.into_inner());
+    let recent = history.recent(now, hold_ms, cfg.chat_lines);
+    if recent.is_empty() { return; }
+    let text = cfg.theme.palette().text;
+    let mut lines = vec![("CHAT".to_string(), text)];
+    for (line, age) in recent {
+        let mut color = match line.kind {
+            chat::Kind::Chat    => text,
+            chat::Kind::Server  => cfg.chat_server_color,
+            chat::Kind::Console => cfg.chat_console_color,
+        };
+        color[3] *= ((hold_ms - age) as f32 / CHAT_FADE_MS as f32).min(1.0);
+        lines.push((names::display(&chat_text(line, api)), color));
+    }
+    diag::draw_panel(hdc, layout, cfg.chat_place, CHAT_W, &lines);
+}
+
+/// The last round's table, for `stats.summary_seconds` after it ended.
+unsafe fn draw_round_summary(hdc: HDC, cfg: &Config, panels: &mut Panels) {
+    if !cfg.stats_enabled || cfg.stats_summary_seconds <= 0.0 { return; }
+    let Ok(stats) = STATS.lock() else { return };
+    let (round, ended) = stats.last_round_end();
+    if ended == 0 || time::now_ms().wrapping_sub(ended) as f32 > cfg.stats_summary_seconds * 1000.0 { return; }
+
+    let text = cfg.theme.palette().text;
+    let mut lines = vec![
+        (format!("ROUND {}", round), text),
+        (format!("{:<16} {:>3} {:>3} {:>3} {:>4} {:>6}", "player", "K", "D", "HS", "dmg", "alive"), text),
+    ];
+    for (name, t) in stats.summary() {
+        let name: String = name.chars().take(STATS_NAME_LEN).collect();
+        lines.push((format!("{:<16} {:>3} {:>3} {:>3} {:>4} {:>5.0}s",
+            name, t.kills, t.deaths, t.headshots, t.damage, t.alive_ms as f32 / 1000.0), text));
+    }
+    diag::draw_panel(hdc, &mut panels.layout, cfg.stats_place, STATS_W, &lines);
+}
+
+// ============================================================
+// Session Export
+// ============================================================
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+static SESSION_SAMPLE_MS: AtomicU32 = AtomicU32::new(0);    // Last time the slots were sampled
+
+/// "maps/de_dust2.bsp" -> "de_dust2", for file names.
+fn map_stem(map: &str) -> &str {
+    map.rsplit('/').next().unwrap_or(map).trim_end_matches(".bsp")
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Write `<prefix>_<unix time>.<ext>` next to the DLL, on a background thread.
+fn export_file(prefix: &str, ext: &str, text: String) {
+    let path = entities::dll_dir().join(format!("{}_{}.{}", prefix, unix_secs(), ext));
+    std::thread::spawn(move || match std::fs::write(&path, text) {
+        Ok(()) => entities::logf(format!("export: wrote {}", path.display())),
+        Err(e) => entities::logf(format!("export: {}: {}", path.display(), e)),
+    });
+}
+
+/// Write the session out when its map ends (a map change event).
+unsafe fn session_event(_: &Event, ctx: &mut EventCtx) {
+    let Ok(mut session) = SESSION.lock() else { return };
+    if let Some(s) = session.take().filter(|s| ctx.cfg.session_export && !s.players.is_empty()) {
+        let diagnostics: Vec<String> = diag::status_page().into_iter().skip(1).map(|(t, _)| t).collect();
+        export_file(&format!("esp_session_{}", map_stem(&s.map)), "json", s.to_json(unix_secs(), &diagnostics));
+    }
+}
+
+/// Add a chat / server message to the session.
+unsafe fn session_chat_event(e: &Event, ctx: &mut EventCtx) {
+    let Event::Message(line) = e else { return };
+    let Ok(mut session) = SESSION.lock() else { return };
+    if let Some(s) = session.as_mut() { s.say(unix_secs(), line.kind.name(), &chat_text(line, ctx.api)); }
+}
+
+/// Sample every player slot into the session (about once a second).
+unsafe fn update_session(cfg: &Config, api: &EngineApi, now: u32, map: Option<&String>) {
+    let Ok(mut session) = SESSION.lock() else { return };
+    if !cfg.session_export { *session = None; return; }
+    let Some(map) = map else { return };
+    if now.wrapping_sub(SESSION_SAMPLE_MS.load(Ordering::Relaxed)) < SESSION_SAMPLE_INTERVAL_MS { return; }
+    SESSION_SAMPLE_MS.store(now, Ordering::Relaxed);
+
+    let secs = unix_secs();
+    let s = session.get_or_insert_with(|| Session::new(map, secs));
+    for idx in 1..=api.max_clients() {
+        let Some(info) = api.player_info(idx) else { continue };
+        let Some(name) = info.name else { continue };
+        let team = player::extra_team(api, idx).unwrap_or(0);
+        s.see(&names::display(&name), info.steam_id, team, player::extra_frags(api, idx), secs);
+    }
+}
+
+// ============================================================
+// Breadcrumb Trails
+// ============================================================
+
+/// Draw each freshly drawn player's recent path along the floor, fading from
+/// transparent (oldest) to the team color (newest). Points that fail to project
+/// split the trail.
+unsafe fn draw_trails(
+    cfg: &Config, api: &EngineApi, proj_scale: f32, drawn_now: &[bool; 33], cache: &PlayerCache,
+    to_px: impl Fn(f32, f32) -> [f32; 2],
+) {
+    if !cfg.trails_enabled || cfg.trails_seconds <= 0.0 { return; }
+    let ttl_ms = (cfg.trails_seconds * 1000.0) as u32;
+    let now = time::now_ms();
+
+    for (i, &fresh) in drawn_now.iter().enumerate().skip(1) {
+        if !fresh { continue; }
+        let samples = entities::trail(i as i32, ttl_ms);
+        if samples.len() < 2 { continue; }
+
+        let Some(cached) = cache.get(i) else { continue };
+        let color = with_alpha(cached.color, TRAIL_ALPHA);
+        let mut run: Vec<([f32; 2], f32)> = Vec::with_capacity(samples.len());
+        for (p, t) in samples {
+            let floor = Vec3 { x: p.x, y: p.y, z: p.z - FLOOR_DROP };
+            let px = project(api, floor, proj_scale)
+                .filter(|(x, y)| x.is_finite() && y.is_finite())
+                .map(|(x, y)| to_px(x, y));
+            match px {
+                Some(px) => {
+                    let age = now.wrapping_sub(t) as f32;
+                    run.push((px, (1.0 - age / ttl_ms as f32).clamp(0.0, 1.0)));
+                }
+                None => {
+                    render::draw_fading_polyline(&run, color, cfg.world_line);
+                    run.clear();
+                }
+            }
+        }
+        render::draw_fading_polyline(&run, color, cfg.world_line);
+    }
+}
+
+// ============================================================
+// Sound Rings
+// ============================================================
+
+/// Draw an expanding, fading ring on the floor where each recent footstep or
+/// shot was heard (events.rs). Sounds without an origin are placed at their
+/// player; our own are skipped.
+unsafe fn draw_sound_rings(
+    cfg: &Config, api: &EngineApi, proj_scale: f32, now: u32, viewer: Vec3,
+    to_px: impl Fn(f32, f32) -> [f32; 2],
+) {
+    if !cfg.sound_rings_enabled || cfg.sound_rings_seconds <= 0.0 { return; }
+    let ttl_ms = (cfg.sound_rings_seconds * 1000.0) as u32;
+
+    for ev in events::recent(ttl_ms) {
+        let (shown, base) = match ev.kind {
+            SoundKind::Footstep => (cfg.sound_rings_footsteps, cfg.sound_rings_footstep_color),
+            SoundKind::Gunfire  => (cfg.sound_rings_gunfire, cfg.sound_rings_gunfire_color),
+        };
+        if !shown { continue; }
+        let Some((radius, alpha)) = sounds::ring(now.wrapping_sub(ev.time_ms), ttl_ms, cfg.sound_rings_radius)
+        else { continue };
+        let origin = match ev.origin {
+            Some(o) => o,
+            None if (1..=api.max_clients()).contains(&ev.ent) => {
+                let Some(p) = api.read_player(ev.ent) else { continue };
+                p.origin
+            }
+            None => continue,
+        };
+        if origin.distance(viewer) < RING_SELF_DIST { continue; }
+
+        let color = with_alpha(base, base[3] * alpha);
+        let z = origin.z - FLOOR_DROP;
+        let points: Vec<Option<[f32; 2]>> = (0..=RING_SEGMENTS)
+            .map(|k| {
+                let (s, c) = (k as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU).sin_cos();
+                let p = Vec3 { x: origin.x + c * radius, y: origin.y + s * radius, z };
+                project(api, p, proj_scale)
+                    .filter(|(x, y)| x.is_finite() && y.is_finite())
+                    .map(|(x, y)| to_px(x, y))
+            })
+            .collect();
+        for pair in points.windows(2) {
+            if let [Some(a), Some(b)] = pair {
+                render::draw_line(a[0], a[1], b[0], b[1], color, cfg.world_line);
+            }
+        }
+    }
+}
+
+// ============================================================
+// Shot Tracers
+// ============================================================
+
+/// A shot, fixed in the world when its gunfire sound was seen.
+struct Tracer {
+    from:    Vec3,       // Shooter's eyes
+    to:      Vec3,       // `tracers.length` along their aim
+    slot:    usize,      // Shooter (for the team color)
+    time_ms: u32,
+}
+
+/// Live tracers, and the last gunfire event turned into one (render thread only).
+static TRACERS: Mutex<Vec<Tracer>> = Mutex::new(Vec::new());
+static LAST_SHOT_SEQ: AtomicU32 = AtomicU32::new(0);
+
+/// Turn new gunfire events from other players into tracers, then draw every
+/// live one fading out, with a flash at the muzzle end while it's fresh.
+unsafe fn draw_tracers(
+    cfg: &Config, api: &EngineApi, proj_scale: f32, now: u32, cache: &PlayerCache,
+    to_px: impl Fn(f32, f32) -> [f32; 2],
+) {
+    if !cfg.tracers_enabled || cfg.tracers_seconds <= 0.0 { return; }
+    let ttl_ms = (cfg.tracers_seconds * 1000.0) as u32;
+    let Ok(mut tracers) = TRACERS.lock() else { return };
+
+    let last = LAST_SHOT_SEQ.load(Ordering::Relaxed);
+    for ev in events::recent(ttl_ms) {
+        if ev.seq <= last { continue; }
+        LAST_SHOT_SEQ.store(ev.seq, Ordering::Relaxed);
+        if ev.kind != SoundKind::Gunfire || !(1..=api.max_clients()).contains(&ev.ent) { continue; }
+        let Some(p) = api.read_player(ev.ent) else { continue };
+        if p.is_local { continue; }
+        let from = p.origin + Vec3::new(0.0, 0.0, player::eye_height(p.is_ducking));
+        let to = from + Angles::from(p.view_angles).forward() * cfg.tracers_length;
+        tracers.push(Tracer { from, to, slot: ev.ent as usize, time_ms: ev.time_ms });
+    }
+    tracers.retain(|t| now.wrapping_sub(t.time_ms) < ttl_ms);
+
+    let px = |p: Vec3| project(api, p, proj_scale)
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .map(|(x, y)| to_px(x, y));
+    for t in tracers.iter() {
+        let life = now.wrapping_sub(t.time_ms) as f32 / ttl_ms as f32;
+        let base = if cfg.tracers_team_color { cache.color(t.slot, cfg.tracers_color) } else { cfg.tracers_color };
+        let color = with_alpha(base, base[3].max(0.5) * (1.0 - life));
+
+        let points: Vec<Option<[f32; 2]>> = (0..=TRACER_PIECES)
+            .map(|k| {
+                let f = k as f32 / TRACER_PIECES as f32;
+                px(t.from.lerp(t.to, f))
+            })
+            .collect();
+        for pair in points.windows(2) {
+            if let [Some(a), Some(b)] = pair {
+                render::draw_line(a[0], a[1], b[0], b[1], color, cfg.world_line);
+            }
+        }
+        if let Some([x, y]) = points[0].filter(|_| life < FLASH_FRACTION) {
+            render::fill_rect(x - FLASH_PX, y - FLASH_PX, x + FLASH_PX, y + FLASH_PX, color);
+        }
+    }
+}
+
+// ============================================================
+// Projection Validation (zoom/FOV)
+// ============================================================
+// TriAPI's WorldToScreen uses the engine's cached projection. To confirm it
+// follows scope zoom, a probe point is placed a known angle off the view axis
+// and projected; the engine's answer is compared with the NDC expected for the
+// current FOV. If they disagree, the ratio rescales every projection this frame.
+
+/// Compare the engine's projection with the expected one for `fov_deg`.
+/// Returns the NDC scale factor to apply (1.0 = engine projection is correct).
+unsafe fn projection_scale(api: &EngineApi, eye: Vec3, fov_deg: f32) -> f32 {
+    let Some(angles) = api.view_angles() else { return 1.0 };
+    let half_fov = (fov_deg * 0.5).to_radians();
+    let probe = half_fov * 0.5;
+    let (fwd, right, _) = Angles::from(angles).vectors();
+    let p = eye + fwd * PROBE_DIST + right * (PROBE_DIST * probe.tan());
+    let Some((nx, _)) = api.world_to_screen(p) else { return 1.0 };
+    if !nx.is_finite() || nx.abs() < 1e-4 { return 1.0; }
+
+    let scale = (probe.tan() / half_fov.tan()) / nx;
+    if !(0.25..=4.0).contains(&scale) { return 1.0; } // Implausible — don't "correct" with garbage
+    if (scale - 1.0).abs() <= PROBE_TOLERANCE { 1.0 } else { scale }
+}
+
+/// The captured camera's view frustum, for culling whole boxes before
+/// projecting their corners (None until V_CalcRefdef has been seen).
+fn view_frustum() -> Option<Frustum> {
+    let rd = entities::refdef(CAMERA_MAX_AGE_MS)?;
+    let [_, _, w, h] = rd.viewport;
+    if w <= 0 || h <= 0 { return None; }
+    let vp = math::view_projection(rd.origin, rd.angles.into(), rd.fov, w as f32 / h as f32);
+    Some(Frustum::from_matrix(&vp))
+}
+
+/// Project a world point to NDC, applying the FOV correction factor.
+unsafe fn project(api: &EngineApi, p: Vec3, scale: f32) -> Option<(f32, f32)> {
+    api.world_to_screen(p).map(|(x, y)| (x * scale, y * scale))
+}
+
+/// What placing things at world points needs this frame: the engine's W2S
+/// (corrected by `proj_scale`), NDC to pixels, and the eye for distances.
+struct WorldView<'a, F: Fn(f32, f32) -> [f32; 2]> {
+    hdc:        HDC,
+    api:        &'a EngineApi,
+    proj_scale: f32,
+    to_px:      F,
+    eye:        Vec3,
+    screen:     [f32; 2],
+}
+
+impl<F: Fn(f32, f32) -> [f32; 2]> WorldView<'_, F> {
+    /// Pixel position of world point `p`; None behind the camera or too far
+    /// off screen for anything drawn there to show.
+    unsafe fn project(&self, p: Vec3) -> Option<[f32; 2]> {
+        let (nx, ny) = project(self.api, p, self.proj_scale)?;
+        if !nx.is_finite() || !ny.is_finite() { return None; }
+        let px = (self.to_px)(nx, ny);
+        view::near_screen(px, self.screen[0], self.screen[1]).then_some(px)
+    }
+
+    /// Draw `text` by world point `p` as `style` says, shrunk for its
+    /// distance from the eye. Nothing is drawn where `project` gives None.
+    unsafe fn draw_text_3d(&self, p: Vec3, text: &str, style: &Text3d) {
+        let Some(px) = self.project(p) else { return };
+        let (text, [x, y], scale) = style.layout(px, text, self.eye.distance(p) / UNITS_PER_METER);
+        render::draw_text_scaled(self.hdc, x, y, &text, style.color, scale);
+    }
+}
+
+/// Project a player's world box (hull center; half height and center offset,
+/// see view::world_box) to a pixel box and the feet point through the engine's W2S.
+unsafe fn project_box(
+    api: &EngineApi, origin: Vec3, world_box: (f32, f32), scale: f32,
+    to_px: impl Fn(f32, f32) -> [f32; 2],
+) -> Option<([f32; 4], [f32; 2])> {
+    view::project_world_box(origin, world_box, |p| project(api, p, scale), to_px)
+}
+
 // ============================================================
 // Coordinate Conversion
 // ============================================================
 
-/// Convert engine NDC (normalized device coordinates) to pixel coordinates.
-/// The engine's W2S returns NDC where (-1,-1) is bottom-left and (1,1) is top-right.
-/// We need pixel coords where (0,0) is top-left and (w,h) is bottom-right.
-fn ndc_to_px(ndc_x: f32, ndc_y: f32, screen_h: f32, vx: f32, vy: f32, vw: f32, vh: f32) -> [f32; 2] {
-    let x = vx + (ndc_x + 1.0) * 0.5 * vw;
-    let y_bottom_left = vy + (ndc_y + 1.0) * 0.5 * vh;
-    [x, screen_h - y_bottom_left] // Flip Y: bottom-left -> top-left origin
+/// Screen point where snap-lines start, in top-left pixel coordinates.
+fn snap_origin(cfg: &Config, screen_h: f32, vx: f32, vy: f32, vw: f32, vh: f32) -> [f32; 2] {
+    let x = vx + vw * 0.5;
+    match cfg.snapline_origin {
+        SnapOrigin::Bottom    => [x, screen_h - vy],
+        SnapOrigin::Crosshair => [x, screen_h - (vy + vh * 0.5)],
+        SnapOrigin::Top       => [x, screen_h - (vy + vh)],
+    }
+}
+
+// ============================================================
+// Panic Guards
+// ============================================================
+// Optional parts of the frame run under their own catch_unwind; the caught
+// panics are counted in panics.rs, which decides when a part is switched off.
+
+static PANICS: Mutex<PanicLog> = Mutex::new(PanicLog::new());
+
+/// Average time per frame of each guarded subsystem (F7 page).
+static SUBSYSTEM_TIMES: Mutex<Timings<Subsystem>> = Mutex::new(Timings::new());
+
+/// Run `f`, part of subsystem `sub`, timing it; a panic in it is recorded and
+/// the frame goes on.
+fn guarded(sub: Subsystem, f: impl FnOnce()) {
+    let sw = Stopwatch::start();
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        note_panic(sub, e.as_ref());
+    }
+    if let Ok(mut t) = SUBSYSTEM_TIMES.lock() { t.record(sub, sw.elapsed_us() as f32); }
+}
+
+/// The `n` guarded subsystems taking the most time per frame (average us).
+pub fn slowest_subsystems(n: usize) -> Vec<(&'static str, f32)> {
+    SUBSYSTEM_TIMES.lock().map(|t| t.slowest(n).into_iter().map(|(s, us)| (s.name(), us)).collect()).unwrap_or_default()
+}
+
+/// Record a panic caught in `sub` (also called by hook.rs for the whole
+/// frame). The first in a minute is logged; at `[panics] disable_after` the
+/// subsystem's config flag is cleared, or the overlay hidden for the frame itself.
+pub fn note_panic(sub: Subsystem, payload: &(dyn std::any::Any + Send)) {
+    let now = time::now_ms();
+    let limit = config::get().panic_limit;
+    let (off, count) = {
+        let mut log = PANICS.lock().unwrap_or_else(|e| e.into_inner());
+        let off = log.record(sub, now, limit);
+        (off, log.count(sub, now))
+    };
+    if count == 1 && !off {
+        entities::logf(format!("panic in {}: {}", sub.name(), panics::message(payload)));
+    }
+    if !off { return; }
+    entities::logf(format!("{} panicked {} times within a minute ({}); switched off until the config is reloaded",
+                           sub.name(), limit, panics::message(payload)));
+    if sub == Subsystem::Frame {
+        set_visible(false);
+        return;
+    }
+    config::update(|c| match sub {
+        Subsystem::Brushes      => c.debug_brushes = false,
+        Subsystem::Trails       => c.trails_enabled = false,
+        Subsystem::DeathMarkers => c.death_markers_enabled = false,
+        Subsystem::Grenades     => c.grenades_enabled = false,
+        Subsystem::SoundRings   => c.sound_rings_enabled = false,
+        Subsystem::Tracers      => c.tracers_enabled = false,
+        Subsystem::Script       => c.script_enabled = false,
+        Subsystem::Plugins      => c.plugins_enabled = false,
+        Subsystem::Minimap      => c.minimap_enabled = false,
+        Subsystem::Alerts       => c.alerts_enabled = false,
+        Subsystem::Threat       => c.threat_enabled = false,
+        Subsystem::Stats        => c.stats_enabled = false,
+        Subsystem::Session      => c.session_export = false,
+        Subsystem::Feed         => c.feed_enabled = false,
+        Subsystem::Netgraph     => c.netgraph_enabled = false,
+        Subsystem::Slots        => c.slots_enabled = false,
+        Subsystem::Interp       => c.interp_enabled = false,
+        Subsystem::HistoryGraph => c.history_graph_enabled = false,
+        Subsystem::Spectators   => c.spectators_enabled = false,
+        Subsystem::Chat         => c.chat_enabled = false,
+        Subsystem::Annotations  => c.annotations_enabled = false,
+        Subsystem::Markers      => c.markers_enabled = false,
+        Subsystem::Compass      => c.compass_enabled = false,
+        Subsystem::Summary      => c.stats_summary_seconds = 0.0,
+        Subsystem::Frame        => {}
+    });
+}
+
+/// Panics caught since load, in the last minute, and the subsystems switched
+/// off for it (diagnostics page).
+pub fn panic_report() -> (u32, usize, Vec<&'static str>) {
+    let log = PANICS.lock().unwrap_or_else(|e| e.into_inner());
+    (log.total(), log.last_minute(time::now_ms()), log.disabled().iter().map(|s| s.name()).collect())
 }
 
 // ============================================================
 // Main Frame Handler
 // ============================================================
 
-/// Called every frame from the wglSwapBuffers detour.
-/// Reads all player data and draws the ESP overlay.
-pub unsafe fn on_frame(hdc: HDC) {
+/// Overlay time against `[performance] budget_ms` (budget.rs).
+static BUDGET: Mutex<FrameBudget> = Mutex::new(FrameBudget::new());
+
+/// Game frame times and the overlay's share of them (pacing.rs).
+static PACING: Mutex<Pacing> = Mutex::new(Pacing::new());
+static PACING_WARNED: AtomicU32 = AtomicU32::new(0); // When the spike warning was last logged
+const PACING_WARN_MS: u32 = 60_000;                  // At most one warning per minute
+
+/// Called every frame from the present detour or the HUD_Redraw hook (`site`).
+/// Reads all player data and draws the ESP overlay, timing it for the budget
+/// and the pacing report.
+pub unsafe fn on_frame(hdc: HDC, site: DrawSite) {
+    if crashdump::faulted() { return; }
+    let frame_start = time::begin_frame();
+    if let Ok(mut p) = PACING.lock() { p.present(frame_start); }
+    let started = Stopwatch::started_at(frame_start);
+    let level = BUDGET.lock().map(|b| b.level()).unwrap_or(0);
+    capture::before_overlay();
+    draw_frame(hdc, site, level);
+    capture::after_overlay();
+    bundle::frame();
+    let spent_us = started.elapsed_us() as f32;
+    let budget_us = config::get().budget_ms * 1000.0;
+    if let Ok(mut b) = BUDGET.lock() {
+        b.record(spent_us, budget_us, time::frame_delta() * 1000.0);
+    }
+    if let Ok(mut p) = PACING.lock() { p.overlay(spent_us); }
+    warn_overlay_spikes();
+}
+
+/// Average overlay time per frame (microseconds) and the degradation level,
+/// for the diagnostics page.
+pub fn frame_cost() -> (f32, u8) {
+    BUDGET.lock().map(|b| (b.avg_us(), b.level())).unwrap_or((0.0, 0))
+}
+
+/// Game frame rate, jitter, fps cap and spikes over the last few seconds.
+pub fn frame_pacing() -> Option<pacing::Report> {
+    PACING.lock().ok().and_then(|p| p.report())
+}
+
+/// Log (rate-limited) when the overlay's own time lines up with the game's frame spikes.
+fn warn_overlay_spikes() {
+    let now = time::now_ms();
+    let last = PACING_WARNED.load(Ordering::Relaxed);
+    if last != 0 && now.wrapping_sub(last) < PACING_WARN_MS { return; }
+    let Some(r) = frame_pacing().filter(|r| r.overlay_suspect()) else { return };
+    PACING_WARNED.store(now, Ordering::Relaxed);
+    entities::logf(format!(
+        "frame pacing: the overlay was slow in {} of the last {} frame spikes (overlay avg {:.0} us, game {:.0} fps); \
+         [performance] budget_ms sheds elements under load", r.overlay_spikes, r.spikes, frame_cost().0, r.fps));
+}
+
+/// One overlay frame, with the elements degradation `level` sheds left out.
+unsafe fn draw_frame(hdc: HDC, site: DrawSite, level: u8) {
     // Check for F6 toggle
     poll_toggle();
 
@@ -110,23 +1496,54 @@ pub unsafe fn on_frame(hdc: HDC) {
         None => return,
     };
 
+    // Settings snapshot for this frame (after a profile switch, if any)
+    poll_profile_key(&config::get());
+    poll_opacity_key(&config::get());
+    let cfg = config::get();
+
+    // Keep screenshots clean: draw nothing at all, not even the status line
+    if cfg.hide_on_screenshot && screenshot_pending(time::now_ms()) {
+        return;
+    }
+    diag::count_frame();
+    count_fps(time::now_ms());
+
+    let snap_from = snap_origin(&cfg, screen_h, vx, vy, vw, vh);
+    let center = [vx + vw * 0.5, screen_h - (vy + vh * 0.5)]; // Crosshair position
+
     // Enter 2D drawing mode
-    render::begin_2d(screen_w, screen_h);
+    begin_overlay(site, screen_w, screen_h);
+    let mut panels = Panels::new(&cfg, screen_w, screen_h);
 
-    // Draw status indicator
+    // If ESP is toggled off, just show the status and return; on a toggle
+    // everything but the panels fades in or out first
     let vis = VISIBLE.load(Ordering::Relaxed);
-    let status = if vis { "[ESP ON]  F6=toggle" } else { "[ESP OFF] F6=toggle" };
-    render::draw_text(hdc, 6.0, 14.0, status, [1.0, 0.15, 0.15, 1.0]);
-
-    // If ESP is toggled off, just show the status and return
-    if !vis {
-        render::end_2d();
+    let alpha = TOGGLE_FADE.lock().unwrap_or_else(|e| e.into_inner())
+        .update(vis, time::now_ms(), cfg.toggle_fade_ms);
+    let pal = cfg.theme.palette();
+    if let Some(line) = warmup::line() { panels.status(line); }
+    if level > 0 {
+        panels.status(format!("DEGRADED: no {}  (over {:.2} ms)", budget::shed_names(level).join(", "), cfg.budget_ms));
+    }
+    if alpha <= 0.0 {
+        finish_frame(hdc, site, &cfg, &mut panels);
+        return;
+    }
+    render::set_alpha(alpha * cfg.opacity);
+
+    // Timestamp shared by every fade/TTL check this frame
+    let now = time::now_ms();
+
+    // Benchmark mode: synthetic players through the normal box path, no engine reads
+    if cfg.benchmark {
+        let mut boxes = benchmark_boxes(&cfg, now, screen_w, screen_h, vx, vy, vw, vh);
+        panels.status(format!("BENCHMARK  {} synthetic players  {:.2} ms/frame",
+                              bench::PLAYERS, frame_cost().0 / 1000.0));
+        draw_box_list(hdc, &cfg, &mut boxes, snap_from, center, level);
+        panels.players = Some(boxes.len() as u32);
+        finish_frame(hdc, site, &cfg, &mut panels);
         return;
     }
-
-    // Increment frame counter and sync it with the entity staleness tracker
-    let frame = FRAME_ID.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
-    crate::entities::set_frame_counter(frame);
 
     // Try to resolve the engine API (may fail if map isn't loaded yet)
     let api = match EngineApi::resolve() {
@@ -134,16 +1551,18 @@ pub unsafe fn on_frame(hdc: HDC) {
         None => {
             // Show a "waiting" message if the map hasn't loaded
             if !EngineApi::map_loaded() {
-                render::draw_text(hdc, 6.0, 28.0,
-                    "waiting for map load (start a game)...",
-                    [1.0, 0.15, 0.15, 1.0]);
+                panels.status("waiting for map load (start a game)...");
             }
             // Still draw cached boxes from when we last had data
-            let _ = draw_cached_boxes(hdc, screen_h, vx, vy, vw, frame, CACHE_TTL_FRAMES, 0.65);
-            render::end_2d();
+            let mut boxes = Vec::new();
+            let _ = queue_cached_boxes(&cfg, now, CACHE_TTL_MS, 0.65, &mut boxes);
+            draw_box_list(hdc, &cfg, &mut boxes, snap_from, center, level);
+            finish_frame(hdc, site, &cfg, &mut panels);
             return;
         }
     };
+    dispatch_events(&cfg, &api, now);
+    if let Some(line) = hook_lost_line(now) { panels.status(line); }
 
     // --- Read local player position ---
     let local_pos = match api.local_origin() {
@@ -163,74 +1582,226 @@ pub unsafe fn on_frame(hdc: HDC) {
     };
     let have_local = LAST_LOCAL_VALID.load(Ordering::Relaxed);
 
+    // --- Spectating / dead: where to measure from ---
+    // In first-person spectate the camera sits inside the watched player, so
+    // drawing them would produce a giant box, and distances are measured from
+    // their position. Dead or in free look / chase cam, the local entity stays
+    // behind, so the rendered camera is used instead.
+    let (obs_mode, obs_target) = api.observer().unwrap_or((0, 0));
+    let camera = entities::refdef(CAMERA_MAX_AGE_MS);
+    let dead = camera.is_some_and(|c| c.health <= 0 && !c.spectator);
+    let (spec_target, spec_player, local_pos, have_local) =
+        match player::view_reference(obs_mode, obs_target, dead, camera.is_some()) {
+            ViewRef::Target(t) => {
+                let p = api.read_player(t);
+                let pos = p.as_ref().map(|p| p.origin).unwrap_or(local_pos);
+                (t, p, pos, have_local)
+            }
+            ViewRef::Camera => {
+                let o = camera.map(|c| c.origin).unwrap_or_default();
+                (0, None, o - Vec3::new(0.0, 0.0, EYE_HEIGHT), true)
+            }
+            ViewRef::Local => (0, None, local_pos, have_local),
+        };
+
+    // --- Whose team counts as "friendly" (enemy/friend colors) ---
+    let view_team = match &spec_player {
+        Some(p) => p.team,
+        None    => api.local_team().unwrap_or(0),
+    };
+    VIEW_TEAM.store(view_team, Ordering::Relaxed);
+
+    // --- Demo playback ---
+    panels.map = api.level_name();
+    let demo = api.demo_playback();
+    if demo {
+        panels.status("DEMO PLAYBACK");
+    }
+
+    // --- FOV and projection check ---
+    let fov = api.fov();
+    let proj_scale = if cfg.fov_correction && have_local {
+        let eye = local_pos + Vec3::new(0.0, 0.0, EYE_HEIGHT);
+        projection_scale(&api, eye, fov)
+    } else { 1.0 };
+    if cfg.fov_indicator {
+        let state = if proj_scale == 1.0 {
+            "W2S ok".to_string()
+        } else {
+            format!("W2S corrected x{:.2}", proj_scale)
+        };
+        panels.status(format!("FOV {:.0}  {}", fov, state));
+    }
+
+    // --- Crosshair elements ---
+    let mut sight = DrawList::new();
+    crosshair(&cfg, center, &mut sight);
+    fov_circle(&cfg, center, vw, fov, &mut sight);
+    render::draw_list(hdc, &sight);
+
     // --- Draw ESP for each player ---
     let mut drawn = 0u32;
     let mut drawn_now = [false; 33]; // Track which slots were drawn fresh this frame
+    let mut cache = PLAYER_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut boxes: Vec<BoxDraw> = Vec::new();
+    #[cfg(feature = "radar")]
+    let mut blips: Vec<Blip> = Vec::new();
+    let mut enemies: Vec<(i32, Vec3)> = Vec::new();
+    let mut alive: Vec<String> = Vec::new();   // For the statistics
+    let mut damaged: Vec<String> = Vec::new();
+    #[cfg(feature = "radar")]
+    let mut feed_players: Vec<FeedPlayer> = Vec::new();
+    #[cfg(feature = "scripting")]
+    let mut script_players: Vec<ScriptPlayer> = Vec::new();
+
+    // Player data from the engine (invalid/dead/spectator slots are left out),
+    // read this frame or reused from the last read; a recorded snapshot while
+    // frozen, which is only drawn: nothing in it is counted, published or cached
+    let (players, frozen) = scrub_players(&cfg, read_players(&cfg, &api, now), now, &mut panels);
+    let plugins = !frozen && subsystems::running(subsystems::PLUGINS) && plugin::active(&cfg);
+    #[cfg(feature = "radar")]
+    let feed = !frozen && cfg.feed_enabled && subsystems::running(subsystems::FEED);
+    let mut plugin_players: Vec<EspPlayer> = Vec::new();
+    // The rendered camera's yaw follows chase cams and free look; GetViewAngles doesn't
+    let yaw = camera.map(|c| c.angles.y).or_else(|| api.view_angles().map(|a| a.y)).unwrap_or(90.0);
+    // Where the crosshair points from, for the focus highlight: the rendered camera, else the local eye
+    let aim = camera.map(|c| (c.origin, Angles::from(c.angles))).or_else(|| api.view_angles()
+        .filter(|_| have_local).map(|a| (local_pos + Vec3::new(0.0, 0.0, EYE_HEIGHT), Angles::from(a))));
+    let mut focus: Option<(usize, f32)> = None; // Index in `boxes`, degrees off the crosshair
+    let mut aimed: Option<(i32, f32)> = None;   // Slot closest to the crosshair at any angle, degrees
+    let newest = api.latest_message().unwrap_or(0);
+    let mut scratch: PlayerCacheEntry; // A frozen frame's copy of a cache entry
+
+    for (idx, player) in players {
+        if !frozen && !player.is_dead { alive.push(names::display(&player.name)); }
+        #[cfg(feature = "scripting")]
+        if cfg.script_enabled && !frozen && !player.is_local {
+            script_players.push(ScriptPlayer {
+                index: idx,
+                name: names::display(&player.name),
+                team: player.team,
+                enemy: theme::is_friendly(player.team, view_team) == Some(false),
+                alive: !player.is_dead,
+                health: player.health,
+                origin: player.origin,
+                yaw: player.view_angles.y,
+                dist: local_pos.distance(player.origin) / UNITS_PER_METER,
+                behind: crate::alerts::is_behind(local_pos, yaw, player.origin, cfg.alerts_behind_cone),
+                screen: project(&api, player.origin, proj_scale)
+                    .filter(|(x, y)| x.is_finite() && y.is_finite())
+                    .map(|(x, y)| view::ndc_to_px(x, y, screen_h, vx, vy, vw, vh)),
+            });
+        }
+        if plugins && !player.is_local {
+            let screen = project(&api, player.origin, proj_scale)
+                .filter(|(x, y)| x.is_finite() && y.is_finite())
+                .map(|(x, y)| view::ndc_to_px(x, y, screen_h, vx, vy, vw, vh));
+            plugin_players.push(EspPlayer::new(
+                idx, &names::display(&player.name), player.team,
+                theme::is_friendly(player.team, view_team) == Some(false), !player.is_dead, player.health,
+                player.origin, player.view_angles.y, local_pos.distance(player.origin) / UNITS_PER_METER, screen,
+            ));
+        }
+        #[cfg(feature = "radar")]
+        if feed && !player.is_local {
+            feed_players.push(FeedPlayer {
+                index: idx,
+                name: names::display(&player.name),
+                team: player.team,
+                origin: player.origin,
+                yaw: player.view_angles.y,
+                health: player.health,
+                alive: !player.is_dead,
+            });
+        }
 
-    for idx in 1..=api.max_clients() {
-        // Read player data from the engine (returns None for invalid/dead/spectator players)
-        let Some(player) = api.read_player(idx) else { continue };
-
-        // Skip the local player (don't draw ESP on yourself)
-        if player.is_local || (have_local && local_pos.distance(player.origin) < 4.0) {
+        // Skip the local player (don't draw ESP on yourself). During demo playback
+        // the camera isn't necessarily at the recorder, so trust only `thisplayer`.
+        let near_self = !demo && have_local && local_pos.distance(player.origin) < 4.0;
+        if player.is_local || near_self {
             continue;
         }
 
-        // --- Calculate bounding box in world space ---
-        let mut half_h = (player.maxs_z * 0.5).max(8.0);
-        let mut z_offset = 0.0f32;
-        if player.is_ducking {
-            half_h = half_h.max(26.0);
-            z_offset = 6.0; // Adjust center when ducking
+        // --- Damage: a health drop since the last read starts a pulse ---
+        let i = idx as usize;
+        if let Some(h) = player.health.filter(|_| !frozen) {
+            if cache.entry(i).health.update(player.steam_id, &player.name, h, player.is_dead, now) {
+                damaged.push(names::display(&player.name));
+            }
         }
-        let feet = Vec3 {
-            x: player.origin.x, y: player.origin.y,
-            z: player.origin.z - half_h + z_offset,
-        };
-        let head = Vec3 {
-            x: player.origin.x, y: player.origin.y,
-            z: player.origin.z + half_h + z_offset,
-        };
 
-        // --- Project feet and head to screen coordinates ---
-        let Some((fx, fy)) = api.world_to_screen(feet) else { continue };
-        let Some((hx, hy)) = api.world_to_screen(head) else { continue };
-        if !fx.is_finite() || !fy.is_finite() || !hx.is_finite() || !hy.is_finite() { continue; }
+        // --- Weapon state: reloading or switching ---
+        let action = if cfg.weapon_state && !frozen {
+            let seen = Seen { weapon: player.weapon_model, model: player.model_index, sequence: player.sequence };
+            ACTIONS.lock().ok().and_then(|mut t| t.update(idx, seen, now, cfg.weapon_switch_ms,
+                || anim::sequence_name(&api, api.model(player.model_index), player.sequence)))
+        } else { None };
+
+        // --- Team color ---
+        let team_name = usermsg::team_name(idx as usize, player.team);
+        let mut color = box_color(&cfg, player.steam_id, player.team, team_name.as_deref())
+            .unwrap_or_else(|| pal.player_color(cfg.color_mode, player.team, view_team));
+        if let (HealthColor::Box, Some(h)) = (cfg.health_color, player.health) {
+            color = pal.health_color(h);
+        }
+        let hit = cache.get(i).map_or(0, |e| e.health.hit_ms);
+        if hit != 0 {
+            let pulse = view::damage_pulse(now.wrapping_sub(hit), cfg.health_pulse_ms);
+            color = theme::blend(color, DAMAGE_COLOR, pulse);
+        }
+        if player.is_dead { color[3] = DEAD_ALPHA; } // Demo playback only
+
+        // Players missing from the latest update are outside our PVS: the engine
+        // knows where they were, but isn't rendering them
+        let out_of_pvs = !player::in_pvs(player.message, newest);
+        if out_of_pvs && cfg.pvs_tint {
+            color = theme::blend(color, cfg.pvs_color, cfg.pvs_strength);
+        }
 
-        let feet_px = ndc_to_px(fx, fy, screen_h, vx, vy, vw, vh);
-        let head_px = ndc_to_px(hx, hy, screen_h, vx, vy, vw, vh);
+        // Every player gets a minimap blip, on screen or not; enemies feed the alerts
+        if idx != spec_target {
+            #[cfg(feature = "radar")]
+            blips.push(Blip { pos: player.origin, color });
+            if !player.is_dead && theme::is_friendly(player.team, view_team) == Some(false) {
+                enemies.push((idx, player.origin));
+            }
+        }
 
-        // Skip if way off-screen
-        if feet_px[0] < -PIXEL_MARGIN || feet_px[0] > screen_w + PIXEL_MARGIN
-        || feet_px[1] < -PIXEL_MARGIN || feet_px[1] > screen_h + PIXEL_MARGIN {
+        // Skip the player we're spectating in first person (unless configured to box them)
+        if idx == spec_target && !cfg.spectate_box_target {
             continue;
         }
 
-        // --- Calculate 2D bounding box ---
-        let y0 = head_px[1].min(feet_px[1]);  // Top of box
-        let y1 = head_px[1].max(feet_px[1]);  // Bottom of box
-        let box_h = (y1 - y0).max(4.0);
-        let box_w = box_h * BOX_ASPECT;        // Width proportional to height
-        let cx = (feet_px[0] + head_px[0]) * 0.5; // Center X
-        let x0 = cx - box_w * 0.5;
-        let x1 = cx + box_w * 0.5;
-
-        // --- Team color ---
-        let color: [f32; 4] = match player.team {
-            1 => [0.95, 0.18, 0.18, 1.0], // Terrorists = red
-            2 => [0.18, 0.50, 0.95, 1.0], // Counter-Terrorists = blue
-            _ => [0.10, 0.95, 0.10, 1.0], // Unknown = green
+        // --- Project the world box to a 2D bounding box ---
+        // (while frozen, on a copy of the entry: the cache stays as it was live)
+        let entry = if frozen {
+            scratch = cache.get(i).cloned().unwrap_or_default();
+            &mut scratch
+        } else {
+            cache.entry(i)
+        };
+        let world_box = entry.stance.update(player.maxs_z, player.is_ducking, now, cfg.box_stance_ms);
+        let projected = project_box(&api, player.origin, world_box, proj_scale,
+            |nx, ny| view::ndc_to_px(nx, ny, screen_h, vx, vy, vw, vh));
+        let (rect, feet_px) = match projected {
+            Some((rect, feet_px)) => {
+                // Skip if way off-screen
+                if !view::near_screen(feet_px, screen_w, screen_h) { continue; }
+                entry.last_projected = now;
+                (rect, feet_px)
+            }
+            // The read was fine but the point was clipped for a frame or two:
+            // hold last frame's box rather than let it blink
+            None if view::hold_projection(now.wrapping_sub(entry.last_projected), entry.last_projected != 0) => {
+                (entry.rect, entry.feet)
+            }
+            None => continue,
         };
 
-        // --- Draw the ESP elements ---
-        render::draw_rect_outline(x0, y0, x1, y1);  // Dark shadow outline
-        render::draw_box_corners(x0, y0, x1, y1, color); // Colored corner brackets
-
-        // Snap-line from bottom-center of screen to the player's feet
-        render::draw_line(
-            vx + vw * 0.5, screen_h - vy,
-            feet_px[0], feet_px[1],
-            [1.0, 1.0, 0.15, 0.55],
+        // --- Smooth toward the new position (last frame's box is the filter state) ---
+        let ([x0, y0, x1, y1], feet_px) = view::smooth_box(
+            entry.rect, entry.feet, rect, feet_px,
+            now.wrapping_sub(entry.last_seen), entry.last_seen != 0,
         );
 
         // Distance in meters
@@ -238,123 +1809,591 @@ pub unsafe fn on_frame(hdc: HDC) {
             local_pos.distance(player.origin) / UNITS_PER_METER
         } else { 0.0 };
 
-        // Player name centered above the box
-        let name_x = cx - (player.name.len() as f32 * 3.5);
-        render::draw_text(hdc, name_x, y0 - 2.0, &player.name, [1.0, 1.0, 1.0, 1.0]);
-
-        // Distance and weapon label below the box
-        let mut info = format!("{:.1}m", dist);
-        if !player.weapon.is_empty() {
-            info.push_str(&format!("  [{}]", player.weapon));
+        // Labels above (name with equipment tags) and below (distance, weapon, state) the box
+        let mut tags: Vec<&str> = Vec::new();
+        if cfg.tag_kit && player.has_kit == Some(true) { tags.push("[KIT]"); }
+        if cfg.tag_helmet && player.has_helmet == Some(true) { tags.push("[H]"); }
+        let fields = LabelFields {
+            idx,
+            name:   names::display(&player.name),
+            tags:   tags.join(" "),
+            dist,
+            weapon: &player.weapon,
+            health: player.health,
+            team:   team_name.clone().unwrap_or_else(|| player.team.to_string()),
+            dead:   player.is_dead,
+            action: action.map(|a| a.tag()),
+            no_pvs: out_of_pvs && cfg.pvs_tint,
+        };
+        let name = layout::expand_words(&cfg.label_above, |f| fields.field(f));
+        let info = layout::expand_words(&cfg.label_below, |f| fields.field(f));
+
+        // Closest to the crosshair so far (within [focus] angle)?
+        let off = aim.and_then(|(eye, angles)| angles.off_axis(eye, player.origin)).filter(|_| !player.is_dead);
+        if let Some(off) = off {
+            if cfg.focus_enabled && off <= cfg.focus_angle && !focus.is_some_and(|(_, best)| best <= off) {
+                focus = Some((boxes.len(), off));
+            }
+            if !aimed.is_some_and(|(_, best)| best <= off) { aimed = Some((idx, off)); }
         }
-        render::draw_text(hdc, x0, y1 + 12.0, &info, [1.0, 1.0, 1.0, 1.0]);
+
+        // Eye point for the head-line, projected as is (not smoothed with the box)
+        let head = if cfg.snapline_head_line {
+            let eye = player.origin + Vec3::new(0.0, 0.0, player::eye_height(player.is_ducking));
+            project(&api, eye, proj_scale).map(|(nx, ny)| view::ndc_to_px(nx, ny, screen_h, vx, vy, vw, vh))
+        } else { None };
+
+        // Queue the box; it's drawn after sorting by distance
+        boxes.push(BoxDraw {
+            rect: [x0, y0, x1, y1],
+            feet: feet_px,
+            dist,
+            color,
+            team: player.team,
+            snap_alpha: 1.0,
+            name: (!name.is_empty()).then(|| name.clone()),
+            info,
+            text_alpha: if player.is_dead { DEAD_ALPHA } else { 1.0 },
+            health: player.health,
+            focus: false,
+            head,
+            line: if out_of_pvs { cfg.pvs_lines } else { Dash::Solid },
+        });
 
         drawn += 1;
 
         // --- Cache this frame's data for fade-out ---
-        let i = idx as usize;
         drawn_now[i] = true;
-        LAST_BOX[i] = [x0, y0, x1, y1];
-        LAST_FEET[i] = [feet_px[0], feet_px[1]];
-        LAST_DIST[i] = dist;
-        LAST_COLOR[i] = color;
-        LAST_SEEN[i] = frame;
+        *entry = PlayerCacheEntry {
+            rect: [x0, y0, x1, y1],
+            feet: feet_px,
+            dist,
+            color,
+            team: player.team,
+            steam_id: player.steam_id,
+            team_name,
+            origin: player.origin,
+            maxs_z: player.maxs_z,
+            ducking: player.is_ducking,
+            name,
+            weapon: player.weapon.clone(),
+            last_seen: now,
+            last_projected: entry.last_projected,
+            stance: entry.stance,
+            health: std::mem::take(&mut entry.health),
+        };
     }
 
+    if let Some((i, _)) = focus { boxes[i].focus = true; }
+
     // --- Draw cached/fading boxes for players not seen this frame ---
     for idx in 1..=api.max_clients() {
         let i = idx as usize;
         if drawn_now[i] { continue; } // Already drawn fresh above
-
-        let seen = LAST_SEEN[i];
-        if seen == 0 { continue; } // Never seen
+        if budget::sheds(level, Shed::CachedBoxes) { break; }
 
         // Distance-dependent TTL: closer players stay cached longer
-        let dist = LAST_DIST[i];
-        let ttl = if dist > 0.0 {
-            if dist < 10.0 { 300u32 } else if dist < 30.0 { 150u32 } else { CACHE_TTL_FRAMES }
-        } else { CACHE_TTL_FRAMES };
-
-        if frame.wrapping_sub(seen) > ttl { continue; } // Expired
-
-        let [x0, y0, x1, y1] = LAST_BOX[i];
-        let [fx, fy] = LAST_FEET[i];
-        if x0 == 0.0 && y0 == 0.0 && x1 == 0.0 && y1 == 0.0 { continue; }
-
-        // Fade out over ~12 frames using ease-out curve
-        let mut color = LAST_COLOR[i];
-        let base_alpha = if dist > 0.0 && dist < 10.0 { 0.95 } else { 0.60 };
-        let age = frame.wrapping_sub(seen) as f32;
-        let fade_t = (age / 12.0).clamp(0.0, 1.0);
-        let ease = 1.0_f32 - (1.0 - fade_t).powf(2.0);
-        let final_alpha = (base_alpha * (1.0 - ease)).max(0.02);
-        if final_alpha <= 0.02 { continue; }
-        color[3] = final_alpha;
-
-        // Draw the cached box with faded alpha
-        render::draw_rect_outline(x0, y0, x1, y1);
-        render::draw_box_corners(x0, y0, x1, y1, color);
-        render::draw_line(vx + vw * 0.5, screen_h - vy, fx, fy, [1.0, 0.15, 0.15, final_alpha * 0.6]);
-        let label = format!("{:.1}m", LAST_DIST[i]);
-        render::draw_text(hdc, x0, y1 + 12.0, &label, [1.0, 1.0, 1.0, final_alpha]);
+        let Some(e) = cache.get(i) else { continue };
+        let Some(age) = e.cached_age(now, CACHE_TTL_MS) else { continue };
+
+        // Fade out over FADE_MS using ease-out curve
+        let base_alpha = if e.dist > 0.0 && e.dist < 10.0 { 0.95 } else { 0.60 };
+        let Some(mut b) = cached_box(&cfg, e, age, base_alpha) else { continue };
+
+        // Reproject the last known world box so it stays put as the camera moves
+        let Some((rect, feet_px)) = project_box(&api, e.origin, view::world_box(e.maxs_z, e.ducking), proj_scale,
+            |nx, ny| view::ndc_to_px(nx, ny, screen_h, vx, vy, vw, vh)) else { continue };
+        if !view::near_screen(feet_px, screen_w, screen_h) { continue; }
+        (b.rect, b.feet) = (rect, feet_px);
+        boxes.push(b);
         drawn += 1;
     }
 
+    // Brush outlines, trails, death spots, grenades, sound rings and tracers go under the boxes
+    let to_px = |nx: f32, ny: f32| view::ndc_to_px(nx, ny, screen_h, vx, vy, vw, vh);
+    guarded(Subsystem::Brushes, || draw_brush_entities(hdc, &cfg, &api, proj_scale, to_px));
+    if !budget::sheds(level, Shed::Trails) {
+        guarded(Subsystem::Trails, || draw_trails(&cfg, &api, proj_scale, &drawn_now, &cache, to_px));
+    }
+    let world = WorldView {
+        hdc, api: &api, proj_scale, to_px,
+        eye: local_pos + Vec3::new(0.0, 0.0, EYE_HEIGHT), screen: [screen_w, screen_h],
+    };
+    guarded(Subsystem::DeathMarkers, || draw_death_markers(&cfg, &world, now));
+    guarded(Subsystem::Grenades, || draw_grenades(&cfg, &world, now));
+    guarded(Subsystem::Annotations, || draw_annotations(&cfg, &world, local_pos, have_local));
+    guarded(Subsystem::SoundRings, || draw_sound_rings(&cfg, &api, proj_scale, now, local_pos, to_px));
+    guarded(Subsystem::Tracers, || draw_tracers(&cfg, &api, proj_scale, now, &cache, to_px));
+
+    // The fade doesn't reach the overlay; dim the boxes with it only if asked to
+    let blind = blind_state(&api);
+    if let Some(b) = blind.filter(|_| !cfg.flash_full_alpha) {
+        for bx in &mut boxes {
+            bx.color[3] *= 1.0 - b.coverage;
+            bx.snap_alpha *= 1.0 - b.coverage;
+            bx.text_alpha *= 1.0 - b.coverage;
+        }
+    }
+
+    // Draw every queued box, farthest first
+    draw_box_list(hdc, &cfg, &mut boxes, snap_from, center, level);
+
+    // Diagnostics of one player: `slot` in the config, or 0 = the one closest to the crosshair
+    let selected = |slot: i32| if slot > 0 { Some(slot) } else { aimed.map(|(idx, _)| idx) };
+    guarded(Subsystem::Interp, || draw_interp(hdc, &cfg, &api, selected(cfg.interp_slot), proj_scale, to_px,
+                                              &mut panels.layout));
+    guarded(Subsystem::HistoryGraph, || draw_history_graph(hdc, &cfg, &api, selected(cfg.history_graph_slot),
+                                                           &mut panels.layout));
+
+    // User script elements over the boxes
+    #[cfg(feature = "scripting")]
+    {
+        let frame = ScriptFrame {
+            time: now,
+            map: panels.map.as_deref(),
+            screen_w,
+            screen_h,
+            viewer: have_local.then_some((local_pos, yaw)),
+            players: script_players,
+        };
+        let mut error = None;
+        guarded(Subsystem::Script, || error = script::frame(hdc, &cfg, now, &frame));
+        if let Some(e) = error {
+            panels.status(format!("script: {}", e));
+        }
+    }
+    if let Some(b) = &blind { draw_flash_indicator(hdc, &cfg, b, screen_w); }
+
+    // Registered subsystems: plugin elements, the minimap, the live feed (subsystems.rs)
+    let map = panels.map.clone();
+    let mut ctx = FrameCtx {
+        hdc, cfg: &cfg, api: &api, now,
+        map: map.as_deref(),
+        screen: [screen_w, screen_h],
+        eye: local_pos, yaw, has_viewer: have_local,
+        layout: &mut panels.layout,
+        status: Vec::new(),
+        #[cfg(feature = "radar")]
+        frozen,
+        #[cfg(feature = "radar")]
+        blips: &blips,
+        #[cfg(feature = "radar")]
+        feed_players: &feed_players,
+        plugin_players: &plugin_players,
+    };
+    for (sub, f) in subsystems::frames() {
+        guarded(sub, || f(&mut ctx));
+    }
+    for line in ctx.status { panels.status(line); }
+
+    if have_local {
+        if !frozen { guarded(Subsystem::Alerts, || play_alerts(&cfg, &api, local_pos, yaw, now, &enemies)); }
+        guarded(Subsystem::Threat, || draw_threat(hdc, &cfg, local_pos, yaw, fov, &enemies, &mut panels.layout));
+        guarded(Subsystem::Compass, || draw_compass(hdc, &cfg, local_pos, yaw, &enemies, screen_w));
+    }
+    guarded(Subsystem::Slots, || draw_slots(hdc, &cfg, &api, &mut panels.layout));
+    guarded(Subsystem::Spectators, || draw_spectators(hdc, &cfg, &api, &mut panels.layout));
+    guarded(Subsystem::Chat, || draw_chat(hdc, &cfg, &api, now, &mut panels.layout));
+    if !frozen { guarded(Subsystem::Stats, || update_stats(&cfg, now, &alive, &damaged)); }
+    guarded(Subsystem::Session, || update_session(&cfg, &api, now, panels.map.as_ref()));
+    guarded(Subsystem::Markers, || update_markers(&cfg, now));
+
     // Show a hint if no players were found
+    panels.players = Some(drawn);
     if drawn == 0 {
-        render::draw_text(hdc, 6.0, 84.0, "no players (in-game?)", [1.0, 0.15, 0.15, 1.0]);
+        let why = slots::missing(&entities::read_outcomes());
+        panels.status(if why.is_empty() { "no players (in-game?)".to_string() } else { format!("no players: {}", why) });
+    }
+
+    finish_frame(hdc, site, &cfg, &mut panels);
+}
+
+// ============================================================
+// Player Reads (throttled, see readrate.rs)
+// ============================================================
+
+/// The last read of every player slot, and each player's motion across reads.
+struct PlayerReads {
+    throttle: Throttle,
+    players:  Vec<(i32, PlayerData)>,
+    tracks:   [Track; 33],
+}
+
+static READS: Mutex<PlayerReads> = Mutex::new(PlayerReads {
+    throttle: Throttle::new(),
+    players:  Vec::new(),
+    tracks:   [Track::new(); 33],
+});
+
+/// This frame's players: read from the engine when `[performance]` says so,
+/// otherwise the last read with positions extrapolated to `now`.
+unsafe fn read_players(cfg: &Config, api: &EngineApi, now: u32) -> Vec<(i32, PlayerData)> {
+    let Ok(mut reads) = READS.lock() else { return Vec::new() };
+    let reads = &mut *reads;
+    let rate = ReadRate { every_frames: cfg.read_every, hz: cfg.read_hz };
+    if reads.throttle.due(rate, now) {
+        reads.players = (1..=api.max_clients())
+            .filter_map(|idx| api.read_player(idx).map(|p| (idx, p)))
+            .collect();
+        let mut seen = [false; 33];
+        for (idx, p) in &reads.players {
+            let i = *idx as usize;
+            if i < seen.len() {
+                reads.tracks[i].push(p.origin, now);
+                seen[i] = true;
+            }
+        }
+        for (track, _) in reads.tracks.iter_mut().zip(seen).filter(|(_, s)| !s) { track.clear(); }
+    }
+
+    let mut players = reads.players.clone();
+    if cfg.read_extrapolate {
+        for (idx, p) in &mut players {
+            if let Some(pos) = reads.tracks.get(*idx as usize).and_then(|t| t.predict(now)) {
+                p.origin = pos;
+            }
+        }
+    }
+    players
+}
+
+// ============================================================
+// Interpolation Diagnostics
+// ============================================================
+
+const INTERP_MARK_PX: f32 = 4.0;       // Half size of an origin marker
+const INTERP_W: f32 = 300.0;
+
+/// Marker colors: interpolated origin, curstate origin, newest history sample.
+const INTERP_COLORS: [[f32; 4]; 3] = [[0.2, 1.0, 0.2, 1.0], [1.0, 0.85, 0.1, 1.0], [1.0, 0.25, 0.9, 1.0]];
+
+/// `[interp]`: where the selected player's entity puts them by each source
+/// (player::origin_sources) as a circle, a square and a cross, and a panel
+/// with how far the raw ones are from the interpolated one. The three agree
+/// on a player standing still; interpolation or extrapolation trouble shows
+/// as the circle trailing or overshooting the other two.
+unsafe fn draw_interp(
+    hdc: HDC, cfg: &Config, api: &EngineApi, slot: Option<i32>, proj_scale: f32,
+    to_px: impl Fn(f32, f32) -> [f32; 2], layout: &mut Layout,
+) {
+    if !cfg.interp_enabled { return; }
+    let Some(slot) = slot else { return };
+    let Some(o) = player::origin_sources(api, slot) else { return };
+
+    let r = INTERP_MARK_PX;
+    let style = cfg.world_line;
+    let mut list = DrawList::new();
+    for (i, pos) in [o.interpolated, o.state, o.history].into_iter().enumerate() {
+        let Some((nx, ny)) = project(api, pos, proj_scale) else { continue };
+        if !nx.is_finite() || !ny.is_finite() { continue; }
+        let [x, y] = to_px(nx, ny);
+        let color = INTERP_COLORS[i];
+        match i {
+            0 => list.push(Cmd::Circle { center: [x, y], radius: r, segments: 12, color, style }),
+            1 => list.push(Cmd::Rect { rect: [x - r, y - r, x + r, y + r], shade: Shade::Solid(color), style }),
+            _ => {
+                list.line([x - r, y - r], [x + r, y + r], color, style);
+                list.line([x - r, y + r], [x + r, y - r], color, style);
+            }
+        }
+    }
+    render::draw_list(hdc, &list);
+
+    let text = cfg.theme.palette().text;
+    let lines = [
+        (format!("INTERP  player {}", slot), text),
+        (format!("interpolated (circle) {:.0} {:.0} {:.0}", o.interpolated.x, o.interpolated.y, o.interpolated.z),
+         INTERP_COLORS[0]),
+        (format!("state (square)        {:.1} u off", o.state.distance(o.interpolated)), INTERP_COLORS[1]),
+        (format!("history (cross)       {:.1} u off", o.history.distance(o.interpolated)), INTERP_COLORS[2]),
+    ];
+    diag::draw_panel(hdc, layout, cfg.interp_place, INTERP_W, &lines);
+}
+
+// ============================================================
+// Position History Graph (see posgraph.rs)
+// ============================================================
+
+const HISTORY_GRAPH_W: f32 = 320.0;
+const HISTORY_GRAPH_ROWS: usize = 4;
+
+/// `[history_graph]`: the selected player's speed (top) and height (bottom)
+/// over their position history.
+unsafe fn draw_history_graph(hdc: HDC, cfg: &Config, api: &EngineApi, slot: Option<i32>, layout: &mut Layout) {
+    if !cfg.history_graph_enabled { return; }
+    let Some(slot) = slot else { return };
+    let points = posgraph::points(&player::position_history(api, slot));
+    let pal = cfg.theme.palette();
+    let colors = [pal.health[2], pal.health[1]];
+    let lines = [
+        (format!("HISTORY  player {}", slot), pal.text),
+        (posgraph::summary(&points), pal.text),
+        ("speed".to_string(), colors[0]),
+        ("z".to_string(), colors[1]),
+    ];
+    let rows = if points.len() >= 2 { HISTORY_GRAPH_ROWS } else { 0 };
+    let Some(area) = diag::draw_panel_with_area(hdc, layout, cfg.history_graph_place, HISTORY_GRAPH_W, &lines, rows)
+        else { return };
+    if rows > 0 { render::draw_list(hdc, &posgraph::plot(&points, area, colors)); }
+}
+
+// ============================================================
+// History Scrubbing (see scrub.rs)
+// ============================================================
+
+static SCRUB: Mutex<Scrubber<Vec<(i32, PlayerData)>>> = Mutex::new(Scrubber::new());
+
+/// This frame's players, or while frozen the snapshot being looked at (and
+/// true). Reads the `[scrub]` keys and records the live players.
+fn scrub_players(cfg: &Config, live: Vec<(i32, PlayerData)>, now: u32, panels: &mut Panels) -> (Vec<(i32, PlayerData)>, bool) {
+    if cfg.scrub_key == 0 { return (live, false); }
+    let Ok(mut scrub) = SCRUB.lock() else { return (live, false) };
+    if input::key_pressed(cfg.scrub_key, &SCRUB_KEYS_PREV[0]) { scrub.toggle(); }
+    // Read every frame so a step pressed while live isn't acted on once frozen
+    let back = input::key_pressed(cfg.scrub_back_key, &SCRUB_KEYS_PREV[1]);
+    let forward = input::key_pressed(cfg.scrub_forward_key, &SCRUB_KEYS_PREV[2]);
+    if scrub.frozen() {
+        let step = cfg.scrub_step_ms as i32;
+        if back { scrub.step(-step); }
+        if forward { scrub.step(step); }
+    } else {
+        scrub.record(now, (cfg.scrub_seconds.max(0.0) * 1000.0) as u32, || live.clone());
     }
+    let Some((frame, behind)) = scrub.current() else { return (live, false) };
+    panels.status(format!("FROZEN  -{:.2} s", behind as f32 / 1000.0));
+    (frame.clone(), true)
+}
 
-    render::end_2d();
+// ============================================================
+// Screen Panels
+// ============================================================
+
+/// This frame's panel layout ([layout] in the config), and the status lines
+/// collected while drawing, shown together at the end of the frame.
+struct Panels {
+    layout:  Layout,
+    width:   f32,            // Screen width
+    status:  [f32; 4],       // Rect reserved for the status lines
+    lines:   Vec<String>,    // Warnings etc., below the banner
+    players: Option<u32>,    // Players drawn this frame ({players})
+    map:     Option<String>, // Level path ({map})
+}
+
+impl Panels {
+    /// Start the layout with the status block, so it keeps its corner.
+    fn new(cfg: &Config, screen_w: f32, screen_h: f32) -> Self {
+        let mut layout = Layout::new(screen_w, screen_h, cfg.layout_gap);
+        let status = layout.place(cfg.status_place, STATUS_W, STATUS_LINES as f32 * STATUS_LINE_H);
+        Self { layout, width: screen_w, status, lines: Vec::new(), players: None, map: None }
+    }
+
+    fn status(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+}
+
+/// Value of a `[status] template` placeholder. None = not a placeholder.
+fn status_field(name: &str, panels: &Panels) -> Option<String> {
+    Some(match name {
+        "state"   => if VISIBLE.load(Ordering::Relaxed) { "ON" } else { "OFF" }.to_string(),
+        "fps"     => FPS.load(Ordering::Relaxed).to_string(),
+        "opacity" => format!("{:.0}%", config::get().opacity * 100.0),
+        "players" => panels.players.map_or_else(|| "-".to_string(), |n| n.to_string()),
+        "map"     => panels.map.as_deref()
+            .map(|m| m.trim_start_matches("maps/").trim_end_matches(".bsp").to_string())
+            .unwrap_or_default(),
+        "path"    => hook::present_path_label().map(|p| format!("({})", p)).unwrap_or_default(),
+        _ => return None,
+    })
+}
+
+/// Draw the banner (unless hidden) and the status lines in their block,
+/// aligned to its corner: right-aligned on the right, and ending at the
+/// block's bottom in the bottom corners.
+unsafe fn draw_status(hdc: HDC, cfg: &Config, panels: &Panels) {
+    let [x0, y0, x1, y1] = panels.status;
+    let anchor = cfg.status_place.anchor;
+    let banner = cfg.status_enabled
+        .then(|| layout::expand(&cfg.status_template, |name| status_field(name, panels)))
+        .filter(|b| !b.is_empty());
+    let lines: Vec<&str> = banner.iter().chain(&panels.lines).map(String::as_str).take(STATUS_LINES).collect();
+    let top = if anchor.is_bottom() { y1 - lines.len() as f32 * STATUS_LINE_H } else { y0 };
+    let color = cfg.status_color.unwrap_or(cfg.theme.palette().status);
+    for (i, line) in lines.iter().enumerate() {
+        let x = if anchor.is_right() { x1 - render::text_width(line) } else { x0 };
+        render::draw_text(hdc, x, top + i as f32 * STATUS_LINE_H, line, color);
+    }
+
+    // Red "ERR n" beside the first line once anything has panicked (see note_panic)
+    let errors = PANICS.lock().map(|p| p.total()).unwrap_or(0);
+    if errors > 0 {
+        let badge = format!("ERR {}", errors);
+        let (w, first) = (render::text_width(&badge), lines.first().map_or(0.0, |l| render::text_width(l) + 8.0));
+        let x = if anchor.is_right() { x1 - first - w } else { x0 + first };
+        let y = if lines.is_empty() && anchor.is_bottom() { y1 - STATUS_LINE_H } else { top };
+        render::fill_rect(x - 2.0, y - 2.0, x + w + 2.0, y + render::TEXT_HEIGHT + 2.0, ERR_BADGE_COLOR);
+        render::draw_text(hdc, x, y, &badge, [1.0; 4]);
+    }
+}
+
+/// The profile's name, centered near the top and fading out, for a moment
+/// after a switch.
+unsafe fn draw_profile_notice(hdc: HDC, cfg: &Config, screen_w: f32) {
+    let switched = config::profile_switched_ms();
+    if switched == 0 { return; }
+    let age = time::now_ms().wrapping_sub(switched);
+    if age >= PROFILE_NOTICE_MS { return; }
+    let name = if cfg.profile.is_empty() { "(base settings)" } else { cfg.profile.as_str() };
+    let text = format!("profile: {}", name);
+    let alpha = 1.0 - age as f32 / PROFILE_NOTICE_MS as f32;
+    let color = cfg.status_color.unwrap_or(cfg.theme.palette().status);
+    let x = screen_w * 0.5 - render::text_width(&text) * 0.5;
+    render::draw_text(hdc, x, PROFILE_Y, &text, with_alpha(color, color[3] * alpha));
 }
 
 // ============================================================
-// Cached Box Drawing (used when engine API is unavailable)
+// Draw Site State
 // ============================================================
 
-/// Draw only the cached/fading boxes (used when the engine API is temporarily unavailable).
-unsafe fn draw_cached_boxes(
-    hdc: HDC,
-    screen_h: f32,
-    vx: f32,
-    vy: f32,
-    vw: f32,
-    frame: u32,
-    ttl_frames: u32,
+/// Set up GL state for the draw site (full save/restore at swap time; the HUD
+/// pass already has a 2D projection).
+unsafe fn begin_overlay(site: DrawSite, w: f32, h: f32) {
+    match site {
+        DrawSite::SwapBuffers => render::begin_2d(w, h),
+        DrawSite::HudRedraw   => render::begin_hud(),
+    }
+}
+
+/// Draw the diagnostics page and the menu on top of everything, then leave 2D mode.
+unsafe fn finish_frame(hdc: HDC, site: DrawSite, cfg: &Config, panels: &mut Panels) {
+    // Panels stay up while the overlay is toggled off, dimmed with it; the
+    // diagnostics page and the menu are always drawn in full
+    render::set_alpha(cfg.opacity);
+    draw_status(hdc, cfg, panels);
+    draw_profile_notice(hdc, cfg, panels.width);
+    guarded(Subsystem::Summary, || draw_round_summary(hdc, cfg, panels));
+    guarded(Subsystem::Netgraph, || draw_netgraph(hdc, cfg, panels));
+    render::set_alpha(1.0);
+    diag::frame(hdc, &mut panels.layout);
+    #[cfg(feature = "menu")]
+    menu::frame(hdc);
+    end_overlay(site);
+}
+
+unsafe fn end_overlay(site: DrawSite) {
+    match site {
+        DrawSite::SwapBuffers => render::end_2d(),
+        DrawSite::HudRedraw   => render::end_hud(),
+    }
+}
+
+// ============================================================
+// Label Templates
+// ============================================================
+
+/// What a player's label templates (`[labels] above` / `below`) can show.
+struct LabelFields<'a> {
+    idx:    i32,
+    name:   String,
+    tags:   String,         // "[KIT] [H]" per [tags]
+    dist:   f32,            // Meters
+    weapon: &'a str,
+    health: Option<i32>,
+    team:   String,         // Team name from TeamInfo, else the number
+    dead:   bool,
+    action: Option<&'static str>, // RELOADING / SWITCHING
+    no_pvs: bool,           // Outside the PVS (and [pvs] tint is on)
+}
+
+impl LabelFields<'_> {
+    /// Value of a label placeholder: empty = nothing to show for this player
+    /// (the word is dropped), None = not a placeholder.
+    fn field(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "name"   => self.name.clone(),
+            "tags"   => self.tags.clone(),
+            "dist"   => format!("{:.1}", self.dist),
+            "weapon" => self.weapon.to_string(),
+            "hp"     => self.health.map(|h| h.to_string()).unwrap_or_default(),
+            "team"   => self.team.clone(),
+            "index"  => self.idx.to_string(),
+            "dead"   => if self.dead { "DEAD" } else { "" }.to_string(),
+            "action" => self.action.unwrap_or_default().to_string(),
+            "pvs"    => if self.no_pvs { "no PVS" } else { "" }.to_string(),
+            _ => return None,
+        })
+    }
+}
+
+// ============================================================
+// Benchmark Mode
+// ============================================================
+
+/// Boxes for bench.rs's synthetic players at `now`, built the way fresh
+/// player boxes are (team colors, name and weapon labels, health).
+#[allow(clippy::too_many_arguments)]
+fn benchmark_boxes(
+    cfg: &Config, now: u32, screen_w: f32, screen_h: f32, vx: f32, vy: f32, vw: f32, vh: f32,
+) -> Vec<BoxDraw> {
+    let pal = cfg.theme.palette();
+    let scene = bench::scene(now);
+    let aspect = if vh > 0.0 { vw / vh } else { 1.0 };
+    let mut boxes = Vec::with_capacity(scene.players.len());
+    for p in &scene.players {
+        let Some((rect, feet)) = view::project_box(p.origin, p.maxs_z, p.ducking,
+            |w| scene.project(w, aspect), |nx, ny| view::ndc_to_px(nx, ny, screen_h, vx, vy, vw, vh)) else { continue };
+        if !view::near_screen(feet, screen_w, screen_h) { continue; }
+        let dist = scene.eye.distance(p.origin) / UNITS_PER_METER;
+        let mut color = pal.player_color(cfg.color_mode, p.team, 1);
+        if let HealthColor::Box = cfg.health_color { color = pal.health_color(p.health); }
+        let fields = LabelFields {
+            idx: 0, name: p.name.clone(), tags: String::new(), dist, weapon: p.weapon, health: Some(p.health),
+            team: p.team.to_string(), dead: false, action: None, no_pvs: false,
+        };
+        let name = layout::expand_words(&cfg.label_above, |f| fields.field(f));
+        boxes.push(BoxDraw {
+            rect,
+            feet,
+            dist,
+            color,
+            team: p.team,
+            snap_alpha: 1.0,
+            name: (!name.is_empty()).then_some(name),
+            info: layout::expand_words(&cfg.label_below, |f| fields.field(f)),
+            text_alpha: 1.0,
+            health: Some(p.health),
+            focus: false,
+            head: None,
+            line: Dash::Solid,
+        });
+    }
+    boxes
+}
+
+// ============================================================
+// Cached Box Queueing (used when engine API is unavailable)
+// ============================================================
+
+/// Queue only the cached/fading boxes at their last pixel positions (used when
+/// the engine API is temporarily unavailable, so there's nothing to reproject with).
+fn queue_cached_boxes(
+    cfg: &Config,
+    now: u32,
+    ttl_ms: u32,
     alpha: f32,
+    boxes: &mut Vec<BoxDraw>,
 ) -> u32 {
     let mut drawn = 0u32;
+    let cache = PLAYER_CACHE.lock().unwrap_or_else(|e| e.into_inner());
     for idx in 1..=32usize {
-        let seen = LAST_SEEN[idx];
-        if seen == 0 { continue; }
-
         // Distance-dependent TTL
-        let dist = LAST_DIST[idx];
-        let ttl = if dist > 0.0 {
-            if dist < 10.0 { 300u32 } else if dist < 30.0 { 150u32 } else { ttl_frames }
-        } else { ttl_frames };
-
-        if frame.wrapping_sub(seen) > ttl { continue; }
-
-        let [x0, y0, x1, y1] = LAST_BOX[idx];
-        let [fx, fy] = LAST_FEET[idx];
-        if x0 == 0.0 && y0 == 0.0 && x1 == 0.0 && y1 == 0.0 { continue; }
+        let Some(e) = cache.get(idx) else { continue };
+        let Some(age) = e.cached_age(now, ttl_ms) else { continue };
 
         // Fade-out with ease-out curve
-        let mut color = LAST_COLOR[idx];
-        let base_alpha = if dist > 0.0 && dist < 10.0 { (alpha + 0.6).min(1.0) } else { alpha };
-        let age = frame.wrapping_sub(seen) as f32;
-        let fade_t = (age / 12.0).clamp(0.0, 1.0);
-        let ease = 1.0_f32 - (1.0 - fade_t).powf(2.0);
-        let final_alpha = (base_alpha * (1.0 - ease)).max(0.02);
-        if final_alpha <= 0.02 { continue; }
-        color[3] = final_alpha;
-
-        render::draw_rect_outline(x0, y0, x1, y1);
-        render::draw_box_corners(x0, y0, x1, y1, color);
-        render::draw_line(vx + vw * 0.5, screen_h - vy, fx, fy, [1.0, 0.15, 0.15, final_alpha * 0.6]);
-        let label = format!("{:.1}m", LAST_DIST[idx]);
-        render::draw_text(hdc, x0, y1 + 12.0, &label, [1.0, 1.0, 1.0, final_alpha]);
+        let base_alpha = if e.dist > 0.0 && e.dist < 10.0 { (alpha + 0.6).min(1.0) } else { alpha };
+        let Some(b) = cached_box(cfg, e, age, base_alpha) else { continue };
+        boxes.push(b);
         drawn += 1;
     }
     drawn
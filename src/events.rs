@@ -0,0 +1,143 @@
+// events.rs — Sounds the client plays, captured for the sound rings (esp.rs).
+//
+// Two sources, both hooked with MinHook once the engine table is known:
+//
+//   EV_PlaySound      event_api_t entry 1, reached through the engine table's
+//                     pEventAPI. client.dll's event handlers (gunfire, shells,
+//                     the local player's predicted footsteps) play through it.
+//   S_StartDynamicSound
+//                     hw.dll's sound dispatcher, which also gets server-sent
+//                     sounds: other players' footsteps and shots. It isn't
+//                     exported, so it's only hooked when `sound_rings.
+//                     start_sound_rva` gives its offset for the running build.
+//
+// Each detour classifies the sample (sounds.rs), records the ones worth showing
+// in a small ring buffer, and calls the original.
+
+use crate::config;
+use crate::entities::{self, is_executable, read_cstr, read_u32, read_vec3};
+use crate::hook;
+use crate::math::Vec3;
+use crate::sounds::{self, SoundKind};
+use crate::time;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const SLOT_EVENT_API: usize = 84;     // cl_enginefunc_t::pEventAPI
+const EVENT_API_PLAY_SOUND: usize = 1; // event_api_t::EV_PlaySound (after `version`)
+const MAX_EVENTS: usize = 128;        // Sounds kept; older ones are dropped first
+const MAX_SAMPLE_LEN: usize = 64;     // sfx_t::name / sample path length
+
+/// A sound that was played.
+#[derive(Clone, Copy, Debug)]
+pub struct SoundEvent {
+    pub seq:     u32,          // Increases by one per recorded sound (never 0)
+    pub kind:    SoundKind,
+    pub ent:     i32,          // Entity that made it (0 = world / unknown)
+    pub origin:  Option<Vec3>, // Where; None = at the entity
+    pub time_ms: u32,          // time::now_ms() when played
+}
+
+static EVENTS: Mutex<VecDeque<SoundEvent>> = Mutex::new(VecDeque::new());
+static SEQ: AtomicU32 = AtomicU32::new(0);
+
+static PLAY_SOUND_ORIG: AtomicUsize = AtomicUsize::new(0);
+static START_SOUND_ORIG: AtomicUsize = AtomicUsize::new(0);
+static HOOKED: AtomicBool = AtomicBool::new(false);
+
+/// event_api_t::EV_PlaySound.
+type FnPlaySound = unsafe extern "C" fn(
+    ent: i32, origin: *const f32, channel: i32, sample: *const i8,
+    volume: f32, attenuation: f32, flags: i32, pitch: i32,
+);
+
+/// hw.dll!S_StartDynamicSound. `sfx` points to an sfx_t, whose first member
+/// is the sample name.
+type FnStartSound = unsafe extern "C" fn(
+    entnum: i32, channel: i32, sfx: *const i8, origin: *const f32,
+    volume: f32, attenuation: f32, flags: i32, pitch: i32,
+);
+
+/// Hook the sound functions (attempted once, after the engine table is found).
+pub unsafe fn install(table: usize) {
+    if HOOKED.swap(true, Ordering::Relaxed) { return; }
+
+    let api = read_u32(table + SLOT_EVENT_API * 4) as usize;
+    let target = if api < 0x10000 { 0 } else { read_u32(api + EVENT_API_PLAY_SOUND * 4) as usize };
+    if !is_executable(target) {
+        entities::logf(format!("EV_PlaySound not found (pEventAPI {:#010x})", api));
+    } else {
+        match hook::hook_address("engine!EV_PlaySound", target, hk_play_sound as *mut std::ffi::c_void) {
+            Ok(orig) => {
+                PLAY_SOUND_ORIG.store(orig as usize, Ordering::Release);
+                entities::log("EV_PlaySound hook installed");
+            }
+            Err(e) => entities::logf(format!("EV_PlaySound hook failed: {}", e)),
+        }
+    }
+
+    let rva = config::get().sound_start_rva;
+    if rva == 0 { return; }
+    let Some((base, end)) = entities::module_range(b"hw.dll\0") else { return };
+    let target = base + rva as usize;
+    if target >= end || !is_executable(target) {
+        entities::logf(format!("S_StartDynamicSound: rva {:#x} is outside hw.dll's code", rva));
+        return;
+    }
+    match hook::hook_address("hw.dll!S_StartDynamicSound", target, hk_start_sound as *mut std::ffi::c_void) {
+        Ok(orig) => {
+            START_SOUND_ORIG.store(orig as usize, Ordering::Release);
+            entities::logf(format!("S_StartDynamicSound hook installed (hw.dll+{:#x})", rva));
+        }
+        Err(e) => entities::logf(format!("S_StartDynamicSound hook failed: {}", e)),
+    }
+}
+
+unsafe extern "C" fn hk_play_sound(
+    ent: i32, origin: *const f32, channel: i32, sample: *const i8,
+    volume: f32, attenuation: f32, flags: i32, pitch: i32,
+) {
+    let _guard = hook::DetourGuard::enter();
+    record(ent, origin, channel, sample);
+    let orig = PLAY_SOUND_ORIG.load(Ordering::Acquire);
+    if orig != 0 {
+        let f: FnPlaySound = std::mem::transmute(orig);
+        f(ent, origin, channel, sample, volume, attenuation, flags, pitch);
+    }
+}
+
+unsafe extern "C" fn hk_start_sound(
+    entnum: i32, channel: i32, sfx: *const i8, origin: *const f32,
+    volume: f32, attenuation: f32, flags: i32, pitch: i32,
+) {
+    let _guard = hook::DetourGuard::enter();
+    record(entnum, origin, channel, sfx);
+    let orig = START_SOUND_ORIG.load(Ordering::Acquire);
+    if orig != 0 {
+        let f: FnStartSound = std::mem::transmute(orig);
+        f(entnum, channel, sfx, origin, volume, attenuation, flags, pitch);
+    }
+}
+
+/// Classify a sound and keep it if it's shown.
+unsafe fn record(ent: i32, origin: *const f32, channel: i32, sample: *const i8) {
+    let Some(name) = read_cstr(sample, MAX_SAMPLE_LEN) else { return };
+    let Some(kind) = sounds::classify(&name, channel) else { return };
+    let origin = if entities::is_readable(origin as usize, 12) {
+        Some(read_vec3(origin as usize)).filter(|o| !o.is_zero())
+    } else {
+        None
+    };
+    let Ok(mut events) = EVENTS.lock() else { return };
+    if events.len() >= MAX_EVENTS { events.pop_front(); }
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed).wrapping_add(1).max(1);
+    events.push_back(SoundEvent { seq, kind, ent, origin, time_ms: time::now_ms() });
+}
+
+/// Sounds played within the last `max_age_ms`, oldest first.
+pub fn recent(max_age_ms: u32) -> Vec<SoundEvent> {
+    let now = time::now_ms();
+    let Ok(events) = EVENTS.lock() else { return Vec::new() };
+    events.iter().filter(|e| now.wrapping_sub(e.time_ms) <= max_age_ms).copied().collect()
+}
@@ -0,0 +1,108 @@
+// fault.rs — Hardware exceptions in the overlay's own code, and the crash report.
+//
+// A panic is caught (panics.rs), but an access violation in the DLL's code
+// goes straight past catch_unwind and takes the game down with a report that
+// says nothing about us. crashdump.rs watches for those with a vectored
+// exception handler; this is the part that decides which exceptions count
+// and what the text written next to the minidump says.
+//
+// Only real faults count: Rust panics travel as C++ exceptions (0xE06D7363)
+// and are caught further up, and the debugger's breakpoints and single steps
+// must go through untouched.
+
+pub const ACCESS_VIOLATION: u32      = 0xC000_0005;
+pub const IN_PAGE_ERROR: u32         = 0xC000_0006;
+pub const ILLEGAL_INSTRUCTION: u32   = 0xC000_001D;
+pub const INT_DIVIDE_BY_ZERO: u32    = 0xC000_0094;
+pub const PRIVILEGED_INSTRUCTION: u32 = 0xC000_0096;
+
+/// Whether an exception with this code is a fault worth a crash report.
+pub fn is_fault(code: u32) -> bool {
+    matches!(code, ACCESS_VIOLATION | IN_PAGE_ERROR | ILLEGAL_INSTRUCTION
+                 | INT_DIVIDE_BY_ZERO | PRIVILEGED_INSTRUCTION)
+}
+
+/// What went wrong, from the exception code and its parameters
+/// (ExceptionInformation): "access violation writing 0x00000010".
+pub fn describe(code: u32, params: &[usize]) -> String {
+    let name = match code {
+        ACCESS_VIOLATION       => "access violation",
+        IN_PAGE_ERROR          => "in-page error",
+        ILLEGAL_INSTRUCTION    => "illegal instruction",
+        INT_DIVIDE_BY_ZERO     => "integer divide by zero",
+        PRIVILEGED_INSTRUCTION => "privileged instruction",
+        _ => return format!("exception {:#010x}", code),
+    };
+    // For the first two, [0] is the kind of access and [1] the address
+    match (code, params) {
+        (ACCESS_VIOLATION | IN_PAGE_ERROR, &[kind, addr, ..]) => {
+            let verb = match kind { 0 => "reading", 1 => "writing", 8 => "executing", _ => "accessing" };
+            format!("{} {} {:#010x}", name, verb, addr)
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Whether `addr` is inside the module spanning `[start, end)`.
+pub fn in_module(range: (usize, usize), addr: usize) -> bool {
+    addr >= range.0 && addr < range.1
+}
+
+/// File name (without extension) for a crash at `unix_secs`; the .dmp and
+/// .log of one crash share it.
+pub fn file_stem(unix_secs: u64) -> String {
+    format!("crash_{}", unix_secs)
+}
+
+/// The text written next to the minidump: the fault, where it happened
+/// (module offset, so it can be looked up in the build's .pdb) and the
+/// log lines leading up to it.
+pub fn report(what: &str, addr: usize, module_base: usize, thread: u32, log_tail: &[String]) -> String {
+    let mut out = format!(
+        "{} at {:#010x} (overlay+{:#x}) on thread {}\n\nlast {} log line(s):\n",
+        what, addr, addr.wrapping_sub(module_base), thread, log_tail.len(),
+    );
+    for l in log_tail {
+        out.push_str(l);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_faults_count() {
+        assert!(is_fault(ACCESS_VIOLATION));
+        assert!(is_fault(INT_DIVIDE_BY_ZERO));
+        assert!(!is_fault(0xE06D_7363)); // C++ exception (a Rust panic unwinding)
+        assert!(!is_fault(0x8000_0003)); // Breakpoint
+        assert!(!is_fault(0x4001_0006)); // OutputDebugString
+    }
+
+    #[test]
+    fn describes_the_access() {
+        assert_eq!(describe(ACCESS_VIOLATION, &[0, 0x10]), "access violation reading 0x00000010");
+        assert_eq!(describe(ACCESS_VIOLATION, &[1, 0xdead_beef]), "access violation writing 0xdeadbeef");
+        assert_eq!(describe(ACCESS_VIOLATION, &[8, 0]), "access violation executing 0x00000000");
+        assert_eq!(describe(ACCESS_VIOLATION, &[]), "access violation");
+        assert_eq!(describe(ILLEGAL_INSTRUCTION, &[]), "illegal instruction");
+        assert_eq!(describe(0xC000_00FD, &[]), "exception 0xc00000fd");
+    }
+
+    #[test]
+    fn module_range_and_report() {
+        assert!(in_module((0x1000_0000, 0x1008_0000), 0x1000_0000));
+        assert!(in_module((0x1000_0000, 0x1008_0000), 0x1007_ffff));
+        assert!(!in_module((0x1000_0000, 0x1008_0000), 0x1008_0000));
+        assert!(!in_module((0, 0), 0));
+
+        assert_eq!(file_stem(1_700_000_000), "crash_1700000000");
+        let r = report("access violation reading 0x00000010", 0x1000_1234, 0x1000_0000, 42,
+                       &["hook installed".to_string(), "map loaded".to_string()]);
+        assert_eq!(r, "access violation reading 0x00000010 at 0x10001234 (overlay+0x1234) on thread 42\n\n\
+                       last 2 log line(s):\nhook installed\nmap loaded\n");
+    }
+}
@@ -0,0 +1,323 @@
+// feed.rs — Live player snapshots over HTTP and WebSocket, for browser radars.
+//
+// esp.rs builds a JSON snapshot of the players (`[feed] rate_hz` times a
+// second) and hands it to `publish`; a background thread started on the first
+// publish serves it on `[feed] bind`:`port`:
+//
+//   - A plain `GET` gets the latest snapshot as an HTTP response (polling).
+//   - A WebSocket upgrade (RFC 6455) gets every new snapshot pushed as a text
+//     frame until it disconnects.
+//
+// Everything here is std only: the handshake's SHA-1 and base64 are below.
+// The address is taken from the first publish; changing it needs a restart.
+
+use crate::bsp::Bounds;
+use crate::markers::Marker;
+use crate::math::Vec3;
+use crate::session::json_str;
+use crate::time::Stopwatch;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"; // RFC 6455 handshake suffix
+const MAX_REQUEST: usize = 4096;       // Longest HTTP request read
+const MAX_CLIENTS: usize = 16;         // WebSocket clients served at once
+const POLL: Duration = Duration::from_millis(10);
+const IO_TIMEOUT: Duration = Duration::from_millis(200);
+
+// ============================================================
+// Snapshots
+// ============================================================
+
+/// One player in a snapshot.
+#[derive(Clone, Debug)]
+pub struct FeedPlayer {
+    pub index:  i32,
+    pub name:   String,
+    pub team:   i32,
+    pub origin: Vec3,
+    pub yaw:    f32,
+    pub health: Option<i32>,
+    pub alive:  bool,
+}
+
+/// A snapshot as JSON: the map and its horizontal bounds `[min x, min y,
+/// max x, max y]` (for scaling a radar; null until the .bsp is read), the
+/// viewer (if known), the players, and the health markers raised since the
+/// previous snapshot (markers.rs).
+pub fn snapshot_json(time_ms: u32, map: Option<&str>, bounds: Option<&Bounds>, viewer: Option<(Vec3, f32)>,
+                     players: &[FeedPlayer], markers: &[Marker]) -> String {
+    let vec = |v: Vec3| format!("[{:.1}, {:.1}, {:.1}]", v.x, v.y, v.z);
+    let map = map.map(json_str).unwrap_or_else(|| "null".to_string());
+    let bounds = match bounds {
+        Some(b) => format!("[{:.1}, {:.1}, {:.1}, {:.1}]", b.mins[0], b.mins[1], b.maxs[0], b.maxs[1]),
+        None => "null".to_string(),
+    };
+    let viewer = match viewer {
+        Some((o, yaw)) => format!("{{\"origin\": {}, \"yaw\": {:.1}}}", vec(o), yaw),
+        None => "null".to_string(),
+    };
+    let players: Vec<String> = players.iter().map(|p| format!(
+        "{{\"index\": {}, \"name\": {}, \"team\": {}, \"origin\": {}, \"yaw\": {:.1}, \"health\": {}, \"alive\": {}}}",
+        p.index, json_str(&p.name), p.team, vec(p.origin), p.yaw,
+        p.health.map(|h| h.to_string()).unwrap_or_else(|| "null".to_string()), p.alive,
+    )).collect();
+    let markers: Vec<String> = markers.iter().map(Marker::to_json).collect();
+    format!("{{\"time\": {}, \"map\": {}, \"bounds\": {}, \"viewer\": {}, \"players\": [{}], \"markers\": [{}]}}",
+        time_ms, map, bounds, viewer, players.join(", "), markers.join(", "))
+}
+
+// ============================================================
+// Protocol
+// ============================================================
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 { msg.push(0); }
+    msg.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for chunk in msg.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19  => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _       => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, t);
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e]) { *x = x.wrapping_add(y); }
+    }
+    let mut out = [0u8; 20];
+    for (o, x) in out.chunks_exact_mut(4).zip(h) { o.copy_from_slice(&x.to_be_bytes()); }
+    out
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for group in data.chunks(3) {
+        let n = group.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= group.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Sec-WebSocket-Accept for a client's Sec-WebSocket-Key.
+pub fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key.trim(), WS_GUID).as_bytes()))
+}
+
+/// The Sec-WebSocket-Key of an HTTP request, if it asks for an upgrade.
+pub fn websocket_key(request: &str) -> Option<&str> {
+    request.lines().skip(1)
+        .filter_map(|l| l.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-key"))
+        .map(|(_, value)| value.trim())
+}
+
+/// An unmasked, unfragmented text frame (servers don't mask).
+pub fn text_frame(payload: &str) -> Vec<u8> {
+    let len = payload.len();
+    let mut out = vec![0x81];
+    match len {
+        0..=125 => out.push(len as u8),
+        126..=0xFFFF => { out.push(126); out.extend_from_slice(&(len as u16).to_be_bytes()); }
+        _ => { out.push(127); out.extend_from_slice(&(len as u64).to_be_bytes()); }
+    }
+    out.extend_from_slice(payload.as_bytes());
+    out
+}
+
+// ============================================================
+// Server
+// ============================================================
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static LATEST: Mutex<(u32, String)> = Mutex::new((0, String::new())); // (sequence, snapshot)
+static ERROR: Mutex<Option<String>> = Mutex::new(None);
+static CLIENTS: AtomicU32 = AtomicU32::new(0);
+static STOPPING: AtomicBool = AtomicBool::new(false);
+static THREAD: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Make `snapshot` the latest, starting the server on `bind`:`port` the
+/// first time.
+pub fn publish(bind: &str, port: u16, snapshot: String) {
+    if let Ok(mut latest) = LATEST.lock() {
+        latest.0 = latest.0.wrapping_add(1);
+        latest.1 = snapshot;
+    }
+    if STARTED.swap(true, Ordering::Relaxed) { return; }
+    let addr = format!("{}:{}", bind, port);
+    let handle = std::thread::spawn(move || {
+        if let Err(e) = serve(&addr) {
+            if let Ok(mut err) = ERROR.lock() { *err = Some(format!("{}: {}", addr, e)); }
+        }
+    });
+    if let Ok(mut t) = THREAD.lock() { *t = Some(handle); }
+}
+
+/// Stop the server and wait for its thread (before the DLL unloads). The
+/// server doesn't start again afterwards.
+pub fn stop() {
+    STOPPING.store(true, Ordering::Release);
+    STARTED.store(true, Ordering::Relaxed);
+    if let Some(handle) = THREAD.lock().ok().and_then(|mut t| t.take()) {
+        let _ = handle.join();
+    }
+}
+
+/// Why the server isn't running (None = running or not started).
+pub fn error() -> Option<String> {
+    ERROR.lock().ok().and_then(|e| e.clone())
+}
+
+/// WebSocket clients connected now.
+pub fn clients() -> u32 {
+    CLIENTS.load(Ordering::Relaxed)
+}
+
+fn serve(addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let mut clients: Vec<TcpStream> = Vec::new();
+    let mut sent = 0u32;
+
+    while !STOPPING.load(Ordering::Acquire) {
+        // --- New connections: answer a poll, or upgrade ---
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Some(ws) = handshake(stream) {
+                        if clients.len() < MAX_CLIENTS { clients.push(ws); }
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+
+        // --- Drop clients that closed (or sent a close frame) ---
+        clients.retain_mut(|c| {
+            let mut buf = [0u8; 256];
+            match c.read(&mut buf) {
+                Ok(0) => false,
+                Ok(n) => buf[..n].first().map(|b| b & 0x0F) != Some(0x8),
+                Err(e) => e.kind() == ErrorKind::WouldBlock,
+            }
+        });
+
+        // --- Push a new snapshot ---
+        let latest = LATEST.lock().ok().filter(|l| l.0 != sent).map(|l| (l.0, text_frame(&l.1)));
+        if let Some((seq, frame)) = latest {
+            sent = seq;
+            clients.retain_mut(|c| write_all(c, &frame));
+        }
+        CLIENTS.store(clients.len() as u32, Ordering::Relaxed);
+        std::thread::sleep(POLL);
+    }
+    CLIENTS.store(0, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Read a request. A WebSocket upgrade is answered and the stream returned
+/// (non-blocking); anything else gets the latest snapshot and is closed.
+fn handshake(mut stream: TcpStream) -> Option<TcpStream> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(IO_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(IO_TIMEOUT)).ok()?;
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        let n = stream.read(&mut buf).ok()?;
+        if n == 0 { return None; }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    if !request.starts_with("GET ") { return None; }
+
+    match websocket_key(&request) {
+        Some(key) => {
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key));
+            stream.write_all(response.as_bytes()).ok()?;
+            stream.set_nonblocking(true).ok()?;
+            Some(stream)
+        }
+        None => {
+            let body = LATEST.lock().map(|l| l.1.clone()).unwrap_or_default();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body);
+            let _ = stream.write_all(response.as_bytes());
+            None
+        }
+    }
+}
+
+/// Write all of `data` to a non-blocking stream, giving up after IO_TIMEOUT.
+fn write_all(stream: &mut TcpStream, mut data: &[u8]) -> bool {
+    let start = Stopwatch::start();
+    while !data.is_empty() {
+        match stream.write(data) {
+            Ok(0) => return false,
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == ErrorKind::WouldBlock && start.elapsed() < IO_TIMEOUT => std::thread::sleep(POLL),
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn websocket_handshake() {
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        let req = "GET /feed HTTP/1.1\r\nHost: localhost\r\nsec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        assert_eq!(websocket_key(req), Some("dGhlIHNhbXBsZSBub25jZQ=="));
+        assert_eq!(websocket_key("GET / HTTP/1.1\r\nHost: x\r\n\r\n"), None);
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="); // RFC 6455 example
+    }
+
+    #[test]
+    fn frames_and_snapshots() {
+        assert_eq!(text_frame("hi"), vec![0x81, 2, b'h', b'i']);
+        assert_eq!(&text_frame(&"x".repeat(300))[..4], &[0x81, 126, 1, 44]);
+        let p = FeedPlayer {
+            index: 3, name: "a".into(), team: 2, origin: Vec3::new(1.0, 2.0, 3.0), yaw: 90.0, health: None, alive: true,
+        };
+        let bounds = Bounds { mins: [-512.0, -256.0, -64.0], maxs: [512.0, 256.0, 64.0] };
+        assert_eq!(snapshot_json(5, Some("maps/x.bsp"), Some(&bounds), None, &[p], &[]),
+            "{\"time\": 5, \"map\": \"maps/x.bsp\", \"bounds\": [-512.0, -256.0, 512.0, 256.0], \"viewer\": null, \"players\": [{\"index\": 3, \"name\": \"a\", \
+             \"team\": 2, \"origin\": [1.0, 2.0, 3.0], \"yaw\": 90.0, \"health\": null, \"alive\": true}], \"markers\": []}");
+        let hook = Marker::hook_lost(9, "wglSwapBuffers");
+        assert!(snapshot_json(9, None, None, None, &[], &[hook]).ends_with(
+            "\"players\": [], \"markers\": [{\"time\": 9, \"kind\": \"hook_lost\", \"detail\": \"wglSwapBuffers overwritten\"}]}"));
+    }
+}
@@ -0,0 +1,136 @@
+// flash.rs — How blinded the local player is, from the engine's screen fade.
+//
+// A flashbang (or any ScreenFade user message) sets the engine's screenfade_t:
+// a color, a peak alpha, and client times for when the hold ends (`reset`)
+// and the fade is over (`end`). For a fade *in* from the color — the flashbang
+// case — the screen is fully covered until `reset`, then clears linearly until
+// `end`. A fade *out* goes the other way and, with STAYOUT, stays covered.
+//
+// The overlay is drawn after the engine's fade, so it's never dimmed by it;
+// this only reports the state for the indicator (and for dimming the ESP on
+// purpose, if the user wants it to match what they can see).
+
+/// screenfade_t::fadeFlags bits.
+pub const FFADE_OUT:     i32 = 0x0001; // Fade to the color (instead of from it)
+pub const FFADE_STAYOUT: i32 = 0x0004; // Stay covered after a fade out
+
+/// A fade at least this strong (fraction of full coverage) counts as blinded.
+pub const BLIND_ALPHA: f32 = 0.5;
+
+/// The engine's screen fade (screenfade_t), times in client seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScreenFade {
+    pub speed: f32,     // Alpha units per second
+    pub end:   f32,     // Fade finishes
+    pub reset: f32,     // Hold ends (fade in)
+    pub color: [u8; 4], // RGB + peak alpha
+    pub flags: i32,
+}
+
+impl ScreenFade {
+    /// How much of the screen the fade covers at `time`, 0..1.
+    pub fn coverage(&self, time: f32) -> f32 {
+        let peak = self.color[3] as f32;
+        let alpha = if self.flags & FFADE_OUT != 0 {
+            if time >= self.end {
+                if self.flags & FFADE_STAYOUT != 0 { peak } else { 0.0 }
+            } else {
+                peak - self.speed.abs() * (self.end - time)
+            }
+        } else if time >= self.end {
+            0.0
+        } else if time < self.reset {
+            peak
+        } else {
+            self.speed.abs() * (self.end - time)
+        };
+        (alpha / 255.0).clamp(0.0, 1.0)
+    }
+
+    /// Seconds until the fade is over (0 once it is; infinite while stayed out).
+    pub fn remaining(&self, time: f32) -> f32 {
+        if self.flags & (FFADE_OUT | FFADE_STAYOUT) == FFADE_OUT | FFADE_STAYOUT && time >= self.end {
+            return f32::INFINITY;
+        }
+        (self.end - time).max(0.0)
+    }
+}
+
+/// A flash in progress, for the indicator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Blind {
+    pub coverage:  f32, // 0..1 right now
+    pub remaining: f32, // Seconds left
+    pub fraction:  f32, // Remaining / total, for the countdown bar
+}
+
+/// Follows the fade across frames so the bar knows each flash's full length.
+#[derive(Default)]
+pub struct FlashTracker {
+    end:   f32, // `end` of the fade being followed
+    total: f32, // Its length, measured when first seen
+}
+
+impl FlashTracker {
+    pub const fn new() -> Self { Self { end: 0.0, total: 0.0 } }
+
+    /// Feed this frame's fade. Some while the local player is blinded, i.e.
+    /// the fade is mostly white and covers at least BLIND_ALPHA.
+    pub fn update(&mut self, fade: &ScreenFade, time: f32) -> Option<Blind> {
+        let remaining = fade.remaining(time);
+        if fade.end != self.end {
+            // A new (or re-triggered) fade: its length starts now
+            self.end = fade.end;
+            self.total = remaining;
+        }
+        let coverage = fade.coverage(time);
+        let white = fade.color[..3].iter().all(|&c| c >= 200);
+        if !white || coverage < BLIND_ALPHA || remaining <= 0.0 { return None; }
+        let fraction = if self.total.is_finite() && self.total > 0.0 {
+            (remaining / self.total).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        Some(Blind { coverage, remaining, fraction })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A flashbang: hold white until 12.0, then clear by 14.0.
+    const FLASH: ScreenFade = ScreenFade {
+        speed: 127.5, end: 14.0, reset: 12.0, color: [255, 255, 255, 255], flags: 0,
+    };
+
+    #[test]
+    fn fade_in_holds_then_clears() {
+        assert_eq!(FLASH.coverage(11.0), 1.0);
+        assert_eq!(FLASH.coverage(13.0), 0.5);
+        assert_eq!(FLASH.coverage(14.5), 0.0);
+        assert_eq!(FLASH.remaining(13.0), 1.0);
+    }
+
+    #[test]
+    fn fade_out_and_stayout() {
+        let f = ScreenFade { speed: -255.0, end: 5.0, reset: 0.0, color: [0, 0, 0, 255], flags: FFADE_OUT };
+        assert_eq!(f.coverage(4.5), 0.5);
+        assert_eq!(f.coverage(6.0), 0.0);
+        let stay = ScreenFade { flags: FFADE_OUT | FFADE_STAYOUT, ..f };
+        assert_eq!(stay.coverage(6.0), 1.0);
+        assert!(stay.remaining(6.0).is_infinite());
+    }
+
+    #[test]
+    fn tracker_measures_each_flash() {
+        let mut t = FlashTracker::new();
+        let b = t.update(&FLASH, 10.0).unwrap();
+        assert_eq!((b.remaining, b.fraction), (4.0, 1.0));
+        assert_eq!(t.update(&FLASH, 12.0).unwrap().fraction, 0.5);
+        assert!(t.update(&FLASH, 13.5).is_none()); // Mostly clear
+        // Dark fades (e.g. death) aren't flashes
+        let dark = ScreenFade { color: [0, 0, 0, 255], end: 30.0, reset: 29.0, ..FLASH };
+        assert!(t.update(&dark, 20.0).is_none());
+    }
+}
@@ -0,0 +1,126 @@
+// glstate.rs — A snapshot of the GL state the overlay could disturb, and the
+// differences between two snapshots.
+//
+// With `[debug] gl_audit` on, render.rs snapshots the state before the overlay
+// draws and again after it has restored everything, and logs any difference:
+// something begin_2d/end_2d (or the HUD variants) failed to put back, which
+// shows up in game as flickering HUD sprites or wrong colors. The snapshot is
+// wider than what glPushAttrib covers: the active texture units, client arrays,
+// the blend equation and the matrix stack depths.
+//
+// Reading the state is render.rs's job (it needs a GL context); this file only
+// names the fields and compares them, so the comparison is tested on any host.
+
+/// Capabilities checked with glIsEnabled: (enum, name).
+pub const CAPS: [(u32, &str); 12] = [
+    (0x0BE2, "GL_BLEND"),
+    (0x0DE1, "GL_TEXTURE_2D"),
+    (0x0B71, "GL_DEPTH_TEST"),
+    (0x0BC0, "GL_ALPHA_TEST"),
+    (0x0B44, "GL_CULL_FACE"),
+    (0x0C11, "GL_SCISSOR_TEST"),
+    (0x0B90, "GL_STENCIL_TEST"),
+    (0x0B60, "GL_FOG"),
+    (0x0B50, "GL_LIGHTING"),
+    (0x0B20, "GL_LINE_SMOOTH"),
+    (0x0B57, "GL_COLOR_MATERIAL"),
+    (0x8037, "GL_POLYGON_OFFSET_FILL"),
+];
+
+/// Client-side vertex arrays checked with glIsEnabled: (enum, name).
+pub const ARRAYS: [(u32, &str); 4] = [
+    (0x8074, "GL_VERTEX_ARRAY"),
+    (0x8076, "GL_COLOR_ARRAY"),
+    (0x8078, "GL_TEXTURE_COORD_ARRAY"),
+    (0x8075, "GL_NORMAL_ARRAY"),
+];
+
+/// The audited state. Enums are kept as the raw values GL returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlState {
+    pub caps:             u32,      // Bit i = CAPS[i] enabled
+    pub arrays:           u32,      // Bit i = ARRAYS[i] enabled (active client texture unit)
+    pub blend_src:        i32,
+    pub blend_dst:        i32,
+    pub blend_equation:   i32,
+    pub active_texture:   i32,      // GL_TEXTURE0 + unit
+    pub client_texture:   i32,      // Client active texture unit
+    pub texture_binding:  i32,      // GL_TEXTURE_BINDING_2D of the active unit
+    pub matrix_mode:      i32,
+    pub modelview_depth:  i32,      // Matrix stack depths
+    pub projection_depth: i32,
+    pub shade_model:      i32,
+    pub depth_mask:       bool,
+    pub line_width:       f32,
+    pub color:            [f32; 4], // Current color
+    pub viewport:         [i32; 4],
+}
+
+impl GlState {
+    /// Human-readable differences from `self` (before) to `after`; empty when
+    /// nothing leaked.
+    pub fn diff(&self, after: &GlState) -> Vec<String> {
+        let mut out = Vec::new();
+        for (i, (_, name)) in CAPS.iter().enumerate() {
+            push_bit(&mut out, name, self.caps, after.caps, i);
+        }
+        for (i, (_, name)) in ARRAYS.iter().enumerate() {
+            push_bit(&mut out, name, self.arrays, after.arrays, i);
+        }
+        let enums = [
+            ("blend src",        self.blend_src,        after.blend_src),
+            ("blend dst",        self.blend_dst,        after.blend_dst),
+            ("blend equation",   self.blend_equation,   after.blend_equation),
+            ("active texture",   self.active_texture,   after.active_texture),
+            ("client texture",   self.client_texture,   after.client_texture),
+            ("texture binding",  self.texture_binding,  after.texture_binding),
+            ("matrix mode",      self.matrix_mode,      after.matrix_mode),
+            ("modelview depth",  self.modelview_depth,  after.modelview_depth),
+            ("projection depth", self.projection_depth, after.projection_depth),
+            ("shade model",      self.shade_model,      after.shade_model),
+        ];
+        for (name, a, b) in enums {
+            if a != b { out.push(format!("{}: {:#x} -> {:#x}", name, a, b)); }
+        }
+        if self.depth_mask != after.depth_mask {
+            out.push(format!("depth mask: {} -> {}", self.depth_mask, after.depth_mask));
+        }
+        if self.line_width != after.line_width {
+            out.push(format!("line width: {} -> {}", self.line_width, after.line_width));
+        }
+        if self.color != after.color {
+            out.push(format!("color: {:?} -> {:?}", self.color, after.color));
+        }
+        if self.viewport != after.viewport {
+            out.push(format!("viewport: {:?} -> {:?}", self.viewport, after.viewport));
+        }
+        out
+    }
+}
+
+fn push_bit(out: &mut Vec<String>, name: &str, before: u32, after: u32, bit: usize) {
+    let (a, b) = (before >> bit & 1 != 0, after >> bit & 1 != 0);
+    if a != b {
+        out.push(format!("{}: {} -> {}", name, on_off(a), on_off(b)));
+    }
+}
+
+fn on_off(on: bool) -> &'static str { if on { "on" } else { "off" } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_what_changed() {
+        let before = GlState { caps: 0b101, active_texture: 0x84C0, line_width: 1.0, ..Default::default() };
+        assert!(before.diff(&before).is_empty());
+
+        let after = GlState { caps: 0b100, arrays: 0b1, active_texture: 0x84C1, ..before };
+        assert_eq!(before.diff(&after), [
+            "GL_BLEND: on -> off",
+            "GL_VERTEX_ARRAY: off -> on",
+            "active texture: 0x84c0 -> 0x84c1",
+        ]);
+    }
+}
@@ -0,0 +1,110 @@
+// grenades.rs — Thrown grenades: which kind, and roughly how long until they go off.
+//
+// A thrown grenade is an ordinary entity with MOVETYPE_BOUNCE and the grenade's
+// world model ("models/w_hegrenade.mdl"); the kind comes from the model name.
+// The fuse isn't sent to the client, so it's counted from when the entity was
+// first seen: CS sets every grenade to go off 1.5 s after the throw (a smoke
+// grenade then keeps its entity while it smokes). Entities are usually seen
+// within a frame or two of the throw, so the countdown runs a little late at
+// worst.
+//
+// Model names are only looked up when an entity slot shows a new model.
+
+use std::collections::HashMap;
+
+pub const MOVETYPE_BOUNCE: i32 = 10;   // Thrown grenades (and a few other bouncing things)
+pub const FUSE_MS: u32 = 1500;         // Throw to detonation, for all three kinds
+
+/// A kind of thrown grenade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grenade { He, Flash, Smoke }
+
+impl Grenade {
+    /// The kind whose world model is `model` ("models/w_flashbang.mdl").
+    pub fn from_model(model: &str) -> Option<Self> {
+        let file = model.rsplit('/').next().unwrap_or(model).to_ascii_lowercase();
+        match file.as_str() {
+            "w_hegrenade.mdl"    => Some(Grenade::He),
+            "w_flashbang.mdl"    => Some(Grenade::Flash),
+            "w_smokegrenade.mdl" => Some(Grenade::Smoke),
+            _ => None,
+        }
+    }
+
+    /// Label shown at the grenade.
+    pub fn name(self) -> &'static str {
+        match self {
+            Grenade::He    => "HE",
+            Grenade::Flash => "FLASH",
+            Grenade::Smoke => "SMOKE",
+        }
+    }
+}
+
+struct Seen {
+    model: i32,
+    kind:  Option<Grenade>, // None = not a grenade
+    since: u32,             // When this model was first seen in the slot (ms)
+    last:  u32,             // Frame it was last seen in (ms)
+}
+
+/// Per-entity grenade classification and fuse timing.
+#[derive(Default)]
+pub struct FuseTracker {
+    seen: HashMap<i32, Seen>,
+}
+
+impl FuseTracker {
+    /// Record that entity `idx` shows model `model` as of `now`. Returns its
+    /// kind and the fuse time left (ms, 0 once it's due), or None for anything
+    /// that isn't a grenade. `name` looks the model name up; it's only called
+    /// when the slot shows a model it didn't before.
+    pub fn update(&mut self, idx: i32, model: i32, now: u32,
+                  name: impl FnOnce() -> Option<String>) -> Option<(Grenade, u32)> {
+        let s = self.seen.entry(idx).or_insert(Seen { model: 0, kind: None, since: now, last: now });
+        if s.model != model {
+            *s = Seen {
+                model,
+                kind: name().and_then(|n| Grenade::from_model(&n)),
+                since: now,
+                last: now,
+            };
+        }
+        s.last = now;
+        let kind = s.kind?;
+        Some((kind, FUSE_MS.saturating_sub(now.wrapping_sub(s.since))))
+    }
+
+    /// Forget entities not updated as of `now`, so a slot reused by a new
+    /// throw starts its fuse over.
+    pub fn end_frame(&mut self, now: u32) {
+        self.seen.retain(|_, s| s.last == now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_by_world_model() {
+        assert_eq!(Grenade::from_model("models/w_hegrenade.mdl"), Some(Grenade::He));
+        assert_eq!(Grenade::from_model("models/W_FLASHBANG.MDL"), Some(Grenade::Flash));
+        assert_eq!(Grenade::from_model("models/w_smokegrenade.mdl"), Some(Grenade::Smoke));
+        assert_eq!(Grenade::from_model("models/p_hegrenade.mdl"), None); // Held, not thrown
+        assert_eq!(Grenade::from_model("*12"), None);
+    }
+
+    #[test]
+    fn counts_the_fuse_down_from_first_sight() {
+        let mut t = FuseTracker::default();
+        let he = || Some("models/w_hegrenade.mdl".to_string());
+        assert_eq!(t.update(70, 40, 1000, he), Some((Grenade::He, 1500)));
+        assert_eq!(t.update(70, 40, 1600, || panic!("looked up again")), Some((Grenade::He, 900)));
+        t.end_frame(1600);
+        assert_eq!(t.update(70, 40, 3000, he), Some((Grenade::He, 0)));
+        t.end_frame(3100); // Gone for a frame: the next one seen is a new throw
+        assert_eq!(t.update(70, 40, 3200, he), Some((Grenade::He, 1500)));
+        assert_eq!(t.update(71, 12, 3200, || Some("models/w_ak47.mdl".into())), None);
+    }
+}
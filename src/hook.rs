@@ -7,21 +7,61 @@
 //
 // Flow:
 //   install()   -> Initialize MinHook -> Hook client.dll!Initialize -> Hook wglSwapBuffers
-//   uninstall() -> Remove hooks -> Uninitialize MinHook
+//   uninstall() -> Restore WndProc -> Disable hooks -> Drain detour calls -> Uninitialize MinHook
 //   detour()    -> Called every frame -> esp::on_frame() -> original wglSwapBuffers
+//
+// GL-to-D3D translators (a replacement opengl32.dll in the game folder) don't
+// always present through wglSwapBuffers. When one is detected, gdi32!SwapBuffers
+// is hooked too; whichever of the two actually runs draws the frame, and the
+// chosen path is logged and shown on the status line.
+//
+// With `draw_site = "hud_redraw"`, client.dll!HUD_Redraw is hooked and the
+// overlay is drawn inside the engine's HUD pass instead; the present detours
+// only draw when the HUD pass isn't running (menus, loading screens).
+//
+// Every hook, MinHook detour or hand-made JMP patch (client.dll!Initialize,
+// which has to be caught before MinHook is of any use to the engine table),
+// is registered by name in the `HookManager` with its target and state
+// (enabled, disabled, or failed with the error), so `uninstall()` undoes them
+// newest first from one list and the F7 status page shows them all. A JMP
+// patch keeps its original bytes and its trampoline, which lives in its own
+// VirtualAlloc'd executable page.
+//
+// Unload: every detour holds a `DetourGuard` for its whole call, the call to
+// the original included. `uninstall()` disables all hooks so no new call can
+// enter, waits for the calls in progress to drain, and only then frees the
+// trampolines; the DLL is unloaded only when that succeeded (see lib.rs).
+//
+// glReadPixels is hooked as well: the engine's `snapshot`/`screenshot` commands
+// (and most in-process capture tools) read the framebuffer through it, so a call
+// marks a capture and the overlay sits out the following frames.
 
+use crate::config::{self, DrawSite};
 use crate::entities;
+use crate::error::{self, cstr_name, OverlayError};
 use crate::esp;
+use crate::input;
+use crate::panics::Subsystem;
+use crate::render;
+use crate::time;
+use crate::usermsg;
 use minhook_sys::{
     MH_CreateHook, MH_DisableHook, MH_EnableHook,
-    MH_Initialize, MH_OK, MH_RemoveHook, MH_Uninitialize,
+    MH_Initialize, MH_RemoveHook, MH_Uninitialize,
 };
 use once_cell::sync::OnceCell;
 use std::ffi::c_void;
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
 use winapi::shared::minwindef::BOOL;
 use winapi::shared::windef::HDC;
-use winapi::um::libloaderapi::{GetModuleHandleA, GetProcAddress};
+use winapi::shared::minwindef::HMODULE;
+use winapi::um::libloaderapi::{GetModuleFileNameA, GetModuleHandleA, GetProcAddress};
+use winapi::um::memoryapi::{VirtualAlloc, VirtualFree, VirtualProtect};
+use winapi::um::processthreadsapi::{FlushInstructionCache, GetCurrentProcess};
+use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE};
+use winapi::um::winuser::WindowFromDC;
 
 /// Function signature for the real wglSwapBuffers.
 type WglSwapBuffersFn = unsafe extern "system" fn(HDC) -> BOOL;
@@ -29,62 +69,600 @@ type WglSwapBuffersFn = unsafe extern "system" fn(HDC) -> BOOL;
 /// Stores the original (unhooked) wglSwapBuffers function pointer.
 static ORIGINAL: OnceCell<WglSwapBuffersFn> = OnceCell::new();
 
-/// Stores the address of the hook target (for cleanup).
-static TARGET: OnceCell<usize> = OnceCell::new();
+/// Original gdi32!SwapBuffers (only hooked behind a GL wrapper).
+static GDI_ORIGINAL: OnceCell<WglSwapBuffersFn> = OnceCell::new();
+
+/// Names the present and capture hooks are registered under.
+pub const HOOK_WGL:     &str = "opengl32.dll!wglSwapBuffers";
+const HOOK_GDI:         &str = "gdi32.dll!SwapBuffers";
+const HOOK_READ_PIXELS: &str = "opengl32.dll!glReadPixels";
+
+/// Set while a detour is drawing, so nested present calls (gdi32 -> opengl32)
+/// don't draw the overlay twice.
+static IN_PRESENT: AtomicBool = AtomicBool::new(false);
+
+/// Which present path drew the last frame (PATH_* constants).
+static ACTIVE_PATH: AtomicU8 = AtomicU8::new(PATH_NONE);
+
+const PATH_NONE: u8 = 0;
+const PATH_WGL:  u8 = 1;
+const PATH_GDI:  u8 = 2;
+
+/// Name of the detected GL wrapper (None = the system opengl32.dll).
+static WRAPPER: OnceCell<Option<String>> = OnceCell::new();
+
+/// D3D runtimes whose presence next to a non-system opengl32 confirms a translator.
+const D3D_MODULES: [&[u8]; 4] = [b"d3d8.dll\0", b"d3d9.dll\0", b"d3d11.dll\0", b"dxgi.dll\0"];
+
+/// Function signature for client.dll's HUD_Redraw export.
+type HudRedrawFn = unsafe extern "C" fn(time: f32, intermission: i32) -> i32;
+
+/// Original HUD_Redraw (trampoline).
+static HUD_REDRAW_ORIGINAL: OnceCell<HudRedrawFn> = OnceCell::new();
+
+/// Whether installing the HUD_Redraw hook has been attempted.
+static HUD_REDRAW_TRIED: AtomicBool = AtomicBool::new(false);
+
+/// time::now_ms() of the last HUD pass (0 = never).
+static HUD_LAST_MS: AtomicU32 = AtomicU32::new(0);
+
+/// The HUD pass counts as running if it fired this recently.
+const HUD_ACTIVE_MS: u32 = 250;
+
+/// Function signature for the real glReadPixels.
+pub type GlReadPixelsFn = unsafe extern "system" fn(
+    x: i32, y: i32, w: i32, h: i32, format: u32, ty: u32, data: *mut c_void,
+);
+
+/// Original glReadPixels.
+static READ_PIXELS_ORIGINAL: OnceCell<GlReadPixelsFn> = OnceCell::new();
 
-/// Install all hooks: engine Initialize hook + wglSwapBuffers detour.
-pub unsafe fn install() -> Result<(), i32> {
+/// time::now_ms() of the last glReadPixels call (0 = never).
+static LAST_CAPTURE_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Detour calls in progress (see DetourGuard).
+static ACTIVE_CALLS: AtomicU32 = AtomicU32::new(0);
+
+/// How long `uninstall()` waits for detour calls to return.
+const DRAIN_TIMEOUT_MS: u32 = 2000;
+
+/// Extra wait after draining, for a call that jumped into a detour just
+/// before the hooks were disabled but hasn't taken its guard yet.
+const DRAIN_GRACE_MS: u64 = 20;
+
+/// Install the present hooks: the wglSwapBuffers detour (and gdi32 behind a wrapper).
+pub unsafe fn install() -> Result<(), OverlayError> {
     // Initialize the MinHook library
-    let s = MH_Initialize();
-    if s != MH_OK { return Err(s); }
+    error::mh("MH_Initialize", MH_Initialize())?;
 
-    // Hook client.dll's Initialize export to capture the engine function table.
-    // This gives us access to engine APIs like GetLocalPlayer, GetEntityByIndex, etc.
-    entities::install_initialize_hook();
+    // client.dll's Initialize hook (the engine table) comes after, from the
+    // worker thread: its memory scan shouldn't hold up the first frames
 
     // Locate wglSwapBuffers in the already-loaded opengl32.dll
+    let swap = export_address(HOOK_WGL, b"opengl32.dll\0", b"wglSwapBuffers\0")?;
     let ogl = GetModuleHandleA(b"opengl32.dll\0".as_ptr() as _);
-    if ogl.is_null() { return Err(-1); }
-    let swap = GetProcAddress(ogl, b"wglSwapBuffers\0".as_ptr() as _);
-    if swap.is_null() { return Err(-2); }
 
     // Create a MinHook detour: swap -> our detour, saving the original
-    let mut original = ptr::null_mut::<c_void>();
-    let s = MH_CreateHook(swap as *mut c_void, detour as *mut c_void, &mut original);
-    if s != MH_OK { return Err(s); }
-
-    // Save the original function pointer and target address
+    let original = create(HOOK_WGL, swap, detour as *mut c_void)?;
     let _ = ORIGINAL.set(std::mem::transmute::<*mut c_void, WglSwapBuffersFn>(original));
-    let _ = TARGET.set(swap as usize);
 
     // Activate the hook (starts redirecting calls)
-    let s = MH_EnableHook(swap as *mut c_void);
-    if s != MH_OK { return Err(s); }
+    set_enabled(HOOK_WGL, true)?;
+
+    // Behind a GL wrapper, also take the GDI present path
+    let wrapper = WRAPPER.get_or_init(|| detect_wrapper(ogl));
+    match wrapper {
+        Some(w) => {
+            entities::logf(format!("GL wrapper detected: {}", w));
+            match install_gdi_hook() {
+                Ok(())  => entities::logf("present hooks: wglSwapBuffers + gdi32!SwapBuffers".into()),
+                Err(e)  => entities::logf(format!("gdi32!SwapBuffers hook failed: {}", e)),
+            }
+        }
+        None => entities::logf("present hook: wglSwapBuffers (system opengl32)".into()),
+    }
+
+    // Screenshot detection is optional — the overlay works without it
+    if let Err(e) = install_read_pixels_hook() {
+        entities::logf(format!("glReadPixels hook failed: {}", e));
+    }
 
     Ok(())
 }
 
-/// Remove all hooks and shut down MinHook.
-pub unsafe fn uninstall() {
-    if let Some(&addr) = TARGET.get() {
-        let p = addr as *mut c_void;
-        MH_DisableHook(p);  // Stop redirecting calls
-        MH_RemoveHook(p);   // Free the trampoline
+/// Describe the GL wrapper in use, if opengl32.dll isn't the system one.
+unsafe fn detect_wrapper(ogl: HMODULE) -> Option<String> {
+    let mut buf = [0u8; 260];
+    let n = GetModuleFileNameA(ogl, buf.as_mut_ptr() as _, buf.len() as u32) as usize;
+    if n == 0 { return None; }
+    let path = String::from_utf8_lossy(&buf[..n]).to_string();
+    let lower = path.to_ascii_lowercase();
+    if lower.contains("\\system32\\") || lower.contains("\\syswow64\\") { return None; }
+
+    // Name any D3D runtime the wrapper pulled in
+    let d3d: Vec<&str> = D3D_MODULES.iter()
+        .filter(|m| !GetModuleHandleA(m.as_ptr() as _).is_null())
+        .map(|m| std::str::from_utf8(&m[..m.len() - 1]).unwrap_or("?"))
+        .collect();
+    if d3d.is_empty() {
+        Some(path)
+    } else {
+        Some(format!("{} (via {})", path, d3d.join(", ")))
+    }
+}
+
+/// Hook gdi32!SwapBuffers as the alternative present path.
+unsafe fn install_gdi_hook() -> Result<(), OverlayError> {
+    let target = export_address(HOOK_GDI, b"gdi32.dll\0", b"SwapBuffers\0")?;
+    let original = create(HOOK_GDI, target, gdi_detour as *mut c_void)?;
+    let _ = GDI_ORIGINAL.set(std::mem::transmute::<*mut c_void, WglSwapBuffersFn>(original));
+    set_enabled(HOOK_GDI, true)
+}
+
+/// Status-line label for the present path, only when a GL wrapper is in use
+/// (the normal wglSwapBuffers path isn't worth mentioning).
+pub fn present_path_label() -> Option<&'static str> {
+    if !matches!(WRAPPER.get(), Some(Some(_))) { return None; }
+    Some(match ACTIVE_PATH.load(Ordering::Relaxed) {
+        PATH_WGL => "wrapper: wglSwapBuffers",
+        PATH_GDI => "wrapper: gdi32 SwapBuffers",
+        _        => "wrapper: no present seen",
+    })
+}
+
+/// Hook client.dll!HUD_Redraw once client.dll is loaded (first request only).
+unsafe fn ensure_hud_redraw_hook() {
+    if HUD_REDRAW_TRIED.load(Ordering::Relaxed) { return; }
+    if GetModuleHandleA(b"client.dll\0".as_ptr() as _).is_null() { return; }
+    HUD_REDRAW_TRIED.store(true, Ordering::Relaxed);
+
+    match hook_export(b"client.dll\0", b"HUD_Redraw\0", hk_hud_redraw as *mut c_void) {
+        Ok(orig) => {
+            let _ = HUD_REDRAW_ORIGINAL.set(std::mem::transmute::<*mut c_void, HudRedrawFn>(orig));
+            entities::logf("HUD_Redraw hooked (draw site: HUD pass)".into());
+        }
+        Err(e) => entities::logf(format!("HUD_Redraw hook failed: {}", e)),
+    }
+}
+
+/// Whether the HUD pass is currently drawing the overlay.
+fn hud_pass_active(now: u32) -> bool {
+    let t = HUD_LAST_MS.load(Ordering::Relaxed);
+    t != 0 && now.wrapping_sub(t) <= HUD_ACTIVE_MS
+}
+
+/// HUD_Redraw detour — draws the overlay first so the engine's HUD lands on top.
+unsafe extern "C" fn hk_hud_redraw(time: f32, intermission: i32) -> i32 {
+    let _guard = DetourGuard::enter();
+    if config::get().draw_site == DrawSite::HudRedraw {
+        HUD_LAST_MS.store(time::now_ms(), Ordering::Relaxed);
+        if let Err(e) = std::panic::catch_unwind(|| {
+            esp::on_frame(render::current_dc(), DrawSite::HudRedraw);
+        }) {
+            esp::note_panic(Subsystem::Frame, e.as_ref());
+        }
+    }
+    match HUD_REDRAW_ORIGINAL.get() {
+        Some(f) => f(time, intermission),
+        None    => 1,
+    }
+}
+
+/// Hook opengl32!glReadPixels to notice framebuffer captures.
+unsafe fn install_read_pixels_hook() -> Result<(), OverlayError> {
+    let target = export_address(HOOK_READ_PIXELS, b"opengl32.dll\0", b"glReadPixels\0")?;
+    let original = create(HOOK_READ_PIXELS, target, read_pixels_detour as *mut c_void)?;
+    let _ = READ_PIXELS_ORIGINAL.set(std::mem::transmute::<*mut c_void, GlReadPixelsFn>(original));
+    set_enabled(HOOK_READ_PIXELS, true)
+}
+
+/// Whether a framebuffer capture happened within the last `window_ms`.
+pub fn capture_recent(now: u32, window_ms: u32) -> bool {
+    let t = LAST_CAPTURE_MS.load(Ordering::Relaxed);
+    t != 0 && now.wrapping_sub(t) <= window_ms
+}
+
+/// The real glReadPixels, for the overlay's own captures (capture.rs), which
+/// mustn't count as a screenshot. None while the hook isn't installed.
+pub fn read_pixels_original() -> Option<GlReadPixelsFn> {
+    READ_PIXELS_ORIGINAL.get().copied()
+}
+
+// ============================================================
+// Hook Manager
+// ============================================================
+
+/// Bytes a JMP patch overwrites (JMP rel32).
+const JMP_LEN: usize = 5;
+
+/// Trampoline size: the displaced bytes plus a JMP back, rounded up.
+const TRAMPOLINE_LEN: usize = 16;
+
+/// How a hook is made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookKind {
+    MinHook,   // MinHook detour (the usual way)
+    Jmp,       // Hand-made JMP patch (HookManager::jmp)
+}
+
+/// Where a hook stands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HookState {
+    Enabled,
+    Disabled,
+    Failed(OverlayError),   // Never made; retried by its owner if it retries
+}
+
+/// A registered hook, as shown on the diagnostics page.
+#[derive(Clone, Debug)]
+pub struct HookInfo {
+    pub name:   String,
+    pub kind:   HookKind,
+    pub target: usize,      // 0 = the target was never found
+    pub state:  HookState,
+}
+
+/// A JMP patch's replaced bytes and trampoline page.
+struct JmpPatch {
+    original:   [u8; JMP_LEN],
+    detour:     usize,
+    trampoline: usize,
+}
+
+struct Hook {
+    info: HookInfo,
+    jmp:  Option<JmpPatch>,          // Jmp hooks only
+    live: Option<[u8; JMP_LEN]>,     // The target's first bytes once enabled
+}
+
+/// Every hook this DLL makes, by name, in the order they were made; undone
+/// in reverse order.
+pub struct HookManager {
+    hooks: Vec<Hook>,
+}
+
+/// The hooks made so far.
+static HOOKS: Mutex<HookManager> = Mutex::new(HookManager { hooks: Vec::new() });
+
+impl HookManager {
+    /// Add or replace (a retry) the hook named `name`.
+    fn register(&mut self, name: &str, kind: HookKind, target: usize, state: HookState, jmp: Option<JmpPatch>) {
+        let live = if state == HookState::Enabled { unsafe { first_bytes(target) } } else { None };
+        let hook = Hook { info: HookInfo { name: name.to_string(), kind, target, state }, jmp, live };
+        match self.hooks.iter_mut().find(|h| h.info.name == name) {
+            Some(h) => *h = hook,
+            None => self.hooks.push(hook),
+        }
+    }
+
+    fn fail(&mut self, name: &str, kind: HookKind, target: usize, e: &OverlayError) {
+        self.register(name, kind, target, HookState::Failed(e.clone()), None);
     }
+
+    /// Create a MinHook detour (disabled). Returns the trampoline.
+    unsafe fn create(&mut self, name: &str, target: usize, detour: *mut c_void) -> Result<*mut c_void, OverlayError> {
+        let mut original = ptr::null_mut::<c_void>();
+        let r = error::mh("MH_CreateHook", MH_CreateHook(target as *mut c_void, detour, &mut original));
+        match r {
+            Ok(()) => self.register(name, HookKind::MinHook, target, HookState::Disabled, None),
+            Err(ref e) => self.fail(name, HookKind::MinHook, target, e),
+        }
+        r.map(|()| original)
+    }
+
+    /// Patch a JMP to `detour` over the first JMP_LEN bytes of `target`.
+    /// Those bytes must be whole instructions with no relative operands: they
+    /// are copied as they are into the trampoline, followed by a JMP back.
+    /// `original` gets the trampoline before the patch goes live, so the
+    /// detour never sees it unset.
+    unsafe fn jmp(&mut self, name: &str, target: usize, detour: usize,
+                  original: &AtomicUsize) -> Result<(), OverlayError> {
+        let r = self.make_jmp(target, detour, original);
+        match r {
+            Ok(patch) => {
+                self.register(name, HookKind::Jmp, target, HookState::Enabled, Some(patch));
+                Ok(())
+            }
+            Err(e) => {
+                self.fail(name, HookKind::Jmp, target, &e);
+                Err(e)
+            }
+        }
+    }
+
+    unsafe fn make_jmp(&mut self, target: usize, detour: usize, original: &AtomicUsize) -> Result<JmpPatch, OverlayError> {
+        if !entities::is_readable(target, JMP_LEN) { return Err(OverlayError::NotReadable(target)); }
+        let page = VirtualAlloc(ptr::null_mut(), TRAMPOLINE_LEN, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) as usize;
+        if page == 0 { return Err(OverlayError::AllocFailed(target)); }
+
+        // Trampoline: the displaced bytes, then JMP target+JMP_LEN
+        let mut saved = [0u8; JMP_LEN];
+        ptr::copy_nonoverlapping(target as *const u8, saved.as_mut_ptr(), JMP_LEN);
+        ptr::copy_nonoverlapping(saved.as_ptr(), page as *mut u8, JMP_LEN);
+        write_rel_jmp(page + JMP_LEN, target + JMP_LEN);
+        let mut old = 0u32;
+        VirtualProtect(page as *mut _, TRAMPOLINE_LEN, PAGE_EXECUTE_READ, &mut old);
+        FlushInstructionCache(GetCurrentProcess(), page as *const _, TRAMPOLINE_LEN);
+        original.store(page, Ordering::Release);
+
+        if let Err(e) = patch_code(target, &rel_jmp(target, detour)) {
+            original.store(0, Ordering::Release);
+            VirtualFree(page as *mut _, 0, MEM_RELEASE);
+            return Err(e);
+        }
+        Ok(JmpPatch { original: saved, detour, trampoline: page })
+    }
+
+    /// Enable or disable a made hook.
+    unsafe fn set_enabled(&mut self, name: &str, on: bool) -> Result<(), OverlayError> {
+        let Some(h) = self.hooks.iter_mut().find(|h| h.info.name == name) else {
+            return Err(OverlayError::HookUnknown(name.to_string()));
+        };
+        if let HookState::Failed(e) = &h.info.state { return Err(e.clone()); }
+        let target = h.info.target;
+        let r = match (&h.jmp, on) {
+            (Some(p), true)  => patch_code(target, &rel_jmp(target, p.detour)),
+            (Some(p), false) => patch_code(target, &p.original),
+            (None, true)     => error::mh("MH_EnableHook", MH_EnableHook(target as *mut c_void)),
+            (None, false)    => error::mh("MH_DisableHook", MH_DisableHook(target as *mut c_void)),
+        };
+        if r.is_ok() {
+            h.info.state = if on { HookState::Enabled } else { HookState::Disabled };
+            h.live = if on { first_bytes(target) } else { None };
+        }
+        r
+    }
+
+    /// Whether the enabled hook `name` still starts with the bytes it had
+    /// when enabled. None = no such enabled hook.
+    unsafe fn intact(&self, name: &str) -> Option<bool> {
+        let h = self.hooks.iter().find(|h| h.info.name == name && h.info.state == HookState::Enabled)?;
+        Some(h.live.is_some() && first_bytes(h.info.target) == h.live)
+    }
+
+    /// Disable every hook, newest first. The trampolines stay (a call may
+    /// still be running through one) until `remove_all()`. False if one
+    /// couldn't be disabled.
+    unsafe fn disable_all(&mut self) -> bool {
+        let names: Vec<String> = self.hooks.iter().rev()
+            .filter(|h| h.info.state == HookState::Enabled)
+            .map(|h| h.info.name.clone())
+            .collect();
+        let mut all = true;
+        for name in names {
+            if let Err(e) = self.set_enabled(&name, false) {
+                entities::logf(format!("unhook {}: {}", name, e));
+                all = false;
+            }
+        }
+        all
+    }
+
+    /// Free the trampolines of every disabled hook, newest first, and forget
+    /// them (and the failures).
+    unsafe fn remove_all(&mut self) {
+        while let Some(i) = self.hooks.iter().rposition(|h| h.info.state != HookState::Enabled) {
+            let h = self.hooks.remove(i);
+            match (&h.jmp, &h.info.state) {
+                (_, HookState::Failed(_)) => {}
+                (Some(p), _) => { VirtualFree(p.trampoline as *mut _, 0, MEM_RELEASE); }
+                (None, _) => { MH_RemoveHook(h.info.target as *mut c_void); }
+            }
+        }
+    }
+}
+
+fn manager() -> std::sync::MutexGuard<'static, HookManager> {
+    HOOKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Create a MinHook detour registered as `name`, not enabled yet (store the
+/// trampoline first, then `set_enabled`). Returns the trampoline.
+pub unsafe fn create(name: &str, target: usize, detour: *mut c_void) -> Result<*mut c_void, OverlayError> {
+    manager().create(name, target, detour)
+}
+
+/// Enable or disable the hook registered as `name`.
+pub unsafe fn set_enabled(name: &str, on: bool) -> Result<(), OverlayError> {
+    manager().set_enabled(name, on)
+}
+
+/// Patch `target` to jump to `detour` (see HookManager::jmp); the trampoline
+/// for calling the original is stored in `original`.
+pub unsafe fn jmp_hook(name: &str, target: usize, detour: usize,
+                       original: &AtomicUsize) -> Result<(), OverlayError> {
+    manager().jmp(name, target, detour, original)
+}
+
+/// Record a hook that couldn't be made because its target wasn't found.
+pub fn record_failure(name: &str, e: &OverlayError) {
+    manager().fail(name, HookKind::MinHook, 0, e);
+}
+
+/// Whether the enabled hook `name` is still in place, i.e. nothing has
+/// patched over its JMP since (self-test). None = not enabled.
+pub unsafe fn intact(name: &str) -> Option<bool> {
+    manager().intact(name)
+}
+
+/// Every registered hook, oldest first (diagnostics).
+pub fn hooks() -> Vec<HookInfo> {
+    manager().hooks.iter().map(|h| h.info.clone()).collect()
+}
+
+/// JMP rel32 from `from` to `to`.
+fn rel_jmp(from: usize, to: usize) -> [u8; JMP_LEN] {
+    let rel = (to as i64 - from as i64 - JMP_LEN as i64) as i32;
+    let mut code = [0xE9, 0, 0, 0, 0];
+    code[1..].copy_from_slice(&rel.to_le_bytes());
+    code
+}
+
+/// Write a JMP rel32 into memory we own and can already write.
+unsafe fn write_rel_jmp(at: usize, to: usize) {
+    ptr::copy_nonoverlapping(rel_jmp(at, to).as_ptr(), at as *mut u8, JMP_LEN);
+}
+
+/// The first JMP_LEN bytes at `at` (None = not readable).
+unsafe fn first_bytes(at: usize) -> Option<[u8; JMP_LEN]> {
+    if !entities::is_readable(at, JMP_LEN) { return None; }
+    let mut out = [0u8; JMP_LEN];
+    ptr::copy_nonoverlapping(at as *const u8, out.as_mut_ptr(), JMP_LEN);
+    Some(out)
+}
+
+/// Overwrite code at `at`, lifting and restoring its protection.
+unsafe fn patch_code(at: usize, bytes: &[u8]) -> Result<(), OverlayError> {
+    let mut old = 0u32;
+    if VirtualProtect(at as *mut _, bytes.len(), PAGE_EXECUTE_READWRITE, &mut old) == 0 {
+        return Err(OverlayError::ProtectFailed(at));
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr(), at as *mut u8, bytes.len());
+    VirtualProtect(at as *mut _, bytes.len(), old, &mut old);
+    FlushInstructionCache(GetCurrentProcess(), at as *const _, bytes.len());
+    Ok(())
+}
+
+/// The address of an export, recording a failure under `name` if it's missing.
+unsafe fn export_address(name: &str, module: &'static [u8], export: &'static [u8]) -> Result<usize, OverlayError> {
+    let h = GetModuleHandleA(module.as_ptr() as _);
+    let r = if h.is_null() {
+        Err(OverlayError::ModuleMissing(cstr_name(module).into()))
+    } else {
+        match GetProcAddress(h, export.as_ptr() as _) as usize {
+            0 => Err(OverlayError::ExportMissing { module: cstr_name(module), export: cstr_name(export) }),
+            a => Ok(a),
+        }
+    };
+    if let Err(e) = &r { record_failure(name, e); }
+    r
+}
+
+// ============================================================
+// Unload Safety
+// ============================================================
+
+/// Held by a detour for the duration of its call. Take it first thing.
+pub struct DetourGuard(());
+
+impl DetourGuard {
+    pub fn enter() -> Self {
+        ACTIVE_CALLS.fetch_add(1, Ordering::AcqRel);
+        DetourGuard(())
+    }
+}
+
+impl Drop for DetourGuard {
+    fn drop(&mut self) {
+        ACTIVE_CALLS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Wait up to `timeout_ms` for the detour calls in progress to return.
+fn drain(timeout_ms: u32) -> bool {
+    let start = time::now_ms();
+    while ACTIVE_CALLS.load(Ordering::Acquire) != 0 {
+        if time::now_ms().wrapping_sub(start) > timeout_ms { return false; }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(DRAIN_GRACE_MS));
+    ACTIVE_CALLS.load(Ordering::Acquire) == 0
+}
+
+/// Remove all hooks and shut down MinHook: restore the WndProc and the user
+/// message handlers, disable every hook newest first, wait for the calls in
+/// progress, then free the trampolines. False if calls didn't drain in time
+/// or a patch couldn't be undone; whatever is left stays allocated, and the
+/// DLL must stay loaded.
+pub unsafe fn uninstall() -> bool {
+    input::uninstall();
+    usermsg::uninstall();
+    let disabled = manager().disable_all();
+    // Drain without the lock: a detour still running may take it (status
+    // page, self-test, HUD_Redraw hook install) before it can return
+    if !drain(DRAIN_TIMEOUT_MS) {
+        entities::logf(format!("unhook: {} detour call(s) still running after {} ms",
+            ACTIVE_CALLS.load(Ordering::Relaxed), DRAIN_TIMEOUT_MS));
+        return false;
+    }
+    manager().remove_all();
+    if !disabled { return false; } // A hook into this DLL is still live
     MH_Uninitialize();
+    true
+}
+
+/// Create and enable a MinHook detour on an export of an already-loaded
+/// module, registered as "module!export". Used for client.dll exports (e.g.
+/// HUD_UpdateClientData); requires `install()` to have initialized MinHook.
+/// Returns the trampoline for calling the original.
+pub unsafe fn hook_export(
+    module: &'static [u8],
+    export: &'static [u8],
+    detour: *mut c_void,
+) -> Result<*mut c_void, OverlayError> {
+    let name = format!("{}!{}", cstr_name(module), cstr_name(export));
+    let target = export_address(&name, module, export)?;
+    hook_address(&name, target, detour)
+}
+
+/// Create and enable a MinHook detour named `name` on a function by address
+/// (engine functions reached through a table, or found by scanning). Returns
+/// the trampoline for calling the original.
+pub unsafe fn hook_address(name: &str, target: usize, detour: *mut c_void) -> Result<*mut c_void, OverlayError> {
+    let original = create(name, target, detour)?;
+    set_enabled(name, true)?;
+    Ok(original)
 }
 
 /// Our detour function — called every frame instead of the real wglSwapBuffers.
 /// Draws the ESP overlay, then calls the original to actually swap buffers.
 unsafe extern "system" fn detour(hdc: HDC) -> BOOL {
-    // catch_unwind prevents panics in our overlay code from crashing the game
-    let _ = std::panic::catch_unwind(|| {
-        esp::on_frame(hdc);
-    });
+    let _guard = DetourGuard::enter();
+    present(hdc, PATH_WGL, ORIGINAL.get())
+}
+
+/// gdi32!SwapBuffers detour — same as `detour`, for wrappers that present through GDI.
+unsafe extern "system" fn gdi_detour(hdc: HDC) -> BOOL {
+    let _guard = DetourGuard::enter();
+    present(hdc, PATH_GDI, GDI_ORIGINAL.get())
+}
+
+/// Shared body of the present detours: draw once per frame, then forward.
+unsafe fn present(hdc: HDC, path: u8, original: Option<&WglSwapBuffersFn>) -> BOOL {
+    let outer = !IN_PRESENT.swap(true, Ordering::Acquire);
+    if outer {
+        ACTIVE_PATH.store(path, Ordering::Relaxed);
+        if !input::installed() { input::install(WindowFromDC(hdc)); }
+        let hud = config::get().draw_site == DrawSite::HudRedraw;
+        if hud { ensure_hud_redraw_hook(); }
 
-    // Call the original wglSwapBuffers to display the frame
-    match ORIGINAL.get() {
+        // Draw here unless the HUD pass already did
+        if !hud || !hud_pass_active(time::now_ms()) {
+            // catch_unwind prevents panics in our overlay code from crashing the game
+            if let Err(e) = std::panic::catch_unwind(|| {
+                esp::on_frame(hdc, DrawSite::SwapBuffers);
+            }) {
+                esp::note_panic(Subsystem::Frame, e.as_ref());
+            }
+        }
+    }
+
+    // Call the original to display the frame
+    let r = match original {
         Some(f) => f(hdc),
         None    => 1, // Fallback: pretend success
+    };
+    if outer { IN_PRESENT.store(false, Ordering::Release); }
+    r
+}
+
+/// glReadPixels detour — records the capture time, then reads as normal.
+unsafe extern "system" fn read_pixels_detour(
+    x: i32, y: i32, w: i32, h: i32, format: u32, ty: u32, data: *mut c_void,
+) {
+    let _guard = DetourGuard::enter();
+    LAST_CAPTURE_MS.store(time::now_ms(), Ordering::Relaxed);
+    if let Some(f) = READ_PIXELS_ORIGINAL.get() {
+        f(x, y, w, h, format, ty, data);
     }
 }
@@ -0,0 +1,289 @@
+// input.rs — Keyboard input through a subclass of the game window's WndProc.
+//
+// GetAsyncKeyState sees every key on the system: toggles fire while typing in
+// the console or chat, and even while the game is in the background. Instead,
+// the game window's WndProc is replaced with ours, which sees only the key
+// messages the window actually receives (so only while focused), records
+// key presses for the overlay, and passes everything on to the original.
+//
+// Lifecycle: the subclass is installed from the first frame (that's when the
+// window handle is known) and removed in hook::uninstall().
+//
+// Chat/console typing: the engine's key destination isn't exposed, so typing
+// is tracked from the keys that open and close the message and console lines
+// (Y/U/` in, Enter/Escape out). While typing, presses aren't recorded. A line
+// can also close without those keys (alt-tab, a rebound Y that never opened
+// one), so losing focus or activation ends it too, and it lapses after
+// TYPING_TIMEOUT_MS.
+//
+// Control message: `control::WINDOW_MESSAGE` is registered at install, so
+// tools that can only post window messages can toggle the overlay or switch
+// profiles (the wParam commands are listed in control.rs).
+//
+// Menus: while a menu has captured input, navigation keys and mouse buttons
+// are consumed here and never reach the game, the mouse position is tracked in
+// client coordinates, and client.dll's mouse look is switched off
+// (IN_DeactivateMouse) so the cursor can move freely.
+
+use crate::config;
+use crate::control;
+use crate::entities;
+use crate::hook;
+#[cfg(feature = "ipc")]
+use crate::ipc;
+use crate::time;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicUsize, Ordering};
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+#[cfg(feature = "menu")]
+use winapi::um::libloaderapi::{GetModuleHandleA, GetProcAddress};
+use winapi::um::winuser::{
+    CallWindowProcA, GetAsyncKeyState, RegisterWindowMessageA, SetWindowLongA, WNDPROC,
+    WM_ACTIVATE, WM_CHAR, WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP,
+    WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SYSKEYDOWN,
+};
+
+// ============================================================
+// Constants
+// ============================================================
+
+const GWL_WNDPROC: i32 = -4;        // SetWindowLong index of the window procedure
+const TYPING_TIMEOUT_MS: u32 = 60_000; // A chat / console line counted as open this long is taken as closed
+
+pub const VK_LBUTTON: i32 = 0x01;   // Pseudo key for left clicks (take_press)
+
+const VK_RETURN: u32 = 0x0D;
+const VK_ESCAPE: u32 = 0x1B;
+const VK_UP:     u32 = 0x26;
+const VK_DOWN:   u32 = 0x28;
+const VK_LEFT:   u32 = 0x25;
+const VK_RIGHT:  u32 = 0x27;
+const VK_Y:      u32 = 0x59;        // Default `messagemode` bind
+const VK_U:      u32 = 0x55;        // Default `messagemode2` bind (team chat)
+const VK_OEM_3:  u32 = 0xC0;        // ` / ~ — console toggle
+
+/// Keys consumed while a menu has captured the keyboard.
+const NAV_KEYS: [u32; 6] = [VK_UP, VK_DOWN, VK_LEFT, VK_RIGHT, VK_RETURN, VK_ESCAPE];
+
+// ============================================================
+// State
+// ============================================================
+
+/// Subclassed window and the WndProc it had before (0 = not installed).
+static HWND_SUB: AtomicUsize = AtomicUsize::new(0);
+static ORIG_WNDPROC: AtomicUsize = AtomicUsize::new(0);
+
+/// Per virtual-key "pressed since last taken" flags.
+static PRESSED: [AtomicBool; 256] = [const { AtomicBool::new(false) }; 256];
+
+/// The chat or console input line is open, and since when (time::now_ms()).
+static TYPING: AtomicBool = AtomicBool::new(false);
+static TYPING_SINCE: AtomicU32 = AtomicU32::new(0);
+
+/// A menu has captured input (navigation keys and mouse buttons are swallowed).
+static MENU_CAPTURE: AtomicBool = AtomicBool::new(false);
+
+/// Last mouse position (client coordinates) and left button state.
+static MOUSE_X: AtomicI32 = AtomicI32::new(0);
+static MOUSE_Y: AtomicI32 = AtomicI32::new(0);
+static MOUSE_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Registered control message id (0 = not registered).
+static CONTROL_MSG: AtomicU32 = AtomicU32::new(0);
+
+// ============================================================
+// Install / Uninstall
+// ============================================================
+
+/// Subclass `hwnd` (no-op if already installed or `hwnd` is null).
+pub unsafe fn install(hwnd: HWND) {
+    if hwnd.is_null() || HWND_SUB.load(Ordering::Acquire) != 0 { return; }
+    let prev = SetWindowLongA(hwnd, GWL_WNDPROC, wndproc as *const () as usize as _);
+    if prev == 0 {
+        entities::log("WndProc subclass failed");
+        return;
+    }
+    ORIG_WNDPROC.store(prev as usize, Ordering::Release);
+    HWND_SUB.store(hwnd as usize, Ordering::Release);
+    entities::logf(format!("WndProc subclassed (hwnd={:#x})", hwnd as usize));
+
+    if config::get().control_window_message {
+        let name = std::ffi::CString::new(control::WINDOW_MESSAGE).unwrap_or_default();
+        let id = RegisterWindowMessageA(name.as_ptr());
+        CONTROL_MSG.store(id, Ordering::Relaxed);
+        entities::logf(format!("control window message '{}' = {:#06x}", control::WINDOW_MESSAGE, id));
+    }
+}
+
+/// Restore the original WndProc.
+pub unsafe fn uninstall() {
+    let hwnd = HWND_SUB.swap(0, Ordering::AcqRel);
+    let orig = ORIG_WNDPROC.load(Ordering::Acquire);
+    if hwnd != 0 && orig != 0 {
+        SetWindowLongA(hwnd as HWND, GWL_WNDPROC, orig as _);
+    }
+}
+
+/// Whether the subclass is active (callers fall back to GetAsyncKeyState otherwise).
+pub fn installed() -> bool {
+    HWND_SUB.load(Ordering::Acquire) != 0
+}
+
+// ============================================================
+// Queries
+// ============================================================
+
+/// Whether `vk` was pressed since the last call (consumes the press).
+pub fn take_press(vk: i32) -> bool {
+    match PRESSED.get(vk as usize) {
+        Some(flag) => flag.swap(false, Ordering::AcqRel),
+        None => false,
+    }
+}
+
+/// Whether virtual key `vk` went down since the last call (0 = no key): a
+/// press recorded by the subclass, or, before it's installed, a change in
+/// GetAsyncKeyState against `prev` (the caller's state from last time).
+pub fn key_pressed(vk: u32, prev: &AtomicBool) -> bool {
+    let vk = vk as i32;
+    if vk <= 0 || vk > 0xFF { return false; }
+    if installed() {
+        take_press(vk)
+    } else {
+        let down = unsafe { (GetAsyncKeyState(vk) as u16) & 0x8000 != 0 };
+        let was = prev.swap(down, Ordering::Relaxed);
+        down && !was
+    }
+}
+
+/// Whether virtual key `vk` is held down now (modifiers like Shift).
+pub fn key_down(vk: u32) -> bool {
+    vk > 0 && vk <= 0xFF && unsafe { (GetAsyncKeyState(vk as i32) as u16) & 0x8000 != 0 }
+}
+
+/// Drop every pending press (e.g. so keys hit before a menu opened don't act in it).
+#[cfg(feature = "menu")]
+pub fn clear_presses() {
+    for flag in PRESSED.iter() { flag.store(false, Ordering::Relaxed); }
+}
+
+/// Whether the chat or console line is open (as far as the keys tell).
+pub fn typing() -> bool {
+    TYPING.load(Ordering::Relaxed)
+        && time::now_ms().wrapping_sub(TYPING_SINCE.load(Ordering::Relaxed)) < TYPING_TIMEOUT_MS
+}
+
+/// Capture (or release) input for a menu. Mouse look is suspended while captured.
+#[cfg(feature = "menu")]
+pub fn set_menu_capture(on: bool) {
+    if MENU_CAPTURE.swap(on, Ordering::Relaxed) == on { return; }
+    MOUSE_DOWN.store(false, Ordering::Relaxed);
+    unsafe { set_mouse_look(!on); }
+}
+
+/// Last mouse position in client coordinates.
+#[cfg(feature = "menu")]
+pub fn mouse_pos() -> (f32, f32) {
+    (MOUSE_X.load(Ordering::Relaxed) as f32, MOUSE_Y.load(Ordering::Relaxed) as f32)
+}
+
+/// Whether the left mouse button is held (tracked only while captured).
+#[cfg(feature = "menu")]
+pub fn mouse_down() -> bool {
+    MOUSE_DOWN.load(Ordering::Relaxed)
+}
+
+/// Switch client.dll's mouse look on or off through its IN_ActivateMouse /
+/// IN_DeactivateMouse exports.
+#[cfg(feature = "menu")]
+unsafe fn set_mouse_look(on: bool) {
+    let client = GetModuleHandleA(b"client.dll\0".as_ptr() as _);
+    if client.is_null() { return; }
+    let name: &[u8] = if on { b"IN_ActivateMouse\0" } else { b"IN_DeactivateMouse\0" };
+    let f = GetProcAddress(client, name.as_ptr() as _);
+    if f.is_null() { return; }
+    let f: unsafe extern "C" fn() = std::mem::transmute(f);
+    f();
+}
+
+// ============================================================
+// Window Procedure
+// ============================================================
+
+/// Our WndProc: record key presses, swallow menu keys, forward the rest.
+unsafe extern "system" fn wndproc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let _guard = hook::DetourGuard::enter();
+    let control_msg = CONTROL_MSG.load(Ordering::Relaxed);
+    if control_msg != 0 && msg == control_msg { return on_control_message(wparam, lparam); }
+    let vk = wparam as u32;
+    match msg {
+        WM_KEYDOWN | WM_SYSKEYDOWN => {
+            let repeat = (lparam as u32) & (1 << 30) != 0; // Previous key state: held
+            if !repeat { on_key_down(vk); }
+            if MENU_CAPTURE.load(Ordering::Relaxed) && NAV_KEYS.contains(&vk) { return 0; }
+        }
+        WM_KEYUP | WM_CHAR => {
+            // Keep the matching release/char of a swallowed key away from the game too
+            if MENU_CAPTURE.load(Ordering::Relaxed) && msg == WM_KEYUP && NAV_KEYS.contains(&vk) { return 0; }
+            if MENU_CAPTURE.load(Ordering::Relaxed) && msg == WM_CHAR && (vk == VK_RETURN || vk == VK_ESCAPE) { return 0; }
+        }
+        // Focus or activation moving (alt-tab, another window) closes the engine's input line
+        WM_KILLFOCUS | WM_ACTIVATE => TYPING.store(false, Ordering::Relaxed),
+        WM_MOUSEMOVE => {
+            MOUSE_X.store((lparam & 0xFFFF) as i16 as i32, Ordering::Relaxed);
+            MOUSE_Y.store(((lparam >> 16) & 0xFFFF) as i16 as i32, Ordering::Relaxed);
+        }
+        WM_LBUTTONDOWN | WM_LBUTTONUP | WM_RBUTTONDOWN | WM_RBUTTONUP | WM_MOUSEWHEEL
+            if MENU_CAPTURE.load(Ordering::Relaxed) =>
+        {
+            // Clicks go to the menu, not the game
+            if msg == WM_LBUTTONDOWN {
+                MOUSE_DOWN.store(true, Ordering::Relaxed);
+                PRESSED[VK_LBUTTON as usize].store(true, Ordering::Release);
+            } else if msg == WM_LBUTTONUP {
+                MOUSE_DOWN.store(false, Ordering::Relaxed);
+            }
+            return 0;
+        }
+        _ => {}
+    }
+
+    let orig: WNDPROC = std::mem::transmute(ORIG_WNDPROC.load(Ordering::Acquire));
+    CallWindowProcA(orig, hwnd, msg, wparam, lparam)
+}
+
+/// Carry out a control message like the control pipe would. Returns 1 for a
+/// known command, 0 otherwise (always, in a build without `ipc`).
+#[cfg_attr(not(feature = "ipc"), allow(unused_variables))]
+fn on_control_message(wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if wparam == control::MSG_DUPLICATE {
+        entities::log("a second copy of the overlay was injected; it unloaded itself");
+    }
+    #[cfg(feature = "ipc")]
+    {
+        let profiles = config::get().profile_names.clone();
+        if let Some(request) = control::message_request(wparam, lparam, &profiles) {
+            if let (_, Err(e)) = ipc::run(request) { entities::logf(format!("control message: {}", e)); }
+            return 1;
+        }
+    }
+    0
+}
+
+/// Track the typing state and record the press.
+fn on_key_down(vk: u32) {
+    if typing() {
+        if vk == VK_RETURN || vk == VK_ESCAPE || vk == VK_OEM_3 {
+            TYPING.store(false, Ordering::Relaxed);
+        }
+        return;
+    }
+    TYPING.store(false, Ordering::Relaxed); // It may have lapsed (TYPING_TIMEOUT_MS)
+    if vk == VK_Y || vk == VK_U || vk == VK_OEM_3 {
+        TYPING_SINCE.store(time::now_ms(), Ordering::Relaxed);
+        TYPING.store(true, Ordering::Relaxed);
+    }
+    if let Some(flag) = PRESSED.get(vk as usize) {
+        flag.store(true, Ordering::Release);
+    }
+}
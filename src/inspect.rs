@@ -0,0 +1,101 @@
+// inspect.rs — Rows of the memory inspector (diagnostics page 4).
+//
+// The inspector shows a window of one player's cl_entity_t, live, ROW_BYTES
+// to a line, as hex bytes, 32-bit integers or floats. Known fields are named
+// at the end of the row they start in, at the offsets in use (offsets.rs), so
+// a value nudged in the offset tuner moves its name here too: when the name
+// sits on a row of plausible coordinates, the offset is right.
+//
+// Reading the memory is entities.rs's job; this file only lays the rows out.
+
+use crate::offsets::Offset;
+use crate::player::{ES_ANGLES, ES_IUSER1, ES_IUSER2, ES_MAXS, ES_ORIGIN, ES_USEHULL, ES_WEAPONMODEL};
+use crate::world::{ES_MESSAGENUM, ES_MODELINDEX};
+
+/// Bytes per row.
+pub const ROW_BYTES: usize = 8;
+
+/// How a row's bytes are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View { Hex, Int, Float }
+
+impl View {
+    pub const ALL: [View; 3] = [View::Hex, View::Int, View::Float];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Hex   => "hex",
+            View::Int   => "int32",
+            View::Float => "float",
+        }
+    }
+}
+
+/// Known cl_entity_t fields at the offsets in use: (offset, name), by offset.
+pub fn fields() -> Vec<(usize, &'static str)> {
+    let cs = Offset::Curstate.get();
+    let mut out = vec![
+        (0x00, "index"),
+        (0x04, "player"),
+        (Offset::CurPos.get(), "current_position"),
+        (Offset::PosHistory.get(), "ph[]"),
+        (Offset::Origin.get(), "origin"),
+        (cs, "curstate"),
+        (cs + ES_MESSAGENUM, "cs.messagenum"),
+        (cs + ES_ORIGIN, "cs.origin"),
+        (cs + ES_ANGLES, "cs.angles"),
+        (cs + ES_MODELINDEX, "cs.modelindex"),
+        (cs + ES_MAXS, "cs.maxs"),
+        (cs + ES_WEAPONMODEL, "cs.weaponmodel"),
+        (cs + ES_USEHULL, "cs.usehull"),
+        (cs + ES_IUSER1, "cs.iuser1"),
+        (cs + ES_IUSER2, "cs.iuser2"),
+    ];
+    out.sort_by_key(|f| f.0);
+    out
+}
+
+/// The first known field after `offset` (wrapping to the first), for jumping
+/// between them.
+pub fn next_field(offset: usize, fields: &[(usize, &'static str)]) -> Option<usize> {
+    fields.iter().map(|f| f.0).find(|&o| o > offset).or(fields.first().map(|f| f.0))
+}
+
+/// One row at `offset`: the offset, `bytes` (None = unreadable) in `view`,
+/// and the names of the fields starting in it.
+pub fn format_row(offset: usize, bytes: Option<&[u8]>, view: View, fields: &[(usize, &'static str)]) -> String {
+    let values = match bytes {
+        None => "?? ".repeat(ROW_BYTES).trim_end().to_string(),
+        Some(b) => match view {
+            View::Hex => b.iter().map(|x| format!("{:02x}", x)).collect::<Vec<_>>().join(" "),
+            View::Int => b.chunks_exact(4)
+                .map(|c| format!("{:>11}", i32::from_le_bytes([c[0], c[1], c[2], c[3]])))
+                .collect::<Vec<_>>().join(" "),
+            View::Float => b.chunks_exact(4)
+                .map(|c| format!("{:>11.2}", f32::from_le_bytes([c[0], c[1], c[2], c[3]])))
+                .collect::<Vec<_>>().join(" "),
+        },
+    };
+    let names: Vec<&str> = fields.iter()
+        .filter(|f| (offset..offset + ROW_BYTES).contains(&f.0))
+        .map(|f| f.1)
+        .collect();
+    format!("+{:04x}  {}  {}", offset, values, names.join(", ")).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_rows_with_field_names() {
+        let f = [(0x00, "index"), (0x04, "player"), (0x10, "origin")];
+        let b = [1, 0, 0, 0, 0, 0, 0x80, 0x3F];
+        assert_eq!(format_row(0, Some(&b), View::Hex, &f), "+0000  01 00 00 00 00 00 80 3f  index, player");
+        assert_eq!(format_row(0, Some(&b), View::Int, &f), "+0000            1  1065353216  index, player");
+        assert_eq!(format_row(8, Some(&b), View::Float, &f), "+0008         0.00        1.00");
+        assert_eq!(format_row(0x10, None, View::Hex, &f), "+0010  ?? ?? ?? ?? ?? ?? ?? ??  origin");
+        assert_eq!(next_field(0x04, &f), Some(0x10));
+        assert_eq!(next_field(0x10, &f), Some(0x00));
+    }
+}
@@ -0,0 +1,244 @@
+// ipc.rs — The control pipe: settings, profiles and visibility from outside the game.
+//
+// A background thread serves `control::PIPE_NAME` (local clients only, one
+// at a time) for the controller app in `controller/` and for scripts; the
+// protocol is in control.rs. The pipe is non-blocking and polled, like the
+// worker loop, so `stop()` can end the thread before the DLL unloads.
+
+use crate::bundle;
+use crate::config::{self, Config};
+use crate::control::{self, Request};
+use crate::entities;
+use crate::esp;
+use crate::selftest;
+use crate::time::Stopwatch;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use winapi::shared::ntdef::HANDLE;
+use winapi::shared::winerror::{ERROR_NO_DATA, ERROR_PIPE_CONNECTED};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::fileapi::{ReadFile, WriteFile};
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::namedpipeapi::{ConnectNamedPipe, DisconnectNamedPipe};
+use winapi::um::winbase::{
+    CreateNamedPipeW, PIPE_ACCESS_DUPLEX, PIPE_NOWAIT, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE,
+};
+
+const POLL: Duration = Duration::from_millis(50);
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+const PIPE_BUFFER: u32 = 4096;
+const SELFTEST_WAIT: Duration = Duration::from_millis(2000);   // For the next overlay frame to run it
+const BUNDLE_WAIT: Duration = Duration::from_millis(5000);     // For the next frame and the archive write
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+static THREAD: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Start serving the pipe, if `[control] pipe` is on. Called once by the
+/// worker thread.
+pub fn start(cfg: &Config) {
+    if !cfg.control_pipe || RUNNING.swap(true, Ordering::AcqRel) { return; }
+    let handle = std::thread::spawn(|| unsafe { serve() });
+    if let Ok(mut t) = THREAD.lock() { *t = Some(handle); }
+}
+
+/// Stop serving and wait for the thread (at most one poll interval).
+pub fn stop() {
+    RUNNING.store(false, Ordering::Release);
+    if let Some(handle) = THREAD.lock().ok().and_then(|mut t| t.take()) {
+        let _ = handle.join();
+    }
+}
+
+/// Whether a client is connected now (diagnostics).
+pub fn connected() -> bool {
+    CONNECTED.load(Ordering::Relaxed)
+}
+
+unsafe fn serve() {
+    let name: Vec<u16> = control::PIPE_NAME.encode_utf16().chain(Some(0)).collect();
+    let pipe = CreateNamedPipeW(
+        name.as_ptr(), PIPE_ACCESS_DUPLEX,
+        PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_NOWAIT | PIPE_REJECT_REMOTE_CLIENTS,
+        1, PIPE_BUFFER, PIPE_BUFFER, 0, ptr::null_mut(),
+    );
+    if pipe == INVALID_HANDLE_VALUE {
+        entities::logf(format!("control pipe: can't create {} ({})", control::PIPE_NAME, std::io::Error::last_os_error()));
+        return;
+    }
+    entities::logf(format!("control pipe: listening on {}", control::PIPE_NAME));
+
+    let mut pending: Vec<u8> = Vec::new();
+    while RUNNING.load(Ordering::Acquire) {
+        // --- Wait for a client (nonzero = free for a new client, still listening) ---
+        if !CONNECTED.load(Ordering::Relaxed) {
+            if ConnectNamedPipe(pipe, ptr::null_mut()) == 0 {
+                match GetLastError() {
+                    ERROR_PIPE_CONNECTED => {
+                        CONNECTED.store(true, Ordering::Relaxed);
+                        pending.clear();
+                    }
+                    ERROR_NO_DATA => { DisconnectNamedPipe(pipe); } // Came and went
+                    _ => {}                                         // Listening
+                }
+            }
+            if !CONNECTED.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL);
+                continue;
+            }
+        }
+
+        // --- Read what's there; each complete line is a request ---
+        let mut buf = [0u8; 1024];
+        let mut read = 0u32;
+        if ReadFile(pipe, buf.as_mut_ptr() as _, buf.len() as u32, &mut read, ptr::null_mut()) == 0 {
+            if GetLastError() != ERROR_NO_DATA { disconnect(pipe); } // Closed by the client
+            std::thread::sleep(POLL);
+            continue;
+        }
+        pending.extend_from_slice(&buf[..read as usize]);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let reply = handle(&String::from_utf8_lossy(&line));
+            if !write_all(pipe, reply.as_bytes()) {
+                disconnect(pipe);
+                break;
+            }
+        }
+        if pending.len() > control::MAX_LINE {
+            pending.clear();
+            if !write_all(pipe, control::reply(&[], Err("request too long".to_string())).as_bytes()) { disconnect(pipe); }
+        }
+        if read == 0 { std::thread::sleep(POLL); }
+    }
+    disconnect(pipe);
+    CloseHandle(pipe);
+}
+
+unsafe fn disconnect(pipe: HANDLE) {
+    DisconnectNamedPipe(pipe);
+    CONNECTED.store(false, Ordering::Relaxed);
+}
+
+/// Write all of `data` to the non-blocking pipe, giving up after WRITE_TIMEOUT.
+unsafe fn write_all(pipe: HANDLE, mut data: &[u8]) -> bool {
+    let start = Stopwatch::start();
+    while !data.is_empty() {
+        let mut written = 0u32;
+        if WriteFile(pipe, data.as_ptr() as _, data.len() as u32, &mut written, ptr::null_mut()) == 0 {
+            return false;
+        }
+        data = &data[written as usize..];
+        if written == 0 {
+            if start.elapsed() > WRITE_TIMEOUT { return false; }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+    true
+}
+
+/// Carry out one request line and build its reply.
+fn handle(line: &str) -> String {
+    match Request::parse(line) {
+        Ok(request) => {
+            let (data, result) = run(request);
+            control::reply(&data, result)
+        }
+        Err(e) => control::reply(&[], Err(e)),
+    }
+}
+
+/// Carry out a request (from the pipe or a window message): its data lines
+/// and result.
+pub fn run(request: Request) -> (Vec<String>, Result<(), String>) {
+    let mut data = Vec::new();
+    let result = match request {
+        Request::Status => {
+            let cfg = config::get();
+            data.push(format!("profile {}", cfg.profile));
+            data.push(format!("visible {}", if esp::visible() { "on" } else { "off" }));
+            data.push(format!("config {}", config::file_path().display()));
+            data.push(format!("profiles {}", cfg.profile_names.join(",")));
+            Ok(())
+        }
+        Request::Config => {
+            data.extend(config::file_settings().into_iter().map(|(k, v)| format!("{} = {}", k, v)));
+            Ok(())
+        }
+        Request::Log(n) => {
+            data = entities::recent_log(n);
+            Ok(())
+        }
+        Request::Set { key, value } => config::set_live(&key, &value),
+        Request::Save { key, value } => config::save_setting(&key, &value),
+        Request::Profile(None) => {
+            config::next_profile();
+            Ok(())
+        }
+        Request::Profile(Some(name)) => {
+            let name = if name == "none" { String::new() } else { name };
+            if name.is_empty() || config::get().profile_names.contains(&name) {
+                config::select_profile(&name);
+                Ok(())
+            } else {
+                Err(format!("no profile '{}'", name))
+            }
+        }
+        Request::Toggle => {
+            esp::toggle_visible();
+            Ok(())
+        }
+        Request::Visible(on) => {
+            esp::set_visible(on);
+            Ok(())
+        }
+        Request::Eject if config::command_registered() => {
+            Err("a console command (esp_profile, esp_mark, esp_bundle) is registered; the DLL can't be unloaded".to_string())
+        }
+        Request::Eject => {
+            crate::request_eject();
+            Ok(())
+        }
+        Request::SelfTest => {
+            let before = selftest::runs();
+            selftest::request();
+            let start = Stopwatch::start();
+            while selftest::runs() == before && start.elapsed() < SELFTEST_WAIT {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            match selftest::last(SELFTEST_WAIT.as_millis() as u32) {
+                Some(checks) if selftest::runs() != before => {
+                    for c in &checks {
+                        data.push(match &c.result {
+                            Ok(detail) => format!("{} pass {}", c.name, detail),
+                            Err(e)     => format!("{} FAIL {}", c.name, e),
+                        });
+                    }
+                    Ok(())
+                }
+                _ => Err(format!("no overlay frame within {} ms (the present hook isn't running)",
+                                 SELFTEST_WAIT.as_millis())),
+            }
+        }
+        Request::Bundle => {
+            let before = bundle::runs();
+            bundle::request();
+            let start = Stopwatch::start();
+            while bundle::runs() == before && start.elapsed() < BUNDLE_WAIT {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            match bundle::last() {
+                Some(Ok(path)) if bundle::runs() != before => {
+                    data.push(path);
+                    Ok(())
+                }
+                Some(Err(e)) if bundle::runs() != before => Err(e),
+                _ => Err(format!("no bundle within {} ms (the present hook isn't running)", BUNDLE_WAIT.as_millis())),
+            }
+        }
+    };
+    (data, result)
+}
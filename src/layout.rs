@@ -0,0 +1,200 @@
+// layout.rs — Where the overlay's screen panels go.
+//
+// Each panel (status lines, minimap, diagnostics) is placed by a corner anchor
+// and an offset in pixels inward from that corner, instead of fixed pixel
+// coordinates that collide with the game's HUD at some resolutions. Panels
+// sharing a corner stack away from it in the order they're placed — downward
+// from the top corners, upward from the bottom ones — with a gap between them,
+// so they never overlap however large each one gets.
+//
+// A panel's offset is a minimum: it starts there, or after the panels already
+// in its corner, whichever is further in.
+//
+// Text panels can be given as templates with `{name}` placeholders (the status
+// line's `{fps}`, `{map}`, ...), filled in each frame by `expand`. Player
+// labels use `expand_words`, where a placeholder without a value takes the
+// word around it along ("[{weapon}]", "{hp}hp"), so optional fields don't
+// leave stray brackets or units behind.
+
+/// Screen corner a panel is attached to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor { TopLeft, TopRight, BottomLeft, BottomRight }
+
+impl Anchor {
+    /// Parse a config name ("top_left", "bottom_right", ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top_left"     => Some(Anchor::TopLeft),
+            "top_right"    => Some(Anchor::TopRight),
+            "bottom_left"  => Some(Anchor::BottomLeft),
+            "bottom_right" => Some(Anchor::BottomRight),
+            _ => None,
+        }
+    }
+
+    /// Whether the panel hangs off the right edge (text is right-aligned).
+    pub fn is_right(self) -> bool {
+        matches!(self, Anchor::TopRight | Anchor::BottomRight)
+    }
+
+    /// Whether the panel sits on the bottom edge (stacks upward).
+    pub fn is_bottom(self) -> bool {
+        matches!(self, Anchor::BottomLeft | Anchor::BottomRight)
+    }
+}
+
+/// Where a panel goes: a corner and an inward offset from it (pixels).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    pub anchor: Anchor,
+    pub offset: [f32; 2],
+}
+
+impl Placement {
+    pub const fn new(anchor: Anchor, x: f32, y: f32) -> Self {
+        Self { anchor, offset: [x, y] }
+    }
+}
+
+/// Places one frame's panels, stacking those that share a corner.
+pub struct Layout {
+    w:    f32,
+    h:    f32,
+    gap:  f32,
+    used: [f32; 4], // Distance from the screen edge taken so far, per Anchor
+}
+
+impl Layout {
+    pub fn new(screen_w: f32, screen_h: f32, gap: f32) -> Self {
+        Self { w: screen_w, h: screen_h, gap: gap.max(0.0), used: [0.0; 4] }
+    }
+
+    /// Place a `w` x `h` panel; returns its rect [x0, y0, x1, y1]. The panel
+    /// is kept on screen where it fits.
+    pub fn place(&mut self, p: Placement, w: f32, h: f32) -> [f32; 4] {
+        let slot = &mut self.used[p.anchor as usize];
+        let depth = p.offset[1].max(*slot);
+        *slot = depth + h + self.gap;
+
+        let x0 = if p.anchor.is_right() { self.w - p.offset[0] - w } else { p.offset[0] };
+        let y0 = if p.anchor.is_bottom() { self.h - depth - h } else { depth };
+        let x0 = x0.min(self.w - w).max(0.0);
+        let y0 = y0.min(self.h - h).max(0.0);
+        [x0, y0, x0 + w, y0 + h]
+    }
+}
+
+/// Fill the `{name}` placeholders in `template` from `lookup`. Placeholders
+/// `lookup` doesn't know are kept as written, so a typo shows up on screen.
+/// Trailing spaces left by empty values are trimmed.
+pub fn expand(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let tail = &rest[open..];
+        match tail.find('}').and_then(|close| lookup(&tail[1..close]).map(|v| (close, v))) {
+            Some((close, value)) => {
+                out.push_str(&value);
+                rest = &tail[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// `expand` word by word (words are split on whitespace, which is kept as
+/// written): a word with a placeholder whose value is empty is left out,
+/// together with the whitespace before it.
+pub fn expand_words(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while !rest.is_empty() {
+        let word_at = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+        let (space, tail) = rest.split_at(word_at);
+        let word_len = tail.find(char::is_whitespace).unwrap_or(tail.len());
+        let (word, next) = tail.split_at(word_len);
+        rest = next;
+        if word.is_empty() { break; }
+
+        let empty = std::cell::Cell::new(false);
+        let value = expand(word, |name| {
+            let v = lookup(name);
+            if v.as_deref() == Some("") { empty.set(true); }
+            v
+        });
+        if empty.get() { continue; }
+        if !out.is_empty() { out.push_str(space); }
+        out.push_str(&value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_to_each_corner() {
+        let mut l = Layout::new(800.0, 600.0, 4.0);
+        assert_eq!(l.place(Placement::new(Anchor::TopLeft, 6.0, 14.0), 100.0, 20.0), [6.0, 14.0, 106.0, 34.0]);
+        assert_eq!(l.place(Placement::new(Anchor::TopRight, 6.0, 6.0), 100.0, 20.0), [694.0, 6.0, 794.0, 26.0]);
+        assert_eq!(l.place(Placement::new(Anchor::BottomLeft, 0.0, 10.0), 50.0, 50.0), [0.0, 540.0, 50.0, 590.0]);
+        assert_eq!(l.place(Placement::new(Anchor::BottomRight, 10.0, 0.0), 50.0, 50.0), [740.0, 550.0, 790.0, 600.0]);
+    }
+
+    #[test]
+    fn stacks_panels_sharing_a_corner() {
+        let mut l = Layout::new(800.0, 600.0, 4.0);
+        let a = l.place(Placement::new(Anchor::TopLeft, 6.0, 14.0), 300.0, 56.0);
+        let b = l.place(Placement::new(Anchor::TopLeft, 6.0, 20.0), 200.0, 200.0);
+        assert_eq!(b[1], a[3] + 4.0); // Pushed below, not overlapping
+        let c = l.place(Placement::new(Anchor::TopLeft, 6.0, 400.0), 10.0, 10.0);
+        assert_eq!(c[1], 400.0);      // Already clear: keeps its offset
+        let d = l.place(Placement::new(Anchor::BottomLeft, 6.0, 0.0), 10.0, 30.0);
+        let e = l.place(Placement::new(Anchor::BottomLeft, 6.0, 0.0), 10.0, 30.0);
+        assert_eq!((d[1], e[1]), (570.0, 536.0)); // Upward from the bottom
+    }
+
+    #[test]
+    fn keeps_panels_on_screen() {
+        let mut l = Layout::new(320.0, 240.0, 0.0);
+        assert_eq!(l.place(Placement::new(Anchor::TopRight, 6.0, 6.0), 400.0, 20.0)[0], 0.0);
+        assert_eq!(l.place(Placement::new(Anchor::TopLeft, 6.0, 300.0), 10.0, 20.0)[1], 220.0);
+        assert_eq!(Anchor::from_name("bottom_right"), Some(Anchor::BottomRight));
+        assert_eq!(Anchor::from_name("middle"), None);
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        let lookup = |name: &str| match name {
+            "fps"  => Some("144".to_string()),
+            "path" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(expand("{fps} fps  {path}", lookup), "144 fps");
+        assert_eq!(expand("{nope} {fps}", lookup), "{nope} 144");
+        assert_eq!(expand("a { b", lookup), "a { b");
+        assert_eq!(expand("no placeholders", lookup), "no placeholders");
+    }
+
+    #[test]
+    fn words_without_a_value_are_dropped() {
+        let lookup = |name: &str| match name {
+            "dist"   => Some("12.5".to_string()),
+            "weapon" => Some("AK-47".to_string()),
+            "hp" | "state" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(expand_words("{dist}m  [{weapon}]  {state}", lookup), "12.5m  [AK-47]");
+        assert_eq!(expand_words("{hp}hp {dist}m", lookup), "12.5m");
+        assert_eq!(expand_words("  {state}  [{weapon}] {nope}", lookup), "[AK-47] {nope}");
+        assert_eq!(expand_words("", lookup), "");
+    }
+}
@@ -4,63 +4,355 @@
 // with DLL_PROCESS_ATTACH. It spawns a background worker thread that:
 //   1. Installs a hook on the engine's Initialize function (to capture the engine table)
 //   2. Installs a detour on wglSwapBuffers (to draw the ESP overlay each frame)
-//   3. Stays alive until DLL_PROCESS_DETACH signals shutdown
+//   3. Stays alive until DLL_PROCESS_DETACH signals shutdown, or until an
+//      eject is requested (control pipe `eject`): then it unhooks, waits for
+//      detour calls in progress to return (hook.rs), stops its threads and
+//      unloads the DLL with FreeLibraryAndExitThread
 //
-// Must be compiled as a 32-bit cdylib (i686-pc-windows-msvc).
+// Only one copy runs per game: a second injection (another copy of the DLL,
+// e.g. a double-click in an injector) would stack a second set of hooks and
+// crash the game, so it finds the first one's mutex and unloads itself.
+//
+// Must be compiled as a 32-bit cdylib (i686-pc-windows-msvc). The
+// platform-independent modules also build on any host for `cargo test`.
+//
+// The optional parts (`menu`, `radar`, `ipc`, `scripting`; see Cargo.toml)
+// are only declared, started and called when their feature is on, so
+// `--no-default-features` leaves a DLL with just the core ESP.
 
 #![allow(non_snake_case)]
+#![cfg_attr(not(windows), allow(dead_code))]
 
-// Compile-time guard: only allow 32-bit x86 builds
-#[cfg(not(target_arch = "x86"))]
+// Compile-time guard: only allow 32-bit x86 builds (tests run on the host)
+#[cfg(all(not(target_arch = "x86"), not(test)))]
 compile_error!("Build with i686-pc-windows-msvc (32-bit x86).");
 
-// Internal modules
-mod entities; // Engine API access, memory reading, player data
-mod esp;      // ESP drawing logic (bounding boxes, labels)
-mod hook;     // wglSwapBuffers hook install/uninstall
-mod math;     // Vector math (Vec3, distance)
-mod render;   // OpenGL 2D drawing primitives (lines, text, boxes)
-
-use std::ptr;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
-use winapi::shared::minwindef::{BOOL, DWORD, HINSTANCE, LPVOID, TRUE};
-use winapi::um::handleapi::CloseHandle;
-use winapi::um::libloaderapi::DisableThreadLibraryCalls;
-use winapi::um::processthreadsapi::CreateThread;
-use winapi::um::winnt::{DLL_PROCESS_ATTACH, DLL_PROCESS_DETACH};
+// Platform-independent modules (unit-tested)
+mod alerts;   // Audio alert triggers (enemy near / behind, cooldowns)
+mod alive;    // Dead / alive fallback without extra info (EF_NODRAW, death sequences, DeathMsg)
+mod anim;     // Weapon state inference (reload sequences, weapon switches)
+mod annotations; // Named map markers and their per-map files
+mod bench;    // Synthetic players for the benchmark mode (no engine reads)
+mod bsp;      // .bsp map file reading (entity lump)
+mod bus;      // Frame events (map loaded, rounds, deaths, chat, config reloaded, hook lost) and who handles them
+mod chat;     // Chat / server message and console print decoding, the chat history
+mod compass;  // Enemy bearings on a compass strip
+mod control;  // Control pipe line protocol (requests, replies)
+mod draw;     // Draw lists (rect / line / circle / text commands) and the box pass
+mod budget;   // Overlay frame-time budget and load shedding order
+mod environment; // Startup report pieces (OS name, known overlay DLLs, file versions, CRC-32)
+#[cfg(feature = "radar")] mod feed; // Live player snapshots over HTTP / WebSocket (browser radar)
+mod fault;    // Faults in the overlay's own code (which count, crash report text)
+mod flash;    // Screen fade state (flashbang blindness)
+mod glstate;  // GL state snapshots and their differences (GL audit)
+mod grenades; // Thrown grenade kinds and fuse countdowns
+mod inspect;  // Memory inspector rows (hex / int / float, known field names)
+mod layout;   // Screen panel placement (corner anchors, stacking)
+mod markers;  // Client health markers (fps drops, loss spikes, lost hooks) for the session and feed
+mod math;     // Vector math (Vec3 ops, Angles, world-to-screen)
+mod mods;     // Running mod from the game directory, per-mod profiles (extra info, teams, weapons, hulls)
+mod names;    // Player name decoding (UTF-8 / CP1251 / GBK)
+mod netgraph; // Net channel samples (latency, choke, loss, rate) and the mini graph
+mod offsets;  // Structure offsets overridable at runtime (esp_offsets.toml, offset tuner)
+mod pacing;   // Game frame rate, pacing jitter, fps cap, overlay-caused spikes
+mod panics;   // Caught panics per subsystem, switching off one that keeps panicking
+mod pattern;  // IDA-style byte signatures ("0F BF 87 ?? ...")
+mod player;   // Player slot field interpretation (EngineReader)
+mod posgraph; // A player's speed and height over their position history
+mod readrate; // Player read throttle and between-read extrapolation
+mod registry; // Optional subsystems switched on / off at runtime (init, frame, shutdown)
+mod schedule; // Worker thread periodic tasks (intervals, how long to sleep)
+mod scrub;    // Last seconds of player snapshots, freeze and step through them
+mod session;  // Players seen on a map (SteamIDs, teams, frags), JSON export
+mod slots;    // Player slot occupancy strip and per-slot read outcomes
+mod sounds;   // Sound classification (footsteps, gunfire) and ring timing
+mod spectators; // Who is spectating whom, who is watching the local player
+mod stats;    // Round statistics (kills, deaths, damage events, time alive, CSV)
+mod teams;    // Team names from the TeamInfo / TeamNames user messages
+mod tga;      // .tga image decoding (icon textures)
+mod theme;    // Color palettes (default, deuteranopia, high contrast)
+mod time;     // Shared monotonic clock (QueryPerformanceCounter), frame delta, stopwatches
+mod toml;     // The config file's TOML subset (sections, values, comments)
+mod view;     // Screen-space math (NDC, boxes, fades, label layout)
+mod warmup;   // Startup steps on the worker thread and the progress line
+mod watch;    // Watch list expressions (module+offset, pointer chains, value types)
+mod world;    // All-entity scan (RawEntity, edict count)
+mod zip;      // .zip archive writing (stored files, for support bundles)
+#[cfg(test)] mod mock; // Fake EngineReader for unit tests
+
+// Windows-only modules
+#[cfg(windows)] mod bundle;   // Support bundles (log, env report, entity dump, config, offsets in one zip)
+#[cfg(windows)] mod capture;  // Overlay screenshots (F11, before/after .tga)
+#[cfg(windows)] mod config;   // User settings (esp_config.toml, live reload)
+#[cfg(windows)] mod crashdump; // Vectored exception handler: minidump + log tail when our code faults
+#[cfg(windows)] mod diag;     // F7 diagnostics pages (hook status, offsets, log tail)
+#[cfg(windows)] mod entities; // Engine API access, memory reading, player data
+#[cfg(windows)] mod error;    // OverlayError (hook / engine-access failures)
+#[cfg(windows)] mod esp;      // ESP drawing logic (bounding boxes, labels)
+#[cfg(windows)] mod events;   // Engine sound hooks (EV_PlaySound, S_StartDynamicSound)
+#[cfg(windows)] mod hook;     // wglSwapBuffers hook install/uninstall
+#[cfg(windows)] mod input;    // Game window WndProc subclass (key presses, typing state)
+#[cfg(all(windows, feature = "ipc"))] mod ipc; // Control pipe server (settings, profiles, visibility from outside)
+#[cfg(windows)] mod mapdata;  // Current map's .bsp, loaded in the background
+#[cfg(all(windows, feature = "menu"))] mod menu; // In-game settings menu (Insert)
+#[cfg(all(windows, feature = "radar"))] mod minimap; // Top-down minimap (map walls + player blips)
+#[cfg(windows)] mod plugin;   // Native overlay plugins (plugins/*.dll, C ABI)
+#[cfg(windows)] mod render;   // OpenGL 2D drawing primitives (lines, text, boxes)
+#[cfg(all(windows, feature = "scripting"))] mod script; // Rhai overlay scripts (esp_script.rhai, hot-reloaded)
+#[cfg(windows)] mod scancache; // Pattern-scan results cached per client.dll/hw.dll build
+#[cfg(windows)] mod startup;  // One-shot environment report in the log (OS, game build, renderer, other hooks)
+#[cfg(windows)] mod subsystems; // The registered subsystems and their frame callbacks (minimap, feed, plugins, pipe)
+#[cfg(windows)] mod selftest; // Hook health self-test (patches intact, table and extra info sane)
+#[cfg(windows)] mod ui;       // Immediate-mode widgets (window, label, checkbox, slider, selector)
+#[cfg(windows)] mod usermsg;  // Server user message handlers (DeathMsg, RoundTime, TeamScore)
+
+#[cfg(windows)]
+use {
+    crate::schedule::Scheduler,
+    crate::warmup::Step,
+    std::ptr,
+    std::sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    std::time::Duration,
+    winapi::shared::minwindef::{BOOL, DWORD, FALSE, HINSTANCE, LPARAM, LPVOID, TRUE},
+    winapi::shared::windef::HWND,
+    winapi::shared::winerror::ERROR_ALREADY_EXISTS,
+    winapi::um::errhandlingapi::GetLastError,
+    winapi::um::handleapi::CloseHandle,
+    winapi::um::libloaderapi::{DisableThreadLibraryCalls, FreeLibraryAndExitThread},
+    winapi::um::processthreadsapi::{CreateThread, GetCurrentProcessId},
+    winapi::um::synchapi::{CreateEventA, CreateMutexA, SetEvent, WaitForSingleObject},
+    winapi::um::winbase::INFINITE,
+    winapi::um::winnt::{DLL_PROCESS_ATTACH, DLL_PROCESS_DETACH},
+    winapi::um::winuser::{EnumWindows, GetWindowThreadProcessId, PostMessageA, RegisterWindowMessageA},
+};
 
 /// Flag to keep the worker thread alive. Set to false on DLL_PROCESS_DETACH.
+#[cfg(windows)]
 static RUNNING: AtomicBool = AtomicBool::new(false);
 
-/// Background worker thread entry point.
-/// Installs hooks, then loops until RUNNING is set to false (on DLL unload).
-unsafe extern "system" fn worker(_: LPVOID) -> DWORD {
+/// Set by `request_eject()`; the worker unhooks and unloads the DLL.
+#[cfg(windows)]
+static EJECT: AtomicBool = AtomicBool::new(false);
+
+/// Wait before unloading, for short-lived background threads (file exports,
+/// screenshot writes) to finish.
+#[cfg(windows)]
+const EJECT_SETTLE: Duration = Duration::from_millis(250);
+
+/// Event the worker waits on between periodic tasks (0 = none; see wake_worker).
+#[cfg(windows)]
+static WAKE: AtomicUsize = AtomicUsize::new(0);
+
+/// The worker's poll interval when the wake event couldn't be created.
+#[cfg(windows)]
+const FALLBACK_POLL_MS: u32 = 50;
+
+/// Periodic tasks of the worker thread (schedule.rs).
+#[cfg(windows)]
+#[derive(Clone, Copy, PartialEq)]
+enum Task {
+    Retry,       // A pending hook install / scan (only while one is pending)
+    ConfigWatch, // esp_config.toml edits
+    Heartbeat,   // `heartbeat:` log line
+}
+
+/// Handle of this process's instance mutex, held while we're loaded (0 = none).
+#[cfg(windows)]
+static INSTANCE_MUTEX: AtomicUsize = AtomicUsize::new(0);
+
+// ============================================================
+// Single Instance
+// ============================================================
+
+/// Create the per-process instance mutex. False if another copy of the
+/// overlay already holds it.
+#[cfg(windows)]
+unsafe fn claim_instance() -> bool {
+    let name = std::ffi::CString::new(format!("Local\\GoldSrcOverlay.{}", GetCurrentProcessId())).unwrap_or_default();
+    let h = CreateMutexA(ptr::null_mut(), FALSE, name.as_ptr());
+    if h.is_null() { return true; } // Can't tell; better running than not
+    if GetLastError() == ERROR_ALREADY_EXISTS {
+        CloseHandle(h);
+        return false;
+    }
+    INSTANCE_MUTEX.store(h as usize, Ordering::Relaxed);
+    true
+}
+
+/// Thread of a second copy: tell the first one (it logs the attempt and
+/// shows the overlay, see control::MSG_DUPLICATE), then unload.
+#[cfg(windows)]
+unsafe extern "system" fn back_out(hinst: LPVOID) -> DWORD {
+    let name = std::ffi::CString::new(control::WINDOW_MESSAGE).unwrap_or_default();
+    let msg = RegisterWindowMessageA(name.as_ptr());
+    if msg != 0 { EnumWindows(Some(notify_window), msg as LPARAM); }
+    FreeLibraryAndExitThread(hinst as _, 0);
+    0
+}
+
+/// Post the duplicate notice to each top-level window of this process (only
+/// the game window's subclass acts on it).
+#[cfg(windows)]
+unsafe extern "system" fn notify_window(hwnd: HWND, msg: LPARAM) -> BOOL {
+    let mut pid = 0;
+    GetWindowThreadProcessId(hwnd, &mut pid);
+    if pid == GetCurrentProcessId() { PostMessageA(hwnd, msg as _, control::MSG_DUPLICATE, 0); }
+    TRUE
+}
+
+/// Ask the worker to unhook and unload the DLL (control pipe `eject`).
+#[cfg(all(windows, feature = "ipc"))]
+pub fn request_eject() {
+    EJECT.store(true, Ordering::Release);
+    wake_worker();
+}
+
+/// Wake the worker thread for work a frame or the menu asked for (a scan,
+/// an icon, a subsystem switched), or to see RUNNING / EJECT.
+#[cfg(windows)]
+pub fn wake_worker() {
+    let h = WAKE.load(Ordering::Acquire);
+    if h != 0 { unsafe { SetEvent(h as _); } }
+}
+
+/// Sleep until woken or `ms` (None = until woken) has passed.
+#[cfg(windows)]
+unsafe fn wait_for_work(ms: Option<u32>) {
+    match WAKE.load(Ordering::Acquire) {
+        0 => std::thread::sleep(Duration::from_millis(ms.unwrap_or(u32::MAX).min(FALLBACK_POLL_MS) as u64)),
+        h => { WaitForSingleObject(h as _, ms.unwrap_or(INFINITE)); }
+    }
+}
+
+/// Set the worker's task intervals from the config and what's pending.
+#[cfg(windows)]
+fn schedule_tasks(sched: &mut Scheduler<Task>, now: u32) {
+    let cfg = config::get();
+    sched.set(Task::Retry, if entities::scans_pending() { cfg.worker_retry_ms } else { 0 }, now);
+    sched.set(Task::ConfigWatch, cfg.worker_config_ms, now);
+    sched.set(Task::Heartbeat, cfg.worker_heartbeat_s.saturating_mul(1000), now);
+}
+
+/// Log the frames drawn since the last heartbeat (`last` = its time and
+/// frame count) and the overlay's time per frame.
+#[cfg(windows)]
+fn heartbeat(last: &mut (u32, u32)) {
+    let (now, frames) = (time::now_ms(), diag::frames());
+    entities::logf(format!("heartbeat: {} frames in {:.0} s, overlay {:.0} us/frame",
+                           frames.wrapping_sub(last.1), now.wrapping_sub(last.0) as f32 / 1000.0, esp::frame_cost().0));
+    *last = (now, frames);
+}
+
+/// Pass the config's crash report and log mirror settings on (they're kept
+/// outside the config so the crash handler and the logger never wait on it).
+#[cfg(windows)]
+fn apply_log_settings() {
+    let cfg = config::get();
+    crashdump::configure(&cfg);
+    entities::set_log_mirror(cfg.log_debug_string, cfg.log_etw);
+}
+
+/// Background worker thread entry point (`hinst` = our module).
+/// Runs every startup step (warmup.rs) off the attach path, then loops until
+/// RUNNING is set to false (on DLL unload) or an eject is requested.
+#[cfg(windows)]
+unsafe extern "system" fn worker(hinst: LPVOID) -> DWORD {
+    let wake = CreateEventA(ptr::null_mut(), FALSE, FALSE, ptr::null());
+    WAKE.store(wake as usize, Ordering::Release);
+
     // Brief delay to let the engine finish initializing
     std::thread::sleep(Duration::from_millis(500));
 
-    // Install the wglSwapBuffers hook (which also triggers the Initialize hook)
+    // Load user settings before the first overlay frame
+    warmup::begin(Step::Settings);
+    config::load();
+    entities::load_offsets();
+
+    apply_log_settings();
+
+    // From here on, a fault in our code leaves a minidump in crashes/
+    crashdump::install(hinst as usize);
+
+    // Install the wglSwapBuffers hook first: the rest of startup is shown on
+    // the overlay while it runs
+    warmup::begin(Step::RenderHook);
     match hook::install() {
         Ok(()) => entities::log("hook installed"),
         Err(e) => {
-            entities::logf(format!("hook install failed: err={}", e));
+            entities::logf(format!("hook install failed: {}", e));
             entities::flush_log();
             return 1; // Exit thread on failure
         }
     }
 
-    // Keep thread alive until DLL is unloaded
+    // The engine table: client.dll scan, or the Initialize hook for the next
+    // map (retried below every `[worker] retry_ms` if client.dll isn't there yet)
+    warmup::begin(Step::EngineTable);
+    entities::install_initialize_hook();
+
+    // Optional subsystems (native plugins, the control pipe, ...); a frame
+    // only calls into one once its init has returned (registry.rs)
+    warmup::begin(Step::Subsystems);
+    subsystems::register();
+    subsystems::reconcile();
+
+    warmup::begin(Step::Images);
+    render::preload_icon(&config::get().minimap_image);
+
+    warmup::begin(Step::Report);
+    startup::report();
+    warmup::finish();
+
+    // Keep thread alive until DLL is unloaded: asleep until a frame or the
+    // menu wakes it or a periodic task is due
     RUNNING.store(true, Ordering::Release);
-    while RUNNING.load(Ordering::Acquire) {
-        std::thread::sleep(Duration::from_millis(50));
+    let mut sched = Scheduler::new();
+    let mut beat = (time::now_ms(), diag::frames());
+    while RUNNING.load(Ordering::Acquire) && !EJECT.load(Ordering::Acquire) {
+        schedule_tasks(&mut sched, time::now_ms());
+        wait_for_work(sched.wait_ms(time::now_ms()));
+        if !RUNNING.load(Ordering::Acquire) || EJECT.load(Ordering::Acquire) { break; }
+
+        // Whatever woke us: what frames and the menu asked for
+        entities::background_scans(); // Extra info, net channel, Initialize hook retry, ...
+        render::load_wanted_icons();
+        subsystems::reconcile();      // Menu switches take effect here
+
+        for task in sched.due(time::now_ms()) {
+            match task {
+                Task::Retry => {} // Only wakes the worker: the scans above retried
+                Task::ConfigWatch => {
+                    config::poll_reload();
+                    apply_log_settings();
+                }
+                Task::Heartbeat => heartbeat(&mut beat),
+            }
+        }
     }
 
-    // Cleanup: remove hooks before thread exits
-    hook::uninstall();
+    // Cleanup: remove hooks before thread exits. If detour calls didn't
+    // drain, our code may still be running on a game thread: keep everything
+    // loaded rather than pull it out from under that call.
+    if !hook::uninstall() {
+        entities::log("eject: detour calls didn't return; staying loaded (hooks disabled)");
+        entities::flush_log();
+        return 1;
+    }
+    subsystems::shutdown_all();
+    if EJECT.load(Ordering::Acquire) {
+        crashdump::uninstall();
+        let wake = WAKE.swap(0, Ordering::AcqRel);
+        if wake != 0 { CloseHandle(wake as _); }
+        std::thread::sleep(EJECT_SETTLE);
+        entities::log("eject: unhooked, unloading");
+        entities::flush_log();
+        FreeLibraryAndExitThread(hinst as _, 0);
+    }
     0
 }
 
 /// DLL entry point — called by Windows when the DLL is loaded/unloaded.
+#[cfg(windows)]
 #[no_mangle]
 pub unsafe extern "system" fn DllMain(
     hinst: HINSTANCE,
@@ -72,15 +364,29 @@ pub unsafe extern "system" fn DllMain(
             // Prevent DLL_THREAD_ATTACH/DETACH notifications (we don't need them)
             DisableThreadLibraryCalls(hinst);
 
+            // A second copy backs out before touching anything. Its module
+            // handle is never stored, so its log stays in memory and can't
+            // overwrite the first copy's esp_debug.log.
+            if !claim_instance() {
+                entities::log("another overlay instance is running in this process; unloading");
+                let h = CreateThread(ptr::null_mut(), 0, Some(back_out), hinst as LPVOID, 0, ptr::null_mut());
+                if !h.is_null() { CloseHandle(h); }
+                return TRUE;
+            }
+
+            time::attach();
+
             // Save the DLL's module handle (used for resolving the log file path)
             entities::set_dll_hinst(hinst as usize);
             entities::log("DLL attached");
-            entities::flush_log();
+            if entities::try_dll_dir().is_none() {
+                entities::log("DLL path unknown: log kept in memory (F7 page, control pipe) only");
+            }
 
             // Spawn the worker thread that installs hooks
             let h = CreateThread(
                 ptr::null_mut(), 0, Some(worker),
-                ptr::null_mut(), 0, ptr::null_mut(),
+                hinst as LPVOID, 0, ptr::null_mut(),
             );
             if !h.is_null() {
                 CloseHandle(h); // We don't need the thread handle
@@ -89,7 +395,10 @@ pub unsafe extern "system" fn DllMain(
         DLL_PROCESS_DETACH => {
             // Signal the worker thread to stop
             RUNNING.store(false, Ordering::Release);
+            wake_worker();
             entities::flush_log();
+            let mutex = INSTANCE_MUTEX.swap(0, Ordering::Relaxed);
+            if mutex != 0 { CloseHandle(mutex as _); }
         }
         _ => {}
     }
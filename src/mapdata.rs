@@ -0,0 +1,102 @@
+// mapdata.rs — The current map's .bsp, read from disk in the background.
+//
+// The engine only tells us the level's path ("maps/de_dust2.bsp"); the file is
+// looked up under the mod's directory, then its _downloads twin (custom maps
+// fetched from a server), then valve/. Reading and parsing a multi-megabyte
+// file (entity classes, world bounds, wall outlines for the minimap) doesn't
+// belong in a frame, so the first frame on a new map starts a loader thread
+// and gets None until the result is published. A map that can't be read is remembered as
+// failed and not retried until the level changes.
+
+use crate::bsp;
+use crate::entities::{self, EngineApi};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// What's known about the loaded map.
+pub struct MapData {
+    pub level:         String,                  // "maps/de_dust2.bsp"
+    pub brush_classes: HashMap<String, String>, // Inline model "*12" -> classname
+    pub walls:         Vec<bsp::Wall>,          // World walls, flattened (minimap, annotation aim)
+    pub bounds:        Option<bsp::Bounds>,     // World bounding box (None = bad models lump)
+}
+
+/// Load state of the current level.
+enum State {
+    Idle,
+    Loading(String),     // Level name being read
+    Ready(Arc<MapData>),
+    Failed(String),      // Level name that couldn't be read
+}
+
+static STATE: Mutex<State> = Mutex::new(State::Idle);
+
+/// The current map's data, or None while it's loading (or unreadable).
+/// Starts a load when the level changed since the last call.
+pub unsafe fn current(api: &EngineApi) -> Option<Arc<MapData>> {
+    let level = api.level_name()?;
+    let mut state = STATE.lock().ok()?;
+    match &*state {
+        State::Ready(m) if m.level == level => return Some(m.clone()),
+        State::Loading(l) | State::Failed(l) if *l == level => return None,
+        _ => {}
+    }
+
+    let paths = candidates(&level, api.game_dir().as_deref().unwrap_or("valve"));
+    *state = State::Loading(level.clone());
+    std::thread::spawn(move || {
+        let loaded = load(&level, &paths);
+        let Ok(mut state) = STATE.lock() else { return };
+        // A newer level may have started loading meanwhile; don't clobber it
+        if !matches!(&*state, State::Loading(l) if *l == level) { return; }
+        *state = match loaded {
+            Some(m) => State::Ready(Arc::new(m)),
+            None    => State::Failed(level),
+        };
+    });
+    None
+}
+
+/// Where the level's file may be, most likely first.
+fn candidates(level: &str, game_dir: &str) -> Vec<PathBuf> {
+    let root = exe_dir();
+    let mut dirs = vec![game_dir.to_string(), format!("{}_downloads", game_dir)];
+    if game_dir != "valve" { dirs.push("valve".to_string()); }
+    dirs.iter().map(|d| root.join(d).join(level)).collect()
+}
+
+/// Read and parse the first candidate that exists.
+fn load(level: &str, paths: &[PathBuf]) -> Option<MapData> {
+    let Some((path, data)) = paths.iter().find_map(|p| std::fs::read(p).ok().map(|d| (p, d))) else {
+        entities::logf(format!("map: {} not found on disk", level));
+        return None;
+    };
+    let Some(ents) = bsp::entities(&data) else {
+        entities::logf(format!("map: {} is not a v{} BSP", path.display(), bsp::BSP_VERSION));
+        return None;
+    };
+    let brush_classes: HashMap<String, String> = bsp::brush_classes(&ents).into_iter().collect();
+    let walls = bsp::walls(&data).unwrap_or_else(|| {
+        entities::logf(format!("map: {}: bad geometry lumps, no minimap", path.display()));
+        Vec::new()
+    });
+    let bounds = bsp::world_bounds(&data);
+    entities::logf(format!("map: {} ({} entities, {} brush models, {} walls, world {})",
+        path.display(), ents.len(), brush_classes.len(), walls.len(),
+        bounds.map_or("bounds unknown".to_string(), |b| format!("{:?} .. {:?}", b.mins, b.maxs))));
+    Some(MapData { level: level.to_string(), brush_classes, walls, bounds })
+}
+
+/// Directory of hl.exe (the game's root folder).
+fn exe_dir() -> PathBuf {
+    use winapi::um::libloaderapi::GetModuleFileNameA;
+    let mut buf = [0u8; 512];
+    let len = unsafe {
+        GetModuleFileNameA(std::ptr::null_mut(), buf.as_mut_ptr() as _, buf.len() as u32)
+    } as usize;
+    std::str::from_utf8(&buf[..len]).ok()
+        .and_then(|s| std::path::Path::new(s).parent())
+        .map(|d| d.to_path_buf())
+        .unwrap_or_default()
+}
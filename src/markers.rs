@@ -0,0 +1,140 @@
+// markers.rs — Client health markers for the recorded and streamed telemetry.
+//
+// When the game's frame rate falls under `[markers] fps_below`, the net
+// channel's loss goes over `loss_above`, or one of our hooks is found
+// overwritten, esp.rs writes a marker into what it's recording: the session
+// JSON (`markers`) and the next live feed snapshot, and logs it. Tools reading
+// those files can then tell gameplay data taken while the client struggled
+// from the rest.
+//
+// A marker is raised when a value crosses its threshold, not for every
+// second it stays past it: the frame rate has to recover to REARM times the
+// threshold (the loss to its threshold divided by REARM) before another one.
+
+#[cfg(feature = "radar")]
+use crate::session::json_str;
+#[cfg(feature = "radar")]
+use std::sync::Mutex;
+
+const REARM: f32 = 1.2;       // How far back past a threshold a value must get to raise again
+#[cfg(feature = "radar")]
+const MAX_QUEUED: usize = 64; // Markers kept for the feed between snapshots
+
+/// What a marker is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind { FpsDrop, LossSpike, HookLost }
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::FpsDrop   => "fps_drop",
+            Kind::LossSpike => "loss_spike",
+            Kind::HookLost  => "hook_lost",
+        }
+    }
+}
+
+/// One marker: when (time::now_ms), what, and the numbers behind it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    #[cfg_attr(not(feature = "radar"), allow(dead_code))] // Only the feed sends it
+    pub time_ms: u32,
+    pub kind:    Kind,
+    pub detail:  String,
+}
+
+impl Marker {
+    pub fn hook_lost(time_ms: u32, hook: &str) -> Self {
+        Self { time_ms, kind: Kind::HookLost, detail: format!("{} overwritten", hook) }
+    }
+
+    #[cfg(feature = "radar")]
+    pub fn to_json(&self) -> String {
+        format!("{{\"time\": {}, \"kind\": {}, \"detail\": {}}}", self.time_ms, json_str(self.kind.name()), json_str(&self.detail))
+    }
+}
+
+/// Threshold crossings of the frame rate and the loss.
+#[derive(Debug, Default)]
+pub struct Watch {
+    fps_low:   bool,
+    loss_high: bool,
+}
+
+impl Watch {
+    pub const fn new() -> Self {
+        Self { fps_low: false, loss_high: false }
+    }
+
+    /// A marker if `fps` just fell under `below` (0 = off).
+    pub fn fps(&mut self, now: u32, fps: f32, below: f32) -> Option<Marker> {
+        if below <= 0.0 { return None; }
+        if self.fps_low {
+            self.fps_low = fps < below * REARM;
+            return None;
+        }
+        self.fps_low = fps < below;
+        self.fps_low.then(|| Marker { time_ms: now, kind: Kind::FpsDrop, detail: format!("{:.0} fps (under {:.0})", fps, below) })
+    }
+
+    /// A marker if `loss` (percent) just went over `above` (0 = off).
+    pub fn loss(&mut self, now: u32, loss: f32, above: f32) -> Option<Marker> {
+        if above <= 0.0 { return None; }
+        if self.loss_high {
+            self.loss_high = loss > above / REARM;
+            return None;
+        }
+        self.loss_high = loss > above;
+        self.loss_high.then(|| Marker { time_ms: now, kind: Kind::LossSpike, detail: format!("{:.1}% loss (over {:.1}%)", loss, above) })
+    }
+}
+
+#[cfg(feature = "radar")]
+static FEED_QUEUE: Mutex<Vec<Marker>> = Mutex::new(Vec::new());
+
+/// Keep `m` for the next feed snapshot.
+#[cfg(feature = "radar")]
+pub fn queue_for_feed(m: Marker) {
+    let mut q = FEED_QUEUE.lock().unwrap_or_else(|e| e.into_inner());
+    if q.len() < MAX_QUEUED { q.push(m); }
+}
+
+/// The markers raised since the last snapshot, oldest first.
+#[cfg(feature = "radar")]
+pub fn take_for_feed() -> Vec<Marker> {
+    std::mem::take(&mut *FEED_QUEUE.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raises_once_per_crossing() {
+        let mut w = Watch::new();
+        assert_eq!(w.fps(1, 90.0, 60.0), None);
+        assert_eq!(w.fps(2, 45.0, 60.0).unwrap().detail, "45 fps (under 60)");
+        assert_eq!(w.fps(3, 30.0, 60.0), None);       // Still low
+        assert_eq!(w.fps(4, 65.0, 60.0), None);       // Not recovered enough to re-arm
+        assert_eq!(w.fps(5, 50.0, 60.0), None);
+        assert_eq!(w.fps(6, 80.0, 60.0), None);       // Re-armed
+        assert_eq!(w.fps(7, 50.0, 60.0).map(|m| m.kind), Some(Kind::FpsDrop));
+        assert_eq!(w.fps(8, 1.0, 0.0), None);         // Off
+
+        assert_eq!(w.loss(1, 2.0, 5.0), None);
+        assert_eq!(w.loss(2, 12.5, 5.0).unwrap().detail, "12.5% loss (over 5.0%)");
+        assert_eq!(w.loss(3, 4.5, 5.0), None);        // Above 5 / 1.2: still high
+        assert_eq!(w.loss(4, 3.0, 5.0), None);
+        assert!(w.loss(5, 6.0, 5.0).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "radar")]
+    fn feed_queue_drains() {
+        let m = Watch::new().loss(2, 12.5, 5.0).unwrap();
+        assert_eq!(m.to_json(), "{\"time\": 2, \"kind\": \"loss_spike\", \"detail\": \"12.5% loss (over 5.0%)\"}");
+        queue_for_feed(Marker::hook_lost(7, "wglSwapBuffers"));
+        assert_eq!(take_for_feed(), [Marker { time_ms: 7, kind: Kind::HookLost, detail: "wglSwapBuffers overwritten".into() }]);
+        assert!(take_for_feed().is_empty());
+    }
+}
@@ -1,8 +1,10 @@
-// math.rs — Simple 3D vector type used throughout the overlay.
+// math.rs — 3D vector type and view-angle helpers used throughout the overlay.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
 /// A 3-component vector (x, y, z) matching the engine's float[3] layout.
 /// Used for world-space positions (player origins, head/feet positions).
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[repr(C)]
 pub struct Vec3 {
     pub x: f32,
@@ -10,17 +12,395 @@ pub struct Vec3 {
     pub z: f32,
 }
 
+#[allow(dead_code)] // A general vector API; not every operation has a caller yet
 impl Vec3 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0, z: 0.0 };
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self { Self { x, y, z } }
+
     /// Euclidean distance between two 3D points.
     pub fn distance(self, other: Self) -> f32 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        let dz = self.z - other.z;
-        (dx * dx + dy * dy + dz * dz).sqrt()
+        (self - other).length()
     }
 
     /// Check if all components are exactly zero (uninitialized entity).
     pub fn is_zero(self) -> bool {
         self.x == 0.0 && self.y == 0.0 && self.z == 0.0
     }
+
+    /// All three components are finite (not NaN or infinite).
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    pub fn dot(self, o: Self) -> f32 {
+        self.x * o.x + self.y * o.y + self.z * o.z
+    }
+
+    pub fn cross(self, o: Self) -> Self {
+        Self {
+            x: self.y * o.z - self.z * o.y,
+            y: self.z * o.x - self.x * o.z,
+            z: self.x * o.y - self.y * o.x,
+        }
+    }
+
+    pub fn length(self) -> f32 { self.length2().sqrt() }
+
+    /// Squared length (cheaper; for comparisons).
+    pub fn length2(self) -> f32 { self.dot(self) }
+
+    /// Unit vector in the same direction (zero stays zero).
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+        if len > 0.0 { self * (1.0 / len) } else { self }
+    }
+
+    /// Linear interpolation: `self` at t = 0, `o` at t = 1.
+    pub fn lerp(self, o: Self, t: f32) -> Self {
+        self + (o - self) * t
+    }
+
+    /// Just the horizontal part (z = 0).
+    pub fn flat(self) -> Self { Self { z: 0.0, ..self } }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+    fn add(self, o: Self) -> Self { Self { x: self.x + o.x, y: self.y + o.y, z: self.z + o.z } }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self { Self { x: self.x - o.x, y: self.y - o.y, z: self.z - o.z } }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Self;
+    fn mul(self, k: f32) -> Self { Self { x: self.x * k, y: self.y * k, z: self.z * k } }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+    fn neg(self) -> Self { Self { x: -self.x, y: -self.y, z: -self.z } }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, o: Self) { *self = *self + o; }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, o: Self) { *self = *self - o; }
+}
+
+// ============================================================
+// View Angles
+// ============================================================
+
+/// Engine view angles in degrees. Pitch is positive looking down; yaw is
+/// counter-clockwise from +x; roll tilts the view.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Angles {
+    pub pitch: f32,
+    pub yaw:   f32,
+    pub roll:  f32,
+}
+
+#[allow(dead_code)]
+impl Angles {
+    pub const fn new(pitch: f32, yaw: f32, roll: f32) -> Self { Self { pitch, yaw, roll } }
+
+    /// Forward, right and up basis vectors, matching the engine's AngleVectors.
+    pub fn vectors(self) -> (Vec3, Vec3, Vec3) {
+        let (sp, cp) = self.pitch.to_radians().sin_cos();
+        let (sy, cy) = self.yaw.to_radians().sin_cos();
+        let (sr, cr) = self.roll.to_radians().sin_cos();
+        let forward = Vec3 { x: cp * cy, y: cp * sy, z: -sp };
+        let right = Vec3 {
+            x: -sr * sp * cy + cr * sy,
+            y: -sr * sp * sy - cr * cy,
+            z: -sr * cp,
+        };
+        let up = Vec3 {
+            x: cr * sp * cy + sr * sy,
+            y: cr * sp * sy - sr * cy,
+            z: cr * cp,
+        };
+        (forward, right, up)
+    }
+
+    pub fn forward(self) -> Vec3 { self.vectors().0 }
+
+    /// Angles looking along `dir` (no roll), like the engine's VectorAngles
+    /// but with pitch positive down. Zero stays zero.
+    pub fn from_forward(dir: Vec3) -> Self {
+        if dir.x == 0.0 && dir.y == 0.0 {
+            let pitch = if dir.z > 0.0 { -90.0 } else if dir.z < 0.0 { 90.0 } else { 0.0 };
+            return Self { pitch, yaw: 0.0, roll: 0.0 };
+        }
+        let yaw = dir.y.atan2(dir.x).to_degrees();
+        let pitch = -dir.z.atan2((dir.x * dir.x + dir.y * dir.y).sqrt()).to_degrees();
+        Self { pitch, yaw, roll: 0.0 }
+    }
+
+    /// Angle in degrees between the view direction from `eye` and the
+    /// direction to `target` (0 = under the crosshair, 180 = straight
+    /// behind). None when `target` is at the eye.
+    pub fn off_axis(self, eye: Vec3, target: Vec3) -> Option<f32> {
+        let to = (target - eye).normalize();
+        if to.is_zero() { return None; }
+        Some(self.forward().dot(to).clamp(-1.0, 1.0).acos().to_degrees())
+    }
+}
+
+/// The engine passes angles as a float[3]: (pitch, yaw, roll).
+impl From<Vec3> for Angles {
+    fn from(v: Vec3) -> Self { Self { pitch: v.x, yaw: v.y, roll: v.z } }
+}
+
+// ============================================================
+// Matrices and Frustum
+// ============================================================
+
+/// Nearest depth (units in front of the eye) a point can project at.
+const NEAR_PLANE: f32 = 4.0;
+
+/// Far clip distance for projections (GoldSrc's default sv_zmax).
+const FAR_PLANE: f32 = 4096.0;
+
+/// A 4x4 matrix, row-major, applied to column vectors: `m * [x, y, z, 1]`.
+/// View matrices follow GL conventions (x right, y up, looking down -z), so
+/// `perspective(..) * view(..)` maps the world to GL clip space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4],
+}
+
+#[allow(dead_code)]
+impl Mat4 {
+    pub const IDENTITY: Self = Self { m: [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ] };
+
+    /// GL perspective projection (gluPerspective): vertical FOV in degrees.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).to_radians().tan();
+        Self { m: [
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / (near - far), 2.0 * far * near / (near - far)],
+            [0.0, 0.0, -1.0, 0.0],
+        ] }
+    }
+
+    /// View matrix with the camera at `eye`, looking at `target`, `up` as up (gluLookAt).
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalize();
+        let s = f.cross(up).normalize();
+        Self::from_basis(eye, f, s, s.cross(f))
+    }
+
+    /// View matrix for a camera at `eye` with engine view angles (roll included).
+    pub fn view(eye: Vec3, angles: Angles) -> Self {
+        let (fwd, right, up) = angles.vectors();
+        Self::from_basis(eye, fwd, right, up)
+    }
+
+    fn from_basis(eye: Vec3, fwd: Vec3, right: Vec3, up: Vec3) -> Self {
+        Self { m: [
+            [right.x, right.y, right.z, -right.dot(eye)],
+            [up.x, up.y, up.z, -up.dot(eye)],
+            [-fwd.x, -fwd.y, -fwd.z, fwd.dot(eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ] }
+    }
+
+    /// `self * o`: applies `o` first.
+    pub fn mul(&self, o: &Self) -> Self {
+        let mut m = [[0.0f32; 4]; 4];
+        for (r, row) in m.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                *cell = (0..4).map(|k| self.m[r][k] * o.m[k][c]).sum();
+            }
+        }
+        Self { m }
+    }
+
+    /// Transform a point: (x, y, z) and w before the perspective divide.
+    pub fn transform(&self, p: Vec3) -> (Vec3, f32) {
+        let row = |r: [f32; 4]| r[0] * p.x + r[1] * p.y + r[2] * p.z + r[3];
+        (Vec3::new(row(self.m[0]), row(self.m[1]), row(self.m[2])), row(self.m[3]))
+    }
+
+    /// Transform a point and divide by w. None when w <= 0 (behind the camera).
+    pub fn transform_point(&self, p: Vec3) -> Option<Vec3> {
+        let (v, w) = self.transform(p);
+        if w <= 0.0 { return None; }
+        Some(v * (1.0 / w))
+    }
+}
+
+/// World to GL clip space for a camera at `eye` with view `angles`,
+/// horizontal FOV `fov_x` (degrees) and viewport `aspect` (width / height).
+pub fn view_projection(eye: Vec3, angles: Angles, fov_x: f32, aspect: f32) -> Mat4 {
+    let fov_y = (2.0 * ((fov_x * 0.5).to_radians().tan() / aspect).atan()).to_degrees();
+    Mat4::perspective(fov_y, aspect, NEAR_PLANE, FAR_PLANE).mul(&Mat4::view(eye, angles))
+}
+
+/// The six clip planes of a view-projection matrix, facing inward
+/// (`n . p + d >= 0` inside).
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    planes: [(Vec3, f32); 6],
+}
+
+#[allow(dead_code)]
+impl Frustum {
+    /// Extract the planes from a world-to-clip matrix (Gribb / Hartmann).
+    pub fn from_matrix(vp: &Mat4) -> Self {
+        let r = |i: usize| vp.m[i];
+        let plane = |a: [f32; 4], b: [f32; 4], sign: f32| {
+            let n = Vec3::new(a[0] + sign * b[0], a[1] + sign * b[1], a[2] + sign * b[2]);
+            let d = a[3] + sign * b[3];
+            let len = n.length();
+            if len > 0.0 { (n * (1.0 / len), d / len) } else { (n, d) }
+        };
+        Self { planes: [
+            plane(r(3), r(0), 1.0),  // Left
+            plane(r(3), r(0), -1.0), // Right
+            plane(r(3), r(1), 1.0),  // Bottom
+            plane(r(3), r(1), -1.0), // Top
+            plane(r(3), r(2), 1.0),  // Near
+            plane(r(3), r(2), -1.0), // Far
+        ] }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.planes.iter().all(|(n, d)| n.dot(p) + d >= 0.0)
+    }
+
+    /// Whether any part of the axis-aligned box `mins..maxs` may be visible
+    /// (conservative: a box near a corner can pass while just outside).
+    pub fn intersects_box(&self, mins: Vec3, maxs: Vec3) -> bool {
+        self.planes.iter().all(|(n, d)| {
+            // The box corner furthest along the plane normal
+            let far = Vec3::new(
+                if n.x >= 0.0 { maxs.x } else { mins.x },
+                if n.y >= 0.0 { maxs.y } else { mins.y },
+                if n.z >= 0.0 { maxs.z } else { mins.z },
+            );
+            n.dot(far) + d >= 0.0
+        })
+    }
+}
+
+/// Project `p` onto the screen of a camera at `eye` looking along `angles`
+/// with horizontal field of view `fov_x` (degrees), for a viewport `aspect`
+/// (width / height) wide. Returns normalized device coordinates (-1..1, y up),
+/// the same convention as the engine's TriAPI WorldToScreen, or None for
+/// points behind the near plane.
+pub fn world_to_ndc(eye: Vec3, angles: Vec3, fov_x: f32, aspect: f32, p: Vec3) -> Option<(f32, f32)> {
+    if !(fov_x > 0.0 && fov_x < 180.0) || aspect <= 0.0 { return None; }
+    let (clip, w) = view_projection(eye, angles.into(), fov_x, aspect).transform(p);
+    if w < NEAR_PLANE { return None; } // w is the depth in front of the eye
+    Some((clip.x / w, clip.y / w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32, z: f32) -> Vec3 { Vec3 { x, y, z } }
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4
+    }
+
+    #[test]
+    fn vector_ops() {
+        let (a, b) = (v(1.0, 2.0, 3.0), v(4.0, -5.0, 6.0));
+        assert_eq!(a + b, v(5.0, -3.0, 9.0));
+        assert_eq!(a - b, v(-3.0, 7.0, -3.0));
+        assert_eq!(-a * 2.0, v(-2.0, -4.0, -6.0));
+        assert_eq!(a.dot(b), 12.0);
+        assert_eq!(v(1.0, 0.0, 0.0).cross(v(0.0, 1.0, 0.0)), v(0.0, 0.0, 1.0));
+        assert_eq!(v(3.0, 4.0, 0.0).length(), 5.0);
+        assert_eq!(v(3.0, 4.0, 0.0).normalize(), v(0.6, 0.8, 0.0));
+        assert_eq!(Vec3::ZERO.normalize(), Vec3::ZERO);
+        assert_eq!(a.lerp(b, 0.5), v(2.5, -1.5, 4.5));
+    }
+
+    #[test]
+    fn angles_round_trip() {
+        let a = Angles::new(30.0, 135.0, 0.0);
+        let back = Angles::from_forward(a.forward());
+        assert!((back.pitch - 30.0).abs() < 1e-3 && (back.yaw - 135.0).abs() < 1e-3);
+        let (f, r, u) = Angles::default().vectors();
+        assert_eq!((f, r, u), (v(1.0, 0.0, 0.0), v(0.0, -1.0, 0.0), v(0.0, 0.0, 1.0)));
+        assert_eq!(Angles::from_forward(v(0.0, 0.0, -1.0)).pitch, 90.0); // Straight down
+    }
+
+    #[test]
+    fn measures_angles_off_the_crosshair() {
+        let eye = v(0.0, 0.0, 64.0);
+        let look = Angles::new(0.0, 90.0, 0.0); // Along +y
+        assert!(look.off_axis(eye, v(0.0, 500.0, 64.0)).unwrap() < 1e-3);
+        assert!((look.off_axis(eye, v(100.0, 100.0, 64.0)).unwrap() - 45.0).abs() < 1e-3);
+        assert!((look.off_axis(eye, v(0.0, -100.0, 64.0)).unwrap() - 180.0).abs() < 1e-3); // Behind
+        assert!((Angles::new(45.0, 90.0, 0.0).off_axis(eye, v(0.0, 100.0, -36.0)).unwrap()).abs() < 1e-2);
+        assert_eq!(look.off_axis(eye, eye), None);
+    }
+
+    #[test]
+    fn projects_center_and_edges() {
+        let eye = v(0.0, 0.0, 0.0);
+        let ahead = v(0.0, 0.0, 0.0); // Looking along +x
+        assert!(close(world_to_ndc(eye, ahead, 90.0, 1.0, v(100.0, 0.0, 0.0)).unwrap(), (0.0, 0.0)));
+        // 45 degrees to the right (-y) is the right edge at fov 90
+        assert!(close(world_to_ndc(eye, ahead, 90.0, 1.0, v(100.0, -100.0, 0.0)).unwrap(), (1.0, 0.0)));
+        // Up is +y in NDC; a wide viewport narrows the vertical range
+        assert!(close(world_to_ndc(eye, ahead, 90.0, 2.0, v(100.0, 0.0, 50.0)).unwrap(), (0.0, 1.0)));
+        assert!(world_to_ndc(eye, ahead, 90.0, 1.0, v(-100.0, 0.0, 0.0)).is_none()); // Behind
+    }
+
+    #[test]
+    fn matrices() {
+        let m = Mat4::view(v(10.0, 20.0, 30.0), Angles::new(0.0, 90.0, 0.0));
+        assert_eq!(Mat4::IDENTITY.mul(&m), m);
+        // Looking along +y: a point ahead ends up on -z in view space
+        let ahead = m.transform_point(v(10.0, 120.0, 30.0)).unwrap();
+        assert!((ahead.x.abs() + ahead.y.abs()) < 1e-4 && (ahead.z + 100.0).abs() < 1e-3);
+        let look = Mat4::look_at(v(10.0, 20.0, 30.0), v(10.0, 120.0, 30.0), v(0.0, 0.0, 1.0));
+        let p = v(-40.0, 300.0, 90.0);
+        let (a, b) = (m.transform_point(p).unwrap(), look.transform_point(p).unwrap());
+        assert!((a - b).length() < 1e-3);
+        // The perspective divide puts the near plane at -1 and the far plane at 1
+        let proj = Mat4::perspective(90.0, 1.0, 4.0, 4096.0);
+        assert!((proj.transform_point(v(0.0, 0.0, -4.0)).unwrap().z + 1.0).abs() < 1e-4);
+        assert!((proj.transform_point(v(0.0, 0.0, -4096.0)).unwrap().z - 1.0).abs() < 1e-3);
+        assert!(proj.transform_point(v(0.0, 0.0, 10.0)).is_none()); // Behind
+    }
+
+    #[test]
+    fn frustum_culls_points_and_boxes() {
+        let f = Frustum::from_matrix(&view_projection(Vec3::ZERO, Angles::default(), 90.0, 1.0));
+        assert!(f.contains_point(v(100.0, 0.0, 0.0)));
+        assert!(!f.contains_point(v(-100.0, 0.0, 0.0)));  // Behind
+        assert!(!f.contains_point(v(100.0, 150.0, 0.0))); // Left of the 45-degree edge
+        assert!(!f.contains_point(v(5000.0, 0.0, 0.0)));  // Past the far plane
+        // A box straddling the left edge is kept; one fully behind is culled
+        assert!(f.intersects_box(v(90.0, 80.0, -10.0), v(110.0, 200.0, 10.0)));
+        assert!(!f.intersects_box(v(-200.0, -10.0, -10.0), v(-100.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn follows_view_angles() {
+        // Yaw 90 looks along +y; pitch 45 down looks at the floor ahead
+        let eye = v(0.0, 0.0, 0.0);
+        assert!(close(world_to_ndc(eye, v(0.0, 90.0, 0.0), 90.0, 1.0, v(0.0, 100.0, 0.0)).unwrap(), (0.0, 0.0)));
+        assert!(close(world_to_ndc(eye, v(45.0, 0.0, 0.0), 90.0, 1.0, v(100.0, 0.0, -100.0)).unwrap(), (0.0, 0.0)));
+    }
 }
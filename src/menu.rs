@@ -0,0 +1,200 @@
+// menu.rs — In-game settings menu (Insert to open).
+//
+// A small panel of toggles and sliders over the live settings. Changes are
+// applied with config::update() — they last until the config file is next
+// reloaded, so the file stays the place for permanent settings.
+//
+// The rows are ui.rs widgets, one per entry in ITEMS:
+//   - Keyboard: Up/Down select, Left/Right adjust, Enter toggles/cycles, Escape/Insert close
+//   - Mouse: hover highlights, click toggles/cycles, drag moves sliders
+// While the menu is open, input::set_menu_capture() keeps clicks and navigation
+// keys away from the game and suspends mouse look, and a cursor is drawn.
+//
+// Below the settings, a MODULES block has a switch per registered subsystem
+// (subsystems.rs). Unlike the settings, switching one off stops it for the
+// rest of the session: config reloads don't bring it back.
+
+use crate::config::{self, Config};
+use crate::input;
+use crate::registry::State;
+use crate::subsystems;
+use crate::theme::{ColorMode, Theme};
+use crate::ui::{self, Input, Style, UiState, Window};
+use crate::view::OPACITY_STEPS;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use winapi::shared::windef::HDC;
+
+// ============================================================
+// Layout & Keys
+// ============================================================
+
+const MENU_X: f32 = 40.0;        // Panel top-left corner
+const MENU_Y: f32 = 110.0;
+const MENU_W: f32 = 280.0;       // Panel width
+
+const VK_INSERT: i32 = 0x2D;
+const VK_ESCAPE: i32 = 0x1B;
+
+const STYLE: Style = Style {
+    row_h:    16.0,
+    pad:      6.0,
+    slider_w: 90.0,
+    bg:       [0.05, 0.05, 0.07, 0.80],
+    border:   Some([1.0, 0.15, 0.15, 0.90]),
+    hover:    [1.0, 1.0, 1.0, 0.10],
+    text:     [1.0, 1.0, 1.0, 1.0],
+    accent:   [1.0, 0.15, 0.15, 1.0],
+    track:    [1.0, 1.0, 1.0, 0.25],
+};
+
+// ============================================================
+// Items
+// ============================================================
+
+/// What a menu row edits.
+enum Kind {
+    Toggle(fn(&Config) -> bool, fn(&mut Config, bool)),
+    Slider { min: f32, max: f32, step: f32, get: fn(&Config) -> f32, set: fn(&mut Config, f32) },
+    /// One of `count` named options; `name` labels option i.
+    Choice { count: usize, name: fn(usize) -> &'static str, get: fn(&Config) -> usize, set: fn(&mut Config, usize) },
+}
+
+struct Item {
+    label: &'static str,
+    kind:  Kind,
+}
+
+const ITEMS: &[Item] = &[
+    Item { label: "Theme", kind: Kind::Choice {
+        count: Theme::ALL.len(), name: |i| Theme::ALL[i].name(),
+        get: |c| Theme::ALL.iter().position(|&t| t == c.theme).unwrap_or(0),
+        set: |c, i| c.theme = Theme::ALL[i] } },
+    Item { label: "Box colors", kind: Kind::Choice {
+        count: 2, name: |i| if i == 0 { "team" } else { "enemy/friend" },
+        get: |c| (c.color_mode == ColorMode::Relation) as usize,
+        set: |c, i| c.color_mode = if i == 0 { ColorMode::Team } else { ColorMode::Relation } } },
+    Item { label: "Opacity", kind: Kind::Choice {
+        count: OPACITY_STEPS.len(), name: |i| ["100%", "75%", "50%", "25%"][i],
+        get: |c| OPACITY_STEPS.iter().position(|&s| (s - c.opacity).abs() < 1e-3).unwrap_or(0),
+        set: |c, i| c.opacity = OPACITY_STEPS[i] } },
+    Item { label: "Snap-lines", kind: Kind::Toggle(|c| c.snapline_enabled, |c, v| c.snapline_enabled = v) },
+    Item { label: "Crosshair", kind: Kind::Toggle(|c| c.crosshair_enabled, |c, v| c.crosshair_enabled = v) },
+    Item { label: "Crosshair size", kind: Kind::Slider {
+        min: 2.0, max: 20.0, step: 1.0, get: |c| c.crosshair_size, set: |c, v| c.crosshair_size = v } },
+    Item { label: "FOV circle", kind: Kind::Toggle(|c| c.fov_circle_enabled, |c, v| c.fov_circle_enabled = v) },
+    Item { label: "FOV circle radius", kind: Kind::Slider {
+        min: 1.0, max: 30.0, step: 0.5, get: |c| c.fov_circle_radius, set: |c, v| c.fov_circle_radius = v } },
+    Item { label: "Crosshair focus", kind: Kind::Toggle(|c| c.focus_enabled, |c, v| c.focus_enabled = v) },
+    Item { label: "Status line", kind: Kind::Toggle(|c| c.status_enabled, |c, v| c.status_enabled = v) },
+    Item { label: "FOV indicator", kind: Kind::Toggle(|c| c.fov_indicator, |c, v| c.fov_indicator = v) },
+    Item { label: "Death markers", kind: Kind::Toggle(|c| c.death_markers_enabled, |c, v| c.death_markers_enabled = v) },
+    Item { label: "Death marker time", kind: Kind::Slider {
+        min: 1.0, max: 30.0, step: 1.0, get: |c| c.death_markers_seconds, set: |c, v| c.death_markers_seconds = v } },
+    Item { label: "Grenade labels", kind: Kind::Toggle(|c| c.grenades_enabled, |c, v| c.grenades_enabled = v) },
+    Item { label: "Map annotations", kind: Kind::Toggle(|c| c.annotations_enabled, |c, v| c.annotations_enabled = v) },
+    Item { label: "Trails", kind: Kind::Toggle(|c| c.trails_enabled, |c, v| c.trails_enabled = v) },
+    Item { label: "Trail length", kind: Kind::Slider {
+        min: 0.5, max: 10.0, step: 0.5, get: |c| c.trails_seconds, set: |c, v| c.trails_seconds = v } },
+    Item { label: "Sound rings", kind: Kind::Toggle(|c| c.sound_rings_enabled, |c, v| c.sound_rings_enabled = v) },
+    Item { label: "Shot tracers", kind: Kind::Toggle(|c| c.tracers_enabled, |c, v| c.tracers_enabled = v) },
+    Item { label: "Flash indicator", kind: Kind::Toggle(|c| c.flash_indicator, |c, v| c.flash_indicator = v) },
+    Item { label: "Audio alerts", kind: Kind::Toggle(|c| c.alerts_enabled, |c, v| c.alerts_enabled = v) },
+    Item { label: "Alert radius", kind: Kind::Slider {
+        min: 100.0, max: 2000.0, step: 50.0, get: |c| c.alerts_radius, set: |c, v| c.alerts_radius = v } },
+    Item { label: "Threat warning", kind: Kind::Toggle(|c| c.threat_enabled, |c, v| c.threat_enabled = v) },
+    Item { label: "Compass strip", kind: Kind::Toggle(|c| c.compass_enabled, |c, v| c.compass_enabled = v) },
+    Item { label: "Slot strip", kind: Kind::Toggle(|c| c.slots_enabled, |c, v| c.slots_enabled = v) },
+    Item { label: "Spectator list", kind: Kind::Toggle(|c| c.spectators_enabled, |c, v| c.spectators_enabled = v) },
+    Item { label: "Chat history", kind: Kind::Toggle(|c| c.chat_panel, |c, v| c.chat_panel = v) },
+    Item { label: "PVS tint", kind: Kind::Toggle(|c| c.pvs_tint, |c, v| c.pvs_tint = v) },
+    Item { label: "Minimap", kind: Kind::Toggle(|c| c.minimap_enabled, |c, v| c.minimap_enabled = v) },
+    Item { label: "Minimap range", kind: Kind::Slider {
+        min: 500.0, max: 5000.0, step: 250.0, get: |c| c.minimap_range, set: |c, v| c.minimap_range = v } },
+    Item { label: "Hide on screenshot", kind: Kind::Toggle(|c| c.hide_on_screenshot, |c, v| c.hide_on_screenshot = v) },
+];
+
+// ============================================================
+// State
+// ============================================================
+
+static OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Selected row and slider drag.
+static UI: Mutex<UiState> = Mutex::new(UiState::new());
+
+// ============================================================
+// Frame
+// ============================================================
+
+/// Handle input and draw the menu (if open). Called once per overlay frame,
+/// inside the 2D drawing block.
+pub unsafe fn frame(hdc: HDC) {
+    if !input::installed() { return; }
+
+    if input::take_press(VK_INSERT) {
+        let open = !OPEN.load(Ordering::Relaxed);
+        set_open(open);
+    }
+    if !OPEN.load(Ordering::Relaxed) { return; }
+    if input::take_press(VK_ESCAPE) {
+        set_open(false);
+        return;
+    }
+
+    draw(hdc);
+}
+
+/// Open or close the menu, capturing input while open.
+fn set_open(open: bool) {
+    OPEN.store(open, Ordering::Relaxed);
+    if let Ok(mut ui) = UI.lock() { ui.release(); }
+    if open { input::clear_presses(); }
+    input::set_menu_capture(open);
+}
+
+// ============================================================
+// Drawing
+// ============================================================
+
+/// Declare one widget per item; a changed value is applied to the live config.
+/// Then one switch per registered subsystem.
+unsafe fn draw(hdc: HDC) {
+    let Ok(mut state) = UI.lock() else { return };
+    let cfg = config::get();
+    let modules = subsystems::list();
+    let rows = ITEMS.len() + if modules.is_empty() { 0 } else { modules.len() + 1 };
+    let rect = [MENU_X, MENU_Y, MENU_X + MENU_W, MENU_Y + ui::window_height(&STYLE, rows)];
+    let mut w = Window::begin(hdc, &STYLE, &mut state, Input::poll(), rect, "SETTINGS  (INS to close)");
+
+    for item in ITEMS {
+        match item.kind {
+            Kind::Toggle(get, set) => {
+                let mut v = get(&cfg);
+                if w.checkbox(item.label, &mut v) { config::update(|c| set(c, v)); }
+            }
+            Kind::Slider { min, max, step, get, set } => {
+                let mut v = get(&cfg);
+                if w.slider(item.label, &mut v, min, max, step) { config::update(|c| set(c, v)); }
+            }
+            Kind::Choice { count, name, get, set } => {
+                let mut v = get(&cfg);
+                if w.selector(item.label, &mut v, count, name) { config::update(|c| set(c, v)); }
+            }
+        }
+    }
+
+    if !modules.is_empty() { w.label("MODULES", STYLE.accent); }
+    for (name, wanted, state) in modules {
+        let label = match state {
+            State::Failed(_) => format!("{} (failed)", name),
+            State::Starting  => format!("{} (starting)", name),
+            _ => name.to_string(),
+        };
+        let mut v = wanted;
+        if w.checkbox(&label, &mut v) { subsystems::set_enabled(name, v); }
+    }
+    w.end();
+
+    ui::draw_cursor(STYLE.text);
+}
@@ -0,0 +1,97 @@
+// minimap.rs — Top-down minimap drawn from the current map's walls.
+//
+// The walls come from the map's .bsp (mapdata.rs / bsp.rs), flattened to 2D
+// once per map; each frame they're transformed around the local player (or the
+// spectated one), clipped to the panel and drawn in one batch. Walls whose
+// height range doesn't reach within `height_window` of the viewer are dimmed,
+// so the floor you're on stands out on multi-level maps. Players are blips in
+// their box color; those beyond the range are pinned to the panel edge.
+
+use crate::config::Config;
+use crate::entities::EngineApi;
+use crate::mapdata;
+use crate::math::Vec3;
+use crate::render::{self, LineStyle};
+use crate::view::{self, MapView};
+
+const BLIP_PX: f32 = 3.0;             // Half size of a player blip
+const ARROW_PX: f32 = 6.0;            // Size of the viewer's arrow
+const DIM_ALPHA: f32 = 0.25;          // Alpha factor for walls on other levels
+const COLOR_BORDER: [f32; 4] = [1.0, 1.0, 1.0, 0.35];
+const COLOR_SELF:   [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// A player dot on the minimap.
+pub struct Blip {
+    pub pos:   Vec3,
+    pub color: [f32; 4],
+}
+
+/// Whether the minimap is shown (and so takes a place in the layout).
+pub fn enabled(cfg: &Config) -> bool {
+    cfg.minimap_enabled && cfg.minimap_size >= 16.0 && cfg.minimap_range > 0.0
+}
+
+/// Draw the minimap with its top-left corner at `at`, centered on `eye` (view
+/// yaw `yaw` in degrees), with `blips`. Draws nothing until the map's file is
+/// loaded.
+pub unsafe fn draw(cfg: &Config, api: &EngineApi, at: [f32; 2], eye: Vec3, yaw: f32, blips: &[Blip]) {
+    if !enabled(cfg) { return; }
+    let Some(map) = mapdata::current(api) else { return };
+
+    let ([x0, y0], size) = (at, cfg.minimap_size);
+    let rect = [x0, y0, x0 + size, y0 + size];
+    let half = size * 0.5;
+    // A range wider than the whole map only shrinks it; cap it at the map's extent
+    let range = map.bounds.map_or(cfg.minimap_range, |b| cfg.minimap_range.min(b.extent()));
+    let mv = MapView {
+        center: [eye.x, eye.y],
+        origin: [x0 + half, y0 + half],
+        scale:  half / range,
+        yaw:    if cfg.minimap_rotate { yaw } else { 90.0 },
+    };
+
+    match render::icon(&cfg.minimap_image) {
+        Some(tex) => {
+            let tint = [1.0, 1.0, 1.0, cfg.minimap_background[3]];
+            render::draw_texture(tex, rect[0], rect[1], rect[2], rect[3], tint);
+        }
+        None => render::fill_rect(rect[0], rect[1], rect[2], rect[3], cfg.minimap_background),
+    }
+
+    // Walls: near the viewer's height in full color, other levels dimmed
+    let reach = range * std::f32::consts::SQRT_2; // Corner distance when rotated
+    let around = [eye.x - reach, eye.y - reach, eye.x + reach, eye.y + reach];
+    let (mut near, mut far) = (Vec::new(), Vec::new());
+    for w in &map.walls {
+        if view::clip_segment(w.a, w.b, around).is_none() { continue; } // Cheap world-space cull
+        let Some(seg) = view::clip_segment(mv.to_px(w.a[0], w.a[1]), mv.to_px(w.b[0], w.b[1]), rect) else {
+            continue;
+        };
+        let level = w.z[1] >= eye.z - cfg.minimap_height && w.z[0] <= eye.z + cfg.minimap_height;
+        if level { near.push(seg); } else { far.push(seg); }
+    }
+    let c = cfg.minimap_wall_color;
+    render::draw_lines(&far, [c[0], c[1], c[2], c[3] * DIM_ALPHA], LineStyle::DEFAULT);
+    render::draw_lines(&near, c, LineStyle::DEFAULT);
+
+    // Players, pinned to the edge when out of range
+    for b in blips {
+        let [bx, by] = mv.to_px(b.pos.x, b.pos.y);
+        let bx = bx.clamp(rect[0] + BLIP_PX, rect[2] - BLIP_PX);
+        let by = by.clamp(rect[1] + BLIP_PX, rect[3] - BLIP_PX);
+        render::fill_rect(bx - BLIP_PX, by - BLIP_PX, bx + BLIP_PX, by + BLIP_PX, b.color);
+    }
+
+    // The viewer: an arrow along the view direction
+    let (s, c) = (yaw - mv.yaw).to_radians().sin_cos();
+    let turn = |x: f32, y: f32| [mv.origin[0] + x * c + y * s, mv.origin[1] - x * s + y * c];
+    render::fill_triangle(
+        turn(0.0, -ARROW_PX),
+        turn(-ARROW_PX * 0.6, ARROW_PX * 0.6),
+        turn(ARROW_PX * 0.6, ARROW_PX * 0.6),
+        COLOR_SELF,
+    );
+
+    render::draw_rect(rect[0], rect[1], rect[2], rect[3], COLOR_BORDER, LineStyle::DEFAULT);
+}
+
@@ -0,0 +1,58 @@
+// mock.rs — Fake engine for unit tests (test builds only).
+//
+// Byte-addressed process memory plus canned GetPlayerInfo / GetEntityByIndex
+// answers, behind the same `EngineReader` trait the live EngineApi implements.
+
+use crate::mods::GameMod;
+use crate::player::{EngineReader, SlotInfo, CURSTATE_OFFSET, ENT_ORIGIN};
+use crate::world::ES_MODELINDEX;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Byte-addressed fake process memory plus canned engine answers.
+#[derive(Default)]
+pub struct MockEngine {
+    pub mem:      RefCell<HashMap<usize, u8>>,
+    pub infos:    HashMap<i32, SlotInfo>,
+    pub entities: HashMap<i32, usize>,
+    pub extra:    usize,
+    pub game_mod: GameMod,
+}
+
+impl MockEngine {
+    pub fn write(&self, addr: usize, bytes: &[u8]) {
+        let mut mem = self.mem.borrow_mut();
+        for (i, b) in bytes.iter().enumerate() { mem.insert(addr + i, *b); }
+    }
+    pub fn write_i32(&self, addr: usize, v: i32) { self.write(addr, &v.to_le_bytes()); }
+    pub fn write_vec3(&self, addr: usize, v: [f32; 3]) {
+        for (i, c) in v.iter().enumerate() { self.write(addr + i * 4, &c.to_le_bytes()); }
+    }
+    fn bytes<const N: usize>(&self, addr: usize) -> [u8; N] {
+        let mem = self.mem.borrow();
+        std::array::from_fn(|i| *mem.get(&(addr + i)).unwrap_or(&0))
+    }
+
+    /// A standing player entity for slot `idx` at `base`.
+    pub fn add_player(&mut self, idx: i32, base: usize, name: &str, origin: [f32; 3]) {
+        self.infos.insert(idx, SlotInfo { name: Some(name.into()), ..Default::default() });
+        self.entities.insert(idx, base);
+        self.write_i32(base, idx);
+        self.write_i32(base + 4, 1);
+        self.write_vec3(base + ENT_ORIGIN, origin);
+        self.write_i32(base + CURSTATE_OFFSET + ES_MODELINDEX, 1);
+    }
+}
+
+impl EngineReader for MockEngine {
+    fn read_i32(&self, addr: usize) -> i32 { i32::from_le_bytes(self.bytes(addr)) }
+    fn read_i16(&self, addr: usize) -> i16 { i16::from_le_bytes(self.bytes(addr)) }
+    fn read_u8(&self, addr: usize) -> u8 { self.bytes::<1>(addr)[0] }
+    fn read_f32(&self, addr: usize) -> f32 { f32::from_le_bytes(self.bytes(addr)) }
+    fn player_info(&self, idx: i32) -> Option<SlotInfo> {
+        Some(self.infos.get(&idx).cloned().unwrap_or_default())
+    }
+    fn entity(&self, idx: i32) -> usize { *self.entities.get(&idx).unwrap_or(&0) }
+    fn extra_info_base(&self) -> usize { self.extra }
+    fn game_mod(&self) -> GameMod { self.game_mod }
+}
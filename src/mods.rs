@@ -0,0 +1,260 @@
+// mods.rs — The running mod, from the game directory, and what differs per mod.
+//
+// Much of the overlay was written against Counter-Strike: g_PlayerExtraInfo is
+// CS's client.dll array (team, health, dead), a CT's body group 1 is the
+// defuse kit, and team numbers 1 / 2 are T / CT. entities.rs reads the game
+// directory when it finds the engine table (pfnGetGameDirectory: "cstrike",
+// "czero", "dod", "tfc", "valve") and everything mod-specific goes through the
+// profile here:
+//
+//   extra_info     whether to look for g_PlayerExtraInfo at all
+//   extra_sigs     built-in patterns for it in the mod's client.dll (tried
+//                  after `[extra_info] signature`)
+//   extra_layout   its extra_player_info_t layout, when it isn't CS's (the
+//                  offsets.rs defaults); esp_offsets.toml still overrides it
+//   kit_from_body  the CS defuse kit inference from entity_state_t::body
+//   bot_slots      whether the mod has server-side bots (CS / CZ's ZBot): a bot
+//                  slot can be nameless while it joins, and a bot that stands
+//                  still isn't given up on as stale (see player.rs / entities.rs)
+//   team_colors    box colors for the mod's team numbers (after [team_colors],
+//                  before the theme; empty = the theme's T / CT colors)
+//   weapons        weapon model stems → display names (unlisted stems are shown
+//                  upper-cased, as before)
+//   hulls          standing / ducking hull heights when client.dll's hull table
+//                  can't be read (see player.rs)
+//
+// Unknown game directories get the HL profile: no extra info, no kit, no team
+// colors, standard hulls.
+//
+// DoD's and TFC's client.dll declare extra_player_info_t the way the HL SDK's
+// cl_dll/hud.h does, much smaller than CS's, so they get that layout. No
+// signature for their g_PlayerExtraInfo has been checked against a real
+// client.dll, so none is built in: set `[extra_info] signature` (and
+// `pointer_offset`) for the build in use. The SDK declares health and dead
+// but notes they're unused, so the mod may leave them zero; health is a short
+// there and isn't read.
+
+/// A mod the overlay knows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMod {
+    #[default]
+    Cstrike,
+    Czero,
+    Dod,
+    Tfc,
+    Valve,
+    Other,
+}
+
+/// Where a mod's extra_player_info_t keeps the fields offsets.rs reads
+/// (health 0 = not read).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtraLayout {
+    pub stride: usize,
+    pub team:   usize,
+    pub dead:   usize,
+    pub health: usize,
+}
+
+/// Mod-specific behavior.
+pub struct ModProfile {
+    pub extra_info:    bool,
+    pub extra_sigs:    &'static [(&'static str, usize)], // (pattern, offset of the array pointer from the match start)
+    pub extra_layout:  Option<ExtraLayout>,              // None = the offsets.rs defaults (CS)
+    pub kit_from_body: bool,
+    pub bot_slots:     bool,
+    pub team_colors:   &'static [(i32, [f32; 4])],
+    pub weapons:       &'static [(&'static str, &'static str)],
+    pub hulls:         Hulls,
+}
+
+/// Player hull heights (maxs.z - mins.z), standing and ducking.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hulls {
+    pub stand: f32,
+    pub duck:  f32,
+}
+
+const MIN_HULL: f32 = 16.0;   // Hull tables outside these heights aren't player hulls
+const MAX_HULL: f32 = 200.0;
+
+impl Hulls {
+    /// Standard human hull (all these mods use it).
+    pub const STANDARD: Hulls = Hulls { stand: 72.0, duck: 44.0 };
+
+    /// From hull 0's and hull 1's (mins.z, maxs.z) as client.dll's
+    /// HUD_GetHullBounds gives them. None unless they're player-sized and
+    /// ducking is lower than standing.
+    pub fn from_bounds(stand: (f32, f32), duck: (f32, f32)) -> Option<Self> {
+        let h = Hulls { stand: stand.1 - stand.0, duck: duck.1 - duck.0 };
+        (h.stand.is_finite() && h.duck.is_finite() && MIN_HULL <= h.duck && h.duck < h.stand && h.stand <= MAX_HULL)
+            .then_some(h)
+    }
+
+    /// Height for the stance.
+    pub fn height(self, ducking: bool) -> f32 {
+        if ducking { self.duck } else { self.stand }
+    }
+}
+
+/// extra_player_info_t in the HL SDK: short frags, deaths, playerclass, health;
+/// bool dead; short teamnumber; char teamname[16].
+const SDK_EXTRA: ExtraLayout = ExtraLayout { stride: 0x1C, team: 0x0A, dead: 0x08, health: 0 };
+
+const CSTRIKE: ModProfile = ModProfile {
+    extra_info:    true,
+    extra_sigs:    &[
+        // Primary (references g_PlayerExtraInfo via a pointer operand)
+        ("0F BF 87 ?? ?? ?? ?? 8B 16 50 68 ?? ?? ?? ?? 8B CE FF 52 ?? 8D 4C AD 00 66 8B 04 8D", 27),
+        // Alternate (different code generation, same data)
+        ("0F BF 87 ?? ?? ?? ?? 8B 16 50 68 ?? ?? ?? ?? 8B CE FF 52 ?? 8B CD C1 E1 05 66 8B 81 ?? ?? ?? ?? 66 3D 01 00 7D 46", 3),
+    ],
+    extra_layout:  None,
+    kit_from_body: true,
+    bot_slots:     true,
+    team_colors:   &[],
+    weapons:       &[],
+    hulls:         Hulls::STANDARD,
+};
+
+const DOD: ModProfile = ModProfile {
+    extra_info:    true,
+    extra_sigs:    &[],
+    extra_layout:  Some(SDK_EXTRA),
+    kit_from_body: false,
+    bot_slots:     false,
+    team_colors:   &[
+        (1, [0.35, 0.75, 0.30, 1.0]), // Allies
+        (2, [0.80, 0.30, 0.25, 1.0]), // Axis
+    ],
+    weapons:       &[
+        ("garand", "M1 Garand"), ("kar", "Kar98k"), ("thompson", "Thompson"), ("mp40", "MP40"),
+        ("colt", "Colt"), ("luger", "Luger"), ("bar", "BAR"), ("mp44", "StG 44"), ("spring", "Springfield"),
+    ],
+    hulls:         Hulls::STANDARD,
+};
+
+const TFC: ModProfile = ModProfile {
+    extra_info:    true,
+    extra_sigs:    &[],
+    extra_layout:  Some(SDK_EXTRA),
+    kit_from_body: false,
+    bot_slots:     false,
+    team_colors:   &[
+        (1, [0.25, 0.45, 1.00, 1.0]), // Blue
+        (2, [0.95, 0.20, 0.20, 1.0]), // Red
+        (3, [0.95, 0.90, 0.20, 1.0]), // Yellow
+        (4, [0.25, 0.85, 0.25, 1.0]), // Green
+    ],
+    weapons:       &[],
+    hulls:         Hulls::STANDARD,
+};
+
+const VALVE: ModProfile = ModProfile {
+    extra_info:    false,
+    extra_sigs:    &[],
+    extra_layout:  None,
+    kit_from_body: false,
+    bot_slots:     false,
+    team_colors:   &[],
+    weapons:       &[
+        ("9mmhandgun", "Glock"), ("9mmAR", "MP5"), ("357", ".357"), ("crowbar", "Crowbar"),
+        ("shotgun", "Shotgun"), ("crossbow", "Crossbow"), ("rpg", "RPG"), ("gauss", "Gauss"),
+        ("egon", "Egon"), ("hgun", "Hornet Gun"), ("squeak", "Snarks"), ("satchel", "Satchel"),
+        ("tripmine", "Tripmine"), ("grenade", "Grenade"),
+    ],
+    hulls:         Hulls::STANDARD,
+};
+
+impl GameMod {
+    /// The mod running from `game_dir` (as pfnGetGameDirectory gives it; a
+    /// full path works too).
+    pub fn from_game_dir(game_dir: &str) -> Self {
+        let dir = game_dir.trim_end_matches(['/', '\\']).rsplit(['/', '\\']).next().unwrap_or("");
+        match dir.to_ascii_lowercase().as_str() {
+            "cstrike" => GameMod::Cstrike,
+            "czero"   => GameMod::Czero,
+            "dod"     => GameMod::Dod,
+            "tfc"     => GameMod::Tfc,
+            "valve"   => GameMod::Valve,
+            _         => GameMod::Other,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMod::Cstrike => "Counter-Strike",
+            GameMod::Czero   => "Condition Zero",
+            GameMod::Dod     => "Day of Defeat",
+            GameMod::Tfc     => "Team Fortress Classic",
+            GameMod::Valve   => "Half-Life",
+            GameMod::Other   => "unknown mod",
+        }
+    }
+
+    pub fn profile(self) -> &'static ModProfile {
+        match self {
+            GameMod::Cstrike | GameMod::Czero => &CSTRIKE,
+            GameMod::Dod                      => &DOD,
+            GameMod::Tfc                      => &TFC,
+            GameMod::Valve | GameMod::Other   => &VALVE,
+        }
+    }
+}
+
+impl ModProfile {
+    /// Display name of a weapon from its model stem ("ak47" from p_ak47.mdl).
+    pub fn weapon_name(&self, stem: &str) -> String {
+        self.weapons.iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(stem))
+            .map_or_else(|| stem.to_uppercase(), |(_, name)| name.to_string())
+    }
+
+    /// The mod's color for team number `team`, if it has its own.
+    pub fn team_color(&self, team: i32) -> Option<[f32; 4]> {
+        self.team_colors.iter().find(|(t, _)| *t == team).map(|(_, c)| *c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_mods_from_game_dir() {
+        assert_eq!(GameMod::from_game_dir("cstrike"), GameMod::Cstrike);
+        assert_eq!(GameMod::from_game_dir("CZero"), GameMod::Czero);
+        assert_eq!(GameMod::from_game_dir("C:\\Games\\Half-Life\\dod\\"), GameMod::Dod);
+        assert_eq!(GameMod::from_game_dir("/home/x/hl/tfc"), GameMod::Tfc);
+        assert_eq!(GameMod::from_game_dir("valve"), GameMod::Valve);
+        assert_eq!(GameMod::from_game_dir("gearbox"), GameMod::Other);
+        assert_eq!(GameMod::from_game_dir(""), GameMod::Other);
+    }
+
+    #[test]
+    fn profiles_differ_where_cs_assumptions_do() {
+        let cs = GameMod::Cstrike.profile();
+        assert!(cs.extra_info && cs.kit_from_body && cs.team_color(1).is_none());
+        assert_eq!(cs.weapon_name("ak47"), "AK47");
+
+        let tfc = GameMod::Tfc.profile();
+        assert!(tfc.extra_info && tfc.extra_sigs.is_empty() && !tfc.kit_from_body);
+        assert_eq!(tfc.extra_layout.map(|l| (l.stride, l.team)), Some((0x1C, 0x0A)));
+        assert!(!GameMod::Valve.profile().extra_info);
+        assert_eq!(tfc.team_color(4), Some([0.25, 0.85, 0.25, 1.0]));
+        assert_eq!(tfc.team_color(5), None);
+
+        assert_eq!(GameMod::Dod.profile().weapon_name("Garand"), "M1 Garand");
+        assert_eq!(GameMod::Other.profile().weapon_name("9mmAR"), "MP5");
+        assert_eq!(GameMod::Valve.profile().weapon_name("displacer"), "DISPLACER");
+    }
+
+    #[test]
+    fn hull_tables_are_checked() {
+        let hl = Hulls::from_bounds((-36.0, 36.0), (-18.0, 18.0)).unwrap();
+        assert_eq!((hl.height(false), hl.height(true)), (72.0, 36.0));
+        assert_eq!(Hulls::from_bounds((f32::NAN, f32::NAN), (-18.0, 18.0)), None); // Not filled in
+        assert_eq!(Hulls::from_bounds((-18.0, 18.0), (-36.0, 36.0)), None);         // Ducking taller
+        assert_eq!(Hulls::from_bounds((0.0, 0.0), (0.0, 0.0)), None);
+    }
+}
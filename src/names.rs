@@ -0,0 +1,223 @@
+// names.rs — Decoding of player names from the engine's raw C strings.
+//
+// Names arrive as whatever bytes the player's client sent. Newer clients send
+// UTF-8, but plenty of Russian servers are full of CP1251 names and Chinese
+// ones of GBK. The decoder picks one of those per name:
+//
+//   1. Valid UTF-8 (with at least one non-ASCII byte)  -> taken as is
+//   2. Bytes that pair up as GBK double-byte characters,
+//      and don't look like mostly-lowercase Cyrillic     -> GBK, each character becomes '?'
+//   3. Anything else                                     -> CP1251
+//
+// GBK needs a ~22k-entry table to decode properly, which isn't worth carrying
+// in the DLL; a name like "??abc" is still far better than dropping the player.
+// Control characters are removed in every case.
+//
+// The overlay font covers ASCII and Latin-1 letters, so `display()` keeps those
+// and transliterates Cyrillic to Latin letters for drawing; the decoded name
+// (real Unicode) goes to the log.
+
+/// Which encoding a name was decoded as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding { Ascii, Utf8, Gbk, Cp1251 }
+
+/// Share of high bytes that must be CP1251 lowercase letters (0xE0..=0xFF) for a
+/// GBK-shaped name to be read as CP1251. Russian names are mostly lowercase;
+/// GBK lead/trail bytes spread evenly over 0xA1..=0xFE.
+const CP1251_LOWER_SHARE: f32 = 0.5;
+
+/// Decode raw name bytes (without the terminator). Returns None if nothing
+/// printable is left.
+pub fn decode(raw: &[u8]) -> Option<(String, Encoding)> {
+    let enc = detect(raw);
+    let text: String = match enc {
+        Encoding::Ascii | Encoding::Utf8 => String::from_utf8_lossy(raw).into_owned(),
+        Encoding::Gbk    => decode_gbk_lossy(raw),
+        Encoding::Cp1251 => raw.iter().map(|&b| cp1251_char(b)).collect(),
+    };
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    if text.trim().is_empty() { None } else { Some((text, enc)) }
+}
+
+/// Guess the encoding of `raw`.
+pub fn detect(raw: &[u8]) -> Encoding {
+    if raw.is_ascii() { return Encoding::Ascii; }
+    if std::str::from_utf8(raw).is_ok() { return Encoding::Utf8; }
+    if pairs_as_gbk(raw) {
+        let high: Vec<u8> = raw.iter().copied().filter(|&b| b >= 0x80).collect();
+        let lower = high.iter().filter(|&&b| b >= 0xE0).count();
+        // Punctuation/symbol bytes and ASCII-range trail bytes don't occur in Cyrillic names
+        let non_letter = high.iter().any(|&b| b < 0xC0 && !is_cp1251_letter(b));
+        let ascii_trail = raw.windows(2).any(|w| w[0] >= 0x81 && (0x40..0x80).contains(&w[1]));
+        if non_letter || ascii_trail || (lower as f32) < high.len() as f32 * CP1251_LOWER_SHARE {
+            return Encoding::Gbk;
+        }
+    }
+    Encoding::Cp1251
+}
+
+/// Whether every high byte in `raw` is part of a GBK lead/trail pair.
+fn pairs_as_gbk(raw: &[u8]) -> bool {
+    let mut i = 0;
+    while i < raw.len() {
+        let b = raw[i];
+        if b < 0x80 { i += 1; continue; }
+        let Some(&t) = raw.get(i + 1) else { return false };
+        if !(0x81..=0xFE).contains(&b) || !(0x40..=0xFE).contains(&t) || t == 0x7F { return false; }
+        i += 2;
+    }
+    true
+}
+
+/// GBK with every double-byte character replaced by '?'.
+fn decode_gbk_lossy(raw: &[u8]) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] < 0x80 {
+            out.push(raw[i] as char);
+            i += 1;
+        } else {
+            out.push('?');
+            i += 2;
+        }
+    }
+    out
+}
+
+/// CP1251 letters: А..я plus Ё/ё and the Ukrainian/Belarusian extras.
+fn is_cp1251_letter(b: u8) -> bool {
+    b >= 0xC0 || matches!(b, 0xA1 | 0xA2 | 0xA5 | 0xA8 | 0xAA | 0xAF | 0xB2 | 0xB3 | 0xB4 | 0xB8 | 0xBA | 0xBF)
+}
+
+/// Unicode for a CP1251 byte.
+fn cp1251_char(b: u8) -> char {
+    const HIGH: [u16; 64] = [
+        0x0402, 0x0403, 0x201A, 0x0453, 0x201E, 0x2026, 0x2020, 0x2021,
+        0x20AC, 0x2030, 0x0409, 0x2039, 0x040A, 0x040C, 0x040B, 0x040F,
+        0x0452, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+        0xFFFD, 0x2122, 0x0459, 0x203A, 0x045A, 0x045C, 0x045B, 0x045F,
+        0x00A0, 0x040E, 0x045E, 0x0408, 0x00A4, 0x0490, 0x00A6, 0x00A7,
+        0x0401, 0x00A9, 0x0404, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x0407,
+        0x00B0, 0x00B1, 0x0406, 0x0456, 0x0491, 0x00B5, 0x00B6, 0x00B7,
+        0x0451, 0x2116, 0x0454, 0x00BB, 0x0458, 0x0405, 0x0455, 0x0457,
+    ];
+    match b {
+        0x00..=0x7F => b as char,
+        0x80..=0xBF => char::from_u32(HIGH[(b - 0x80) as usize] as u32).unwrap_or('?'),
+        _           => char::from_u32(0x0410 + (b - 0xC0) as u32).unwrap_or('?'), // А..я
+    }
+}
+
+// ============================================================
+// Display
+// ============================================================
+
+/// Rendering of a decoded name for the overlay font: ASCII and Latin-1 letters
+/// (U+00C0..U+00FF) pass through, Cyrillic is transliterated, anything else becomes '?'.
+pub fn display(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii() || ('\u{C0}'..='\u{FF}').contains(&c) {
+            out.push(c);
+        } else if let Some(t) = translit_cyrillic(c) {
+            out.push_str(t);
+        } else {
+            out.push('?');
+        }
+    }
+    out
+}
+
+/// Latin transliteration of a Cyrillic letter (None if `c` isn't one).
+fn translit_cyrillic(c: char) -> Option<&'static str> {
+    const UPPER: [&str; 32] = [
+        "A", "B", "V", "G", "D", "E", "Zh", "Z", "I", "Y", "K", "L", "M", "N", "O", "P",
+        "R", "S", "T", "U", "F", "Kh", "Ts", "Ch", "Sh", "Shch", "", "Y", "", "E", "Yu", "Ya",
+    ];
+    const LOWER: [&str; 32] = [
+        "a", "b", "v", "g", "d", "e", "zh", "z", "i", "y", "k", "l", "m", "n", "o", "p",
+        "r", "s", "t", "u", "f", "kh", "ts", "ch", "sh", "shch", "", "y", "", "e", "yu", "ya",
+    ];
+    let u = c as u32;
+    Some(match u {
+        0x0410..=0x042F => UPPER[(u - 0x0410) as usize],
+        0x0430..=0x044F => LOWER[(u - 0x0430) as usize],
+        0x0401 => "Yo",
+        0x0451 => "yo",
+        0x0404 => "Ye",
+        0x0454 => "ye",
+        0x0406 | 0x0407 => "I",
+        0x0456 | 0x0457 => "i",
+        0x040E => "U",
+        0x045E => "u",
+        0x0490 => "G",
+        0x0491 => "g",
+        _ => return None,
+    })
+}
+
+/// Raw bytes as hex, for the log ("d0 98 d0 b2 ...").
+pub fn hex(raw: &[u8]) -> String {
+    raw.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_and_utf8_pass_through() {
+        assert_eq!(decode(b"player"), Some(("player".into(), Encoding::Ascii)));
+        let utf8 = "Иван".as_bytes();
+        assert_eq!(decode(utf8), Some(("Иван".into(), Encoding::Utf8)));
+    }
+
+    #[test]
+    fn cp1251_cyrillic() {
+        // "Иван" in CP1251
+        let raw = [0xC8, 0xE2, 0xE0, 0xED];
+        assert_eq!(detect(&raw), Encoding::Cp1251);
+        assert_eq!(decode(&raw).unwrap().0, "Иван");
+        // "Ёж" — Ё lives outside the А..я block
+        assert_eq!(decode(&[0xA8, 0xE6]).unwrap().0, "Ёж");
+    }
+
+    #[test]
+    fn gbk_becomes_question_marks() {
+        // "中文" in GBK, then ASCII
+        let raw = [0xD6, 0xD0, 0xCE, 0xC4, b'x'];
+        assert_eq!(detect(&raw), Encoding::Gbk);
+        assert_eq!(decode(&raw).unwrap().0, "??x");
+        // Trail byte in the ASCII range is only possible in GBK
+        assert_eq!(detect(&[0x81, 0x40]), Encoding::Gbk);
+    }
+
+    #[test]
+    fn odd_high_run_is_cp1251() {
+        // Three Cyrillic letters can't pair up as GBK
+        assert_eq!(detect(&[0xEF, 0xF0, 0xE8]), Encoding::Cp1251);
+    }
+
+    #[test]
+    fn control_chars_and_empty() {
+        assert_eq!(decode(b"\x01a\x7fb").unwrap().0, "ab");
+        assert_eq!(decode(b""), None);
+        assert_eq!(decode(b"\x02\x03"), None);
+        assert_eq!(decode(b"   "), None);
+    }
+
+    #[test]
+    fn display_transliterates() {
+        assert_eq!(display("Иван"), "Ivan");
+        assert_eq!(display("Щука"), "Shchuka");
+        assert_eq!(display("a中b"), "a?b");
+        assert_eq!(display("plain"), "plain");
+        assert_eq!(display("Zoë Ø"), "Zoë Ø");
+    }
+
+    #[test]
+    fn hex_dump() {
+        assert_eq!(hex(&[0xC8, 0x00, 0x7F]), "c8 00 7f");
+    }
+}
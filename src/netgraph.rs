@@ -0,0 +1,158 @@
+// netgraph.rs — Net channel statistics and the netgraph-style mini panel.
+//
+// The scoreboard ping (HudPlayerInfo) for the local player is only refreshed
+// when the server sends a new one, and is often seconds stale. The engine's own
+// net channel (netchan_t, in hw.dll) has live numbers: esp.rs finds it with
+// `[netgraph] signature` and reads latency, choke, loss and the incoming rate
+// at the `[netgraph] *_offset` fields, SAMPLE_MS apart. Here the samples are
+// kept, summed up in a few text rows and drawn as a bar per sample, colored by
+// latency the way the game's net_graph does.
+//
+// Every field is optional: an offset of 0 isn't read and shows as "-".
+
+use crate::draw::{Cmd, DrawList};
+
+pub const SAMPLE_MS: u32 = 50;          // Time between samples
+const HISTORY: usize = 64;              // Samples in the graph
+const LATENCY_WARN_MS: f32 = 100.0;     // Bars turn yellow above this ...
+const LATENCY_BAD_MS: f32 = 200.0;      // ... and red above this
+const GRAPH_MIN_MS: f32 = 100.0;        // Graph height covers at least this much latency
+
+/// One read of the net channel. None = field not configured or unreadable.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetSample {
+    pub latency_ms: Option<f32>,
+    pub choke:      Option<f32>, // Percent
+    pub loss:       Option<f32>, // Percent
+    pub rate_in:    Option<f32>, // Incoming kB/s
+}
+
+/// Colors of the bars: fine, high latency, very high latency.
+pub struct BarColors {
+    pub ok:   [f32; 4],
+    pub warn: [f32; 4],
+    pub bad:  [f32; 4],
+}
+
+/// The recent samples.
+pub struct NetGraph {
+    samples: Vec<NetSample>, // Oldest first
+    last_ms: u32,            // When the last sample was taken (0 = never)
+}
+
+impl NetGraph {
+    pub const fn new() -> Self {
+        Self { samples: Vec::new(), last_ms: 0 }
+    }
+
+    /// Whether a new sample is due at `now`.
+    pub fn due(&self, now: u32) -> bool {
+        self.last_ms == 0 || now.wrapping_sub(self.last_ms) >= SAMPLE_MS
+    }
+
+    pub fn push(&mut self, now: u32, sample: NetSample) {
+        if self.samples.len() == HISTORY { self.samples.remove(0); }
+        self.samples.push(sample);
+        self.last_ms = now;
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.last_ms = 0;
+    }
+
+    pub fn latest(&self) -> Option<&NetSample> {
+        self.samples.last()
+    }
+
+    /// Text rows: the latest values, then the latency range over the graph.
+    pub fn lines(&self) -> Vec<String> {
+        let Some(s) = self.latest() else { return vec!["no samples".to_string()] };
+        let f = |v: Option<f32>, unit: &str| v.map_or_else(|| "-".to_string(), |v| format!("{:.0}{}", v, unit));
+        let mut out = vec![
+            format!("latency {}  in {}", f(s.latency_ms, " ms"), s.rate_in.map_or_else(|| "-".to_string(), |r| format!("{:.1} kB/s", r))),
+            format!("choke {}  loss {}", f(s.choke, "%"), f(s.loss, "%")),
+        ];
+        let lat: Vec<f32> = self.samples.iter().filter_map(|s| s.latency_ms).collect();
+        if let (Some(lo), Some(hi)) = (lat.iter().copied().reduce(f32::min), lat.iter().copied().reduce(f32::max)) {
+            out.push(format!("range {:.0}-{:.0} ms over {:.1} s", lo, hi,
+                (self.samples.len() as u32 * SAMPLE_MS) as f32 / 1000.0));
+        }
+        out
+    }
+
+    /// One latency bar per sample in `area` ([x0, y0, x1, y1], pixels), newest
+    /// at the right, scaled to the highest latency shown. Samples without a
+    /// latency leave a gap.
+    pub fn bars(&self, area: [f32; 4], colors: &BarColors) -> DrawList {
+        let mut list = DrawList::new();
+        let [x0, y0, x1, y1] = area;
+        let top = self.samples.iter().filter_map(|s| s.latency_ms).fold(GRAPH_MIN_MS, f32::max);
+        let w = (x1 - x0) / HISTORY as f32;
+        let skip = HISTORY - self.samples.len();
+        for (i, s) in self.samples.iter().enumerate() {
+            let Some(ms) = s.latency_ms else { continue };
+            let color = if ms > LATENCY_BAD_MS {
+                colors.bad
+            } else if ms > LATENCY_WARN_MS {
+                colors.warn
+            } else {
+                colors.ok
+            };
+            let x = x0 + (skip + i) as f32 * w;
+            let h = (ms / top).clamp(0.0, 1.0) * (y1 - y0);
+            list.push(Cmd::Fill { rect: [x, y1 - h.max(1.0), x + (w - 1.0).max(1.0), y1], color });
+        }
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: BarColors = BarColors { ok: [0.0, 1.0, 0.0, 1.0], warn: [1.0, 1.0, 0.0, 1.0], bad: [1.0, 0.0, 0.0, 1.0] };
+
+    fn lat(ms: f32) -> NetSample {
+        NetSample { latency_ms: Some(ms), ..NetSample::default() }
+    }
+
+    #[test]
+    fn samples_are_paced_and_bounded() {
+        let mut g = NetGraph::new();
+        assert!(g.due(1));
+        g.push(1000, lat(40.0));
+        assert!(!g.due(1000 + SAMPLE_MS - 1) && g.due(1000 + SAMPLE_MS));
+        for i in 0..HISTORY as u32 + 10 { g.push(2000 + i * SAMPLE_MS, lat(i as f32)); }
+        assert_eq!(g.samples.len(), HISTORY);
+        assert_eq!(g.latest(), Some(&lat((HISTORY + 9) as f32)));
+        g.clear();
+        assert!(g.due(5) && g.latest().is_none());
+    }
+
+    #[test]
+    fn lines_show_missing_fields_as_dashes() {
+        let mut g = NetGraph::new();
+        assert_eq!(g.lines(), ["no samples"]);
+        g.push(1, NetSample { latency_ms: Some(35.4), choke: None, loss: Some(2.0), rate_in: Some(12.25) });
+        g.push(60, lat(80.0));
+        assert_eq!(g.lines(), ["latency 80 ms  in -", "choke -  loss -", "range 35-80 ms over 0.1 s"]);
+    }
+
+    #[test]
+    fn bars_are_colored_and_scaled_by_latency() {
+        let mut g = NetGraph::new();
+        for (t, ms) in [(1, 50.0), (60, 150.0), (120, 300.0)] { g.push(t, lat(ms)); }
+        g.push(180, NetSample::default());
+        let list = g.bars([0.0, 0.0, 128.0, 30.0], &COLORS);
+        let fills: Vec<([f32; 4], [f32; 4])> = list.cmds().iter().map(|c| match c {
+            Cmd::Fill { rect, color } => (*rect, *color),
+            other => panic!("unexpected {}", other),
+        }).collect();
+        assert_eq!(fills.len(), 3); // The sample without latency is a gap
+        assert_eq!(fills.iter().map(|f| f.1).collect::<Vec<_>>(), [COLORS.ok, COLORS.warn, COLORS.bad]);
+        assert_eq!(fills[2].0[1], 0.0);  // Tallest = full height
+        assert_eq!(fills[0].0[1], 25.0); // 50 of 300 ms
+        assert_eq!(fills[2].0[0], 124.0); // Right-aligned: slot 62 of 64, 2 px each
+    }
+}
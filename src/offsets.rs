@@ -0,0 +1,158 @@
+// offsets.rs — Structure offsets that can be changed without a rebuild.
+//
+// player.rs's constants are the offsets for engine build 4554. The ones that
+// move between builds — where entity_state_t and the interpolated origin sit
+// in cl_entity_t, the position history, and the layout of client.dll's
+// extra_player_info_t — are read through here instead, so they can be
+// overridden from esp_offsets.toml next to the DLL, and nudged live with the
+// offset tuner (diagnostics page 3), which writes the working set back there.
+//
+// The file is `name = value` lines, value in hex (0x2B0) or decimal, using the
+// names below. Lines that don't parse are reported and skipped. entity_state_t
+// itself is the network format and doesn't change, so its fields stay fixed.
+//
+// extra_info.kit and extra_info.helmet are fields build 4554 doesn't have;
+// they're 0 (not read) unless set for a build that does.
+
+use crate::player::{
+    CURSTATE_OFFSET, ENT_CURPOS, ENT_ORIGIN, ENT_PH_BASE,
+    EXTRA_OFF_DEAD, EXTRA_OFF_HEALTH, EXTRA_OFF_HELMET, EXTRA_OFF_KIT, EXTRA_OFF_TEAMNUMBER, EXTRA_STRIDE,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An overridable offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Offset {
+    Curstate,    // cl_entity_t -> entity_state_t curstate
+    Origin,      // cl_entity_t::origin
+    CurPos,      // cl_entity_t::current_position
+    PosHistory,  // cl_entity_t::ph[]
+    ExtraStride, // sizeof(extra_player_info_t)
+    ExtraTeam,   // extra_player_info_t::teamnumber
+    ExtraDead,   // extra_player_info_t::dead
+    ExtraHealth, // extra_player_info_t::health
+    ExtraKit,    // extra_player_info_t::has_defuse_kit (0 = absent)
+    ExtraHelmet, // extra_player_info_t helmet flag (0 = absent)
+}
+
+pub const ALL: [Offset; 10] = [
+    Offset::Curstate, Offset::Origin, Offset::CurPos, Offset::PosHistory,
+    Offset::ExtraStride, Offset::ExtraTeam, Offset::ExtraDead, Offset::ExtraHealth,
+    Offset::ExtraKit, Offset::ExtraHelmet,
+];
+
+/// Values in use, indexed by Offset.
+static VALUES: [AtomicUsize; 10] = [
+    AtomicUsize::new(CURSTATE_OFFSET),
+    AtomicUsize::new(ENT_ORIGIN),
+    AtomicUsize::new(ENT_CURPOS),
+    AtomicUsize::new(ENT_PH_BASE),
+    AtomicUsize::new(EXTRA_STRIDE),
+    AtomicUsize::new(EXTRA_OFF_TEAMNUMBER),
+    AtomicUsize::new(EXTRA_OFF_DEAD),
+    AtomicUsize::new(EXTRA_OFF_HEALTH),
+    AtomicUsize::new(EXTRA_OFF_KIT),
+    AtomicUsize::new(EXTRA_OFF_HELMET),
+];
+
+impl Offset {
+    /// Name in esp_offsets.toml and on the diagnostics page.
+    pub fn name(self) -> &'static str {
+        match self {
+            Offset::Curstate    => "cl_entity.curstate",
+            Offset::Origin      => "cl_entity.origin",
+            Offset::CurPos      => "cl_entity.curpos",
+            Offset::PosHistory  => "cl_entity.ph",
+            Offset::ExtraStride => "extra_info.stride",
+            Offset::ExtraTeam   => "extra_info.team",
+            Offset::ExtraDead   => "extra_info.dead",
+            Offset::ExtraHealth => "extra_info.health",
+            Offset::ExtraKit    => "extra_info.kit",
+            Offset::ExtraHelmet => "extra_info.helmet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL.into_iter().find(|o| o.name() == name)
+    }
+
+    /// The build 4554 value.
+    pub fn default_value(self) -> usize {
+        match self {
+            Offset::Curstate    => CURSTATE_OFFSET,
+            Offset::Origin      => ENT_ORIGIN,
+            Offset::CurPos      => ENT_CURPOS,
+            Offset::PosHistory  => ENT_PH_BASE,
+            Offset::ExtraStride => EXTRA_STRIDE,
+            Offset::ExtraTeam   => EXTRA_OFF_TEAMNUMBER,
+            Offset::ExtraDead   => EXTRA_OFF_DEAD,
+            Offset::ExtraHealth => EXTRA_OFF_HEALTH,
+            Offset::ExtraKit    => EXTRA_OFF_KIT,
+            Offset::ExtraHelmet => EXTRA_OFF_HELMET,
+        }
+    }
+
+    /// The value in use.
+    pub fn get(self) -> usize {
+        VALUES[self as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn set(self, value: usize) {
+        VALUES[self as usize].store(value, Ordering::Relaxed);
+    }
+}
+
+/// Every offset with the value in use.
+pub fn current() -> Vec<(Offset, usize)> {
+    ALL.iter().map(|&o| (o, o.get())).collect()
+}
+
+/// Parse an override file: the values it sets, and the (1-based) numbers of
+/// the lines it couldn't use.
+pub fn parse(text: &str) -> (Vec<(Offset, usize)>, Vec<usize>) {
+    let (mut values, mut bad) = (Vec::new(), Vec::new());
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() { continue; }
+        let parsed = line.split_once('=').and_then(|(k, v)| {
+            let v = v.trim().trim_matches('"');
+            let value = match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+                Some(hex) => usize::from_str_radix(hex, 16).ok(),
+                None      => v.parse().ok(),
+            };
+            Some((Offset::from_name(k.trim())?, value?))
+        });
+        match parsed {
+            Some(p) => values.push(p),
+            None    => bad.push(i + 1),
+        }
+    }
+    (values, bad)
+}
+
+/// Contents of an override file holding `values`; `note` goes in the header.
+pub fn format(values: &[(Offset, usize)], note: &str) -> String {
+    let mut out = format!("# Structure offsets ({})\n", note);
+    for &(o, v) in values {
+        out.push_str(&format!("{:<20} = {:#x}\n", o.name(), v));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_file_round_trips() {
+        let values = [(Offset::Curstate, 0x2B8), (Offset::ExtraDead, 64)];
+        let text = format(&values, "build 8684");
+        assert!(text.starts_with("# Structure offsets (build 8684)\n"));
+        assert_eq!(parse(&text), (values.to_vec(), vec![]));
+
+        let (values, bad) = parse("cl_entity.origin = \"0xB50\"\nnot.a.field = 1\nextra_info.dead = zz\n");
+        assert_eq!(values, [(Offset::Origin, 0xB50)]);
+        assert_eq!(bad, [2, 3]);
+        assert_eq!(Offset::ExtraStride.default_value(), EXTRA_STRIDE);
+    }
+}
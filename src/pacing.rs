@@ -0,0 +1,166 @@
+// pacing.rs — Game frame rate, frame pacing and whether the overlay causes spikes.
+//
+// esp.rs stamps every present (the swap buffers detour, or HUD_Redraw) and
+// times its own work in it. The intervals between presents are the game's
+// frame times: over the last WINDOW frames they give the frame rate, the
+// jitter (standard deviation of the frame time) and, when nearly every frame
+// takes the same time, the frame rate cap (fps_max) the game is held at.
+//
+// A spike is a frame that took well over the median. If the overlay's own time
+// in that frame accounts for a good part of the excess, the spike is counted
+// against the overlay; when enough of the recent spikes are, esp.rs logs a
+// warning. Spikes without the overlay to blame point at the game or the server.
+
+const WINDOW: usize = 240;              // Frames kept
+const MIN_FRAMES: usize = 60;           // Frames needed before anything is reported
+const MAX_INTERVAL_US: u64 = 250_000;   // Longer gaps (paused, loading, alt-tab) aren't frames
+const SPIKE_FACTOR: f32 = 2.0;          // A spike takes this many medians ...
+const SPIKE_MIN_EXCESS_US: f32 = 2000.0; // ... and at least this much longer than one
+const OVERLAY_SHARE: f32 = 0.5;         // Overlay time covering this part of the excess = overlay's fault
+const MIN_SPIKES: usize = 3;            // Spikes in the window before blaming the overlay
+const CAP_TOLERANCE: f32 = 0.08;        // Frames within this fraction of the median count as "on the cap"
+const CAP_SHARE: f32 = 0.9;             // Share of frames on the cap for the rate to be capped
+
+/// One game frame: time since the previous present, and the overlay's part of it.
+#[derive(Clone, Copy, Debug, Default)]
+struct Frame {
+    interval_us: f32,
+    overlay_us:  f32,
+}
+
+/// What the last WINDOW frames look like.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Report {
+    pub fps:            f32,
+    pub jitter_ms:      f32,
+    pub cap:            Option<u32>, // Frame rate the game is held at, if it is
+    pub spikes:         usize,
+    pub overlay_spikes: usize,       // Spikes the overlay's own time accounts for
+}
+
+impl Report {
+    /// Whether the overlay looks responsible for the frame spikes.
+    pub fn overlay_suspect(&self) -> bool {
+        self.spikes >= MIN_SPIKES && self.overlay_spikes * 2 >= self.spikes
+    }
+}
+
+/// Present timestamps and overlay times of the recent frames.
+pub struct Pacing {
+    frames:     Vec<Frame>,
+    next:       usize,       // Ring position of the next frame
+    last_at_us: u64,         // Previous present (0 = none yet)
+    overlay_us: f32,         // Overlay time of the frame in progress
+}
+
+impl Pacing {
+    pub const fn new() -> Self {
+        Self { frames: Vec::new(), next: 0, last_at_us: 0, overlay_us: 0.0 }
+    }
+
+    /// A present at `at_us`: closes the frame that started at the previous one.
+    pub fn present(&mut self, at_us: u64) {
+        let last = std::mem::replace(&mut self.last_at_us, at_us);
+        let overlay_us = std::mem::take(&mut self.overlay_us);
+        let interval = at_us.wrapping_sub(last);
+        if last == 0 || interval == 0 || interval > MAX_INTERVAL_US { return; }
+
+        let frame = Frame { interval_us: interval as f32, overlay_us };
+        if self.frames.len() < WINDOW {
+            self.frames.push(frame);
+        } else {
+            self.frames[self.next] = frame;
+        }
+        self.next = (self.next + 1) % WINDOW;
+    }
+
+    /// The overlay's time in the frame in progress.
+    pub fn overlay(&mut self, us: f32) {
+        self.overlay_us += us;
+    }
+
+    /// Frame rate, jitter, cap and spikes, once MIN_FRAMES frames are in.
+    pub fn report(&self) -> Option<Report> {
+        let n = self.frames.len();
+        if n < MIN_FRAMES { return None; }
+
+        let mut sorted: Vec<f32> = self.frames.iter().map(|f| f.interval_us).collect();
+        sorted.sort_by(f32::total_cmp);
+        let median = sorted[n / 2];
+        let mean = sorted.iter().sum::<f32>() / n as f32;
+        let var = sorted.iter().map(|i| (i - mean) * (i - mean)).sum::<f32>() / n as f32;
+
+        let on_cap = sorted.iter().filter(|&&i| (i - median).abs() <= median * CAP_TOLERANCE).count();
+        let cap = (on_cap as f32 >= n as f32 * CAP_SHARE).then(|| (1_000_000.0 / median).round() as u32);
+
+        let spike = |f: &&Frame| {
+            let excess = f.interval_us - median;
+            f.interval_us > median * SPIKE_FACTOR && excess >= SPIKE_MIN_EXCESS_US
+        };
+        let spikes: Vec<&Frame> = self.frames.iter().filter(spike).collect();
+        let overlay_spikes = spikes.iter()
+            .filter(|f| f.overlay_us >= (f.interval_us - median) * OVERLAY_SHARE)
+            .count();
+
+        Some(Report {
+            fps: 1_000_000.0 / mean,
+            jitter_ms: var.sqrt() / 1000.0,
+            cap,
+            spikes: spikes.len(),
+            overlay_spikes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed frames of the given intervals and overlay times.
+    fn run(p: &mut Pacing, at: &mut u64, frames: impl IntoIterator<Item = (u64, f32)>) {
+        for (interval, overlay) in frames {
+            p.overlay(overlay);
+            *at += interval;
+            p.present(*at);
+        }
+    }
+
+    #[test]
+    fn steady_frames_are_capped() {
+        let (mut p, mut at) = (Pacing::new(), 1);
+        p.present(at);
+        run(&mut p, &mut at, (0..MIN_FRAMES - 1).map(|_| (10_000, 300.0)));
+        assert_eq!(p.report(), None);
+
+        run(&mut p, &mut at, (0..100).map(|i| (if i % 2 == 0 { 9_800 } else { 10_200 }, 300.0)));
+        let r = p.report().unwrap();
+        assert_eq!(r.cap, Some(100));
+        assert!((r.fps - 100.0).abs() < 1.0 && r.jitter_ms < 0.5);
+        assert_eq!(r.spikes, 0);
+
+        // A long gap isn't a frame
+        at += 2_000_000;
+        p.present(at);
+        assert_eq!(p.report().unwrap().spikes, 0);
+    }
+
+    #[test]
+    fn spikes_are_blamed_on_the_overlay_only_when_it_was_slow() {
+        let (mut p, mut at) = (Pacing::new(), 1);
+        p.present(at);
+        let uneven = (0..200u64).map(|i| (4_000 + (i * 37 % 5) * 400, 200.0));
+        run(&mut p, &mut at, uneven);
+
+        // Server / game spikes: the overlay was as fast as ever
+        run(&mut p, &mut at, (0..4).map(|_| (30_000, 200.0)));
+        let r = p.report().unwrap();
+        assert_eq!((r.spikes, r.overlay_spikes, r.cap), (4, 0, None));
+        assert!(!r.overlay_suspect());
+
+        // The overlay took most of the extra time
+        run(&mut p, &mut at, (0..4).map(|_| (30_000, 20_000.0)));
+        let r = p.report().unwrap();
+        assert_eq!((r.spikes, r.overlay_spikes), (8, 4));
+        assert!(r.overlay_suspect());
+    }
+}
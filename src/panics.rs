@@ -0,0 +1,156 @@
+// panics.rs — Panics caught in the overlay, and when to give up on a part of it.
+//
+// The optional parts of a frame (trails, grenades, the minimap, the feed, ...)
+// each run under their own catch_unwind (esp.rs), so a bug in one of them
+// costs that part instead of the rest of the frame. The caught panics are
+// counted here per subsystem over a sliding minute. One that panics
+// `threshold` times within it is switched off through its config flag (until
+// the config is reloaded) rather than failing quietly every frame; the status
+// line shows an ERR badge as soon as anything has panicked.
+
+pub const WINDOW_MS: u32 = 60_000;      // Panics are counted over the last minute
+
+/// A part of the overlay that runs under its own panic guard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subsystem {
+    Frame, // The whole frame (the guard in hook.rs)
+    Brushes, Trails, DeathMarkers, Grenades, SoundRings, Tracers,
+    #[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+    Script,
+    Plugins,
+    #[cfg_attr(not(feature = "radar"), allow(dead_code))]
+    Minimap,
+    Alerts, Threat, Compass, Stats, Session,
+    #[cfg_attr(not(feature = "radar"), allow(dead_code))]
+    Feed,
+    Netgraph, Summary, Slots, Interp, HistoryGraph, Spectators, Chat, Annotations, Markers,
+}
+
+impl Subsystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Frame        => "frame",
+            Subsystem::Brushes      => "brush outlines",
+            Subsystem::Trails       => "trails",
+            Subsystem::DeathMarkers => "death markers",
+            Subsystem::Grenades     => "grenades",
+            Subsystem::SoundRings   => "sound rings",
+            Subsystem::Tracers      => "tracers",
+            Subsystem::Script       => "script",
+            Subsystem::Plugins      => "plugins",
+            Subsystem::Minimap      => "minimap",
+            Subsystem::Alerts       => "alerts",
+            Subsystem::Threat       => "threat warning",
+            Subsystem::Compass      => "compass strip",
+            Subsystem::Stats        => "stats",
+            Subsystem::Session      => "session export",
+            Subsystem::Feed         => "feed",
+            Subsystem::Netgraph     => "net graph",
+            Subsystem::Summary      => "round summary",
+            Subsystem::Slots        => "slot strip",
+            Subsystem::Interp       => "interp diagnostics",
+            Subsystem::HistoryGraph => "history graph",
+            Subsystem::Spectators   => "spectator list",
+            Subsystem::Chat         => "chat",
+            Subsystem::Annotations  => "annotations",
+            Subsystem::Markers      => "health markers",
+        }
+    }
+}
+
+/// Caught panics: the last minute's per subsystem, the session total, and
+/// what has been switched off.
+pub struct PanicLog {
+    recent:   Vec<(Subsystem, u32)>, // (subsystem, time in ms) within WINDOW_MS
+    total:    u32,
+    disabled: Vec<Subsystem>,
+}
+
+impl PanicLog {
+    pub const fn new() -> Self {
+        Self { recent: Vec::new(), total: 0, disabled: Vec::new() }
+    }
+
+    /// Record a panic in `sub` at `now`. True = it has now panicked
+    /// `threshold` times within a minute and should be switched off (0 =
+    /// never); its count starts over, so if it's turned back on it gets the
+    /// same allowance again.
+    pub fn record(&mut self, sub: Subsystem, now: u32, threshold: u32) -> bool {
+        self.recent.retain(|&(_, t)| now.wrapping_sub(t) < WINDOW_MS);
+        self.recent.push((sub, now));
+        self.total = self.total.saturating_add(1);
+        if threshold == 0 || self.count(sub, now) < threshold as usize { return false; }
+        self.recent.retain(|&(s, _)| s != sub);
+        if !self.disabled.contains(&sub) { self.disabled.push(sub); }
+        true
+    }
+
+    /// Panics in `sub` within the last minute.
+    pub fn count(&self, sub: Subsystem, now: u32) -> usize {
+        self.recent.iter().filter(|&&(s, t)| s == sub && now.wrapping_sub(t) < WINDOW_MS).count()
+    }
+
+    /// Panics in anything within the last minute.
+    pub fn last_minute(&self, now: u32) -> usize {
+        self.recent.iter().filter(|&&(_, t)| now.wrapping_sub(t) < WINDOW_MS).count()
+    }
+
+    /// Panics caught since the DLL was loaded.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Subsystems switched off for panicking, in the order it happened.
+    pub fn disabled(&self) -> &[Subsystem] {
+        &self.disabled
+    }
+
+    /// The config was reloaded, so what was switched off through its config
+    /// flag is back on: forget it, and return it. The frame (hidden, not a
+    /// flag) stays on the list.
+    pub fn reenable(&mut self) -> Vec<Subsystem> {
+        let (frame, back) = self.disabled.iter().partition(|&&s| s == Subsystem::Frame);
+        self.disabled = frame;
+        back
+    }
+}
+
+/// The message of a caught panic's payload.
+pub fn message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(no message)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_off_at_the_threshold_within_a_minute() {
+        let mut log = PanicLog::new();
+        assert!(!log.record(Subsystem::Trails, 1000, 3));
+        assert!(!log.record(Subsystem::Trails, 2000, 3));
+        assert!(!log.record(Subsystem::Minimap, 2500, 3));
+        assert!(log.record(Subsystem::Trails, 3000, 3));
+        assert_eq!(log.disabled(), [Subsystem::Trails]);
+        assert_eq!((log.total(), log.count(Subsystem::Trails, 3000)), (4, 0)); // Counting starts over
+
+        // Spread over more than a minute: never three at once
+        assert!(!log.record(Subsystem::Minimap, 70_000, 3));
+        assert!(!log.record(Subsystem::Minimap, 71_000, 3));
+        assert_eq!(log.last_minute(71_000), 2);
+        assert!(!log.record(Subsystem::Feed, 80_000, 0)); // 0 = never
+
+        assert_eq!(log.reenable(), [Subsystem::Trails]);
+        assert!(log.disabled().is_empty());
+    }
+
+    #[test]
+    fn reads_panic_messages() {
+        let e = std::panic::catch_unwind(|| panic!("index {} out of range", 40)).unwrap_err();
+        assert_eq!(message(e.as_ref()), "index 40 out of range");
+        let e = std::panic::catch_unwind(|| panic!("plain")).unwrap_err();
+        assert_eq!(message(e.as_ref()), "plain");
+    }
+}
@@ -0,0 +1,173 @@
+// pattern.rs — IDA-style byte signatures.
+//
+// Signatures are written the way disassemblers print them:
+//
+//     "0F BF 87 ?? ?? ?? ?? 8B 16 50 68"
+//
+// Each token is a hex byte or a wildcard (`??` or `?`). Compiling the string
+// produces the byte/mask pair the scanner matches against, so a signature for
+// a new build is one line instead of two hand-aligned arrays.
+//
+// Searching: rather than trying every offset, `find()` picks one fixed "anchor"
+// byte (preferring bytes that are rare in x86 code), jumps between occurrences
+// of it with a 16-bytes-at-a-time SSE2 compare, and only runs the full masked
+// comparison there. A full client.dll scan takes a few milliseconds.
+
+/// A compiled byte signature. `mask[i]` is false where byte `i` is a wildcard.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub bytes: Vec<u8>,
+    pub mask:  Vec<bool>,
+    anchor:    usize, // Index of the fixed byte used to find candidates
+}
+
+/// Bytes too common in x86 code to make a good anchor (padding, ModRM-heavy
+/// opcodes, int3 fill).
+const COMMON_BYTES: [u8; 6] = [0x00, 0xFF, 0xCC, 0x8B, 0x89, 0x90];
+
+impl Pattern {
+    /// Compile an IDA-style signature string.
+    pub fn parse(sig: &str) -> Result<Self, String> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+        for tok in sig.split_whitespace() {
+            if tok == "?" || tok == "??" {
+                bytes.push(0);
+                mask.push(false);
+            } else if tok.len() == 2 {
+                let b = u8::from_str_radix(tok, 16)
+                    .map_err(|_| format!("bad byte `{}` in pattern", tok))?;
+                bytes.push(b);
+                mask.push(true);
+            } else {
+                return Err(format!("bad token `{}` in pattern", tok));
+            }
+        }
+        // First fixed byte that isn't a common one, else the first fixed byte
+        let fixed = || (0..bytes.len()).filter(|&i| mask[i]);
+        let anchor = fixed().find(|&i| !COMMON_BYTES.contains(&bytes[i]))
+            .or_else(|| fixed().next())
+            .ok_or_else(|| "pattern has no fixed bytes".to_string())?;
+        Ok(Self { bytes, mask, anchor })
+    }
+
+    /// Length of the signature in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the signature matches `hay` at offset `at`.
+    pub fn matches_at(&self, hay: &[u8], at: usize) -> bool {
+        let Some(window) = hay.get(at..at + self.len()) else { return false };
+        window.iter()
+            .zip(self.bytes.iter().zip(&self.mask))
+            .all(|(&b, (&p, &m))| !m || b == p)
+    }
+
+    /// Offset of the first match in `hay` at or after `from`.
+    pub fn find(&self, hay: &[u8], from: usize) -> Option<usize> {
+        let n = self.len();
+        let a = self.anchor;
+        let mut pos = from;
+        while pos + n <= hay.len() {
+            // Next candidate: where the anchor byte lines up
+            let search = &hay[pos + a..hay.len() - (n - 1 - a)];
+            let hit = find_byte(search, self.bytes[a])?;
+            let start = pos + hit;
+            if self.matches_at(hay, start) { return Some(start); }
+            pos = start + 1;
+        }
+        None
+    }
+}
+
+/// Index of the first `needle` in `hay`.
+fn find_byte(hay: &[u8], needle: u8) -> Option<usize> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("sse2") {
+            return unsafe { find_byte_sse2(hay, needle) };
+        }
+    }
+    hay.iter().position(|&b| b == needle)
+}
+
+/// SSE2 version of `find_byte`: compares 16 bytes per step.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn find_byte_sse2(hay: &[u8], needle: u8) -> Option<usize> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    let splat = _mm_set1_epi8(needle as i8);
+    let mut i = 0usize;
+    while i + 16 <= hay.len() {
+        let chunk = _mm_loadu_si128(hay.as_ptr().add(i) as *const __m128i);
+        let bits = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, splat)) as u32;
+        if bits != 0 { return Some(i + bits.trailing_zeros() as usize); }
+        i += 16;
+    }
+    hay[i..].iter().position(|&b| b == needle).map(|p| i + p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every offset tried in turn, to check `find` against.
+    fn naive(p: &Pattern, hay: &[u8], from: usize) -> Option<usize> {
+        (from..hay.len()).find(|&at| p.matches_at(hay, at))
+    }
+
+    fn hay(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8 | 0x01).collect() // No 0x00 anywhere
+    }
+
+    #[test]
+    fn find_agrees_with_a_naive_search() {
+        let mut h = hay(100);
+        let p = Pattern::parse("8B ?? 42 00").unwrap();
+        assert_eq!(p.anchor, 2);                          // 8B is common, 42 isn't
+
+        // Last possible offset
+        let last = h.len() - p.len();
+        h[last..].copy_from_slice(&[0x8B, 0x55, 0x42, 0x00]);
+        assert_eq!(p.find(&h, 0), Some(last));
+        assert_eq!(p.find(&h, 0), naive(&p, &h, 0));
+
+        // Past index 16 (SIMD loop), and earlier than the one at the end
+        h[40..44].copy_from_slice(&[0x8B, 0x10, 0x42, 0x00]);
+        h[30] = 0x42;                                     // An anchor byte without a match around it
+        assert_eq!(p.find(&h, 0), Some(40));
+        assert_eq!(p.find(&h, 41), Some(last));
+        for from in 0..h.len() + 2 {
+            assert_eq!(p.find(&h, from), naive(&p, &h, from), "from {}", from);
+        }
+
+        // Within the scalar tail of the anchor search (fewer than 16 bytes left)
+        let mut short = hay(12);
+        short[5..9].copy_from_slice(&[0x8B, 0x00, 0x42, 0x00]);
+        assert_eq!(p.find(&short, 0), Some(5));
+
+        // Haystack shorter than the pattern, and `from` past the end
+        assert_eq!(p.find(&[0x8B, 0x00, 0x42], 0), None);
+        assert_eq!(p.find(&h, h.len() + 10), None);
+        assert_eq!(p.find(&[], 0), None);
+    }
+
+    #[test]
+    fn parses_signatures() {
+        let p = Pattern::parse("0F ?? ? bf").unwrap();
+        assert_eq!(p.bytes, [0x0F, 0x00, 0x00, 0xBF]);
+        assert_eq!(p.mask, [true, false, false, true]);
+        assert_eq!(p.anchor, 0);
+        assert_eq!(Pattern::parse("FF 00 CC").unwrap().anchor, 0);  // Only common bytes: the first fixed one
+
+        assert_eq!(Pattern::parse("0F G1").unwrap_err(), "bad byte `G1` in pattern");
+        assert_eq!(Pattern::parse("0F 123").unwrap_err(), "bad token `123` in pattern");
+        assert_eq!(Pattern::parse("?? ? ??").unwrap_err(), "pattern has no fixed bytes");
+        assert_eq!(Pattern::parse("").unwrap_err(), "pattern has no fixed bytes");
+    }
+}
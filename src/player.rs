@@ -0,0 +1,828 @@
+// player.rs — Interpretation of the engine's per-player structures.
+//
+// entities.rs owns the live side (the engine table, hooks, caches across
+// frames); this module only knows how to turn the raw fields of one player
+// slot — cl_entity_t, entity_state_t, hud_player_info_t and client.dll's
+// extra_player_info_t — into values, scored against sanity checks so that a
+// wrong pointer or offset is rejected rather than drawn. All memory access
+// goes through the `EngineReader` trait, so the interpretation can be
+// exercised against a mock engine in unit tests on any host.
+
+use crate::bsp::Bounds;
+use crate::math::Vec3;
+use crate::mods::{GameMod, Hulls};
+use crate::offsets::Offset;
+use crate::world::{ES_MESSAGENUM, ES_MODELINDEX};
+
+pub const MAX_CLIENTS: i32 = 32; // Maximum player slots in GoldSrc
+
+/// Observer mode value for first-person ("in-eye") spectating.
+pub const OBS_IN_EYE: i32 = 4;
+
+// ============================================================
+// Entity Structure Offsets
+// ============================================================
+// These are byte offsets into the engine's cl_entity_t structure.
+// They vary by engine build — these are for Build 4554. The ones that move
+// between builds are read through offsets.rs, which can override them.
+
+pub const CURSTATE_OFFSET: usize = 0x2B0;  // Offset to entity_state_t (current state)
+pub const ENT_ORIGIN:      usize = 0xB48;  // cl_entity_t::origin (interpolated position)
+pub const ENT_CURPOS:      usize = 0x404;  // Current position history index
+pub const ENT_PH_BASE:     usize = 0x408;  // Start of position history array
+pub const PH_ENTRY_SIZE:   usize = 28;     // Size of one position history entry
+pub const PH_HISTORY_MASK: usize = 63;     // Bitmask for position history ring buffer index
+
+// Entity state sub-offsets (relative to CURSTATE_OFFSET)
+pub const ES_ORIGIN:       usize = 0x10;   // entity_state_t::origin
+pub const ES_ANGLES:       usize = 0x1C;   // entity_state_t::angles (model angles, pitch scaled)
+pub const ES_SEQUENCE:     usize = 0x2C;   // entity_state_t::sequence (upper-body animation)
+pub const ES_BODY:         usize = 0x64;   // entity_state_t::body (CS: 1 on a CT carrying a defuse kit)
+pub const ES_WEAPONMODEL:  usize = 0xB4;   // entity_state_t::weaponmodel (model index)
+pub const ES_MAXS:         usize = 0x88;   // entity_state_t::maxs (bounding box top)
+pub const ES_USEHULL:      usize = 0xC8;   // entity_state_t::usehull (0=standing, 1=ducking)
+pub const ES_IUSER1:       usize = 0x104;  // entity_state_t::iuser1 (observer mode while spectating)
+pub const ES_IUSER2:       usize = 0x108;  // entity_state_t::iuser2 (observer target index)
+
+// ============================================================
+// Player Extra Info Offsets
+// ============================================================
+// g_PlayerExtraInfo is client.dll's per-player metadata array.
+// Used to get team numbers, alive/dead status and health.
+
+pub const EXTRA_OFF_FRAGS:       usize = 0x00;  // Frags (short; first field, so it doesn't move between builds)
+pub const EXTRA_OFF_TEAMNUMBER: usize = 0x2A;  // Team number (1=T, 2=CT)
+pub const EXTRA_OFF_DEAD:       usize = 0x3C;  // Dead flag (0=alive, nonzero=dead)
+pub const EXTRA_OFF_HEALTH:     usize = 0x44;  // Health (int, from the HealthInfo message)
+pub const EXTRA_STRIDE:         usize = 0x68;  // Size of one extra_player_info_t entry
+
+// Fields later builds added to extra_player_info_t. Build 4554 has neither,
+// so they default to 0 (= not in this build's layout) and can be set in
+// esp_offsets.toml for a build that has them.
+pub const EXTRA_OFF_KIT:        usize = 0;     // Defuse kit flag (int)
+pub const EXTRA_OFF_HELMET:     usize = 0;     // Helmet flag (int)
+
+// ============================================================
+// Hull Heights
+// ============================================================
+
+const HULL_MARGIN:     f32 = 4.0;   // Added to maxs.z so the box clears the model
+const HULL_OVER:       f32 = 1.25;  // maxs.z up to this many standing heights is plausible
+const VIEW_STAND:      f32 = 28.0;  // Eye height above the origin, standing (VEC_VIEW)
+const VIEW_DUCK:       f32 = 12.0;  // Eye height above the origin, ducking (VEC_DUCK_VIEW)
+
+/// The server sends a player's model pitch as -view pitch / 3 (so the body
+/// only leans a little); multiply back to get where they're aiming.
+const MODEL_PITCH_SCALE: f32 = -3.0;
+
+/// Highest health taken at face value; anything above is a wrong offset.
+const MAX_HEALTH: i32 = 255;
+
+/// Highest team number (CS: 3 = spectator; TFC has four teams).
+const MAX_TEAM: i32 = 4;
+
+// ============================================================
+// Sanity Limits
+// ============================================================
+
+const WORLD_LIMIT:     f32 = 4096.0;  // Map coordinates stay within +-this (MAX_COORD) ...
+const WORLD_MARGIN:    f32 = 64.0;    // ... or within the map's world bounds, plus this
+const HULL_HALF_MIN:   f32 = 8.0;     // Plausible maxs.x / maxs.y (16 for players)
+const HULL_HALF_MAX:   f32 = 32.0;
+const MAX_MODELS:      i32 = 512;     // Engine model precache limit
+const MAX_MODEL_PITCH: f32 = 35.0;    // |model pitch| (a third of the view pitch, plus slack)
+const MAX_ANGLE:       f32 = 360.0;   // |yaw| / |roll|
+
+// ============================================================
+// Engine Access
+// ============================================================
+
+/// What GetPlayerInfo reports for a slot.
+#[derive(Clone, Debug, Default)]
+pub struct SlotInfo {
+    pub name:      Option<String>, // None = empty slot
+    pub spectator: bool,
+    pub local:     bool,           // hud_player_info_t::thisplayer
+    pub steam_id:  u64,            // hud_player_info_t::m_nSteamID (0 = bot / unknown)
+    pub bot:       bool,           // Looks like a server-side bot: SteamID 0, ping 0, not us
+}
+
+/// The reads needed to interpret a player slot. Unreadable memory reads as 0.
+pub trait EngineReader {
+    fn read_i32(&self, addr: usize) -> i32;
+    fn read_i16(&self, addr: usize) -> i16;
+    fn read_u8(&self, addr: usize) -> u8;
+    fn read_f32(&self, addr: usize) -> f32;
+
+    /// GetPlayerInfo for `idx` (None if the slot can't be used).
+    fn player_info(&self, idx: i32) -> Option<SlotInfo>;
+
+    /// GetEntityByIndex for `idx` (0 = no entity).
+    fn entity(&self, idx: i32) -> usize;
+
+    /// Base of g_PlayerExtraInfo (0 = not found).
+    fn extra_info_base(&self) -> usize;
+
+    /// The current map's world bounds (None = not known yet).
+    fn world_bounds(&self) -> Option<Bounds> { None }
+
+    /// The running mod (mods.rs).
+    fn game_mod(&self) -> GameMod { GameMod::Cstrike }
+
+    /// Player hull heights (client.dll's hull table where it can be read).
+    fn hulls(&self) -> Hulls { self.game_mod().profile().hulls }
+
+    fn read_vec3(&self, addr: usize) -> Vec3 {
+        Vec3 { x: self.read_f32(addr), y: self.read_f32(addr + 4), z: self.read_f32(addr + 8) }
+    }
+}
+
+// ============================================================
+// Interpretation
+// ============================================================
+
+/// One player slot's fields, before any cross-frame logic (staleness, death
+/// tracking, trails) is applied.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerFields {
+    pub name:         Option<String>, // None when GetPlayerInfo isn't usable
+    pub is_local:     bool,
+    pub steam_id:     u64,            // 0 when unknown
+    pub is_bot:       bool,           // SlotInfo::bot, in a mod with bots
+    pub origin:       Vec3,
+    pub cur_pos:      usize,          // Position history index (changes on every update)
+    pub message:      i32,            // entity_state_t::messagenum (packet it last arrived in)
+    pub team:         i32,            // 0 when g_PlayerExtraInfo isn't known
+    pub has_extra:    bool,           // Team/dead came from g_PlayerExtraInfo
+    pub is_dead:      bool,
+    pub health:       Option<i32>,    // From g_PlayerExtraInfo; None when unknown (see health())
+    pub has_kit:      Option<bool>,   // Defuse kit; None when neither source is available
+    pub has_helmet:   Option<bool>,   // Helmet; only from an extra_info.helmet offset
+    pub weapon_model: i32,            // entity_state_t::weaponmodel (0 = none)
+    pub model_index:  i32,            // entity_state_t::modelindex (the player model)
+    pub sequence:     i32,            // entity_state_t::sequence (see anim.rs)
+    pub view_angles:  Vec3,           // Where they're aiming (pitch, yaw, roll), from the model angles
+    pub is_ducking:   bool,
+    pub maxs_z:       f32,            // Box height including margin
+    pub sanity:       Sanity,         // Checks failed (at most one minor one, or it's rejected)
+}
+
+/// Interpret player slot `idx`. Returns None for empty slots, spectators,
+/// non-player entities, players without a usable origin and slots that fail
+/// the sanity checks (see interpret_player_checked).
+pub fn interpret_player(r: &impl EngineReader, idx: i32) -> Option<PlayerFields> {
+    interpret_player_checked(r, idx).ok()
+}
+
+/// Why interpret_player_checked() turned a slot down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejected {
+    Empty,           // Bad index, or no player in the slot
+    Spectator,
+    NoEntity,        // No entity, a non-player one, or another slot's
+    NoOrigin,        // No usable origin
+    Insane(Sanity),  // Failed enough sanity checks to be garbage
+}
+
+/// Interpret player slot `idx`, saying why it was turned down. The fields are
+/// scored against the sanity checks (see Sanity): a slot scoring REJECT_SCORE
+/// or more is rejected; one failing a single minor check is kept with the
+/// failing fields treated as unknown.
+pub fn interpret_player_checked(r: &impl EngineReader, idx: i32) -> Result<PlayerFields, Rejected> {
+    if idx <= 0 || idx > MAX_CLIENTS { return Err(Rejected::Empty); }
+
+    // --- Player info (name, spectator status) ---
+    // No name means an empty slot, except for a bot: CZ's bots can sit in a
+    // slot without a name while they join. Those need a real team from the
+    // extra info below to count.
+    let profile = r.game_mod().profile();
+    let info = r.player_info(idx);
+    let mut nameless_bot = false;
+    if let Some(info) = &info {
+        if info.name.is_none() {
+            if !(info.bot && profile.bot_slots) { return Err(Rejected::Empty); }
+            nameless_bot = true;
+        }
+        if info.spectator { return Err(Rejected::Spectator); }
+    }
+
+    // --- Entity ---
+    let base = r.entity(idx);
+    if base == 0 { return Err(Rejected::NoEntity); }
+
+    // Validate entity index and player flag
+    let ent_index = r.read_i32(base);
+    let is_player = r.read_i32(base + 0x04);
+    if is_player == 0 { return Err(Rejected::NoEntity); }
+    if ent_index > 0 && ent_index <= MAX_CLIENTS && ent_index != idx { return Err(Rejected::NoEntity); }
+
+    let cs = base + Offset::Curstate.get(); // entity_state_t
+    let cur_pos = r.read_i32(base + Offset::CurPos.get()) as usize & PH_HISTORY_MASK;
+    let origin = resolve_origin(r, base, cur_pos).ok_or(Rejected::NoOrigin)?;
+
+    // --- Team and alive/dead status from g_PlayerExtraInfo ---
+    let extra = r.extra_info_base();
+    let mut slot = if extra != 0 { extra + idx as usize * Offset::ExtraStride.get() } else { 0 };
+
+    // --- Sanity ---
+    let is_ducking = r.read_i32(cs + ES_USEHULL) == 1; // Hull 1 = duck hull
+    let model_index = r.read_i32(cs + ES_MODELINDEX);
+    let model_angles = r.read_vec3(cs + ES_ANGLES);
+    let extra_fields = (slot != 0).then(|| {
+        (r.read_i16(slot + Offset::ExtraTeam.get()) as i32, extra_health(r, slot).unwrap_or(0))
+    });
+    let hulls = r.hulls();
+    let sanity = Sanity::check(origin, r.world_bounds().as_ref(), r.read_vec3(cs + ES_MAXS), is_ducking, hulls,
+                               model_index, model_angles, extra_fields);
+    if sanity.rejected() { return Err(Rejected::Insane(sanity)); }
+    if sanity.failed(Sanity::EXTRA) { slot = 0; }   // Not this slot's record
+    let view_angles = if sanity.failed(Sanity::ANGLES) { Vec3::default() } else { view_from_model_angles(model_angles) };
+
+    let team = if slot != 0 { extra_team(r, idx).unwrap_or(0) } else { 0 };
+    if nameless_bot && !(1..=2).contains(&team) { return Err(Rejected::Empty); }
+    let is_dead = slot != 0 && r.read_u8(slot + Offset::ExtraDead.get()) != 0;
+
+    // --- Equipment: extra info fields where the build has them, else the
+    //     kit shown on a CT's model (CS only) ---
+    let extra_flag = |o: Offset| (slot != 0 && o.get() != 0).then(|| r.read_i32(slot + o.get()) != 0);
+    let has_kit = extra_flag(Offset::ExtraKit)
+        .or_else(|| (profile.kit_from_body && team == 2).then(|| r.read_i32(cs + ES_BODY) == 1));
+    let has_helmet = extra_flag(Offset::ExtraHelmet);
+
+    // --- Hull ---
+    let maxs_z = hull_height(r.read_f32(cs + ES_MAXS + 8), is_ducking, hulls);
+
+    let (name, is_local, steam_id, is_bot) = match info {
+        Some(i) => (i.name, i.local, i.steam_id, i.bot && profile.bot_slots),
+        None    => (None, false, 0, false),
+    };
+    Ok(PlayerFields {
+        name,
+        is_local,
+        steam_id,
+        is_bot,
+        origin,
+        cur_pos,
+        message: r.read_i32(cs + ES_MESSAGENUM),
+        team,
+        has_extra: slot != 0,
+        is_dead,
+        has_kit,
+        has_helmet,
+        health: if slot != 0 { extra_health(r, slot).and_then(health) } else { None },
+        weapon_model: r.read_i32(cs + ES_WEAPONMODEL),
+        model_index,
+        sequence: r.read_i32(cs + ES_SEQUENCE),
+        view_angles,
+        is_ducking,
+        maxs_z,
+        sanity,
+    })
+}
+
+/// A player's health from extra_player_info_t::health. The client only hears
+/// it through HealthInfo messages, which the server doesn't send for every
+/// player, so 0 (never updated) and implausible values count as unknown.
+pub fn health(raw: i32) -> Option<i32> {
+    (1..=MAX_HEALTH).contains(&raw).then_some(raw)
+}
+
+/// Whether a player was in the server's latest update, i.e. inside the local
+/// player's PVS: its message number has caught up with `newest` (the local
+/// player's, who is in every update). An unknown `newest` (0) counts as yes.
+pub fn in_pvs(message: i32, newest: i32) -> bool {
+    newest == 0 || message >= newest
+}
+
+/// Where distances (and the minimap) are measured from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewRef {
+    Local,       // The local player, alive and playing
+    Target(i32), // The player being watched in first person
+    Camera,      // The rendered camera: death cam, chase cam or free look
+}
+
+/// Pick the distance reference from the local observer state (`obs_mode`,
+/// `obs_target` from iuser1/iuser2), whether the local player is dead, and
+/// whether a fresh camera position is known.
+pub fn view_reference(obs_mode: i32, obs_target: i32, dead: bool, have_camera: bool) -> ViewRef {
+    if obs_mode == OBS_IN_EYE && (1..=MAX_CLIENTS).contains(&obs_target) {
+        return ViewRef::Target(obs_target);
+    }
+    if (obs_mode != 0 || dead) && have_camera { ViewRef::Camera } else { ViewRef::Local }
+}
+
+/// Raw health in the extra info entry at `slot` (None where the mod's layout
+/// doesn't have it: offset 0).
+fn extra_health(r: &impl EngineReader, slot: usize) -> Option<i32> {
+    match Offset::ExtraHealth.get() {
+        0   => None,
+        off => Some(r.read_i32(slot + off)),
+    }
+}
+
+/// Team number of slot `idx` from g_PlayerExtraInfo (None if it isn't known).
+pub fn extra_team(r: &impl EngineReader, idx: i32) -> Option<i32> {
+    if idx <= 0 || idx > MAX_CLIENTS { return None; }
+    let extra = r.extra_info_base();
+    if extra == 0 { return None; }
+    Some(r.read_i16(extra + idx as usize * Offset::ExtraStride.get() + Offset::ExtraTeam.get()) as i32)
+}
+
+/// Frags of slot `idx` from g_PlayerExtraInfo (None if it isn't known).
+pub fn extra_frags(r: &impl EngineReader, idx: i32) -> Option<i32> {
+    if idx <= 0 || idx > MAX_CLIENTS { return None; }
+    let extra = r.extra_info_base();
+    if extra == 0 { return None; }
+    Some(r.read_i16(extra + idx as usize * Offset::ExtraStride.get() + EXTRA_OFF_FRAGS) as i32)
+}
+
+/// Check that g_PlayerExtraInfo still holds plausible data: every slot's
+/// team number within 0..=MAX_TEAM and health within 0..=MAX_HEALTH (unused
+/// slots are zeroed). Err names the first slot that isn't.
+pub fn check_extra_info(r: &impl EngineReader) -> Result<(), String> {
+    let extra = r.extra_info_base();
+    if extra == 0 { return Err("not found".to_string()); }
+    for idx in 1..=MAX_CLIENTS {
+        let slot = extra + idx as usize * Offset::ExtraStride.get();
+        let team = r.read_i16(slot + Offset::ExtraTeam.get()) as i32;
+        if !(0..=MAX_TEAM).contains(&team) { return Err(format!("slot {}: team {}", idx, team)); }
+        let health = extra_health(r, slot).unwrap_or(0);
+        if !(0..=MAX_HEALTH).contains(&health) { return Err(format!("slot {}: health {}", idx, health)); }
+    }
+    Ok(())
+}
+
+/// Player origin, with fallbacks:
+/// interpolated origin -> position history -> entity state origin.
+fn resolve_origin(r: &impl EngineReader, base: usize, cur_pos: usize) -> Option<Vec3> {
+    let usable = |v: Vec3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite() && !v.is_zero();
+
+    let origin = r.read_vec3(base + Offset::Origin.get());
+    if usable(origin) { return Some(origin); }
+
+    let ph_addr = base + Offset::PosHistory.get() + cur_pos * PH_ENTRY_SIZE;
+    let ph_origin = r.read_vec3(ph_addr + 4);
+    if usable(ph_origin) { return Some(ph_origin); }
+
+    let cs_origin = r.read_vec3(base + Offset::Curstate.get() + ES_ORIGIN);
+    if usable(cs_origin) { return Some(cs_origin); }
+    None // All origin sources failed
+}
+
+/// Where a player's entity puts them, by source: what the client
+/// interpolated (cl_entity_t::origin), the latest state from the server
+/// (curstate.origin) and the newest position history sample, which
+/// interpolation works from. Unlike resolve_origin, nothing is checked or
+/// substituted: differences between them are the point.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OriginSources {
+    pub interpolated: Vec3,
+    pub state:        Vec3,
+    pub history:      Vec3,
+}
+
+/// The origin sources of slot `idx` (None without an entity).
+pub fn origin_sources(r: &impl EngineReader, idx: i32) -> Option<OriginSources> {
+    if idx <= 0 || idx > MAX_CLIENTS { return None; }
+    let base = r.entity(idx);
+    if base == 0 { return None; }
+    let cur_pos = r.read_i32(base + Offset::CurPos.get()) as usize & PH_HISTORY_MASK;
+    Some(OriginSources {
+        interpolated: r.read_vec3(base + Offset::Origin.get()),
+        state:        r.read_vec3(base + Offset::Curstate.get() + ES_ORIGIN),
+        history:      r.read_vec3(base + Offset::PosHistory.get() + cur_pos * PH_ENTRY_SIZE + 4),
+    })
+}
+
+/// Every entry of slot `idx`'s position history ring buffer: message time
+/// (position_history_t::animtime) and origin, in buffer order. Unused entries
+/// are left in (time zero); posgraph.rs sorts and filters.
+pub fn position_history(r: &impl EngineReader, idx: i32) -> Vec<(f32, Vec3)> {
+    if idx <= 0 || idx > MAX_CLIENTS { return Vec::new(); }
+    let base = r.entity(idx);
+    if base == 0 { return Vec::new(); }
+    (0..=PH_HISTORY_MASK).map(|i| {
+        let entry = base + Offset::PosHistory.get() + i * PH_ENTRY_SIZE;
+        (r.read_f32(entry), r.read_vec3(entry + 4))
+    }).collect()
+}
+
+/// A player's view angles from their model angles (see MODEL_PITCH_SCALE).
+pub fn view_from_model_angles(a: Vec3) -> Vec3 {
+    Vec3 { x: (a.x * MODEL_PITCH_SCALE).clamp(-89.0, 89.0), y: a.y, z: 0.0 }
+}
+
+/// Eye height above a player's origin (the hull center).
+pub fn eye_height(ducking: bool) -> f32 {
+    if ducking { VIEW_DUCK } else { VIEW_STAND }
+}
+
+/// Box height from entity_state_t::maxs.z, falling back to the hull table's
+/// height when the value is implausible for the stance.
+pub fn hull_height(maxs_z: f32, ducking: bool, hulls: Hulls) -> f32 {
+    let z = if hull_plausible(maxs_z, ducking, hulls) { maxs_z } else { hulls.height(ducking) };
+    z + HULL_MARGIN
+}
+
+/// Whether `maxs_z` is a hull height for the stance: ducking below halfway
+/// between the two hulls, standing above it and up to HULL_OVER standing heights.
+fn hull_plausible(maxs_z: f32, ducking: bool, hulls: Hulls) -> bool {
+    let mid = (hulls.stand + hulls.duck) * 0.5;
+    if ducking { maxs_z > 0.0 && maxs_z < mid } else { maxs_z > mid && maxs_z < hulls.stand * HULL_OVER }
+}
+
+// ============================================================
+// Sanity Scoring
+// ============================================================
+// A wrong entity pointer or offset reads as garbage in more than one field at
+// once, while a real player can be off in one (a maxs the server hasn't sent
+// yet). Each failed check adds its weight; REJECT_SCORE or more rejects the
+// slot, less keeps it with the failing fields treated as unknown.
+
+/// Score at which a slot is rejected.
+const REJECT_SCORE: u32 = 2;
+
+/// Sanity checks a player's fields failed (bit flags).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sanity(pub u8);
+
+impl Sanity {
+    pub const ORIGIN: u8 = 1 << 0;   // Origin outside the world bounds (weight 2)
+    pub const HULL:   u8 = 1 << 1;   // maxs outside human hull ranges
+    pub const MODEL:  u8 = 1 << 2;   // Model index outside the precache range
+    pub const ANGLES: u8 = 1 << 3;   // Model angles not finite or out of range
+    pub const EXTRA:  u8 = 1 << 4;   // Implausible team or health in g_PlayerExtraInfo
+
+    const NAMES: [(u8, &'static str); 5] = [
+        (Self::ORIGIN, "origin"), (Self::HULL, "hull"), (Self::MODEL, "model"),
+        (Self::ANGLES, "angles"), (Self::EXTRA, "extra info"),
+    ];
+
+    /// Check a player's raw fields. The origin must be inside `world` (the
+    /// map's bounds), or within the engine's coordinate limit while they're
+    /// unknown. `maxs` of zero (not sent) isn't held against it, and its height
+    /// is judged against `hulls`; `extra` is (team, health) when
+    /// g_PlayerExtraInfo is known.
+    #[allow(clippy::too_many_arguments)]
+    pub fn check(origin: Vec3, world: Option<&Bounds>, maxs: Vec3, ducking: bool, hulls: Hulls, model_index: i32,
+                 model_angles: Vec3, extra: Option<(i32, i32)>) -> Self {
+        let mut failed = 0;
+        let limit = |v: f32| v.is_finite() && v.abs() <= WORLD_LIMIT;
+        let in_world = match world {
+            Some(b) => b.contains([origin.x, origin.y, origin.z], WORLD_MARGIN),
+            None    => limit(origin.x) && limit(origin.y) && limit(origin.z),
+        };
+        if !in_world { failed |= Self::ORIGIN; }
+        let half = |v: f32| v == 0.0 || (HULL_HALF_MIN..=HULL_HALF_MAX).contains(&v);
+        let hull_ok = maxs.is_zero() || (half(maxs.x) && half(maxs.y) && hull_plausible(maxs.z, ducking, hulls));
+        if !hull_ok { failed |= Self::HULL; }
+        if !(1..MAX_MODELS).contains(&model_index) { failed |= Self::MODEL; }
+        let a = model_angles;
+        let angle = |v: f32, max: f32| v.is_finite() && v.abs() <= max;
+        if !(angle(a.x, MAX_MODEL_PITCH) && angle(a.y, MAX_ANGLE) && angle(a.z, MAX_ANGLE)) {
+            failed |= Self::ANGLES;
+        }
+        if let Some((team, health)) = extra {
+            if !(0..=MAX_TEAM).contains(&team) || !(0..=MAX_HEALTH).contains(&health) { failed |= Self::EXTRA; }
+        }
+        Self(failed)
+    }
+
+    pub fn failed(self, check: u8) -> bool {
+        self.0 & check != 0
+    }
+
+    /// Weighted number of failed checks.
+    pub fn score(self) -> u32 {
+        self.0.count_ones() + u32::from(self.failed(Self::ORIGIN))
+    }
+
+    pub fn rejected(self) -> bool {
+        self.score() >= REJECT_SCORE
+    }
+
+    /// Names of the failed checks.
+    pub fn reasons(self) -> Vec<&'static str> {
+        Self::NAMES.iter().filter(|&&(bit, _)| self.failed(bit)).map(|&(_, name)| name).collect()
+    }
+}
+
+// ============================================================
+// SteamIDs
+// ============================================================
+
+const STEAM64_BASE: u64 = 76_561_197_960_265_728; // SteamID64 of STEAM_0:0:0
+
+/// Parse a SteamID as written in the config: SteamID64 ("76561197960287930")
+/// or the legacy text form ("STEAM_0:1:11101", any universe digit). Returns
+/// the SteamID64, or None for anything else (including 0).
+pub fn parse_steam_id(text: &str) -> Option<u64> {
+    let text = text.trim().trim_matches('"');
+    if let Some(rest) = text.strip_prefix("STEAM_") {
+        let mut parts = rest.split(':');
+        let (_universe, y, z) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() { return None; }
+        let y: u64 = y.parse().ok().filter(|&y| y <= 1)?;
+        let z: u64 = z.parse().ok()?;
+        return Some(STEAM64_BASE + z * 2 + y);
+    }
+    text.parse().ok().filter(|&id| id != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockEngine;
+
+    const BASE: usize = 0x1000_0000;
+
+    #[test]
+    fn reads_basic_player() {
+        let mut e = MockEngine::default();
+        e.add_player(3, BASE, "alice", [10.0, 20.0, 30.0]);
+        let p = interpret_player(&e, 3).unwrap();
+        assert_eq!(p.name.as_deref(), Some("alice"));
+        assert_eq!((p.origin.x, p.origin.y, p.origin.z), (10.0, 20.0, 30.0));
+        assert_eq!(p.team, 0);
+        assert!(!p.has_extra && !p.is_dead && !p.is_ducking);
+        assert_eq!(p.maxs_z, Hulls::STANDARD.stand + HULL_MARGIN);
+    }
+
+    #[test]
+    fn rejects_empty_spectator_and_bad_slots() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        e.infos.get_mut(&1).unwrap().spectator = true;
+        assert_eq!(interpret_player_checked(&e, 1).err(), Some(Rejected::Spectator));
+
+        e.add_player(2, BASE + 0x10000, "b", [1.0, 1.0, 1.0]);
+        e.infos.get_mut(&2).unwrap().name = None;
+        assert_eq!(interpret_player_checked(&e, 2).err(), Some(Rejected::Empty));
+
+        assert!(interpret_player(&e, 0).is_none());
+        assert!(interpret_player(&e, MAX_CLIENTS + 1).is_none());
+        assert_eq!(interpret_player_checked(&e, 5).err(), Some(Rejected::Empty)); // Nobody there
+        e.infos.insert(6, SlotInfo { name: Some("c".into()), ..Default::default() });
+        assert_eq!(interpret_player_checked(&e, 6).err(), Some(Rejected::NoEntity));
+    }
+
+    #[test]
+    fn rejects_non_player_and_mismatched_index() {
+        let mut e = MockEngine::default();
+        e.add_player(4, BASE, "a", [1.0, 1.0, 1.0]);
+        e.write_i32(BASE + 4, 0);
+        assert_eq!(interpret_player_checked(&e, 4).err(), Some(Rejected::NoEntity));
+
+        e.write_i32(BASE + 4, 1);
+        e.write_i32(BASE, 7); // Entity claims to be slot 7
+        assert!(interpret_player(&e, 4).is_none());
+    }
+
+    #[test]
+    fn origin_falls_back_to_history_then_state() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [0.0, 0.0, 0.0]);
+        e.write_i32(BASE + ENT_CURPOS, 65); // Masked to 1
+        e.write_vec3(BASE + ENT_PH_BASE + PH_ENTRY_SIZE + 4, [5.0, 6.0, 7.0]);
+        let p = interpret_player(&e, 1).unwrap();
+        assert_eq!((p.origin.x, p.cur_pos), (5.0, 1));
+
+        e.write_vec3(BASE + ENT_PH_BASE + PH_ENTRY_SIZE + 4, [f32::NAN, 0.0, 0.0]);
+        e.write_vec3(BASE + CURSTATE_OFFSET + ES_ORIGIN, [8.0, 9.0, 10.0]);
+        assert_eq!(interpret_player(&e, 1).unwrap().origin.z, 10.0);
+
+        e.write_vec3(BASE + CURSTATE_OFFSET + ES_ORIGIN, [0.0, 0.0, 0.0]);
+        assert!(interpret_player(&e, 1).is_none());
+    }
+
+    #[test]
+    fn reads_every_origin_source() {
+        let mut e = MockEngine::default();
+        e.add_player(3, BASE, "a", [1.0, 2.0, 3.0]);
+        e.write_i32(BASE + ENT_CURPOS, 2);
+        e.write_vec3(BASE + ENT_PH_BASE + 2 * PH_ENTRY_SIZE + 4, [4.0, 5.0, 6.0]);
+        e.write_vec3(BASE + CURSTATE_OFFSET + ES_ORIGIN, [7.0, 8.0, 9.0]);
+        let o = origin_sources(&e, 3).unwrap();
+        assert_eq!((o.interpolated, o.history, o.state),
+                   (Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0), Vec3::new(7.0, 8.0, 9.0)));
+        assert_eq!(origin_sources(&e, 4), None);
+    }
+
+    #[test]
+    fn reads_the_whole_position_history() {
+        let mut e = MockEngine::default();
+        e.add_player(2, BASE, "a", [1.0, 1.0, 1.0]);
+        e.write(BASE + ENT_PH_BASE + 63 * PH_ENTRY_SIZE, &12.5f32.to_le_bytes());
+        e.write_vec3(BASE + ENT_PH_BASE + 63 * PH_ENTRY_SIZE + 4, [4.0, 5.0, 6.0]);
+        let h = position_history(&e, 2);
+        assert_eq!(h.len(), 64);
+        assert_eq!(h[63], (12.5, Vec3::new(4.0, 5.0, 6.0)));
+        assert_eq!(h[0].0, 0.0);
+        assert!(position_history(&e, 3).is_empty());
+    }
+
+    #[test]
+    fn team_and_dead_from_extra_info() {
+        let mut e = MockEngine::default();
+        e.add_player(2, BASE, "a", [1.0, 1.0, 1.0]);
+        e.extra = 0x2000_0000;
+        let slot = e.extra + 2 * EXTRA_STRIDE;
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &2i16.to_le_bytes());
+        e.write(slot + EXTRA_OFF_DEAD, &[1]);
+        e.write_i32(slot + EXTRA_OFF_HEALTH, 64);
+        e.write(slot + EXTRA_OFF_FRAGS, &(-3i16).to_le_bytes());
+        let p = interpret_player(&e, 2).unwrap();
+        assert_eq!(p.team, 2);
+        assert_eq!(p.health, Some(64));
+        assert!(p.has_extra && p.is_dead);
+        assert_eq!(extra_team(&e, 2), Some(2));
+        assert_eq!(extra_team(&e, 0), None);
+        assert_eq!(extra_frags(&e, 2), Some(-3));
+        e.extra = 0;
+        assert_eq!(extra_team(&e, 2), None);
+        assert_eq!(extra_frags(&e, 2), None);
+    }
+
+    #[test]
+    fn nameless_bots_need_a_team() {
+        let mut e = MockEngine::default();
+        e.add_player(4, BASE, "x", [1.0, 1.0, 1.0]);
+        e.infos.insert(4, SlotInfo { name: None, bot: true, ..Default::default() });
+        assert!(interpret_player(&e, 4).is_none()); // No extra info to vouch for it
+        e.extra = 0x2000_0000;
+        e.write(e.extra + 4 * EXTRA_STRIDE + EXTRA_OFF_TEAMNUMBER, &1i16.to_le_bytes());
+        let p = interpret_player(&e, 4).unwrap();
+        assert!(p.is_bot && p.name.is_none() && p.team == 1);
+
+        e.infos.insert(4, SlotInfo { name: None, ..Default::default() }); // Not a bot: empty slot
+        assert!(interpret_player(&e, 4).is_none());
+        e.infos.insert(4, SlotInfo { name: None, bot: true, ..Default::default() });
+        e.game_mod = GameMod::Tfc; // No bots in this mod
+        assert!(interpret_player(&e, 4).is_none());
+    }
+
+    #[test]
+    fn checks_extra_info_sanity() {
+        let mut e = MockEngine::default();
+        assert!(check_extra_info(&e).is_err());
+        e.extra = 0x2000_0000;
+        let slot = e.extra + 5 * EXTRA_STRIDE;
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &1i16.to_le_bytes());
+        e.write_i32(slot + EXTRA_OFF_HEALTH, 100);
+        assert_eq!(check_extra_info(&e), Ok(()));
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &0x4141i16.to_le_bytes());
+        assert_eq!(check_extra_info(&e), Err("slot 5: team 16705".to_string()));
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &2i16.to_le_bytes());
+        e.write_i32(slot + EXTRA_OFF_HEALTH, -7);
+        assert_eq!(check_extra_info(&e), Err("slot 5: health -7".to_string()));
+    }
+
+    #[test]
+    fn kit_from_model_body_without_extra_fields() {
+        let mut e = MockEngine::default();
+        e.add_player(2, BASE, "a", [1.0, 1.0, 1.0]);
+        assert_eq!(interpret_player(&e, 2).unwrap().has_kit, None); // Team unknown
+        e.extra = 0x2000_0000;
+        let slot = e.extra + 2 * EXTRA_STRIDE;
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &2i16.to_le_bytes());
+        e.write_i32(BASE + CURSTATE_OFFSET + ES_BODY, 1);
+        let p = interpret_player(&e, 2).unwrap();
+        assert_eq!((p.has_kit, p.has_helmet), (Some(true), None));
+        e.game_mod = GameMod::Dod;
+        assert_eq!(interpret_player(&e, 2).unwrap().has_kit, None); // Nor outside CS
+        e.game_mod = GameMod::Cstrike;
+        e.write(slot + EXTRA_OFF_TEAMNUMBER, &1i16.to_le_bytes());
+        assert_eq!(interpret_player(&e, 2).unwrap().has_kit, None); // Body means nothing on a T
+    }
+
+    #[test]
+    fn health_unknown_unless_plausible() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        assert_eq!(interpret_player(&e, 1).unwrap().health, None); // No extra info
+        assert_eq!(health(0), None);
+        assert_eq!(health(100), Some(100));
+        assert_eq!(health(-3), None);
+        assert_eq!(health(0x4000_0000), None);
+    }
+
+    #[test]
+    fn scores_sanity_checks() {
+        let v = Vec3::new;
+        let sane = Sanity::check(v(100.0, -200.0, 36.0), None, v(16.0, 16.0, 72.0), false, Hulls::STANDARD, 5, v(-3.0, 270.0, 0.0), Some((1, 100)));
+        assert_eq!(sane, Sanity(0));
+        let unsent = Sanity::check(v(1.0, 1.0, 1.0), None, Vec3::default(), false, Hulls::STANDARD, 5, Vec3::default(), None);
+        assert_eq!(unsent, Sanity(0));
+
+        let tall = Sanity::check(v(1.0, 1.0, 1.0), None, v(16.0, 16.0, 120.0), false, Hulls::STANDARD, 5, Vec3::default(), None);
+        assert_eq!((tall.reasons(), tall.rejected()), (vec!["hull"], false));
+        let outside = Sanity::check(v(1.0e7, 1.0, 1.0), None, Vec3::default(), false, Hulls::STANDARD, 5, Vec3::default(), None);
+        assert_eq!((outside.score(), outside.rejected()), (2, true));
+        let garbage = Sanity::check(v(1.0, 1.0, 1.0), None, Vec3::default(), false, Hulls::STANDARD, 0x4141_4141,
+                                    v(f32::NAN, 0.0, 0.0), Some((0x4141, 0)));
+        assert_eq!(garbage.reasons(), ["model", "angles", "extra info"]);
+        assert!(garbage.rejected());
+
+        // Inside the engine's limits but outside this map
+        let map = Bounds { mins: [-1024.0, -1024.0, -256.0], maxs: [1024.0, 1024.0, 256.0] };
+        let off_map = Sanity::check(v(3000.0, 0.0, 0.0), Some(&map), Vec3::default(), false, Hulls::STANDARD, 5, Vec3::default(), None);
+        assert!(off_map.rejected());
+        let on_map = Sanity::check(v(1050.0, 0.0, 0.0), Some(&map), Vec3::default(), false, Hulls::STANDARD, 5, Vec3::default(), None);
+        assert_eq!(on_map, Sanity(0));
+    }
+
+    #[test]
+    fn rejects_insane_and_drops_suspect_fields() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0e7, 1.0, 1.0]);
+        assert_eq!(interpret_player_checked(&e, 1).err(), Some(Rejected::Insane(Sanity(Sanity::ORIGIN))));
+        assert_eq!(interpret_player_checked(&e, 2).err(), Some(Rejected::Empty));
+
+        e.add_player(2, BASE + 0x10000, "b", [1.0, 1.0, 1.0]);
+        e.extra = 0x2000_0000;
+        e.write(e.extra + 2 * EXTRA_STRIDE + EXTRA_OFF_TEAMNUMBER, &0x4141i16.to_le_bytes());
+        let p = interpret_player_checked(&e, 2).unwrap();
+        assert_eq!(p.sanity.reasons(), ["extra info"]);
+        assert!(!p.has_extra);
+        assert_eq!(p.team, 0);
+    }
+
+    #[test]
+    fn ducking_uses_duck_hull() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        e.write_i32(BASE + CURSTATE_OFFSET + ES_USEHULL, 1);
+        e.write(BASE + CURSTATE_OFFSET + ES_MAXS + 8, &18.0f32.to_le_bytes());
+        let p = interpret_player(&e, 1).unwrap();
+        assert!(p.is_ducking);
+        assert_eq!(p.maxs_z, 18.0 + HULL_MARGIN);
+    }
+
+    #[test]
+    fn view_angles_unscale_model_pitch() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        e.write_vec3(BASE + CURSTATE_OFFSET + ES_ANGLES, [-10.0, 135.0, 4.0]);
+        let a = interpret_player(&e, 1).unwrap().view_angles;
+        assert_eq!((a.x, a.y, a.z), (30.0, 135.0, 0.0)); // Looking 30 degrees down
+        assert_eq!(view_from_model_angles(Vec3 { x: 40.0, y: 0.0, z: 0.0 }).x, -89.0);
+    }
+
+    #[test]
+    fn view_reference_by_observer_state() {
+        assert_eq!(view_reference(0, 0, false, true), ViewRef::Local);
+        assert_eq!(view_reference(OBS_IN_EYE, 5, false, true), ViewRef::Target(5));
+        assert_eq!(view_reference(3, 0, false, true), ViewRef::Camera);  // Free look
+        assert_eq!(view_reference(0, 0, true, true), ViewRef::Camera);   // Death cam
+        assert_eq!(view_reference(3, 0, false, false), ViewRef::Local);  // No camera captured
+        assert_eq!(view_reference(OBS_IN_EYE, 0, false, true), ViewRef::Camera);
+    }
+
+    #[test]
+    fn steam_id_carried_from_player_info() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        e.infos.get_mut(&1).unwrap().steam_id = 76561197960287930;
+        assert_eq!(interpret_player(&e, 1).unwrap().steam_id, 76561197960287930);
+    }
+
+    #[test]
+    fn pvs_from_message_numbers() {
+        let mut e = MockEngine::default();
+        e.add_player(1, BASE, "a", [1.0, 1.0, 1.0]);
+        e.write_i32(BASE + CURSTATE_OFFSET + ES_MESSAGENUM, 41);
+        let p = interpret_player(&e, 1).unwrap();
+        assert_eq!(p.message, 41);
+        assert!(in_pvs(p.message, 41));
+        assert!(!in_pvs(p.message, 42)); // Left out of the newest packet
+        assert!(in_pvs(p.message, 0));
+    }
+
+    #[test]
+    fn steam_id_forms() {
+        assert_eq!(parse_steam_id("76561197960287930"), Some(76561197960287930));
+        assert_eq!(parse_steam_id("STEAM_0:0:11101"), Some(76561197960287930));
+        assert_eq!(parse_steam_id("\"STEAM_1:1:11101\""), Some(76561197960287931));
+        assert_eq!(parse_steam_id("STEAM_0:2:1"), None);
+        assert_eq!(parse_steam_id("STEAM_0:1"), None);
+        assert_eq!(parse_steam_id("0"), None);
+        assert_eq!(parse_steam_id("bob"), None);
+    }
+
+    #[test]
+    fn hull_height_fallbacks() {
+        let std = Hulls::STANDARD;
+        assert_eq!(hull_height(0.0, false, std), std.stand + HULL_MARGIN);
+        assert_eq!(hull_height(80.0, false, std), 80.0 + HULL_MARGIN);
+        assert_eq!(hull_height(100.0, true, std), std.duck + HULL_MARGIN);
+        assert_eq!(hull_height(36.0, true, std), 36.0 + HULL_MARGIN);
+
+        // A taller hull table moves both the fallback and what's plausible
+        let tall = Hulls { stand: 100.0, duck: 60.0 };
+        assert_eq!(hull_height(0.0, false, tall), 100.0 + HULL_MARGIN);
+        assert_eq!(hull_height(110.0, false, tall), 110.0 + HULL_MARGIN);
+        assert_eq!(hull_height(70.0, true, tall), 70.0 + HULL_MARGIN);
+        assert_eq!(hull_height(70.0, false, tall), 100.0 + HULL_MARGIN);
+    }
+}
@@ -0,0 +1,307 @@
+// plugin.rs — Native overlay plugins (`plugins/*.dll` next to the DLL).
+//
+// Every DLL in the plugins folder is loaded once at attach, by the worker
+// thread; frames call in only after the load. A plugin exports, with the C
+// calling convention:
+//
+//   int  esp_plugin_init(uint32_t api_version);   // nonzero = keep me loaded
+//   void esp_plugin_frame(const EspFrame *frame, const EspDrawApi *draw);
+//   void esp_plugin_shutdown(void);                // optional
+//
+// `esp_plugin_frame` runs on the render thread after the boxes are drawn.
+// The layouts below are fixed for a given `API_VERSION` (32-bit, 4-byte
+// aligned; `size` fields let a plugin check what it was given):
+//
+//   typedef struct {
+//       int32_t  index, team, health;      // health -1 = unknown
+//       uint8_t  enemy, alive, on_screen, _pad;
+//       float    origin[3], yaw;
+//       float    dist;                     // meters from the viewer
+//       float    screen[2];                // pixels, valid when on_screen
+//       char     name[32];                 // UTF-8, NUL-terminated
+//   } EspPlayer;                           // 76 bytes
+//
+//   typedef struct {
+//       uint32_t size, time_ms;
+//       float    screen_w, screen_h;
+//       uint8_t  has_viewer, _pad[3];
+//       float    viewer[3], viewer_yaw;
+//       uint32_t player_count;
+//       const EspPlayer *players;
+//       const char *map;                   // NULL when not in a map
+//   } EspFrame;                            // 48 bytes
+//
+//   typedef struct {
+//       uint32_t size;
+//       void  (*draw_text)(float x, float y, const char *text, const float rgba[4]);
+//       void  (*draw_line)(float x0, float y0, float x1, float y1, const float rgba[4]);
+//       void  (*draw_rect)(float x0, float y0, float x1, float y1, const float rgba[4]);
+//       float (*text_width)(const char *text);
+//   } EspDrawApi;                          // cdecl
+//
+// Lifetimes: the frame, its players and map string are only valid during
+// the `esp_plugin_frame` call, and text passed to the draw functions is
+// copied before they return. Draw calls are collected and drawn once every
+// plugin has returned. Plugins are shut down and freed by the worker thread
+// after the render hook is removed, under the same lock the frame call
+// holds, so no plugin code is running when its DLL goes away.
+
+use crate::config::Config;
+use crate::entities;
+use crate::math::Vec3;
+use crate::render;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::sync::Mutex;
+use winapi::shared::minwindef::FARPROC;
+use winapi::shared::windef::HDC;
+use winapi::um::libloaderapi::{FreeLibrary, GetProcAddress, LoadLibraryW};
+
+/// Passed to `esp_plugin_init`; bumped whenever a layout above changes.
+pub const API_VERSION: u32 = 1;
+
+const NAME_LEN: usize = 32;
+const MAX_COMMANDS: usize = 4096;      // Draw calls kept per frame (all plugins)
+
+/// One player, as plugins see it.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EspPlayer {
+    pub index:     i32,
+    pub team:      i32,
+    pub health:    i32,              // -1 = unknown
+    pub enemy:     u8,
+    pub alive:     u8,
+    pub on_screen: u8,
+    pub _pad:      u8,
+    pub origin:    [f32; 3],
+    pub yaw:       f32,
+    pub dist:      f32,              // Meters from the viewer
+    pub screen:    [f32; 2],         // Pixels, valid when on_screen != 0
+    pub name:      [u8; NAME_LEN],   // UTF-8, NUL-terminated (cut at a char boundary)
+}
+
+#[repr(C)]
+pub struct EspFrame {
+    pub size:         u32,
+    pub time_ms:      u32,
+    pub screen_w:     f32,
+    pub screen_h:     f32,
+    pub has_viewer:   u8,
+    pub _pad:         [u8; 3],
+    pub viewer:       [f32; 3],
+    pub viewer_yaw:   f32,
+    pub player_count: u32,
+    pub players:      *const EspPlayer,
+    pub map:          *const c_char,
+}
+
+#[repr(C)]
+pub struct EspDrawApi {
+    pub size:       u32,
+    pub draw_text:  extern "C" fn(f32, f32, *const c_char, *const f32),
+    pub draw_line:  extern "C" fn(f32, f32, f32, f32, *const f32),
+    pub draw_rect:  extern "C" fn(f32, f32, f32, f32, *const f32),
+    pub text_width: extern "C" fn(*const c_char) -> f32,
+}
+
+// The layouts plugins are compiled against
+const _: () = assert!(std::mem::size_of::<EspPlayer>() == 76);
+#[cfg(target_arch = "x86")]
+const _: () = assert!(std::mem::size_of::<EspFrame>() == 48);
+
+impl EspPlayer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(index: i32, name: &str, team: i32, enemy: bool, alive: bool, health: Option<i32>,
+               origin: Vec3, yaw: f32, dist: f32, screen: Option<[f32; 2]>) -> Self {
+        let mut buf = [0u8; NAME_LEN];
+        let mut end = name.len().min(NAME_LEN - 1);
+        while !name.is_char_boundary(end) { end -= 1; }
+        buf[..end].copy_from_slice(&name.as_bytes()[..end]);
+        Self {
+            index, team, health: health.unwrap_or(-1),
+            enemy: enemy as u8, alive: alive as u8, on_screen: screen.is_some() as u8, _pad: 0,
+            origin: [origin.x, origin.y, origin.z], yaw, dist,
+            screen: screen.unwrap_or([0.0; 2]), name: buf,
+        }
+    }
+}
+
+type InitFn = unsafe extern "C" fn(u32) -> i32;
+type FrameFn = unsafe extern "C" fn(*const EspFrame, *const EspDrawApi);
+type ShutdownFn = unsafe extern "C" fn();
+
+struct Plugin {
+    name:     String,
+    module:   usize,                 // HMODULE
+    frame:    FrameFn,
+    shutdown: Option<ShutdownFn>,
+}
+
+static PLUGINS: Mutex<Vec<Plugin>> = Mutex::new(Vec::new());
+
+enum Command {
+    Text(f32, f32, String, [f32; 4]),
+    Line(f32, f32, f32, f32, [f32; 4]),
+    Rect(f32, f32, f32, f32, [f32; 4]),
+}
+
+thread_local! {
+    static COMMANDS: RefCell<Vec<Command>> = const { RefCell::new(Vec::new()) };
+}
+
+// ============================================================
+// Loading
+// ============================================================
+
+/// Load every DLL in the plugins folder. Called by the worker thread
+/// (subsystems.rs); frames don't call into plugins until it has returned.
+pub unsafe fn load(cfg: &Config) {
+    if !cfg.plugins_enabled { return; }
+    let dir = entities::dll_dir().join(&cfg.plugins_dir);
+    let Ok(entries) = std::fs::read_dir(&dir) else { return };
+    let mut paths: Vec<_> = entries.flatten().map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("dll")))
+        .collect();
+    paths.sort();
+
+    let mut plugins = match PLUGINS.lock() { Ok(p) => p, Err(_) => return };
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let wide: Vec<u16> = path.as_os_str().to_string_lossy().encode_utf16().chain(Some(0)).collect();
+        let module = LoadLibraryW(wide.as_ptr());
+        if module.is_null() {
+            entities::logf(format!("plugin {}: LoadLibrary failed ({})", name, std::io::Error::last_os_error()));
+            continue;
+        }
+        let init = GetProcAddress(module, c"esp_plugin_init".as_ptr());
+        let frame = GetProcAddress(module, c"esp_plugin_frame".as_ptr());
+        let shutdown = GetProcAddress(module, c"esp_plugin_shutdown".as_ptr());
+        if init.is_null() || frame.is_null() {
+            entities::logf(format!("plugin {}: missing esp_plugin_init / esp_plugin_frame", name));
+            FreeLibrary(module);
+            continue;
+        }
+        let init = std::mem::transmute::<FARPROC, InitFn>(init);
+        if init(API_VERSION) == 0 {
+            entities::logf(format!("plugin {}: declined API version {}", name, API_VERSION));
+            FreeLibrary(module);
+            continue;
+        }
+        entities::logf(format!("plugin {}: loaded", name));
+        plugins.push(Plugin {
+            name,
+            module: module as usize,
+            frame: std::mem::transmute::<FARPROC, FrameFn>(frame),
+            shutdown: (!shutdown.is_null()).then(|| std::mem::transmute::<FARPROC, ShutdownFn>(shutdown)),
+        });
+    }
+}
+
+/// Shut down and free every plugin. Called by the worker thread after the
+/// render hook is removed; waits for a frame call in progress.
+pub unsafe fn unload() {
+    let Ok(mut plugins) = PLUGINS.lock() else { return };
+    for p in plugins.drain(..).rev() {
+        if let Some(shutdown) = p.shutdown { shutdown(); }
+        FreeLibrary(p.module as _);
+        entities::logf(format!("plugin {}: unloaded", p.name));
+    }
+}
+
+/// Names of the loaded plugins (diagnostics).
+pub fn names() -> Vec<String> {
+    PLUGINS.lock().map(|p| p.iter().map(|p| p.name.clone()).collect()).unwrap_or_default()
+}
+
+/// Whether any plugin wants frames (so esp.rs can skip building the snapshot).
+pub fn active(cfg: &Config) -> bool {
+    cfg.plugins_enabled && PLUGINS.lock().is_ok_and(|p| !p.is_empty())
+}
+
+// ============================================================
+// Draw Table
+// ============================================================
+
+/// Copy a plugin's string; NULL reads as empty.
+unsafe fn text(s: *const c_char) -> String {
+    if s.is_null() { return String::new(); }
+    CStr::from_ptr(s).to_string_lossy().into_owned()
+}
+
+unsafe fn rgba(c: *const f32) -> [f32; 4] {
+    if c.is_null() { return [1.0; 4]; }
+    let c = std::slice::from_raw_parts(c, 4);
+    [c[0], c[1], c[2], c[3]].map(|v| if v.is_finite() { v.clamp(0.0, 1.0) } else { 1.0 })
+}
+
+fn push(cmd: Command) {
+    COMMANDS.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.len() < MAX_COMMANDS { c.push(cmd); }
+    });
+}
+
+extern "C" fn api_draw_text(x: f32, y: f32, s: *const c_char, c: *const f32) {
+    unsafe { push(Command::Text(x, y, text(s), rgba(c))); }
+}
+
+extern "C" fn api_draw_line(x0: f32, y0: f32, x1: f32, y1: f32, c: *const f32) {
+    unsafe { push(Command::Line(x0, y0, x1, y1, rgba(c))); }
+}
+
+extern "C" fn api_draw_rect(x0: f32, y0: f32, x1: f32, y1: f32, c: *const f32) {
+    unsafe { push(Command::Rect(x0, y0, x1, y1, rgba(c))); }
+}
+
+extern "C" fn api_text_width(s: *const c_char) -> f32 {
+    unsafe { render::text_width(&text(s)) }
+}
+
+static DRAW_API: EspDrawApi = EspDrawApi {
+    size: std::mem::size_of::<EspDrawApi>() as u32,
+    draw_text: api_draw_text,
+    draw_line: api_draw_line,
+    draw_rect: api_draw_rect,
+    text_width: api_text_width,
+};
+
+// ============================================================
+// Frame
+// ============================================================
+
+/// Call every plugin's `esp_plugin_frame`, then draw what they asked for.
+pub unsafe fn frame(
+    hdc: HDC, cfg: &Config, now: u32, map: Option<&str>, screen: [f32; 2],
+    viewer: Option<(Vec3, f32)>, players: &[EspPlayer],
+) {
+    if !cfg.plugins_enabled { return; }
+    let map = map.and_then(|m| CString::new(m).ok());
+    let (origin, yaw) = viewer.unwrap_or((Vec3::ZERO, 0.0));
+    let snapshot = EspFrame {
+        size: std::mem::size_of::<EspFrame>() as u32,
+        time_ms: now,
+        screen_w: screen[0],
+        screen_h: screen[1],
+        has_viewer: viewer.is_some() as u8,
+        _pad: [0; 3],
+        viewer: [origin.x, origin.y, origin.z],
+        viewer_yaw: yaw,
+        player_count: players.len() as u32,
+        players: players.as_ptr(),
+        map: map.as_ref().map_or(std::ptr::null(), |m| m.as_ptr()),
+    };
+    {
+        let Ok(plugins) = PLUGINS.lock() else { return };
+        for p in plugins.iter() { (p.frame)(&snapshot, &DRAW_API); }
+    }
+
+    let commands = COMMANDS.with(|c| std::mem::take(&mut *c.borrow_mut()));
+    for cmd in commands {
+        match cmd {
+            Command::Text(x, y, text, c) => render::draw_text(hdc, x, y, &text, c),
+            Command::Line(x0, y0, x1, y1, c) => render::draw_line(x0, y0, x1, y1, c, cfg.world_line),
+            Command::Rect(x0, y0, x1, y1, c) => render::fill_rect(x0, y0, x1, y1, c),
+        }
+    }
+}
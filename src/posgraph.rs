@@ -0,0 +1,113 @@
+// posgraph.rs — A player's recent speed and height, from the engine's own
+// position history.
+//
+// cl_entity_t keeps the last 64 positions the client received for an entity
+// (position_history_t: message time and origin), which is what interpolation
+// and lag compensation work from. The `[history_graph]` panel plots, for one
+// player, the horizontal speed between consecutive samples and the z
+// coordinate over the time they cover: a teleport or a rubber-band shows as a
+// speed spike, a bad update as a jump in z or a gap in time.
+
+use crate::draw::{DrawList, LineStyle};
+use crate::math::Vec3;
+
+const MIN_SPEED_SCALE: f32 = 300.0; // Speed plot covers at least 0..this (units/s)
+const MIN_Z_SCALE: f32 = 32.0;      // Height plot covers at least this range (units)
+
+/// A point of the graph: seconds before the newest sample, speed (units/s)
+/// and z.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub ago:   f32,
+    pub speed: f32,
+    pub z:     f32,
+}
+
+/// The graph points for `history` (message time, origin) in any order:
+/// sorted by time, without unused (zero time) or repeated entries.
+pub fn points(history: &[(f32, Vec3)]) -> Vec<Point> {
+    let mut samples: Vec<(f32, Vec3)> = history.iter().copied()
+        .filter(|&(t, p)| t > 0.0 && t.is_finite() && p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+        .collect();
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    samples.dedup_by(|a, b| a.0 == b.0);
+    let Some(&(newest, _)) = samples.last() else { return Vec::new() };
+    samples.windows(2).map(|w| {
+        let ((t0, p0), (t1, p1)) = (w[0], w[1]);
+        let flat = Vec3 { x: p1.x - p0.x, y: p1.y - p0.y, z: 0.0 };
+        Point { ago: newest - t1, speed: flat.length() / (t1 - t0), z: p1.z }
+    }).collect()
+}
+
+/// "speed 250 u/s (max 320)  z 36 (range 12)  1.2 s", for `points` in time
+/// order (as `points` returns them).
+pub fn summary(points: &[Point]) -> String {
+    let Some(last) = points.last() else { return "no position history".to_string() };
+    let max = points.iter().map(|p| p.speed).fold(0.0, f32::max);
+    let (lo, hi) = z_range(points);
+    let span = points.iter().map(|p| p.ago).fold(0.0, f32::max);
+    format!("speed {:.0} u/s (max {:.0})  z {:.0} (range {:.0})  {:.1} s", last.speed, max, last.z, hi - lo, span)
+}
+
+fn z_range(points: &[Point]) -> (f32, f32) {
+    points.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.z), hi.max(p.z)))
+}
+
+/// Speed in the top half of `area` and z in the bottom half, oldest on the
+/// left, as lines through the points.
+pub fn plot(points: &[Point], area: [f32; 4], colors: [[f32; 4]; 2]) -> DrawList {
+    let mut list = DrawList::new();
+    let [x0, y0, x1, y1] = area;
+    let mid = (y0 + y1) * 0.5;
+    let span = points.iter().map(|p| p.ago).fold(0.0, f32::max);
+    if points.len() < 2 || span <= 0.0 { return list; }
+    let x = |p: &Point| x1 - (p.ago / span) * (x1 - x0);
+    let style = LineStyle { width: 1.0, smooth: false };
+
+    let top = points.iter().map(|p| p.speed).fold(MIN_SPEED_SCALE, f32::max);
+    let (lo, hi) = z_range(points);
+    let pad = (MIN_Z_SCALE - (hi - lo)).max(0.0) * 0.5;
+    let (lo, hi) = (lo - pad, hi + pad);
+    let speed_y = |p: &Point| mid - 1.0 - (p.speed / top).clamp(0.0, 1.0) * (mid - 1.0 - y0);
+    let z_y = |p: &Point| y1 - ((p.z - lo) / (hi - lo)).clamp(0.0, 1.0) * (y1 - mid - 1.0);
+
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| b.ago.total_cmp(&a.ago));
+    for w in sorted.windows(2) {
+        list.line([x(&w[0]), speed_y(&w[0])], [x(&w[1]), speed_y(&w[1])], colors[0], style);
+        list.line([x(&w[0]), z_y(&w[0])], [x(&w[1]), z_y(&w[1])], colors[1], style);
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(t: f32, x: f32, z: f32) -> (f32, Vec3) { (t, Vec3 { x, y: 0.0, z }) }
+
+    #[test]
+    fn speeds_between_samples_in_time_order() {
+        // Ring buffer order, an unused entry and a repeat
+        let history = [at(10.2, 50.0, 0.0), at(0.0, 0.0, 0.0), at(10.0, 0.0, 0.0), at(10.1, 25.0, 8.0),
+                       at(10.2, 50.0, 0.0)];
+        let p = points(&history);
+        assert_eq!(p.len(), 2);
+        assert!((p[0].speed - 250.0).abs() < 0.1 && (p[0].ago - 0.1).abs() < 1e-4 && p[0].z == 8.0);
+        assert!((p[1].speed - 250.0).abs() < 0.1 && p[1].ago == 0.0);
+        assert!(summary(&p).starts_with("speed 250 u/s (max 250)  z 0 (range 8)  0.1 s"));
+        assert_eq!(summary(&[]), "no position history");
+    }
+
+    #[test]
+    fn plots_speed_above_height() {
+        let p = points(&[at(1.0, 0.0, 0.0), at(2.0, 300.0, 0.0), at(3.0, 300.0, 0.0)]);
+        let list = plot(&p, [0.0, 0.0, 100.0, 42.0], [[1.0; 4], [0.5; 4]]);
+        let lines: Vec<_> = list.cmds().iter().map(|c| match c {
+            crate::draw::Cmd::Line { from, to, .. } => (*from, *to),
+            _ => panic!("only lines"),
+        }).collect();
+        assert_eq!(lines[0], ([0.0, 0.0], [100.0, 20.0]));   // 300 u/s at the top, then stopped
+        assert_eq!(lines[1], ([0.0, 32.0], [100.0, 32.0]));  // Flat z, centered in its half
+    }
+}
@@ -0,0 +1,126 @@
+// readrate.rs — How often player data is read from engine memory, and how the
+// frames between reads are filled in.
+//
+// Reading 32 player slots (entity, extra info, model name) every frame is
+// wasted work at 300+ FPS. With `[performance] read_every` / `read_hz` set, the
+// reads run every N frames or at a fixed rate, and the frames in between reuse
+// the last snapshot. Each player's position is then extrapolated from its last
+// two reads, so boxes keep moving smoothly instead of stepping at the read rate.
+//
+// Extrapolation is capped: never further ahead than MAX_AHEAD_MS, and not at
+// all when the last two reads imply an impossible speed (a respawn or teleport).
+
+use crate::math::Vec3;
+
+/// Furthest a position is extrapolated past its last read.
+const MAX_AHEAD_MS: u32 = 100;
+
+/// Faster than this (units/s) between two reads is a teleport, not movement.
+const MAX_SPEED: f32 = 2000.0;
+
+/// How often to read: every `every_frames` frames, or `hz` times a second when
+/// set (`hz` wins). Both at their minimum (1 frame, 0 Hz) = every frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReadRate {
+    pub every_frames: u32,
+    pub hz:           f32,
+}
+
+/// Decides which frames read.
+#[derive(Default)]
+pub struct Throttle {
+    frames: u32, // Frames since the last read
+    last:   u32, // Time of the last read (0 = never)
+}
+
+impl Throttle {
+    pub const fn new() -> Self { Self { frames: 0, last: 0 } }
+
+    /// Whether this frame (at `now` ms) should read. Counts the frame.
+    pub fn due(&mut self, rate: ReadRate, now: u32) -> bool {
+        self.frames += 1;
+        let due = if self.last == 0 {
+            true
+        } else if rate.hz > 0.0 {
+            now.wrapping_sub(self.last) as f32 >= 1000.0 / rate.hz
+        } else {
+            self.frames >= rate.every_frames.max(1)
+        };
+        if due {
+            self.frames = 0;
+            self.last = now;
+        }
+        due
+    }
+}
+
+/// One player's last two read positions, for extrapolation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Track {
+    prev: Option<(Vec3, u32)>,
+    cur:  Option<(Vec3, u32)>,
+}
+
+impl Track {
+    pub const fn new() -> Self { Self { prev: None, cur: None } }
+
+    /// Record a position read at `time` ms.
+    pub fn push(&mut self, pos: Vec3, time: u32) {
+        if self.cur.is_some_and(|(_, t)| t == time) { return; }
+        self.prev = self.cur;
+        self.cur = Some((pos, time));
+    }
+
+    /// Forget the player (left the server, not read this time).
+    pub fn clear(&mut self) { *self = Self::default(); }
+
+    /// Where the player is likely to be at `now`: the last read, moved along
+    /// the velocity between the last two reads for at most MAX_AHEAD_MS.
+    pub fn predict(&self, now: u32) -> Option<Vec3> {
+        let (pos, t) = self.cur?;
+        let Some((prev, t0)) = self.prev else { return Some(pos) };
+        let span = t.wrapping_sub(t0);
+        if span == 0 { return Some(pos); }
+        let velocity = (pos - prev) * (1000.0 / span as f32);
+        if velocity.length() > MAX_SPEED { return Some(pos); }
+        let ahead = now.wrapping_sub(t).min(MAX_AHEAD_MS) as f32 / 1000.0;
+        Some(pos + velocity * ahead)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_n_frames_or_at_a_rate() {
+        let mut t = Throttle::new();
+        let every3 = ReadRate { every_frames: 3, hz: 0.0 };
+        let reads: Vec<bool> = (0..7).map(|i| t.due(every3, 1 + i * 4)).collect();
+        assert_eq!(reads, [true, false, false, true, false, false, true]);
+
+        let mut t = Throttle::new();
+        let hz20 = ReadRate { every_frames: 1, hz: 20.0 };
+        assert!(t.due(hz20, 1));
+        assert!(!t.due(hz20, 40));
+        assert!(t.due(hz20, 51));
+
+        let mut t = Throttle::new();
+        let always = ReadRate { every_frames: 0, hz: 0.0 };
+        assert!((0..5).all(|i| t.due(always, 1 + i)));
+    }
+
+    #[test]
+    fn extrapolates_between_reads() {
+        let mut tr = Track::default();
+        assert_eq!(tr.predict(10), None);
+        tr.push(Vec3::new(0.0, 0.0, 0.0), 100);
+        assert_eq!(tr.predict(150), Some(Vec3::new(0.0, 0.0, 0.0))); // One read: no velocity
+        tr.push(Vec3::new(25.0, 0.0, 0.0), 200);                     // 250 units/s
+        assert_eq!(tr.predict(200), Some(Vec3::new(25.0, 0.0, 0.0)));
+        assert_eq!(tr.predict(240), Some(Vec3::new(35.0, 0.0, 0.0)));
+        assert_eq!(tr.predict(900), Some(Vec3::new(50.0, 0.0, 0.0))); // Capped at MAX_AHEAD_MS
+        tr.push(Vec3::new(3000.0, 0.0, 0.0), 300);                   // Teleport
+        assert_eq!(tr.predict(350), Some(Vec3::new(3000.0, 0.0, 0.0)));
+    }
+}
@@ -0,0 +1,206 @@
+// registry.rs — Optional subsystems that can be switched off while running.
+//
+// Cargo features decide what's in the DLL at all; this decides what of it
+// runs. Each optional subsystem (the minimap, the live feed, plugins, the
+// control pipe, ...) is registered with its callbacks: `init` when it's
+// switched on (and at startup), `frame` every overlay frame while it runs,
+// `shutdown` when it's switched off (and at eject). Switching one off from
+// the menu stops it for the rest of the session, config reloads included,
+// so a misbehaving part can be taken out in the field without ejecting.
+//
+// Switching only records what's wanted; `reconcile` runs the init / shutdown
+// callbacks, on the worker thread (they may start or join threads, load
+// DLLs). A subsystem gets frames only once its init has returned. The frame
+// callbacks are copied out with `frames`, so none of the callbacks ever runs
+// under the registry's own lock.
+
+/// Where a registered subsystem is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum State {
+    Stopped,
+    Starting,       // `init` handed out by `reconcile`, not finished yet
+    Running,
+    Failed(String), // `init` returned this; switching it off and on retries
+}
+
+/// A subsystem's callbacks. `F` is the frame callback's type (esp.rs
+/// passes its frame context; tests use anything `Copy`).
+#[derive(Clone, Copy)]
+pub struct Module<F: Copy> {
+    pub name:     &'static str,
+    pub init:     Option<fn() -> Result<(), String>>,
+    pub frame:    Option<F>,
+    pub shutdown: Option<fn()>,
+}
+
+struct Entry<F: Copy> {
+    module: Module<F>,
+    wanted: bool,
+    state:  State,
+}
+
+/// The registered subsystems, in registration order.
+pub struct Registry<F: Copy> {
+    entries: Vec<Entry<F>>,
+}
+
+impl<F: Copy> Registry<F> {
+    pub const fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Add a subsystem, switched on (it starts at the next `reconcile`). A
+    /// name that's already registered is ignored.
+    pub fn register(&mut self, module: Module<F>) {
+        if self.entries.iter().any(|e| e.module.name == module.name) { return; }
+        self.entries.push(Entry { module, wanted: true, state: State::Stopped });
+    }
+
+    /// Ask for a subsystem to be switched on or off. False if there's no
+    /// subsystem by that name.
+    pub fn set_enabled(&mut self, name: &str, on: bool) -> bool {
+        match self.entries.iter_mut().find(|e| e.module.name == name) {
+            Some(e) => {
+                if on && !e.wanted && matches!(e.state, State::Failed(_)) { e.state = State::Stopped; }
+                e.wanted = on;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ask for everything to be switched off (eject).
+    pub fn disable_all(&mut self) {
+        for e in &mut self.entries { e.wanted = false; }
+    }
+
+    /// The init / shutdown calls that bring each subsystem to what's wanted,
+    /// in order: shutdowns in reverse registration order, then inits.
+    /// Each is returned with its subsystem's index for `finished`. A
+    /// subsystem being shut down is Stopped at once; one being started is
+    /// Starting until its init is reported back through `finished`.
+    pub fn reconcile(&mut self) -> Vec<(usize, Call)> {
+        let mut calls = Vec::new();
+        for (i, e) in self.entries.iter_mut().enumerate().rev() {
+            if !e.wanted && e.state == State::Running {
+                e.state = State::Stopped;
+                if let Some(f) = e.module.shutdown { calls.push((i, Call::Shutdown(f))); }
+            }
+        }
+        for (i, e) in self.entries.iter_mut().enumerate() {
+            if e.wanted && e.state == State::Stopped {
+                match e.module.init {
+                    Some(f) => { e.state = State::Starting; calls.push((i, Call::Init(f))); }
+                    None => e.state = State::Running,
+                }
+            }
+        }
+        calls
+    }
+
+    /// Record the outcome of a call from `reconcile` (only an init's matters).
+    pub fn finished(&mut self, index: usize, result: Result<(), String>) {
+        let Some(e) = self.entries.get_mut(index).filter(|e| e.state == State::Starting) else { return };
+        e.state = match result {
+            Ok(()) => State::Running,
+            Err(why) => State::Failed(why),
+        };
+    }
+
+    /// Frame callbacks of the running subsystems, in registration order.
+    pub fn frames(&self) -> Vec<(&'static str, F)> {
+        self.entries.iter()
+            .filter(|e| e.state == State::Running)
+            .filter_map(|e| e.module.frame.map(|f| (e.module.name, f)))
+            .collect()
+    }
+
+    /// Whether the named subsystem is running.
+    pub fn running(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| e.module.name == name && e.state == State::Running)
+    }
+
+    /// Every subsystem: name, whether it's wanted on, and its state.
+    pub fn list(&self) -> Vec<(&'static str, bool, State)> {
+        self.entries.iter().map(|e| (e.module.name, e.wanted, e.state.clone())).collect()
+    }
+}
+
+/// A callback `reconcile` wants made.
+#[derive(Clone, Copy)]
+pub enum Call {
+    Init(fn() -> Result<(), String>),
+    Shutdown(fn()),
+}
+
+impl Call {
+    /// Make the call. Shutdowns always succeed.
+    pub fn run(self) -> Result<(), String> {
+        match self {
+            Call::Init(f) => f(),
+            Call::Shutdown(f) => { f(); Ok(()) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok() -> Result<(), String> { Ok(()) }
+    fn fails() -> Result<(), String> { Err("port in use".to_string()) }
+    fn stop() {}
+
+    fn module(name: &'static str, init: fn() -> Result<(), String>, frame: Option<u8>) -> Module<u8> {
+        Module { name, init: Some(init), frame, shutdown: Some(stop) }
+    }
+
+    fn run(reg: &mut Registry<u8>) -> Vec<(usize, bool)> {
+        let calls = reg.reconcile();
+        let made = calls.iter().map(|&(i, c)| (i, matches!(c, Call::Init(_)))).collect();
+        for (i, c) in calls { reg.finished(i, c.run()); }
+        made
+    }
+
+    #[test]
+    fn starts_stops_and_restarts() {
+        let mut reg = Registry::new();
+        reg.register(module("minimap", ok, Some(1)));
+        reg.register(module("pipe", ok, None));
+        reg.register(module("minimap", ok, Some(9))); // Same name: ignored
+        assert!(reg.frames().is_empty());             // Nothing runs before reconcile
+
+        let calls = reg.reconcile();
+        assert_eq!(calls.len(), 2);
+        assert!(reg.frames().is_empty() && !reg.running("pipe")); // No frames until init returns
+        for (i, c) in calls { reg.finished(i, c.run()); }
+        assert_eq!(reg.frames(), [("minimap", 1)]);
+        assert!(run(&mut reg).is_empty());            // Already where it's wanted
+
+        assert!(reg.set_enabled("minimap", false));
+        assert!(!reg.set_enabled("radar", false));
+        assert_eq!(run(&mut reg), [(0, false)]);
+        assert!(reg.frames().is_empty() && !reg.running("minimap") && reg.running("pipe"));
+
+        reg.set_enabled("minimap", true);
+        assert_eq!(run(&mut reg), [(0, true)]);
+        assert!(reg.running("minimap"));
+
+        reg.disable_all();
+        assert_eq!(run(&mut reg), [(1, false), (0, false)]); // Last registered goes first
+    }
+
+    #[test]
+    fn failed_init_stays_off_until_switched_again() {
+        let mut reg = Registry::new();
+        reg.register(module("feed", fails, Some(2)));
+        run(&mut reg);
+        assert_eq!(reg.list(), [("feed", true, State::Failed("port in use".to_string()))]);
+        assert!(reg.frames().is_empty());
+        assert!(run(&mut reg).is_empty());            // Not retried every tick
+
+        reg.set_enabled("feed", false);
+        reg.set_enabled("feed", true);
+        assert_eq!(run(&mut reg), [(0, true)]);       // Retried after an off / on
+    }
+}
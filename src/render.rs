@@ -12,7 +12,21 @@
 //     font (`glVertex2f`), mimicking the classic blocky CS 1.6 HUD typography.
 //   - State Preservation: The `begin_2d()` and `end_2d()` functions ensure the game's 
 //     original 3D pipeline state is saved and restored perfectly, avoiding visual artifacts.
+//     Client array state is saved too (glPushClientAttrib), and `[log] gl_audit`
+//     compares a wider snapshot before and after the overlay (glstate.rs).
 
+use crate::config::{self, DigitStyle};
+use crate::draw::{self, BracketStyle, Cmd, Dash, DrawList};
+pub use crate::draw::LineStyle;
+use crate::entities;
+use crate::glstate::{self, GlState};
+use crate::hook;
+use crate::theme::Shade;
+use crate::tga;
+use crate::view::{CHAR_W, FONT_SCALE as SC};
+pub use crate::view::{text_width, TEXT_HEIGHT};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
 use winapi::shared::windef::HDC;
 
 const GL_ALL_ATTRIB_BITS:     u32 = 0x000F_FFFF;
@@ -31,6 +45,46 @@ const GL_ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
 const GL_PROJECTION:          u32 = 0x1701;
 const GL_MODELVIEW:           u32 = 0x1700;
 const GL_LINES:               u32 = 0x0001;
+const GL_LINE_LOOP:           u32 = 0x0002;
+const GL_LINE_STRIP:          u32 = 0x0003;
+const GL_TRIANGLES:           u32 = 0x0004;
+const GL_QUADS:               u32 = 0x0007;
+const GL_CURRENT_BIT:         u32 = 0x0000_0001;
+const GL_LINE_BIT:            u32 = 0x0000_0004;
+const GL_ENABLE_BIT:          u32 = 0x0000_2000;
+const GL_COLOR_BUFFER_BIT:    u32 = 0x0000_4000;
+const GL_CLIENT_ALL_ATTRIB_BITS: u32 = 0xFFFF_FFFF;
+const GL_BLEND_SRC:           u32 = 0x0BE1;
+const GL_BLEND_DST:           u32 = 0x0BE0;
+const GL_BLEND_EQUATION:      u32 = 0x8009;
+const GL_ACTIVE_TEXTURE:      u32 = 0x84E0;
+const GL_CLIENT_ACTIVE_TEXTURE: u32 = 0x84E1;
+const GL_TEXTURE_BINDING_2D:  u32 = 0x8069;
+const GL_MATRIX_MODE:         u32 = 0x0BA0;
+const GL_MODELVIEW_STACK_DEPTH: u32 = 0x0BA3;
+const GL_PROJECTION_STACK_DEPTH: u32 = 0x0BA4;
+const GL_SHADE_MODEL:         u32 = 0x0B54;
+const GL_DEPTH_WRITEMASK:     u32 = 0x0B72;
+const GL_LINE_WIDTH:          u32 = 0x0B21;
+const GL_CURRENT_COLOR:       u32 = 0x0B00;
+const GL_NO_ERROR:            u32 = 0;
+const GL_LINE_SMOOTH:         u32 = 0x0B20;
+const GL_UNSIGNED_BYTE:       u32 = 0x1401;
+const GL_PACK_ALIGNMENT:      u32 = 0x0D05;
+const GL_BGR_EXT:             u32 = 0x80E0;
+
+// Icon textures (the minimap background)
+#[cfg(feature = "radar")] const GL_TEXTURE_MIN_FILTER:  u32 = 0x2801;
+#[cfg(feature = "radar")] const GL_TEXTURE_MAG_FILTER:  u32 = 0x2800;
+#[cfg(feature = "radar")] const GL_TEXTURE_WRAP_S:      u32 = 0x2802;
+#[cfg(feature = "radar")] const GL_TEXTURE_WRAP_T:      u32 = 0x2803;
+#[cfg(feature = "radar")] const GL_LINEAR:              u32 = 0x2601;
+#[cfg(feature = "radar")] const GL_CLAMP:               u32 = 0x2900;
+#[cfg(feature = "radar")] const GL_UNPACK_ALIGNMENT:    u32 = 0x0CF5;
+#[cfg(feature = "radar")] const GL_RGBA:                u32 = 0x1908;
+#[cfg(feature = "radar")] const GL_TEXTURE_ENV:         u32 = 0x2300;
+#[cfg(feature = "radar")] const GL_TEXTURE_ENV_MODE:    u32 = 0x2200;
+#[cfg(feature = "radar")] const GL_MODULATE:            u32 = 0x2100;
 
 #[link(name = "opengl32")]
 extern "system" {
@@ -50,6 +104,23 @@ extern "system" {
     fn glEnd();
     fn glLineWidth(w: f32);
     fn glGetIntegerv(pname: u32, data: *mut i32);
+    fn glGetFloatv(pname: u32, data: *mut f32);
+    fn glGetBooleanv(pname: u32, data: *mut u8);
+    fn glIsEnabled(cap: u32) -> u8;
+    fn glGetError() -> u32;
+    fn glPushClientAttrib(mask: u32);
+    fn glPopClientAttrib();
+    fn glBindTexture(target: u32, texture: u32);
+    fn glTexParameteri(target: u32, pname: u32, param: i32);
+    fn glTexImage2D(target: u32, level: i32, internal: i32, w: i32, h: i32, border: i32,
+                    format: u32, ty: u32, pixels: *const std::ffi::c_void);
+    fn glTexEnvi(target: u32, pname: u32, param: i32);
+    fn glGetTexEnviv(target: u32, pname: u32, params: *mut i32);
+    fn glTexCoord2f(s: f32, t: f32);
+    fn glPixelStorei(pname: u32, param: i32);
+    fn glReadPixels(x: i32, y: i32, w: i32, h: i32, format: u32, ty: u32, data: *mut std::ffi::c_void);
+    fn wglGetCurrentDC() -> HDC;
+    fn wglGetCurrentContext() -> *mut std::ffi::c_void;
 }
 
 // ============================================================
@@ -57,7 +128,9 @@ extern "system" {
 // ============================================================
 
 pub unsafe fn begin_2d(w: f32, h: f32) {
+    audit_begin();
     glPushAttrib(GL_ALL_ATTRIB_BITS);
+    glPushClientAttrib(GL_CLIENT_ALL_ATTRIB_BITS);
     glDisable(GL_DEPTH_TEST);
     glDisable(GL_TEXTURE_2D);
     glDisable(GL_LIGHTING);
@@ -68,7 +141,8 @@ pub unsafe fn begin_2d(w: f32, h: f32) {
     glDisable(GL_STENCIL_TEST);
     glEnable(GL_BLEND);
     glBlendFunc(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA);
-    glLineWidth(1.5);
+    reset_line_style();
+    set_alpha(1.0);
     glColor4f(1.0, 1.0, 1.0, 1.0);
     glMatrixMode(GL_PROJECTION);
     glPushMatrix();
@@ -83,7 +157,99 @@ pub unsafe fn end_2d() {
     glPopMatrix();
     glMatrixMode(GL_PROJECTION);
     glPopMatrix();
+    glPopClientAttrib();
+    glPopAttrib();
+    audit_end("swapbuffers");
+}
+
+/// Lighter variant of `begin_2d()` for drawing inside the engine's HUD pass.
+/// The engine has already set a pixel-space, top-left-origin ortho projection
+/// there, so only the few states the HUD leaves on (texturing, alpha test) are
+/// touched — no matrix pushes.
+pub unsafe fn begin_hud() {
+    audit_begin();
+    glPushAttrib(GL_ENABLE_BIT | GL_COLOR_BUFFER_BIT | GL_CURRENT_BIT | GL_LINE_BIT);
+    glPushClientAttrib(GL_CLIENT_ALL_ATTRIB_BITS);
+    glDisable(GL_TEXTURE_2D);
+    glDisable(GL_ALPHA_TEST);
+    glDisable(GL_DEPTH_TEST);
+    glEnable(GL_BLEND);
+    glBlendFunc(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA);
+    reset_line_style();
+    set_alpha(1.0);
+}
+
+pub unsafe fn end_hud() {
+    glPopClientAttrib();
     glPopAttrib();
+    audit_end("hud_redraw");
+}
+
+// ============================================================
+// GL State Audit ([log] gl_audit)
+// ============================================================
+
+/// State captured by `audit_begin` for this frame's `audit_end`.
+static AUDIT_BEFORE: Mutex<Option<GlState>> = Mutex::new(None);
+
+/// Differences already logged (each is reported once, not every frame).
+static AUDIT_LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+unsafe fn audit_begin() {
+    if !config::get().gl_audit { return; }
+    let state = capture_state();
+    if let Ok(mut g) = AUDIT_BEFORE.lock() { *g = Some(state); }
+}
+
+/// Compare against the state before the overlay and log new differences.
+unsafe fn audit_end(site: &str) {
+    let Some(before) = AUDIT_BEFORE.lock().ok().and_then(|mut g| g.take()) else { return };
+    let after = capture_state();
+    let Ok(mut logged) = AUDIT_LOGGED.lock() else { return };
+    for d in before.diff(&after) {
+        if logged.contains(&d) { continue; }
+        entities::logf(format!("GL audit ({}): {}", site, d));
+        logged.push(d);
+    }
+}
+
+/// Read the audited state. Queries an old driver doesn't know leave their
+/// field at 0; the errors they raise are cleared so the game never sees them.
+unsafe fn capture_state() -> GlState {
+    let int = |pname: u32| { let mut v = 0i32; glGetIntegerv(pname, &mut v); v };
+    let bits = |caps: &[(u32, &str)]| caps.iter().enumerate()
+        .fold(0u32, |acc, (i, &(cap, _))| if glIsEnabled(cap) != 0 { acc | 1 << i } else { acc });
+
+    let mut state = GlState {
+        caps:             bits(&glstate::CAPS),
+        arrays:           bits(&glstate::ARRAYS),
+        blend_src:        int(GL_BLEND_SRC),
+        blend_dst:        int(GL_BLEND_DST),
+        blend_equation:   int(GL_BLEND_EQUATION),
+        active_texture:   int(GL_ACTIVE_TEXTURE),
+        client_texture:   int(GL_CLIENT_ACTIVE_TEXTURE),
+        texture_binding:  int(GL_TEXTURE_BINDING_2D),
+        matrix_mode:      int(GL_MATRIX_MODE),
+        modelview_depth:  int(GL_MODELVIEW_STACK_DEPTH),
+        projection_depth: int(GL_PROJECTION_STACK_DEPTH),
+        shade_model:      int(GL_SHADE_MODEL),
+        ..GlState::default()
+    };
+    let mut mask = 0u8;
+    glGetBooleanv(GL_DEPTH_WRITEMASK, &mut mask);
+    state.depth_mask = mask != 0;
+    glGetFloatv(GL_LINE_WIDTH, &mut state.line_width);
+    glGetFloatv(GL_CURRENT_COLOR, state.color.as_mut_ptr());
+    glGetIntegerv(GL_VIEWPORT, state.viewport.as_mut_ptr());
+    for _ in 0..16 {
+        if glGetError() == GL_NO_ERROR { break; }
+    }
+    state
+}
+
+/// Device context of the current GL context (for draw sites that don't get one).
+pub unsafe fn current_dc() -> HDC {
+    wglGetCurrentDC()
 }
 
 pub unsafe fn viewport_rect() -> Option<(f32, f32, f32, f32)> {
@@ -93,174 +259,625 @@ pub unsafe fn viewport_rect() -> Option<(f32, f32, f32, f32)> {
     Some((vp[0] as f32, vp[1] as f32, vp[2] as f32, vp[3] as f32))
 }
 
+/// The viewport's pixels as bottom-up BGR rows: (width, height, pixels).
+/// Reads through the unhooked glReadPixels so it isn't taken for a screenshot.
+pub unsafe fn read_viewport() -> Option<(u16, u16, Vec<u8>)> {
+    let (x, y, w, h) = viewport_rect()?;
+    let (w, h) = (w as usize, h as usize);
+    let mut pixels = vec![0u8; w * h * 3];
+    let mut align = 4i32;
+    glGetIntegerv(GL_PACK_ALIGNMENT, &mut align);
+    glPixelStorei(GL_PACK_ALIGNMENT, 1);
+    let read = hook::read_pixels_original().unwrap_or(glReadPixels);
+    read(x as i32, y as i32, w as i32, h as i32, GL_BGR_EXT, GL_UNSIGNED_BYTE, pixels.as_mut_ptr() as _);
+    glPixelStorei(GL_PACK_ALIGNMENT, align);
+    Some((w as u16, h as u16, pixels))
+}
+
+// ============================================================
+// Line Style
+// ============================================================
+// Each element draws its lines with its own width and anti-aliasing (`[lines]`
+// in the config). The GL state is only touched when the style changes; both
+// draw sites save GL_LINE_BIT, so the game gets its own back afterwards.
+
+/// Line style currently set in GL (width as f32 bits).
+static LINE_WIDTH: AtomicU32 = AtomicU32::new(0);
+static LINE_SMOOTH: AtomicBool = AtomicBool::new(false);
+
+/// Set the default style unconditionally (start of a frame: the GL state
+/// isn't ours yet).
+unsafe fn reset_line_style() {
+    let d = LineStyle::DEFAULT;
+    glLineWidth(d.width);
+    glDisable(GL_LINE_SMOOTH);
+    LINE_WIDTH.store(d.width.to_bits(), Ordering::Relaxed);
+    LINE_SMOOTH.store(d.smooth, Ordering::Relaxed);
+}
+
+/// Switch to `style` if it isn't already set.
+unsafe fn set_line_style(style: LineStyle) {
+    let width = style.width.clamp(0.5, 10.0);
+    if LINE_WIDTH.swap(width.to_bits(), Ordering::Relaxed) != width.to_bits() {
+        glLineWidth(width);
+    }
+    if LINE_SMOOTH.swap(style.smooth, Ordering::Relaxed) != style.smooth {
+        if style.smooth { glEnable(GL_LINE_SMOOTH); } else { glDisable(GL_LINE_SMOOTH); }
+    }
+}
+
+// ============================================================
+// Overlay Alpha
+// ============================================================
+// Every color drawn is multiplied by one alpha, so the whole overlay can fade
+// in and out on a toggle (view::OverlayFade) without each element knowing.
+// Both draw sites start a frame at 1.
+
+static ALPHA: AtomicU32 = AtomicU32::new(0x3F80_0000); // 1.0f32
+
+/// Alpha multiplier for everything drawn from now on (0..1).
+pub fn set_alpha(a: f32) {
+    ALPHA.store(a.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+}
+
+/// glColor4f with the overlay alpha applied.
+unsafe fn color(c: [f32; 4]) {
+    glColor4f(c[0], c[1], c[2], c[3] * f32::from_bits(ALPHA.load(Ordering::Relaxed)));
+}
+
 // ============================================================
 // Drawing Primitives
 // ============================================================
 
-pub unsafe fn draw_rect(x0: f32, y0: f32, x1: f32, y1: f32, c: [f32; 4]) {
-    glColor4f(c[0], c[1], c[2], c[3]);
+/// Rect outline. `shade` is a plain color or a top-to-bottom Shade.
+pub unsafe fn draw_rect(x0: f32, y0: f32, x1: f32, y1: f32, shade: impl Into<Shade>, style: LineStyle) {
+    set_line_style(style);
+    let v = shaded_vertex(shade.into(), y0, y1);
     glBegin(GL_LINES);
+    v(x0, y0); v(x1, y0);
+    v(x1, y0); v(x1, y1);
+    v(x1, y1); v(x0, y1);
+    v(x0, y1); v(x0, y0);
+    glEnd();
+}
+
+pub unsafe fn fill_rect(x0: f32, y0: f32, x1: f32, y1: f32, c: [f32; 4]) {
+    color(c);
+    glBegin(GL_QUADS);
     glVertex2f(x0, y0); glVertex2f(x1, y0);
-    glVertex2f(x1, y0); glVertex2f(x1, y1);
     glVertex2f(x1, y1); glVertex2f(x0, y1);
-    glVertex2f(x0, y1); glVertex2f(x0, y0);
     glEnd();
 }
 
-pub unsafe fn draw_box_corners(x0: f32, y0: f32, x1: f32, y1: f32, c: [f32; 4]) {
-    let bw = x1 - x0;
-    let bh = y1 - y0;
-    let lw = (bw * 0.22).clamp(4.0, 18.0);
-    let lh = (bh * 0.22).clamp(4.0, 18.0);
-    glColor4f(c[0], c[1], c[2], c[3]);
-    glBegin(GL_LINES);
-    glVertex2f(x0,     y0); glVertex2f(x0 + lw, y0);
-    glVertex2f(x0,     y0); glVertex2f(x0,      y0 + lh);
-    glVertex2f(x1,     y0); glVertex2f(x1 - lw, y0);
-    glVertex2f(x1,     y0); glVertex2f(x1,      y0 + lh);
-    glVertex2f(x0,     y1); glVertex2f(x0 + lw, y1);
-    glVertex2f(x0,     y1); glVertex2f(x0,      y1 - lh);
-    glVertex2f(x1,     y1); glVertex2f(x1 - lw, y1);
-    glVertex2f(x1,     y1); glVertex2f(x1,      y1 - lh);
+#[cfg(any(feature = "menu", feature = "radar"))]
+pub unsafe fn fill_triangle(a: [f32; 2], b: [f32; 2], d: [f32; 2], c: [f32; 4]) {
+    color(c);
+    glBegin(GL_TRIANGLES);
+    glVertex2f(a[0], a[1]); glVertex2f(b[0], b[1]); glVertex2f(d[0], d[1]);
     glEnd();
 }
 
-pub unsafe fn draw_rect_outline(x0: f32, y0: f32, x1: f32, y1: f32) {
-    draw_rect(x0 - 1.0, y0 - 1.0, x1 + 1.0, y1 + 1.0, [0.0, 0.0, 0.0, 0.6]);
+/// A box's corner brackets in one of the `[box] corners` styles, solid or
+/// cut into a dash pattern CPU-side (the segments come from draw::brackets).
+/// Cutting them here rather than with glLineStipple keeps the pattern in
+/// pixels at any line width and leaves no stipple state to restore.
+pub unsafe fn draw_box_corners(x0: f32, y0: f32, x1: f32, y1: f32, shade: impl Into<Shade>, style: LineStyle,
+                               kind: BracketStyle, line: Dash) {
+    let shade = shade.into();
+    for (extra, segs) in draw::brackets([x0, y0, x1, y1], kind, line) {
+        set_line_style(LineStyle { width: style.width + extra, ..style });
+        let v = shaded_vertex(shade, y0, y1);
+        glBegin(GL_LINES);
+        for (a, b) in segs {
+            v(a[0], a[1]);
+            v(b[0], b[1]);
+        }
+        glEnd();
+    }
 }
 
-pub unsafe fn draw_line(x0: f32, y0: f32, x1: f32, y1: f32, c: [f32; 4]) {
-    glColor4f(c[0], c[1], c[2], c[3]);
+/// Vertex emitter for a shape spanning `y0..y1`: a solid shade sets the color
+/// once (here, before glBegin), others color each vertex by its height.
+unsafe fn shaded_vertex(shade: Shade, y0: f32, y1: f32) -> impl Fn(f32, f32) {
+    if let Shade::Solid(c) = shade {
+        color(c);
+    }
+    let span = y1 - y0;
+    move |x, y| unsafe {
+        if !matches!(shade, Shade::Solid(_)) {
+            let c = shade.at(if span > 0.0 { (y - y0) / span } else { 0.0 });
+            color(c);
+        }
+        glVertex2f(x, y);
+    }
+}
+
+pub unsafe fn draw_line(x0: f32, y0: f32, x1: f32, y1: f32, c: [f32; 4], style: LineStyle) {
+    set_line_style(style);
+    color(c);
     glBegin(GL_LINES);
     glVertex2f(x0, y0);
     glVertex2f(x1, y1);
     glEnd();
 }
 
+/// Many separate line segments of one color in a single batch.
+#[cfg(feature = "radar")]
+pub unsafe fn draw_lines(segs: &[([f32; 2], [f32; 2])], c: [f32; 4], style: LineStyle) {
+    if segs.is_empty() { return; }
+    set_line_style(style);
+    color(c);
+    glBegin(GL_LINES);
+    for (a, b) in segs {
+        glVertex2f(a[0], a[1]);
+        glVertex2f(b[0], b[1]);
+    }
+    glEnd();
+}
+
+/// Circle outline centered at (cx, cy), approximated with `segments` line segments.
+pub unsafe fn draw_circle(cx: f32, cy: f32, r: f32, segments: u32, c: [f32; 4], style: LineStyle) {
+    let n = segments.max(8);
+    set_line_style(style);
+    color(c);
+    glBegin(GL_LINE_LOOP);
+    for i in 0..n {
+        let a = i as f32 / n as f32 * std::f32::consts::TAU;
+        glVertex2f(cx + a.cos() * r, cy + a.sin() * r);
+    }
+    glEnd();
+}
+
+/// Draw a connected polyline whose alpha varies per vertex (GL interpolates
+/// between them). Each point carries a 0..1 factor applied to `c`'s alpha.
+pub unsafe fn draw_fading_polyline(points: &[([f32; 2], f32)], c: [f32; 4], style: LineStyle) {
+    if points.len() < 2 { return; }
+    set_line_style(style);
+    glBegin(GL_LINE_STRIP);
+    for &([x, y], a) in points {
+        color([c[0], c[1], c[2], c[3] * a]);
+        glVertex2f(x, y);
+    }
+    glEnd();
+}
+
+// ============================================================
+// Draw Lists
+// ============================================================
+
+/// Draw every command of `list` in order (see draw.rs).
+pub unsafe fn draw_list(hdc: HDC, list: &DrawList) {
+    for cmd in list.cmds() {
+        match cmd {
+            Cmd::Rect { rect: [x0, y0, x1, y1], shade, style } => draw_rect(*x0, *y0, *x1, *y1, *shade, *style),
+            Cmd::Corners { rect: [x0, y0, x1, y1], shade, style, kind, line } => {
+                draw_box_corners(*x0, *y0, *x1, *y1, *shade, *style, *kind, *line)
+            }
+            Cmd::Fill { rect: [x0, y0, x1, y1], color } => fill_rect(*x0, *y0, *x1, *y1, *color),
+            Cmd::Line { from, to, color, style } => draw_line(from[0], from[1], to[0], to[1], *color, *style),
+            Cmd::Circle { center, radius, segments, color, style } => {
+                draw_circle(center[0], center[1], *radius, *segments, *color, *style)
+            }
+            Cmd::Text { at, text, color, scale } => draw_text_scaled(hdc, at[0], at[1], text, *color, *scale),
+        }
+    }
+}
+
+// ============================================================
+// Icon Textures
+// ============================================================
+// Images (.tga, decoded by tga.rs) from files next to the DLL, uploaded as GL
+// textures the first time they're drawn. Reading and decoding happen on the
+// worker thread (preload_icon at startup, or load_wanted_icons for a file a
+// frame asked for), so a frame only ever uploads; until the image is ready
+// the caller draws its untextured look. A file that's missing or can't be
+// decoded is logged once and remembered as failed.
+//
+// Texture names come from a fixed high range instead of glGenTextures: the
+// engine numbers its own textures with a counter and never asks GL for free
+// names, so a generated name could be overwritten by the next map's textures.
+// Textures belong to the GL context; a new context (video mode change) drops
+// the cache and everything is uploaded again.
+
+/// First texture name used for icons.
+#[cfg(feature = "radar")]
+const TEXTURE_BASE: u32 = 0x7E00_0000;
+
+/// An icon uploaded as a GL texture.
+#[cfg(feature = "radar")]
+#[derive(Clone, Copy, Debug)]
+pub struct Texture {
+    id: u32,
+}
+
+/// Loaded icons by file name (None = failed), and the context they belong to.
+#[cfg(feature = "radar")]
+struct Icons {
+    context: usize,
+    loaded:  Vec<(String, Option<Texture>)>,
+}
+
+#[cfg(feature = "radar")]
+static ICONS: Mutex<Icons> = Mutex::new(Icons { context: 0, loaded: Vec::new() });
+
+/// Images decoded by the worker thread by file name (None = failed), kept for
+/// uploading again in a new context.
+static DECODED: Mutex<Vec<(String, Option<tga::Image>)>> = Mutex::new(Vec::new());
+
+/// Files frames asked for that haven't been decoded yet.
+static WANTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Read and decode `file` (relative to the DLL's directory) for `icon`.
+/// Worker thread; does nothing for a file that's been decoded already.
+pub fn preload_icon(file: &str) {
+    if file.is_empty() { return; }
+    if DECODED.lock().is_ok_and(|d| d.iter().any(|(name, _)| name == file)) { return; }
+    let path = entities::dll_dir().join(file);
+    let img = match std::fs::read(&path) {
+        Ok(data) => tga::decode(&data).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = &img { entities::logf(format!("icon {}: {}", path.display(), e)); }
+    if let Ok(mut d) = DECODED.lock() { d.push((file.to_string(), img.ok())); }
+}
+
+/// Decode the files frames have asked for since the last call (worker thread).
+pub fn load_wanted_icons() {
+    let wanted = WANTED.lock().map(|mut w| std::mem::take(&mut *w)).unwrap_or_default();
+    for file in wanted { preload_icon(&file); }
+}
+
+/// The image `file` (relative to the DLL's directory), uploaded on first use
+/// once the worker has decoded it. Must be called with the game's GL context
+/// current.
+#[cfg(feature = "radar")]
+pub unsafe fn icon(file: &str) -> Option<Texture> {
+    if file.is_empty() { return None; }
+    let Ok(mut icons) = ICONS.lock() else { return None };
+    let context = wglGetCurrentContext() as usize;
+    if icons.context != context {
+        icons.context = context;
+        icons.loaded.clear();
+    }
+    if let Some((_, tex)) = icons.loaded.iter().find(|(name, _)| name == file) {
+        return *tex;
+    }
+
+    let Ok(decoded) = DECODED.lock() else { return None };
+    let Some((_, img)) = decoded.iter().find(|(name, _)| name == file) else {
+        if let Ok(mut w) = WANTED.lock() {
+            if !w.iter().any(|name| name == file) {
+                w.push(file.to_string());
+                crate::wake_worker();
+            }
+        }
+        return None;
+    };
+    let id = TEXTURE_BASE + icons.loaded.len() as u32;
+    let tex = img.as_ref().map(|img| upload(id, img));
+    icons.loaded.push((file.to_string(), tex));
+    tex
+}
+
+#[cfg(feature = "radar")]
+unsafe fn upload(id: u32, img: &tga::Image) -> Texture {
+    let mut prev = 0i32;
+    glGetIntegerv(GL_TEXTURE_BINDING_2D, &mut prev);
+    glBindTexture(GL_TEXTURE_2D, id);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR as i32);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR as i32);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP as i32);
+    glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP as i32);
+    glPixelStorei(GL_UNPACK_ALIGNMENT, 4);
+    glTexImage2D(GL_TEXTURE_2D, 0, GL_RGBA as i32, img.width as i32, img.height as i32, 0,
+                 GL_RGBA, GL_UNSIGNED_BYTE, img.rgba.as_ptr() as _);
+    glBindTexture(GL_TEXTURE_2D, prev as u32);
+    Texture { id }
+}
+
+/// Draw `tex` stretched over the rect, multiplied by `tint`. The texture
+/// binding and environment are put back (the HUD draw site doesn't push them).
+#[cfg(feature = "radar")]
+pub unsafe fn draw_texture(tex: Texture, x0: f32, y0: f32, x1: f32, y1: f32, tint: [f32; 4]) {
+    let (mut prev, mut env) = (0i32, 0i32);
+    glGetIntegerv(GL_TEXTURE_BINDING_2D, &mut prev);
+    glGetTexEnviv(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, &mut env);
+    glEnable(GL_TEXTURE_2D);
+    glBindTexture(GL_TEXTURE_2D, tex.id);
+    glTexEnvi(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, GL_MODULATE as i32);
+    color(tint);
+    glBegin(GL_QUADS);
+    glTexCoord2f(0.0, 0.0); glVertex2f(x0, y0);
+    glTexCoord2f(1.0, 0.0); glVertex2f(x1, y0);
+    glTexCoord2f(1.0, 1.0); glVertex2f(x1, y1);
+    glTexCoord2f(0.0, 1.0); glVertex2f(x0, y1);
+    glEnd();
+    glTexEnvi(GL_TEXTURE_ENV, GL_TEXTURE_ENV_MODE, env);
+    glBindTexture(GL_TEXTURE_2D, prev as u32);
+    glDisable(GL_TEXTURE_2D);
+}
+
 // ============================================================
 // Stroke Font - CS 1.6 styled, pure GL lines
 // ============================================================
-// Characters are drawn on a 6-wide x 8-tall grid, scaled by SC.
+// Characters are drawn on a 6-wide x 8-tall grid, scaled by SC (times the
+// text's own scale, for draw_text_scaled).
 // Grid origin = top-left. Y increases downward.
 // Mostly horizontal/vertical strokes for the blocky bitmap-font look.
+// Lowercase letters sit on rows 3..8 with descenders down to row 10; Latin-1
+// accented letters are their base glyph plus a diacritic (see `latin1_parts`).
 //
 // CHAR_W  = total column width (char + spacing)
-// SC      = pixel scale — increase for bigger text
+// SC      = pixel scale (view::FONT_SCALE) — increase for bigger text
+//
+// The metrics (and text_width / TEXT_HEIGHT) live in view.rs, so label layout
+// can be worked out off the game.
 
-const CHAR_W: f32 = 9.0;
-const SC:     f32 = 1.2;
+unsafe fn draw_stroke_char(cx: f32, cy: f32, ch: char, digits: DigitStyle, sc: f32) {
+    if let Some((base, accent)) = latin1_parts(ch) {
+        draw_stroke_char(cx, cy, base, digits, sc);
+        emit_segs(cx, cy, &accent_segs(accent, base.is_ascii_uppercase()), sc);
+        return;
+    }
 
-unsafe fn draw_stroke_char(cx: f32, cy: f32, ch: u8) {
     macro_rules! seg {
         ($x1:expr,$y1:expr, $x2:expr,$y2:expr) => {
             ($x1 as f32, $y1 as f32, $x2 as f32, $y2 as f32)
         };
     }
 
-    let segs: &[(f32,f32,f32,f32)] = match ch.to_ascii_uppercase() {
-        b'A' => &[seg!(0,8,  0,2), seg!(0,2,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,2),
+    let segs: &[(f32,f32,f32,f32)] = match ch {
+        'A' => &[seg!(0,8,  0,2), seg!(0,2,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,2),
                   seg!(5,2,  5,8), seg!(0,5,  5,5)],
-        b'B' => &[seg!(0,0,  0,8), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        'B' => &[seg!(0,0,  0,8), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  4,4), seg!(0,4,  4,4), seg!(4,4,  5,5), seg!(5,5,  5,7),
                   seg!(5,7,  4,8), seg!(0,8,  4,8)],
-        b'C' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
+        'C' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
                   seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7)],
-        b'D' => &[seg!(0,0,  0,8), seg!(0,0,  3,0), seg!(3,0,  5,2), seg!(5,2,  5,6),
+        'D' => &[seg!(0,0,  0,8), seg!(0,0,  3,0), seg!(3,0,  5,2), seg!(5,2,  5,6),
                   seg!(5,6,  3,8), seg!(3,8,  0,8)],
-        b'E' => &[seg!(0,0,  0,8), seg!(0,0,  5,0), seg!(0,4,  4,4), seg!(0,8,  5,8)],
-        b'F' => &[seg!(0,0,  0,8), seg!(0,0,  5,0), seg!(0,4,  4,4)],
-        b'G' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
+        'E' => &[seg!(0,0,  0,8), seg!(0,0,  5,0), seg!(0,4,  4,4), seg!(0,8,  5,8)],
+        'F' => &[seg!(0,0,  0,8), seg!(0,0,  5,0), seg!(0,4,  4,4)],
+        'G' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
                   seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7), seg!(5,7,  5,4),
                   seg!(3,4,  5,4)],
-        b'H' => &[seg!(0,0,  0,8), seg!(5,0,  5,8), seg!(0,4,  5,4)],
-        b'I' => &[seg!(1,0,  4,0), seg!(2,0,  2,8), seg!(1,8,  4,8)],
-        b'J' => &[seg!(2,0,  5,0), seg!(4,0,  4,7), seg!(4,7,  3,8), seg!(3,8,  1,8),
+        'H' => &[seg!(0,0,  0,8), seg!(5,0,  5,8), seg!(0,4,  5,4)],
+        'I' => &[seg!(1,0,  4,0), seg!(2,0,  2,8), seg!(1,8,  4,8)],
+        'J' => &[seg!(2,0,  5,0), seg!(4,0,  4,7), seg!(4,7,  3,8), seg!(3,8,  1,8),
                   seg!(1,8,  0,7)],
-        b'K' => &[seg!(0,0,  0,8), seg!(5,0,  0,4), seg!(1,4,  5,8)],
-        b'L' => &[seg!(0,0,  0,8), seg!(0,8,  5,8)],
-        b'M' => &[seg!(0,8,  0,0), seg!(0,0,  3,5), seg!(3,5,  6,0), seg!(6,0,  6,8)],
-        b'N' => &[seg!(0,8,  0,0), seg!(0,0,  5,8), seg!(5,8,  5,0)],
-        b'O' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
+        'K' => &[seg!(0,0,  0,8), seg!(5,0,  0,4), seg!(1,4,  5,8)],
+        'L' => &[seg!(0,0,  0,8), seg!(0,8,  5,8)],
+        'M' => &[seg!(0,8,  0,0), seg!(0,0,  3,5), seg!(3,5,  6,0), seg!(6,0,  6,8)],
+        'N' => &[seg!(0,8,  0,0), seg!(0,0,  5,8), seg!(5,8,  5,0)],
+        'O' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
                   seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0)],
-        b'P' => &[seg!(0,8,  0,0), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        'P' => &[seg!(0,8,  0,0), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  4,4), seg!(4,4,  0,4)],
-        b'Q' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
+        'Q' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
                   seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0),
                   seg!(3,6,  6,8)],
-        b'R' => &[seg!(0,8,  0,0), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        'R' => &[seg!(0,8,  0,0), seg!(0,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  4,4), seg!(4,4,  0,4), seg!(2,4,  5,8)],
-        b'S' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,3),
+        'S' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,3),
                   seg!(0,3,  1,4), seg!(1,4,  4,4), seg!(4,4,  5,5), seg!(5,5,  5,7),
                   seg!(5,7,  4,8), seg!(4,8,  1,8), seg!(1,8,  0,7)],
-        b'T' => &[seg!(0,0,  5,0), seg!(2,0,  2,8)],
-        b'U' => &[seg!(0,0,  0,7), seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7),
+        'T' => &[seg!(0,0,  5,0), seg!(2,0,  2,8)],
+        'U' => &[seg!(0,0,  0,7), seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7),
                   seg!(5,7,  5,0)],
-        b'V' => &[seg!(0,0,  2,8), seg!(2,8,  5,0)],
-        b'W' => &[seg!(0,0,  1,8), seg!(1,8,  3,4), seg!(3,4,  5,8), seg!(5,8,  6,0)],
-        b'X' => &[seg!(0,0,  5,8), seg!(5,0,  0,8)],
-        b'Y' => &[seg!(0,0,  2,4), seg!(5,0,  2,4), seg!(2,4,  2,8)],
-        b'Z' => &[seg!(0,0,  5,0), seg!(5,0,  0,8), seg!(0,8,  5,8)],
+        'V' => &[seg!(0,0,  2,8), seg!(2,8,  5,0)],
+        'W' => &[seg!(0,0,  1,8), seg!(1,8,  3,4), seg!(3,4,  5,8), seg!(5,8,  6,0)],
+        'X' => &[seg!(0,0,  5,8), seg!(5,0,  0,8)],
+        'Y' => &[seg!(0,0,  2,4), seg!(5,0,  2,4), seg!(2,4,  2,8)],
+        'Z' => &[seg!(0,0,  5,0), seg!(5,0,  0,8), seg!(0,8,  5,8)],
 
-        b'0' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
-                  seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0),
-                  seg!(1,2,  4,6)], // slash through 0 (CS 1.6 style)
-        b'1' => &[seg!(1,2,  2,0), seg!(2,0,  2,8), seg!(1,8,  4,8)],
-        b'2' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        'a' => &[seg!(1,3,  4,3), seg!(4,3,  5,4), seg!(5,4,  5,8), seg!(5,5,  1,5),
+                 seg!(1,5,  0,6), seg!(0,6,  0,7), seg!(0,7,  1,8), seg!(1,8,  5,8)],
+        'b' => &[seg!(0,0,  0,8), seg!(0,8,  4,8), seg!(4,8,  5,7), seg!(5,7,  5,4),
+                 seg!(5,4,  4,3), seg!(4,3,  0,3)],
+        'c' => &[seg!(5,3,  1,3), seg!(1,3,  0,4), seg!(0,4,  0,7), seg!(0,7,  1,8),
+                 seg!(1,8,  5,8)],
+        'd' => &[seg!(5,0,  5,8), seg!(5,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,4),
+                 seg!(0,4,  1,3), seg!(1,3,  5,3)],
+        'e' => &[seg!(0,5,  5,5), seg!(5,5,  5,4), seg!(5,4,  4,3), seg!(4,3,  1,3),
+                 seg!(1,3,  0,4), seg!(0,4,  0,7), seg!(0,7,  1,8), seg!(1,8,  5,8)],
+        'f' => &[seg!(4,0,  3,0), seg!(3,0,  2,1), seg!(2,1,  2,8), seg!(0,3,  4,3)],
+        'g' => &[seg!(5,3,  5,9), seg!(5,9,  4,10), seg!(4,10, 1,10), seg!(5,3,  1,3),
+                 seg!(1,3,  0,4), seg!(0,4,  0,6), seg!(0,6,  1,7), seg!(1,7,  5,7)],
+        'h' => &[seg!(0,0,  0,8), seg!(0,4,  1,3), seg!(1,3,  4,3), seg!(4,3,  5,4),
+                 seg!(5,4,  5,8)],
+        'i' => &[seg!(1,3,  2,3), seg!(2,3,  2,8), seg!(1,8,  3,8), seg!(2,1,  2,2)],
+        'ı' => &[seg!(1,3,  2,3), seg!(2,3,  2,8), seg!(1,8,  3,8)], // dotless, base for ì í î ï
+        'j' => &[seg!(2,3,  3,3), seg!(3,3,  3,9), seg!(3,9,  2,10), seg!(2,10, 0,10),
+                 seg!(3,1,  3,2)],
+        'k' => &[seg!(0,0,  0,8), seg!(4,3,  0,6), seg!(1,5,  4,8)],
+        'l' => &[seg!(1,0,  2,0), seg!(2,0,  2,8), seg!(1,8,  3,8)],
+        'm' => &[seg!(0,3,  0,8), seg!(0,4,  1,3), seg!(1,3,  2,3), seg!(2,3,  3,4),
+                 seg!(3,4,  3,8), seg!(3,4,  4,3), seg!(4,3,  5,3), seg!(5,3,  6,4),
+                 seg!(6,4,  6,8)],
+        'n' => &[seg!(0,3,  0,8), seg!(0,4,  1,3), seg!(1,3,  4,3), seg!(4,3,  5,4),
+                 seg!(5,4,  5,8)],
+        'o' => &[seg!(1,3,  4,3), seg!(4,3,  5,4), seg!(5,4,  5,7), seg!(5,7,  4,8),
+                 seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,4), seg!(0,4,  1,3)],
+        'p' => &[seg!(0,3,  0,10), seg!(0,3,  4,3), seg!(4,3,  5,4), seg!(5,4,  5,6),
+                 seg!(5,6,  4,7), seg!(4,7,  0,7)],
+        'q' => &[seg!(5,3,  5,10), seg!(5,3,  1,3), seg!(1,3,  0,4), seg!(0,4,  0,6),
+                 seg!(0,6,  1,7), seg!(1,7,  5,7)],
+        'r' => &[seg!(0,3,  0,8), seg!(0,5,  2,3), seg!(2,3,  5,3)],
+        's' => &[seg!(5,3,  1,3), seg!(1,3,  0,4), seg!(0,4,  1,5), seg!(1,5,  4,6),
+                 seg!(4,6,  5,7), seg!(5,7,  4,8), seg!(4,8,  0,8)],
+        't' => &[seg!(2,1,  2,7), seg!(2,7,  3,8), seg!(3,8,  5,8), seg!(0,3,  4,3)],
+        'u' => &[seg!(0,3,  0,7), seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7),
+                 seg!(5,3,  5,8)],
+        'v' => &[seg!(0,3,  2,8), seg!(2,8,  5,3)],
+        'w' => &[seg!(0,3,  1,8), seg!(1,8,  3,5), seg!(3,5,  5,8), seg!(5,8,  6,3)],
+        'x' => &[seg!(0,3,  5,8), seg!(5,3,  0,8)],
+        'y' => &[seg!(0,3,  0,6), seg!(0,6,  1,7), seg!(1,7,  5,7), seg!(5,3,  5,9),
+                 seg!(5,9,  4,10), seg!(4,10, 0,10)],
+        'z' => &[seg!(0,3,  5,3), seg!(5,3,  0,8), seg!(0,8,  5,8)],
+
+        '0' => match digits {
+            DigitStyle::Slashed => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
+                                     seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0),
+                                     seg!(1,2,  4,6)], // slash through 0 (CS 1.6 style)
+            DigitStyle::Dotted  => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
+                                     seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0),
+                                     seg!(2,3,  3,3), seg!(3,3,  3,5), seg!(3,5,  2,5), seg!(2,5,  2,3)],
+            DigitStyle::Plain   => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
+                                     seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,1), seg!(0,1,  1,0)],
+        },
+        '1' => match digits {
+            DigitStyle::Plain => &[seg!(1,2,  2,0), seg!(2,0,  2,8)],
+            _                 => &[seg!(1,2,  2,0), seg!(2,0,  2,8), seg!(1,8,  4,8)],
+        },
+        '2' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  0,8), seg!(0,8,  5,8)],
-        b'3' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        '3' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  3,4), seg!(0,4,  3,4), seg!(3,4,  5,5), seg!(5,5,  5,7),
                   seg!(5,7,  4,8), seg!(4,8,  1,8), seg!(1,8,  0,7)],
-        b'4' => &[seg!(0,0,  0,4), seg!(0,4,  5,4), seg!(4,0,  4,8)],
-        b'5' => &[seg!(5,0,  0,0), seg!(0,0,  0,4), seg!(0,4,  4,4), seg!(4,4,  5,5),
+        '4' => &[seg!(0,0,  0,4), seg!(0,4,  5,4), seg!(4,0,  4,8)],
+        '5' => &[seg!(5,0,  0,0), seg!(0,0,  0,4), seg!(0,4,  4,4), seg!(4,4,  5,5),
                   seg!(5,5,  5,7), seg!(5,7,  4,8), seg!(4,8,  1,8), seg!(1,8,  0,7)],
-        b'6' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
+        '6' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,7),
                   seg!(0,7,  1,8), seg!(1,8,  4,8), seg!(4,8,  5,7), seg!(5,7,  5,5),
                   seg!(5,5,  4,4), seg!(4,4,  0,4)],
-        b'7' => &[seg!(0,0,  5,0), seg!(5,0,  2,8)],
-        b'8' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3), seg!(5,3,  4,4),
+        '7' => &[seg!(0,0,  5,0), seg!(5,0,  2,8)],
+        '8' => &[seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3), seg!(5,3,  4,4),
                   seg!(1,4,  4,4), seg!(4,4,  5,5), seg!(5,5,  5,7), seg!(5,7,  4,8),
                   seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,5), seg!(0,5,  1,4),
                   seg!(1,4,  0,3), seg!(0,3,  0,1), seg!(0,1,  1,0)],
-        b'9' => &[seg!(5,4,  1,4), seg!(1,4,  0,3), seg!(0,3,  0,1), seg!(0,1,  1,0),
+        '9' => &[seg!(5,4,  1,4), seg!(1,4,  0,3), seg!(0,3,  0,1), seg!(0,1,  1,0),
                   seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,7), seg!(5,7,  4,8),
                   seg!(4,8,  1,8)],
 
-        b'.' => &[seg!(2,7,  3,7), seg!(3,7,  3,8), seg!(3,8,  2,8), seg!(2,8,  2,7)],
-        b',' => &[seg!(3,7,  2,9)],
-        b':' => &[seg!(2,2,  3,2), seg!(2,6,  3,6)],
-        b';' => &[seg!(2,2,  3,2), seg!(3,6,  2,8)],
-        b'!' => &[seg!(2,0,  2,5), seg!(2,7,  2,8)],
-        b'?' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
+        '.' => &[seg!(2,7,  3,7), seg!(3,7,  3,8), seg!(3,8,  2,8), seg!(2,8,  2,7)],
+        ',' => &[seg!(3,7,  2,9)],
+        ':' => &[seg!(2,2,  3,2), seg!(2,6,  3,6)],
+        ';' => &[seg!(2,2,  3,2), seg!(3,6,  2,8)],
+        '!' => &[seg!(2,0,  2,5), seg!(2,7,  2,8)],
+        '?' => &[seg!(0,1,  1,0), seg!(1,0,  4,0), seg!(4,0,  5,1), seg!(5,1,  5,3),
                   seg!(5,3,  3,5), seg!(3,5,  3,6), seg!(3,7,  3,8)],
-        b'-' => &[seg!(1,4,  4,4)],
-        b'+' => &[seg!(1,4,  4,4), seg!(2,2,  2,6)],
-        b'=' => &[seg!(1,3,  4,3), seg!(1,5,  4,5)],
-        b'_' => &[seg!(0,8,  5,8)],
-        b'/' => &[seg!(0,8,  5,0)],
-        b'\\' => &[seg!(0,0,  5,8)],
-        b'(' => &[seg!(4,0,  2,2), seg!(2,2,  2,6), seg!(2,6,  4,8)],
-        b')' => &[seg!(2,0,  4,2), seg!(4,2,  4,6), seg!(4,6,  2,8)],
-        b'[' => &[seg!(4,0,  2,0), seg!(2,0,  2,8), seg!(2,8,  4,8)],
-        b']' => &[seg!(2,0,  4,0), seg!(4,0,  4,8), seg!(4,8,  2,8)],
-        b'<' => &[seg!(4,0,  1,4), seg!(1,4,  4,8)],
-        b'>' => &[seg!(1,0,  4,4), seg!(4,4,  1,8)],
-        b'*' => &[seg!(1,1,  4,7), seg!(4,1,  1,7), seg!(0,4,  5,4)],
-        b'#' => &[seg!(1,0,  1,8), seg!(4,0,  4,8), seg!(0,3,  5,3), seg!(0,6,  5,6)],
-        b'%' => &[seg!(0,8,  5,0), seg!(1,0,  1,2), seg!(0,1,  2,1), seg!(4,6,  4,8),
+        '-' => &[seg!(1,4,  4,4)],
+        '+' => &[seg!(1,4,  4,4), seg!(2,2,  2,6)],
+        '=' => &[seg!(1,3,  4,3), seg!(1,5,  4,5)],
+        '_' => &[seg!(0,8,  5,8)],
+        '…' => &[seg!(0,8,  1,8), seg!(2,8,  3,8), seg!(4,8,  5,8)], // Ellipsis (view::ELLIPSIS)
+        '/' => &[seg!(0,8,  5,0)],
+        '\\' => &[seg!(0,0,  5,8)],
+        '(' => &[seg!(4,0,  2,2), seg!(2,2,  2,6), seg!(2,6,  4,8)],
+        ')' => &[seg!(2,0,  4,2), seg!(4,2,  4,6), seg!(4,6,  2,8)],
+        '[' => &[seg!(4,0,  2,0), seg!(2,0,  2,8), seg!(2,8,  4,8)],
+        ']' => &[seg!(2,0,  4,0), seg!(4,0,  4,8), seg!(4,8,  2,8)],
+        '<' => &[seg!(4,0,  1,4), seg!(1,4,  4,8)],
+        '>' => &[seg!(1,0,  4,4), seg!(4,4,  1,8)],
+        '*' => &[seg!(1,1,  4,7), seg!(4,1,  1,7), seg!(0,4,  5,4)],
+        '#' => &[seg!(1,0,  1,8), seg!(4,0,  4,8), seg!(0,3,  5,3), seg!(0,6,  5,6)],
+        '%' => &[seg!(0,8,  5,0), seg!(1,0,  1,2), seg!(0,1,  2,1), seg!(4,6,  4,8),
                   seg!(3,7,  5,7)],
-        b'\'' => &[seg!(2,0,  2,2)],
-        b'"' => &[seg!(1,0,  1,2), seg!(3,0,  3,2)],
-        b'~' => &[seg!(0,4,  1,3), seg!(1,3,  2,4), seg!(2,4,  3,3), seg!(3,3,  4,4),
+        '\'' => &[seg!(2,0,  2,2)],
+        '"' => &[seg!(1,0,  1,2), seg!(3,0,  3,2)],
+        '~' => &[seg!(0,4,  1,3), seg!(1,3,  2,4), seg!(2,4,  3,3), seg!(3,3,  4,4),
                   seg!(4,4,  5,3)],
-        b'|' => &[seg!(2,0,  2,8)],
-        b'^' => &[seg!(1,3,  3,0), seg!(3,0,  5,3)],
-        b' ' => &[],
+        '|' => &[seg!(2,0,  2,8)],
+        '^' => &[seg!(1,3,  3,0), seg!(3,0,  5,3)],
+        '@' => &[seg!(4,5,  4,3), seg!(4,3,  2,3), seg!(2,3,  2,5), seg!(2,5,  5,5),
+                 seg!(5,5,  5,1), seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1),
+                 seg!(0,1,  0,7), seg!(0,7,  1,8), seg!(1,8,  5,8)],
+        '&' => &[seg!(5,8,  1,3), seg!(1,3,  1,1), seg!(1,1,  2,0), seg!(2,0,  3,1),
+                 seg!(3,1,  3,2), seg!(3,2,  0,5), seg!(0,5,  0,7), seg!(0,7,  1,8),
+                 seg!(1,8,  3,8), seg!(3,8,  5,5)],
+        '$' => &[seg!(5,1,  4,0), seg!(4,0,  1,0), seg!(1,0,  0,1), seg!(0,1,  0,3),
+                 seg!(0,3,  1,4), seg!(1,4,  4,4), seg!(4,4,  5,5), seg!(5,5,  5,7),
+                 seg!(5,7,  4,8), seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(2,-1, 2,9)],
+        '{' => &[seg!(4,0,  3,0), seg!(3,0,  2,1), seg!(2,1,  2,3), seg!(2,3,  1,4),
+                 seg!(1,4,  2,5), seg!(2,5,  2,7), seg!(2,7,  3,8), seg!(3,8,  4,8)],
+        '}' => &[seg!(1,0,  2,0), seg!(2,0,  3,1), seg!(3,1,  3,3), seg!(3,3,  4,4),
+                 seg!(4,4,  3,5), seg!(3,5,  3,7), seg!(3,7,  2,8), seg!(2,8,  1,8)],
+        '`' => &[seg!(1,0,  3,2)],
+
+        // Latin-1 letters that aren't a base letter plus an accent
+        'Æ' => &[seg!(0,8,  0,2), seg!(0,2,  2,0), seg!(2,0,  6,0), seg!(3,0,  3,8),
+                 seg!(3,8,  6,8), seg!(0,5,  3,5), seg!(3,4,  5,4)],
+        'æ' => &[seg!(1,3,  3,3), seg!(3,3,  3,8), seg!(3,5,  1,5), seg!(1,5,  0,6),
+                 seg!(0,6,  0,7), seg!(0,7,  1,8), seg!(1,8,  6,8), seg!(3,3,  5,3),
+                 seg!(5,3,  6,4), seg!(6,4,  6,5), seg!(6,5,  3,5)],
+        'Ð' => &[seg!(0,0,  0,8), seg!(0,0,  3,0), seg!(3,0,  5,2), seg!(5,2,  5,6),
+                 seg!(5,6,  3,8), seg!(3,8,  0,8), seg!(-1,4, 2,4)],
+        'ð' => &[seg!(1,3,  4,3), seg!(4,3,  5,4), seg!(5,4,  5,7), seg!(5,7,  4,8),
+                 seg!(4,8,  1,8), seg!(1,8,  0,7), seg!(0,7,  0,4), seg!(0,4,  1,3),
+                 seg!(5,4,  3,0), seg!(2,1,  5,1)],
+        'Þ' => &[seg!(0,0,  0,8), seg!(0,2,  4,2), seg!(4,2,  5,3), seg!(5,3,  5,5),
+                 seg!(5,5,  4,6), seg!(4,6,  0,6)],
+        'þ' => &[seg!(0,0,  0,10), seg!(0,3,  4,3), seg!(4,3,  5,4), seg!(5,4,  5,6),
+                 seg!(5,6,  4,7), seg!(4,7,  0,7)],
+        'ß' => &[seg!(0,8,  0,1), seg!(0,1,  1,0), seg!(1,0,  3,0), seg!(3,0,  4,1),
+                 seg!(4,1,  4,2), seg!(4,2,  3,3), seg!(3,3,  4,4), seg!(4,4,  5,5),
+                 seg!(5,5,  5,7), seg!(5,7,  4,8), seg!(4,8,  2,8)],
+        '×' => &[seg!(1,2,  4,6), seg!(4,2,  1,6)],
+        '÷' => &[seg!(1,4,  4,4), seg!(2,2,  3,2), seg!(2,6,  3,6)],
+        ' ' => &[],
         _    => &[seg!(0,0,  4,0), seg!(4,0,  4,8), seg!(4,8,  0,8), seg!(0,8,  0,0)],
     };
 
+    emit_segs(cx, cy, segs, sc);
+}
+
+/// Emit grid-space segments as GL_LINES vertices (inside glBegin/glEnd),
+/// `sc` pixels per grid unit.
+unsafe fn emit_segs(cx: f32, cy: f32, segs: &[(f32, f32, f32, f32)], sc: f32) {
     for &(x1, y1, x2, y2) in segs {
-        glVertex2f(cx + x1 * SC, cy + y1 * SC);
-        glVertex2f(cx + x2 * SC, cy + y2 * SC);
+        glVertex2f(cx + x1 * sc, cy + y1 * sc);
+        glVertex2f(cx + x2 * sc, cy + y2 * sc);
+    }
+}
+
+/// Diacritic drawn with a base glyph for Latin-1 accented letters.
+#[derive(Clone, Copy)]
+enum Accent { Grave, Acute, Circumflex, Tilde, Diaeresis, Ring, Cedilla, Stroke }
+
+/// Split a Latin-1 accented letter into its base glyph and accent.
+/// Letters with a glyph of their own (Æ, ß, Þ, ...) return None.
+fn latin1_parts(ch: char) -> Option<(char, Accent)> {
+    use Accent::*;
+    Some(match ch {
+        'À' => ('A', Grave), 'Á' => ('A', Acute), 'Â' => ('A', Circumflex),
+        'Ã' => ('A', Tilde), 'Ä' => ('A', Diaeresis), 'Å' => ('A', Ring),
+        'Ç' => ('C', Cedilla),
+        'È' => ('E', Grave), 'É' => ('E', Acute), 'Ê' => ('E', Circumflex), 'Ë' => ('E', Diaeresis),
+        'Ì' => ('I', Grave), 'Í' => ('I', Acute), 'Î' => ('I', Circumflex), 'Ï' => ('I', Diaeresis),
+        'Ñ' => ('N', Tilde),
+        'Ò' => ('O', Grave), 'Ó' => ('O', Acute), 'Ô' => ('O', Circumflex),
+        'Õ' => ('O', Tilde), 'Ö' => ('O', Diaeresis), 'Ø' => ('O', Stroke),
+        'Ù' => ('U', Grave), 'Ú' => ('U', Acute), 'Û' => ('U', Circumflex), 'Ü' => ('U', Diaeresis),
+        'Ý' => ('Y', Acute),
+        'à' => ('a', Grave), 'á' => ('a', Acute), 'â' => ('a', Circumflex),
+        'ã' => ('a', Tilde), 'ä' => ('a', Diaeresis), 'å' => ('a', Ring),
+        'ç' => ('c', Cedilla),
+        'è' => ('e', Grave), 'é' => ('e', Acute), 'ê' => ('e', Circumflex), 'ë' => ('e', Diaeresis),
+        'ì' => ('ı', Grave), 'í' => ('ı', Acute), 'î' => ('ı', Circumflex), 'ï' => ('ı', Diaeresis),
+        'ñ' => ('n', Tilde),
+        'ò' => ('o', Grave), 'ó' => ('o', Acute), 'ô' => ('o', Circumflex),
+        'õ' => ('o', Tilde), 'ö' => ('o', Diaeresis), 'ø' => ('o', Stroke),
+        'ù' => ('u', Grave), 'ú' => ('u', Acute), 'û' => ('u', Circumflex), 'ü' => ('u', Diaeresis),
+        'ý' => ('y', Acute), 'ÿ' => ('y', Diaeresis),
+        _ => return None,
+    })
+}
+
+/// Grid segments for an accent. Capitals carry it above the grid (rows -3..-1),
+/// lowercase letters between the ascender line and the x-height (rows 0..2).
+fn accent_segs(accent: Accent, upper: bool) -> Vec<(f32, f32, f32, f32)> {
+    let t = if upper { -3.0 } else { 0.0 };
+    match accent {
+        Accent::Grave      => vec![(1.5, t, 3.0, t + 2.0)],
+        Accent::Acute      => vec![(2.0, t + 2.0, 3.5, t)],
+        Accent::Circumflex => vec![(1.0, t + 2.0, 2.5, t), (2.5, t, 4.0, t + 2.0)],
+        Accent::Tilde      => vec![(0.5, t + 2.0, 1.5, t + 1.0), (1.5, t + 1.0, 3.5, t + 2.0),
+                                   (3.5, t + 2.0, 4.5, t + 1.0)],
+        Accent::Diaeresis  => vec![(1.0, t + 1.0, 1.0, t + 2.0), (4.0, t + 1.0, 4.0, t + 2.0)],
+        Accent::Ring       => vec![(2.0, t, 3.0, t), (3.0, t, 3.0, t + 2.0),
+                                   (3.0, t + 2.0, 2.0, t + 2.0), (2.0, t + 2.0, 2.0, t)],
+        Accent::Cedilla    => vec![(2.5, 8.0, 2.5, 9.0), (2.5, 9.0, 1.5, 10.0)],
+        Accent::Stroke     => vec![(0.0, 8.0, 5.0, if upper { 0.0 } else { 3.0 })],
     }
 }
 
@@ -268,26 +885,35 @@ unsafe fn draw_stroke_char(cx: f32, cy: f32, ch: u8) {
 /// Draw text at screen position (x, y) using the stroke font.
 /// Draws a dark shadow first for contrast, then the colored text on top.
 /// Works in windowed AND fullscreen - uses only glVertex2f, same as boxes/lines.
-pub unsafe fn draw_text(_hdc: HDC, x: f32, y: f32, text: &str, c: [f32; 4]) {
+pub unsafe fn draw_text(hdc: HDC, x: f32, y: f32, text: &str, c: [f32; 4]) {
+    draw_text_scaled(hdc, x, y, text, c, 1.0);
+}
+
+/// `draw_text` at `scale` times the normal size (view::text_width(text) * scale wide).
+pub unsafe fn draw_text_scaled(_hdc: HDC, x: f32, y: f32, text: &str, c: [f32; 4], scale: f32) {
     if text.is_empty() { return; }
+    set_line_style(LineStyle::DEFAULT);
+
+    let digits = config::get().font_digits;
+    let (sc, advance) = (SC * scale, CHAR_W * scale);
 
     // Shadow pass (dark, slightly offset for readability)
-    glColor4f(0.0, 0.0, 0.0, c[3] * 0.75);
+    color([0.0, 0.0, 0.0, c[3] * 0.75]);
     glBegin(GL_LINES);
     let mut cx = 0.0f32;
-    for &b in text.as_bytes() {
-        draw_stroke_char(x + cx + 1.0, y + 1.0, b);
-        cx += CHAR_W;
+    for ch in text.chars() {
+        draw_stroke_char(x + cx + 1.0, y + 1.0, ch, digits, sc);
+        cx += advance;
     }
     glEnd();
 
     // Foreground pass
-    glColor4f(c[0], c[1], c[2], c[3]);
+    color(c);
     glBegin(GL_LINES);
     cx = 0.0;
-    for &b in text.as_bytes() {
-        draw_stroke_char(x + cx, y, b);
-        cx += CHAR_W;
+    for ch in text.chars() {
+        draw_stroke_char(x + cx, y, ch, digits, sc);
+        cx += advance;
     }
     glEnd();
 }
\ No newline at end of file
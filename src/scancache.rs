@@ -0,0 +1,140 @@
+// scancache.rs — Remembers pattern-scan results between injections.
+//
+// Scanning client.dll for the engine table and g_PlayerExtraInfo is the slow
+// part of startup. The addresses found are written to `scan_cache.txt` next to
+// the DLL, together with a key describing the exact client.dll and hw.dll that
+// were loaded (base address, image size, PE timestamp/checksum and a hash of
+// the headers). On the next injection a matching key means the addresses are
+// reused without scanning; any difference (game update, different mod, ASLR
+// relocation) invalidates the whole file and the scans run again.
+//
+// Cached addresses are still validated by the callers before use.
+
+use crate::entities;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Name of the cache file (resolved next to the DLL).
+const CACHE_FILE: &str = "scan_cache.txt";
+
+/// Modules whose identity the cache is keyed on.
+const KEY_MODULES: [&[u8]; 2] = [b"client.dll\0", b"hw.dll\0"];
+
+/// Bytes of the module image hashed into the key (the PE headers).
+const HEADER_HASH_LEN: usize = 0x1000;
+
+/// Loaded cache: (key it was written for, name -> address). None = not read yet.
+static CACHE: Mutex<Option<(String, HashMap<String, usize>)>> = Mutex::new(None);
+
+// ============================================================
+// Public API
+// ============================================================
+
+/// Cached address for `name`, if the cache matches the loaded modules.
+pub fn lookup(name: &str) -> Option<usize> {
+    let key = unsafe { current_key()? };
+    let mut guard = CACHE.lock().ok()?;
+    let (file_key, entries) = guard.get_or_insert_with(read_file);
+    if *file_key != key {
+        if !entries.is_empty() {
+            entities::log("scan cache: modules changed, ignoring cached addresses");
+            entries.clear();
+        }
+        return None;
+    }
+    entries.get(name).copied()
+}
+
+/// Record the address found for `name` and rewrite the cache file.
+pub fn store(name: &str, addr: usize) {
+    let Some(key) = (unsafe { current_key() }) else { return };
+    let Ok(mut guard) = CACHE.lock() else { return };
+    let (file_key, entries) = guard.get_or_insert_with(read_file);
+    if *file_key != key {
+        // Stale entries belong to other binaries — start over
+        *file_key = key;
+        entries.clear();
+    }
+    entries.insert(name.to_string(), addr);
+    write_file(file_key, entries);
+}
+
+// ============================================================
+// Module Key
+// ============================================================
+
+/// Identity of the loaded client.dll + hw.dll (None if either isn't loaded).
+unsafe fn current_key() -> Option<String> {
+    let parts: Option<Vec<String>> = KEY_MODULES.iter().map(|m| module_key(m)).collect();
+    Some(parts?.join("|"))
+}
+
+/// "base:size:timestamp:checksum:hash" for one module.
+unsafe fn module_key(name: &[u8]) -> Option<String> {
+    let (base, end) = entities::module_range(name)?;
+    let size = end - base;
+
+    // PE header fields: IMAGE_NT_HEADERS at e_lfanew; TimeDateStamp in the file
+    // header, CheckSum in the optional header.
+    let e_lfanew = entities::read_u32(base + 0x3C) as usize;
+    if e_lfanew == 0 || e_lfanew >= HEADER_HASH_LEN { return None; }
+    let nt = base + e_lfanew;
+    let timestamp = entities::read_u32(nt + 0x08);
+    let checksum = entities::read_u32(nt + 0x18 + 0x40);
+
+    let headers = std::slice::from_raw_parts(base as *const u8, HEADER_HASH_LEN.min(size));
+    Some(format!("{:#x}:{:#x}:{:08x}:{:08x}:{:016x}", base, size, timestamp, checksum, fnv1a(headers)))
+}
+
+/// 64-bit FNV-1a hash.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// ============================================================
+// File I/O
+// ============================================================
+
+fn cache_path() -> std::path::PathBuf {
+    entities::dll_dir().join(CACHE_FILE)
+}
+
+/// Read the cache file. A missing or unreadable file is an empty cache.
+///
+/// Format:
+///   key = <client key>|<hw key>
+///   <name> = 0x<address>
+fn read_file() -> (String, HashMap<String, usize>) {
+    let mut key = String::new();
+    let mut entries = HashMap::new();
+    let Ok(text) = std::fs::read_to_string(cache_path()) else { return (key, entries) };
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let Some((k, v)) = line.split_once('=') else { continue };
+        let (k, v) = (k.trim(), v.trim());
+        if k == "key" {
+            key = v.to_string();
+        } else if let Some(hex) = v.strip_prefix("0x") {
+            if let Ok(addr) = usize::from_str_radix(hex, 16) {
+                entries.insert(k.to_string(), addr);
+            }
+        }
+    }
+    (key, entries)
+}
+
+/// Write the cache file (best effort — a failure only costs a rescan next time).
+fn write_file(key: &str, entries: &HashMap<String, usize>) {
+    let mut out = String::from("# Pattern scan cache — safe to delete, rebuilt automatically\n");
+    out.push_str(&format!("key = {}\n", key));
+    let mut names: Vec<_> = entries.keys().collect();
+    names.sort();
+    for name in names {
+        out.push_str(&format!("{} = {:#x}\n", name, entries[name]));
+    }
+    if let Err(e) = std::fs::write(cache_path(), out) {
+        entities::logf(format!("scan cache: write failed: {}", e));
+    }
+}
@@ -0,0 +1,92 @@
+// schedule.rs — Periodic tasks of the worker thread and how long it can sleep.
+//
+// The worker waits on an event (lib.rs) instead of waking 20 times a second:
+// a frame or the menu that needs something done (a scan, an icon, a
+// subsystem switched) sets it, and in between the worker only wakes for the
+// next periodic task due here, `[worker]` intervals apart. With nothing
+// pending and the config watch and heartbeat off, it sleeps until unload.
+
+/// A task and its interval; `last` is when it last ran (0 = not yet).
+struct Entry<T> {
+    task:     T,
+    interval: u32, // ms; 0 = off
+    last:     u32,
+}
+
+/// Periodic tasks, each due `interval` ms after it last ran.
+pub struct Scheduler<T: Copy + PartialEq> {
+    entries: Vec<Entry<T>>,
+}
+
+impl<T: Copy + PartialEq> Scheduler<T> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Add `task` or change its interval (0 = off). A changed interval
+    /// counts from when the task last ran (or from `now`, for a new task).
+    pub fn set(&mut self, task: T, interval: u32, now: u32) {
+        match self.entries.iter_mut().find(|e| e.task == task) {
+            Some(e) => e.interval = interval,
+            None => self.entries.push(Entry { task, interval, last: now }),
+        }
+    }
+
+    /// The tasks due at `now`, in the order they were added; each counts as
+    /// run at `now`.
+    pub fn due(&mut self, now: u32) -> Vec<T> {
+        let mut out = Vec::new();
+        for e in &mut self.entries {
+            if e.interval != 0 && now.wrapping_sub(e.last) >= e.interval {
+                e.last = now;
+                out.push(e.task);
+            }
+        }
+        out
+    }
+
+    /// How long from `now` until the next task is due (0 = one is due
+    /// already; None = every task is off, sleep until woken).
+    pub fn wait_ms(&self, now: u32) -> Option<u32> {
+        self.entries.iter()
+            .filter(|e| e.interval != 0)
+            .map(|e| e.interval.saturating_sub(now.wrapping_sub(e.last)))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Task { Retry, Config, Heartbeat }
+
+    #[test]
+    fn runs_tasks_at_their_intervals() {
+        let mut s = Scheduler::new();
+        s.set(Task::Retry, 500, 1000);
+        s.set(Task::Config, 2000, 1000);
+        s.set(Task::Heartbeat, 0, 1000);
+        assert_eq!(s.wait_ms(1000), Some(500));
+        assert!(s.due(1200).is_empty());
+        assert_eq!(s.wait_ms(1200), Some(300));
+
+        assert_eq!(s.due(1500), [Task::Retry]);
+        assert_eq!(s.wait_ms(1500), Some(500));
+        assert_eq!(s.due(3100), [Task::Retry, Task::Config]); // Late: runs once, not catching up
+        assert_eq!(s.wait_ms(3100), Some(500));
+    }
+
+    #[test]
+    fn sleeps_until_woken_with_everything_off() {
+        let mut s = Scheduler::new();
+        assert_eq!(s.wait_ms(5), None);
+        s.set(Task::Retry, 500, 0);
+        s.set(Task::Retry, 0, 100);          // Nothing pending any more
+        assert_eq!(s.wait_ms(100), None);
+        assert!(s.due(10_000).is_empty());
+        s.set(Task::Retry, 500, 10_000);     // Counts from when it last ran: due at once
+        assert_eq!(s.wait_ms(10_000), Some(0));
+    }
+}
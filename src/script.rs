@@ -0,0 +1,251 @@
+// script.rs — User overlay elements written in Rhai (`esp_script.rhai`).
+//
+// Built with the `scripting` feature. The script next to the DLL is compiled
+// once and recompiled whenever the file changes; each frame its `on_frame`
+// function is called with a snapshot of the frame:
+//
+//   fn on_frame(f) {
+//       for p in f.players {
+//           if p.enemy && p.alive && p.dist < 5.0 && p.behind {
+//               draw_text(f.screen_w / 2 - 60, 120, "ENEMY BEHIND", [1.0, 0.2, 0.2]);
+//           }
+//       }
+//   }
+//
+//   f.time, f.map, f.screen_w, f.screen_h
+//   f.viewer     #{x, y, z, yaw} or () when unknown
+//   f.players    [#{index, name, team, enemy, alive, health, x, y, z, yaw,
+//                  dist (meters), behind, screen: [x, y] or ()}]
+//
+// Drawing functions (pixels from the top left, colors [r, g, b] or [r, g, b, a]):
+//   draw_text(x, y, text, color)    draw_line(x0, y0, x1, y1, color)
+//   draw_rect(x0, y0, x1, y1, color)     text_width(text)
+//
+// The script runs on the render thread with an operation limit, so a runaway
+// loop is stopped rather than freezing the game. Draw calls are collected
+// while it runs and drawn afterwards. Errors are logged once per script
+// version and shown on the status line.
+
+use crate::config::Config;
+use crate::entities;
+use crate::math::Vec3;
+use crate::render;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use std::cell::RefCell;
+use std::time::SystemTime;
+use winapi::shared::windef::HDC;
+
+const MAX_OPERATIONS: u64 = 200_000;   // Per on_frame call
+const RELOAD_CHECK_MS: u32 = 1000;     // How often the file's mtime is checked
+const MAX_COMMANDS: usize = 4096;      // Draw calls kept per frame
+
+/// One player as the script sees it.
+pub struct ScriptPlayer {
+    pub index:  i32,
+    pub name:   String,
+    pub team:   i32,
+    pub enemy:  bool,
+    pub alive:  bool,
+    pub health: Option<i32>,
+    pub origin: Vec3,
+    pub yaw:    f32,
+    pub dist:   f32,                 // Meters from the viewer
+    pub behind: bool,                // In the viewer's rear arc
+    pub screen: Option<[f32; 2]>,    // Projected origin (None = off screen)
+}
+
+/// What `on_frame` gets.
+pub struct ScriptFrame<'a> {
+    pub time:     u32,
+    pub map:      Option<&'a str>,
+    pub screen_w: f32,
+    pub screen_h: f32,
+    pub viewer:   Option<(Vec3, f32)>,
+    pub players:  Vec<ScriptPlayer>,
+}
+
+enum Command {
+    Text(f32, f32, String, [f32; 4]),
+    Line(f32, f32, f32, f32, [f32; 4]),
+    Rect(f32, f32, f32, f32, [f32; 4]),
+}
+
+struct Loaded {
+    engine:   Engine,
+    ast:      Option<AST>,
+    scope:    Scope<'static>,
+    path:     std::path::PathBuf,
+    mtime:    Option<SystemTime>,
+    checked:  u32,             // Last mtime check (ms)
+    error:    Option<String>,  // Compile or runtime error of this version
+}
+
+thread_local! {
+    static SCRIPT: RefCell<Option<Loaded>> = const { RefCell::new(None) };
+    static COMMANDS: RefCell<Vec<Command>> = const { RefCell::new(Vec::new()) };
+}
+
+// ============================================================
+// Engine
+// ============================================================
+
+/// A number argument, whether the script wrote `5` or `5.0`.
+fn num(d: &Dynamic) -> f32 {
+    d.as_float().map(|f| f as f32).or_else(|_| d.as_int().map(|i| i as f32)).unwrap_or(0.0)
+}
+
+/// A color argument: [r, g, b] or [r, g, b, a], components 0..1.
+fn color(d: &Dynamic) -> [f32; 4] {
+    let mut c = [1.0; 4];
+    if let Ok(items) = d.clone().into_array() {
+        for (i, v) in items.iter().take(4).enumerate() { c[i] = num(v).clamp(0.0, 1.0); }
+    }
+    c
+}
+
+fn push(cmd: Command) {
+    COMMANDS.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.len() < MAX_COMMANDS { c.push(cmd); }
+    });
+}
+
+fn new_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.register_fn("draw_text", |x: Dynamic, y: Dynamic, text: &str, c: Dynamic| {
+        push(Command::Text(num(&x), num(&y), text.to_string(), color(&c)));
+    });
+    engine.register_fn("draw_line", |x0: Dynamic, y0: Dynamic, x1: Dynamic, y1: Dynamic, c: Dynamic| {
+        push(Command::Line(num(&x0), num(&y0), num(&x1), num(&y1), color(&c)));
+    });
+    engine.register_fn("draw_rect", |x0: Dynamic, y0: Dynamic, x1: Dynamic, y1: Dynamic, c: Dynamic| {
+        push(Command::Rect(num(&x0), num(&y0), num(&x1), num(&y1), color(&c)));
+    });
+    engine.register_fn("text_width", |text: &str| render::text_width(text) as f64);
+    engine
+}
+
+/// (Re)compile the script at `loaded.path`. A missing file is an error until
+/// it appears.
+fn compile(loaded: &mut Loaded) {
+    loaded.mtime = std::fs::metadata(&loaded.path).and_then(|m| m.modified()).ok();
+    loaded.ast = None;
+    loaded.scope = Scope::new();
+    loaded.error = None;
+    let source = match std::fs::read_to_string(&loaded.path) {
+        Ok(s) => s,
+        Err(e) => {
+            loaded.error = Some(format!("{}: {}", loaded.path.display(), e));
+            return;
+        }
+    };
+    match loaded.engine.compile(&source) {
+        Ok(ast) => {
+            // Top-level statements run once, to set up globals
+            if let Err(e) = loaded.engine.run_ast_with_scope(&mut loaded.scope, &ast) {
+                loaded.error = Some(e.to_string());
+            }
+            loaded.ast = Some(ast);
+            entities::logf(format!("script: loaded {}", loaded.path.display()));
+        }
+        Err(e) => loaded.error = Some(e.to_string()),
+    }
+    if let Some(e) = &loaded.error { entities::logf(format!("script: {}", e)); }
+}
+
+// ============================================================
+// Frame
+// ============================================================
+
+fn to_dynamic(frame: &ScriptFrame) -> Dynamic {
+    let f = |v: f32| Dynamic::from(v as f64);
+    let mut m = Map::new();
+    m.insert("time".into(), Dynamic::from(frame.time as i64));
+    m.insert("map".into(), frame.map.map(|m| Dynamic::from(m.to_string())).unwrap_or(Dynamic::UNIT));
+    m.insert("screen_w".into(), f(frame.screen_w));
+    m.insert("screen_h".into(), f(frame.screen_h));
+    m.insert("viewer".into(), match frame.viewer {
+        Some((o, yaw)) => {
+            let mut v = Map::new();
+            v.insert("x".into(), f(o.x));
+            v.insert("y".into(), f(o.y));
+            v.insert("z".into(), f(o.z));
+            v.insert("yaw".into(), f(yaw));
+            Dynamic::from_map(v)
+        }
+        None => Dynamic::UNIT,
+    });
+    let players: Array = frame.players.iter().map(|p| {
+        let mut m = Map::new();
+        m.insert("index".into(), Dynamic::from(p.index as i64));
+        m.insert("name".into(), Dynamic::from(p.name.clone()));
+        m.insert("team".into(), Dynamic::from(p.team as i64));
+        m.insert("enemy".into(), Dynamic::from(p.enemy));
+        m.insert("alive".into(), Dynamic::from(p.alive));
+        m.insert("health".into(), p.health.map(|h| Dynamic::from(h as i64)).unwrap_or(Dynamic::UNIT));
+        m.insert("x".into(), f(p.origin.x));
+        m.insert("y".into(), f(p.origin.y));
+        m.insert("z".into(), f(p.origin.z));
+        m.insert("yaw".into(), f(p.yaw));
+        m.insert("dist".into(), f(p.dist));
+        m.insert("behind".into(), Dynamic::from(p.behind));
+        m.insert("screen".into(), match p.screen {
+            Some([x, y]) => Dynamic::from_array(vec![f(x), f(y)]),
+            None => Dynamic::UNIT,
+        });
+        Dynamic::from_map(m)
+    }).collect();
+    m.insert("players".into(), Dynamic::from_array(players));
+    Dynamic::from_map(m)
+}
+
+/// Run the script's `on_frame` for this frame and draw what it asked for.
+/// Returns the script's error, if it has one, for the status line.
+pub unsafe fn frame(hdc: HDC, cfg: &Config, now: u32, frame: &ScriptFrame) -> Option<String> {
+    if !cfg.script_enabled { return None; }
+    let path = entities::dll_dir().join(&cfg.script_file);
+
+    let error = SCRIPT.with(|s| {
+        let mut s = s.borrow_mut();
+        let loaded = s.get_or_insert_with(|| Loaded {
+            engine: new_engine(), ast: None, scope: Scope::new(), path: path.clone(),
+            mtime: None, checked: now, error: None,
+        });
+
+        // --- Hot reload: a new path, or the file changed ---
+        if loaded.path != path {
+            loaded.path = path;
+            compile(loaded);
+        } else if loaded.ast.is_none() && loaded.error.is_none() {
+            compile(loaded);
+        } else if now.wrapping_sub(loaded.checked) >= RELOAD_CHECK_MS {
+            loaded.checked = now;
+            let mtime = std::fs::metadata(&loaded.path).and_then(|m| m.modified()).ok();
+            if mtime != loaded.mtime { compile(loaded); }
+        }
+
+        // --- Run it ---
+        let Loaded { engine, ast, scope, error, .. } = loaded;
+        if let (Some(ast), None) = (ast.as_ref(), error.as_ref()) {
+            let options = CallFnOptions::new().eval_ast(false);
+            if let Err(e) = engine.call_fn_with_options::<Dynamic>(options, scope, ast, "on_frame", (to_dynamic(frame),)) {
+                let text = e.to_string();
+                entities::logf(format!("script: {}", text));
+                *error = Some(text);
+            }
+        }
+        error.clone()
+    });
+
+    // --- Draw what it asked for ---
+    let commands = COMMANDS.with(|c| std::mem::take(&mut *c.borrow_mut()));
+    for cmd in commands {
+        match cmd {
+            Command::Text(x, y, text, c) => render::draw_text(hdc, x, y, &text, c),
+            Command::Line(x0, y0, x1, y1, c) => render::draw_line(x0, y0, x1, y1, c, cfg.world_line),
+            Command::Rect(x0, y0, x1, y1, c) => render::fill_rect(x0, y0, x1, y1, c),
+        }
+    }
+    error
+}
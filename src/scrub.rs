@@ -0,0 +1,108 @@
+// scrub.rs — The last few seconds of players, for freeze + scrub.
+//
+// esp.rs records the players it reads every SAMPLE_MS, keeping the last
+// `[scrub] seconds`. `[scrub] key` freezes the ESP on the newest snapshot:
+// recording stops, and `[scrub] back_key` / `forward_key` step the frozen
+// boxes through the buffer `step_ms` at a time, drawn from wherever the
+// camera is now, so what just happened can be looked at again without a
+// demo. The same key goes back to live.
+
+use std::collections::VecDeque;
+
+/// Time between recorded snapshots.
+pub const SAMPLE_MS: u32 = 20;
+
+/// Recorded snapshots and where the frozen view is.
+pub struct Scrubber<T> {
+    frames: VecDeque<(u32, T)>, // (time in ms, snapshot), oldest first
+    cursor: Option<usize>,      // Frozen on this frame; None = live
+}
+
+impl<T> Scrubber<T> {
+    pub const fn new() -> Self {
+        Self { frames: VecDeque::new(), cursor: None }
+    }
+
+    /// Record a snapshot taken at `now` (made only if one is due), keeping
+    /// the last `span_ms`. Nothing is recorded while frozen.
+    pub fn record(&mut self, now: u32, span_ms: u32, snapshot: impl FnOnce() -> T) {
+        if self.cursor.is_some() { return; }
+        if self.frames.back().is_some_and(|&(t, _)| now.wrapping_sub(t) < SAMPLE_MS) { return; }
+        self.frames.push_back((now, snapshot()));
+        while self.frames.front().is_some_and(|&(t, _)| now.wrapping_sub(t) > span_ms) {
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Freeze on the newest snapshot, or go back to live. Nothing to freeze
+    /// on before the first snapshot.
+    pub fn toggle(&mut self) {
+        self.cursor = match self.cursor {
+            Some(_) => None,
+            None => self.frames.len().checked_sub(1),
+        };
+    }
+
+    /// Move the frozen view `ms` later (negative = earlier): to the snapshot
+    /// closest to that time, at least one over, within the buffer.
+    pub fn step(&mut self, ms: i32) {
+        let (Some(i), Some(&(newest, _))) = (self.cursor, self.frames.back()) else { return };
+        let age = |j: usize| newest.wrapping_sub(self.frames[j].0) as i64;
+        let target = age(i) - ms as i64;
+        let mut best = (0..self.frames.len()).min_by_key(|&j| (age(j) - target).abs()).unwrap_or(i);
+        if best == i && ms != 0 {
+            best = if ms < 0 { i.saturating_sub(1) } else { (i + 1).min(self.frames.len() - 1) };
+        }
+        self.cursor = Some(best);
+    }
+
+    /// The frozen snapshot and how far it is behind the newest (ms).
+    pub fn current(&self) -> Option<(&T, u32)> {
+        let (t, frame) = self.frames.get(self.cursor?)?;
+        let newest = self.frames.back()?.0;
+        Some((frame, newest.wrapping_sub(*t)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_last_span_and_pauses_while_frozen() {
+        let mut s = Scrubber::new();
+        s.toggle();
+        assert!(!s.frozen()); // Nothing recorded yet
+        for t in 0..100 { s.record(1000 + t * 10, 500, || t); } // Every other one is due
+        assert_eq!(s.frames.len(), 26);                       // 1980 back to 1480
+        assert_eq!(s.frames.front().map(|f| f.0), Some(1480));
+
+        s.toggle();
+        assert_eq!(s.current(), Some((&98, 0)));
+        s.record(5000, 500, || 1);                            // Frozen: not recorded
+        assert_eq!(s.current(), Some((&98, 0)));
+        s.toggle();
+        assert!(!s.frozen() && s.current().is_none());
+    }
+
+    #[test]
+    fn steps_through_the_buffer() {
+        let mut s = Scrubber::new();
+        for t in 0..10u32 { s.record(t * 100, 10_000, || t); }
+        s.toggle();
+        s.step(-240);
+        assert_eq!(s.current(), Some((&7, 200)));             // Closest to 240 ms back
+        s.step(-10);
+        assert_eq!(s.current(), Some((&6, 300)));             // Less than a snapshot: still moves one
+        s.step(-10_000);
+        assert_eq!(s.current(), Some((&0, 900)));             // Stops at the oldest
+        s.step(-100);
+        assert_eq!(s.current(), Some((&0, 900)));
+        s.step(10_000);
+        assert_eq!(s.current(), Some((&9, 0)));
+    }
+}
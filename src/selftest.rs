@@ -0,0 +1,130 @@
+// selftest.rs — Hook health self-test (Home on the F7 status page, or `selftest`
+// on the control pipe).
+//
+// Other overlays and anticheat-style plugins sometimes patch the same
+// functions we do, and when one writes over our JMP the overlay just stops
+// drawing with nothing in the log. The self-test checks, item by item:
+//
+//   present hook   wglSwapBuffers still starts with the JMP MinHook wrote
+//   Initialize     the client.dll!Initialize patch is intact, or the engine
+//                  table was already caught through it (or found by scan, so
+//                  it was never patched)
+//   engine table   its verified slots pass verification again
+//   extra info     g_PlayerExtraInfo holds plausible teams and health
+//
+// A request only sets a flag: the checks call engine functions, so they run
+// on the render thread at the next overlay frame. The results are logged and
+// shown in their own panel for SHOW_MS; a hook found overwritten is also
+// published as a frame event (bus.rs).
+
+use crate::bus::{self, Event};
+use crate::entities::{self, EngineApi};
+use crate::hook;
+use crate::offsets::Offset;
+use crate::player;
+use crate::time;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+
+const SHOW_MS: u32 = 15_000;   // How long the results panel stays up
+
+/// One checked item.
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name:   &'static str,
+    pub result: Result<String, String>,   // Detail either way
+}
+
+/// A run has been asked for.
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Completed runs (the control pipe waits for this to move).
+static RUNS: AtomicU32 = AtomicU32::new(0);
+
+/// The last results and time::now_ms() when they were taken.
+static LAST: Mutex<Option<(u32, Vec<Check>)>> = Mutex::new(None);
+
+/// Ask for a self-test at the next overlay frame.
+pub fn request() {
+    PENDING.store(true, Ordering::Release);
+}
+
+/// Number of runs completed so far.
+#[cfg(feature = "ipc")]
+pub fn runs() -> u32 {
+    RUNS.load(Ordering::Acquire)
+}
+
+/// Run the self-test if one was asked for. Called once per overlay frame.
+pub unsafe fn frame() {
+    if !PENDING.swap(false, Ordering::AcqRel) { return; }
+    let checks = run();
+    for c in &checks {
+        match &c.result {
+            Ok(detail) => entities::logf(format!("self-test {}: pass ({})", c.name, detail)),
+            Err(e)     => entities::logf(format!("self-test {}: FAIL ({})", c.name, e)),
+        }
+    }
+    if let Ok(mut g) = LAST.lock() { *g = Some((time::now_ms(), checks)); }
+    RUNS.fetch_add(1, Ordering::AcqRel);
+}
+
+/// The last results, if taken within `max_age_ms`.
+pub fn last(max_age_ms: u32) -> Option<Vec<Check>> {
+    let g = LAST.lock().ok()?;
+    let (at, checks) = g.as_ref()?;
+    (time::now_ms().wrapping_sub(*at) <= max_age_ms).then(|| checks.clone())
+}
+
+/// The results to show on screen (None once SHOW_MS has passed).
+pub fn shown() -> Option<Vec<Check>> {
+    last(SHOW_MS)
+}
+
+unsafe fn run() -> Vec<Check> {
+    vec![
+        Check { name: "present hook", result: present_hook() },
+        Check { name: "Initialize",   result: initialize_patch() },
+        Check { name: "engine table", result: engine_table() },
+        Check { name: "extra info",   result: extra_info() },
+    ]
+}
+
+unsafe fn present_hook() -> Result<String, String> {
+    match hook::intact(hook::HOOK_WGL) {
+        Some(true)  => Ok("our JMP is in place".to_string()),
+        Some(false) => {
+            bus::publish(Event::HookLost(hook::HOOK_WGL));
+            Err("overwritten by something else".to_string())
+        }
+        None        => Err("not enabled".to_string()),
+    }
+}
+
+unsafe fn initialize_patch() -> Result<String, String> {
+    let caught = entities::engine_table() != 0;
+    match hook::intact(entities::HOOK_INITIALIZE) {
+        Some(true)           => Ok("intact".to_string()),
+        Some(false) if caught => Ok("overwritten, but the engine table was already caught".to_string()),
+        Some(false)          => {
+            bus::publish(Event::HookLost(entities::HOOK_INITIALIZE));
+            Err("overwritten before the engine table was caught".to_string())
+        }
+        None if caught       => Ok("not patched (engine table found by scan)".to_string()),
+        None                 => Err("not patched and no engine table".to_string()),
+    }
+}
+
+unsafe fn engine_table() -> Result<String, String> {
+    entities::recheck_slots().map(|()| format!("{:#010x} verified", entities::engine_table()))
+}
+
+unsafe fn extra_info() -> Result<String, String> {
+    let base = entities::extra_info_base();
+    if base == 0 { return Err("not found".to_string()); }
+    if !entities::is_readable(base, Offset::ExtraStride.get() * (player::MAX_CLIENTS as usize + 1)) {
+        return Err(format!("{:#010x} not readable", base));
+    }
+    let api = EngineApi::resolve().ok_or("engine API not resolved")?;
+    player::check_extra_info(&api).map(|()| format!("{:#010x} plausible", base))
+}
@@ -0,0 +1,152 @@
+// session.rs — Who was on the server during a map, written out as JSON at map end.
+//
+// esp.rs samples every player slot about once a second (name, SteamID, team,
+// frags, from the same engine reads the ESP uses) into a `Session`; when the
+// level changes or the client disconnects, the session is written to
+// `esp_session_<map>_<unix time>.json` next to the DLL along with the hook
+// diagnostics of the moment, for building server population reports. With
+// `[chat]` capture on, the chat and server messages of the map are written
+// along with it, and with `[markers]` on, the moments the client struggled
+// (markers.rs).
+//
+// Players are keyed by SteamID, or by name for bots and unknown IDs.
+
+/// One player seen during the session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeenPlayer {
+    pub name:       String,   // Latest name
+    pub steam_id:   u64,      // 0 = bot / unknown
+    pub teams:      Vec<i32>, // Every team they were seen on, in order
+    pub frags:      i32,      // Latest frag count
+    pub first_secs: u64,      // Unix time first and last seen
+    pub last_secs:  u64,
+}
+
+/// Players seen on one map.
+pub struct Session {
+    pub map:        String,   // "maps/de_dust2.bsp"
+    pub start_secs: u64,      // Unix time the map was first seen
+    pub players:    Vec<SeenPlayer>,
+    pub chat:       Vec<(u64, &'static str, String)>, // Unix time, kind ("chat", ...), text
+    pub markers:    Vec<(u64, &'static str, String)>, // Unix time, kind ("fps_drop", ...), detail
+}
+
+const MAX_CHAT: usize = 5000;    // Chat lines kept per map; later ones are dropped
+const MAX_MARKERS: usize = 1000; // Likewise markers
+
+impl Session {
+    pub fn new(map: &str, start_secs: u64) -> Self {
+        Self { map: map.to_string(), start_secs, players: Vec::new(), chat: Vec::new(), markers: Vec::new() }
+    }
+
+    /// Record a chat / server message line.
+    pub fn say(&mut self, secs: u64, kind: &'static str, text: &str) {
+        if self.chat.len() < MAX_CHAT { self.chat.push((secs, kind, text.to_string())); }
+    }
+
+    /// Record a client health marker.
+    pub fn mark(&mut self, secs: u64, kind: &'static str, detail: &str) {
+        if self.markers.len() < MAX_MARKERS { self.markers.push((secs, kind, detail.to_string())); }
+    }
+
+    /// Record a player as seen at `secs`. Team 0 (unassigned) and unknown
+    /// frags don't overwrite what's known.
+    pub fn see(&mut self, name: &str, steam_id: u64, team: i32, frags: Option<i32>, secs: u64) {
+        let i = self.players.iter().position(|p| match steam_id {
+            0 => p.steam_id == 0 && p.name == name,
+            id => p.steam_id == id,
+        });
+        let p = match i {
+            Some(i) => &mut self.players[i],
+            None => {
+                self.players.push(SeenPlayer {
+                    name: String::new(), steam_id, teams: Vec::new(), frags: 0, first_secs: secs, last_secs: secs,
+                });
+                self.players.last_mut().unwrap()
+            }
+        };
+        p.name = name.to_string();
+        if team != 0 && p.teams.last() != Some(&team) { p.teams.push(team); }
+        if let Some(f) = frags { p.frags = f; }
+        p.last_secs = secs;
+    }
+
+    /// The session as a JSON document, ended at `end_secs`, with
+    /// `diagnostics` (one line each) attached.
+    pub fn to_json(&self, end_secs: u64, diagnostics: &[String]) -> String {
+        let mut out = String::from("{\n");
+        out.push_str(&format!("  \"map\": {},\n", json_str(&self.map)));
+        out.push_str(&format!("  \"start\": {},\n  \"end\": {},\n", self.start_secs, end_secs));
+        out.push_str("  \"players\": [");
+        for (i, p) in self.players.iter().enumerate() {
+            let teams: Vec<String> = p.teams.iter().map(|t| t.to_string()).collect();
+            let steam = if p.steam_id == 0 { "null".to_string() } else { json_str(&p.steam_id.to_string()) };
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            out.push_str(&format!(
+                "    {{\"name\": {}, \"steam_id\": {}, \"teams\": [{}], \"frags\": {}, \"first_seen\": {}, \"last_seen\": {}}}",
+                json_str(&p.name), steam, teams.join(", "), p.frags, p.first_secs, p.last_secs));
+        }
+        out.push_str(if self.players.is_empty() { "],\n" } else { "\n  ],\n" });
+        timed_json(&mut out, "chat", "text", &self.chat);
+        timed_json(&mut out, "markers", "detail", &self.markers);
+        let diag: Vec<String> = diagnostics.iter().map(|d| json_str(d)).collect();
+        out.push_str(&format!("  \"diagnostics\": [{}]\n}}\n", diag.join(", ")));
+        out
+    }
+}
+
+/// `"name": [...],` with an object per (time, kind, `field`) entry.
+fn timed_json(out: &mut String, name: &str, field: &str, entries: &[(u64, &'static str, String)]) {
+    out.push_str(&format!("  \"{}\": [", name));
+    for (i, (secs, kind, text)) in entries.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        out.push_str(&format!("    {{\"time\": {}, \"kind\": {}, \"{}\": {}}}", secs, json_str(kind), field, json_str(text)));
+    }
+    out.push_str(if entries.is_empty() { "],\n" } else { "\n  ],\n" });
+}
+
+/// A JSON string literal. SteamIDs are written as strings too, since they
+/// don't fit in a double.
+pub fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_players_and_writes_json() {
+        let mut s = Session::new("maps/de_dust2.bsp", 100);
+        s.see("bob", 76561197960287930, 1, Some(2), 101);
+        s.see("bob (2)", 76561197960287930, 2, None, 130); // Renamed, switched team
+        s.see("Bot \"Al\"", 0, 0, Some(5), 140);
+        s.say(141, "chat", "bob (2): gg");
+        s.mark(142, "fps_drop", "25 fps (under 30)");
+        assert_eq!(s.players.len(), 2);
+        assert_eq!(s.players[0].teams, vec![1, 2]);
+        assert_eq!((s.players[0].frags, s.players[0].last_secs), (2, 130));
+        assert_eq!(s.to_json(150, &["hook: ok".to_string()]),
+            "{\n  \"map\": \"maps/de_dust2.bsp\",\n  \"start\": 100,\n  \"end\": 150,\n  \"players\": [\n\
+             \x20   {\"name\": \"bob (2)\", \"steam_id\": \"76561197960287930\", \"teams\": [1, 2], \"frags\": 2, \"first_seen\": 101, \"last_seen\": 130},\n\
+             \x20   {\"name\": \"Bot \\\"Al\\\"\", \"steam_id\": null, \"teams\": [], \"frags\": 5, \"first_seen\": 140, \"last_seen\": 140}\n\
+             \x20 ],\n  \"chat\": [\n\
+             \x20   {\"time\": 141, \"kind\": \"chat\", \"text\": \"bob (2): gg\"}\n\
+             \x20 ],\n  \"markers\": [\n\
+             \x20   {\"time\": 142, \"kind\": \"fps_drop\", \"detail\": \"25 fps (under 30)\"}\n\
+             \x20 ],\n  \"diagnostics\": [\"hook: ok\"]\n}\n");
+        assert!(Session::new("m", 0).to_json(0, &[]).contains("\"players\": [],\n  \"chat\": [],\n  \"markers\": [],\n  \"diagnostics\": []"));
+    }
+}
@@ -0,0 +1,200 @@
+// slots.rs — Server population as a strip of player slots.
+//
+// The `[slots]` panel draws one cell per slot index: filled for a connected
+// player, in their team's color (gray when the slot is connected but no
+// player data could be read for it, which is the slot to look at when a
+// player gets no box), hollow for a spectator, and a dim outline for an
+// empty slot. Every eighth index is numbered below the strip. esp.rs fills
+// in a Slot per index from GetPlayerInfo and the latest player reads.
+//
+// A second row shows how the last read of each slot went (entities.rs
+// records a ReadOutcome per slot in read_player), one color per reason, so
+// "no players" comes with why: every slot empty, all of them stale, the
+// entity table not resolving, ...
+
+use crate::draw::{Cmd, DrawList, LineStyle};
+use crate::theme::{self, Shade};
+
+const CELL_GAP: f32 = 1.0;      // Pixels between cells
+const NUMBER_EVERY: usize = 8;  // Number every this many slots
+
+/// What's in one player slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    Empty,
+    Spectator,
+    Player(Option<i32>), // Team, None = connected but nothing read
+}
+
+/// How the last read of a slot went, in the order read_player checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadOutcome {
+    Read,      // A player came back
+    Empty,     // No player in the slot (or not read yet)
+    Spectator,
+    NoEntity,  // GetEntityByIndex gave nothing, a non-player, or another slot's entity
+    NoOrigin,  // No usable origin in the history or the entity state
+    Insane,    // Failed the sanity checks
+    Stale,     // Position history stopped moving
+    Dead,      // Left out while dead (outside demos)
+}
+
+impl ReadOutcome {
+    pub const ALL: [ReadOutcome; 8] = [ReadOutcome::Read, ReadOutcome::Empty, ReadOutcome::Spectator,
+                                       ReadOutcome::NoEntity, ReadOutcome::NoOrigin, ReadOutcome::Insane,
+                                       ReadOutcome::Stale, ReadOutcome::Dead];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadOutcome::Read      => "read",
+            ReadOutcome::Empty     => "empty",
+            ReadOutcome::Spectator => "spectator",
+            ReadOutcome::NoEntity  => "no entity",
+            ReadOutcome::NoOrigin  => "no origin",
+            ReadOutcome::Insane    => "insane",
+            ReadOutcome::Stale     => "stale",
+            ReadOutcome::Dead      => "dead",
+        }
+    }
+
+    /// Its cell color in the read row.
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            ReadOutcome::Read      => [0.20, 0.85, 0.20, 1.0],
+            ReadOutcome::Empty     => [0.25, 0.25, 0.25, 1.0],
+            ReadOutcome::Spectator => [0.60, 0.60, 0.60, 1.0],
+            ReadOutcome::NoEntity  => [1.00, 0.55, 0.10, 1.0],
+            ReadOutcome::NoOrigin  => [1.00, 0.90, 0.15, 1.0],
+            ReadOutcome::Insane    => [0.95, 0.20, 0.85, 1.0],
+            ReadOutcome::Stale     => [0.45, 0.45, 1.00, 1.0],
+            ReadOutcome::Dead      => [0.65, 0.10, 0.10, 1.0],
+        }
+    }
+}
+
+/// "31 empty, 1 stale": the slots that gave no player, per reason.
+pub fn missing(reads: &[ReadOutcome]) -> String {
+    ReadOutcome::ALL.iter().skip(1)
+        .map(|&r| (r, reads.iter().filter(|&&o| o == r).count()))
+        .filter(|&(_, n)| n > 0)
+        .map(|(r, n)| format!("{} {}", n, r.name()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A line per reason seen in `reads` (not empty or read): the reason and its
+/// slots, in the reason's color.
+pub fn legend(reads: &[ReadOutcome]) -> Vec<(String, [f32; 4])> {
+    ReadOutcome::ALL.iter().skip(2).filter_map(|&r| {
+        let slots: Vec<String> = reads.iter().enumerate().filter(|&(_, &o)| o == r).map(|(i, _)| (i + 1).to_string()).collect();
+        (!slots.is_empty()).then(|| (format!("{}: slot {}", r.name(), slots.join(", ")), r.color()))
+    }).collect()
+}
+
+/// Colors of the strip.
+pub struct SlotColors {
+    pub team:   [[f32; 4]; 3], // Palette::team
+    pub unread: [f32; 4],      // Connected, no data
+    pub hollow: [f32; 4],      // Spectator outline
+    pub empty:  [f32; 4],      // Empty slot outline
+    pub text:   [f32; 4],      // Slot numbers
+}
+
+/// "12 players, 2 spectators, 18 empty", and the slots with no data.
+pub fn summary(slots: &[Slot]) -> Vec<String> {
+    let count = |f: fn(&Slot) -> bool| slots.iter().filter(|s| f(s)).count();
+    let mut lines = vec![format!("{} players, {} spectators, {} empty",
+                                 count(|s| matches!(s, Slot::Player(_))),
+                                 count(|s| *s == Slot::Spectator),
+                                 count(|s| *s == Slot::Empty))];
+    let unread: Vec<String> = slots.iter().enumerate()
+        .filter(|(_, s)| **s == Slot::Player(None))
+        .map(|(i, _)| (i + 1).to_string())
+        .collect();
+    if !unread.is_empty() { lines.push(format!("no data: slot {}", unread.join(", "))); }
+    lines
+}
+
+/// The strip for `slots` (index 1 first) in `area`, in rows of `row_h`
+/// pixels: the occupancy cells, the read outcome cells (with `reads`), then
+/// the slot numbers.
+pub fn strip(slots: &[Slot], reads: Option<&[ReadOutcome]>, area: [f32; 4], row_h: f32, colors: &SlotColors) -> DrawList {
+    let mut list = DrawList::new();
+    let [x0, y0, x1, _] = area;
+    if slots.is_empty() { return list; }
+    let w = (x1 - x0) / slots.len() as f32;
+    let style = LineStyle { width: 1.0, smooth: false };
+    let cell = |i: usize, row: usize| {
+        let (x, y) = (x0 + i as f32 * w, y0 + row as f32 * row_h);
+        [x, y, x + (w - CELL_GAP).max(1.0), y + row_h - CELL_GAP]
+    };
+    for (i, slot) in slots.iter().enumerate() {
+        let rect = cell(i, 0);
+        match *slot {
+            Slot::Player(Some(team)) => list.push(Cmd::Fill { rect, color: colors.team[theme::team_index(team)] }),
+            Slot::Player(None)       => list.push(Cmd::Fill { rect, color: colors.unread }),
+            Slot::Spectator          => list.push(Cmd::Rect { rect, shade: Shade::Solid(colors.hollow), style }),
+            Slot::Empty              => list.push(Cmd::Rect { rect, shade: Shade::Solid(colors.empty), style }),
+        }
+    }
+    let rows = match reads {
+        Some(reads) => {
+            for (i, r) in reads.iter().take(slots.len()).enumerate() {
+                list.push(Cmd::Fill { rect: cell(i, 1), color: r.color() });
+            }
+            2
+        }
+        None => 1,
+    };
+    for i in (0..slots.len()).step_by(NUMBER_EVERY) {
+        list.text([x0 + i as f32 * w, y0 + rows as f32 * row_h], (i + 1).to_string(), colors.text, 1.0);
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: SlotColors = SlotColors {
+        team: [[0.0, 1.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]],
+        unread: [0.5; 4], hollow: [1.0; 4], empty: [0.3; 4], text: [1.0; 4],
+    };
+
+    #[test]
+    fn counts_slots_and_lists_the_unread_ones() {
+        let slots = [Slot::Player(Some(1)), Slot::Empty, Slot::Player(None), Slot::Spectator, Slot::Player(None)];
+        assert_eq!(summary(&slots), ["3 players, 1 spectators, 1 empty", "no data: slot 3, 5"]);
+        assert_eq!(summary(&[Slot::Empty; 2]), ["0 players, 0 spectators, 2 empty"]);
+    }
+
+    #[test]
+    fn draws_a_cell_per_slot() {
+        let mut slots = [Slot::Empty; 16];
+        slots[0] = Slot::Player(Some(2));
+        slots[9] = Slot::Player(None);
+        slots[10] = Slot::Spectator;
+        let list = strip(&slots, None, [0.0, 0.0, 160.0, 24.0], 12.0, &COLORS);
+        let cmds = list.cmds();
+        assert_eq!(cmds[0], Cmd::Fill { rect: [0.0, 0.0, 9.0, 11.0], color: COLORS.team[2] });
+        assert!(matches!(&cmds[16], Cmd::Text { at: [0.0, 12.0], text, .. } if text == "1"));
+        let cells: Vec<&Cmd> = cmds.iter().filter(|c| !matches!(c, Cmd::Text { .. })).collect();
+        assert_eq!(cells.len(), 16);
+        assert_eq!(*cells[9], Cmd::Fill { rect: [90.0, 0.0, 99.0, 11.0], color: COLORS.unread });
+        assert!(matches!(cells[10], Cmd::Rect { shade: Shade::Solid(c), .. } if *c == COLORS.hollow));
+        assert_eq!(cmds.iter().filter(|c| matches!(c, Cmd::Text { .. })).count(), 2); // Slots 1 and 9
+    }
+
+    #[test]
+    fn read_row_and_reasons() {
+        let reads = [ReadOutcome::Read, ReadOutcome::Stale, ReadOutcome::Empty, ReadOutcome::Stale, ReadOutcome::Insane];
+        assert_eq!(missing(&reads), "1 empty, 1 insane, 2 stale");
+        assert_eq!(missing(&[ReadOutcome::Read]), "");
+        assert_eq!(legend(&reads), [("insane: slot 5".to_string(), ReadOutcome::Insane.color()),
+                                    ("stale: slot 2, 4".to_string(), ReadOutcome::Stale.color())]);
+
+        let list = strip(&[Slot::Empty; 5], Some(&reads), [0.0, 0.0, 50.0, 36.0], 12.0, &COLORS);
+        assert_eq!(list.cmds()[6], Cmd::Fill { rect: [10.0, 12.0, 19.0, 23.0], color: ReadOutcome::Stale.color() });
+        assert!(matches!(&list.cmds()[10], Cmd::Text { at: [0.0, 24.0], .. })); // Numbers below both rows
+    }
+}
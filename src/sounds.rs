@@ -0,0 +1,77 @@
+// sounds.rs — Which engine sounds are worth showing, and how a ring around
+// one grows and fades.
+//
+// events.rs sees every sound the client plays, by sample name ("player/
+// pl_step3.wav") and channel. Only two kinds are kept:
+//
+//   Footstep  player/pl_* movement sounds (steps on every surface, ladders,
+//             wading, swimming)
+//   Gunfire   weapons/* on the weapon channel, minus the ones that aren't
+//             shots (knife swings, dry fire, scope zoom)
+//
+// Sample names may carry the engine's prefixes: `!` (sentence) and `*`
+// (streamed), which are ignored.
+
+/// Engine channel used for weapon sounds (CHAN_WEAPON).
+pub const CHAN_WEAPON: i32 = 1;
+
+/// A sound the overlay shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundKind { Footstep, Gunfire }
+
+/// Footstep samples: "player/pl_" followed by one of these.
+const FOOTSTEP_STEMS: [&str; 11] = [
+    "step", "dirt", "duct", "grate", "metal", "slosh", "snow", "tile", "ladder", "wade", "swim",
+];
+
+/// weapons/ samples on the weapon channel that aren't shots.
+const NOT_SHOTS: [&str; 5] = ["knife", "dryfire", "zoom", "clipin", "clipout"];
+
+/// Classify a sound by sample name and channel. None = not shown.
+pub fn classify(sample: &str, channel: i32) -> Option<SoundKind> {
+    let name = sample.trim_start_matches(['!', '*']).to_ascii_lowercase().replace('\\', "/");
+    if let Some(rest) = name.strip_prefix("player/pl_") {
+        return FOOTSTEP_STEMS.iter().any(|s| rest.starts_with(s)).then_some(SoundKind::Footstep);
+    }
+    if let Some(rest) = name.strip_prefix("weapons/") {
+        if channel != CHAN_WEAPON || NOT_SHOTS.iter().any(|s| rest.contains(s)) { return None; }
+        return Some(SoundKind::Gunfire);
+    }
+    None
+}
+
+/// Radius and alpha of a ring `age_ms` after its sound, or None once it's
+/// gone. The ring grows fast then slows (ease-out) to `max_radius`, fading
+/// linearly over `ttl_ms`.
+pub fn ring(age_ms: u32, ttl_ms: u32, max_radius: f32) -> Option<(f32, f32)> {
+    if ttl_ms == 0 || age_ms >= ttl_ms { return None; }
+    let t = age_ms as f32 / ttl_ms as f32;
+    let grow = 1.0 - (1.0 - t) * (1.0 - t);
+    Some((max_radius * grow, 1.0 - t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_samples() {
+        assert_eq!(classify("player/pl_step3.wav", 4), Some(SoundKind::Footstep));
+        assert_eq!(classify("player/PL_LADDER1.WAV", 0), Some(SoundKind::Footstep));
+        assert_eq!(classify("*weapons/ak47-1.wav", CHAN_WEAPON), Some(SoundKind::Gunfire));
+        assert_eq!(classify("weapons/ak47-1.wav", 2), None);             // Not the weapon channel
+        assert_eq!(classify("weapons/knife_hit1.wav", CHAN_WEAPON), None);
+        assert_eq!(classify("player/pl_pain2.wav", 4), None);            // Not movement
+        assert_eq!(classify("!HG_ALERT1", 0), None);
+    }
+
+    #[test]
+    fn ring_grows_and_fades() {
+        let (r0, a0) = ring(0, 1000, 64.0).unwrap();
+        let (r1, a1) = ring(500, 1000, 64.0).unwrap();
+        assert_eq!((r0, a0), (0.0, 1.0));
+        assert_eq!((r1, a1), (48.0, 0.5)); // Three quarters grown at half time
+        assert!(ring(1000, 1000, 64.0).is_none());
+        assert!(ring(0, 0, 64.0).is_none());
+    }
+}
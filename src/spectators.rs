@@ -0,0 +1,147 @@
+// spectators.rs — Who is spectating, and who is watching the local player.
+//
+// GetPlayerInfo flags the players on the spectator team; dead players
+// spectate too, without the flag. Either way the server sends the observer
+// mode and target in their entity_state_t (iuser1 / iuser2), as long as their
+// entity is in the update at all (its messagenum is the newest). From those
+// the `[spectators]` panel lists everyone spectating and marks the ones whose
+// camera follows the local player (chase, first person, or chasing on the
+// overview). A free camera has no target; a spectator whose entity isn't in
+// the update is listed without one.
+
+use crate::names;
+use crate::offsets::Offset;
+use crate::player::{EngineReader, ES_IUSER1, ES_IUSER2, MAX_CLIENTS, OBS_IN_EYE};
+use crate::world::ES_MESSAGENUM;
+
+// Observer modes (iuser1), from the HL SDK
+const OBS_CHASE_LOCKED: i32 = 1;
+const OBS_CHASE_FREE:   i32 = 2;
+const OBS_ROAMING:      i32 = 3;
+const OBS_MAP_FREE:     i32 = 5;
+const OBS_MAP_CHASE:    i32 = 6;
+
+/// What a spectator's camera is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    You,
+    Player(i32),
+    Free,     // Roaming or the free overview: nobody in particular
+    Unknown,  // Their entity isn't in the update
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectator {
+    pub slot:   i32,
+    pub name:   String,
+    pub mode:   i32,   // iuser1 (0 when unknown)
+    pub target: Target,
+}
+
+/// "first person", "chase", ... for an observer mode.
+pub fn mode_name(mode: i32) -> &'static str {
+    match mode {
+        OBS_CHASE_LOCKED => "chase (locked)",
+        OBS_CHASE_FREE   => "chase",
+        OBS_ROAMING      => "free look",
+        OBS_IN_EYE       => "first person",
+        OBS_MAP_FREE     => "overview",
+        OBS_MAP_CHASE    => "overview (chase)",
+        _                => "spectating",
+    }
+}
+
+/// Whether mode `mode` follows its target (iuser2 means something).
+fn follows(mode: i32) -> bool {
+    matches!(mode, OBS_CHASE_LOCKED | OBS_CHASE_FREE | OBS_IN_EYE | OBS_MAP_CHASE)
+}
+
+/// Every player slot spectating, the local player (`local`) excluded.
+/// `newest` is the newest update's message number (EngineApi::latest_message;
+/// 0 = unknown, every entity is taken as current).
+pub fn read(r: &impl EngineReader, local: i32, newest: i32) -> Vec<Spectator> {
+    let mut out = Vec::new();
+    for slot in 1..=MAX_CLIENTS {
+        if slot == local { continue; }
+        let Some(info) = r.player_info(slot) else { continue };
+        let Some(name) = info.name.map(|n| names::display(&n)) else { continue };
+        let base = r.entity(slot);
+        let cs = base + Offset::Curstate.get();
+        let current = base != 0 && (newest == 0 || r.read_i32(cs + ES_MESSAGENUM) >= newest);
+        let mode = if current { r.read_i32(cs + ES_IUSER1) } else { 0 };
+        if mode == 0 && !info.spectator { continue; }
+
+        let target = match r.read_i32(cs + ES_IUSER2) {
+            _ if !current || mode == 0 => Target::Unknown,
+            _ if !follows(mode) => Target::Free,
+            t if t == local => Target::You,
+            t if (1..=MAX_CLIENTS).contains(&t) => Target::Player(t),
+            _ => Target::Free,
+        };
+        out.push(Spectator { slot, name, mode, target });
+    }
+    out
+}
+
+/// The panel's lines: a title with how many watch you, then one per
+/// spectator (those watching you first) and whether it's you they watch.
+pub fn lines(specs: &[Spectator], name_of: impl Fn(i32) -> Option<String>) -> Vec<(String, bool)> {
+    let watching = specs.iter().filter(|s| s.target == Target::You).count();
+    let mut out = vec![(format!("SPECTATORS  {} watching you", watching), false)];
+    let mut sorted: Vec<&Spectator> = specs.iter().collect();
+    sorted.sort_by_key(|s| (s.target != Target::You, s.slot));
+    for s in sorted {
+        let on = match s.target {
+            Target::You       => "you".to_string(),
+            Target::Player(t) => name_of(t).unwrap_or_else(|| format!("P{}", t)),
+            Target::Free      => "-".to_string(),
+            Target::Unknown   => "?".to_string(),
+        };
+        out.push((format!("{:<16} {:<16} {}", s.name, mode_name(s.mode), on), s.target == Target::You));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockEngine;
+    use crate::player::{SlotInfo, CURSTATE_OFFSET};
+
+    fn spectate(e: &mut MockEngine, idx: i32, mode: i32, target: i32, msg: i32) {
+        let base = 0x10000 * idx as usize;
+        e.add_player(idx, base, &format!("p{}", idx), [0.0; 3]);
+        e.write_i32(base + CURSTATE_OFFSET + ES_IUSER1, mode);
+        e.write_i32(base + CURSTATE_OFFSET + ES_IUSER2, target);
+        e.write_i32(base + CURSTATE_OFFSET + ES_MESSAGENUM, msg);
+    }
+
+    #[test]
+    fn finds_who_watches_whom() {
+        let mut e = MockEngine::default();
+        spectate(&mut e, 1, 0, 0, 100);                 // Local, alive
+        spectate(&mut e, 2, OBS_IN_EYE, 1, 100);        // Watching us
+        spectate(&mut e, 3, OBS_CHASE_FREE, 5, 100);    // Someone else
+        spectate(&mut e, 4, OBS_ROAMING, 1, 100);       // Free look: iuser2 means nothing
+        spectate(&mut e, 5, 0, 0, 100);                 // Playing
+        spectate(&mut e, 6, OBS_IN_EYE, 1, 90);         // Not in the update
+        e.infos.insert(6, SlotInfo { name: Some("p6".into()), spectator: true, ..Default::default() });
+        spectate(&mut e, 7, OBS_IN_EYE, 1, 90);         // Not in the update, not flagged: left out
+
+        let specs = read(&e, 1, 100);
+        let targets: Vec<(i32, Target)> = specs.iter().map(|s| (s.slot, s.target)).collect();
+        assert_eq!(targets, [(2, Target::You), (3, Target::Player(5)), (4, Target::Free), (6, Target::Unknown)]);
+    }
+
+    #[test]
+    fn lists_your_watchers_first() {
+        let specs = [
+            Spectator { slot: 3, name: "bob".into(), mode: OBS_CHASE_FREE, target: Target::Player(5) },
+            Spectator { slot: 8, name: "ann".into(), mode: OBS_IN_EYE, target: Target::You },
+        ];
+        let lines = lines(&specs, |t| (t == 5).then(|| "eve".to_string()));
+        assert_eq!(lines[0], ("SPECTATORS  1 watching you".to_string(), false));
+        assert!(lines[1].0.starts_with("ann") && lines[1].0.ends_with("you") && lines[1].1);
+        assert!(lines[2].0.contains("chase") && lines[2].0.ends_with("eve") && !lines[2].1);
+    }
+}
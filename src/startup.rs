@@ -0,0 +1,164 @@
+// startup.rs — One-shot environment report in the log (`env:` lines).
+//
+// Written once by the worker thread after the hooks are in, so a log
+// attached to a report already says: Windows version, game executable and
+// its version, the overlay's version and build features, the renderer
+// module and where opengl32.dll came from, client.dll's size and CRC-32,
+// other overlays or hook DLLs loaded in the game (environment.rs), the mod,
+// the config profile and any structure offsets that differ from the build
+// 4554 defaults.
+
+use crate::config;
+use crate::entities;
+use crate::environment;
+use crate::offsets;
+use std::ffi::CStr;
+use std::ptr;
+use winapi::shared::minwindef::{DWORD, HMODULE};
+use winapi::um::libloaderapi::{GetModuleFileNameA, GetModuleHandleA, GetProcAddress};
+use winapi::um::processthreadsapi::GetCurrentProcess;
+use winapi::um::psapi::EnumProcessModules;
+use winapi::um::sysinfoapi::GetSystemDirectoryA;
+use winapi::um::winnt::OSVERSIONINFOW;
+use winapi::um::winver::{GetFileVersionInfoA, GetFileVersionInfoSizeA, VerQueryValueA};
+use winapi::um::wow64apiset::GetSystemWow64DirectoryA;
+
+/// Most modules listed from the process.
+const MAX_MODULES: usize = 1024;
+
+/// VS_FIXEDFILEINFO, up to the file version (winapi doesn't declare it).
+#[repr(C)]
+struct FixedFileInfo {
+    _signature:     DWORD,
+    _version:       DWORD,
+    version_ms:     DWORD,
+    version_ls:     DWORD,
+}
+
+/// Log the environment report.
+pub unsafe fn report() {
+    entities::logf(format!("env: os {}", os_version()));
+
+    let exe = module_path(ptr::null_mut()).unwrap_or_else(|| "?".to_string());
+    let version = file_version(&exe).unwrap_or_else(|| "no version info".to_string());
+    entities::logf(format!("env: game {} ({})", exe, version));
+    entities::logf(format!("env: overlay {} with {}", env!("CARGO_PKG_VERSION"), features()));
+
+    let modules = loaded_modules();
+    let renderer: Vec<&str> = ["hw.dll", "sw.dll"].into_iter()
+        .filter(|r| modules.iter().any(|m| file_name(m).eq_ignore_ascii_case(r)))
+        .collect();
+    entities::logf(format!("env: renderer {}", if renderer.is_empty() { "not loaded".to_string() } else { renderer.join(", ") }));
+    match modules.iter().find(|m| file_name(m).eq_ignore_ascii_case("opengl32.dll")) {
+        Some(gl) if system_dirs().iter().all(|d| environment::is_wrapper(gl, d)) =>
+            entities::logf(format!("env: opengl32.dll {} (not the system one: a wrapper)", gl)),
+        Some(gl) => entities::logf(format!("env: opengl32.dll {}", gl)),
+        None     => entities::log("env: opengl32.dll not loaded"),
+    }
+
+    let client = modules.iter().find(|m| file_name(m).eq_ignore_ascii_case("client.dll"));
+    match client.map(|c| (c, std::fs::read(c))) {
+        Some((c, Ok(bytes))) => entities::logf(format!("env: client.dll {} ({} bytes, crc32 {:08x})",
+                                                       c, bytes.len(), environment::crc32(&bytes))),
+        Some((c, Err(e)))    => entities::logf(format!("env: client.dll {} (can't read: {})", c, e)),
+        None                 => entities::log("env: client.dll not loaded"),
+    }
+
+    let hooks: Vec<String> = modules.iter()
+        .filter_map(|m| environment::known_hook(m).map(|what| format!("{} ({})", what, file_name(m))))
+        .collect();
+    entities::logf(format!("env: other overlays {}", if hooks.is_empty() { "none".to_string() } else { hooks.join(", ") }));
+
+    let game_mod = entities::game_mod();
+    let cfg = config::get();
+    let profile = if cfg.profile.is_empty() { "none" } else { cfg.profile.as_str() };
+    entities::logf(format!("env: mod {}, config profile {}", game_mod.name(), profile));
+
+    let changed: Vec<String> = offsets::current().into_iter()
+        .filter(|&(o, v)| v != o.default_value())
+        .map(|(o, v)| format!("{} = {:#x}", o.name(), v))
+        .collect();
+    if changed.is_empty() {
+        entities::log("env: offsets build 4554 defaults");
+    } else {
+        entities::logf(format!("env: offsets {} changed from build 4554: {}", changed.len(), changed.join(", ")));
+    }
+}
+
+/// The optional parts this DLL was built with (Cargo features).
+fn features() -> String {
+    let on: Vec<&str> = [
+        ("menu", cfg!(feature = "menu")),
+        ("radar", cfg!(feature = "radar")),
+        ("ipc", cfg!(feature = "ipc")),
+        ("scripting", cfg!(feature = "scripting")),
+    ].into_iter().filter(|&(_, built)| built).map(|(name, _)| name).collect();
+    if on.is_empty() { "core only".to_string() } else { on.join(", ") }
+}
+
+/// "Windows 10 (10.0.19045)" from ntdll!RtlGetVersion.
+unsafe fn os_version() -> String {
+    type FnRtlGetVersion = unsafe extern "system" fn(info: *mut OSVERSIONINFOW) -> i32;
+    let ntdll = GetModuleHandleA(c"ntdll.dll".as_ptr());
+    let f = if ntdll.is_null() { ptr::null_mut() } else { GetProcAddress(ntdll, c"RtlGetVersion".as_ptr()) };
+    if f.is_null() { return "unknown".to_string(); }
+    let get: FnRtlGetVersion = std::mem::transmute(f);
+    let mut info: OSVERSIONINFOW = std::mem::zeroed();
+    info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOW>() as u32;
+    if get(&mut info) != 0 { return "unknown".to_string(); }
+    environment::os_name(info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber)
+}
+
+/// The file version resource of the file at `path`.
+unsafe fn file_version(path: &str) -> Option<String> {
+    let c = std::ffi::CString::new(path).ok()?;
+    let size = GetFileVersionInfoSizeA(c.as_ptr(), ptr::null_mut());
+    if size == 0 { return None; }
+    let mut data = vec![0u8; size as usize];
+    if GetFileVersionInfoA(c.as_ptr(), 0, size, data.as_mut_ptr() as _) == 0 { return None; }
+    let mut fixed = ptr::null_mut();
+    let mut len = 0u32;
+    if VerQueryValueA(data.as_ptr() as _, c"\\".as_ptr(), &mut fixed, &mut len) == 0
+        || fixed.is_null() || (len as usize) < std::mem::size_of::<FixedFileInfo>() {
+        return None;
+    }
+    let info = &*(fixed as *const FixedFileInfo);
+    Some(environment::file_version(info.version_ms, info.version_ls))
+}
+
+/// Full paths of every module loaded in the game.
+unsafe fn loaded_modules() -> Vec<String> {
+    let mut handles: Vec<HMODULE> = vec![ptr::null_mut(); MAX_MODULES];
+    let mut needed = 0u32;
+    let bytes = (handles.len() * std::mem::size_of::<HMODULE>()) as u32;
+    if EnumProcessModules(GetCurrentProcess(), handles.as_mut_ptr(), bytes, &mut needed) == 0 { return Vec::new(); }
+    handles.truncate((needed as usize / std::mem::size_of::<HMODULE>()).min(MAX_MODULES));
+    handles.into_iter().filter_map(|h| module_path(h)).collect()
+}
+
+/// Path of a loaded module (null = the executable).
+unsafe fn module_path(h: HMODULE) -> Option<String> {
+    let mut buf = [0u8; 512];
+    let len = GetModuleFileNameA(h, buf.as_mut_ptr() as _, buf.len() as u32) as usize;
+    if len == 0 || len >= buf.len() { return None; }
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+/// The directories system DLLs load from in a 32-bit process (SysWOW64 on
+/// 64-bit Windows, System32 on 32-bit).
+unsafe fn system_dirs() -> Vec<String> {
+    type FnSystemDir = unsafe extern "system" fn(buf: *mut i8, size: u32) -> u32;
+    let mut out = Vec::new();
+    for get in [GetSystemWow64DirectoryA as FnSystemDir, GetSystemDirectoryA] {
+        let mut buf = [0i8; 260];
+        let len = get(buf.as_mut_ptr(), buf.len() as u32) as usize;
+        if len > 0 && len < buf.len() {
+            out.push(CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned());
+        }
+    }
+    out
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
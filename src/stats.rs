@@ -0,0 +1,204 @@
+// stats.rs — Per-session match statistics: kills, deaths, rounds, damage, time alive.
+//
+// Fed once per frame from esp.rs, by player name (slots get reused across a
+// session, names mostly don't):
+//
+//   - Kills and headshots come from DeathMsg (usermsg.rs) when the user
+//     message hook is installed; without it, deaths are taken from the
+//     alive→dead transitions entities.rs already tracks, and no kills are known.
+//   - Rounds are counted between RoundTime (start) and TeamScore (end).
+//   - A damage event is a drop in a player's health as the client sees it.
+//   - Time alive is summed over frames in which the player is listed alive.
+//
+// The session table is written out as CSV at map end; the last round's table
+// is what the end-of-round summary panel shows.
+
+/// A frame gap longer than this (paused, alt-tabbed, loading) isn't counted as
+/// time alive.
+pub const MAX_FRAME_GAP_MS: u32 = 250;
+
+/// Fields of CS's DeathMsg: BYTE killer, BYTE victim, BYTE headshot, STRING weapon.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeathMsg {
+    pub killer:   i32,    // Player slot (0 = the world)
+    pub victim:   i32,
+    pub headshot: bool,
+    pub weapon:   String, // "ak47", "grenade", "world"
+}
+
+/// Decode a DeathMsg payload. None if it's too short to be one.
+pub fn parse_death_msg(buf: &[u8]) -> Option<DeathMsg> {
+    let [killer, victim, headshot, rest @ ..] = buf else { return None };
+    let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    Some(DeathMsg {
+        killer: *killer as i32,
+        victim: *victim as i32,
+        headshot: *headshot != 0,
+        weapon: String::from_utf8_lossy(&rest[..end]).into_owned(),
+    })
+}
+
+/// One player's numbers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub kills:     u32,
+    pub deaths:    u32,
+    pub headshots: u32,
+    pub damage:    u32, // Damage events (health drops), not hit points
+    pub alive_ms:  u64,
+    pub rounds:    u32, // Rounds the player was seen in
+}
+
+/// Tallies by player name, in the order players were first seen.
+type Table = Vec<(String, Tally)>;
+
+fn entry<'a>(table: &'a mut Table, name: &str) -> &'a mut Tally {
+    let i = match table.iter().position(|(n, _)| n == name) {
+        Some(i) => i,
+        None => { table.push((name.to_string(), Tally::default())); table.len() - 1 }
+    };
+    &mut table[i].1
+}
+
+/// The session's statistics.
+pub struct Stats {
+    session:    Table,
+    round:      Table,       // Since the current round started
+    last_round: Table,       // The round that ended last (the summary)
+    rounds:     u32,         // Rounds ended this session
+    ended_ms:   u32,         // When the last round ended (0 = none yet)
+    last_frame: u32,         // Previous frame() call (0 = none)
+}
+
+impl Stats {
+    pub const fn new() -> Self {
+        Self {
+            session: Vec::new(),
+            round: Vec::new(),
+            last_round: Vec::new(),
+            rounds: 0,
+            ended_ms: 0,
+            last_frame: 0,
+        }
+    }
+
+    fn add(&mut self, name: &str, f: impl Fn(&mut Tally)) {
+        f(entry(&mut self.session, name));
+        f(entry(&mut self.round, name));
+    }
+
+    /// A kill (`killer` None = killed by the world or themselves).
+    pub fn kill(&mut self, killer: Option<&str>, victim: &str, headshot: bool) {
+        if let Some(k) = killer.filter(|&k| k != victim) {
+            self.add(k, |t| { t.kills += 1; t.headshots += headshot as u32; });
+        }
+        self.death(victim);
+    }
+
+    /// A death with no killer known.
+    pub fn death(&mut self, victim: &str) {
+        self.add(victim, |t| t.deaths += 1);
+    }
+
+    /// A drop in `name`'s health.
+    pub fn damage(&mut self, name: &str) {
+        self.add(name, |t| t.damage += 1);
+    }
+
+    /// A frame at `now` in which `alive` are listed alive.
+    pub fn frame(&mut self, now: u32, alive: &[String]) {
+        let gap = if self.last_frame == 0 { 0 } else { now.wrapping_sub(self.last_frame) };
+        self.last_frame = now;
+        if gap == 0 || gap > MAX_FRAME_GAP_MS { return; }
+        for name in alive {
+            self.add(name, |t| t.alive_ms += gap as u64);
+        }
+    }
+
+    /// A round started: what happened before it belongs to no round.
+    pub fn round_start(&mut self) {
+        self.round.clear();
+    }
+
+    /// The round ended at `now`; its table becomes the summary.
+    pub fn round_end(&mut self, now: u32) {
+        if self.round.is_empty() { return; } // Scores sent on connect, or a second TeamScore
+        self.rounds += 1;
+        self.ended_ms = now.max(1);
+        for (name, _) in &self.round {
+            entry(&mut self.session, name).rounds += 1;
+        }
+        self.last_round = std::mem::take(&mut self.round);
+    }
+
+    /// Rounds ended this session and when the last one did (0 = none).
+    pub fn last_round_end(&self) -> (u32, u32) {
+        (self.rounds, self.ended_ms)
+    }
+
+    /// The last round's table, most kills first.
+    pub fn summary(&self) -> Vec<(String, Tally)> {
+        let mut rows = self.last_round.clone();
+        rows.sort_by(|a, b| b.1.kills.cmp(&a.1.kills).then(a.1.deaths.cmp(&b.1.deaths)));
+        rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.session.is_empty()
+    }
+
+    /// The session table as CSV, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("name,kills,deaths,headshots,damage_events,seconds_alive,rounds\n");
+        for (name, t) in &self.session {
+            out.push_str(&format!("{},{},{},{},{},{:.1},{}\n",
+                csv_field(name), t.kills, t.deaths, t.headshots, t.damage, t.alive_ms as f64 / 1000.0, t.rounds));
+        }
+        out
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_death_messages() {
+        assert_eq!(parse_death_msg(b"\x03\x07\x01ak47\0"), Some(DeathMsg {
+            killer: 3, victim: 7, headshot: true, weapon: "ak47".to_string(),
+        }));
+        assert_eq!(parse_death_msg(b"\x00\x02\x00").map(|d| d.weapon), Some(String::new()));
+        assert_eq!(parse_death_msg(b"\x01\x02"), None);
+    }
+
+    #[test]
+    fn tallies_rounds_and_exports_csv() {
+        let mut s = Stats::new();
+        let names = |n: &[&str]| n.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        s.round_start();
+        s.frame(1000, &names(&["a", "b"]));
+        s.frame(1100, &names(&["a", "b"]));
+        s.frame(5000, &names(&["a"]));           // Too long a gap to count
+        s.damage("b");
+        s.kill(Some("a"), "b", true);
+        s.kill(Some("a,\"x\""), "a,\"x\"", false); // Suicide: a death, no kill
+        s.round_end(6000);
+        s.round_end(6100);                        // Nothing since: not a round
+        assert_eq!(s.last_round_end(), (1, 6000));
+        assert_eq!(s.summary()[0].0, "a");
+        assert_eq!(s.to_csv(),
+            "name,kills,deaths,headshots,damage_events,seconds_alive,rounds\n\
+             a,1,0,1,0,0.1,1\n\
+             b,0,1,0,1,0.1,1\n\
+             \"a,\"\"x\"\"\",0,1,0,0,0.0,1\n");
+    }
+}
@@ -0,0 +1,179 @@
+// subsystems.rs — The optional subsystems in the runtime registry (registry.rs).
+//
+// Registered by the worker thread at startup: the minimap and the live feed
+// (`radar` builds), native plugins, and the control pipe (`ipc` builds).
+// Switching one in the menu wakes the worker, which calls `reconcile` to
+// start or shut it down, and `shutdown_all` at eject. esp.rs runs
+// the frame callbacks once the boxes are drawn, each under its own panic
+// guard, with what it collected for them this frame in a FrameCtx.
+
+use crate::config::{self, Config};
+use crate::entities::{self, EngineApi};
+#[cfg(feature = "radar")]
+use crate::feed::{self, FeedPlayer};
+#[cfg(feature = "ipc")]
+use crate::ipc;
+#[cfg(feature = "radar")]
+use crate::markers;
+use crate::layout::Layout;
+use crate::math::Vec3;
+#[cfg(feature = "radar")]
+use crate::minimap::{self, Blip};
+use crate::panics::Subsystem;
+use crate::player::EngineReader;
+use crate::plugin::{self, EspPlayer};
+use crate::registry::{Module, Registry, State};
+#[cfg(feature = "radar")]
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use winapi::shared::windef::HDC;
+
+#[cfg(feature = "radar")] pub const MINIMAP: &str = "minimap";
+#[cfg(feature = "radar")] pub const FEED: &str = "live feed";
+pub const PLUGINS: &str = "plugins";
+#[cfg(feature = "ipc")] pub const PIPE: &str = "control pipe";
+
+/// What a frame callback gets: the frame's drawing state and the per-player
+/// data esp.rs collected for the subsystems.
+pub struct FrameCtx<'a> {
+    pub hdc:     HDC,
+    pub cfg:     &'a Config,
+    pub api:     &'a EngineApi,
+    pub now:     u32,
+    pub map:     Option<&'a str>,
+    pub screen:  [f32; 2],
+    pub eye:     Vec3,           // Viewer position and yaw (zero / last known without a viewer)
+    pub yaw:     f32,
+    pub has_viewer: bool,
+    pub layout:  &'a mut Layout,
+    pub status:  Vec<String>,    // Lines for the status block
+    #[cfg(feature = "radar")]
+    pub frozen:  bool,           // The ESP shows a recorded frame (scrub.rs); nothing is published
+    #[cfg(feature = "radar")]
+    pub blips:   &'a [Blip],
+    #[cfg(feature = "radar")]
+    pub feed_players: &'a [FeedPlayer],
+    pub plugin_players: &'a [EspPlayer],
+}
+
+impl FrameCtx<'_> {
+    fn viewer(&self) -> Option<(Vec3, f32)> {
+        self.has_viewer.then_some((self.eye, self.yaw))
+    }
+}
+
+/// A frame callback and the panic guard it runs under.
+pub type FrameFn = (Subsystem, unsafe fn(&mut FrameCtx));
+
+static REGISTRY: Mutex<Registry<FrameFn>> = Mutex::new(Registry::new());
+
+// ============================================================
+// Lifecycle (worker thread)
+// ============================================================
+
+/// Register every subsystem in this build. They start at the first `reconcile`.
+pub fn register() {
+    let Ok(mut reg) = REGISTRY.lock() else { return };
+    #[cfg(feature = "radar")]
+    {
+        reg.register(Module { name: MINIMAP, init: None, frame: Some((Subsystem::Minimap, minimap_frame)), shutdown: None });
+        reg.register(Module { name: FEED, init: None, frame: Some((Subsystem::Feed, feed_frame)), shutdown: Some(feed::stop) });
+    }
+    reg.register(Module { name: PLUGINS, init: Some(plugins_init), frame: Some((Subsystem::Plugins, plugins_frame)),
+                          shutdown: Some(plugins_shutdown) });
+    #[cfg(feature = "ipc")]
+    reg.register(Module { name: PIPE, init: Some(pipe_init), frame: None, shutdown: Some(ipc::stop) });
+}
+
+/// Start and stop subsystems to match what's wanted.
+pub fn reconcile() {
+    let calls = match REGISTRY.lock() {
+        Ok(mut reg) => reg.reconcile(),
+        Err(_) => return,
+    };
+    for (i, call) in calls {
+        let result = call.run();
+        if let Err(e) = &result { entities::logf(format!("subsystem failed to start: {}", e)); }
+        if let Ok(mut reg) = REGISTRY.lock() { reg.finished(i, result); }
+    }
+}
+
+/// Stop everything (eject; the render hook is already removed).
+pub fn shutdown_all() {
+    if let Ok(mut reg) = REGISTRY.lock() { reg.disable_all(); }
+    reconcile();
+}
+
+/// Switch a subsystem on or off (menu); it's started or stopped by the
+/// worker's `reconcile`, which this wakes it for.
+pub fn set_enabled(name: &str, on: bool) {
+    let known = REGISTRY.lock().is_ok_and(|mut reg| reg.set_enabled(name, on));
+    if known {
+        entities::logf(format!("subsystem {} switched {}", name, if on { "on" } else { "off" }));
+        crate::wake_worker();
+    }
+}
+
+/// Every subsystem: name, whether it's wanted on, and its state (menu, diagnostics).
+pub fn list() -> Vec<(&'static str, bool, State)> {
+    REGISTRY.lock().map(|reg| reg.list()).unwrap_or_default()
+}
+
+/// Whether a subsystem is running (esp.rs skips collecting data for one that isn't).
+pub fn running(name: &str) -> bool {
+    REGISTRY.lock().is_ok_and(|reg| reg.running(name))
+}
+
+/// Frame callbacks of the running subsystems.
+pub fn frames() -> Vec<FrameFn> {
+    REGISTRY.lock().map(|reg| reg.frames().into_iter().map(|(_, f)| f).collect()).unwrap_or_default()
+}
+
+// ============================================================
+// Callbacks
+// ============================================================
+
+fn plugins_init() -> Result<(), String> {
+    unsafe { plugin::load(&config::get()); }
+    Ok(())
+}
+
+fn plugins_shutdown() {
+    unsafe { plugin::unload(); }
+}
+
+unsafe fn plugins_frame(ctx: &mut FrameCtx) {
+    if !plugin::active(ctx.cfg) { return; }
+    plugin::frame(ctx.hdc, ctx.cfg, ctx.now, ctx.map, ctx.screen, ctx.viewer(), ctx.plugin_players);
+}
+
+#[cfg(feature = "ipc")]
+fn pipe_init() -> Result<(), String> {
+    ipc::start(&config::get());
+    Ok(())
+}
+
+/// The minimap over the boxes (it's an opaque-ish panel).
+#[cfg(feature = "radar")]
+unsafe fn minimap_frame(ctx: &mut FrameCtx) {
+    if !minimap::enabled(ctx.cfg) { return; }
+    let size = ctx.cfg.minimap_size;
+    let [x0, y0, _, _] = ctx.layout.place(ctx.cfg.minimap_place, size, size);
+    minimap::draw(ctx.cfg, ctx.api, [x0, y0], ctx.eye, ctx.yaw, ctx.blips);
+}
+
+#[cfg(feature = "radar")]
+static FEED_MS: AtomicU32 = AtomicU32::new(0); // Last snapshot published
+
+/// Publish a snapshot to the feed server, `[feed] rate_hz` times a second.
+#[cfg(feature = "radar")]
+unsafe fn feed_frame(ctx: &mut FrameCtx) {
+    if !ctx.cfg.feed_enabled || ctx.frozen { return; }
+    if let Some(e) = feed::error() { ctx.status.push(format!("feed: {}", e)); }
+    let interval = (1000.0 / ctx.cfg.feed_rate_hz) as u32;
+    if ctx.now.wrapping_sub(FEED_MS.load(Ordering::Relaxed)) < interval { return; }
+    FEED_MS.store(ctx.now, Ordering::Relaxed);
+    let snapshot = feed::snapshot_json(ctx.now, ctx.map, ctx.api.world_bounds().as_ref(), ctx.viewer(), ctx.feed_players,
+                                       &markers::take_for_feed());
+    feed::publish(&ctx.cfg.feed_bind, ctx.cfg.feed_port, snapshot);
+}
@@ -0,0 +1,104 @@
+// teams.rs — Team names from the TeamInfo / TeamNames user messages.
+//
+// Team numbers 1 and 2 only mean T and CT in Counter-Strike; TFC, DoD and the
+// team modes of other mods have their own teams. The server names them:
+//
+//   TeamInfo   BYTE player slot, STRING team name ("TERRORIST", "CT", "Blue", "Allies", ...)
+//   TeamNames  BYTE count, then that many STRING team names, in team number order
+//
+// usermsg.rs feeds both into a TeamTable, and esp.rs looks up each player's
+// team name for `[team_colors]` entries keyed by name, so boxes can be colored
+// by team in any mod. Entries are kept until the server sends new ones: it
+// resends a player's TeamInfo whenever they change teams, and to every client
+// that joins.
+
+const MAX_SLOT: usize = 32;
+
+/// Decode a TeamInfo payload: (player slot, team name).
+pub fn parse_team_info(buf: &[u8]) -> Option<(usize, String)> {
+    let [slot, rest @ ..] = buf else { return None };
+    let slot = *slot as usize;
+    if slot == 0 || slot > MAX_SLOT { return None; }
+    Some((slot, c_string(rest).0))
+}
+
+/// Decode a TeamNames payload: the team names, team 1 first.
+pub fn parse_team_names(buf: &[u8]) -> Vec<String> {
+    let [count, rest @ ..] = buf else { return Vec::new() };
+    let mut rest = rest;
+    let mut names = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        if rest.is_empty() { break; }
+        let (name, used) = c_string(rest);
+        names.push(name);
+        rest = &rest[used..];
+    }
+    names
+}
+
+/// A NUL-terminated string at the start of `buf`, and the bytes it took.
+fn c_string(buf: &[u8]) -> (String, usize) {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    (String::from_utf8_lossy(&buf[..end]).into_owned(), (end + 1).min(buf.len()))
+}
+
+/// Team names as the server last sent them.
+#[derive(Default)]
+pub struct TeamTable {
+    players: Vec<String>, // By slot ("" = not sent)
+    teams:   Vec<String>, // By team number - 1 (TeamNames)
+}
+
+impl TeamTable {
+    pub const fn new() -> Self {
+        Self { players: Vec::new(), teams: Vec::new() }
+    }
+
+    /// A TeamInfo message: `slot` is on `team` now.
+    pub fn set_player(&mut self, slot: usize, team: String) {
+        if self.players.len() <= slot { self.players.resize(slot + 1, String::new()); }
+        self.players[slot] = team;
+    }
+
+    /// A TeamNames message.
+    pub fn set_teams(&mut self, names: Vec<String>) {
+        self.teams = names;
+    }
+
+    /// The team name of the player in `slot`: from TeamInfo, else the name
+    /// TeamNames gave their team number.
+    pub fn name(&self, slot: usize, team: i32) -> Option<&str> {
+        let own = self.players.get(slot).filter(|n| !n.is_empty());
+        let by_number = || usize::try_from(team - 1).ok().and_then(|i| self.teams.get(i)).filter(|n| !n.is_empty());
+        own.or_else(by_number).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_team_messages() {
+        assert_eq!(parse_team_info(b"\x05TERRORIST\0"), Some((5, "TERRORIST".to_string())));
+        assert_eq!(parse_team_info(b"\x03Blue"), Some((3, "Blue".to_string())));
+        assert_eq!(parse_team_info(b"\x00CT\0"), None);
+        assert_eq!(parse_team_info(b""), None);
+        assert_eq!(parse_team_names(b"\x02Allies\0Axis\0"), ["Allies", "Axis"]);
+        assert_eq!(parse_team_names(b"\x04Blue\0Red\0"), ["Blue", "Red"]); // Truncated
+        assert!(parse_team_names(b"").is_empty());
+    }
+
+    #[test]
+    fn player_team_wins_over_team_number() {
+        let mut t = TeamTable::new();
+        assert_eq!(t.name(3, 1), None);
+        t.set_teams(vec!["Blue".into(), "Red".into()]);
+        assert_eq!(t.name(3, 2), Some("Red"));
+        assert_eq!(t.name(3, 0), None);
+        t.set_player(3, "Yellow".into());
+        assert_eq!(t.name(3, 2), Some("Yellow"));
+        t.set_player(3, String::new());
+        assert_eq!(t.name(3, 1), Some("Blue"));
+    }
+}
@@ -0,0 +1,152 @@
+// tga.rs — Reading and writing .tga images (icons in, screenshots out).
+//
+// Icons (a logo, weapon icons, the minimap background) are loaded from files
+// next to the DLL and uploaded as GL textures by render.rs. TGA is the format
+// GoldSrc mods already ship their HUD art and skies in, and it's simple enough
+// to decode without a dependency:
+//
+//   type 2 / 10   true color, raw / run-length encoded, 24 or 32 bits (BGR[A])
+//   type 3 / 11   grayscale, raw / run-length encoded, 8 bits
+//
+// Color-mapped images are rejected. Pixels are returned as RGBA, top row
+// first, whatever the file's origin. The file comes from disk, so every read is
+// bounds-checked and a bad file yields an error rather than a panic.
+//
+// The overlay's own screenshots (capture.rs) are written the other way, as
+// uncompressed 24-bit files straight from glReadPixels' bottom-up BGR rows.
+
+const HEADER_SIZE: usize = 18;
+
+/// Largest side accepted (pixels); anything bigger isn't an icon.
+const MAX_SIDE: usize = 2048;
+
+/// A decoded image: `width * height` RGBA pixels, top row first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width:  u32,
+    pub height: u32,
+    pub rgba:   Vec<u8>,
+}
+
+/// Decode a .tga file; the error says why it can't be used.
+pub fn decode(data: &[u8]) -> Result<Image, &'static str> {
+    if data.len() < HEADER_SIZE { return Err("truncated header"); }
+    let id_len = data[0] as usize;
+    let image_type = data[2];
+    let width = u16::from_le_bytes([data[12], data[13]]) as usize;
+    let height = u16::from_le_bytes([data[14], data[15]]) as usize;
+    let bits = data[16];
+    let top_down = data[17] & 0x20 != 0;
+
+    if data[1] != 0 { return Err("color-mapped images are not supported"); }
+    let (gray, rle) = match image_type {
+        2  => (false, false),
+        3  => (true,  false),
+        10 => (false, true),
+        11 => (true,  true),
+        _ => return Err("unsupported image type"),
+    };
+    let bpp = match (gray, bits) {
+        (true, 8) => 1,
+        (false, 24) => 3,
+        (false, 32) => 4,
+        _ => return Err("unsupported pixel depth"),
+    };
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err("bad image size");
+    }
+
+    let pixels = width * height;
+    let mut src = data.get(HEADER_SIZE + id_len..).ok_or("truncated header")?;
+    let mut raw = Vec::with_capacity(pixels * bpp);
+    if rle {
+        while raw.len() < pixels * bpp {
+            let (&packet, rest) = src.split_first().ok_or("truncated pixel data")?;
+            let count = (packet & 0x7F) as usize + 1;
+            let take = if packet & 0x80 != 0 { bpp } else { count * bpp };
+            let chunk = rest.get(..take).ok_or("truncated pixel data")?;
+            if packet & 0x80 != 0 {
+                for _ in 0..count { raw.extend_from_slice(chunk); }
+            } else {
+                raw.extend_from_slice(chunk);
+            }
+            src = &rest[take..];
+        }
+        raw.truncate(pixels * bpp);
+    } else {
+        raw.extend_from_slice(src.get(..pixels * bpp).ok_or("truncated pixel data")?);
+    }
+
+    let mut rgba = Vec::with_capacity(pixels * 4);
+    for row in 0..height {
+        let y = if top_down { row } else { height - 1 - row };
+        for p in raw[y * width * bpp..(y + 1) * width * bpp].chunks_exact(bpp) {
+            match bpp {
+                1 => rgba.extend_from_slice(&[p[0], p[0], p[0], 255]),
+                3 => rgba.extend_from_slice(&[p[2], p[1], p[0], 255]),
+                _ => rgba.extend_from_slice(&[p[2], p[1], p[0], p[3]]),
+            }
+        }
+    }
+    Ok(Image { width: width as u32, height: height as u32, rgba })
+}
+
+/// Encode `width * height` BGR pixels, bottom row first (glReadPixels with
+/// GL_BGR_EXT), as an uncompressed 24-bit .tga.
+pub fn encode_bgr(width: u16, height: u16, bgr: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; HEADER_SIZE];
+    out[2] = 2;
+    out[12..14].copy_from_slice(&width.to_le_bytes());
+    out[14..16].copy_from_slice(&height.to_le_bytes());
+    out[16] = 24;
+    out.extend_from_slice(bgr);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(image_type: u8, w: u16, h: u16, bits: u8, descriptor: u8) -> Vec<u8> {
+        let mut v = vec![0u8; HEADER_SIZE];
+        v[2] = image_type;
+        v[12..14].copy_from_slice(&w.to_le_bytes());
+        v[14..16].copy_from_slice(&h.to_le_bytes());
+        v[16] = bits;
+        v[17] = descriptor;
+        v
+    }
+
+    #[test]
+    fn decodes_raw_bottom_up_bgr() {
+        let mut f = header(2, 2, 2, 24, 0);
+        f.extend_from_slice(&[0, 0, 255,  0, 255, 0]);  // Bottom row: red, green
+        f.extend_from_slice(&[255, 0, 0,  9, 9, 9]);    // Top row: blue, gray
+        let img = decode(&f).unwrap();
+        assert_eq!((img.width, img.height), (2, 2));
+        assert_eq!(img.rgba, [
+            0, 0, 255, 255,  9, 9, 9, 255,
+            255, 0, 0, 255,  0, 255, 0, 255,
+        ]);
+    }
+
+    #[test]
+    fn decodes_rle_and_rejects_bad_files() {
+        let mut f = header(10, 3, 1, 32, 0x28);          // Top-down, 8 alpha bits
+        f.extend_from_slice(&[0x81, 1, 2, 3, 4]);        // Run of 2
+        f.extend_from_slice(&[0x00, 5, 6, 7, 8]);        // 1 raw pixel
+        assert_eq!(decode(&f).unwrap().rgba, [3, 2, 1, 4,  3, 2, 1, 4,  7, 6, 5, 8]);
+
+        assert!(decode(&f[..f.len() - 1]).is_err());     // Truncated
+        assert!(decode(&header(1, 1, 1, 8, 0)).is_err()); // Color-mapped type
+        assert!(decode(&header(2, 0, 1, 24, 0)).is_err());
+        assert!(decode(&[0; 4]).is_err());
+    }
+
+    #[test]
+    fn encoded_frames_decode() {
+        let bgr = [0, 0, 255,  255, 0, 0]; // One bottom-up row: red, blue
+        let img = decode(&encode_bgr(2, 1, &bgr)).unwrap();
+        assert_eq!(img.rgba, [255, 0, 0, 255,  0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,270 @@
+// theme.rs — Named color palettes for the overlay.
+//
+// Every color esp.rs draws with comes from one Palette: box colors per team,
+// the snap-line defaults, the colors of boxes kept from earlier frames, and the
+// label / status text. The active palette is picked by `theme` in the config
+// (or from the menu) and switches immediately.
+//
+//   default        red T / blue CT, yellow snap-lines (the original look)
+//   deuteranopia   orange T / sky-blue CT (Okabe-Ito), safe for red-green color blindness
+//   high_contrast  saturated colors at full alpha, yellow status text
+//
+// Team indices follow the engine: 1 = Terrorists, 2 = Counter-Terrorists,
+// anything else = unknown/other.
+//
+// Health brackets (low / mid / high, split at HEALTH_MID and HEALTH_HIGH) have
+// their colors here too, for coloring boxes or badges by health.
+//
+// With `color_mode = "relation"` boxes are colored by relation to the local
+// player instead (enemy / friend), falling back to team colors while the local
+// team isn't known (not joined, or g_PlayerExtraInfo not found).
+
+/// Lowest health in the mid and high brackets.
+pub const HEALTH_MID:  i32 = 35;
+pub const HEALTH_HIGH: i32 = 70;
+
+/// A selectable palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme { Default, Deuteranopia, HighContrast }
+
+/// How box colors are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    Team,     // Absolute team colors (T / CT)
+    Relation, // Enemy / friend, relative to the local player's team
+}
+
+/// All colors of one theme. Team tables are indexed [other, T, CT].
+pub struct Palette {
+    pub team:     [[f32; 4]; 3], // Box corner brackets
+    pub snapline: [[f32; 4]; 3], // Snap-lines (unless overridden in [snapline])
+    pub cached:   [[f32; 4]; 3], // Boxes kept from an earlier frame (alpha is replaced by the fade)
+    pub enemy:    [f32; 4],      // Relation mode: other team
+    pub friend:   [f32; 4],      // Relation mode: local player's team
+    pub text:     [f32; 4],      // Name / distance labels, death-marker names
+    pub status:   [f32; 4],      // Status line, FOV indicator, warnings
+    pub health:   [[f32; 4]; 3], // Health brackets: low, mid, high
+}
+
+const DEFAULT: Palette = Palette {
+    team:     [[0.10, 0.95, 0.10, 1.0], [0.95, 0.18, 0.18, 1.0], [0.18, 0.50, 0.95, 1.0]],
+    snapline: [[1.0, 1.0, 0.15, 0.55]; 3],
+    cached:   [[0.10, 0.95, 0.10, 1.0], [0.95, 0.18, 0.18, 1.0], [0.18, 0.50, 0.95, 1.0]],
+    enemy:    [0.95, 0.18, 0.18, 1.0],
+    friend:   [0.10, 0.95, 0.10, 1.0],
+    text:     [1.0, 1.0, 1.0, 1.0],
+    status:   [1.0, 0.15, 0.15, 1.0],
+    health:   [[0.95, 0.18, 0.18, 1.0], [0.95, 0.85, 0.15, 1.0], [0.10, 0.95, 0.10, 1.0]],
+};
+
+const DEUTERANOPIA: Palette = Palette {
+    team:     [[0.94, 0.89, 0.26, 1.0], [0.90, 0.62, 0.00, 1.0], [0.34, 0.71, 0.91, 1.0]],
+    snapline: [[0.80, 0.47, 0.65, 0.60]; 3],
+    cached:   [[0.94, 0.89, 0.26, 1.0], [0.90, 0.62, 0.00, 1.0], [0.34, 0.71, 0.91, 1.0]],
+    enemy:    [0.90, 0.62, 0.00, 1.0],
+    friend:   [0.34, 0.71, 0.91, 1.0],
+    text:     [1.0, 1.0, 1.0, 1.0],
+    status:   [0.90, 0.62, 0.00, 1.0],
+    health:   [[0.84, 0.37, 0.00, 1.0], [0.94, 0.89, 0.26, 1.0], [0.34, 0.71, 0.91, 1.0]],
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    team:     [[1.0, 1.0, 1.0, 1.0], [1.0, 0.10, 0.10, 1.0], [0.20, 0.60, 1.0, 1.0]],
+    snapline: [[1.0, 1.0, 1.0, 0.85]; 3],
+    cached:   [[0.70, 0.70, 0.70, 1.0]; 3],
+    enemy:    [1.0, 0.10, 0.10, 1.0],
+    friend:   [0.10, 1.0, 0.10, 1.0],
+    text:     [1.0, 1.0, 1.0, 1.0],
+    status:   [1.0, 1.0, 0.0, 1.0],
+    health:   [[1.0, 0.10, 0.10, 1.0], [1.0, 1.0, 0.0, 1.0], [0.10, 1.0, 0.10, 1.0]],
+};
+
+impl Theme {
+    /// Every theme, in menu order.
+    pub const ALL: [Theme; 3] = [Theme::Default, Theme::Deuteranopia, Theme::HighContrast];
+
+    /// Config / menu name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Default      => "default",
+            Theme::Deuteranopia => "deuteranopia",
+            Theme::HighContrast => "high_contrast",
+        }
+    }
+
+    /// Theme for a config name.
+    pub fn from_name(name: &str) -> Option<Theme> {
+        Self::ALL.iter().copied().find(|t| t.name() == name)
+    }
+
+    /// The theme's colors.
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Default      => &DEFAULT,
+            Theme::Deuteranopia => &DEUTERANOPIA,
+            Theme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+}
+
+/// Whether `team` is on the local player's team. None when either team isn't
+/// T or CT (unknown, unassigned, spectator).
+pub fn is_friendly(team: i32, local_team: i32) -> Option<bool> {
+    let playing = |t: i32| t == 1 || t == 2;
+    if playing(team) && playing(local_team) { Some(team == local_team) } else { None }
+}
+
+/// Mix `b` into `a` by `t` (0 = a, 1 = b), keeping `a`'s alpha.
+pub fn blend(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |i: usize| a[i] + (b[i] - a[i]) * t;
+    [mix(0), mix(1), mix(2), a[3]]
+}
+
+/// How an outline's color runs from its top edge to its bottom edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shade {
+    Solid([f32; 4]),
+    Gradient([f32; 4], [f32; 4]), // Top, bottom: interpolated in between
+    TwoTone([f32; 4], [f32; 4]),  // Top half, bottom half
+}
+
+impl Shade {
+    /// Color at height `t` (0 = top edge, 1 = bottom edge).
+    pub fn at(&self, t: f32) -> [f32; 4] {
+        match *self {
+            Shade::Solid(c) => c,
+            Shade::Gradient(top, bottom) => {
+                let c = blend(top, bottom, t);
+                [c[0], c[1], c[2], top[3] + (bottom[3] - top[3]) * t.clamp(0.0, 1.0)]
+            }
+            Shade::TwoTone(top, bottom) => if t < 0.5 { top } else { bottom },
+        }
+    }
+}
+
+impl From<[f32; 4]> for Shade {
+    fn from(c: [f32; 4]) -> Self { Shade::Solid(c) }
+}
+
+/// Index into a team table (1 = T, 2 = CT, anything else = other).
+pub fn team_index(team: i32) -> usize {
+    match team {
+        1 | 2 => team as usize,
+        _ => 0,
+    }
+}
+
+impl Palette {
+    /// Box color for a team.
+    pub fn team_color(&self, team: i32) -> [f32; 4] {
+        self.team[team_index(team)]
+    }
+
+    /// Default snap-line color for a team.
+    pub fn snapline_color(&self, team: i32) -> [f32; 4] {
+        self.snapline[team_index(team)]
+    }
+
+    /// Color of a cached (not currently visible) box for a team.
+    pub fn cached_color(&self, team: i32) -> [f32; 4] {
+        self.cached[team_index(team)]
+    }
+
+    /// Color of the bracket `health` falls in.
+    pub fn health_color(&self, health: i32) -> [f32; 4] {
+        match health {
+            h if h >= HEALTH_HIGH => self.health[2],
+            h if h >= HEALTH_MID  => self.health[1],
+            _ => self.health[0],
+        }
+    }
+
+    /// Enemy/friend color in relation mode, if the relation is known.
+    fn relation_color(&self, mode: ColorMode, team: i32, local_team: i32) -> Option<[f32; 4]> {
+        if mode != ColorMode::Relation { return None; }
+        is_friendly(team, local_team).map(|f| if f { self.friend } else { self.enemy })
+    }
+
+    /// Box color for a player on `team` (`local_team` 0 = unknown).
+    pub fn player_color(&self, mode: ColorMode, team: i32, local_team: i32) -> [f32; 4] {
+        self.relation_color(mode, team, local_team).unwrap_or_else(|| self.team_color(team))
+    }
+
+    /// Cached-box color for a player on `team` (`local_team` 0 = unknown).
+    pub fn cached_player_color(&self, mode: ColorMode, team: i32, local_team: i32) -> [f32; 4] {
+        self.relation_color(mode, team, local_team).unwrap_or_else(|| self.cached_color(team))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for t in Theme::ALL {
+            assert_eq!(Theme::from_name(t.name()), Some(t));
+        }
+        assert_eq!(Theme::from_name("Default"), None);
+    }
+
+    #[test]
+    fn team_lookup() {
+        let p = Theme::Default.palette();
+        assert_eq!(p.team_color(1), [0.95, 0.18, 0.18, 1.0]);
+        assert_eq!(p.team_color(2), [0.18, 0.50, 0.95, 1.0]);
+        assert_eq!(p.team_color(0), p.team_color(3));
+        assert_eq!(p.team_color(-1), p.team[0]);
+    }
+
+    #[test]
+    fn relation_mode() {
+        let p = Theme::Default.palette();
+        assert_eq!(p.player_color(ColorMode::Relation, 1, 1), p.friend);
+        assert_eq!(p.player_color(ColorMode::Relation, 2, 1), p.enemy);
+        // Local team unknown or a spectator: fall back to team colors
+        assert_eq!(p.player_color(ColorMode::Relation, 2, 0), p.team_color(2));
+        assert_eq!(p.player_color(ColorMode::Relation, 2, 3), p.team_color(2));
+        assert_eq!(p.player_color(ColorMode::Team, 1, 1), p.team_color(1));
+        assert_eq!(p.cached_player_color(ColorMode::Relation, 2, 0), p.cached_color(2));
+    }
+
+    #[test]
+    fn health_brackets() {
+        let p = Theme::Default.palette();
+        assert_eq!(p.health_color(100), p.health[2]);
+        assert_eq!(p.health_color(HEALTH_HIGH), p.health[2]);
+        assert_eq!(p.health_color(HEALTH_HIGH - 1), p.health[1]);
+        assert_eq!(p.health_color(HEALTH_MID), p.health[1]);
+        assert_eq!(p.health_color(1), p.health[0]);
+    }
+
+    #[test]
+    fn blend_keeps_alpha() {
+        let a = [1.0, 0.0, 0.0, 0.5];
+        let b = [0.0, 0.0, 1.0, 1.0];
+        assert_eq!(blend(a, b, 0.0), a);
+        assert_eq!(blend(a, b, 0.5), [0.5, 0.0, 0.5, 0.5]);
+        assert_eq!(blend(a, b, 3.0), [0.0, 0.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn shades_top_to_bottom() {
+        let (top, bottom) = ([1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 0.5]);
+        assert_eq!(Shade::Solid(top).at(0.9), top);
+        assert_eq!(Shade::Gradient(top, bottom).at(0.0), top);
+        assert_eq!(Shade::Gradient(top, bottom).at(0.5), [0.5, 0.5, 0.0, 0.75]);
+        assert_eq!(Shade::Gradient(top, bottom).at(1.0), bottom);
+        assert_eq!(Shade::TwoTone(top, bottom).at(0.49), top);
+        assert_eq!(Shade::TwoTone(top, bottom).at(0.5), bottom);
+    }
+
+    #[test]
+    fn deuteranopia_teams_differ_in_blue() {
+        // Red-green deficiency leaves the blue-yellow axis, so T and CT must
+        // be told apart by their blue channel, not by red vs. green.
+        let p = Theme::Deuteranopia.palette();
+        assert!((p.team_color(1)[2] - p.team_color(2)[2]).abs() > 0.5);
+    }
+}
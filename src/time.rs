@@ -0,0 +1,150 @@
+// time.rs — Shared monotonic clock for fades, cache TTLs, and staleness tracking.
+//
+// Frame counts mean very different things at 60 vs. 250 FPS, so everything that
+// expires or animates is measured in wall-clock milliseconds read from here.
+// The clock is std's Instant, which on Windows is QueryPerformanceCounter:
+// sub-microsecond resolution and no drift against the game's own timing.
+// It starts at DLL attach (`attach`), so its readings are time since attach.
+//
+// On top of it: the time between overlay frames (`begin_frame` /
+// `frame_delta`), stopwatches for timing a piece of work, and Timings, a
+// running average per subsystem of what its stopwatch read.
+
+use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Reference point for all timestamps (set at attach, or on first use).
+static START: OnceCell<Instant> = OnceCell::new();
+
+static FRAME_US: AtomicU64 = AtomicU64::new(0); // Start of the current overlay frame
+static DELTA_US: AtomicU64 = AtomicU64::new(0); // ... and how long after the previous one
+
+const TIMING_WEIGHT: f32 = 0.05; // Weight of the newest reading in a Timings average
+
+/// Start the clock (DLL attach).
+pub fn attach() {
+    START.get_or_init(Instant::now);
+}
+
+/// Time since the clock started.
+pub fn since_attach() -> Duration {
+    START.get_or_init(Instant::now).elapsed()
+}
+
+/// Milliseconds elapsed since the clock was first read.
+/// Never returns 0, so callers can keep using 0 as a "never seen" sentinel.
+/// Wraps after ~49 days — compare timestamps with `wrapping_sub`.
+pub fn now_ms() -> u32 {
+    (since_attach().as_millis() as u32).wrapping_add(1)
+}
+
+/// Microseconds elapsed since the clock was first read, for frame timing.
+/// Never returns 0, like `now_ms`.
+pub fn now_us() -> u64 {
+    since_attach().as_micros() as u64 + 1
+}
+
+/// Mark the start of an overlay frame; returns its time (now_us).
+pub fn begin_frame() -> u64 {
+    let now = now_us();
+    let prev = FRAME_US.swap(now, Ordering::Relaxed);
+    DELTA_US.store(if prev == 0 { 0 } else { now - prev }, Ordering::Relaxed);
+    now
+}
+
+/// Start of the current overlay frame (its begin_frame time, 0 before the
+/// first): tells one frame from the next.
+pub fn frame_start() -> u64 {
+    FRAME_US.load(Ordering::Relaxed)
+}
+
+/// Seconds between the last two overlay frames (0 before the second).
+pub fn frame_delta() -> f32 {
+    DELTA_US.load(Ordering::Relaxed) as f32 / 1_000_000.0
+}
+
+/// Times a piece of work from when it was started.
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    started_us: u64,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Self::started_at(now_us())
+    }
+
+    pub const fn started_at(us: u64) -> Self {
+        Self { started_us: us }
+    }
+
+    pub fn elapsed_us(&self) -> u64 {
+        self.elapsed_us_at(now_us())
+    }
+
+    /// Microseconds from the start to `now_us`.
+    pub fn elapsed_us_at(&self, now_us: u64) -> u64 {
+        now_us.saturating_sub(self.started_us)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed_us())
+    }
+}
+
+/// Running average time (microseconds) per key, e.g. per subsystem.
+pub struct Timings<K> {
+    entries: Vec<(K, f32)>,
+}
+
+impl<K: Copy + PartialEq> Timings<K> {
+    pub const fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Add a reading for `key`; its first one is taken as is.
+    pub fn record(&mut self, key: K, us: f32) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, avg)) => *avg += (us - *avg) * TIMING_WEIGHT,
+            None => self.entries.push((key, us)),
+        }
+    }
+
+    /// The `n` keys with the highest average, highest first.
+    pub fn slowest(&self, n: usize) -> Vec<(K, f32)> {
+        let mut all = self.entries.clone();
+        all.sort_by(|a, b| b.1.total_cmp(&a.1));
+        all.truncate(n);
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopwatch_and_frame_delta() {
+        let sw = Stopwatch::started_at(1_000);
+        assert_eq!(sw.elapsed_us_at(3_500), 2_500);
+        assert_eq!(sw.elapsed_us_at(500), 0);        // Never negative
+
+        let first = begin_frame();
+        let second = begin_frame();
+        assert!(second >= first);
+        assert_eq!(frame_delta(), (second - first) as f32 / 1_000_000.0);
+        assert_eq!(frame_start(), second);
+    }
+
+    #[test]
+    fn averages_per_key_slowest_first() {
+        let mut t = Timings::new();
+        t.record("trails", 100.0);
+        t.record("labels", 300.0);
+        t.record("trails", 200.0);                    // 100 + 5 % of the difference
+        t.record("tracers", 50.0);
+        assert_eq!(t.slowest(2), [("labels", 300.0), ("trails", 105.0)]);
+        assert_eq!(t.slowest(5).len(), 3);
+    }
+}
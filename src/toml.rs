@@ -0,0 +1,239 @@
+// toml.rs — The TOML subset the config file is written in.
+//
+// `[section]` headers, `key = value` lines and `#` comments, with four kinds
+// of value:
+//
+//   booleans        true, false
+//   numbers         12, -0.5, 27_080 (underscores are ignored)
+//   strings         "text" (no escapes; a `#` inside one is not a comment)
+//   arrays          [1.0, 0.2, 0.2, 1.0], ["a", "b"], nested, on one line
+//
+// Keys come back as "section.key" with the line they're on, so config.rs can
+// report unknown keys and bad values by line and skip them. Typed readers
+// (as_color, as_hex, ...) turn a value into what a setting wants.
+
+/// A parsed value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Value::Bool(b) => Some(*b), _ => None }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self { Value::Num(n) => Some(*n as f32), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Value::Str(s) => Some(s.as_str()), _ => None }
+    }
+
+    /// An address or offset: a hex string (`"0x1d8f0"`) or a plain number.
+    pub fn as_hex(&self) -> Option<u32> {
+        match self {
+            Value::Str(s) => u32::from_str_radix(s.trim().trim_start_matches("0x").trim_start_matches("0X"), 16).ok(),
+            Value::Num(n) if *n >= 0.0 && *n <= u32::MAX as f64 => Some(*n as u32),
+            _ => None,
+        }
+    }
+
+    /// An array of strings: `["a", "b"]`.
+    pub fn as_str_list(&self) -> Option<Vec<String>> {
+        let Value::Array(items) = self else { return None };
+        items.iter().map(|i| i.as_str().map(str::to_string)).collect()
+    }
+
+    /// An [x, y] pair of numbers (pixel offsets).
+    pub fn as_pair(&self) -> Option<[f32; 2]> {
+        let Value::Array(items) = self else { return None };
+        match items.as_slice() {
+            [x, y] => Some([x.as_f32()?, y.as_f32()?]),
+            _ => None,
+        }
+    }
+
+    /// An RGBA color: `[r, g, b]` or `[r, g, b, a]` with components in 0..1.
+    pub fn as_color(&self) -> Option<[f32; 4]> {
+        let Value::Array(items) = self else { return None };
+        if items.len() != 3 && items.len() != 4 { return None; }
+        let mut c = [1.0f32; 4];
+        for (i, item) in items.iter().enumerate() {
+            c[i] = item.as_f32()?.clamp(0.0, 1.0);
+        }
+        Some(c)
+    }
+}
+
+/// Parse a file into (line number, "section.key", value) entries.
+/// A value that fails to parse is returned as None so the caller can report it.
+pub fn parse(text: &str) -> Vec<(usize, String, Option<Value>)> {
+    parse_raw(text).into_iter()
+        .map(|(line_no, key, v)| (line_no, key, v.and_then(|v| parse_value(&v))))
+        .collect()
+}
+
+/// Parse a file into (line number, "section.key", value text) entries.
+/// A line without `=` has no value.
+pub fn parse_raw(text: &str) -> Vec<(usize, String, Option<String>)> {
+    let mut out = Vec::new();
+    let mut section = String::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = strip_comment(raw).trim();
+        if line.is_empty() { continue; }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        let Some((k, v)) = line.split_once('=') else {
+            out.push((i + 1, line.to_string(), None));
+            continue;
+        };
+        let key = if section.is_empty() {
+            k.trim().to_string()
+        } else {
+            format!("{}.{}", section, k.trim())
+        };
+        out.push((i + 1, key, Some(v.trim().to_string())));
+    }
+    out
+}
+
+/// Remove a trailing `# comment` (ignoring `#` inside quoted strings).
+pub fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse a single value: bool, number, "string", or [array].
+pub fn parse_value(s: &str) -> Option<Value> {
+    if s == "true" { return Some(Value::Bool(true)); }
+    if s == "false" { return Some(Value::Bool(false)); }
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        return Some(Value::Str(s[1..s.len() - 1].to_string()));
+    }
+    if s.starts_with('[') && s.ends_with(']') {
+        let inner = s[1..s.len() - 1].trim();
+        if inner.is_empty() { return Some(Value::Array(Vec::new())); }
+        let items: Option<Vec<Value>> = split_array(inner).into_iter()
+            .map(|item| parse_value(item.trim()))
+            .collect();
+        return items.map(Value::Array);
+    }
+    s.replace('_', "").parse::<f64>().ok().map(Value::Num)
+}
+
+/// Split the inside of an array on top-level commas (not inside strings or nested arrays).
+fn split_array(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_str = false;
+    let mut start = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '[' if !in_str => depth += 1,
+            ']' if !in_str => depth -= 1,
+            ',' if !in_str && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = &s[start..];
+    if !last.trim().is_empty() { parts.push(last); }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Option<Value> {
+        parse(text).into_iter().next().and_then(|(_, _, v)| v)
+    }
+
+    #[test]
+    fn reads_sections_keys_and_comments() {
+        let text = "# header\nenabled = true  # trailing\n\n[esp]\n  max_distance = 3_000\n[profile.aim.esp]\nbox = false\n";
+        let entries: Vec<(usize, String, Option<Value>)> = parse(text);
+        assert_eq!(entries, [
+            (2, "enabled".to_string(), Some(Value::Bool(true))),
+            (5, "esp.max_distance".to_string(), Some(Value::Num(3000.0))),
+            (7, "profile.aim.esp.box".to_string(), Some(Value::Bool(false))),
+        ]);
+        assert_eq!(strip_comment("key = 1 # note"), "key = 1 ");
+        assert_eq!(strip_comment("# all comment"), "");
+    }
+
+    #[test]
+    fn keeps_hashes_inside_strings() {
+        assert_eq!(value("label = \"#1 {name}\" # comment"), Some(Value::Str("#1 {name}".into())));
+        assert_eq!(strip_comment("a = \"x#y\"#z"), "a = \"x#y\"");
+        assert_eq!(value("list = [\"a#b\", \"c\"] # two"), Some(Value::Array(vec![Value::Str("a#b".into()), Value::Str("c".into())])));
+    }
+
+    #[test]
+    fn reads_arrays() {
+        assert_eq!(parse_value("[]"), Some(Value::Array(Vec::new())));
+        assert_eq!(parse_value("[1, [2, 3], \"x,y\"]"), Some(Value::Array(vec![
+            Value::Num(1.0),
+            Value::Array(vec![Value::Num(2.0), Value::Num(3.0)]),
+            Value::Str("x,y".into()),
+        ])));
+        assert_eq!(parse_value("[1, 2,]"), Some(Value::Array(vec![Value::Num(1.0), Value::Num(2.0)])));
+        assert_eq!(parse_value("[\"a\", \"b\"]").and_then(|v| v.as_str_list()), Some(vec!["a".into(), "b".into()]));
+        assert_eq!(parse_value("[12, -4]").and_then(|v| v.as_pair()), Some([12.0, -4.0]));
+        assert_eq!(parse_value("[1, 2, 3]").and_then(|v| v.as_pair()), None);
+        assert_eq!(parse_value("[1, oops]"), None);                  // One bad item spoils the array
+    }
+
+    #[test]
+    fn reads_colors() {
+        assert_eq!(parse_value("[1.0, 0.2, 0.2]").and_then(|v| v.as_color()), Some([1.0, 0.2, 0.2, 1.0]));
+        assert_eq!(parse_value("[0, 0, 0, 0.5]").and_then(|v| v.as_color()), Some([0.0, 0.0, 0.0, 0.5]));
+        assert_eq!(parse_value("[2, -1, 0.5, 1]").and_then(|v| v.as_color()), Some([1.0, 0.0, 0.5, 1.0])); // Clamped
+        assert_eq!(parse_value("[1, 1]").and_then(|v| v.as_color()), None);
+        assert_eq!(parse_value("[1, 1, \"red\"]").and_then(|v| v.as_color()), None);
+    }
+
+    #[test]
+    fn reads_hex_ints() {
+        assert_eq!(parse_value("\"0x1d8f0\"").and_then(|v| v.as_hex()), Some(0x1D8F0));
+        assert_eq!(parse_value("\"0X1D8F0\"").and_then(|v| v.as_hex()), Some(0x1D8F0));
+        assert_eq!(parse_value("\"7c\"").and_then(|v| v.as_hex()), Some(0x7C));
+        assert_eq!(parse_value("4096").and_then(|v| v.as_hex()), Some(4096));
+        assert_eq!(parse_value("-1").and_then(|v| v.as_hex()), None);
+        assert_eq!(parse_value("\"0xZZ\"").and_then(|v| v.as_hex()), None);
+        assert_eq!(parse_value("true").and_then(|v| v.as_hex()), None);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let entries = parse("[esp]\njust some words\nbox = \nname = \"unterminated\ncolor = [1, 0, 0\n");
+        assert_eq!(entries, [
+            (2, "just some words".to_string(), None),       // No `=`
+            (3, "esp.box".to_string(), None),               // Nothing after it
+            (4, "esp.name".to_string(), None),
+            (5, "esp.color".to_string(), None),
+        ]);
+        assert_eq!(parse_raw("[esp]\nbox = yes # sure")[0], (2, "esp.box".to_string(), Some("yes".to_string())));
+        assert_eq!(parse_value("yes"), None);
+        assert_eq!(parse_value("\""), None);
+    }
+}
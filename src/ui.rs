@@ -0,0 +1,287 @@
+// ui.rs — A small immediate-mode widget layer over render.rs.
+//
+// A panel describes itself every frame, top to bottom: a window frame with a
+// title, then rows of labels, checkboxes, sliders, selectors and steppers. Each widget
+// draws itself and handles its input in the same call, and returns whether it
+// changed the value it was given — so the panel keeps its own data and no row
+// positions are worked out by hand.
+//
+// Between frames a window only remembers which row is selected and which
+// slider is being dragged (UiState, owned by the panel). Interactive rows are
+// numbered in the order they're declared, so a panel declares its rows in the
+// same order every frame.
+//
+// Input (Input::poll) comes from the WndProc subclass (input.rs):
+//   - Keyboard: Up/Down move the selection, Left/Right adjust, Enter toggles/cycles
+//   - Mouse: hover selects, click toggles/cycles, drag moves sliders
+// Display-only panels (diagnostics) pass Input::NONE.
+
+#[cfg(feature = "menu")]
+use crate::input;
+use crate::render::{self, LineStyle};
+use winapi::shared::windef::HDC;
+
+#[cfg(feature = "menu")] const VK_RETURN: i32 = 0x0D;
+#[cfg(feature = "menu")] const VK_LEFT:   i32 = 0x25;
+#[cfg(feature = "menu")] const VK_UP:     i32 = 0x26;
+#[cfg(feature = "menu")] const VK_RIGHT:  i32 = 0x27;
+#[cfg(feature = "menu")] const VK_DOWN:   i32 = 0x28;
+
+/// Metrics and colors of a window.
+pub struct Style {
+    pub row_h:    f32,             // Height of one row (the title is a row too)
+    pub pad:      f32,             // Inner padding
+    #[cfg_attr(not(feature = "menu"), allow(dead_code))]
+    pub slider_w: f32,             // Width of a slider track
+    pub bg:       [f32; 4],
+    pub border:   Option<[f32; 4]>,
+    pub hover:    [f32; 4],        // Selected row highlight
+    pub text:     [f32; 4],
+    pub accent:   [f32; 4],        // Title, ON, selector values, filled track
+    #[cfg_attr(not(feature = "menu"), allow(dead_code))]
+    pub track:    [f32; 4],        // OFF, empty track
+}
+
+/// One frame's input for a window.
+#[derive(Clone, Copy)]
+pub struct Input {
+    pub mouse:    [f32; 2], // Cursor in pixels
+    pub click:    bool,     // Left button went down this frame
+    pub held:     bool,     // Left button is down
+    pub prev:     bool,     // Select the row above
+    pub next:     bool,     // Select the row below
+    pub left:     bool,     // Decrease / previous option
+    pub right:    bool,     // Increase / next option
+    #[cfg_attr(not(feature = "menu"), allow(dead_code))]
+    pub activate: bool,     // Toggle / next option
+}
+
+impl Input {
+    /// No input: the window is only drawn.
+    pub const NONE: Input = Input {
+        mouse: [f32::NEG_INFINITY; 2], click: false, held: false,
+        prev: false, next: false, left: false, right: false, activate: false,
+    };
+
+    /// Take this frame's key presses and mouse state from input.rs.
+    #[cfg(feature = "menu")]
+    pub fn poll() -> Input {
+        let (mx, my) = input::mouse_pos();
+        Input {
+            mouse:    [mx, my],
+            click:    input::take_press(input::VK_LBUTTON),
+            held:     input::mouse_down(),
+            prev:     input::take_press(VK_UP),
+            next:     input::take_press(VK_DOWN),
+            left:     input::take_press(VK_LEFT),
+            right:    input::take_press(VK_RIGHT),
+            activate: input::take_press(VK_RETURN),
+        }
+    }
+}
+
+/// What a window keeps between frames.
+pub struct UiState {
+    selected: usize,         // Selected interactive row
+    dragging: Option<usize>, // Slider held with the mouse
+    count:    usize,         // Interactive rows last frame (for wrapping)
+}
+
+impl UiState {
+    pub const fn new() -> Self { Self { selected: 0, dragging: None, count: 0 } }
+
+    /// The selected interactive row.
+    pub fn selected(&self) -> usize { self.selected }
+
+    /// Drop any drag in progress (the window was closed).
+    #[cfg(feature = "menu")]
+    pub fn release(&mut self) { self.dragging = None; }
+}
+
+/// Height of a window with a title and `rows` rows below it.
+pub fn window_height(style: &Style, rows: usize) -> f32 {
+    style.pad * 2.0 + style.row_h * (rows + 1) as f32
+}
+
+/// A window being declared this frame.
+pub struct Window<'a> {
+    hdc:   HDC,
+    style: &'a Style,
+    state: &'a mut UiState,
+    input: Input,
+    rect:  [f32; 4], // [x0, y0, x1, y1]
+    y:     f32,      // Top of the next row
+    next:  usize,    // Number of the next interactive row
+}
+
+impl<'a> Window<'a> {
+    /// Draw the frame and title of a window at `rect` (sized with
+    /// window_height) and apply this frame's selection keys.
+    pub unsafe fn begin(hdc: HDC, style: &'a Style, state: &'a mut UiState, input: Input,
+                        rect: [f32; 4], title: &str) -> Self {
+        let n = state.count;
+        if n > 0 {
+            let mut sel = state.selected.min(n - 1);
+            if input.prev { sel = (sel + n - 1) % n; }
+            if input.next { sel = (sel + 1) % n; }
+            state.selected = sel;
+        }
+        if !input.held { state.dragging = None; }
+
+        let [x0, y0, x1, y1] = rect;
+        render::fill_rect(x0, y0, x1, y1, style.bg);
+        if let Some(border) = style.border {
+            render::draw_rect(x0, y0, x1, y1, border, LineStyle::DEFAULT);
+        }
+        let mut w = Window { hdc, style, state, input, rect, y: y0 + style.pad, next: 0 };
+        w.label(title, style.accent);
+        w
+    }
+
+    /// Finish the window.
+    pub fn end(self) {
+        self.state.count = self.next;
+    }
+
+    /// A row of text.
+    pub unsafe fn label(&mut self, text: &str, color: [f32; 4]) {
+        let (_, ty) = self.row();
+        render::draw_text(self.hdc, self.rect[0] + self.style.pad, ty, text, color);
+    }
+
+    /// Reserve `rows` rows for the caller to draw into: their rect inside the padding.
+    pub fn area(&mut self, rows: usize) -> [f32; 4] {
+        let y = self.y;
+        self.y += self.style.row_h * rows as f32;
+        [self.rect[0] + self.style.pad, y, self.rect[2] - self.style.pad, self.y]
+    }
+
+    /// An ON/OFF row; Enter, Left/Right or a click flips it.
+    #[cfg(feature = "menu")]
+    pub unsafe fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let (y, ty) = self.row();
+        let (_, selected, hovered) = self.interactive(y);
+        let i = self.input;
+        let changed = (selected && (i.left || i.right || i.activate)) || (hovered && i.click);
+        if changed { *value = !*value; }
+
+        self.draw_label(label, ty);
+        let (text, c) = if *value { ("ON", self.style.accent) } else { ("OFF", self.style.track) };
+        self.draw_right(text, ty, c);
+        changed
+    }
+
+    /// A value in `min..=max`; Left/Right move it by `step`, and clicking or
+    /// dragging on the track sets it directly.
+    #[cfg(feature = "menu")]
+    pub unsafe fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32, step: f32) -> bool {
+        let (y, ty) = self.row();
+        let (idx, selected, hovered) = self.interactive(y);
+        let (s, i) = (self.style, self.input);
+        let x0 = self.rect[2] - s.pad - s.slider_w;
+        let before = *value;
+
+        let delta = if i.right { step } else if i.left { -step } else { 0.0 };
+        if selected && delta != 0.0 {
+            *value = (*value + delta).clamp(min, max);
+        }
+        // Only a click on the track itself grabs the slider
+        if hovered && i.click && i.mouse[0] >= x0 - s.pad {
+            self.state.dragging = Some(idx);
+        }
+        if self.state.dragging == Some(idx) {
+            let t = ((i.mouse[0] - x0) / s.slider_w).clamp(0.0, 1.0);
+            *value = min + (max - min) * t;
+        }
+
+        self.draw_label(label, ty);
+        let t = ((*value - min) / (max - min)).clamp(0.0, 1.0);
+        let cy = y + s.row_h * 0.5;
+        render::fill_rect(x0, cy - 1.0, x0 + s.slider_w, cy + 1.0, s.track);
+        render::fill_rect(x0, cy - 1.0, x0 + s.slider_w * t, cy + 1.0, s.accent);
+        let kx = x0 + s.slider_w * t;
+        render::fill_rect(kx - 2.0, cy - 4.0, kx + 2.0, cy + 4.0, s.text);
+        let text = format!("{:.1}", *value);
+        render::draw_text(self.hdc, x0 - s.pad - render::text_width(&text), ty, &text, s.text);
+        *value != before
+    }
+
+    /// One of `count` options named by `name`; Left/Right step through them,
+    /// Enter or a click moves to the next.
+    #[cfg(feature = "menu")]
+    pub unsafe fn selector(&mut self, label: &str, value: &mut usize, count: usize,
+                           name: impl Fn(usize) -> &'static str) -> bool {
+        let (y, ty) = self.row();
+        let (_, selected, hovered) = self.interactive(y);
+        let i = self.input;
+        let step = if selected && i.left {
+            count - 1
+        } else if (selected && (i.right || i.activate)) || (hovered && i.click) {
+            1
+        } else {
+            0
+        };
+        if step != 0 { *value = (*value + step) % count; }
+
+        self.draw_label(label, ty);
+        self.draw_right(&format!("< {} >", name(*value)), ty, self.style.accent);
+        step != 0
+    }
+
+    /// A whole number shown as `text`; Left/Right move it by `step`, a click
+    /// steps it up. Stops at 0.
+    pub unsafe fn stepper(&mut self, label: &str, value: &mut usize, step: usize, text: &str) -> bool {
+        let (y, ty) = self.row();
+        let (_, selected, hovered) = self.interactive(y);
+        let (i, before) = (self.input, *value);
+        if selected && i.left { *value = value.saturating_sub(step); }
+        if (selected && i.right) || (hovered && i.click) { *value += step; }
+
+        self.draw_label(label, ty);
+        self.draw_right(text, ty, if selected { self.style.accent } else { self.style.text });
+        *value != before
+    }
+
+    /// Take the next row: (top, text top).
+    fn row(&mut self) -> (f32, f32) {
+        let y = self.y;
+        self.y += self.style.row_h;
+        (y, y + (self.style.row_h - render::TEXT_HEIGHT) * 0.5)
+    }
+
+    /// Number an interactive row at `y`, select it on hover and highlight it
+    /// when selected: (number, selected, hovered).
+    unsafe fn interactive(&mut self, y: f32) -> (usize, bool, bool) {
+        let idx = self.next;
+        self.next += 1;
+        let [mx, my] = self.input.mouse;
+        let [x0, _, x1, _] = self.rect;
+        let hovered = mx >= x0 && mx <= x1 && my >= y && my < y + self.style.row_h;
+        if hovered && self.state.dragging.is_none() { self.state.selected = idx; }
+        let selected = self.state.selected == idx;
+        if selected {
+            render::fill_rect(x0 + 1.0, y, x1 - 1.0, y + self.style.row_h, self.style.hover);
+        }
+        (idx, selected, hovered)
+    }
+
+    unsafe fn draw_label(&self, text: &str, ty: f32) {
+        render::draw_text(self.hdc, self.rect[0] + self.style.pad, ty, text, self.style.text);
+    }
+
+    unsafe fn draw_right(&self, text: &str, ty: f32, color: [f32; 4]) {
+        let x = self.rect[2] - self.style.pad - render::text_width(text);
+        render::draw_text(self.hdc, x, ty, text, color);
+    }
+}
+
+/// Arrow cursor at the mouse position (the game hides the system cursor).
+#[cfg(feature = "menu")]
+pub unsafe fn draw_cursor(color: [f32; 4]) {
+    let (x, y) = input::mouse_pos();
+    let (a, b, c) = ([x, y], [x, y + 14.0], [x + 10.0, y + 10.0]);
+    render::fill_triangle(a, b, c, color);
+    render::draw_line(a[0], a[1], b[0], b[1], [0.0, 0.0, 0.0, 1.0], LineStyle::DEFAULT);
+    render::draw_line(b[0], b[1], c[0], c[1], [0.0, 0.0, 0.0, 1.0], LineStyle::DEFAULT);
+    render::draw_line(c[0], c[1], a[0], a[1], [0.0, 0.0, 0.0, 1.0], LineStyle::DEFAULT);
+}
@@ -0,0 +1,222 @@
+// usermsg.rs — Server user messages captured for the round statistics (stats.rs),
+// team names (teams.rs) and the chat history (chat.rs).
+//
+// client.dll registers a handler per user message with pfnHookUserMsg; hw.dll
+// keeps them in a linked list of UserMsg:
+//
+//   int iMsg; int iSize; char szName[16]; UserMsg *next; pfnUserMsgHook pfn;
+//
+// The list head isn't exported, so it's only used when `stats.user_msgs_rva`
+// gives the hw.dll offset of the head pointer for the running build. The
+// handlers for DeathMsg, RoundTime, TeamScore, TeamInfo, TeamNames, SayText
+// and TextMsg are swapped for ones that record the message and call the
+// original. Mods that
+// don't register a message (TeamNames is TFC / DoD, ...) simply don't get it
+// hooked. `uninstall()` puts the original
+// handlers back before the DLL unloads. DeathMsg, RoundTime and TeamScore
+// become frame events (bus.rs) for whatever subscribes to them, and so do
+// SayText and TextMsg while `chat.enabled` is on.
+//
+// With `chat.console` on as well, the engine table's pfnConsolePrint is
+// hooked (MinHook) for the rest of what client.dll prints to the console. The
+// original SayText / TextMsg handlers print their message there too; those
+// prints are skipped, the message was already captured.
+
+use crate::bus::{self, Event};
+use crate::chat;
+use crate::config;
+use crate::entities::{self, is_executable, is_readable, read_cbytes, read_u32};
+use crate::hook;
+use crate::stats;
+use crate::teams::{self, TeamTable};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const MSG_NAME:  usize = 0x08;  // UserMsg::szName
+const MSG_NEXT:  usize = 0x18;  // UserMsg::next
+const MSG_PFN:   usize = 0x1C;  // UserMsg::pfn
+const NAME_LEN:  usize = 16;
+const MAX_MSGS:  usize = 512;   // More than this and the list isn't what we think
+const SLOT_CONSOLE_PRINT: usize = 30; // cl_enginefunc_t::pfnConsolePrint
+const MAX_PRINT: usize = 1024;  // Longest console print read
+
+type FnUserMsg = unsafe extern "C" fn(name: *const i8, size: i32, buf: *mut u8) -> i32;
+type FnConsolePrint = unsafe extern "C" fn(text: *const i8);
+
+static DEATH_ORIG: AtomicUsize = AtomicUsize::new(0);
+static ROUND_TIME_ORIG: AtomicUsize = AtomicUsize::new(0);
+static TEAM_SCORE_ORIG: AtomicUsize = AtomicUsize::new(0);
+static TEAM_INFO_ORIG: AtomicUsize = AtomicUsize::new(0);
+static TEAM_NAMES_ORIG: AtomicUsize = AtomicUsize::new(0);
+static SAY_TEXT_ORIG: AtomicUsize = AtomicUsize::new(0);
+static TEXT_MSG_ORIG: AtomicUsize = AtomicUsize::new(0);
+static CONSOLE_PRINT_ORIG: AtomicUsize = AtomicUsize::new(0);
+static IN_MESSAGE: AtomicBool = AtomicBool::new(false); // Inside an original SayText / TextMsg handler
+static TEAMS: Mutex<TeamTable> = Mutex::new(TeamTable::new());
+static ATTEMPTED: AtomicBool = AtomicBool::new(false);
+static CONSOLE_ATTEMPTED: AtomicBool = AtomicBool::new(false);
+static SWAPPED: Mutex<Vec<(usize, usize, usize)>> = Mutex::new(Vec::new()); // (pfn slot, original, ours)
+
+/// Swap in the handlers (attempted once, after the engine table is found).
+pub unsafe fn install() {
+    if ATTEMPTED.swap(true, Ordering::Relaxed) { return; }
+    let rva = config::get().stats_user_msgs_rva;
+    if rva == 0 { return; }
+    let Some((base, end)) = entities::module_range(b"hw.dll\0") else { return };
+    if base + rva as usize >= end {
+        entities::logf(format!("user messages: rva {:#x} is outside hw.dll", rva));
+        return;
+    }
+
+    let hooks: [(&[u8], &AtomicUsize, FnUserMsg); 7] = [
+        (b"DeathMsg", &DEATH_ORIG, hk_death_msg),
+        (b"RoundTime", &ROUND_TIME_ORIG, hk_round_time),
+        (b"TeamScore", &TEAM_SCORE_ORIG, hk_team_score),
+        (b"TeamInfo", &TEAM_INFO_ORIG, hk_team_info),
+        (b"TeamNames", &TEAM_NAMES_ORIG, hk_team_names),
+        (b"SayText", &SAY_TEXT_ORIG, hk_say_text),
+        (b"TextMsg", &TEXT_MSG_ORIG, hk_text_msg),
+    ];
+    let mut msg = read_u32(base + rva as usize) as usize;
+    let mut found = 0;
+    for _ in 0..MAX_MSGS {
+        if msg == 0 || !is_readable(msg, MSG_PFN + 4) { break; }
+        let name = read_cbytes((msg + MSG_NAME) as *const i8, NAME_LEN);
+        for (want, orig, hook) in &hooks {
+            let slot = (msg + MSG_PFN) as *mut usize;
+            if name.as_slice() != *want || orig.load(Ordering::Relaxed) != 0 { continue; }
+            let pfn = *slot;
+            if !entities::is_executable(pfn) { continue; }
+            orig.store(pfn, Ordering::Release);
+            *slot = *hook as usize;
+            if let Ok(mut swapped) = SWAPPED.lock() { swapped.push((slot as usize, pfn, *hook as usize)); }
+            found += 1;
+        }
+        msg = read_u32(msg + MSG_NEXT) as usize;
+    }
+    entities::logf(format!("user messages: {}/{} handlers hooked (hw.dll+{:#x})", found, hooks.len(), rva));
+}
+
+/// Hook pfnConsolePrint when `chat.console` is on (attempted once, when the
+/// engine table is found with it on).
+pub unsafe fn install_console(table: usize) {
+    let cfg = config::get();
+    if !cfg.chat_enabled || !cfg.chat_console || CONSOLE_ATTEMPTED.swap(true, Ordering::Relaxed) { return; }
+    let target = read_u32(table + SLOT_CONSOLE_PRINT * 4) as usize;
+    if !is_executable(target) {
+        entities::logf(format!("pfnConsolePrint not found ({:#010x})", target));
+        return;
+    }
+    match hook::hook_address("engine!pfnConsolePrint", target, hk_console_print as *mut std::ffi::c_void) {
+        Ok(orig) => {
+            CONSOLE_PRINT_ORIG.store(orig as usize, Ordering::Release);
+            entities::log("pfnConsolePrint hook installed");
+        }
+        Err(e) => entities::logf(format!("pfnConsolePrint hook failed: {}", e)),
+    }
+}
+
+/// Put the original handlers back (where ours is still the registered one).
+pub unsafe fn uninstall() {
+    let Ok(mut swapped) = SWAPPED.lock() else { return };
+    for (slot, original, ours) in swapped.drain(..) {
+        let slot = slot as *mut usize;
+        if is_readable(slot as usize, 4) && *slot == ours { *slot = original; }
+    }
+}
+
+/// Whether kills come from DeathMsg (otherwise only deaths are known).
+pub fn hooked() -> bool {
+    DEATH_ORIG.load(Ordering::Acquire) != 0
+}
+
+/// The team name the server gave the player in `slot` (team number `team`).
+pub fn team_name(slot: usize, team: i32) -> Option<String> {
+    TEAMS.lock().ok()?.name(slot, team).map(str::to_string)
+}
+
+/// Call the handler that was registered before ours.
+unsafe fn call(orig: &AtomicUsize, name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    match orig.load(Ordering::Acquire) {
+        0 => 0,
+        f => (std::mem::transmute::<usize, FnUserMsg>(f))(name, size, buf),
+    }
+}
+
+unsafe extern "C" fn hk_death_msg(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    if !buf.is_null() && size > 0 {
+        let bytes = std::slice::from_raw_parts(buf, size as usize);
+        if let Some(d) = stats::parse_death_msg(bytes) { bus::publish(Event::PlayerDied(d)); }
+    }
+    call(&DEATH_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_round_time(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    bus::publish(Event::RoundStart);
+    call(&ROUND_TIME_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_team_score(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    bus::publish(Event::RoundEnd);
+    call(&TEAM_SCORE_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_team_info(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    if !buf.is_null() && size > 0 {
+        let bytes = std::slice::from_raw_parts(buf, size as usize);
+        if let (Some((slot, team)), Ok(mut t)) = (teams::parse_team_info(bytes), TEAMS.lock()) {
+            t.set_player(slot, team);
+        }
+    }
+    call(&TEAM_INFO_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_team_names(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    if !buf.is_null() && size > 0 {
+        let bytes = std::slice::from_raw_parts(buf, size as usize);
+        if let Ok(mut t) = TEAMS.lock() { t.set_teams(teams::parse_team_names(bytes)); }
+    }
+    call(&TEAM_NAMES_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_say_text(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    capture(buf, size, chat::parse_say_text);
+    call_quiet(&SAY_TEXT_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_text_msg(name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    let _guard = hook::DetourGuard::enter();
+    capture(buf, size, chat::parse_text_msg);
+    call_quiet(&TEXT_MSG_ORIG, name, size, buf)
+}
+
+unsafe extern "C" fn hk_console_print(text: *const i8) {
+    let _guard = hook::DetourGuard::enter();
+    if !IN_MESSAGE.load(Ordering::Relaxed) && is_readable(text as usize, 1) {
+        let raw = read_cbytes(text, MAX_PRINT);
+        if let Some(line) = chat::console_line(&raw) { bus::publish(Event::Message(line)); }
+    }
+    let orig = CONSOLE_PRINT_ORIG.load(Ordering::Acquire);
+    if orig != 0 { (std::mem::transmute::<usize, FnConsolePrint>(orig))(text); }
+}
+
+/// Publish a chat / server message (while `chat.enabled` is on).
+unsafe fn capture(buf: *mut u8, size: i32, parse: fn(&[u8]) -> Option<chat::Line>) {
+    if buf.is_null() || size <= 0 || !config::get().chat_enabled { return; }
+    let bytes = std::slice::from_raw_parts(buf, size as usize);
+    if let Some(line) = parse(bytes) { bus::publish(Event::Message(line)); }
+}
+
+/// Call the original handler, its console prints not captured again.
+unsafe fn call_quiet(orig: &AtomicUsize, name: *const i8, size: i32, buf: *mut u8) -> i32 {
+    IN_MESSAGE.store(true, Ordering::Relaxed);
+    let r = call(orig, name, size, buf);
+    IN_MESSAGE.store(false, Ordering::Relaxed);
+    r
+}
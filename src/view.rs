@@ -0,0 +1,816 @@
+// view.rs — Screen-space math for the overlay.
+//
+// The pure parts of esp.rs: NDC-to-pixel conversion, box construction, box
+// smoothing, cache lifetime and fade curves, the label layout pass, and the
+// minimap's world-to-panel transform and clipping. None of it touches the
+// engine or OpenGL, so it is unit-tested on any host.
+
+use crate::math::Vec3;
+
+// ============================================================
+// Constants
+// ============================================================
+
+const BOX_ASPECT: f32 = 0.50;          // Width/height ratio for ESP boxes
+const MIN_BOX_H: f32 = 4.0;            // Smallest box height (pixels)
+const MIN_HALF_H: f32 = 8.0;           // Smallest half-height of the world box
+const DUCK_HALF_H: f32 = 26.0;         // Minimum half-height while ducking
+const DUCK_Z_OFFSET: f32 = 6.0;        // World box center shift while ducking
+const CACHE_TTL_NEAR_MS: u32 = 5000;   // Cache TTL for players closer than 10 m
+const CACHE_TTL_MID_MS: u32 = 2500;    // Cache TTL for players closer than 30 m
+const FADE_MS: f32 = 200.0;            // Duration of the cached-box fade-out
+const PROJECTION_HOLD_MS: u32 = 100;   // A box is held this long when only its projection fails
+const MIN_ALPHA: f32 = 0.02;           // Faded boxes at or below this aren't drawn
+const SMOOTH_TAU_MS: f32 = 45.0;       // Time constant of the box smoothing filter
+const SMOOTH_RESET_MS: u32 = 250;      // Gaps longer than this snap instead of gliding
+const SMOOTH_SNAP_PX: f32 = 150.0;     // Jumps larger than this (pixels) snap instead of gliding
+const PULSE_MIN: f32 = 0.02;           // Damage pulses weaker than this are over
+const HEALTH_GAP_MS: u32 = 2000;       // Health reads further apart don't count as one life
+const LABEL_GAP: f32 = 2.0;            // Vertical spacing between stacked labels
+const LABEL_MAX_SHIFTS: usize = 64;    // Upper bound on layout iterations per label
+
+// ============================================================
+// Coordinate Conversion
+// ============================================================
+
+/// Convert engine NDC (normalized device coordinates) to pixel coordinates.
+/// The engine's W2S returns NDC where (-1,-1) is bottom-left and (1,1) is top-right.
+/// We need pixel coords where (0,0) is top-left and (w,h) is bottom-right.
+pub fn ndc_to_px(ndc_x: f32, ndc_y: f32, screen_h: f32, vx: f32, vy: f32, vw: f32, vh: f32) -> [f32; 2] {
+    let x = vx + (ndc_x + 1.0) * 0.5 * vw;
+    let y_bottom_left = vy + (ndc_y + 1.0) * 0.5 * vh;
+    [x, screen_h - y_bottom_left] // Flip Y: bottom-left -> top-left origin
+}
+
+/// Whether a projected point is within one screen size of the screen. Feet
+/// further out than that can't put any part of a box or label on screen, and
+/// W2S results that far out come from points behind or beside the camera.
+pub fn near_screen(p: [f32; 2], screen_w: f32, screen_h: f32) -> bool {
+    p[0] >= -screen_w && p[0] <= 2.0 * screen_w && p[1] >= -screen_h && p[1] <= 2.0 * screen_h
+}
+
+// ============================================================
+// Box Construction
+// ============================================================
+
+/// World-space box for a player: (half height, center z offset) from the hull
+/// height, so feet = origin.z - half + offset and head = origin.z + half + offset.
+pub fn world_box(maxs_z: f32, ducking: bool) -> (f32, f32) {
+    let half_h = (maxs_z * 0.5).max(MIN_HALF_H);
+    if ducking { (half_h.max(DUCK_HALF_H), DUCK_Z_OFFSET) } else { (half_h, 0.0) }
+}
+
+/// A player's world box (half height, center z offset) across stance changes:
+/// after a change between standing and ducking it moves from the box it had
+/// to the new one over a set time instead of popping, so a duck misread (a
+/// box flicking between the two) shows as a box that never settles.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StanceBlend {
+    ducking: bool,
+    from:    (f32, f32), // World box when the change started
+    last:    (f32, f32), // World box returned last
+    since:   u32,        // When the change started (0 = settled)
+    known:   bool,       // Whether a stance was seen yet
+}
+
+impl StanceBlend {
+    /// The world box for `maxs_z` / `ducking` at `now`, blending over `ms`
+    /// after a stance change (0 = no blend).
+    pub fn update(&mut self, maxs_z: f32, ducking: bool, now: u32, ms: f32) -> (f32, f32) {
+        let target = world_box(maxs_z, ducking);
+        if !self.known || ms <= 0.0 {
+            *self = StanceBlend { ducking, from: target, last: target, since: 0, known: true };
+            return target;
+        }
+        if ducking != self.ducking {
+            self.ducking = ducking;
+            self.from = self.last;
+            self.since = now.max(1);
+        }
+        let t = if self.since == 0 { 1.0 } else { now.wrapping_sub(self.since) as f32 / ms };
+        let out = if t >= 1.0 {
+            self.since = 0;
+            target
+        } else {
+            let s = t * t * (3.0 - 2.0 * t); // Smoothstep
+            (self.from.0 + (target.0 - self.from.0) * s, self.from.1 + (target.1 - self.from.1) * s)
+        };
+        self.last = out;
+        out
+    }
+}
+
+/// Screen box [x0, y0, x1, y1] from the projected head and feet points.
+pub fn screen_box(head_px: [f32; 2], feet_px: [f32; 2]) -> [f32; 4] {
+    let y0 = head_px[1].min(feet_px[1]);  // Top of box
+    let y1 = head_px[1].max(feet_px[1]);  // Bottom of box
+    let box_h = (y1 - y0).max(MIN_BOX_H);
+    let box_w = box_h * BOX_ASPECT;        // Width proportional to height
+    let cx = (feet_px[0] + head_px[0]) * 0.5; // Center X
+    [cx - box_w * 0.5, y0, cx + box_w * 0.5, y1]
+}
+
+/// Project a player's world box (hull center, hull top, stance) with `project`
+/// (world point to NDC) and `to_px` (NDC to pixels): the pixel box and the
+/// feet point, or None if either end doesn't project.
+pub fn project_box(
+    origin: Vec3, maxs_z: f32, ducking: bool,
+    project: impl Fn(Vec3) -> Option<(f32, f32)>, to_px: impl Fn(f32, f32) -> [f32; 2],
+) -> Option<([f32; 4], [f32; 2])> {
+    project_world_box(origin, world_box(maxs_z, ducking), project, to_px)
+}
+
+/// project_box for a world box already worked out (see StanceBlend).
+pub fn project_world_box(
+    origin: Vec3, (half_h, z_offset): (f32, f32),
+    project: impl Fn(Vec3) -> Option<(f32, f32)>, to_px: impl Fn(f32, f32) -> [f32; 2],
+) -> Option<([f32; 4], [f32; 2])> {
+    let (fx, fy) = project(Vec3 { z: origin.z - half_h + z_offset, ..origin })?;
+    let (hx, hy) = project(Vec3 { z: origin.z + half_h + z_offset, ..origin })?;
+    if !fx.is_finite() || !fy.is_finite() || !hx.is_finite() || !hy.is_finite() { return None; }
+    let feet_px = to_px(fx, fy);
+    Some((screen_box(to_px(hx, hy), feet_px), feet_px))
+}
+
+// ============================================================
+// Box Smoothing
+// ============================================================
+// Player positions arrive in discrete network updates, so raw projected boxes
+// jump between them. An exponential moving average in screen space (time-based,
+// so it behaves the same at any FPS) lets them glide instead.
+
+/// Blend a freshly projected box/feet toward last frame's smoothed values.
+/// Snaps straight to the new position after a long gap or a large jump
+/// (respawn, teleport, camera cut) so the box never slides across the screen.
+pub fn smooth_box(
+    prev_box: [f32; 4],
+    prev_feet: [f32; 2],
+    cur_box: [f32; 4],
+    cur_feet: [f32; 2],
+    dt_ms: u32,
+    has_prev: bool,
+) -> ([f32; 4], [f32; 2]) {
+    if !has_prev || dt_ms > SMOOTH_RESET_MS {
+        return (cur_box, cur_feet);
+    }
+    let jump = (cur_feet[0] - prev_feet[0]).abs().max((cur_feet[1] - prev_feet[1]).abs());
+    if jump > SMOOTH_SNAP_PX {
+        return (cur_box, cur_feet);
+    }
+
+    let k = 1.0 - (-(dt_ms as f32) / SMOOTH_TAU_MS).exp();
+    let lerp = |a: f32, b: f32| a + (b - a) * k;
+    (
+        [
+            lerp(prev_box[0], cur_box[0]), lerp(prev_box[1], cur_box[1]),
+            lerp(prev_box[2], cur_box[2]), lerp(prev_box[3], cur_box[3]),
+        ],
+        [lerp(prev_feet[0], cur_feet[0]), lerp(prev_feet[1], cur_feet[1])],
+    )
+}
+
+// ============================================================
+// Cache Timing
+// ============================================================
+
+/// Distance-dependent cache lifetime: closer players stay cached longer.
+pub fn cache_ttl_ms(dist: f32, base_ms: u32) -> u32 {
+    if dist <= 0.0 { return base_ms; }
+    if dist < 10.0 { CACHE_TTL_NEAR_MS } else if dist < 30.0 { CACHE_TTL_MID_MS } else { base_ms }
+}
+
+/// Whether to keep drawing last frame's box for a player whose entity read
+/// succeeded but whose W2S failed, `since_ms` after the last projection that
+/// worked. Points right at the near plane drop out for single frames; holding
+/// the box over the gap stops it blinking. Longer failures fall through to the
+/// fade cache once the player stops being read.
+pub fn hold_projection(since_ms: u32, projected_before: bool) -> bool {
+    projected_before && since_ms <= PROJECTION_HOLD_MS
+}
+
+/// Ease-out fade progress (0 = fully visible, 1 = gone) for a cache entry `age_ms` old.
+pub fn fade_ease(age_ms: u32) -> f32 {
+    let t = (age_ms as f32 / FADE_MS).clamp(0.0, 1.0);
+    1.0 - (1.0 - t).powf(2.0)
+}
+
+/// Alpha of a cached box `age_ms` old starting from `base_alpha`, or None once
+/// it has faded out.
+pub fn cached_alpha(base_alpha: f32, age_ms: u32) -> Option<f32> {
+    let a = (base_alpha * (1.0 - fade_ease(age_ms))).max(MIN_ALPHA);
+    if a <= MIN_ALPHA { None } else { Some(a) }
+}
+
+/// Strength (1 = full, 0 = over) of a damage pulse `age_ms` after the hit,
+/// decaying with time constant `tau_ms` (0 = pulses off).
+pub fn damage_pulse(age_ms: u32, tau_ms: f32) -> f32 {
+    if tau_ms <= 0.0 { return 0.0; }
+    let p = (-(age_ms as f32) / tau_ms).exp();
+    if p < PULSE_MIN { 0.0 } else { p }
+}
+
+/// A slot's last health read, to spot damage: a drop between two reads of
+/// the same player. A different player in the slot, a death or a gap between
+/// reads (they may have died and respawned unseen) starts over, so only a
+/// drop within one life counts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HealthTrack {
+    pub hit_ms: u32,          // time::now_ms() of the last drop (0 = none)
+    last:       Option<i32>,  // Health at the last read (None = not known)
+    read_ms:    u32,          // ... and when it was taken
+    owner:      (u64, String), // Steam ID and name of the player it was read from
+}
+
+impl HealthTrack {
+    /// Take this frame's read; true if it's a drop since the last one.
+    pub fn update(&mut self, steam_id: u64, name: &str, health: i32, dead: bool, now: u32) -> bool {
+        let same = self.owner.0 == steam_id && self.owner.1 == name
+            && now.wrapping_sub(self.read_ms) <= HEALTH_GAP_MS;
+        if !same {
+            *self = HealthTrack { owner: (steam_id, name.to_string()), ..HealthTrack::default() };
+        }
+        let hit = !dead && self.last.is_some_and(|last| last > health);
+        if hit { self.hit_ms = now; }
+        self.last = (!dead).then_some(health);
+        self.read_ms = now;
+        hit
+    }
+}
+
+/// Master opacity levels the opacity hotkey steps through.
+pub const OPACITY_STEPS: [f32; 4] = [1.0, 0.75, 0.5, 0.25];
+
+/// The opacity level after `current`: the next lower step, wrapping to full.
+pub fn next_opacity(current: f32) -> f32 {
+    OPACITY_STEPS.iter().copied().find(|&s| s < current - 1e-3).unwrap_or(OPACITY_STEPS[0])
+}
+
+/// Alpha of the whole overlay across on/off toggles: it goes from wherever it
+/// was to shown (1) or hidden (0) over a set time, so a toggle in the middle
+/// of a fade turns it around instead of jumping.
+#[derive(Clone, Copy, Debug)]
+pub struct OverlayFade {
+    on:    bool,
+    from:  f32, // Alpha when the last toggle happened
+    since: u32, // Time of the last toggle (ms)
+    known: bool, // False until the first frame (no fade-in at startup)
+}
+
+impl OverlayFade {
+    pub const fn new() -> Self {
+        Self { on: true, from: 1.0, since: 0, known: false }
+    }
+
+    /// This frame's alpha, with the overlay toggled `on` at `now`; fades take `ms` (0 = instant).
+    pub fn update(&mut self, on: bool, now: u32, ms: f32) -> f32 {
+        if !self.known || on != self.on {
+            self.from = if self.known { self.alpha(now, ms) } else if on { 1.0 } else { 0.0 };
+            self.on = on;
+            self.since = now;
+            self.known = true;
+        }
+        self.alpha(now, ms)
+    }
+
+    fn alpha(&self, now: u32, ms: f32) -> f32 {
+        let target = if self.on { 1.0 } else { 0.0 };
+        if ms <= 0.0 { return target; }
+        let t = (now.wrapping_sub(self.since) as f32 / ms).min(1.0);
+        self.from + (target - self.from) * t
+    }
+}
+
+// ============================================================
+// Player Cache
+// ============================================================
+// What was last drawn for each player slot. It is both the box smoothing
+// filter's state and what the fade path draws once a player stops being read.
+// The world box is kept too, so a fading box can be reprojected each frame and
+// stay on the player's last position while the camera moves; the pixel box is
+// only drawn as-is when there's no engine to project with.
+
+/// One slot's last drawn box and labels.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerCacheEntry {
+    pub rect:           [f32; 4],  // [x0, y0, x1, y1] in pixels
+    pub feet:           [f32; 2],  // Snap-line target in pixels
+    pub dist:           f32,       // Meters (0 = no local player)
+    pub color:          [f32; 4],  // Box color when last drawn fresh
+    pub team:           i32,
+    pub steam_id:       u64,       // For [player_colors] on cached boxes
+    pub team_name:      Option<String>, // For [team_colors] by team name on cached boxes
+    pub origin:         Vec3,      // World box: hull center,
+    pub maxs_z:         f32,       // hull top,
+    pub ducking:        bool,      // and stance (see world_box)
+    pub stance:         StanceBlend, // Box height across stance changes
+    pub health:         HealthTrack, // Damage pulse state (read even when the box isn't drawn)
+    pub name:           String,    // Name label, tags included
+    pub weapon:         String,
+    pub last_seen:      u32,       // time::now_ms() of the last read (0 = never)
+    pub last_projected: u32,       // ... and of the last projection that worked
+}
+
+impl PlayerCacheEntry {
+    /// Age (ms) of the entry if it should still be drawn from the cache,
+    /// given the distance-dependent TTL over `base_ttl_ms`.
+    pub fn cached_age(&self, now: u32, base_ttl_ms: u32) -> Option<u32> {
+        if self.last_seen == 0 || self.rect == [0.0; 4] { return None; }
+        let age = now.wrapping_sub(self.last_seen);
+        (age <= cache_ttl_ms(self.dist, base_ttl_ms)).then_some(age)
+    }
+}
+
+/// Cache entries by player slot, grown on first write.
+#[derive(Default)]
+pub struct PlayerCache {
+    slots: Vec<PlayerCacheEntry>,
+}
+
+impl PlayerCache {
+    pub const fn new() -> Self {
+        PlayerCache { slots: Vec::new() }
+    }
+
+    /// Slot `slot`'s entry, if it was ever written.
+    pub fn get(&self, slot: usize) -> Option<&PlayerCacheEntry> {
+        self.slots.get(slot)
+    }
+
+    /// Slot `slot`'s entry for writing.
+    pub fn entry(&mut self, slot: usize) -> &mut PlayerCacheEntry {
+        if slot >= self.slots.len() { self.slots.resize_with(slot + 1, Default::default); }
+        &mut self.slots[slot]
+    }
+
+    /// Slot `slot`'s last box color, or `fallback` if it was never drawn.
+    pub fn color(&self, slot: usize, fallback: [f32; 4]) -> [f32; 4] {
+        self.get(slot).filter(|e| e.last_seen != 0).map_or(fallback, |e| e.color)
+    }
+}
+
+// ============================================================
+// Label Layout
+// ============================================================
+// Labels are queued during the frame and drawn in one pass at the end, after
+// overlapping rectangles have been pushed apart vertically.
+
+/// Column width of render.rs's stroke font (glyph + spacing), in pixels.
+pub const CHAR_W: f32 = 9.0;
+
+/// Pixels per grid unit of the stroke font's 6 x 8 glyph grid.
+pub const FONT_SCALE: f32 = 1.2;
+
+const GLYPH_W: f32 = 6.0; // Widest glyph in grid units (M, W, Q)
+
+/// Height of one line of stroke-font text in pixels.
+pub const TEXT_HEIGHT: f32 = 8.0 * FONT_SCALE;
+
+/// Width in pixels of `text` when drawn with render.rs's `draw_text`.
+/// The last character doesn't include the trailing inter-character spacing.
+pub fn text_width(text: &str) -> f32 {
+    text_width_of(text.chars().count())
+}
+
+/// Drawn after the part of a name that fits (render.rs has a glyph for it).
+pub const ELLIPSIS: char = '\u{2026}';
+
+/// `text` cut short, ending in an ellipsis, so that it's at most `max_w`
+/// pixels wide when drawn at `scale`. Unchanged if it fits or `max_w` is 0;
+/// at least one character is kept.
+pub fn truncate_to_width(text: &str, max_w: f32, scale: f32) -> String {
+    if max_w <= 0.0 || text_width(text) * scale <= max_w { return text.to_string(); }
+    let chars: Vec<char> = text.chars().collect();
+    let mut keep = chars.len() - 1;
+    // Every column is CHAR_W wide, so the kept part plus the ellipsis is keep + 1 columns
+    while keep > 1 && text_width_of(keep + 1) * scale > max_w { keep -= 1; }
+    let mut out: String = chars[..keep].iter().collect::<String>().trim_end().to_string();
+    out.push(ELLIPSIS);
+    out
+}
+
+fn text_width_of(chars: usize) -> f32 {
+    if chars == 0 { 0.0 } else { (chars - 1) as f32 * CHAR_W + GLYPH_W * FONT_SCALE }
+}
+
+/// How name labels shrink with distance and how wide they may get.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NameFit {
+    pub shrink_from: f32, // Meters: full size up to here
+    pub shrink_to:   f32, // Meters: min_scale from here on
+    pub min_scale:   f32, // Floor of the text scale
+    pub max_width:   f32, // Pixels on screen (0 = no limit)
+}
+
+impl NameFit {
+    /// Full size, never truncated.
+    pub const OFF: NameFit = NameFit { shrink_from: 0.0, shrink_to: 0.0, min_scale: 1.0, max_width: 0.0 };
+
+    /// Text scale for a name `dist` meters away.
+    pub fn scale(&self, dist: f32) -> f32 {
+        let floor = self.min_scale.clamp(0.1, 1.0);
+        if floor >= 1.0 || dist <= self.shrink_from { return 1.0; }
+        if self.shrink_to <= self.shrink_from { return floor; }
+        let t = ((dist - self.shrink_from) / (self.shrink_to - self.shrink_from)).min(1.0);
+        1.0 - t * (1.0 - floor)
+    }
+
+    /// The name as drawn `dist` meters away, and its text scale.
+    pub fn fit(&self, name: &str, dist: f32) -> (String, f32) {
+        let scale = self.scale(dist);
+        (truncate_to_width(name, self.max_width, scale), scale)
+    }
+}
+
+/// A text label queued for the per-frame layout pass.
+pub struct Label {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32, // Text width in pixels
+    pub h: f32, // Text height in pixels
+    pub text: String,
+    pub color: [f32; 4],
+    pub dir: f32, // -1.0 = stack upward (above a box), 1.0 = stack downward (below a box)
+    pub scale: f32, // Text scale (1.0 = normal size)
+}
+
+impl Label {
+    /// Screen rectangle [x0, y0, x1, y1] covered by this label.
+    pub fn rect(&self) -> [f32; 4] {
+        [self.x, self.y, self.x + self.w, self.y + self.h]
+    }
+}
+
+/// Check whether two [x0, y0, x1, y1] rectangles intersect.
+pub fn rects_overlap(a: [f32; 4], b: [f32; 4]) -> bool {
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
+}
+
+/// Resolve overlapping labels. Labels are placed in queue order; each one is
+/// shifted past any already-placed label it intersects (up or down, depending on
+/// which side of its box it sits) until it lands in free space.
+pub fn layout_labels(labels: &mut [Label]) {
+    let mut placed: Vec<[f32; 4]> = Vec::with_capacity(labels.len());
+    for label in labels.iter_mut() {
+        let mut shifts = 0;
+        let mut moved = true;
+        while moved && shifts < LABEL_MAX_SHIFTS {
+            moved = false;
+            for r in &placed {
+                if rects_overlap(label.rect(), *r) {
+                    label.y = if label.dir < 0.0 {
+                        r[1] - label.h - LABEL_GAP
+                    } else {
+                        r[3] + LABEL_GAP
+                    };
+                    moved = true;
+                    shifts += 1;
+                }
+            }
+        }
+        placed.push(label.rect());
+    }
+}
+
+// ============================================================
+// World-Space Text
+// ============================================================
+
+/// Which side of its world point a label goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextSide { Above, Below }
+
+/// How a label at a world point is drawn (esp.rs `draw_text_3d`): centered
+/// over or under the point, `gap` pixels away, shrinking with distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Text3d {
+    pub color: [f32; 4],
+    pub side:  TextSide,
+    pub gap:   f32,     // Pixels between the point and the text
+    pub fit:   NameFit, // NameFit::OFF keeps it full size
+}
+
+impl Text3d {
+    /// The text as drawn for a point projected to `point`, `dist` meters
+    /// away: cut to the fit's width, its top-left pixel, and its scale.
+    pub fn layout(&self, point: [f32; 2], text: &str, dist: f32) -> (String, [f32; 2], f32) {
+        let (text, scale) = self.fit.fit(text, dist);
+        let x = point[0] - text_width(&text) * scale * 0.5;
+        let y = match self.side {
+            TextSide::Above => point[1] - self.gap - TEXT_HEIGHT * scale,
+            TextSide::Below => point[1] + self.gap,
+        };
+        (text, [x, y], scale)
+    }
+}
+
+// ============================================================
+// Minimap
+// ============================================================
+
+/// Placement of the top-down minimap: world point `center` (x, y) is drawn at
+/// pixel `origin`, `scale` pixels per unit, turned so that view yaw `yaw`
+/// (degrees) points up. Yaw 90 leaves the map unrotated (+Y up, +X right).
+#[derive(Clone, Copy, Debug)]
+#[cfg(feature = "radar")]
+pub struct MapView {
+    pub center: [f32; 2],
+    pub origin: [f32; 2],
+    pub scale:  f32,
+    pub yaw:    f32,
+}
+
+#[cfg(feature = "radar")]
+impl MapView {
+    /// Pixel position of a world point.
+    pub fn to_px(self, x: f32, y: f32) -> [f32; 2] {
+        let (dx, dy) = (x - self.center[0], y - self.center[1]);
+        let (s, c) = (90.0 - self.yaw).to_radians().sin_cos();
+        let (rx, ry) = (dx * c - dy * s, dx * s + dy * c);
+        [self.origin[0] + rx * self.scale, self.origin[1] - ry * self.scale]
+    }
+}
+
+/// Clip the segment a-b to an [x0, y0, x1, y1] rectangle (Liang-Barsky).
+/// None if it lies entirely outside.
+#[cfg(feature = "radar")]
+pub fn clip_segment(a: [f32; 2], b: [f32; 2], r: [f32; 4]) -> Option<([f32; 2], [f32; 2])> {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for (p, q) in [(-dx, a[0] - r[0]), (dx, r[2] - a[0]), (-dy, a[1] - r[1]), (dy, r[3] - a[1])] {
+        if p == 0.0 {
+            if q < 0.0 { return None; } // Parallel and outside
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 { t0 = t0.max(t); } else { t1 = t1.min(t); }
+        if t0 > t1 { return None; }
+    }
+    Some(([a[0] + t0 * dx, a[1] + t0 * dy], [a[0] + t1 * dx, a[1] + t1 * dy]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "radar")]
+    fn near(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3
+    }
+
+    #[test]
+    fn ndc_corners_map_to_viewport() {
+        // 800x600 window, full viewport
+        assert_eq!(ndc_to_px(-1.0, -1.0, 600.0, 0.0, 0.0, 800.0, 600.0), [0.0, 600.0]);
+        assert_eq!(ndc_to_px(1.0, 1.0, 600.0, 0.0, 0.0, 800.0, 600.0), [800.0, 0.0]);
+        assert_eq!(ndc_to_px(0.0, 0.0, 600.0, 0.0, 0.0, 800.0, 600.0), [400.0, 300.0]);
+        // Letterboxed viewport: 800x500 starting 50 px up from the bottom
+        assert_eq!(ndc_to_px(-1.0, -1.0, 600.0, 0.0, 50.0, 800.0, 500.0), [0.0, 550.0]);
+        assert_eq!(ndc_to_px(1.0, 1.0, 600.0, 0.0, 50.0, 800.0, 500.0), [800.0, 50.0]);
+    }
+
+    #[test]
+    fn near_screen_allows_one_screen_of_margin() {
+        assert!(near_screen([400.0, 300.0], 800.0, 600.0));
+        assert!(near_screen([-799.0, 1199.0], 800.0, 600.0));
+        assert!(!near_screen([-801.0, 300.0], 800.0, 600.0));
+        assert!(!near_screen([400.0, 1201.0], 800.0, 600.0));
+        assert!(!near_screen([1e9, 300.0], 800.0, 600.0));
+    }
+
+    #[test]
+    fn projection_hold_is_brief() {
+        assert!(hold_projection(0, true));
+        assert!(hold_projection(PROJECTION_HOLD_MS, true));
+        assert!(!hold_projection(PROJECTION_HOLD_MS + 1, true));
+        assert!(!hold_projection(0, false));
+    }
+
+    #[test]
+    fn player_cache_grows_and_expires() {
+        let mut cache = PlayerCache::new();
+        assert!(cache.get(5).is_none());
+        assert_eq!(cache.color(5, [1.0; 4]), [1.0; 4]);
+
+        let e = cache.entry(5);
+        e.rect = [10.0, 10.0, 20.0, 40.0];
+        e.color = [0.5; 4];
+        e.dist = 50.0;
+        e.last_seen = 1000;
+        assert!(cache.get(4).is_some_and(|e| e.cached_age(1000, 1500).is_none())); // Never seen
+        assert_eq!(cache.color(5, [1.0; 4]), [0.5; 4]);
+
+        let e = cache.get(5).unwrap();
+        assert_eq!(e.cached_age(1200, 1500), Some(200));
+        assert_eq!(e.cached_age(2501, 1500), None);
+        // Closer players stay cached longer
+        let near = PlayerCacheEntry { dist: 5.0, ..e.clone() };
+        assert_eq!(near.cached_age(2501, 1500), Some(1501));
+    }
+
+    #[test]
+    fn world_box_ducking() {
+        assert_eq!(world_box(76.0, false), (38.0, 0.0));
+        assert_eq!(world_box(48.0, true), (DUCK_HALF_H, DUCK_Z_OFFSET));
+        assert_eq!(world_box(2.0, false), (MIN_HALF_H, 0.0));
+    }
+
+    #[test]
+    fn stance_changes_blend() {
+        let (stand, duck) = (world_box(76.0, false), world_box(48.0, true));
+        let mut s = StanceBlend::default();
+        assert_eq!(s.update(76.0, false, 1000, 100.0), stand); // First sight: no blend
+        assert_eq!(s.update(48.0, true, 1000, 100.0), stand);  // Change starts from the old box
+        let mid = s.update(48.0, true, 1050, 100.0);
+        assert!((mid.0 - (stand.0 + duck.0) * 0.5).abs() < 1e-4 && mid.1 == DUCK_Z_OFFSET * 0.5);
+        assert_eq!(s.update(48.0, true, 1100, 100.0), duck);
+
+        // Standing up halfway through goes back from where the box is
+        s.update(76.0, false, 2000, 100.0);
+        let part = s.update(76.0, false, 2050, 100.0);
+        assert_eq!(s.update(48.0, true, 2050, 100.0), part);
+        assert_eq!(s.update(48.0, true, 9000, 0.0), duck); // Blending off
+    }
+
+    #[test]
+    fn project_box_uses_feet_and_head() {
+        let origin = Vec3 { x: 0.0, y: 0.0, z: 36.0 };
+        // Orthographic stand-in: NDC y = z / 100, pixels = NDC * 100 flipped
+        let ortho = |p: Vec3| Some((p.x / 100.0, p.z / 100.0));
+        let px = |x: f32, y: f32| [x * 100.0, 100.0 - y * 100.0];
+        let (rect, feet) = project_box(origin, 72.0, false, ortho, px).unwrap();
+        assert_eq!(feet, [0.0, 100.0]);
+        assert_eq!(rect, [-18.0, 28.0, 18.0, 100.0]);
+        assert!(project_box(origin, 72.0, false, |_| None, px).is_none());
+        assert!(project_box(origin, 72.0, false, |_| Some((f32::NAN, 0.0)), px).is_none());
+    }
+
+    #[test]
+    fn screen_box_orders_and_sizes() {
+        let b = screen_box([100.0, 50.0], [104.0, 150.0]);
+        assert_eq!(b, [77.0, 50.0, 127.0, 150.0]);
+        // Head below feet (camera upside down) still yields y0 < y1
+        let b = screen_box([100.0, 150.0], [100.0, 50.0]);
+        assert!(b[1] < b[3]);
+        // Degenerate height is clamped
+        let b = screen_box([10.0, 10.0], [10.0, 10.0]);
+        assert_eq!(b[3] - b[1], 0.0);
+        assert_eq!(b[2] - b[0], MIN_BOX_H * BOX_ASPECT);
+    }
+
+    #[test]
+    fn smoothing_snaps_and_blends() {
+        let prev = [0.0, 0.0, 10.0, 20.0];
+        let cur = [10.0, 0.0, 20.0, 20.0];
+        assert_eq!(smooth_box(prev, [5.0, 20.0], cur, [15.0, 20.0], 16, false).0, cur);
+        assert_eq!(smooth_box(prev, [5.0, 20.0], cur, [15.0, 20.0], SMOOTH_RESET_MS + 1, true).0, cur);
+        assert_eq!(smooth_box(prev, [5.0, 20.0], cur, [500.0, 20.0], 16, true).0, cur);
+
+        let (b, f) = smooth_box(prev, [5.0, 20.0], cur, [15.0, 20.0], 16, true);
+        assert!(b[0] > 0.0 && b[0] < 10.0);
+        assert!(f[0] > 5.0 && f[0] < 15.0);
+    }
+
+    #[test]
+    fn cache_ttl_tiers() {
+        assert_eq!(cache_ttl_ms(0.0, 1500), 1500);
+        assert_eq!(cache_ttl_ms(5.0, 1500), CACHE_TTL_NEAR_MS);
+        assert_eq!(cache_ttl_ms(20.0, 1500), CACHE_TTL_MID_MS);
+        assert_eq!(cache_ttl_ms(50.0, 1500), 1500);
+    }
+
+    #[test]
+    fn fade_curve() {
+        assert_eq!(fade_ease(0), 0.0);
+        assert_eq!(fade_ease(FADE_MS as u32), 1.0);
+        assert_eq!(fade_ease(10 * FADE_MS as u32), 1.0);
+        assert!(fade_ease(50) < fade_ease(100));
+        // Ease-out: more than half faded at half time
+        assert!(fade_ease(FADE_MS as u32 / 2) > 0.5);
+
+        assert_eq!(cached_alpha(0.6, 0), Some(0.6));
+        assert_eq!(cached_alpha(0.6, FADE_MS as u32), None);
+    }
+
+    #[test]
+    fn damage_pulse_decays() {
+        assert_eq!(damage_pulse(0, 150.0), 1.0);
+        assert!((damage_pulse(150, 150.0) - (-1.0f32).exp()).abs() < 1e-6);
+        assert!(damage_pulse(100, 150.0) > damage_pulse(200, 150.0));
+        assert_eq!(damage_pulse(5000, 150.0), 0.0);
+        assert_eq!(damage_pulse(0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn health_drops_count_within_one_life() {
+        let mut h = HealthTrack::default();
+        assert!(!h.update(7, "bob", 100, false, 1000));     // First read
+        assert!(h.update(7, "bob", 73, false, 1100));
+        assert_eq!(h.hit_ms, 1100);
+        assert!(!h.update(7, "bob", 73, false, 1200));
+        assert!(!h.update(7, "bob", 90, false, 1300));      // Healed
+        assert_eq!(h.hit_ms, 1100);
+
+        // Someone else in the slot starts over, without the old pulse
+        assert!(!h.update(0, "Bot Al", 40, false, 1400));
+        assert_eq!(h.hit_ms, 0);
+        assert!(h.update(0, "Bot Al", 20, false, 1500));
+
+        // A death, or a gap long enough to respawn unseen, starts over too
+        assert!(!h.update(0, "Bot Al", 0, true, 1600));
+        assert!(!h.update(0, "Bot Al", 100, false, 1700));
+        assert!(!h.update(0, "Bot Al", 60, false, 1700 + HEALTH_GAP_MS + 1));
+        assert!(h.update(0, "Bot Al", 50, false, 1800 + HEALTH_GAP_MS));
+    }
+
+    #[test]
+    fn opacity_steps_down_and_wraps() {
+        assert_eq!(next_opacity(1.0), 0.75);
+        assert_eq!(next_opacity(0.5), 0.25);
+        assert_eq!(next_opacity(0.25), 1.0);
+        assert_eq!(next_opacity(0.6), 0.5); // A configured level between steps
+    }
+
+    #[test]
+    fn overlay_fade_turns_around_midway() {
+        let mut f = OverlayFade::new();
+        assert_eq!(f.update(true, 1000, 150.0), 1.0); // No fade-in at startup
+        assert_eq!(f.update(false, 2000, 150.0), 1.0);
+        assert!((f.update(false, 2075, 150.0) - 0.5).abs() < 1e-6);
+        // Back on halfway: from 0.5 up to 1 over the full time
+        assert!((f.update(true, 2075, 150.0) - 0.5).abs() < 1e-6);
+        assert!((f.update(true, 2150, 150.0) - 0.75).abs() < 1e-6);
+        assert_eq!(f.update(true, 9000, 150.0), 1.0);
+        assert_eq!(f.update(false, 9001, 0.0), 0.0);
+    }
+
+    fn label(y: f32, dir: f32) -> Label {
+        Label { x: 0.0, y, w: 50.0, h: 10.0, text: String::new(), color: [1.0; 4], dir, scale: 1.0 }
+    }
+
+    #[test]
+    fn names_shrink_with_distance_and_are_truncated() {
+        let fit = NameFit { shrink_from: 10.0, shrink_to: 50.0, min_scale: 0.5, max_width: 100.0 };
+        assert_eq!(fit.scale(5.0), 1.0);
+        assert!((fit.scale(30.0) - 0.75).abs() < 1e-5);
+        assert_eq!(fit.scale(500.0), 0.5);
+        assert_eq!(NameFit::OFF.scale(500.0), 1.0);
+
+        assert_eq!(fit.fit("short", 5.0).0, "short");
+        let (cut, _) = fit.fit("a very long player name indeed", 5.0);
+        assert!(cut.ends_with(ELLIPSIS) && text_width(&cut) <= 100.0, "{}", cut);
+        assert!(!cut.trim_end_matches(ELLIPSIS).ends_with(' '));
+        // Smaller text fits more of the name
+        let (far, scale) = fit.fit("a very long player name indeed", 500.0);
+        assert!(far.chars().count() > cut.chars().count() && text_width(&far) * scale <= 100.0);
+        assert_eq!(truncate_to_width("abcdef", 1.0, 1.0), "a\u{2026}");
+    }
+
+    #[test]
+    fn labels_are_pushed_apart() {
+        let mut labels = vec![label(100.0, -1.0), label(102.0, -1.0), label(104.0, 1.0)];
+        layout_labels(&mut labels);
+        for i in 0..labels.len() {
+            for j in i + 1..labels.len() {
+                assert!(!rects_overlap(labels[i].rect(), labels[j].rect()), "{} overlaps {}", i, j);
+            }
+        }
+        assert_eq!(labels[0].y, 100.0);                 // First keeps its spot
+        assert_eq!(labels[1].y, 100.0 - 10.0 - LABEL_GAP); // Stacked above
+        assert!(labels[2].y > labels[0].y);              // Stacked below
+    }
+
+    #[test]
+    fn world_text_centers_and_shrinks() {
+        let above = Text3d { color: [1.0; 4], side: TextSide::Above, gap: 4.0, fit: NameFit::OFF };
+        let (text, [x, y], scale) = above.layout([100.0, 200.0], "abc", 80.0);
+        assert_eq!((text.as_str(), scale), ("abc", 1.0));
+        assert_eq!((x, y), (100.0 - text_width("abc") * 0.5, 200.0 - 4.0 - TEXT_HEIGHT));
+
+        let fit = NameFit { shrink_from: 10.0, shrink_to: 20.0, min_scale: 0.5, max_width: 0.0 };
+        let below = Text3d { side: TextSide::Below, fit, ..above };
+        let (_, [x, y], scale) = below.layout([100.0, 200.0], "abc", 40.0);
+        assert_eq!((scale, y), (0.5, 204.0));
+        assert_eq!(x, 100.0 - text_width("abc") * 0.25);
+    }
+
+    #[test]
+    #[cfg(feature = "radar")]
+    fn minimap_forward_is_up() {
+        let v = MapView { center: [100.0, 100.0], origin: [50.0, 50.0], scale: 0.5, yaw: 0.0 };
+        assert!(near(v.to_px(100.0, 100.0), [50.0, 50.0]));
+        assert!(near(v.to_px(120.0, 100.0), [50.0, 40.0]));  // Ahead (+X at yaw 0) -> up
+        assert!(near(v.to_px(100.0, 80.0), [60.0, 50.0]));   // -Y is to the right at yaw 0
+        let north = MapView { yaw: 90.0, ..v };
+        assert!(near(north.to_px(120.0, 120.0), [60.0, 40.0])); // Unrotated: +X right, +Y up
+    }
+
+    #[test]
+    #[cfg(feature = "radar")]
+    fn clips_segments() {
+        let r = [0.0, 0.0, 10.0, 10.0];
+        assert_eq!(clip_segment([2.0, 2.0], [8.0, 8.0], r), Some(([2.0, 2.0], [8.0, 8.0])));
+        assert_eq!(clip_segment([-5.0, 5.0], [15.0, 5.0], r), Some(([0.0, 5.0], [10.0, 5.0])));
+        assert_eq!(clip_segment([-5.0, -5.0], [-1.0, 20.0], r), None);
+        assert_eq!(clip_segment([11.0, 0.0], [11.0, 10.0], r), None); // Vertical, outside
+    }
+}
@@ -0,0 +1,125 @@
+// warmup.rs — Startup steps and the progress line shown while they run.
+//
+// Everything slow at startup (reading the config, pattern scans of
+// client.dll, loading plugins, decoding images, hashing client.dll for the
+// environment report) runs on the worker thread, after the render hook is
+// in: the game never waits on DllMain or on an overlay frame for it. While
+// it runs, the status block says which step the worker is on and for how
+// long, then how long the whole thing took, for a few seconds.
+
+use std::sync::Mutex;
+
+/// How long "ready in ..." stays up once startup is done.
+pub const READY_SHOWN_MS: u32 = 3000;
+
+/// A startup step on the worker thread, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Settings,    // Config, offsets, log settings, crash handler
+    RenderHook,  // wglSwapBuffers (frames start drawing here)
+    EngineTable, // client.dll scan or the Initialize hook
+    Subsystems,  // Plugins, control pipe, ... (subsystems.rs)
+    Images,      // Minimap background and other icons decoded
+    Report,      // Environment report (reads all of client.dll)
+}
+
+impl Step {
+    pub const ALL: [Step; 6] = [Step::Settings, Step::RenderHook, Step::EngineTable,
+                                Step::Subsystems, Step::Images, Step::Report];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::Settings    => "settings",
+            Step::RenderHook  => "render hook",
+            Step::EngineTable => "engine table",
+            Step::Subsystems  => "subsystems",
+            Step::Images      => "images",
+            Step::Report      => "environment report",
+        }
+    }
+
+    fn number(self) -> usize {
+        Step::ALL.iter().position(|&s| s == self).unwrap_or(0) + 1
+    }
+}
+
+/// Where startup is: the current step and when it started, or when it finished.
+pub struct Warmup {
+    step:     Option<(Step, u32)>, // Current step and its start (ms)
+    started:  u32,                 // First step's start (0 = not started)
+    finished: Option<u32>,         // Time taken, once done
+    done_at:  u32,
+}
+
+impl Warmup {
+    pub const fn new() -> Self {
+        Self { step: None, started: 0, finished: None, done_at: 0 }
+    }
+
+    /// Move on to `step` at `now`.
+    pub fn begin(&mut self, step: Step, now: u32) {
+        if self.started == 0 { self.started = now; }
+        self.step = Some((step, now));
+    }
+
+    /// Startup is over at `now`.
+    pub fn finish(&mut self, now: u32) {
+        self.step = None;
+        self.finished = Some(now.wrapping_sub(self.started));
+        self.done_at = now;
+    }
+
+    /// Whether every step has run.
+    pub fn done(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// The status line at `now`: the step being run, then "ready" for
+    /// READY_SHOWN_MS, then nothing.
+    pub fn line(&self, now: u32) -> Option<String> {
+        if let Some(took) = self.finished {
+            return (now.wrapping_sub(self.done_at) < READY_SHOWN_MS)
+                .then(|| format!("overlay ready in {:.1} s", took as f32 / 1000.0));
+        }
+        let (step, since) = self.step?;
+        Some(format!("starting ({}/{}): {}... {:.1} s", step.number(), Step::ALL.len(), step.name(),
+                     now.wrapping_sub(since) as f32 / 1000.0))
+    }
+}
+
+static WARMUP: Mutex<Warmup> = Mutex::new(Warmup::new());
+
+/// Worker thread: move on to `step`.
+pub fn begin(step: Step) {
+    if let Ok(mut w) = WARMUP.lock() { w.begin(step, crate::time::now_ms()); }
+}
+
+/// Worker thread: startup is over.
+pub fn finish() {
+    if let Ok(mut w) = WARMUP.lock() { w.finish(crate::time::now_ms()); }
+}
+
+/// The progress line for the status block, if there's one to show.
+pub fn line() -> Option<String> {
+    WARMUP.lock().ok().and_then(|w| w.line(crate::time::now_ms()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_step_then_ready_then_nothing() {
+        let mut w = Warmup::new();
+        assert_eq!(w.line(100), None);
+        w.begin(Step::Settings, 1000);
+        w.begin(Step::EngineTable, 1200);
+        assert_eq!(w.line(1450).as_deref(), Some("starting (3/6): engine table... 0.2 s"));
+        assert!(!w.done());
+
+        w.finish(2500);
+        assert!(w.done());
+        assert_eq!(w.line(2600).as_deref(), Some("overlay ready in 1.5 s"));
+        assert_eq!(w.line(2500 + READY_SHOWN_MS), None);
+    }
+}
@@ -0,0 +1,142 @@
+// watch.rs — Watch list expressions (`[watch]` in the config).
+//
+// Each `[watch]` entry names a memory location to read every frame and show as
+// a typed value in the watch panel, for poking at engine state while debugging:
+//
+//   health = "client.dll+0x12F4A0 -> 0x1C : f32"
+//   lives  = "0x02A1B3C0 : i32"
+//   name   = "hw.dll+0x7D4E20 -> 0x0 -> 0x100 : str"
+//
+// The base is a module plus an offset, or an absolute address (hex). Each
+// `-> off` step reads a pointer at the current address and adds `off` to it.
+// The type after `:` says how the bytes at the end are shown (u32 if left out).
+//
+// This file only parses expressions and formats values; the chain is walked by
+// entities::resolve_chain, which checks every step for readability.
+
+/// Bytes read for a `str` watch.
+pub const STR_LEN: usize = 32;
+
+/// Where an expression starts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Base {
+    Absolute(usize),
+    Module(String, usize), // Module name ("hw.dll") and offset into it
+}
+
+/// How a watched value is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind { U8, I16, I32, U32, F32, Vec3, Ptr, Str }
+
+impl Kind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8"   => Kind::U8,
+            "i16"  => Kind::I16,
+            "i32"  => Kind::I32,
+            "u32"  => Kind::U32,
+            "f32"  => Kind::F32,
+            "vec3" => Kind::Vec3,
+            "ptr"  => Kind::Ptr,
+            "str"  => Kind::Str,
+            _ => return None,
+        })
+    }
+
+    /// Bytes read for a value of this kind.
+    pub fn size(self) -> usize {
+        match self {
+            Kind::U8 => 1,
+            Kind::I16 => 2,
+            Kind::I32 | Kind::U32 | Kind::F32 | Kind::Ptr => 4,
+            Kind::Vec3 => 12,
+            Kind::Str => STR_LEN,
+        }
+    }
+}
+
+/// A parsed watch expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watch {
+    pub base:  Base,
+    pub chain: Vec<usize>, // Offsets added after each pointer read
+    pub kind:  Kind,
+}
+
+fn parse_hex(s: &str) -> Option<usize> {
+    let s = s.trim();
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+/// Parse an expression; the error says what's wrong with it.
+pub fn parse(expr: &str) -> Result<Watch, &'static str> {
+    let (path, kind) = match expr.rsplit_once(':') {
+        Some((p, k)) => (p, Kind::from_name(k.trim()).ok_or("unknown type (u8 i16 i32 u32 f32 vec3 ptr str)")?),
+        None => (expr, Kind::U32),
+    };
+    let mut steps = path.split("->");
+    let base = steps.next().unwrap_or("").trim();
+    if base.is_empty() { return Err("missing base address"); }
+    let base = match base.split_once('+') {
+        Some((module, off)) => {
+            let module = module.trim();
+            if module.is_empty() { return Err("missing module name"); }
+            Base::Module(module.to_string(), parse_hex(off).ok_or("bad module offset")?)
+        }
+        None => Base::Absolute(parse_hex(base).ok_or("bad address")?),
+    };
+    let chain = steps.map(parse_hex).collect::<Option<Vec<_>>>().ok_or("bad pointer offset")?;
+    Ok(Watch { base, chain, kind })
+}
+
+/// Show `bytes` (kind.size() of them) as a value of `kind`.
+pub fn format_value(kind: Kind, bytes: &[u8]) -> String {
+    let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    let f32_at = |i: usize| f32::from_bits(u32_at(i));
+    if bytes.len() < kind.size() && kind != Kind::Str { return "?".to_string(); }
+    match kind {
+        Kind::U8   => bytes[0].to_string(),
+        Kind::I16  => i16::from_le_bytes([bytes[0], bytes[1]]).to_string(),
+        Kind::I32  => (u32_at(0) as i32).to_string(),
+        Kind::U32  => format!("{} ({:#x})", u32_at(0), u32_at(0)),
+        Kind::F32  => format!("{:.3}", f32_at(0)),
+        Kind::Vec3 => format!("({:.1}, {:.1}, {:.1})", f32_at(0), f32_at(4), f32_at(8)),
+        Kind::Ptr  => format!("{:#010x}", u32_at(0)),
+        Kind::Str  => {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            let text: String = bytes[..end].iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            format!("\"{}\"", text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_expressions() {
+        assert_eq!(parse("client.dll+0x12F4A0 -> 0x1C -> 8 : f32"), Ok(Watch {
+            base: Base::Module("client.dll".to_string(), 0x12F4A0),
+            chain: vec![0x1C, 8],
+            kind: Kind::F32,
+        }));
+        assert_eq!(parse("0x02A1B3C0"), Ok(Watch { base: Base::Absolute(0x02A1B3C0), chain: vec![], kind: Kind::U32 }));
+        assert!(parse("hw.dll+zz").is_err());
+        assert!(parse("0x100 -> : i32").is_err());
+        assert!(parse("0x100 : double").is_err());
+        assert!(parse(" : i32").is_err());
+    }
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(format_value(Kind::I32, &(-5i32).to_le_bytes()), "-5");
+        assert_eq!(format_value(Kind::U32, &[0x10, 0, 0, 0]), "16 (0x10)");
+        assert_eq!(format_value(Kind::F32, &1.5f32.to_le_bytes()), "1.500");
+        assert_eq!(format_value(Kind::Str, b"de_dust2\0junk"), "\"de_dust2\"");
+        assert_eq!(format_value(Kind::Vec3, &[0; 4]), "?");
+    }
+}
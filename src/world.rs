@@ -0,0 +1,172 @@
+// world.rs — Interpretation of every client entity, not just players.
+//
+// The client keeps one cl_entity_t per edict in a contiguous array of
+// cl.max_edicts entries; GetEntityByIndex returns null past its end, which is
+// how the real count is found (it depends on the server's -num_edicts). Each
+// slot is turned into a small `RawEntity` — the fields item, grenade and
+// brush-entity ESP need — through the same `EngineReader` as player.rs.
+//
+// Slots that were never used, or were freed, have model index 0. An entity
+// that's no longer in the server's updates keeps its last state; `current`
+// tells those apart (its message number is behind the newest one seen).
+
+use crate::math::Vec3;
+use crate::offsets::Offset;
+use crate::player::{EngineReader, ES_MAXS, ES_ORIGIN};
+
+// ============================================================
+// Offsets & Limits
+// ============================================================
+
+pub const ES_MESSAGENUM: usize = 0x0C;  // entity_state_t::messagenum (packet it last arrived in)
+pub const ES_MODELINDEX: usize = 0x28;  // entity_state_t::modelindex
+pub const ES_SOLID:      usize = 0x3A;  // entity_state_t::solid (short, SOLID_*)
+pub const ES_EFFECTS:    usize = 0x3C;  // entity_state_t::effects (EF_* bits)
+pub const ES_MOVETYPE:   usize = 0x58;  // entity_state_t::movetype (MOVETYPE_*)
+pub const ES_MINS:       usize = 0x7C;  // entity_state_t::mins
+
+/// Upper bound for the edict count probe (the engine's -num_edicts maximum).
+pub const EDICT_LIMIT: i32 = 4096;
+
+// ============================================================
+// Entities
+// ============================================================
+
+/// One client entity's state.
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(dead_code)] // Fields are read by the entity ESP categories
+pub struct RawEntity {
+    pub index:       i32,
+    pub is_player:   bool,  // cl_entity_t::player
+    pub model_index: i32,   // Index for GetModelByIndex (1 = world)
+    pub origin:      Vec3,  // Interpolated origin (falls back to entity_state_t::origin)
+    pub mins:        Vec3,  // Bounding box, relative to origin (brush entities: model bounds)
+    pub maxs:        Vec3,
+    pub solid:       i16,
+    pub movetype:    i32,
+    pub effects:     i32,
+    pub current:     bool,  // Present in the newest update seen
+    pub message:     i32,   // entity_state_t::messagenum
+}
+
+/// Number of entity slots the engine has (cl.max_edicts): the first index
+/// GetEntityByIndex returns null for, found by binary search.
+pub fn edict_count(r: &impl EngineReader) -> i32 {
+    if r.entity(0) == 0 { return 0; }
+    let (mut lo, mut hi) = (0, EDICT_LIMIT); // entity(lo) exists; entity(hi) is past the end
+    if r.entity(hi - 1) != 0 { return EDICT_LIMIT; }
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if r.entity(mid) != 0 { lo = mid; } else { hi = mid; }
+    }
+    hi
+}
+
+/// Read entity `idx`. None for empty slots (no entity, or model index 0).
+pub fn read_entity(r: &impl EngineReader, idx: i32) -> Option<RawEntity> {
+    let base = r.entity(idx);
+    if base == 0 { return None; }
+    let cs = base + Offset::Curstate.get();
+    let model_index = r.read_i32(cs + ES_MODELINDEX);
+    if model_index <= 0 { return None; }
+
+    let mut origin = r.read_vec3(base + Offset::Origin.get());
+    if origin.is_zero() || !origin.x.is_finite() {
+        origin = r.read_vec3(cs + ES_ORIGIN);
+    }
+    Some(RawEntity {
+        index: idx,
+        is_player: r.read_i32(base + 0x04) != 0,
+        model_index,
+        origin,
+        mins: r.read_vec3(cs + ES_MINS),
+        maxs: r.read_vec3(cs + ES_MAXS),
+        solid: r.read_i16(cs + ES_SOLID),
+        movetype: r.read_i32(cs + ES_MOVETYPE),
+        effects: r.read_i32(cs + ES_EFFECTS),
+        current: false,
+        message: r.read_i32(cs + ES_MESSAGENUM),
+    })
+}
+
+/// Every non-empty entity from index 1 (the world itself is skipped), with
+/// `current` set on those from the newest update.
+pub fn scan(r: &impl EngineReader) -> Vec<RawEntity> {
+    let mut out: Vec<RawEntity> = (1..edict_count(r)).filter_map(|i| read_entity(r, i)).collect();
+    let newest = out.iter().map(|e| e.message).max().unwrap_or(0);
+    for e in &mut out { e.current = e.message == newest; }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockEngine;
+    use crate::player::{CURSTATE_OFFSET, ENT_ORIGIN};
+
+    const BASE: usize = 0x1000_0000;
+    const STRIDE: usize = 0x1000;
+
+    /// Entity slots 0..count, with model index 1 for the world.
+    fn engine(count: i32) -> MockEngine {
+        let mut e = MockEngine::default();
+        for i in 0..count {
+            e.entities.insert(i, BASE + i as usize * STRIDE);
+        }
+        e.write_i32(BASE + CURSTATE_OFFSET + ES_MODELINDEX, 1);
+        e
+    }
+
+    fn add(e: &MockEngine, idx: i32, model: i32, msg: i32, origin: [f32; 3]) {
+        let base = BASE + idx as usize * STRIDE;
+        e.write_i32(base + CURSTATE_OFFSET + ES_MODELINDEX, model);
+        e.write_i32(base + CURSTATE_OFFSET + ES_MESSAGENUM, msg);
+        e.write_vec3(base + ENT_ORIGIN, origin);
+    }
+
+    #[test]
+    fn counts_edicts() {
+        assert_eq!(edict_count(&engine(0)), 0);
+        assert_eq!(edict_count(&engine(1)), 1);
+        assert_eq!(edict_count(&engine(900)), 900);
+        assert_eq!(edict_count(&engine(2048)), 2048);
+        assert_eq!(edict_count(&engine(EDICT_LIMIT)), EDICT_LIMIT);
+    }
+
+    #[test]
+    fn reads_fields_and_skips_empty() {
+        let e = engine(64);
+        add(&e, 40, 12, 7, [1.0, 2.0, 3.0]);
+        let base = BASE + 40 * STRIDE + CURSTATE_OFFSET;
+        e.write(base + ES_SOLID, &4i16.to_le_bytes());
+        e.write_vec3(base + ES_MINS, [-16.0, -16.0, 0.0]);
+        e.write_vec3(base + ES_MAXS, [16.0, 16.0, 32.0]);
+
+        let ent = read_entity(&e, 40).unwrap();
+        assert_eq!((ent.index, ent.model_index, ent.solid, ent.message), (40, 12, 4, 7));
+        assert_eq!((ent.origin.z, ent.mins.x, ent.maxs.z), (3.0, -16.0, 32.0));
+        assert!(!ent.is_player);
+
+        assert!(read_entity(&e, 41).is_none()); // Model index 0
+        assert!(read_entity(&e, 100).is_none()); // Past the end
+    }
+
+    #[test]
+    fn origin_falls_back_to_state() {
+        let e = engine(8);
+        add(&e, 3, 5, 1, [0.0, 0.0, 0.0]);
+        e.write_vec3(BASE + 3 * STRIDE + CURSTATE_OFFSET + ES_ORIGIN, [4.0, 5.0, 6.0]);
+        assert_eq!(read_entity(&e, 3).unwrap().origin.y, 5.0);
+    }
+
+    #[test]
+    fn scan_marks_current() {
+        let e = engine(32);
+        add(&e, 2, 3, 10, [1.0, 1.0, 1.0]);
+        add(&e, 5, 4, 9, [1.0, 1.0, 1.0]);  // Left the update stream
+        add(&e, 9, 6, 10, [1.0, 1.0, 1.0]);
+        let ents = scan(&e);
+        let summary: Vec<(i32, bool)> = ents.iter().map(|x| (x.index, x.current)).collect();
+        assert_eq!(summary, vec![(2, true), (5, false), (9, true)]);
+    }
+}
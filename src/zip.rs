@@ -0,0 +1,148 @@
+// zip.rs — Writing .zip archives (support bundles).
+//
+// bundle.rs packs the log, config and the rest of a bug report into one file
+// that Explorer, 7-Zip and every mail client can open. The files are small
+// text, so they're stored without compression, which keeps the format down to
+// three records and needs no dependency:
+//
+//   local file header + data    per file, in order
+//   central directory header    per file, pointing back at its local header
+//   end of central directory    how many files, where the directory starts
+//
+// All fields are little-endian. Names are plain ASCII paths with '/'; the
+// modification time is the archive's, in the MS-DOS format zip uses (local
+// time isn't known here, so it's UTC).
+
+use crate::environment::crc32;
+
+const LOCAL_HEADER:   u32 = 0x0403_4B50;
+const CENTRAL_HEADER: u32 = 0x0201_4B50;
+const END_OF_CENTRAL: u32 = 0x0605_4B50;
+const VERSION:        u16 = 20;  // 2.0: the first with directories, enough for stored files
+const STORED:         u16 = 0;   // Compression method: none
+
+/// An archive being built in memory.
+pub struct Zip {
+    data:    Vec<u8>,   // Local headers and file data so far
+    central: Vec<u8>,   // Central directory entries so far
+    files:   u16,
+    time:    u16,       // MS-DOS time and date of every entry
+    date:    u16,
+}
+
+impl Zip {
+    /// An empty archive whose files are dated `unix_secs`.
+    pub fn new(unix_secs: u64) -> Self {
+        let (time, date) = dos_time(unix_secs);
+        Self { data: Vec::new(), central: Vec::new(), files: 0, time, date }
+    }
+
+    /// Add a file. Archives stay well under 4 GB and 65535 files here, so the
+    /// zip64 extensions aren't written; past those limits files are dropped.
+    pub fn add(&mut self, name: &str, bytes: &[u8]) {
+        let (Ok(size), Ok(offset)) = (u32::try_from(bytes.len()), u32::try_from(self.data.len())) else { return };
+        if self.files == u16::MAX { return; }
+        let crc = crc32(bytes);
+        let name = name.replace('\\', "/");
+
+        // Fields the local and the central header share, from "version needed" on
+        let mut common = Vec::with_capacity(26);
+        for v in [VERSION, 0, STORED, self.time, self.date] { common.extend_from_slice(&v.to_le_bytes()); }
+        for v in [crc, size, size] { common.extend_from_slice(&v.to_le_bytes()); }
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes()); // Extra field length
+
+        self.data.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        self.data.extend_from_slice(&common);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(bytes);
+
+        self.central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        self.central.extend_from_slice(&VERSION.to_le_bytes()); // Version made by
+        self.central.extend_from_slice(&common);
+        for v in [0u16, 0, 0] { self.central.extend_from_slice(&v.to_le_bytes()); } // Comment, disk, internal attributes
+        for v in [0u32, offset] { self.central.extend_from_slice(&v.to_le_bytes()); } // External attributes, local header
+        self.central.extend_from_slice(name.as_bytes());
+        self.files += 1;
+    }
+
+    /// The archive's bytes.
+    pub fn finish(self) -> Vec<u8> {
+        let Zip { mut data, central, files, .. } = self;
+        let start = data.len() as u32;
+        data.extend_from_slice(&central);
+        data.extend_from_slice(&END_OF_CENTRAL.to_le_bytes());
+        for v in [0u16, 0, files, files] { data.extend_from_slice(&v.to_le_bytes()); }
+        for v in [central.len() as u32, start] { data.extend_from_slice(&v.to_le_bytes()); }
+        data.extend_from_slice(&0u16.to_le_bytes()); // Comment length
+        data
+    }
+}
+
+/// MS-DOS (time, date) of a Unix time: two-second resolution, years from
+/// 1980 (earlier times are clamped to its start).
+fn dos_time(unix_secs: u64) -> (u16, u16) {
+    let (days, secs) = (unix_secs / 86_400, unix_secs % 86_400);
+    let (y, m, d) = civil_date(days as i64);
+    if y < 1980 { return (0, (1 << 5) | 1); }
+    let time = ((secs / 3600) << 11) | ((secs % 3600 / 60) << 5) | (secs % 60 / 2);
+    let date = (((y - 1980).min(127) as u64) << 9) | ((m as u64) << 5) | d as u64;
+    (time as u16, date as u16)
+}
+
+/// (year, month, day) of a day count since 1970-01-01 (proleptic Gregorian).
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(b: &[u8], at: usize) -> u16 { u16::from_le_bytes([b[at], b[at + 1]]) }
+    fn u32_at(b: &[u8], at: usize) -> u32 { u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]) }
+
+    #[test]
+    fn writes_stored_entries_and_a_directory() {
+        let mut z = Zip::new(1_700_000_000);
+        z.add("esp_debug.log", b"hello\n");
+        z.add("config\\esp_config.toml", b"");
+        let b = z.finish();
+
+        assert_eq!(u32_at(&b, 0), LOCAL_HEADER);
+        assert_eq!(u32_at(&b, 14), crc32(b"hello\n"));
+        assert_eq!((u32_at(&b, 18), u16_at(&b, 26)), (6, 13));
+        assert_eq!(&b[30..43], b"esp_debug.log");
+        assert_eq!(&b[43..49], b"hello\n");
+        assert_eq!(&b[49 + 30..49 + 52], b"config/esp_config.toml");
+
+        let end = b.len() - 22;
+        assert_eq!(u32_at(&b, end), END_OF_CENTRAL);
+        assert_eq!((u16_at(&b, end + 8), u16_at(&b, end + 10)), (2, 2));
+        let dir = u32_at(&b, end + 16) as usize;
+        assert_eq!(u32_at(&b, dir), CENTRAL_HEADER);
+        assert_eq!(u32_at(&b, dir + 42), 0);                  // First local header
+        let second = dir + 46 + 13;
+        assert_eq!(u32_at(&b, second + 42), 49);
+        assert_eq!(u32_at(&b, end + 12) as usize, end - dir);
+    }
+
+    #[test]
+    fn converts_unix_time_to_dos() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(19_782), (2024, 2, 29));
+        // 2023-11-14 22:13:20
+        let (time, date) = dos_time(1_700_000_000);
+        assert_eq!((time >> 11, (time >> 5) & 63, (time & 31) * 2), (22, 13, 20));
+        assert_eq!((1980 + (date >> 9), (date >> 5) & 15, date & 31), (2023, 11, 14));
+        assert_eq!(dos_time(0), (0, 0x21));
+    }
+}